@@ -0,0 +1,18 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Lexing any code fails with diagnostics instead of panicking
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vunk_driver::source::Source;
+
+fuzz_target!(|code: &str| {
+    let source = Source {
+        name: "fuzz.vunk".to_string(),
+        code: code.to_string(),
+    };
+    let _ = source.lex();
+});
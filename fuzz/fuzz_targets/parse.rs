@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Parsing any sequence of tokens fails with diagnostics instead of panicking
+//!
+//! The tokens need not be what the lexer produces, like an identifier that is a keyword, so the
+//! parser does not rely on the lexer for what it accepts.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vunk_driver::source::Source;
+use vunk_lexer::Token;
+
+fuzz_target!(|tokens: Vec<Token>| {
+    // A span of one char per token, so that spans are ordered and within the code
+    let source = Source {
+        name: "fuzz.vunk".to_string(),
+        code: " ".repeat(tokens.len()),
+    };
+    let tokens = tokens
+        .into_iter()
+        .enumerate()
+        .map(|(index, token)| (token, index..index + 1))
+        .collect();
+    let _ = vunk_driver::parse(&source, tokens);
+});
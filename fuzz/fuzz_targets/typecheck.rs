@@ -0,0 +1,17 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Desugaring and typechecking any AST fails with diagnostics instead of panicking
+//!
+//! The ASTs are well formed, as the types of the AST allow nothing else, but need not be what the
+//! parser produces, like names that are not identifiers or `?`s where they are not allowed.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vunk_parser::ast::program::Program;
+
+fuzz_target!(|program: Program| {
+    let _ = vunk_driver::desugar(program).and_then(|program| vunk_driver::typecheck(&program));
+});
@@ -0,0 +1,508 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::ArgGroup;
+use clap::Args;
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use is_terminal::IsTerminal;
+use vunk_diagnostics::codes::Code;
+use vunk_diagnostics::json;
+use vunk_diagnostics::limit;
+use vunk_diagnostics::terminal::Terminal;
+use vunk_diagnostics::Diagnostic;
+use vunk_driver::cache::Cache;
+use vunk_driver::cfg::Backend;
+use vunk_driver::cfg::Config;
+use vunk_driver::database::Database;
+use vunk_driver::error::DriverError;
+use vunk_driver::package::manifest::VersionReq;
+use vunk_runtime::profile::Profile;
+use vunk_runtime::sandbox::Sandbox;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    #[command(flatten)]
+    pub diagnostics: DiagnosticArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct DiagnosticArgs {
+    /// How errors are printed, json prints every diagnostic as a JSON object on a line of stdout
+    #[arg(long, global = true, value_enum, default_value_t = MessageFormat::Human)]
+    pub message_format: MessageFormat,
+
+    /// Show at most this many errors, and as many warnings, or all of them with 0
+    #[arg(long, global = true, default_value_t = 50)]
+    pub max_errors: usize,
+
+    /// Whether errors are colored, auto colors them on terminals unless NO_COLOR is set
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Lex, parse and typecheck a file, or the package in a directory
+    Check {
+        file: PathBuf,
+
+        /// Check again whenever a source file changes
+        #[arg(long)]
+        watch: bool,
+
+        #[command(flatten)]
+        cfg: CfgArgs,
+
+        #[command(flatten)]
+        cache: CacheArgs,
+
+        #[command(flatten)]
+        timing: TimingArgs,
+    },
+
+    /// Run the main function of a file
+    Run {
+        file: PathBuf,
+
+        #[command(flatten)]
+        sandbox: SandboxArgs,
+
+        /// Run again whenever a source file changes
+        #[arg(long)]
+        watch: bool,
+
+        #[command(flatten)]
+        cfg: CfgArgs,
+
+        #[command(flatten)]
+        cache: CacheArgs,
+
+        #[command(flatten)]
+        timing: TimingArgs,
+
+        /// Write every function call with its arguments and result to a file, as JSON lines
+        #[arg(long, value_name = "FILE")]
+        trace_eval: Option<PathBuf>,
+
+        #[command(flatten)]
+        profiling: ProfileArgs,
+
+        /// Arguments passed to the program
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+
+    /// Start an interactive session
+    Repl {
+        #[command(flatten)]
+        sandbox: SandboxArgs,
+    },
+
+    /// Compile a file to an executable
+    Build {
+        file: PathBuf,
+
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        cfg: CfgArgs,
+
+        #[command(flatten)]
+        cache: CacheArgs,
+
+        #[command(flatten)]
+        timing: TimingArgs,
+    },
+
+    /// Run the tests and doc comment examples of files and directories
+    Test {
+        #[arg(default_value = ".")]
+        paths: Vec<PathBuf>,
+
+        /// Only run tests whose name contains this
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Print which lines and definitions of the files the tests executed
+        #[arg(long)]
+        coverage: bool,
+
+        /// Write which lines and definitions the tests executed to a file, in the LCOV format
+        #[arg(long, value_name = "FILE")]
+        lcov: Option<PathBuf>,
+
+        #[command(flatten)]
+        sandbox: SandboxArgs,
+    },
+
+    /// Create a package in a new directory
+    New {
+        directory: PathBuf,
+
+        /// Create a library instead of a program
+        #[arg(long)]
+        lib: bool,
+    },
+
+    /// Create a package in the current directory
+    Init {
+        /// Create a library instead of a program
+        #[arg(long)]
+        lib: bool,
+    },
+
+    /// Add a dependency to the package in the current directory
+    #[command(group(ArgGroup::new("source").required(true).args(["path", "git"])))]
+    Add {
+        name: String,
+
+        /// Directory of the dependency
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// URL of the git repository of the dependency
+        #[arg(long)]
+        git: Option<String>,
+
+        /// Commit, tag or branch of the git repository to use
+        #[arg(long, requires = "git")]
+        rev: Option<String>,
+
+        /// Version requirement, like ^1.2
+        #[arg(long)]
+        version: Option<VersionReq>,
+    },
+
+    /// Resolve the dependencies of the package in the current directory again and update
+    /// vunk.lock
+    Update,
+
+    /// Generate HTML documentation and a JSON description of the files
+    Doc {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Directory to write the documentation to
+        #[arg(short, long, default_value = "doc")]
+        output: PathBuf,
+
+        /// Run the examples in the doc comments instead of writing documentation
+        #[arg(long)]
+        test: bool,
+    },
+
+    /// Print a graph of a file, or of the package containing it, in the DOT format
+    #[command(group(ArgGroup::new("graph").required(true).args(["calls", "modules"])))]
+    Graph {
+        file: PathBuf,
+
+        /// Print which functions of the file call which
+        #[arg(long)]
+        calls: bool,
+
+        /// Print which modules of the package and its dependencies use which, with cycles in red
+        #[arg(long)]
+        modules: bool,
+
+        /// Print it as JSON
+        #[arg(long, requires = "modules")]
+        json: bool,
+    },
+
+    /// Print the tokens, syntax tree, desugared syntax tree or types of a file, or the grammar
+    #[command(group(
+        ArgGroup::new("stage")
+            .required(true)
+            .args(["tokens", "ast", "core", "types", "grammar"])
+    ))]
+    Dump {
+        #[arg(required_unless_present = "grammar")]
+        file: Option<PathBuf>,
+
+        #[arg(long)]
+        tokens: bool,
+
+        #[arg(long)]
+        ast: bool,
+
+        /// Print the syntax tree after desugaring and constant folding
+        #[arg(long)]
+        core: bool,
+
+        #[arg(long)]
+        types: bool,
+
+        /// Print the grammar as EBNF, or in JSON for drawing railroad diagrams
+        #[arg(long, conflicts_with = "file")]
+        grammar: bool,
+
+        /// Print it as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print a TextMate grammar for highlighting vunk in editors
+    Syntax,
+
+    /// Format a file
+    Fmt {
+        file: PathBuf,
+
+        /// Only check whether the file is formatted
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Apply the fixes that are certain to be right to a file, or the package in a directory
+    Fix { file: PathBuf },
+
+    /// Explain an error code, like E0003
+    Explain { code: Code },
+}
+
+#[derive(Debug, Args)]
+pub struct SandboxArgs {
+    /// Allow reading files
+    #[arg(long)]
+    allow_read: bool,
+
+    /// Allow creating and writing files
+    #[arg(long)]
+    allow_write: bool,
+
+    /// Allow reading environment variables
+    #[arg(long)]
+    allow_env: bool,
+
+    /// Allow sending requests to other hosts
+    #[arg(long)]
+    allow_net: bool,
+
+    /// Allow running other programs
+    #[arg(long)]
+    allow_run: bool,
+
+    /// Do not allow reading from stdin and writing to stdout
+    #[arg(long)]
+    deny_console: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CacheArgs {
+    /// Compute everything again instead of using results of earlier invocations in target/cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Print how many results were found in and written to the cache
+    #[arg(long)]
+    cache_stats: bool,
+}
+
+impl CacheArgs {
+    pub fn database(&self, path: &Path) -> Database {
+        if self.no_cache {
+            Database::default()
+        } else {
+            Database::with_cache(Cache::for_path(path))
+        }
+    }
+
+    /// Print the cache statistics of the database, if requested
+    pub fn report(&self, database: &Database) {
+        if let Some(statistics) = database.cache_statistics().filter(|_| self.cache_stats) {
+            eprintln!("{}", statistics);
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct CfgArgs {
+    /// Features of the package to enable, separated by commas
+    #[arg(long, value_delimiter = ',')]
+    features: Vec<String>,
+
+    /// Do not enable the default features of the package
+    #[arg(long)]
+    no_default_features: bool,
+
+    /// Evaluate `@cfg(release)` as true and `@cfg(debug)` as false
+    #[arg(long)]
+    release: bool,
+}
+
+impl CfgArgs {
+    /// What the `@cfg` attributes are evaluated against when compiling for `backend`
+    pub fn config(&self, backend: Backend) -> Config {
+        Config {
+            backend,
+            release: self.release,
+            features: self.features.iter().cloned().collect(),
+            default_features: !self.no_default_features,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct TimingArgs {
+    /// Print how long every phase took, in total and per module, and the peak memory use
+    #[arg(long)]
+    time_passes: bool,
+
+    /// Write how long every phase took to a file, as Chrome trace events
+    #[arg(long, value_name = "FILE")]
+    trace: Option<PathBuf>,
+}
+
+impl TimingArgs {
+    pub fn enable(&self, database: &mut Database) {
+        if self.time_passes || self.trace.is_some() {
+            database.enable_timings();
+        }
+    }
+
+    /// Print or write the timings of the database, if requested
+    pub fn report(&self, database: &Database) {
+        let timings = match database.timings() {
+            Some(timings) => timings,
+            None => return,
+        };
+        if self.time_passes {
+            eprint!("{}", timings.report());
+        }
+        if let Some(trace) = self.trace.as_ref() {
+            if let Err(error) = std::fs::write(trace, timings.chrome_trace()) {
+                eprintln!("Could not write {}: {}", trace.display(), error);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ProfileArgs {
+    /// Print how often every function was called, and the time and allocations spent in it
+    #[arg(long)]
+    profile: bool,
+
+    /// Write the time spent in every stack of calls to a file, as collapsed stacks for flame
+    /// graphs
+    #[arg(long, value_name = "FILE")]
+    profile_stacks: Option<PathBuf>,
+}
+
+impl ProfileArgs {
+    /// A profile to run the program with, if requested
+    pub fn profile(&self) -> Option<Arc<Profile>> {
+        (self.profile || self.profile_stacks.is_some()).then(Arc::default)
+    }
+
+    /// Print or write the profile, if requested
+    pub fn report(&self, profile: Option<&Profile>) {
+        let profile = match profile {
+            Some(profile) => profile,
+            None => return,
+        };
+        if self.profile {
+            eprint!("{}", profile.report());
+        }
+        if let Some(stacks) = self.profile_stacks.as_ref() {
+            if let Err(error) = std::fs::write(stacks, profile.collapsed()) {
+                eprintln!("Could not write {}: {}", stacks.display(), error);
+            }
+        }
+    }
+}
+
+impl SandboxArgs {
+    pub fn sandbox(&self) -> Sandbox {
+        Sandbox {
+            console: !self.deny_console,
+            read_files: self.allow_read,
+            write_files: self.allow_write,
+            environment: self.allow_env,
+            network: self.allow_net,
+            run_processes: self.allow_run,
+        }
+    }
+}
+
+impl DiagnosticArgs {
+    /// Make miette render errors the way the terminal they are written to can show them
+    pub fn install(&self) {
+        let color = match self.color {
+            ColorChoice::Auto => None,
+            ColorChoice::Always => Some(true),
+            ColorChoice::Never => Some(false),
+        };
+        let terminal = Terminal::detect(color, std::io::stderr().is_terminal());
+
+        // Only fails if a hook was installed already
+        let _ = miette::set_hook(Box::new(move |_| {
+            Box::new(
+                miette::MietteHandlerOpts::new()
+                    .color(terminal.color)
+                    .unicode(terminal.unicode)
+                    .terminal_links(terminal.color && terminal.unicode)
+                    .build(),
+            )
+        }));
+    }
+
+    pub fn emit_warnings(&self, warnings: Vec<Diagnostic>) {
+        self.print(warnings)
+    }
+
+    pub fn emit(&self, error: miette::Report) {
+        let diagnostics = error
+            .downcast_ref::<DriverError>()
+            .map(DriverError::diagnostics);
+        match (self.message_format, diagnostics) {
+            (MessageFormat::Human, Some(diagnostics)) if diagnostics.len() > 1 => {
+                self.print(diagnostics)
+            }
+            // An error with a single problem is shown with everything miette knows of it
+            (MessageFormat::Human, _) => eprintln!("{:?}", error),
+            (MessageFormat::Json, diagnostics) => self
+                .print(diagnostics.unwrap_or_else(|| vec![Diagnostic::error(error.to_string())])),
+        }
+    }
+
+    // Print the diagnostics in the format, without duplicates and up to the maximum
+    fn print(&self, diagnostics: Vec<Diagnostic>) {
+        let mut diagnostics = limit::deduplicate(diagnostics);
+        if self.max_errors > 0 {
+            diagnostics = limit::truncate(diagnostics, self.max_errors);
+        }
+
+        for diagnostic in diagnostics {
+            match self.message_format {
+                MessageFormat::Human => eprintln!("{:?}", miette::Report::new(diagnostic)),
+                MessageFormat::Json => println!("{}", json::to_json(&diagnostic)),
+            }
+        }
+    }
+}
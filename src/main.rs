@@ -2,8 +2,256 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use std::path::Path;
+use std::sync::Arc;
+
+use clap::Parser;
+use vunk_driver::cfg::Backend;
+use vunk_driver::context::RunOptions;
+use vunk_driver::coverage::Coverage;
+use vunk_driver::dump::Stage;
+use vunk_driver::error::DriverError;
+use vunk_driver::package::manifest::Dependency;
+use vunk_driver::package::scaffold::Kind;
+
+mod cli;
+mod repl;
+mod watch;
+
+use crate::cli::Cli;
+use crate::cli::Command;
+use crate::cli::DiagnosticArgs;
+
 #[tokio::main]
-async fn main() -> Result<(), miette::Error> {
-    println!("Hello World");
+async fn main() {
+    let cli = Cli::parse();
+    cli.diagnostics.install();
+
+    if let Err(error) = run(cli.command, &cli.diagnostics) {
+        cli.diagnostics.emit(error);
+        std::process::exit(1);
+    }
+}
+
+fn run(command: Command, diagnostics: &DiagnosticArgs) -> Result<(), miette::Error> {
+    match command {
+        Command::Check {
+            file,
+            watch,
+            cfg,
+            cache,
+            timing,
+        } => {
+            let mut database = cache.database(&file);
+            database.set_config(cfg.config(Backend::Interpreter));
+            let mut check = || {
+                // Every check is timed on its own
+                timing.enable(&mut database);
+                let result = vunk_driver::lint_with(&mut database, &file).and_then(|warnings| {
+                    diagnostics.emit_warnings(warnings);
+                    vunk_driver::check_with(&mut database, &file)
+                });
+                cache.report(&database);
+                timing.report(&database);
+                result
+            };
+
+            if watch {
+                watch::watch(&file, diagnostics, check)?
+            } else {
+                check()?
+            }
+        }
+        Command::Run {
+            file,
+            sandbox,
+            watch,
+            cfg,
+            cache,
+            timing,
+            trace_eval,
+            profiling,
+            args,
+        } => {
+            let mut options = RunOptions {
+                sandbox: sandbox.sandbox(),
+                args,
+                trace_eval,
+                coverage: None,
+                profile: None,
+            };
+            let mut database = cache.database(&file);
+            database.set_config(cfg.config(Backend::Interpreter));
+
+            if watch {
+                return watch::watch(&file, diagnostics, || {
+                    timing.enable(&mut database);
+                    let options = RunOptions {
+                        profile: profiling.profile(),
+                        ..options.clone()
+                    };
+                    let profile = options.profile.clone();
+                    let code = vunk_driver::run_with(&mut database, &file, options);
+                    cache.report(&database);
+                    timing.report(&database);
+                    profiling.report(profile.as_deref());
+                    let code = code?;
+                    if code != 0 {
+                        eprintln!("Exited with code {}", code);
+                    }
+                    Ok(())
+                });
+            }
+
+            timing.enable(&mut database);
+            options.profile = profiling.profile();
+            let profile = options.profile.clone();
+            let code = vunk_driver::run_with(&mut database, &file, options);
+            cache.report(&database);
+            timing.report(&database);
+            profiling.report(profile.as_deref());
+            let code = code?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Command::Repl { sandbox } => repl::repl(RunOptions {
+            sandbox: sandbox.sandbox(),
+            args: Vec::new(),
+            trace_eval: None,
+            coverage: None,
+            profile: None,
+        })?,
+        Command::Build {
+            file,
+            output,
+            cfg,
+            cache,
+            timing,
+        } => {
+            let output = output.unwrap_or_else(|| file.with_extension(""));
+            let mut database = cache.database(&file);
+            database.set_config(cfg.config(Backend::Native));
+            timing.enable(&mut database);
+            let result = vunk_driver::build_with(&mut database, &file, &output);
+            cache.report(&database);
+            timing.report(&database);
+            result?
+        }
+        Command::Test {
+            paths,
+            filter,
+            coverage,
+            lcov,
+            sandbox,
+        } => {
+            let executed = (coverage || lcov.is_some()).then(Arc::<Coverage>::default);
+            let options = RunOptions {
+                sandbox: sandbox.sandbox(),
+                args: Vec::new(),
+                trace_eval: None,
+                coverage: executed.clone(),
+                profile: None,
+            };
+
+            let summary = vunk_driver::test(&paths, filter.as_deref(), options)?;
+            print!("{}", summary);
+
+            if let Some(executed) = executed {
+                let report = vunk_driver::coverage(&paths, &executed)?;
+                if coverage {
+                    print!("\n{}", report);
+                }
+                if let Some(lcov) = lcov {
+                    std::fs::write(&lcov, report.lcov())
+                        .map_err(|source| DriverError::Write { path: lcov, source })?;
+                }
+            }
+            summary.into_result()?
+        }
+        Command::New { directory, lib } => vunk_driver::new(&directory, kind(lib))?,
+        Command::Init { lib } => vunk_driver::init(Path::new("."), kind(lib))?,
+        Command::Add {
+            name,
+            path,
+            git,
+            rev,
+            version,
+        } => {
+            let dependency = match (path, git) {
+                (Some(path), _) => Dependency::Path { path, version },
+                (None, Some(git)) => Dependency::Git { git, rev, version },
+                (None, None) => unreachable!("clap requires --path or --git"),
+            };
+            vunk_driver::add(Path::new("."), &name, dependency)?
+        }
+        Command::Update => vunk_driver::update(Path::new("."))?,
+        Command::Doc {
+            files, test: true, ..
+        } => vunk_driver::doctest(&files)?,
+        Command::Doc { files, output, .. } => vunk_driver::doc(&files, &output)?,
+        Command::Graph {
+            file,
+            calls: _,
+            modules,
+            json,
+        } => {
+            if modules {
+                let graph = vunk_driver::module_graph(&file)?;
+                if json {
+                    println!("{}", graph.json());
+                } else {
+                    print!("{}", graph.dot());
+                }
+            } else {
+                print!("{}", vunk_driver::call_graph(&file)?)
+            }
+        }
+        Command::Dump {
+            file,
+            tokens,
+            ast,
+            core,
+            types: _,
+            grammar,
+            json,
+        } => {
+            let file = match file {
+                Some(file) if !grammar => file,
+                _ => {
+                    print!("{}", vunk_driver::dump::grammar(json));
+                    return Ok(());
+                }
+            };
+            let stage = if tokens {
+                Stage::Tokens
+            } else if ast {
+                Stage::Ast
+            } else if core {
+                Stage::Core
+            } else {
+                Stage::Types
+            };
+            println!("{}", vunk_driver::dump(&file, stage, json)?.trim_end())
+        }
+        Command::Syntax => println!("{}", vunk_driver::textmate::textmate()),
+        Command::Fmt { file, check } => vunk_driver::fmt(&file, check)?,
+        Command::Fix { file } => {
+            let applied = vunk_driver::fix(&file)?;
+            println!("Applied {} fixes", applied);
+        }
+        Command::Explain { code } => {
+            print!("{}: {}\n\n{}", code, code.summary(), code.explanation())
+        }
+    }
+
     Ok(())
 }
+
+fn kind(lib: bool) -> Kind {
+    if lib {
+        Kind::Library
+    } else {
+        Kind::Binary
+    }
+}
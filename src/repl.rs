@@ -0,0 +1,126 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+
+use miette::IntoDiagnostic;
+use rustyline::completion::Completer;
+use rustyline::completion::Pair;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::Editor;
+use rustyline::Helper;
+use vunk_driver::complete::complete;
+use vunk_driver::context::RunOptions;
+use vunk_driver::repl::is_complete;
+use vunk_driver::repl::Session;
+use vunk_runtime::builtin::Builtins;
+
+const PROMPT: &str = "vunk> ";
+const CONTINUATION_PROMPT: &str = "  ... ";
+
+pub fn repl(options: RunOptions) -> Result<(), miette::Error> {
+    let mut editor = Editor::<ReplHelper>::new().into_diagnostic()?;
+    editor.set_helper(Some(ReplHelper {
+        builtins: Builtins::std(),
+        definitions: String::new(),
+    }));
+    let history = history_path();
+    if let Some(history) = history.as_ref() {
+        // There is no history on the first start
+        let _ = editor.load_history(history);
+    }
+
+    let mut session = Session::new(options);
+    let mut input = String::new();
+
+    loop {
+        let prompt = if input.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+
+        match editor.readline(prompt) {
+            Ok(line) => {
+                input.push_str(&line);
+                input.push('\n');
+                if !is_complete(&input) {
+                    continue;
+                }
+
+                if !input.trim().is_empty() {
+                    editor.add_history_entry(input.trim_end());
+                    match session.eval(&input) {
+                        Ok(reply) => println!("{}", reply),
+                        Err(error) => eprintln!("{:?}", miette::Report::new(error)),
+                    }
+
+                    if let Some(helper) = editor.helper_mut() {
+                        helper.definitions = session.definitions().collect::<Vec<_>>().join("\n");
+                    }
+                }
+                input.clear();
+            }
+
+            // Ctrl-C discards the current input
+            Err(ReadlineError::Interrupted) => input.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(error) => return Err(error).into_diagnostic(),
+        }
+    }
+
+    if let Some(history) = history.as_ref() {
+        editor.save_history(history).into_diagnostic()?;
+    }
+
+    Ok(())
+}
+
+// Completes names using the definitions of the session
+struct ReplHelper {
+    builtins: Builtins,
+    definitions: String,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        // The line is completed as if it was a new item after all definitions
+        let code = format!("{}\n\n{}", self.definitions, line);
+        let prefix = code.len() - line.len();
+        let (start, completions) = complete(&code, prefix + pos, &self.builtins);
+
+        let candidates = completions
+            .into_iter()
+            .map(|completion| Pair {
+                display: completion.label.clone(),
+                replacement: completion.label,
+            })
+            .collect();
+        Ok((start - prefix, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".vunk_history"))
+}
@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::io::Write;
+use std::path::Path;
+
+use is_terminal::IsTerminal;
+use miette::IntoDiagnostic;
+use vunk_driver::error::DriverError;
+use vunk_driver::watch::Watcher;
+
+use crate::cli::DiagnosticArgs;
+use crate::cli::MessageFormat;
+
+/// Moves the cursor to the top left corner and clears the screen
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// Run `command` on `path`, and again whenever a file it depends on changes, until interrupted
+pub fn watch(
+    path: &Path,
+    diagnostics: &DiagnosticArgs,
+    mut command: impl FnMut() -> Result<(), DriverError>,
+) -> Result<(), miette::Error> {
+    loop {
+        // Watching starts before running, so changes made while running are not missed. It is
+        // started again every time, as the dependencies of a package may have changed
+        let watcher = Watcher::new(path)?;
+
+        // Messages in JSON are read by tools, which are not helped by clearing the screen or by
+        // anything else on stdout. Neither are log files
+        let status: fn(String) = match diagnostics.message_format {
+            MessageFormat::Human => {
+                if std::io::stdout().is_terminal() {
+                    print!("{}", CLEAR);
+                    std::io::stdout().flush().into_diagnostic()?;
+                }
+                |message: String| println!("{}", message)
+            }
+            MessageFormat::Json => |message: String| eprintln!("{}", message),
+        };
+        match command() {
+            Ok(()) => status("No errors".to_string()),
+            Err(error) => diagnostics.emit(miette::Report::new(error)),
+        }
+        status(format!("Watching {} for changes", path.display()));
+
+        watcher.wait()?;
+    }
+}
@@ -0,0 +1,63 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Building a string of many pieces, by joining them one by one with `++`, by pushing them to a
+//! `Std.String.Builder`, and with `Std.String.concat`, which chains of `++` are desugared into
+
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::operator::binary;
+use vunk_runtime::operator::Instances;
+use vunk_runtime::value::Value;
+
+const PIECE: &str = "piece of a string ";
+
+fn concat(c: &mut Criterion) {
+    let builtins = Builtins::std();
+    let instances = Instances::default();
+    let function = |name| builtins.value(name).unwrap();
+    let (new, push, build) = (
+        function("Std.String.Builder.new"),
+        function("Std.String.Builder.push"),
+        function("Std.String.Builder.build"),
+    );
+    let concat = function("Std.String.concat");
+    let call = |function, args| apply(&mut BuiltinContext, function, args).unwrap();
+
+    let mut group = c.benchmark_group("concat");
+    for pieces in [100, 1_000, 10_000] {
+        let piece = Value::string(PIECE);
+
+        group.bench_function(BenchmarkId::new("operator", pieces), |b| {
+            b.iter(|| {
+                (0..pieces).fold(Value::string(""), |joined, _| {
+                    binary(&mut BuiltinContext, &instances, "++", &joined, &piece).unwrap()
+                })
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("builder", pieces), |b| {
+            b.iter(|| {
+                let builder = (0..pieces).fold(call(&new, Vec::new()), |builder, _| {
+                    call(&push, vec![piece.clone(), builder])
+                });
+                call(&build, vec![builder])
+            })
+        });
+
+        let list = Value::list(vec![piece.clone(); pieces]);
+        group.bench_function(BenchmarkId::new("chain", pieces), |b| {
+            b.iter(|| call(&concat, vec![list.clone()]))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, concat);
+criterion_main!(benches);
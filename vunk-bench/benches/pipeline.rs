@@ -0,0 +1,67 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BatchSize;
+use criterion::Criterion;
+use criterion::Throughput;
+use vunk_bench::programs;
+use vunk_bench::Program;
+use vunk_driver::context::RunOptions;
+use vunk_driver::database::Database;
+use vunk_driver::source::Source;
+
+const NAME: &str = "bench.vunk";
+
+fn lex(c: &mut Criterion) {
+    stage(c, "lex", |database| database.lex(NAME));
+}
+
+fn parse(c: &mut Criterion) {
+    stage(c, "parse", |database| database.parse(NAME));
+}
+
+fn typecheck(c: &mut Criterion) {
+    stage(c, "typecheck", |database| database.typecheck(NAME));
+}
+
+fn evaluate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("evaluate");
+    let directory = std::env::temp_dir().join("vunk-bench");
+    std::fs::create_dir_all(&directory).unwrap();
+    for program in programs() {
+        let path = directory.join(format!("{}.vunk", program.name));
+        std::fs::write(&path, &program.code).unwrap();
+        group.throughput(Throughput::Bytes(program.code.len() as u64));
+        group.bench_function(&program.name, |b| {
+            b.iter(|| vunk_driver::run(&path, RunOptions::default()))
+        });
+    }
+    group.finish();
+}
+
+// Benchmark a stage on a fresh database for every iteration, as its results are memoized
+fn stage<T>(c: &mut Criterion, name: &str, run: impl Fn(&mut Database) -> T) {
+    let mut group = c.benchmark_group(name);
+    for program in programs() {
+        group.throughput(Throughput::Bytes(program.code.len() as u64));
+        group.bench_function(&program.name, |b| {
+            b.iter_batched_ref(|| database(&program), &run, BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+fn database(program: &Program) -> Database {
+    let mut database = Database::default();
+    database.set_source(Source {
+        name: NAME.to_string(),
+        code: program.code.clone(),
+    });
+    database
+}
+
+criterion_group!(benches, lex, parse, typecheck, evaluate);
+criterion_main!(benches);
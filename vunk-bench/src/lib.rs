@@ -0,0 +1,64 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The programs the pipeline is benchmarked with
+//!
+//! The benchmarks run every stage on every program, see `benches/pipeline.rs`, and are run with
+//! `cargo bench -p vunk-bench`. Stages that do not exist yet fail early, so their numbers are a
+//! baseline of the stages before them until they do.
+
+/// A program to benchmark with, and its name in the reports of criterion
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Program {
+    pub name: String,
+    pub code: String,
+}
+
+/// The representative programs, followed by generated ones of growing size
+pub fn programs() -> Vec<Program> {
+    let mut programs = vec![
+        program("small", include_str!("../../vunk-examples/0001.vunk")),
+        program("medium", include_str!("../../vunk-examples/0041.vunk")),
+        program("nested", &nested(500)),
+        program("operators", &operators(5_000)),
+    ];
+    for definitions in [100, 1_000, 10_000] {
+        programs.push(program(
+            &format!("generated-{}", definitions),
+            &generate(definitions),
+        ));
+    }
+    programs
+}
+
+/// A file of `definitions` functions, each calling the one before it
+pub fn generate(definitions: usize) -> String {
+    let mut code = String::from("f0 = (x: u64) -> x\n");
+    for i in 1..definitions {
+        code.push_str(&format!(
+            "\n# Adds {i} to its argument\nf{i} : (u64) -> u64\nf{i} = (x) -> f{j} (x + {i})\n",
+            i = i,
+            j = i - 1
+        ));
+    }
+    code
+}
+
+/// A definition nested `depth` parentheses deep
+pub fn nested(depth: usize) -> String {
+    format!("a = {}1{}\n", "(".repeat(depth), ")".repeat(depth))
+}
+
+/// A definition of `count` additions on a single line
+pub fn operators(count: usize) -> String {
+    let terms = (0..=count).map(|i| i.to_string()).collect::<Vec<_>>();
+    format!("a = {}\n", terms.join(" + "))
+}
+
+fn program(name: &str, code: &str) -> Program {
+    Program {
+        name: name.to_string(),
+        code: code.to_string(),
+    }
+}
@@ -0,0 +1,37 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_bench::generate;
+use vunk_bench::programs;
+use vunk_driver::source::Source;
+
+#[test]
+fn programs_lex() {
+    for program in programs() {
+        let source = Source {
+            name: program.name.clone(),
+            code: program.code,
+        };
+        assert!(source.lex().is_ok(), "{} does not lex", program.name);
+    }
+}
+
+#[test]
+fn generated_programs_grow_with_their_definitions() {
+    let code = generate(3);
+    assert_eq!(
+        code,
+        "\
+f0 = (x: u64) -> x
+
+# Adds 1 to its argument
+f1 : (u64) -> u64
+f1 = (x) -> f0 (x + 1)
+
+# Adds 2 to its argument
+f2 : (u64) -> u64
+f2 = (x) -> f1 (x + 2)
+"
+    );
+}
@@ -0,0 +1,211 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Golden file tests of the whole pipeline
+//!
+//! Every `.vunk` file in a corpus directory is a case. What every stage makes of it is compared to
+//! a snapshot next to it, named after the case and the stage, like `hello.tokens` for the tokens
+//! of `hello.vunk`. A stage that fails is expected to fail with the diagnostics in the snapshot,
+//! so cases record what does not work yet as well.
+//!
+//! Blessing writes what the stages produce to the snapshots instead of comparing, to add cases or
+//! accept intended changes. The snapshots are reviewed like any other change.
+
+use std::fmt::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use vunk_driver::context::RunOptions;
+use vunk_driver::dump::Stage;
+use vunk_driver::error::DriverError;
+
+/// What a stage made of a case
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Snapshot {
+    Tokens,
+    Ast,
+
+    /// The warnings of lints and the errors of checking
+    Diagnostics,
+
+    /// The exit code of running the case, as its output is written to stdout
+    Eval,
+}
+
+impl Snapshot {
+    pub const ALL: [Snapshot; 4] = [
+        Snapshot::Tokens,
+        Snapshot::Ast,
+        Snapshot::Diagnostics,
+        Snapshot::Eval,
+    ];
+
+    /// The extension of the snapshot files
+    pub fn extension(self) -> &'static str {
+        match self {
+            Snapshot::Tokens => "tokens",
+            Snapshot::Ast => "ast",
+            Snapshot::Diagnostics => "diagnostics",
+            Snapshot::Eval => "eval",
+        }
+    }
+
+    /// What the stage makes of a case now
+    ///
+    /// The path of the case is replaced by its file name, so that snapshots do not depend on where
+    /// the corpus is.
+    pub fn take(self, case: &Path) -> String {
+        let output = match self {
+            Snapshot::Tokens => vunk_driver::dump(case, Stage::Tokens, false),
+            Snapshot::Ast => vunk_driver::dump(case, Stage::Ast, false),
+            Snapshot::Diagnostics => diagnostics(case),
+            Snapshot::Eval => vunk_driver::run(case, RunOptions::default())
+                .map(|code| format!("Exited with code {}\n", code)),
+        };
+        let output = output.unwrap_or_else(|error| render(&error));
+
+        let name = case.file_name().unwrap_or_default().to_string_lossy();
+        output.replace(&case.display().to_string(), &name)
+    }
+}
+
+/// A snapshot that does not match
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub case: PathBuf,
+    pub snapshot: Snapshot,
+
+    /// The content of the snapshot file, if there is one
+    pub expected: Option<String>,
+    pub actual: String,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.expected {
+            Some(expected) => write!(
+                f,
+                "{} of {} changed:\n{}",
+                self.snapshot.extension(),
+                self.case.display(),
+                diff(expected, &self.actual)
+            ),
+            None => write!(
+                f,
+                "{} of {} has no snapshot, it is:\n{}",
+                self.snapshot.extension(),
+                self.case.display(),
+                self.actual
+            ),
+        }
+    }
+}
+
+/// The cases of a corpus, in the order of their names
+pub fn cases(directory: &Path) -> Result<Vec<PathBuf>, DriverError> {
+    let entries = std::fs::read_dir(directory).map_err(|source| DriverError::Read {
+        path: directory.to_path_buf(),
+        source,
+    })?;
+    let mut cases = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .map(|extension| extension == "vunk")
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    cases.sort();
+    Ok(cases)
+}
+
+/// Compare all snapshots of all cases of a corpus, returning those that do not match
+///
+/// If `bless` is set, snapshots that do not match are written instead, and nothing is returned.
+pub fn run(directory: &Path, bless: bool) -> Result<Vec<Mismatch>, DriverError> {
+    let mut mismatches = Vec::new();
+    for case in cases(directory)? {
+        for snapshot in Snapshot::ALL {
+            let path = case.with_extension(snapshot.extension());
+            let expected = std::fs::read_to_string(&path).ok();
+            let actual = snapshot.take(&case);
+            if expected.as_deref() == Some(actual.as_str()) {
+                continue;
+            }
+
+            if bless {
+                std::fs::write(&path, actual)
+                    .map_err(|source| DriverError::Write { path, source })?;
+            } else {
+                mismatches.push(Mismatch {
+                    case: case.clone(),
+                    snapshot,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    Ok(mismatches)
+}
+
+/// The lines that differ, prefixed with `-` if they were expected and with `+` if they are new
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+
+    // The length of the longest common subsequence of the lines from i and j on
+    let mut common = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            let _ = writeln!(out, " {}", expected[i]);
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || common[i + 1][j] >= common[i][j + 1])
+        {
+            let _ = writeln!(out, "-{}", expected[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(out, "+{}", actual[j]);
+            j += 1;
+        }
+    }
+    out
+}
+
+// The warnings of the lints, followed by the errors of checking
+fn diagnostics(case: &Path) -> Result<String, DriverError> {
+    let mut database = vunk_driver::database::Database::default();
+    let warnings = vunk_driver::lint_with(&mut database, case)?;
+    let mut rendered = warnings
+        .iter()
+        .map(|warning| warning.render(false))
+        .collect::<Vec<_>>();
+    if let Err(error) = vunk_driver::check_with(&mut database, case) {
+        rendered.push(render(&error));
+    }
+    Ok(rendered.join("\n"))
+}
+
+fn render(error: &DriverError) -> String {
+    error
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.render(false))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
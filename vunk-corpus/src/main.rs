@@ -0,0 +1,37 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `vunk-corpus [--bless] [DIRECTORY]` compares the snapshots of the corpus in the directory, by
+//! default the one of this crate, or writes them with `--bless`
+
+use std::path::PathBuf;
+
+fn main() {
+    let mut bless = false;
+    let mut directory = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/cases"));
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--bless" => bless = true,
+            _ => directory = PathBuf::from(arg),
+        }
+    }
+
+    match vunk_corpus::run(&directory, bless) {
+        Ok(mismatches) if mismatches.is_empty() => {}
+        Ok(mismatches) => {
+            for mismatch in mismatches.iter() {
+                eprintln!("{}", mismatch);
+            }
+            eprintln!(
+                "{} snapshots do not match, run with --bless to update them",
+                mismatches.len()
+            );
+            std::process::exit(1);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use vunk_corpus::diff;
+
+/// Run with `VUNK_BLESS=1` to update the snapshots
+#[test]
+fn corpus() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("cases");
+    let bless = std::env::var_os("VUNK_BLESS").is_some();
+    let mismatches = vunk_corpus::run(&directory, bless).unwrap();
+    assert!(
+        mismatches.is_empty(),
+        "{}",
+        mismatches
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn diffs_show_the_changed_lines() {
+    let expected = "a\nb\nc\nd\n";
+    let actual = "a\nc\nd\ne\n";
+    assert_eq!(diff(expected, actual), " a\n-b\n c\n d\n+e\n");
+    assert_eq!(diff("", "a\n"), "+a\n");
+}
@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Where breakpoints stop
+//!
+//! Execution can only stop where code starts, so a breakpoint on a line without code, like an
+//! empty line or a comment, moves to the next line with code, as in most debuggers.
+
+use std::collections::BTreeSet;
+
+use vunk_diagnostics::line_column;
+use vunk_driver::source::tokens;
+use vunk_lexer::Token;
+
+/// The lines that breakpoints on `lines` stop at, or `None` for those after the last code
+pub fn verify(code: &str, lines: &[usize]) -> Vec<Option<usize>> {
+    let code_lines = tokens(code)
+        .iter()
+        .filter(|(token, _)| !matches!(token, Token::Comment(_)))
+        .map(|(_, span)| line_column(code, span.start).0)
+        .collect::<BTreeSet<_>>();
+
+    lines
+        .iter()
+        .map(|line| code_lines.range(line..).next().copied())
+        .collect()
+}
@@ -0,0 +1,10 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_dap::server::Server;
+
+fn main() -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    Server::new(std::io::stdout()).run(&mut stdin.lock())
+}
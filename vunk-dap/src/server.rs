@@ -0,0 +1,206 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The requests of the Debug Adapter Protocol
+//!
+//! A launched program is run with `vunk run` once the client is done configuring, and its output
+//! is sent as `output` events. Breakpoints are verified against the code, but the interpreter
+//! cannot pause programs yet, so programs run to their end and requests that need a paused
+//! program, like `stackTrace`, `next` or `evaluate`, fail.
+
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::breakpoints;
+use crate::protocol;
+
+/// The id of the only thread programs run in
+pub const THREAD: u64 = 1;
+
+/// What the adapter supports, as the body of the response to `initialize`
+pub fn capabilities() -> Value {
+    json!({
+        "supportsConfigurationDoneRequest": true,
+        "supportsTerminateRequest": true,
+    })
+}
+
+pub struct Server<W> {
+    output: W,
+
+    /// The sequence number of the last message sent
+    seq: u64,
+
+    /// The program to run once the client is done configuring
+    launch: Option<Launch>,
+}
+
+struct Launch {
+    program: PathBuf,
+
+    /// The vunk executable to run the program with
+    vunk: PathBuf,
+    args: Vec<String>,
+}
+
+impl<W: Write> Server<W> {
+    pub fn new(output: W) -> Self {
+        Server {
+            output,
+            seq: 0,
+            launch: None,
+        }
+    }
+
+    /// Handle requests until the client disconnects or the input ends
+    pub fn run(mut self, input: &mut impl BufRead) -> io::Result<()> {
+        while let Some(message) = protocol::read(input)? {
+            if message["type"] == "request" && !self.request(&message)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Handle a request, returning whether to handle further ones
+    fn request(&mut self, request: &Value) -> io::Result<bool> {
+        let arguments = &request["arguments"];
+        let command = request["command"].as_str().unwrap_or_default();
+        let result = match command {
+            "initialize" => Ok(capabilities()),
+            "launch" => self.launch(arguments),
+            "setBreakpoints" => set_breakpoints(arguments),
+            "configurationDone" | "disconnect" | "terminate" => Ok(json!({})),
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD, "name": "main" }] })),
+            "stackTrace" | "scopes" | "variables" | "evaluate" | "continue" | "next" | "stepIn"
+            | "stepOut" | "pause" => Err("The interpreter cannot pause programs yet".to_string()),
+            command => Err(format!("Unknown command {}", command)),
+        };
+        self.respond(request, result)?;
+
+        match command {
+            "initialize" => self.event("initialized", json!({}))?,
+            "configurationDone" => self.start()?,
+            "disconnect" => return Ok(false),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn launch(&mut self, arguments: &Value) -> Result<Value, String> {
+        let program = arguments["program"]
+            .as_str()
+            .ok_or_else(|| "Launching needs the path of a program".to_string())?;
+        let args: Vec<String> = arguments["args"]
+            .as_array()
+            .map(|args| {
+                args.iter()
+                    .filter_map(|arg| arg.as_str())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.launch = Some(Launch {
+            program: PathBuf::from(program),
+            vunk: PathBuf::from(arguments["vunk"].as_str().unwrap_or("vunk")),
+            args,
+        });
+        Ok(json!({}))
+    }
+
+    // Run the launched program to its end
+    fn start(&mut self) -> io::Result<()> {
+        if let Some(launch) = self.launch.take() {
+            let output = Command::new(&launch.vunk)
+                .arg("run")
+                .arg(&launch.program)
+                .arg("--")
+                .args(&launch.args)
+                .output();
+            match output {
+                Ok(output) => {
+                    self.output("stdout", &String::from_utf8_lossy(&output.stdout))?;
+                    self.output("stderr", &String::from_utf8_lossy(&output.stderr))?;
+                    let code = output.status.code().unwrap_or(1);
+                    self.event("exited", json!({ "exitCode": code }))?;
+                }
+                Err(error) => {
+                    let message = format!("Could not run {}: {}\n", launch.vunk.display(), error);
+                    self.output("stderr", &message)?;
+                }
+            }
+        }
+        self.event("terminated", json!({}))
+    }
+
+    fn respond(&mut self, request: &Value, result: Result<Value, String>) -> io::Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = Value::from(message),
+        }
+        self.send(response)
+    }
+
+    fn event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    fn output(&mut self, category: &str, output: &str) -> io::Result<()> {
+        if output.is_empty() {
+            return Ok(());
+        }
+        self.event("output", json!({ "category": category, "output": output }))
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = Value::from(self.seq);
+        protocol::write(&mut self.output, &message)
+    }
+}
+
+// Verify the breakpoints of a file, which replace those it had before
+fn set_breakpoints(arguments: &Value) -> Result<Value, String> {
+    let path = arguments["source"]["path"]
+        .as_str()
+        .ok_or_else(|| "Breakpoints can only be set in files".to_string())?;
+    let code = std::fs::read_to_string(path)
+        .map_err(|error| format!("Could not read {}: {}", path, error))?;
+
+    let requested = arguments["breakpoints"]
+        .as_array()
+        .map(|breakpoints| {
+            let lines = breakpoints
+                .iter()
+                .filter_map(|breakpoint| breakpoint["line"].as_u64());
+            lines.map(|line| line as usize).collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let breakpoints = requested
+        .iter()
+        .zip(breakpoints::verify(&code, &requested))
+        .map(|(requested, line)| match line {
+            Some(line) => json!({ "verified": true, "line": line }),
+            None => json!({
+                "verified": false,
+                "line": requested,
+                "message": "There is no code at or after this line",
+            }),
+        })
+        .collect::<Vec<_>>();
+    Ok(json!({ "breakpoints": breakpoints }))
+}
@@ -0,0 +1,86 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use serde_json::json;
+use serde_json::Value;
+use vunk_dap::breakpoints::verify;
+use vunk_dap::protocol;
+use vunk_dap::server::Server;
+
+// The messages the server sends in response to `requests`
+fn session(requests: &[Value]) -> Vec<Value> {
+    let mut input = Vec::new();
+    for (seq, request) in requests.iter().enumerate() {
+        let mut request = request.clone();
+        request["seq"] = Value::from(seq + 1);
+        request["type"] = Value::from("request");
+        protocol::write(&mut input, &request).unwrap();
+    }
+
+    let mut output = Vec::new();
+    Server::new(&mut output).run(&mut input.as_slice()).unwrap();
+
+    let mut output = output.as_slice();
+    std::iter::from_fn(|| protocol::read(&mut output).unwrap()).collect()
+}
+
+#[test]
+fn initializing_is_followed_by_the_initialized_event() {
+    let messages = session(&[json!({ "command": "initialize", "arguments": {} })]);
+
+    assert_eq!(messages[0]["type"], "response");
+    assert_eq!(messages[0]["request_seq"], 1);
+    assert_eq!(messages[0]["success"], true);
+    assert_eq!(
+        messages[0]["body"]["supportsConfigurationDoneRequest"],
+        true
+    );
+    assert_eq!(messages[1]["event"], "initialized");
+    assert_eq!(messages[1]["seq"], 2);
+}
+
+#[test]
+fn breakpoints_move_to_the_next_line_with_code() {
+    let path = std::env::temp_dir().join(format!("vunk-dap-{}.vunk", std::process::id()));
+    std::fs::write(&path, "# One\na = 1\n\nb = 2\n").unwrap();
+
+    let messages = session(&[json!({
+        "command": "setBreakpoints",
+        "arguments": {
+            "source": { "path": path },
+            "breakpoints": [{ "line": 1 }, { "line": 4 }, { "line": 5 }],
+        },
+    })]);
+    std::fs::remove_file(&path).unwrap();
+
+    let breakpoints = &messages[0]["body"]["breakpoints"];
+    assert_eq!(breakpoints[0], json!({ "verified": true, "line": 2 }));
+    assert_eq!(breakpoints[1], json!({ "verified": true, "line": 4 }));
+    assert_eq!(breakpoints[2]["verified"], false);
+}
+
+#[test]
+fn requests_that_need_a_paused_program_fail() {
+    let messages = session(&[
+        json!({ "command": "stackTrace", "arguments": { "threadId": 1 } }),
+        json!({ "command": "frobnicate" }),
+        json!({ "command": "disconnect" }),
+        json!({ "command": "threads" }),
+    ]);
+
+    assert_eq!(messages[0]["success"], false);
+    assert_eq!(messages[1]["message"], "Unknown command frobnicate");
+    assert_eq!(messages[2]["success"], true);
+    // Nothing is handled after disconnecting
+    assert_eq!(messages.len(), 3);
+}
+
+#[test]
+fn lines_without_code_are_not_breakpoints() {
+    let code = "# Adds one\nsucc = (a) -> a + 1\n\n# Two\ntwo = succ 1\n";
+    assert_eq!(
+        verify(code, &[1, 2, 3, 5, 6]),
+        [Some(2), Some(2), Some(5), Some(5), None]
+    );
+}
@@ -0,0 +1,427 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The registry of error codes
+//!
+//! Every kind of error that is the fault of the code being compiled has a code, which never
+//! changes and is never reused, so that it can be searched for and explained with `vunk explain`.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Code {
+    E0001,
+    E0002,
+    E0003,
+    E0004,
+    E0005,
+    E0006,
+    E0007,
+    E0008,
+    E0009,
+    E0010,
+    E0011,
+    E0012,
+    E0013,
+    E0014,
+    E0015,
+    E0016,
+}
+
+impl Code {
+    /// All codes, in order
+    pub const ALL: &'static [Code] = &[
+        Code::E0001,
+        Code::E0002,
+        Code::E0003,
+        Code::E0004,
+        Code::E0005,
+        Code::E0006,
+        Code::E0007,
+        Code::E0008,
+        Code::E0009,
+        Code::E0010,
+        Code::E0011,
+        Code::E0012,
+        Code::E0013,
+        Code::E0014,
+        Code::E0015,
+        Code::E0016,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Code::E0001 => "E0001",
+            Code::E0002 => "E0002",
+            Code::E0003 => "E0003",
+            Code::E0004 => "E0004",
+            Code::E0005 => "E0005",
+            Code::E0006 => "E0006",
+            Code::E0007 => "E0007",
+            Code::E0008 => "E0008",
+            Code::E0009 => "E0009",
+            Code::E0010 => "E0010",
+            Code::E0011 => "E0011",
+            Code::E0012 => "E0012",
+            Code::E0013 => "E0013",
+            Code::E0014 => "E0014",
+            Code::E0015 => "E0015",
+            Code::E0016 => "E0016",
+        }
+    }
+
+    /// What kind of error it is, in a few words
+    pub fn summary(&self) -> &'static str {
+        match self {
+            Code::E0001 => "The code cannot be split into tokens",
+            Code::E0002 => "A use refers to a module that does not exist",
+            Code::E0003 => "'?' is used where it cannot return from the function",
+            Code::E0004 => "Packages depend on each other in a cycle",
+            Code::E0005 => "A dependency does not have a version that is required",
+            Code::E0006 => "A dependency changed since it was locked",
+            Code::E0007 => "Modules use each other in a cycle",
+            Code::E0008 => "A use refers to an item its module does not define",
+            Code::E0009 => "A name is brought into scope by more than one glob use",
+            Code::E0010 => "A @cfg attribute has a condition that is not understood",
+            Code::E0011 => "An attribute is not known or does not take its arguments",
+            Code::E0012 => "A match on literals does not cover every value",
+            Code::E0013 => "An impl does not fit the trait it implements",
+            Code::E0014 => "A type is applied to a number of type arguments its kind does not take",
+            Code::E0015 => "A function type names an effect that is not declared",
+            Code::E0016 => "The tokens do not form items of the grammar",
+        }
+    }
+
+    /// The extended description, with examples of code causing the error and how to fix it
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            Code::E0001 => {
+                "\
+The code contains something that is not a token of vunk, like a character that is not used by
+the language or a string that is not closed.
+
+Erroneous code example:
+
+    greeting = \"Hello
+
+Strings end with a '\"' on the same line:
+
+    greeting = \"Hello\"
+"
+            }
+            Code::E0002 => {
+                "\
+A `use` refers to a module that is neither part of the package nor of one of its dependencies.
+
+Erroneous code example:
+
+    use Std.Strnig.toUpper
+
+Check the spelling of the path, or add the package providing the module with `vunk add`:
+
+    use Std.String.toUpper
+"
+            }
+            Code::E0003 => {
+                "\
+`expr?` returns the error of `expr` from the function, so it can only be used where its value
+determines the result of the function. It cannot be used in the bindings of a nested `let` or
+in the condition of an `if`, for example.
+
+Erroneous code example:
+
+    parse_sum = (a: String, b: String) ->
+        if (parse a)? > 0 then Ok 1 else Ok 0
+
+Bind the value in the result of the function first:
+
+    parse_sum = (a: String, b: String) ->
+        let
+            x = (parse a)?
+        in
+        if x > 0 then Ok 1 else Ok 0
+"
+            }
+            Code::E0004 => {
+                "\
+A package depends on itself, directly or through other packages. Packages are compiled after
+their dependencies, which is impossible for packages in a cycle.
+
+Erroneous example, in the vunk.toml of the package a:
+
+    [dependencies]
+    b = { path = \"../b\" }
+
+and in the vunk.toml of the package b:
+
+    [dependencies]
+    a = { path = \"../a\" }
+
+Move the code both packages need into a third package that both depend on.
+"
+            }
+            Code::E0005 => {
+                "\
+The version of a dependency does not match the version requirement in vunk.toml.
+
+Erroneous example, if the package json has the version 1.4.0:
+
+    [dependencies]
+    json = { path = \"../json\", version = \"^2.0\" }
+
+Change the requirement, or use a version of the dependency that matches it:
+
+    [dependencies]
+    json = { path = \"../json\", version = \"^1.4\" }
+"
+            }
+            Code::E0006 => {
+                "\
+The content of a dependency differs from the content recorded in vunk.lock, for example because
+a path dependency was edited or the commit of a git dependency was rewritten.
+
+If the change is expected, run `vunk update` to record the new content in vunk.lock.
+"
+            }
+            Code::E0007 => {
+                "\
+A module uses itself, directly or through other modules. The definitions of a module are
+evaluated after the definitions of the modules it uses, which is impossible for modules in a
+cycle.
+
+Erroneous example, in the module parser:
+
+    use lexer.tokens
+
+and in the module lexer:
+
+    use parser.parse
+
+Move the definitions both modules need into a third module that both use, or merge the modules.
+"
+            }
+            Code::E0008 => {
+                "\
+A `use`, or a path through the alias of a `use`, names an item that the module it refers to does
+not declare or define. The module exists, otherwise the error would be E0002.
+
+Erroneous code example, with the module geometry.vector defining `length`:
+
+    use geometry.vector as V
+
+    main = V.lenght (1, 2)
+
+Check the spelling of the item, or define it in the module:
+
+    main = V.length (1, 2)
+"
+            }
+            Code::E0009 => {
+                "\
+A name is used that is a public item of more than one module used with a glob, so it is not
+clear which one is meant. Globs only conflict where such a name is used.
+
+Erroneous code example:
+
+    use Std.List.*
+    use Std.Option.*
+
+    main = map (x) -> x + 1
+
+Use the item that is meant explicitly, which takes precedence over globs, or refer to it by its
+path:
+
+    use Std.List.*
+    use Std.Option.*
+    use Std.List.map
+
+    main = map (x) -> x + 1
+"
+            }
+            Code::E0010 => {
+                "\
+The condition of a `@cfg` attribute is not one of `backend = \"...\"`, `feature = \"...\"`, `debug`,
+`release` or `not`, `all` or `any` of conditions, or it names a backend or a feature that does not
+exist.
+
+Erroneous code example:
+
+    @cfg(backend = \"js\")
+    print: String -> Io ()
+
+The backends are `interpreter`, `wasm` and `native`, and the features are those of the
+`[features]` table of vunk.toml:
+
+    @cfg(backend = \"wasm\")
+    print: String -> Io ()
+"
+            }
+            Code::E0011 => {
+                "\
+An item has an attribute that does not exist, or an attribute with arguments it does not take.
+The attributes are `@allow`, `@cfg`, `@deny`, `@deprecated`, `@derive`, `@inline`, `@no_prelude`,
+`@test` and `@warn`.
+
+Erroneous code example:
+
+    @inline(sometimes)
+    double = (x) -> x * 2
+
+`@inline` takes no arguments, `always` or `never`:
+
+    @inline(always)
+    double = (x) -> x * 2
+"
+            }
+            Code::E0012 => {
+                "\
+A `match` has arms for strings, numbers or bools, but not for the values it does not list.
+Strings and numbers have too many values to list them all, so such a `match` needs an arm with `_`
+or a variable, or an `else`. A `match` on bools is complete with arms for `true` and `false`.
+Arms with a guard, like `when n when n > 500 -> ...`, do not count, as they may not be taken.
+
+Erroneous code example:
+
+    status = (code) -> match code
+        when 200 -> \"ok\"
+        when 404 -> \"not found\"
+
+An arm with `_` covers all other codes:
+
+    status = (code) -> match code
+        when 200 -> \"ok\"
+        when 404 -> \"not found\"
+        when _ -> \"unknown\"
+"
+            }
+            Code::E0013 => {
+                "\
+An `impl` has to define every method of its trait that the trait has no default for, and it
+cannot define methods that the trait does not declare. The defaults of a trait have to be
+declared as methods of the trait, too. The same goes for associated types, like `type Item`,
+which every `impl` defines and projections like `Collection.Item` have to name.
+
+Erroneous code example:
+
+    trait Eq =
+        { eq: (Self, Self) -> Bool
+          ne: (Self, Self) -> Bool
+          ne = (a, b) -> !(eq a b)
+        }
+
+    impl Eq on Point =
+        { ne = (a, b) -> a.x != b.x
+        }
+
+`ne` has a default, but `eq` has not, so the `impl` has to define it:
+
+    impl Eq on Point =
+        { eq = (a, b) -> a.x == b.x
+        }
+"
+            }
+            Code::E0014 => {
+                "\
+The kind of a type says how many type arguments it takes. `i64` takes none, `Option` takes one
+and `Result` takes two. A type variable gets its kind from how it is used, so `Self` of a trait
+whose methods apply it to a type, like `Self A`, takes one type argument, and only types that
+take one can implement the trait.
+
+Erroneous code example:
+
+    trait Functor =
+        { fmap: ((A) -> B, Self A) -> Self B
+        }
+
+    impl Functor on i64 =
+        { fmap = (f, x) -> f x
+        }
+
+`i64` takes no type arguments, so it cannot stand for `Self` in `Self A`. Implement the trait on
+a type that takes one, like `Option`:
+
+    impl Functor on Option =
+        { fmap = (f, option) -> Option.map f option
+        }
+"
+            }
+            Code::E0015 => {
+                "\
+The effects after `with` in a function type have to be declared with `effect`, or be one of the
+effects the interpreter handles by default, `Console` and `Files`. An effect also has to declare
+each of its operations only once.
+
+Erroneous code example:
+
+    greet: (String) -> () with Log
+
+Declare the effect and its operations:
+
+    effect Log =
+        { log: (String) -> ()
+        }
+
+    greet: (String) -> () with Log
+"
+            }
+            Code::E0016 => {
+                "\
+The tokens of a file have to form items: declarations, definitions, or definitions of types,
+traits, `impl`s, effects and patterns. As line ends do not end an item, a mistake is often
+reported where the next item starts.
+
+Erroneous code example:
+
+    double = (x) -> x *
+
+    triple = (x) -> x * 3
+
+The operator is missing its right operand, so `triple` is taken for it, and the error is reported
+right after `triple =`:
+
+    double = (x) -> x * 2
+
+    triple = (x) -> x * 3
+"
+            }
+        }
+    }
+}
+
+impl Display for Code {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<Code> for String {
+    fn from(code: Code) -> String {
+        code.as_str().to_string()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0} is not an error code")]
+pub struct UnknownCode(pub String);
+
+impl FromStr for Code {
+    type Err = UnknownCode;
+
+    /// Parse a code like `E0042`, the `E` and leading zeros being optional
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = s.strip_prefix(['E', 'e']).unwrap_or(s);
+        number
+            .parse::<usize>()
+            .ok()
+            .filter(|_| !number.starts_with('+'))
+            .and_then(|number| {
+                Code::ALL
+                    .iter()
+                    .find(|code| code.as_str()[1..].parse::<usize>() == Ok(number))
+            })
+            .copied()
+            .ok_or_else(|| UnknownCode(s.to_string()))
+    }
+}
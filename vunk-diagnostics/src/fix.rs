@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Applying suggestions to the code they were made for
+
+use crate::Applicability;
+use crate::Suggestion;
+
+/// Apply the machine-applicable suggestions to `code`, returning the new code and how many were
+/// applied
+///
+/// Suggestions are applied from the start of the code to its end. A suggestion overlapping one
+/// that was applied is left out, as the code it was made for has changed, and so is a suggestion
+/// whose span is not within the code.
+pub fn apply<'a>(
+    code: &str,
+    suggestions: impl IntoIterator<Item = &'a Suggestion>,
+) -> (String, usize) {
+    let mut suggestions = suggestions
+        .into_iter()
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable)
+        .collect::<Vec<_>>();
+    suggestions.sort_by_key(|suggestion| (suggestion.span.start, suggestion.span.end));
+
+    let mut fixed = String::with_capacity(code.len());
+    let mut end = 0;
+    let mut applied = 0;
+    for suggestion in suggestions {
+        let span = &suggestion.span;
+        if span.start < end || code.get(span.clone()).is_none() {
+            continue;
+        }
+
+        fixed.push_str(&code[end..span.start]);
+        fixed.push_str(&suggestion.replacement);
+        end = span.end;
+        applied += 1;
+    }
+    fixed.push_str(&code[end..]);
+
+    (fixed, applied)
+}
@@ -0,0 +1,132 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Diagnostics as JSON objects, for editors and CI tools
+//!
+//! ```json
+//! {
+//!   "severity": "error",
+//!   "code": "E0001",
+//!   "message": "Unexpected ';'",
+//!   "spans": [
+//!     {
+//!       "file": "main.vunk",
+//!       "byte_start": 5,
+//!       "byte_end": 6,
+//!       "line_start": 1,
+//!       "column_start": 6,
+//!       "line_end": 1,
+//!       "column_end": 7,
+//!       "label": "here",
+//!       "primary": true
+//!     }
+//!   ],
+//!   "notes": [],
+//!   "suggestions": [],
+//!   "rendered": "error[E0001]: Unexpected ';'\n --> main.vunk:1:6\n..."
+//! }
+//! ```
+//!
+//! Suggestions have a `message`, the `replacement`, the `span` it replaces and an `applicability`
+//! of `machine-applicable`, `maybe-incorrect`, `has-placeholders` or `unspecified`. Lines and
+//! columns count from 1, columns count chars, and the end is exclusive.
+
+use std::ops::Range;
+
+use crate::line_column;
+use crate::Diagnostic;
+use crate::File;
+use crate::Severity;
+
+#[derive(serde::Serialize)]
+struct Json<'a> {
+    severity: &'static str,
+    code: Option<&'a str>,
+    message: &'a str,
+    spans: Vec<Span<'a>>,
+    notes: &'a [String],
+    suggestions: Vec<Suggestion<'a>>,
+    rendered: String,
+}
+
+#[derive(serde::Serialize)]
+struct Span<'a> {
+    file: &'a str,
+    byte_start: usize,
+    byte_end: usize,
+    line_start: usize,
+    column_start: usize,
+    line_end: usize,
+    column_end: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    primary: Option<bool>,
+}
+
+#[derive(serde::Serialize)]
+struct Suggestion<'a> {
+    message: &'a str,
+    replacement: &'a str,
+    applicability: String,
+    span: Option<Span<'a>>,
+}
+
+/// The diagnostic as a JSON object on a single line
+pub fn to_json(diagnostic: &Diagnostic) -> String {
+    let file = diagnostic.file.as_ref();
+    let json = Json {
+        severity: match diagnostic.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        },
+        code: diagnostic.code.as_deref(),
+        message: &diagnostic.message,
+        spans: file
+            .map(|file| {
+                diagnostic
+                    .labels
+                    .iter()
+                    .map(|label| Span {
+                        label: Some(label.message.as_str()),
+                        primary: Some(label.primary),
+                        ..span(file, &label.span)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        notes: &diagnostic.notes,
+        suggestions: diagnostic
+            .suggestions
+            .iter()
+            .map(|suggestion| Suggestion {
+                message: &suggestion.message,
+                replacement: &suggestion.replacement,
+                applicability: suggestion.applicability.to_string(),
+                span: file.map(|file| span(file, &suggestion.span)),
+            })
+            .collect(),
+        rendered: diagnostic.render(false),
+    };
+
+    // There is nothing in it that cannot be serialized
+    serde_json::to_string(&json).unwrap_or_default()
+}
+
+fn span<'a>(file: &'a File, span: &Range<usize>) -> Span<'a> {
+    let (line_start, column_start) = line_column(&file.code, span.start);
+    let (line_end, column_end) = line_column(&file.code, span.end);
+    Span {
+        file: &file.name,
+        byte_start: span.start,
+        byte_end: span.end,
+        line_start,
+        column_start,
+        line_end,
+        column_end,
+        label: None,
+        primary: None,
+    }
+}
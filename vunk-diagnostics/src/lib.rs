@@ -0,0 +1,320 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Diagnostics of all stages of the pipeline, from lexing to typechecking
+//!
+//! A [`Diagnostic`] points into the code of a [`File`] with labeled spans. It is rendered as an
+//! annotated snippet of the code with [`render::render`], as JSON with [`json::to_json`], or by
+//! miette, as it implements [`miette::Diagnostic`] as well. Suggestions that are certain to be
+//! right are applied with [`fix::apply`].
+
+use std::fmt::Display;
+use std::ops::Range;
+use std::sync::Arc;
+
+use miette::LabeledSpan;
+use miette::MietteError;
+use miette::MietteSpanContents;
+use miette::SourceCode;
+use miette::SourceSpan;
+use miette::SpanContents;
+
+pub mod codes;
+pub mod fix;
+pub mod json;
+pub mod limit;
+pub mod render;
+pub mod suggest;
+pub mod terminal;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A source file, with the name it is shown with
+///
+/// The code is shared, as every diagnostic pointing into the file holds it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct File {
+    pub name: String,
+    pub code: Arc<str>,
+}
+
+/// A span of the file of a diagnostic, with a message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    /// Byte range in the code of the file
+    pub span: Range<usize>,
+    pub message: String,
+
+    /// Whether this is where the problem is, instead of where something related to it is
+    pub primary: bool,
+}
+
+impl Label {
+    pub fn primary(span: Range<usize>, message: impl Into<String>) -> Self {
+        Label {
+            span,
+            message: message.into(),
+            primary: true,
+        }
+    }
+
+    pub fn secondary(span: Range<usize>, message: impl Into<String>) -> Self {
+        Label {
+            span,
+            message: message.into(),
+            primary: false,
+        }
+    }
+}
+
+/// A change of the code that fixes the problem
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    pub message: String,
+
+    /// Byte range in the code of the file that is replaced
+    pub span: Range<usize>,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+/// How sure it is that a suggestion is what was meant, like in rustc
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Applicability {
+    /// The suggestion is definitely what was meant, so it can be applied without asking
+    MachineApplicable,
+
+    /// The suggestion may be what was meant, but may also be wrong
+    MaybeIncorrect,
+
+    /// The replacement contains placeholders like `<expr>` that have to be filled in
+    HasPlaceholders,
+
+    Unspecified,
+}
+
+impl Display for Applicability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Applicability::MachineApplicable => write!(f, "machine-applicable"),
+            Applicability::MaybeIncorrect => write!(f, "maybe-incorrect"),
+            Applicability::HasPlaceholders => write!(f, "has-placeholders"),
+            Applicability::Unspecified => write!(f, "unspecified"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+
+    /// Stable identifier of the kind of problem, like `E0001`, see [`codes::Code`]
+    pub code: Option<String>,
+    pub message: String,
+
+    /// The file the labels and suggestions point into
+    pub file: Option<File>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            code: None,
+            message: message.into(),
+            file: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Diagnostic::new(Severity::Warning, message)
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_file(mut self, file: File) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_suggestion(
+        self,
+        message: impl Into<String>,
+        span: Range<usize>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.with_applicable_suggestion(message, span, replacement, Applicability::Unspecified)
+    }
+
+    /// A suggestion that is definitely what was meant, which `vunk fix` applies
+    pub fn with_fix(
+        self,
+        message: impl Into<String>,
+        span: Range<usize>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.with_applicable_suggestion(
+            message,
+            span,
+            replacement,
+            Applicability::MachineApplicable,
+        )
+    }
+
+    pub fn with_applicable_suggestion(
+        mut self,
+        message: impl Into<String>,
+        span: Range<usize>,
+        replacement: impl Into<String>,
+        applicability: Applicability,
+    ) -> Self {
+        self.suggestions.push(Suggestion {
+            message: message.into(),
+            span,
+            replacement: replacement.into(),
+            applicability,
+        });
+        self
+    }
+
+    /// The diagnostic as an annotated snippet of the code, see [`render::render`]
+    pub fn render(&self, color: bool) -> String {
+        render::render(self, color)
+    }
+}
+
+/// The line and column of a byte offset, both counted from 1, with columns counted in chars
+pub fn line_column(code: &str, offset: usize) -> (usize, usize) {
+    let offset = floor_char_boundary(code, offset);
+    let before = &code[..offset];
+    let line_start = before.rfind('\n').map(|newline| newline + 1).unwrap_or(0);
+    let line = before.matches('\n').count() + 1;
+    (line, before[line_start..].chars().count() + 1)
+}
+
+// The largest offset that is not after `offset` and not within a char
+fn floor_char_boundary(code: &str, offset: usize) -> usize {
+    let mut offset = offset.min(code.len());
+    while !code.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl miette::Diagnostic for Diagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.code
+            .as_ref()
+            .map(|code| Box::new(code) as Box<dyn Display>)
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.severity {
+            Severity::Error => miette::Severity::Error,
+            Severity::Warning => miette::Severity::Warning,
+            Severity::Note => miette::Severity::Advice,
+        })
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let suggestions = self
+            .suggestions
+            .iter()
+            .map(|suggestion| format!("{}: `{}`", suggestion.message, suggestion.replacement));
+        let help = self
+            .notes
+            .iter()
+            .cloned()
+            .chain(suggestions)
+            .collect::<Vec<_>>();
+        if help.is_empty() {
+            None
+        } else {
+            Some(Box::new(help.join("\n")))
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.file.as_ref().map(|file| file as &dyn SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        if self.labels.is_empty() {
+            return None;
+        }
+
+        Some(Box::new(self.labels.iter().map(|label| {
+            let message = Some(label.message.clone()).filter(|message| !message.is_empty());
+            LabeledSpan::new(message, label.span.start, label.span.len())
+        })))
+    }
+}
+
+// Like miette's `NamedSource`, so that miette shows the name of the file
+impl SourceCode for File {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        let contents = self
+            .code
+            .read_span(span, context_lines_before, context_lines_after)?;
+        Ok(Box::new(MietteSpanContents::new_named(
+            self.name.clone(),
+            contents.data(),
+            *contents.span(),
+            contents.line(),
+            contents.column(),
+            contents.line_count(),
+        )))
+    }
+}
@@ -0,0 +1,80 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Keeping the diagnostics of a badly broken file few enough to find the cause among them
+
+use std::ops::Range;
+
+use crate::Diagnostic;
+use crate::Severity;
+
+/// Leave out duplicates, and errors that are most likely caused by earlier ones
+///
+/// Diagnostics with the same code, file and primary span are duplicates, as are diagnostics without
+/// a span with the same code and message. An error whose primary span is within the primary span
+/// of an earlier error in the same file, like the errors following an unclosed string, is most
+/// likely caused by it.
+pub fn deduplicate(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    let mut kept: Vec<Diagnostic> = Vec::new();
+    for diagnostic in diagnostics {
+        let duplicate = kept.iter().any(|earlier| {
+            earlier.code == diagnostic.code
+                && file_name(earlier) == file_name(&diagnostic)
+                && primary_span(earlier) == primary_span(&diagnostic)
+                && (primary_span(earlier).is_some() || earlier.message == diagnostic.message)
+        });
+        let caused = diagnostic.severity == Severity::Error
+            && kept.iter().any(|earlier| {
+                earlier.severity == Severity::Error
+                    && file_name(earlier) == file_name(&diagnostic)
+                    && match (primary_span(earlier), primary_span(&diagnostic)) {
+                        (Some(earlier), Some(span)) => {
+                            earlier.start <= span.start && span.end <= earlier.end
+                        }
+                        _ => false,
+                    }
+            });
+
+        if !duplicate && !caused {
+            kept.push(diagnostic);
+        }
+    }
+    kept
+}
+
+/// Keep at most `max` diagnostics of every severity, followed by a note on how many were left out
+pub fn truncate(diagnostics: Vec<Diagnostic>, max: usize) -> Vec<Diagnostic> {
+    let mut counts = [0; 3];
+    let mut left_out = 0;
+    let mut kept = Vec::new();
+    for diagnostic in diagnostics {
+        let count = &mut counts[diagnostic.severity as usize];
+        *count += 1;
+        if *count > max {
+            left_out += 1;
+        } else {
+            kept.push(diagnostic);
+        }
+    }
+
+    if left_out > 0 {
+        kept.push(Diagnostic::new(
+            Severity::Note,
+            format!("Too many diagnostics, {} not shown", left_out),
+        ));
+    }
+    kept
+}
+
+fn file_name(diagnostic: &Diagnostic) -> Option<&str> {
+    diagnostic.file.as_ref().map(|file| file.name.as_str())
+}
+
+fn primary_span(diagnostic: &Diagnostic) -> Option<&Range<usize>> {
+    diagnostic
+        .labels
+        .iter()
+        .find(|label| label.primary)
+        .map(|label| &label.span)
+}
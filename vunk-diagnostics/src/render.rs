@@ -0,0 +1,197 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Diagnostics as annotated snippets of the code, like rustc shows them:
+//!
+//! ```text
+//! error: Unexpected ';'
+//!  --> main.vunk:1:6
+//!   |
+//! 1 | a = 1;
+//!   |      ^ here
+//!   |
+//!   = note: expressions end at the end of the line
+//! ```
+//!
+//! Every line containing the start of a label is shown, with one line of markers per label. Labels
+//! spanning several lines are marked until the end of their first line.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::line_column;
+use crate::Diagnostic;
+use crate::File;
+use crate::Label;
+use crate::Severity;
+
+const BOLD: &str = "1";
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const CYAN: &str = "1;36";
+const BLUE: &str = "1;34";
+
+struct Painter {
+    color: bool,
+}
+
+impl Painter {
+    fn paint(&self, text: &str, style: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", style, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Render a diagnostic, with ANSI colors if `color` is set
+pub fn render(diagnostic: &Diagnostic, color: bool) -> String {
+    let painter = Painter { color };
+    let style = match diagnostic.severity {
+        Severity::Error => RED,
+        Severity::Warning => YELLOW,
+        Severity::Note => CYAN,
+    };
+
+    let mut out = String::new();
+    let header = match &diagnostic.code {
+        Some(code) => format!("{}[{}]", diagnostic.severity, code),
+        None => diagnostic.severity.to_string(),
+    };
+    let message = format!(": {}", diagnostic.message);
+    let _ = writeln!(
+        out,
+        "{}{}",
+        painter.paint(&header, style),
+        painter.paint(&message, BOLD)
+    );
+
+    let lines = diagnostic
+        .file
+        .as_ref()
+        .map(|file| labels_by_line(file, &diagnostic.labels))
+        .unwrap_or_default();
+    let width = lines
+        .keys()
+        .last()
+        .map(|line| (line + 1).to_string().len())
+        .unwrap_or(1);
+    let pad = " ".repeat(width);
+    let gutter = painter.paint("|", BLUE);
+
+    if let Some(file) = diagnostic.file.as_ref() {
+        let primary = diagnostic
+            .labels
+            .iter()
+            .find(|label| label.primary)
+            .or_else(|| diagnostic.labels.first());
+        let location = match primary {
+            Some(label) => {
+                let (line, column) = line_column(&file.code, label.span.start);
+                format!("{}:{}:{}", file.name, line, column)
+            }
+            None => file.name.clone(),
+        };
+        let _ = writeln!(out, "{}{} {}", pad, painter.paint("-->", BLUE), location);
+    }
+
+    if !lines.is_empty() {
+        let _ = writeln!(out, "{} {}", pad, gutter);
+    }
+    let mut previous = None;
+    for (index, (text, labels)) in lines.iter() {
+        if previous
+            .map(|previous| index - previous > 1)
+            .unwrap_or(false)
+        {
+            let _ = writeln!(out, "{}", painter.paint("...", BLUE));
+        }
+        previous = Some(*index);
+
+        let number = painter.paint(&format!("{:>width$}", index + 1, width = width), BLUE);
+        let _ = writeln!(out, "{} {} {}", number, gutter, text);
+        for (start, end, label) in labels {
+            let (marker, label_style) = if label.primary {
+                ("^", style)
+            } else {
+                ("-", BLUE)
+            };
+            let before = text.get(..*start).unwrap_or_default().chars().count();
+            let length = text.get(*start..*end).unwrap_or_default().chars().count();
+            let markers = format!("{} {}", marker.repeat(length.max(1)), label.message);
+            let _ = writeln!(
+                out,
+                "{} {} {}{}",
+                pad,
+                gutter,
+                " ".repeat(before),
+                painter.paint(markers.trim_end(), label_style)
+            );
+        }
+    }
+
+    let help = diagnostic
+        .notes
+        .iter()
+        .map(|note| ("note", note.clone()))
+        .chain(diagnostic.suggestions.iter().map(|suggestion| {
+            let text = format!("{}: `{}`", suggestion.message, suggestion.replacement);
+            ("help", text)
+        }))
+        .collect::<Vec<_>>();
+    if !help.is_empty() && !lines.is_empty() {
+        let _ = writeln!(out, "{} {}", pad, gutter);
+    }
+    for (kind, text) in help {
+        let _ = writeln!(
+            out,
+            "{} {} {}: {}",
+            pad,
+            painter.paint("=", BLUE),
+            kind,
+            text
+        );
+    }
+
+    out
+}
+
+// The text of a line, with the labels starting in it as ranges of bytes within the line
+type LabeledLine<'a> = (&'a str, Vec<(usize, usize, &'a Label)>);
+
+// The lines of the file that labels start in, by their index, with the labels ordered by their
+// start
+fn labels_by_line<'a>(file: &'a File, labels: &'a [Label]) -> BTreeMap<usize, LabeledLine<'a>> {
+    let mut lines = BTreeMap::<usize, (&str, Vec<_>)>::new();
+    for label in labels {
+        let (line, _) = line_column(&file.code, label.span.start);
+        let index = line - 1;
+        let line_start = file
+            .code
+            .split_inclusive('\n')
+            .take(index)
+            .map(str::len)
+            .sum::<usize>();
+        let text = file
+            .code
+            .get(line_start..)
+            .and_then(|rest| rest.lines().next())
+            .unwrap_or_default();
+
+        let start = label.span.start.saturating_sub(line_start).min(text.len());
+        let end = label
+            .span
+            .end
+            .saturating_sub(line_start)
+            .clamp(start, text.len());
+        let entry = lines.entry(index).or_insert_with(|| (text, Vec::new()));
+        entry.1.push((start, end, label));
+    }
+
+    for (_, labels) in lines.values_mut() {
+        labels.sort_by_key(|(start, end, _)| (*start, *end));
+    }
+    lines
+}
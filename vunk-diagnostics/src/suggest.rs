@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Finding what a misspelled name was meant to be
+
+use crate::Applicability;
+
+/// The number of chars that have to be inserted, removed or replaced to turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a != *b);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The candidate closest to `name`, if it is close enough to be a misspelling of it
+///
+/// The suggestion is machine-applicable if no other candidate is as close.
+pub fn closest<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<(&'a str, Applicability)> {
+    // Like rustc, a third of the length may be misspelled
+    let limit = (name.chars().count() / 3).max(1);
+
+    let mut best = None::<(&str, usize)>;
+    let mut ambiguous = false;
+    for candidate in candidates {
+        let distance = edit_distance(name, candidate);
+        if candidate == name || distance > limit {
+            continue;
+        }
+        match best {
+            Some((best, best_distance)) if distance == best_distance && candidate != best => {
+                ambiguous = true
+            }
+            Some((_, best_distance)) if distance >= best_distance => {}
+            _ => {
+                best = Some((candidate, distance));
+                ambiguous = false;
+            }
+        }
+    }
+
+    best.map(|(candidate, _)| {
+        let applicability = if ambiguous {
+            Applicability::MaybeIncorrect
+        } else {
+            Applicability::MachineApplicable
+        };
+        (candidate, applicability)
+    })
+}
@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! What the terminal, or log file, that diagnostics are written to can show
+
+use std::ffi::OsString;
+
+/// Whether to use colors and Unicode box drawing characters for diagnostics
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Terminal {
+    pub color: bool,
+
+    /// Whether spans are marked with Unicode characters, instead of only ASCII ones
+    pub unicode: bool,
+}
+
+impl Terminal {
+    /// Detect it from the environment of the process, see [`Terminal::from_env`]
+    pub fn detect(color: Option<bool>, is_terminal: bool) -> Self {
+        Terminal::from_env(color, is_terminal, |name| std::env::var_os(name))
+    }
+
+    /// Detect it from whether the output is a terminal and from the environment variables
+    ///
+    /// Colors are used if `color` is `Some(true)`. If it is `None`, they are used when writing to
+    /// a terminal that is not `TERM=dumb`, unless `NO_COLOR` is set to anything but an empty
+    /// string, see <https://no-color.org>. Unicode is only used when writing to a terminal that
+    /// is not dumb, as log files may be read with anything.
+    pub fn from_env(
+        color: Option<bool>,
+        is_terminal: bool,
+        var: impl Fn(&str) -> Option<OsString>,
+    ) -> Self {
+        let capable = is_terminal && var("TERM").map(|term| term != "dumb").unwrap_or(true);
+        let no_color = var("NO_COLOR")
+            .map(|value| !value.is_empty())
+            .unwrap_or(false);
+        Terminal {
+            color: color.unwrap_or(capable && !no_color),
+            unicode: capable,
+        }
+    }
+}
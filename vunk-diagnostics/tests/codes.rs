@@ -0,0 +1,35 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_diagnostics::codes::Code;
+
+#[test]
+fn codes_are_numbered_without_gaps() {
+    for (index, code) in Code::ALL.iter().enumerate() {
+        assert_eq!(code.as_str(), format!("E{:04}", index + 1));
+        assert_eq!(code.as_str(), format!("{:?}", code));
+    }
+}
+
+#[test]
+fn every_code_is_explained() {
+    for code in Code::ALL {
+        assert!(!code.summary().is_empty(), "{} has no summary", code);
+        assert!(
+            code.explanation().ends_with('\n'),
+            "{} does not end in a newline",
+            code
+        );
+    }
+}
+
+#[test]
+fn codes_are_parsed() {
+    assert_eq!("E0003".parse::<Code>().unwrap(), Code::E0003);
+    assert_eq!("e0003".parse::<Code>().unwrap(), Code::E0003);
+    assert_eq!("3".parse::<Code>().unwrap(), Code::E0003);
+    assert!("E9999".parse::<Code>().is_err());
+    assert!("E+3".parse::<Code>().is_err());
+    assert!("three".parse::<Code>().is_err());
+}
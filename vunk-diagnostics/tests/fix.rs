@@ -0,0 +1,56 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_diagnostics::fix::apply;
+use vunk_diagnostics::suggest::closest;
+use vunk_diagnostics::suggest::edit_distance;
+use vunk_diagnostics::Applicability;
+use vunk_diagnostics::Diagnostic;
+
+#[test]
+fn machine_applicable_suggestions_are_applied() {
+    let diagnostic = Diagnostic::error("Problems")
+        .with_fix("rename", 4..9, "String")
+        .with_fix("remove", 0..4, "")
+        .with_suggestion("maybe", 10..13, "toLower")
+        .with_fix("overlapping", 5..7, "x");
+
+    let (code, applied) = apply("use Strng.toUpper", diagnostic.suggestions.iter());
+    assert_eq!(code, "String.toUpper");
+    assert_eq!(applied, 2);
+}
+
+#[test]
+fn suggestions_outside_the_code_are_not_applied() {
+    let diagnostic = Diagnostic::error("Problem").with_fix("append", 3..5, "d");
+    assert_eq!(
+        apply("ab", diagnostic.suggestions.iter()),
+        ("ab".to_string(), 0)
+    );
+}
+
+#[test]
+fn edit_distances_count_chars() {
+    assert_eq!(edit_distance("vector", "vector"), 0);
+    assert_eq!(edit_distance("vectr", "vector"), 1);
+    assert_eq!(edit_distance("vetcor", "vector"), 2);
+    assert_eq!(edit_distance("", "abc"), 3);
+    assert_eq!(edit_distance("ä", "a"), 1);
+}
+
+#[test]
+fn closest_names_are_suggested() {
+    let names = ["vector", "matrix", "vectors"];
+    assert_eq!(
+        closest("vectr", names),
+        Some(("vector", Applicability::MachineApplicable))
+    );
+    assert_eq!(closest("quaternion", names), None);
+
+    // Equally close names are only maybe what was meant
+    assert_eq!(
+        closest("vectorz", names),
+        Some(("vector", Applicability::MaybeIncorrect))
+    );
+}
@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use serde_json::json;
+use serde_json::Value;
+use vunk_diagnostics::json::to_json;
+use vunk_diagnostics::Diagnostic;
+use vunk_diagnostics::File;
+use vunk_diagnostics::Label;
+
+#[test]
+fn spans_have_lines_and_columns() {
+    let diagnostic = Diagnostic::error("Unexpected ';'")
+        .with_code("E0001")
+        .with_file(File {
+            name: "main.vunk".to_string(),
+            code: "x = \"ä\"\na = 1;\n".into(),
+        })
+        .with_label(Label::primary(14..15, "here"))
+        .with_suggestion("remove it", 14..15, "");
+
+    let line = to_json(&diagnostic);
+    assert!(!line.contains('\n'));
+
+    let value: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(value["severity"], "error");
+    assert_eq!(value["code"], "E0001");
+    assert_eq!(value["message"], "Unexpected ';'");
+    assert_eq!(
+        value["spans"],
+        json!([{
+            "file": "main.vunk",
+            "byte_start": 14,
+            "byte_end": 15,
+            "line_start": 2,
+            "column_start": 6,
+            "line_end": 2,
+            "column_end": 7,
+            "label": "here",
+            "primary": true,
+        }])
+    );
+    assert_eq!(value["suggestions"][0]["message"], "remove it");
+    assert_eq!(value["suggestions"][0]["replacement"], "");
+    assert_eq!(value["suggestions"][0]["applicability"], "unspecified");
+    assert_eq!(value["suggestions"][0]["span"]["line_start"], 2);
+    assert_eq!(value["rendered"], diagnostic.render(false));
+}
+
+#[test]
+fn diagnostics_without_a_file_have_no_spans() {
+    let value: Value = serde_json::from_str(&to_json(&Diagnostic::warning("Unused"))).unwrap();
+    assert_eq!(value["severity"], "warning");
+    assert_eq!(value["code"], Value::Null);
+    assert_eq!(value["spans"], json!([]));
+}
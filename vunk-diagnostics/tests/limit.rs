@@ -0,0 +1,81 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_diagnostics::limit::deduplicate;
+use vunk_diagnostics::limit::truncate;
+use vunk_diagnostics::Diagnostic;
+use vunk_diagnostics::File;
+use vunk_diagnostics::Label;
+use vunk_diagnostics::Severity;
+
+fn error(message: &str, span: std::ops::Range<usize>) -> Diagnostic {
+    Diagnostic::error(message)
+        .with_code("E0001")
+        .with_file(File {
+            name: "main.vunk".to_string(),
+            code: "a = \"unclosed + 1\n".into(),
+        })
+        .with_label(Label::primary(span, "here"))
+}
+
+fn messages(diagnostics: &[Diagnostic]) -> Vec<&str> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect()
+}
+
+#[test]
+fn duplicates_are_left_out() {
+    let diagnostics = deduplicate(vec![
+        error("first", 0..1),
+        error("again", 0..1),
+        error("elsewhere", 2..3),
+        Diagnostic::error("no span"),
+        Diagnostic::error("no span"),
+        Diagnostic::error("other"),
+    ]);
+    assert_eq!(
+        messages(&diagnostics),
+        ["first", "elsewhere", "no span", "other"]
+    );
+}
+
+#[test]
+fn errors_within_earlier_errors_are_left_out() {
+    let diagnostics = deduplicate(vec![
+        error("Unclosed string", 4..18),
+        error("Unexpected +", 14..15),
+        error("After it", 18..19),
+        Diagnostic::warning("Within, but a warning")
+            .with_file(File {
+                name: "main.vunk".to_string(),
+                code: "".into(),
+            })
+            .with_label(Label::primary(5..6, "")),
+    ]);
+    assert_eq!(
+        messages(&diagnostics),
+        ["Unclosed string", "After it", "Within, but a warning"]
+    );
+}
+
+#[test]
+fn diagnostics_are_truncated_per_severity() {
+    let diagnostics = truncate(
+        vec![
+            Diagnostic::error("1"),
+            Diagnostic::warning("a"),
+            Diagnostic::error("2"),
+            Diagnostic::error("3"),
+            Diagnostic::warning("b"),
+        ],
+        2,
+    );
+    assert_eq!(
+        messages(&diagnostics),
+        ["1", "a", "2", "b", "Too many diagnostics, 1 not shown"]
+    );
+    assert_eq!(diagnostics[4].severity, Severity::Note);
+}
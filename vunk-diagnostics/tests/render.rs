@@ -0,0 +1,135 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::ffi::OsString;
+
+use vunk_diagnostics::line_column;
+use vunk_diagnostics::terminal::Terminal;
+use vunk_diagnostics::Diagnostic;
+use vunk_diagnostics::File;
+use vunk_diagnostics::Label;
+
+fn file(code: &str) -> File {
+    File {
+        name: "main.vunk".to_string(),
+        code: code.into(),
+    }
+}
+
+// Looks up environment variables in `variables` instead of the environment of the process
+fn environment(
+    variables: &'static [(&'static str, &'static str)],
+) -> impl Fn(&str) -> Option<OsString> {
+    move |name| {
+        variables
+            .iter()
+            .find(|(variable, _)| *variable == name)
+            .map(|(_, value)| OsString::from(value))
+    }
+}
+
+#[test]
+fn lines_and_columns_count_from_one() {
+    let code = "a = 1\nb = \"ä\" + c\n";
+    assert_eq!(line_column(code, 0), (1, 1));
+    assert_eq!(line_column(code, 6), (2, 1));
+    assert_eq!(line_column(code, 16), (2, 10));
+}
+
+#[test]
+fn labels_are_marked_below_their_line() {
+    let diagnostic = Diagnostic::error("Unexpected ';'")
+        .with_code("E0001")
+        .with_file(file("a = 1;\n"))
+        .with_label(Label::primary(5..6, "here"))
+        .with_note("expressions end at the end of the line")
+        .with_suggestion("remove it", 5..6, "");
+
+    let expected = "\
+error[E0001]: Unexpected ';'
+ --> main.vunk:1:6
+  |
+1 | a = 1;
+  |      ^ here
+  |
+  = note: expressions end at the end of the line
+  = help: remove it: ``
+";
+    assert_eq!(diagnostic.render(false), expected);
+}
+
+#[test]
+fn distant_lines_are_separated() {
+    let code = "one = 1\n\n\n\n\n\n\n\n\nten = one + two\n";
+    let diagnostic = Diagnostic::error("two is not defined")
+        .with_file(file(code))
+        .with_label(Label::secondary(0..3, "did you mean this?"))
+        .with_label(Label::primary(28..31, "not defined"));
+
+    let expected = "\
+error: two is not defined
+  --> main.vunk:10:13
+   |
+ 1 | one = 1
+   | --- did you mean this?
+...
+10 | ten = one + two
+   |             ^^^ not defined
+";
+    assert_eq!(diagnostic.render(false), expected);
+}
+
+#[test]
+fn colors_are_optional() {
+    let diagnostic = Diagnostic::warning("Unused");
+    assert_eq!(diagnostic.render(false), "warning: Unused\n");
+    assert!(diagnostic.render(true).contains("\x1b[1;33mwarning\x1b[0m"));
+}
+
+#[test]
+fn terminals_are_detected_from_the_environment() {
+    let fancy = Terminal {
+        color: true,
+        unicode: true,
+    };
+    let plain = Terminal {
+        color: false,
+        unicode: false,
+    };
+
+    assert_eq!(
+        Terminal::from_env(None, true, environment(&[("TERM", "xterm")])),
+        fancy
+    );
+    assert_eq!(Terminal::from_env(None, false, environment(&[])), plain);
+    assert_eq!(
+        Terminal::from_env(None, true, environment(&[("TERM", "dumb")])),
+        plain
+    );
+    assert_eq!(
+        Terminal::from_env(None, true, environment(&[("NO_COLOR", "1")])),
+        Terminal {
+            color: false,
+            unicode: true,
+        }
+    );
+    assert_eq!(
+        Terminal::from_env(None, true, environment(&[("NO_COLOR", "")])),
+        fancy
+    );
+    assert_eq!(
+        Terminal::from_env(Some(true), false, environment(&[])),
+        Terminal {
+            color: true,
+            unicode: false,
+        }
+    );
+    assert_eq!(
+        Terminal::from_env(Some(false), true, environment(&[])),
+        Terminal {
+            color: false,
+            unicode: true,
+        }
+    );
+}
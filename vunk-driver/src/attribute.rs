@@ -0,0 +1,222 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The attributes of items, and the arguments they take
+//!
+//! All attributes share one syntax, see [`vunk_parser::attribute`]. An attribute is known if it is
+//! in [`REGISTRY`], which is all there is to adding one besides what it does. Unknown attributes
+//! and attributes with arguments they do not take are errors, instead of being ignored.
+//!
+//! Until there is a parser, the attributes of an item are parsed from its lines starting with `@`,
+//! see [`crate::outline`].
+
+use std::ops::Range;
+
+use chumsky::primitive::end;
+use chumsky::Parser;
+use chumsky::Stream;
+use vunk_diagnostics::suggest;
+use vunk_lexer::Spanned;
+use vunk_parser::ast::attribute::Attribute;
+use vunk_parser::ast::attribute::Meta;
+use vunk_parser::ast::attribute::MetaValue;
+
+use crate::error::DriverError;
+use crate::outline::outline;
+use crate::outline::Item;
+use crate::source;
+use crate::source::Source;
+
+/// An attribute that items can have
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spec {
+    pub name: &'static str,
+
+    /// What the attribute does, in a few words
+    pub summary: &'static str,
+
+    pub args: Args,
+}
+
+/// The arguments an attribute takes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Args {
+    /// None, like `@test`
+    None,
+
+    /// One or more names, like `@derive(Eq, Show)`
+    Names,
+
+    /// Optionally one of the names, like `@inline(always)`
+    OneOf(&'static [&'static str]),
+
+    /// An optional message, then strings for some of the keys, like
+    /// `@deprecated("use area", since = "0.2")`
+    Message(&'static [&'static str]),
+
+    /// One condition, see [`crate::cfg`]
+    Condition,
+}
+
+/// The known attributes, by name
+pub const REGISTRY: &[Spec] = &[
+    Spec {
+        name: "allow",
+        summary: "Allow lints on the item",
+        args: Args::Names,
+    },
+    Spec {
+        name: "cfg",
+        summary: "Leave the item out unless the condition holds",
+        args: Args::Condition,
+    },
+    Spec {
+        name: "deny",
+        summary: "Make lints on the item errors",
+        args: Args::Names,
+    },
+    Spec {
+        name: "deprecated",
+        summary: "Mark the item as not to be used anymore",
+        args: Args::Message(&["since", "note"]),
+    },
+    Spec {
+        name: "derive",
+        summary: "Implement traits for the type",
+        args: Args::Names,
+    },
+    Spec {
+        name: "inline",
+        summary: "Ask for calls of the function to be inlined",
+        args: Args::OneOf(&["always", "never"]),
+    },
+    Spec {
+        name: "no_prelude",
+        summary: "Leave the prelude out of the scope of the module",
+        args: Args::None,
+    },
+    Spec {
+        name: "test",
+        summary: "Run the definition with vunk test",
+        args: Args::None,
+    },
+    Spec {
+        name: "warn",
+        summary: "Make lints on the item warnings",
+        args: Args::Names,
+    },
+];
+
+pub fn lookup(name: &str) -> Option<&'static Spec> {
+    REGISTRY.iter().find(|spec| spec.name == name)
+}
+
+impl Spec {
+    /// Check the arguments of an attribute, returning what is wrong with them
+    pub fn check(&self, attribute: &Attribute) -> Result<(), String> {
+        let args = &attribute.args;
+        match self.args {
+            Args::None if args.is_empty() => Ok(()),
+            Args::None => Err(format!("@{} takes no arguments", self.name)),
+            Args::Names if args.is_empty() => Err(format!("@{} takes names", self.name)),
+            Args::Names => {
+                let names = args
+                    .iter()
+                    .all(|arg| matches!(arg, Meta::Named { value: None, .. }));
+                if names {
+                    Ok(())
+                } else {
+                    Err(format!("@{} takes names", self.name))
+                }
+            }
+            Args::OneOf(names) => match args.as_slice() {
+                [] => Ok(()),
+                [Meta::Named { name, value: None }] if names.contains(&name.as_str()) => Ok(()),
+                _ => Err(format!("@{} takes one of {}", self.name, names.join(", "))),
+            },
+            Args::Message(keys) => {
+                let keyed = match args.split_first() {
+                    Some((Meta::Str(_), rest)) => rest,
+                    _ => args.as_slice(),
+                };
+                let valid = keyed.iter().all(|arg| match arg {
+                    Meta::Named {
+                        name,
+                        value: Some(MetaValue::Str(_)),
+                    } => keys.contains(&name.as_str()),
+                    _ => false,
+                });
+                if valid {
+                    return Ok(());
+                }
+                let keys = keys.iter().map(|key| format!("{} = \"...\"", key));
+                let keys = keys.collect::<Vec<_>>().join(", ");
+                Err(format!("@{} takes a message, then {}", self.name, keys))
+            }
+            Args::Condition if args.len() == 1 => Ok(()),
+            Args::Condition => Err(format!("@{} takes one condition", self.name)),
+        }
+    }
+}
+
+/// The attributes of an item, with their byte ranges, or the byte range of the first token that is
+/// not part of an attribute
+pub fn attributes(code: &str, item: &Item) -> Result<Vec<Spanned<Attribute>>, Range<usize>> {
+    let mut tokens = Vec::new();
+    let mut offset = item.span.start;
+    for line in code[item.span.start..item.name_span.start].split_inclusive('\n') {
+        if line.trim_start().starts_with('@') {
+            let line = source::tokens(line).into_iter();
+            let line = line.map(|(token, span)| (token, offset + span.start..offset + span.end));
+            tokens.extend(line);
+        }
+        offset += line.len();
+    }
+
+    let end_of_input = tokens
+        .last()
+        .map(|(_, span)| span.end)
+        .unwrap_or(item.span.start);
+    let stream = Stream::from_iter(end_of_input..end_of_input, tokens.into_iter());
+    vunk_parser::attribute::attributes()
+        .then_ignore(end())
+        .parse(stream)
+        .map_err(|errors| errors.first().map(|error| error.span()).unwrap_or_default())
+}
+
+/// Check the attributes of the items of `source` against the registry
+pub fn check(source: &Source) -> Result<(), DriverError> {
+    let code = source.code.as_str();
+    let invalid =
+        |message: String, span: Range<usize>, help: Option<String>| DriverError::InvalidAttribute {
+            message,
+            file: source.file(),
+            span: (span.start, span.len()).into(),
+            help,
+        };
+
+    for item in outline(code) {
+        let attributes = attributes(code, &item).map_err(|span| {
+            let message = "expected an attribute like @name or @name(argument, ...)";
+            invalid(message.to_string(), span, None)
+        })?;
+
+        for (attribute, span) in attributes {
+            let spec = match lookup(&attribute.name) {
+                Some(spec) => spec,
+                None => {
+                    let names = REGISTRY.iter().map(|spec| spec.name);
+                    let help = suggest::closest(&attribute.name, names).map(|(name, _)| {
+                        format!("An attribute with a similar name exists: @{}", name)
+                    });
+                    let message = format!("@{} is not an attribute", attribute.name);
+                    return Err(invalid(message, span, help));
+                }
+            };
+            spec.check(&attribute)
+                .map_err(|message| invalid(message, span, None))?;
+        }
+    }
+    Ok(())
+}
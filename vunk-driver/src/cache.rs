@@ -0,0 +1,109 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Results of pipeline stages kept on disk between invocations
+//!
+//! Every entry is a JSON file in `target/cache/<stage>/`, named after a hash of everything the
+//! result depends on, including the version of vunk. Entries are never invalidated, a change gives
+//! a different hash instead. The cache is only an optimization, so entries that cannot be read or
+//! written are treated as if they did not exist.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::package::manifest::Manifest;
+
+/// Directory of the cache, relative to the package root
+pub const DIRECTORY: &str = "target/cache";
+
+pub struct Cache {
+    directory: PathBuf,
+    statistics: Statistics,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    pub hits: usize,
+    pub misses: usize,
+    pub writes: usize,
+}
+
+impl Cache {
+    pub fn new(directory: PathBuf) -> Cache {
+        Cache {
+            directory,
+            statistics: Statistics::default(),
+        }
+    }
+
+    /// The cache of the package containing `path`, or, outside of packages, the cache next to it
+    pub fn for_path(path: &Path) -> Cache {
+        let root = Manifest::find_root(path).unwrap_or_else(|| {
+            let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            match path.parent() {
+                Some(parent) if path.is_file() => parent.to_path_buf(),
+                _ => path,
+            }
+        });
+        Cache::new(root.join(DIRECTORY))
+    }
+
+    pub fn get<T: serde::de::DeserializeOwned>(&mut self, stage: &str, key: &str) -> Option<T> {
+        let value = std::fs::read(self.path(stage, key))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok());
+        match value {
+            Some(_) => self.statistics.hits += 1,
+            None => self.statistics.misses += 1,
+        }
+        value
+    }
+
+    pub fn put<T: serde::Serialize>(&mut self, stage: &str, key: &str, value: &T) {
+        let path = self.path(stage, key);
+        let written = serde_json::to_vec(value)
+            .map_err(std::io::Error::from)
+            .and_then(|content| {
+                std::fs::create_dir_all(self.directory.join(stage))?;
+                std::fs::write(&path, content)
+            });
+        match written {
+            Ok(()) => self.statistics.writes += 1,
+            Err(error) => tracing::debug!("Could not write {}: {}", path.display(), error),
+        }
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        self.statistics
+    }
+
+    fn path(&self, stage: &str, key: &str) -> PathBuf {
+        self.directory.join(stage).join(format!("{}.json", key))
+    }
+}
+
+impl std::fmt::Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cache: {} hits, {} misses, {} written",
+            self.hits, self.misses, self.writes
+        )
+    }
+}
+
+/// The name of the entry of a result computed from `inputs`
+pub fn key(inputs: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    for input in inputs {
+        // The length keeps ["ab", "c"] and ["a", "bc"] apart
+        hasher.update(input.len().to_le_bytes());
+        hasher.update(input.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
@@ -0,0 +1,381 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Conditional compilation with the `@cfg(...)` attribute
+//!
+//! An item with `@cfg(condition)` in front of it is only part of the program if the condition
+//! holds for the [`Config`] of the invocation. Conditions are:
+//!
+//! * `backend = "interpreter"`, `backend = "wasm"` or `backend = "native"`
+//! * `feature = "name"`, for a feature of the `[features]` table of the manifest
+//! * `debug` or `release`
+//! * `not(condition)`, `all(condition, ...)` and `any(condition, ...)`
+//!
+//! The attributes are evaluated after lexing and before parsing, the tokens of the items that are
+//! left out never reach the parser. So the same name can be declared once for every backend, like
+//! a native binding of the standard library and the fallback of the interpreter.
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use vunk_lexer::Spanned;
+use vunk_lexer::Token;
+
+use crate::error::DriverError;
+use crate::outline::outline;
+use crate::package::Graph;
+use crate::source;
+use crate::source::Source;
+
+/// What the program is compiled for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Backend {
+    #[default]
+    Interpreter,
+    Wasm,
+    Native,
+}
+
+impl Backend {
+    pub const ALL: &'static [Backend] = &[Backend::Interpreter, Backend::Wasm, Backend::Native];
+
+    /// The name of the backend in `@cfg(backend = "...")`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Interpreter => "interpreter",
+            Backend::Wasm => "wasm",
+            Backend::Native => "native",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Backend> {
+        Backend::ALL
+            .iter()
+            .copied()
+            .find(|backend| backend.name() == name)
+    }
+}
+
+/// What the conditions of `@cfg` attributes are evaluated against
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub backend: Backend,
+    pub release: bool,
+
+    /// The features requested for the root package, like with `--features`
+    pub features: BTreeSet<String>,
+
+    /// Whether the `default` features are enabled, which they are unless `--no-default-features`
+    pub default_features: bool,
+}
+
+// Deriving would disable the default features
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            backend: Backend::default(),
+            release: false,
+            features: BTreeSet::new(),
+            default_features: true,
+        }
+    }
+}
+
+/// The features of a package
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Features {
+    /// The features of the `[features]` table of the manifest
+    pub declared: BTreeSet<String>,
+
+    pub enabled: BTreeSet<String>,
+}
+
+impl Config {
+    /// The features of the package with the index `package` in `graph`
+    ///
+    /// The requested features are those of the root package, dependencies only get their default
+    /// features. Enabling a feature enables the features it lists too.
+    pub fn features(&self, graph: &Graph, package: usize) -> Result<Features, DriverError> {
+        let is_root = package == graph.packages.len() - 1;
+        let package = &graph.packages[package];
+
+        let mut pending = Vec::new();
+        if self.default_features || !is_root {
+            pending.extend(
+                package
+                    .features
+                    .get("default")
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            );
+        }
+        if is_root {
+            for feature in self.features.iter() {
+                if !package.features.contains_key(feature) {
+                    return Err(DriverError::UnknownFeature {
+                        feature: feature.clone(),
+                        package: package.name.clone(),
+                    });
+                }
+                pending.push(feature.clone());
+            }
+        }
+
+        let mut enabled = BTreeSet::new();
+        while let Some(feature) = pending.pop() {
+            if let Some(implied) = package.features.get(&feature) {
+                pending.extend(implied.iter().cloned());
+            }
+            enabled.insert(feature);
+        }
+
+        Ok(Features {
+            declared: package.features.keys().cloned().collect(),
+            enabled,
+        })
+    }
+}
+
+/// The condition of a `@cfg` attribute
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    Backend(Backend),
+    Feature(String),
+    Debug,
+    Release,
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    /// Parse the tokens of `(condition)`, with spans in bytes, checking features against
+    /// `declared` if the features of the package are known
+    pub fn parse(
+        tokens: &[Spanned<Token>],
+        declared: Option<&BTreeSet<String>>,
+    ) -> Result<Condition, (String, Range<usize>)> {
+        let mut index = 0;
+        let mut conditions = arguments(tokens, &mut index, declared)?;
+        if let Some((_, span)) = tokens.get(index) {
+            return Err((
+                "expected the end of the attribute".to_string(),
+                span.clone(),
+            ));
+        }
+        match conditions.len() {
+            1 => Ok(conditions.remove(0)),
+            _ => Err(("expected one condition".to_string(), span(tokens))),
+        }
+    }
+
+    pub fn holds(&self, config: &Config, features: Option<&Features>) -> bool {
+        match self {
+            Condition::Backend(backend) => config.backend == *backend,
+            Condition::Feature(feature) => features
+                .map(|features| features.enabled.contains(feature))
+                .unwrap_or(false),
+            Condition::Debug => !config.release,
+            Condition::Release => config.release,
+            Condition::Not(condition) => !condition.holds(config, features),
+            Condition::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.holds(config, features)),
+            Condition::Any(conditions) => conditions
+                .iter()
+                .any(|condition| condition.holds(config, features)),
+        }
+    }
+}
+
+/// Byte ranges of the items of `source` whose `@cfg` conditions do not hold
+///
+/// Files that are not modules of a package have no features, so `feature = "..."` never holds in
+/// them.
+pub fn disabled(
+    source: &Source,
+    config: &Config,
+    features: Option<&Features>,
+) -> Result<Vec<Range<usize>>, DriverError> {
+    let code = source.code.as_str();
+    let declared = features.map(|features| &features.declared);
+
+    let mut disabled = Vec::new();
+    for item in outline(code) {
+        let start = item.span.start;
+        let tokens = source::tokens(&code[start..item.name_span.start])
+            .into_iter()
+            .map(|(token, span)| (token, start + span.start..start + span.end))
+            .collect::<Vec<_>>();
+
+        for index in 0..tokens.len() {
+            let (attribute, cfg) = match &tokens[index..] {
+                [(Token::Ctrl('@'), _), (Token::Ident(name), span), rest @ ..] if name == "cfg" => {
+                    (rest, span)
+                }
+                _ => continue,
+            };
+            let attribute = &attribute[..closing(attribute)];
+            let condition = match attribute {
+                [] => Err(("expected '('".to_string(), cfg.clone())),
+                _ => Condition::parse(attribute, declared),
+            };
+            let condition = condition.map_err(|(message, span)| DriverError::InvalidCfg {
+                message,
+                file: source.file(),
+                span: (span.start, span.len()).into(),
+            })?;
+            if !condition.holds(config, features) {
+                disabled.push(item.span.clone());
+                break;
+            }
+        }
+    }
+    Ok(disabled)
+}
+
+/// The tokens of `code`, as the lexer returns them, without those in the byte ranges of `disabled`
+pub fn strip(
+    code: &str,
+    tokens: Vec<Spanned<Token>>,
+    disabled: &[Range<usize>],
+) -> Vec<Spanned<Token>> {
+    if disabled.is_empty() {
+        return tokens;
+    }
+
+    // The spans of the lexer are in chars
+    let chars = |offset: usize| code[..offset].chars().count();
+    let disabled = disabled
+        .iter()
+        .map(|range| chars(range.start)..chars(range.end))
+        .collect::<Vec<_>>();
+    tokens
+        .into_iter()
+        .filter(|(_, span)| !disabled.iter().any(|range| range.contains(&span.start)))
+        .collect()
+}
+
+// `(condition, ...)`, starting at `index`
+fn arguments(
+    tokens: &[Spanned<Token>],
+    index: &mut usize,
+    declared: Option<&BTreeSet<String>>,
+) -> Result<Vec<Condition>, (String, Range<usize>)> {
+    expect(tokens, index, Token::ParOpen)?;
+    let mut conditions = Vec::new();
+    while !matches!(tokens.get(*index), Some((Token::ParClose, _))) {
+        conditions.push(condition(tokens, index, declared)?);
+        match tokens.get(*index) {
+            Some((Token::Comma, _)) => *index += 1,
+            _ => break,
+        }
+    }
+    expect(tokens, index, Token::ParClose)?;
+    Ok(conditions)
+}
+
+fn condition(
+    tokens: &[Spanned<Token>],
+    index: &mut usize,
+    declared: Option<&BTreeSet<String>>,
+) -> Result<Condition, (String, Range<usize>)> {
+    let (name, name_span) = match tokens.get(*index) {
+        Some((Token::Ident(name), span)) => (name.as_str(), span.clone()),
+        Some((token, span)) => {
+            return Err((
+                format!("expected a condition, found '{}'", token),
+                span.clone(),
+            ))
+        }
+        None => return Err(("expected a condition".to_string(), end(tokens))),
+    };
+    *index += 1;
+
+    match name {
+        "debug" => Ok(Condition::Debug),
+        "release" => Ok(Condition::Release),
+        "backend" | "feature" => {
+            expect(tokens, index, Token::Assign)?;
+            let (value, span) = match tokens.get(*index) {
+                Some((Token::Str(value), span)) => (value.clone(), span.clone()),
+                Some((_, span)) => return Err(("expected a string".to_string(), span.clone())),
+                None => return Err(("expected a string".to_string(), end(tokens))),
+            };
+            *index += 1;
+
+            if name == "backend" {
+                let message = || (format!("{} is not a backend", value), span.clone());
+                return Backend::from_name(&value)
+                    .map(Condition::Backend)
+                    .ok_or_else(message);
+            }
+            if declared
+                .map(|declared| !declared.contains(&value))
+                .unwrap_or(false)
+            {
+                return Err((format!("{} is not a feature", value), span));
+            }
+            Ok(Condition::Feature(value))
+        }
+        "not" | "all" | "any" => {
+            let start = name_span.start;
+            let mut conditions = arguments(tokens, index, declared)?;
+            match name {
+                "not" if conditions.len() == 1 => {
+                    Ok(Condition::Not(Box::new(conditions.remove(0))))
+                }
+                "not" => {
+                    let span = start..tokens[*index - 1].1.end;
+                    Err(("not takes exactly one condition".to_string(), span))
+                }
+                "all" => Ok(Condition::All(conditions)),
+                _ => Ok(Condition::Any(conditions)),
+            }
+        }
+        _ => Err((format!("{} is not a condition", name), name_span)),
+    }
+}
+
+fn expect(
+    tokens: &[Spanned<Token>],
+    index: &mut usize,
+    expected: Token,
+) -> Result<(), (String, Range<usize>)> {
+    match tokens.get(*index) {
+        Some((token, _)) if *token == expected => {
+            *index += 1;
+            Ok(())
+        }
+        Some((_, span)) => Err((format!("expected '{}'", expected), span.clone())),
+        None => Err((format!("expected '{}'", expected), end(tokens))),
+    }
+}
+
+// The number of tokens up to and including the `)` closing the first `(`
+fn closing(tokens: &[Spanned<Token>]) -> usize {
+    let mut depth = 0;
+    for (index, (token, _)) in tokens.iter().enumerate() {
+        match token {
+            Token::ParOpen => depth += 1,
+            Token::ParClose if depth <= 1 => return index + 1,
+            Token::ParClose => depth -= 1,
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+fn span(tokens: &[Spanned<Token>]) -> Range<usize> {
+    match (tokens.first(), tokens.last()) {
+        (Some((_, first)), Some((_, last))) => first.start..last.end,
+        _ => 0..0,
+    }
+}
+
+fn end(tokens: &[Spanned<Token>]) -> Range<usize> {
+    let end = span(tokens).end;
+    end..end
+}
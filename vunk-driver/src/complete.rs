@@ -0,0 +1,177 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Completion of the identifier at a position
+//!
+//! After a module path like `Std.IO.`, the members of the module are suggested. Otherwise, the
+//! suggestions are ranked by scope: names bound in the enclosing item (arguments, `let` and `do`
+//! bindings) come first, then top level items of the file, then the prelude, unless the file opts
+//! out of it, and modules of the standard library, and finally keywords.
+//!
+//! Record fields and ranking by type are not supported yet, as they need types.
+
+use std::collections::BTreeSet;
+
+use chumsky::Parser;
+use vunk_lexer::Token;
+use vunk_runtime::builtin::Builtins;
+
+use crate::outline::outline;
+use crate::outline::ItemKind;
+use crate::package::scope::uses_prelude;
+
+const KEYWORDS: &[&str] = &[
+    "do", "else", "enum", "false", "if", "in", "lazy", "let", "match", "mod", "pub", "true",
+    "type", "use", "when", "where",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+
+    /// The fully qualified name, for builtins
+    pub detail: Option<String>,
+}
+
+/// Kinds of completions, in the order they are ranked
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompletionKind {
+    Local,
+    Definition,
+    Type,
+    Builtin,
+    Module,
+    Keyword,
+}
+
+/// The byte offset where the completed identifier starts, and the completions for it
+pub fn complete(code: &str, offset: usize, builtins: &Builtins) -> (usize, Vec<Completion>) {
+    let path_start = code[..offset]
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$' || c == '.'))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let path = &code[path_start..offset];
+
+    let (completions, partial) = match path.rsplit_once('.') {
+        Some((module, partial)) => (members(module, builtins), partial),
+        None => (in_scope(code, offset, builtins), path),
+    };
+
+    let mut completions = completions
+        .into_iter()
+        .filter(|completion| completion.label.starts_with(partial))
+        .collect::<Vec<_>>();
+    completions.sort_by(|a, b| (a.kind, &a.label).cmp(&(b.kind, &b.label)));
+
+    // Keep the best ranked completion for every name
+    let mut seen = BTreeSet::new();
+    completions.retain(|completion| seen.insert(completion.label.clone()));
+
+    (offset - partial.len(), completions)
+}
+
+// The next path segments of all builtins in the module
+fn members(module: &str, builtins: &Builtins) -> Vec<Completion> {
+    let prefix = format!("{}.", module);
+    builtins
+        .iter()
+        .filter_map(|builtin| {
+            let rest = builtin.name.strip_prefix(&prefix)?;
+            Some(match rest.split_once('.') {
+                Some((submodule, _)) => Completion {
+                    label: submodule.to_string(),
+                    kind: CompletionKind::Module,
+                    detail: None,
+                },
+                None => Completion {
+                    label: rest.to_string(),
+                    kind: CompletionKind::Builtin,
+                    detail: Some(builtin.name.clone()),
+                },
+            })
+        })
+        .collect()
+}
+
+fn in_scope(code: &str, offset: usize, builtins: &Builtins) -> Vec<Completion> {
+    let items = outline(code);
+    let mut completions = Vec::new();
+
+    if let Some(item) = items
+        .iter()
+        .find(|item| item.span.start <= offset && offset <= item.span.end)
+    {
+        completions.extend(
+            local_names(&code[item.span.start..offset])
+                .into_iter()
+                .map(|name| Completion {
+                    label: name,
+                    kind: CompletionKind::Local,
+                    detail: None,
+                }),
+        );
+    }
+
+    completions.extend(items.iter().filter_map(|item| {
+        let kind = match item.kind {
+            ItemKind::Declaration | ItemKind::Definition => CompletionKind::Definition,
+            ItemKind::Type => CompletionKind::Type,
+            ItemKind::Other => return None,
+        };
+        Some(Completion {
+            label: item.name.clone(),
+            kind,
+            detail: None,
+        })
+    }));
+
+    if uses_prelude(code) {
+        completions.extend(builtins.prelude().map(|(name, qualified)| Completion {
+            label: name.to_string(),
+            kind: CompletionKind::Builtin,
+            detail: Some(qualified.to_string()),
+        }));
+    }
+
+    let modules = builtins
+        .iter()
+        .filter_map(|builtin| builtin.name.split_once('.').map(|(module, _)| module))
+        .collect::<BTreeSet<_>>();
+    completions.extend(modules.into_iter().map(|module| Completion {
+        label: module.to_string(),
+        kind: CompletionKind::Module,
+        detail: None,
+    }));
+
+    completions.extend(KEYWORDS.iter().map(|keyword| Completion {
+        label: keyword.to_string(),
+        kind: CompletionKind::Keyword,
+        detail: None,
+    }));
+
+    completions
+}
+
+// Names bound before the cursor: `name: Type` arguments, `name = ...` and `name <- ...`
+fn local_names(code: &str) -> Vec<String> {
+    let (tokens, _) = vunk_lexer::lexer().parse_recovery(code);
+    let tokens = tokens
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(token, _)| token)
+        .filter(|token| !matches!(token, Token::Comment(_) | Token::Pub))
+        .collect::<Vec<_>>();
+
+    tokens
+        .windows(2)
+        .skip(1) // The name of the item itself
+        .filter_map(|window| match (&window[0], &window[1]) {
+            (Token::Ident(name), Token::Declare | Token::Assign | Token::Bind) => {
+                Some(name.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
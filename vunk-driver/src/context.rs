@@ -0,0 +1,86 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::Context;
+use vunk_runtime::profile::Profile;
+use vunk_runtime::sandbox::Sandbox;
+use vunk_runtime::value::Value;
+
+use crate::coverage::Coverage;
+use crate::trace;
+use crate::trace::Trace;
+
+/// Settings a program is run with
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    pub sandbox: Sandbox,
+
+    /// The arguments passed to the program
+    pub args: Vec<String>,
+
+    /// The file to write a trace of the function calls of the program to, see [`crate::trace`]
+    pub trace_eval: Option<PathBuf>,
+
+    /// Where the executed code is recorded, see [`crate::coverage`]
+    pub coverage: Option<Arc<Coverage>>,
+
+    /// Where the time and allocations of every call are measured, see [`Profile`]
+    pub profile: Option<Arc<Profile>>,
+}
+
+/// The context programs run by the driver are evaluated in
+pub struct DriverContext {
+    options: RunOptions,
+    trace: Option<Trace>,
+}
+
+impl DriverContext {
+    pub fn new(options: RunOptions) -> Self {
+        DriverContext {
+            options,
+            trace: None,
+        }
+    }
+
+    /// Write every call made through the context to `trace`
+    pub fn traced(self, trace: Trace) -> Self {
+        DriverContext {
+            trace: Some(trace),
+            ..self
+        }
+    }
+}
+
+impl Context for DriverContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let profile = self.options.profile.clone();
+        if let Some(profile) = profile.as_ref() {
+            profile.enter(&trace::name(function));
+        }
+        if let Some(trace) = self.trace.as_mut() {
+            trace.enter(function, &args);
+        }
+        let result = apply(self, function, args);
+        if let Some(trace) = self.trace.as_mut() {
+            trace.exit(function, &result);
+        }
+        if let Some(profile) = profile {
+            profile.exit();
+        }
+        result
+    }
+
+    fn sandbox(&self) -> &Sandbox {
+        &self.options.sandbox
+    }
+
+    fn program_args(&self) -> &[String] {
+        &self.options.args
+    }
+}
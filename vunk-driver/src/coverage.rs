@@ -0,0 +1,251 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Which code of the files under test was executed, for `vunk test --coverage`
+//!
+//! The evaluator records every span it executes in the [`Coverage`] of the
+//! [`RunOptions`](crate::context::RunOptions) the tests run with. Afterwards, the executed spans
+//! are matched against the files:
+//!
+//! - a line is covered if an executed span overlaps it, and lines that only hold comments or
+//!   attributes do not count
+//! - a region is a definition, which is covered if any code in it was executed
+//!
+//! Definitions marked with `@test` are left out, as running the tests covers them anyway. The
+//! result is shown as a table with [`Report`]'s `Display`, or exported with [`Report::lcov`] for
+//! tools like genhtml.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use vunk_lexer::Token;
+
+use crate::outline::outline;
+use crate::outline::ItemKind;
+use crate::source::tokens;
+use crate::source::Source;
+
+// How often the spans of a file were executed, by their start and end
+type Counts = BTreeMap<(usize, usize), u64>;
+
+/// How often the spans of every file were executed, which tests running in parallel record
+/// concurrently
+#[derive(Debug, Default)]
+pub struct Coverage {
+    spans: Mutex<BTreeMap<String, Counts>>,
+}
+
+impl Coverage {
+    /// Record that the byte range `span` of the file `name` was executed
+    pub fn record(&self, name: &str, span: Range<usize>) {
+        if let Ok(mut spans) = self.spans.lock() {
+            let file = spans.entry(name.to_string()).or_default();
+            *file.entry((span.start, span.end)).or_insert(0) += 1;
+        }
+    }
+
+    /// How often the code in `span` of the file `name` was executed, counting the span executed
+    /// most often
+    pub fn hits(&self, name: &str, span: Range<usize>) -> u64 {
+        let spans = match self.spans.lock() {
+            Ok(spans) => spans,
+            Err(_) => return 0,
+        };
+        let file = match spans.get(name) {
+            Some(file) => file,
+            None => return 0,
+        };
+        file.range(..(span.end, 0))
+            .filter(|((_, end), _)| *end > span.start)
+            .map(|(_, hits)| *hits)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A definition, and how often it was executed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub name: String,
+
+    /// The line of the name, counted from 1
+    pub line: usize,
+    pub hits: u64,
+}
+
+/// The coverage of a file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileReport {
+    pub name: String,
+
+    /// The lines with code, counted from 1, and how often they were executed
+    pub lines: Vec<(usize, u64)>,
+    pub regions: Vec<Region>,
+}
+
+impl FileReport {
+    pub fn new(source: &Source, coverage: &Coverage) -> Self {
+        let code = source.code.as_str();
+        let tokens = tokens(code);
+        let mut lines = BTreeMap::new();
+        let mut regions = Vec::new();
+
+        for item in outline(code) {
+            if item.kind != ItemKind::Definition || item.attributes(code).contains(&"test") {
+                continue;
+            }
+
+            let code_tokens = tokens.iter().filter(|(token, span)| {
+                span.start >= item.name_span.start
+                    && span.end <= item.span.end
+                    && !matches!(token, Token::Comment(_))
+            });
+            for (_, span) in code_tokens {
+                let line = line(code, span.start);
+                let hits = coverage.hits(&source.name, line_span(code, span.start));
+                lines.insert(line, hits);
+            }
+
+            regions.push(Region {
+                name: item.name,
+                line: line(code, item.name_span.start),
+                hits: coverage.hits(&source.name, item.name_span.start..item.span.end),
+            });
+        }
+
+        FileReport {
+            name: source.name.clone(),
+            lines: lines.into_iter().collect(),
+            regions,
+        }
+    }
+
+    pub fn covered_lines(&self) -> usize {
+        self.lines.iter().filter(|(_, hits)| *hits > 0).count()
+    }
+
+    pub fn covered_regions(&self) -> usize {
+        self.regions.iter().filter(|region| region.hits > 0).count()
+    }
+}
+
+/// The coverage of the files under test
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub files: Vec<FileReport>,
+}
+
+impl Report {
+    pub fn new(sources: &[Source], coverage: &Coverage) -> Self {
+        Report {
+            files: sources
+                .iter()
+                .map(|source| FileReport::new(source, coverage))
+                .collect(),
+        }
+    }
+
+    /// The report in the LCOV tracefile format, with a record per file
+    pub fn lcov(&self) -> String {
+        let mut out = String::new();
+        for file in self.files.iter() {
+            let _ = writeln!(out, "TN:");
+            let _ = writeln!(out, "SF:{}", file.name);
+            for region in file.regions.iter() {
+                let _ = writeln!(out, "FN:{},{}", region.line, region.name);
+            }
+            for region in file.regions.iter() {
+                let _ = writeln!(out, "FNDA:{},{}", region.hits, region.name);
+            }
+            let _ = writeln!(out, "FNF:{}", file.regions.len());
+            let _ = writeln!(out, "FNH:{}", file.covered_regions());
+            for (line, hits) in file.lines.iter() {
+                let _ = writeln!(out, "DA:{},{}", line, hits);
+            }
+            let _ = writeln!(out, "LF:{}", file.lines.len());
+            let _ = writeln!(out, "LH:{}", file.covered_lines());
+            let _ = writeln!(out, "end_of_record");
+        }
+        out
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let width = self
+            .files
+            .iter()
+            .map(|file| file.name.len())
+            .chain(std::iter::once("Total".len()))
+            .max()
+            .unwrap_or(0);
+
+        row(f, width, "File", "Lines", "Regions")?;
+        let (mut lines, mut covered_lines, mut regions, mut covered_regions) = (0, 0, 0, 0);
+        for file in self.files.iter() {
+            row(
+                f,
+                width,
+                &file.name,
+                &ratio(file.covered_lines(), file.lines.len()),
+                &ratio(file.covered_regions(), file.regions.len()),
+            )?;
+            lines += file.lines.len();
+            covered_lines += file.covered_lines();
+            regions += file.regions.len();
+            covered_regions += file.covered_regions();
+        }
+        row(
+            f,
+            width,
+            "Total",
+            &ratio(covered_lines, lines),
+            &ratio(covered_regions, regions),
+        )
+    }
+}
+
+fn row(
+    f: &mut std::fmt::Formatter,
+    width: usize,
+    name: &str,
+    lines: &str,
+    regions: &str,
+) -> std::fmt::Result {
+    writeln!(
+        f,
+        "{:<width$} {:>16} {:>16}",
+        name,
+        lines,
+        regions,
+        width = width
+    )
+}
+
+// Like `3/4 (75.0%)`, where nothing to cover is fully covered
+fn ratio(covered: usize, total: usize) -> String {
+    let percent = if total == 0 {
+        100.0
+    } else {
+        100.0 * covered as f64 / total as f64
+    };
+    format!("{}/{} ({:.1}%)", covered, total, percent)
+}
+
+// The line of a byte offset, counted from 1
+fn line(code: &str, offset: usize) -> usize {
+    code[..offset].matches('\n').count() + 1
+}
+
+// The byte range of the line a byte offset is on, without its line break
+fn line_span(code: &str, offset: usize) -> Range<usize> {
+    let start = code[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = code[offset..]
+        .find('\n')
+        .map(|i| offset + i)
+        .unwrap_or(code.len());
+    start..end
+}
@@ -0,0 +1,793 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Incremental computation of the stages of the pipeline
+//!
+//! Every stage is a query on a file, like "the tokens of `main.vunk`". Its result is memoized
+//! together with the inputs and queries it read. Setting an input to a new value starts a new
+//! revision. Afterwards, a memoized result is reused if nothing it read changed, and a result that
+//! is computed again but equal to the previous one does not invalidate the queries that read it.
+//!
+//! This is how salsa works, only without the macros.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rayon::iter::IntoParallelIterator;
+use rayon::iter::ParallelIterator;
+use semver::Version;
+use vunk_lexer::Spanned;
+use vunk_lexer::Token;
+use vunk_parser::ast::program::Program;
+
+use crate::attribute;
+use crate::cache;
+use crate::cache::Cache;
+use crate::cache::Statistics;
+use crate::cfg;
+use crate::cfg::Config;
+use crate::error::DriverError;
+use crate::error::SharedError;
+use crate::package;
+use crate::package::Graph;
+use crate::package::Resolved;
+use crate::source::Source;
+use crate::timing::Timings;
+
+type Revision = u64;
+
+/// The stage of the cache typechecked files are recorded in
+const TYPECHECK: &str = "typecheck";
+
+type Output<T> = Result<Arc<T>, SharedError>;
+
+/// An input or a query, with the name of the file it is about
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Query {
+    Source(String),
+    Graph,
+    Config,
+    Lex(String),
+    Enabled(String),
+    Parse(String),
+    Resolve(String),
+    Typecheck(String),
+}
+
+/// The tokens of a file the parser reads, and where its lines start
+#[derive(Debug, PartialEq, Eq)]
+pub struct Enabled {
+    pub tokens: Vec<Spanned<Token>>,
+
+    /// See [`vunk_parser::parse::line_starts`]
+    pub line_starts: Vec<usize>,
+}
+
+impl Enabled {
+    fn new(source: &Source, tokens: Vec<Spanned<Token>>) -> Enabled {
+        Enabled {
+            line_starts: vunk_parser::parse::line_starts(&source.code, &tokens),
+            tokens,
+        }
+    }
+}
+
+struct Input<T> {
+    value: Option<Arc<T>>,
+    changed_at: Revision,
+}
+
+// Deriving would require `T: Default`
+impl<T> Default for Input<T> {
+    fn default() -> Self {
+        Input {
+            value: None,
+            changed_at: 0,
+        }
+    }
+}
+
+struct Memo<T> {
+    output: Output<T>,
+
+    /// The last revision the output changed in
+    changed_at: Revision,
+
+    /// The last revision the output was known to be up to date in
+    verified_at: Revision,
+
+    /// Everything that was read to compute the output
+    dependencies: Vec<Query>,
+}
+
+#[derive(Default)]
+pub struct Database {
+    revision: Revision,
+    sources: HashMap<String, Input<Source>>,
+    graph: Input<Graph>,
+    config: Input<Config>,
+
+    lexed: HashMap<String, Memo<Vec<Spanned<Token>>>>,
+    enabled: HashMap<String, Memo<Enabled>>,
+    parsed: HashMap<String, Memo<Program>>,
+    resolved: HashMap<String, Memo<Vec<Resolved>>>,
+    typechecked: HashMap<String, Memo<()>>,
+
+    /// What the queries that are being computed read so far, the innermost one last
+    stack: Vec<Vec<Query>>,
+
+    computed: usize,
+
+    /// Results kept on disk, for the queries that are computed again in every invocation
+    cache: Option<Cache>,
+
+    timings: Option<Timings>,
+}
+
+impl Database {
+    pub fn with_cache(cache: Cache) -> Database {
+        Database {
+            cache: Some(cache),
+            ..Database::default()
+        }
+    }
+
+    /// Set the code of a file, which is only a change if the code differs
+    pub fn set_source(&mut self, source: Source) {
+        let current = self
+            .sources
+            .get(&source.name)
+            .and_then(|input| input.value.as_ref());
+        if current
+            .map(|current| current.code == source.code)
+            .unwrap_or(false)
+        {
+            return;
+        }
+
+        self.revision += 1;
+        self.sources.insert(
+            source.name.clone(),
+            Input {
+                value: Some(Arc::new(source)),
+                changed_at: self.revision,
+            },
+        );
+    }
+
+    pub fn remove_source(&mut self, name: &str) {
+        if let Some(input) = self.sources.get_mut(name) {
+            self.revision += 1;
+            input.value = None;
+            input.changed_at = self.revision;
+        }
+    }
+
+    /// Set the package graph, which is used to resolve the `use`s of its modules
+    pub fn set_graph(&mut self, graph: Graph) {
+        // Checksums change with every edit of a module, the sources track those edits already
+        let current = self.graph.value.as_deref().map(structure);
+        if current == Some(structure(&graph)) {
+            return;
+        }
+
+        self.revision += 1;
+        self.graph = Input {
+            value: Some(Arc::new(graph)),
+            changed_at: self.revision,
+        };
+    }
+
+    /// Set what the `@cfg` attributes are evaluated against, see [`crate::cfg`]
+    pub fn set_config(&mut self, config: Config) {
+        if self.config.value.as_deref() == Some(&config) {
+            return;
+        }
+
+        self.revision += 1;
+        self.config = Input {
+            value: Some(Arc::new(config)),
+            changed_at: self.revision,
+        };
+    }
+
+    pub fn source(&mut self, name: &str) -> Option<Arc<Source>> {
+        self.record(Query::Source(name.to_string()));
+        self.sources.get(name).and_then(|input| input.value.clone())
+    }
+
+    pub fn graph(&mut self) -> Option<Arc<Graph>> {
+        self.record(Query::Graph);
+        self.graph.value.clone()
+    }
+
+    /// The config set with [`Database::set_config`], or the default one
+    pub fn config(&mut self) -> Arc<Config> {
+        self.record(Query::Config);
+        self.config.value.clone().unwrap_or_default()
+    }
+
+    pub fn lex(&mut self, name: &str) -> Output<Vec<Spanned<Token>>> {
+        self.memoized(name, Query::Lex, |db| &mut db.lexed, lex, |a, b| a == b)
+    }
+
+    /// The tokens of a file, without those of the items whose `@cfg` conditions do not hold
+    ///
+    /// This and the lines they are on is all the parser reads, so edits that change neither, like
+    /// adding a blank line at the end, do not parse the file again.
+    pub fn enabled(&mut self, name: &str) -> Output<Enabled> {
+        self.memoized(
+            name,
+            Query::Enabled,
+            |db| &mut db.enabled,
+            enabled,
+            |a, b| a == b,
+        )
+    }
+
+    /// The desugared program of a file, without the items whose `@cfg` conditions do not hold
+    pub fn parse(&mut self, name: &str) -> Output<Program> {
+        // Programs cannot be compared, so a new one always invalidates what read the old one
+        self.memoized(name, Query::Parse, |db| &mut db.parsed, parse, |_, _| false)
+    }
+
+    /// What the `use`s of a file refer to
+    ///
+    /// Files that are not modules of the package graph, like files checked on their own, only
+    /// refer to the standard library.
+    pub fn resolve(&mut self, name: &str) -> Output<Vec<Resolved>> {
+        self.memoized(
+            name,
+            Query::Resolve,
+            |db| &mut db.resolved,
+            resolve,
+            |a, b| a == b,
+        )
+    }
+
+    /// Typecheck a file against the declarations of the modules it uses
+    pub fn typecheck(&mut self, name: &str) -> Result<(), SharedError> {
+        self.memoized(
+            name,
+            Query::Typecheck,
+            |db| &mut db.typechecked,
+            typecheck,
+            |a, b| a == b,
+        )
+        .map(drop)
+    }
+
+    /// How many times queries were computed instead of reusing their memoized result
+    pub fn computed(&self) -> usize {
+        self.computed
+    }
+
+    pub fn cache_statistics(&self) -> Option<Statistics> {
+        self.cache.as_ref().map(Cache::statistics)
+    }
+
+    /// Record how long the phases take from now on, see [`Timings`]
+    pub fn enable_timings(&mut self) {
+        self.timings = Some(Timings::default());
+    }
+
+    pub fn timings(&self) -> Option<&Timings> {
+        self.timings.as_ref()
+    }
+
+    /// Run `f` as a run of `phase` on `module`, timing it if timings are enabled
+    pub fn time<T>(&self, phase: &'static str, module: Option<&str>, f: impl FnOnce() -> T) -> T {
+        time(self.timings.as_ref(), phase, module, f)
+    }
+
+    /// Typecheck the files, computing what is not up to date in parallel
+    ///
+    /// The results are in the order of the names, so that diagnostics are always reported in the
+    /// same order.
+    pub fn typecheck_all(&mut self, names: &[String]) -> Vec<Result<(), SharedError>> {
+        self.parse_all(names);
+
+        // Everything a file is typechecked against is read sequentially, as that goes through the
+        // database, and only the typechecking itself runs in parallel
+        let mut pending = Vec::new();
+        for name in names {
+            if self.is_fresh(name, |db| &mut db.typechecked) {
+                continue;
+            }
+            self.stack.push(Vec::new());
+            let prepared = prepare_typecheck(self, name);
+            let dependencies = self.stack.pop().unwrap_or_default();
+            pending.push((name, prepared, dependencies));
+        }
+
+        let timings = self.timings.as_ref();
+        let checked = pending
+            .into_par_iter()
+            .map(|(name, prepared, dependencies)| {
+                let checked = prepared.and_then(|prepared| {
+                    time(timings, "typecheck", Some(name.as_str()), || {
+                        check_prepared(prepared)
+                    })
+                });
+                (name, checked, dependencies)
+            })
+            .collect::<Vec<_>>();
+
+        for (name, checked, dependencies) in checked {
+            let output = checked.map(|key| {
+                if let Some(key) = key {
+                    self.store(TYPECHECK, &key, &());
+                }
+            });
+            let output = output.map(Arc::new).map_err(SharedError::from);
+            self.insert(
+                name,
+                Query::Typecheck,
+                |db| &mut db.typechecked,
+                output,
+                dependencies,
+                |a, b| a == b,
+            );
+        }
+
+        names.iter().map(|name| self.typecheck(name)).collect()
+    }
+
+    // Lex and parse the files that are not up to date in parallel
+    fn parse_all(&mut self, names: &[String]) {
+        let mut pending = Vec::new();
+        for name in names {
+            if !self.is_fresh(name, |db| &mut db.parsed) {
+                // Files that are not loaded fail when they are queried
+                if let Some(source) = self.source(name) {
+                    pending.push((source, disabled(self, name)));
+                }
+            }
+        }
+
+        let timings = self.timings.as_ref();
+        let parsed = pending
+            .into_par_iter()
+            .map(|(source, disabled)| {
+                let name = Some(source.name.as_str());
+                let tokens = time(timings, "lex", name, || source.lex());
+                let enabled = match (&tokens, disabled) {
+                    (Ok(tokens), Ok(disabled)) => {
+                        let tokens = cfg::strip(&source.code, tokens.clone(), &disabled);
+                        Some(Ok(Enabled::new(&source, tokens)))
+                    }
+                    (Ok(_), Err(error)) => Some(Err(error)),
+                    (Err(_), _) => None,
+                };
+                let program = match &enabled {
+                    Some(Ok(enabled)) => {
+                        let program = time(timings, "parse", name, || {
+                            vunk_parser::parse::parse(enabled.tokens.clone(), &enabled.line_starts)
+                                .map_err(|errors| source.parse_error(&errors))
+                        });
+                        Some(program.and_then(|program| {
+                            time(timings, "desugar", name, || crate::desugar(program))
+                        }))
+                    }
+                    _ => None,
+                };
+                (source, tokens, enabled, program)
+            })
+            .collect::<Vec<_>>();
+
+        for (source, tokens, enabled, program) in parsed {
+            let name = source.name.as_str();
+            let tokens = tokens.map(Arc::new).map_err(SharedError::from);
+            let enabled = match (&tokens, enabled) {
+                (_, Some(enabled)) => enabled.map(Arc::new).map_err(SharedError::from),
+                (Err(error), None) => Err(error.clone()),
+                (Ok(_), None) => continue,
+            };
+            let program = match (&enabled, program) {
+                (_, Some(program)) => program.map(Arc::new).map_err(SharedError::from),
+                (Err(error), None) => Err(error.clone()),
+                (Ok(_), None) => continue,
+            };
+
+            // The same as the queries read
+            let read = vec![Query::Source(name.to_string())];
+            self.insert(
+                name,
+                Query::Lex,
+                |db| &mut db.lexed,
+                tokens,
+                read,
+                |a, b| a == b,
+            );
+            let read = vec![
+                Query::Lex(name.to_string()),
+                Query::Source(name.to_string()),
+                Query::Config,
+                Query::Graph,
+            ];
+            self.insert(
+                name,
+                Query::Enabled,
+                |db| &mut db.enabled,
+                enabled,
+                read,
+                |a, b| a == b,
+            );
+            let mut read = vec![Query::Enabled(name.to_string())];
+            if let Err(error) = &program {
+                if matches!(error.error(), DriverError::Parse { .. }) {
+                    read.push(Query::Source(name.to_string()));
+                }
+            }
+            self.insert(
+                name,
+                Query::Parse,
+                |db| &mut db.parsed,
+                program,
+                read,
+                |_, _| false,
+            );
+        }
+    }
+
+    fn memoized<T>(
+        &mut self,
+        name: &str,
+        query: fn(String) -> Query,
+        memos: fn(&mut Database) -> &mut HashMap<String, Memo<T>>,
+        compute: fn(&mut Database, &str) -> Result<T, DriverError>,
+        same: fn(&T, &T) -> bool,
+    ) -> Output<T> {
+        self.record(query(name.to_string()));
+        if self.is_fresh(name, memos) {
+            if let Some(memo) = memos(self).get(name) {
+                return memo.output.clone();
+            }
+        }
+
+        self.stack.push(Vec::new());
+        let output = compute(self, name).map(Arc::new).map_err(SharedError::from);
+        let dependencies = self.stack.pop().unwrap_or_default();
+        self.insert(name, query, memos, output.clone(), dependencies, same);
+        output
+    }
+
+    // Whether the memo of a query is up to date, marking it as verified if it is
+    fn is_fresh<T>(
+        &mut self,
+        name: &str,
+        memos: fn(&mut Database) -> &mut HashMap<String, Memo<T>>,
+    ) -> bool {
+        let memo = memos(self)
+            .get(name)
+            .map(|memo| (memo.verified_at, memo.dependencies.clone()));
+        let (verified_at, dependencies) = match memo {
+            Some(memo) => memo,
+            None => return false,
+        };
+        if verified_at != self.revision && !self.unchanged(&dependencies, verified_at) {
+            return false;
+        }
+
+        let revision = self.revision;
+        if let Some(memo) = memos(self).get_mut(name) {
+            memo.verified_at = revision;
+        }
+        true
+    }
+
+    // Memoize the newly computed output of a query
+    fn insert<T>(
+        &mut self,
+        name: &str,
+        query: fn(String) -> Query,
+        memos: fn(&mut Database) -> &mut HashMap<String, Memo<T>>,
+        output: Output<T>,
+        dependencies: Vec<Query>,
+        same: fn(&T, &T) -> bool,
+    ) {
+        tracing::debug!("Computed {:?}", query(name.to_string()));
+        self.computed += 1;
+
+        let revision = self.revision;
+        let memos = memos(self);
+        let changed_at = match (memos.get(name), &output) {
+            (Some(memo), Ok(new)) => match &memo.output {
+                Ok(old) if same(old, new) => memo.changed_at,
+                _ => revision,
+            },
+            _ => revision,
+        };
+        memos.insert(
+            name.to_string(),
+            Memo {
+                output,
+                changed_at,
+                verified_at: revision,
+                dependencies,
+            },
+        );
+    }
+
+    // Whether nothing of what a memo read changed after `revision`
+    fn unchanged(&mut self, dependencies: &[Query], revision: Revision) -> bool {
+        // Bringing the dependencies up to date is not a read of the query being computed
+        self.stack.push(Vec::new());
+        let unchanged = dependencies
+            .iter()
+            .all(|dependency| self.changed_at(dependency) <= revision);
+        self.stack.pop();
+        unchanged
+    }
+
+    // The last revision a query changed in, after bringing it up to date
+    fn changed_at(&mut self, query: &Query) -> Revision {
+        let changed_at = match query {
+            Query::Source(name) => self.sources.get(name).map(|input| input.changed_at),
+            Query::Graph => Some(self.graph.changed_at),
+            Query::Config => Some(self.config.changed_at),
+            Query::Lex(name) => {
+                let _ = self.lex(name);
+                self.lexed.get(name).map(|memo| memo.changed_at)
+            }
+            Query::Enabled(name) => {
+                let _ = self.enabled(name);
+                self.enabled.get(name).map(|memo| memo.changed_at)
+            }
+            Query::Parse(name) => {
+                let _ = self.parse(name);
+                self.parsed.get(name).map(|memo| memo.changed_at)
+            }
+            Query::Resolve(name) => {
+                let _ = self.resolve(name);
+                self.resolved.get(name).map(|memo| memo.changed_at)
+            }
+            Query::Typecheck(name) => {
+                let _ = self.typecheck(name);
+                self.typechecked.get(name).map(|memo| memo.changed_at)
+            }
+        };
+        changed_at.unwrap_or_default()
+    }
+
+    fn record(&mut self, query: Query) {
+        if let Some(reads) = self.stack.last_mut() {
+            reads.push(query);
+        }
+    }
+
+    fn cached<T: serde::de::DeserializeOwned>(&mut self, stage: &str, key: &str) -> Option<T> {
+        self.cache.as_mut()?.get(stage, key)
+    }
+
+    fn store<T: serde::Serialize>(&mut self, stage: &str, key: &str, value: &T) {
+        if let Some(cache) = self.cache.as_mut() {
+            cache.put(stage, key, value);
+        }
+    }
+
+    fn input(&mut self, name: &str) -> Result<Arc<Source>, DriverError> {
+        self.source(name)
+            .ok_or_else(|| DriverError::UnknownSource(name.to_string()))
+    }
+}
+
+type Structure<'a> = (
+    &'a str,
+    &'a Version,
+    &'a Path,
+    &'a BTreeMap<String, PathBuf>,
+    &'a BTreeMap<String, usize>,
+    &'a BTreeMap<String, Vec<String>>,
+);
+
+// Everything of a package graph but the checksums
+fn structure(graph: &Graph) -> Vec<Structure<'_>> {
+    graph
+        .packages
+        .iter()
+        .map(|package| {
+            (
+                package.name.as_str(),
+                &package.version,
+                package.root.as_path(),
+                &package.modules,
+                &package.dependencies,
+                &package.features,
+            )
+        })
+        .collect()
+}
+
+// The package a file is a module of
+fn package_of(graph: &Graph, name: &str) -> Option<usize> {
+    graph.packages.iter().position(|package| {
+        package
+            .modules
+            .values()
+            .any(|file| file.display().to_string() == name)
+    })
+}
+
+// Runs `f` as a run of `phase`, for the queries that are computed in parallel and cannot borrow
+// the database
+fn time<T>(
+    timings: Option<&Timings>,
+    phase: &'static str,
+    module: Option<&str>,
+    f: impl FnOnce() -> T,
+) -> T {
+    match timings {
+        Some(timings) => timings.time(phase, module, f),
+        None => f(),
+    }
+}
+
+fn lex(db: &mut Database, name: &str) -> Result<Vec<Spanned<Token>>, DriverError> {
+    let source = db.input(name)?;
+    let tokens = db.time("lex", Some(name), || source.lex())?;
+    tracing::debug!(tokens = tokens.len(), "Lexed {}", name);
+    Ok(tokens)
+}
+
+fn enabled(db: &mut Database, name: &str) -> Result<Enabled, DriverError> {
+    let tokens = db.lex(name)?;
+    let source = db.input(name)?;
+    let tokens = cfg::strip(&source.code, tokens.as_ref().clone(), &disabled(db, name)?);
+    Ok(Enabled::new(&source, tokens))
+}
+
+fn parse(db: &mut Database, name: &str) -> Result<Program, DriverError> {
+    let enabled = db.enabled(name)?;
+    let program = db.time("parse", Some(name), || {
+        vunk_parser::parse::parse(enabled.tokens.clone(), &enabled.line_starts)
+    });
+    // Only the errors point into the code, so a file that parses does not depend on it
+    let program = match program {
+        Ok(program) => program,
+        Err(errors) => return Err(db.input(name)?.parse_error(&errors)),
+    };
+    db.time("desugar", Some(name), || crate::desugar(program))
+}
+
+// The byte ranges of the items of a file whose `@cfg` conditions do not hold, once its attributes
+// are known to be valid
+fn disabled(db: &mut Database, name: &str) -> Result<Vec<Range<usize>>, DriverError> {
+    let source = db.input(name)?;
+    attribute::check(&source)?;
+    let config = db.config();
+    let features = match db.graph() {
+        Some(graph) => match package_of(&graph, name) {
+            Some(package) => Some(config.features(&graph, package)?),
+            None => None,
+        },
+        None => None,
+    };
+    cfg::disabled(&source, &config, features.as_ref())
+}
+
+fn resolve(db: &mut Database, name: &str) -> Result<Vec<Resolved>, DriverError> {
+    db.lex(name)?;
+    let source = db.input(name)?;
+    let graph = match db.graph() {
+        Some(graph) => graph,
+        None => return Ok(Vec::new()),
+    };
+    let package = match package_of(&graph, name) {
+        Some(package) => package,
+        None => return Ok(Vec::new()),
+    };
+
+    let uses = package::uses(&source.code);
+    let resolved = db.time("resolve", Some(name), || {
+        uses.iter()
+            .map(|import| {
+                graph
+                    .resolve_import(package, import)
+                    .ok_or_else(|| crate::unresolved_use(&graph, package, import, &source))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    // Whether the used items exist depends on the code of the modules defining them
+    for (import, resolved) in uses.iter().zip(resolved.iter()) {
+        if let Resolved::Module {
+            package,
+            module,
+            item: Some(_),
+        } = resolved
+        {
+            let file = graph.packages[*package].modules[module]
+                .display()
+                .to_string();
+            let used = db.input(&file)?;
+            if let Some(error) = crate::unresolved_item(import, &used, &source) {
+                return Err(error);
+            }
+        }
+    }
+
+    let exports = crate::glob_exports(&graph, package, &source, |file| {
+        let used = db.source(&file.display().to_string())?;
+        Some(package::exports(&used.code))
+    });
+    match crate::ambiguous_name(&source, &exports) {
+        Some(error) => Err(error),
+        None => Ok(resolved),
+    }
+}
+
+// A file that is ready to be typechecked
+enum Prepared {
+    /// It typechecked before, with the same code and the same used modules
+    Cached,
+
+    Program {
+        program: Arc<Program>,
+
+        /// The key of the cache entry to write if it typechecks
+        key: String,
+    },
+}
+
+fn typecheck(db: &mut Database, name: &str) -> Result<(), DriverError> {
+    let prepared = prepare_typecheck(db, name)?;
+    let key = db.time("typecheck", Some(name), || check_prepared(prepared))?;
+    if let Some(key) = key {
+        db.store(TYPECHECK, &key, &());
+    }
+    Ok(())
+}
+
+fn prepare_typecheck(db: &mut Database, name: &str) -> Result<Prepared, DriverError> {
+    let resolved = db.resolve(name)?;
+
+    // The declarations of the used modules are part of the environment
+    let graph = db.graph();
+    let used = resolved
+        .iter()
+        .filter_map(|resolved| match (resolved, graph.as_ref()) {
+            (
+                Resolved::Module {
+                    package, module, ..
+                },
+                Some(graph),
+            ) => graph.packages[*package].modules.get(module),
+            _ => None,
+        })
+        .map(|file| file.display().to_string())
+        .collect::<BTreeSet<_>>();
+
+    let mut sources = vec![db.input(name)?];
+    for used in used.iter() {
+        sources.push(db.input(used)?);
+    }
+    let codes = sources
+        .iter()
+        .map(|source| source.code.as_str())
+        .collect::<Vec<_>>();
+    let key = cache::key(&codes);
+    if db.cached::<()>(TYPECHECK, &key).is_some() {
+        return Ok(Prepared::Cached);
+    }
+
+    let program = db.parse(name)?;
+    for used in used.iter() {
+        db.parse(used)?;
+    }
+    Ok(Prepared::Program { program, key })
+}
+
+// Typecheck a prepared file, returning the cache entry to write
+fn check_prepared(prepared: Prepared) -> Result<Option<String>, DriverError> {
+    match prepared {
+        Prepared::Cached => Ok(None),
+        Prepared::Program { program, key } => crate::typecheck(&program).map(|()| Some(key)),
+    }
+}
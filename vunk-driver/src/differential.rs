@@ -0,0 +1,160 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Differential testing of the backends that evaluate integers
+//!
+//! The runtime computes with `Int`s, and with `BigInt`s once a result does not fit. Both must give
+//! the same results and errors, so expressions are evaluated twice: with the operands of integer
+//! operations as `Int`s, and widened to `BigInt`s, where a result that does not fit into an `Int`
+//! is the overflow the `Int` evaluation fails with.
+//!
+//! Only literals, `if`s and the operators of [`vunk_runtime::arith`] and [`vunk_runtime::cmp`] are
+//! evaluated, other expressions are not checked. Generated expressions are checked with
+//! [`check_generated`], see [`vunk_parser::generate::constant`].
+
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use num_bigint::BigInt;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::ifelse::IfElse;
+use vunk_parser::ast::literal::IntegerValue;
+use vunk_parser::ast::literal::Literal;
+use vunk_parser::ast::op::BinaryOp;
+use vunk_runtime::arith;
+use vunk_runtime::builtin::bool_arg;
+use vunk_runtime::cmp::compare;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::value::Value;
+
+use crate::error::DriverError;
+
+/// How integer operations are evaluated
+#[derive(Clone, Copy, Debug)]
+pub enum Backend {
+    Int,
+    BigInt,
+}
+
+/// Check that both backends give the same for the expression
+pub fn check(expr: &Expr) -> Result<(), DriverError> {
+    let int = evaluate(expr, Backend::Int).map(describe);
+    let big_int = evaluate(expr, Backend::BigInt).map(describe);
+    let (int, big_int) = match (int, big_int) {
+        (Some(int), Some(big_int)) => (int, big_int),
+        _ => return Ok(()),
+    };
+    if int == big_int {
+        return Ok(());
+    }
+
+    Err(DriverError::Differential {
+        expr: vunk_parser::print::expr(expr),
+        int,
+        big_int,
+    })
+}
+
+/// Check the expressions generated from `seeds`, returning the seeds that fail and why
+pub fn check_generated(seeds: Range<u64>, depth: usize) -> Vec<(u64, DriverError)> {
+    seeds
+        .filter_map(|seed| {
+            let expr = vunk_parser::generate::constant(seed, depth);
+            check(&expr).err().map(|error| (seed, error))
+        })
+        .collect()
+}
+
+/// The value of the expression, or `None` if it is not evaluated by the backends
+pub fn evaluate(expr: &Expr, backend: Backend) -> Option<Result<Value, RuntimeError>> {
+    Some(match expr {
+        Expr::Literal(Literal::Bool(b)) => Ok(Value::Bool(b.value)),
+        Expr::Literal(Literal::Float(f)) => Ok(Value::Float(f.value)),
+        Expr::Literal(Literal::Integer(integer)) => Ok(integer_value(&integer.value)),
+        Expr::Binary(op, lhs, rhs) => {
+            let (name, operator) = operator(op)?;
+            let (lhs, rhs) = (evaluate(lhs, backend)?, evaluate(rhs, backend)?);
+            match lhs.and_then(|lhs| apply(backend, operator, &lhs, &rhs?)) {
+                // Where an `Int` would not hold the result, the `Int` backend fails
+                Ok(Value::BigInt(_)) if matches!(backend, Backend::BigInt) => {
+                    Err(RuntimeError::IntegerOverflow { op: name })
+                }
+                result => result,
+            }
+        }
+        Expr::IfElse(IfElse {
+            condition,
+            tru,
+            fals,
+        }) => match evaluate(condition, backend)?.and_then(|c| bool_arg("if", &c)) {
+            Ok(true) => evaluate(tru, backend)?,
+            Ok(false) => evaluate(fals, backend)?,
+            Err(error) => Err(error),
+        },
+        _ => return None,
+    })
+}
+
+fn integer_value(value: &IntegerValue) -> Value {
+    match value {
+        IntegerValue::I8(i) => Value::Integer(i64::from(*i)),
+        IntegerValue::I16(i) => Value::Integer(i64::from(*i)),
+        IntegerValue::I32(i) => Value::Integer(i64::from(*i)),
+        IntegerValue::I64(i) => Value::Integer(*i),
+        IntegerValue::U8(u) => Value::Integer(i64::from(*u)),
+        IntegerValue::U16(u) => Value::Integer(i64::from(*u)),
+        IntegerValue::U32(u) => Value::Integer(i64::from(*u)),
+        IntegerValue::U64(u) => Value::big_int(BigInt::from(*u)),
+        IntegerValue::Big(big) => Value::big_int(big.clone()),
+    }
+}
+
+type Operator = fn(&Value, &Value) -> Result<Value, RuntimeError>;
+
+fn operator(op: &BinaryOp) -> Option<(&'static str, Operator)> {
+    Some(match op {
+        BinaryOp::Add => ("+", arith::add),
+        BinaryOp::Sub => ("-", arith::sub),
+        BinaryOp::Mul => ("*", arith::mul),
+        BinaryOp::Div => ("/", arith::div),
+        BinaryOp::Rem => ("%", arith::rem),
+        BinaryOp::Eq => ("==", |lhs, rhs| ordered(lhs, rhs, Ordering::is_eq)),
+        BinaryOp::NotEq => ("!=", |lhs, rhs| ordered(lhs, rhs, Ordering::is_ne)),
+        BinaryOp::Less => ("<", |lhs, rhs| ordered(lhs, rhs, Ordering::is_lt)),
+        BinaryOp::LessEq => ("<=", |lhs, rhs| ordered(lhs, rhs, Ordering::is_le)),
+        BinaryOp::More => (">", |lhs, rhs| ordered(lhs, rhs, Ordering::is_gt)),
+        BinaryOp::MoreEq => (">=", |lhs, rhs| ordered(lhs, rhs, Ordering::is_ge)),
+        _ => return None,
+    })
+}
+
+// The operator applied by the backend, which widens two `Int`s to `BigInt`s if it computes with
+// those. Other operands are left alone, so that errors name the same types.
+fn apply(
+    backend: Backend,
+    operator: Operator,
+    lhs: &Value,
+    rhs: &Value,
+) -> Result<Value, RuntimeError> {
+    match (backend, lhs, rhs) {
+        (Backend::BigInt, Value::Integer(a), Value::Integer(b)) => operator(
+            &Value::big_int(BigInt::from(*a)),
+            &Value::big_int(BigInt::from(*b)),
+        ),
+        _ => operator(lhs, rhs),
+    }
+}
+
+fn ordered(lhs: &Value, rhs: &Value, is: fn(Ordering) -> bool) -> Result<Value, RuntimeError> {
+    compare(lhs, rhs).map(|ordering| Value::Bool(is(ordering)))
+}
+
+// Displaying tells an `Int` from a `Float`, but not from a `BigInt` of the same value, which the
+// runtime treats alike
+fn describe(result: Result<Value, RuntimeError>) -> String {
+    match result {
+        Ok(value) => value.to_string(),
+        Err(error) => format!("the error {:?}", error.to_string()),
+    }
+}
@@ -0,0 +1,182 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Static HTML pages for the documentation, one per module and an index
+
+use std::collections::BTreeMap;
+
+use vunk_lexer::Token;
+
+use crate::doc::DocKind;
+use crate::doc::Module;
+use crate::source::tokens;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; }
+pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }
+.item { margin-top: 2em; }
+.kind { color: #888; font-size: 0.8em; }
+";
+
+/// The page listing all modules, with links to their pages
+pub fn index(modules: &[Module]) -> String {
+    let mut body = String::from("<h1>Modules</h1>\n<ul>\n");
+    for module in modules {
+        body.push_str(&format!(
+            "<li><a href=\"{}\">{}</a></li>\n",
+            page_name(&module.name),
+            escape(&module.name)
+        ));
+    }
+    body.push_str("</ul>\n");
+    page("Modules", &body)
+}
+
+/// The page of a module, `modules` are used to link to types
+pub fn module(module: &Module, modules: &[Module]) -> String {
+    let types = modules
+        .iter()
+        .flat_map(|module| {
+            module
+                .items
+                .iter()
+                .filter(|item| item.kind == DocKind::Type)
+                .map(move |item| (item.name.as_str(), module.name.as_str()))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let mut body = format!(
+        "<p><a href=\"index.html\">Modules</a></p>\n<h1>{}</h1>\n",
+        escape(&module.name)
+    );
+    for item in module.items.iter() {
+        let kind = match item.kind {
+            DocKind::Function => "function",
+            DocKind::Type => "type",
+        };
+        body.push_str(&format!(
+            "<div class=\"item\" id=\"{name}\">\n<h2><a href=\"#{name}\">{name}</a> \
+             <span class=\"kind\">{kind}</span></h2>\n",
+            name = escape(&item.name),
+            kind = kind,
+        ));
+        if let Some(signature) = item.signature.as_deref() {
+            body.push_str(&format!(
+                "<pre class=\"signature\"><code>{}</code></pre>\n",
+                link_types(signature, &types)
+            ));
+        }
+        body.push_str(&markdown(&item.doc));
+        body.push_str("</div>\n");
+    }
+
+    page(&module.name, &body)
+}
+
+/// The file name of the page of a module
+pub fn page_name(module: &str) -> String {
+    format!("{}.html", module)
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+// The code, with the names of documented types linking to their documentation
+fn link_types(code: &str, types: &BTreeMap<&str, &str>) -> String {
+    let mut html = String::new();
+    let mut end_of_previous = 0;
+    for (token, span) in tokens(code) {
+        let module = match &token {
+            Token::Ident(name) => types.get(name.as_str()),
+            _ => None,
+        };
+
+        if let Some(module) = module {
+            html.push_str(&escape(&code[end_of_previous..span.start]));
+            html.push_str(&format!(
+                "<a href=\"{}#{}\">{}</a>",
+                page_name(module),
+                escape(&code[span.clone()]),
+                escape(&code[span.clone()])
+            ));
+            end_of_previous = span.end;
+        }
+    }
+    html.push_str(&escape(&code[end_of_previous..]));
+    html
+}
+
+// A small subset of markdown: paragraphs, fenced code blocks and inline code
+fn markdown(text: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    for line in text.lines() {
+        let fence = line.trim_start().starts_with("```");
+        match code.as_mut() {
+            Some(lines) if fence => {
+                html.push_str(&format!(
+                    "<pre class=\"example\"><code>{}</code></pre>\n",
+                    escape(&lines.join("\n"))
+                ));
+                code = None;
+            }
+            Some(lines) => lines.push(line),
+            None if fence => {
+                html.push_str(&paragraph_html(&mut paragraph));
+                code = Some(Vec::new());
+            }
+            None if line.trim().is_empty() => html.push_str(&paragraph_html(&mut paragraph)),
+            None => paragraph.push(line.trim()),
+        }
+    }
+
+    // An unterminated code block ends with the comment
+    if let Some(lines) = code {
+        html.push_str(&format!(
+            "<pre class=\"example\"><code>{}</code></pre>\n",
+            escape(&lines.join("\n"))
+        ));
+    }
+    html.push_str(&paragraph_html(&mut paragraph));
+    html
+}
+
+fn paragraph_html(lines: &mut Vec<&str>) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let text = escape(&lines.join(" "));
+    lines.clear();
+
+    // Backticks alternate between starting and ending inline code
+    let html = text
+        .split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                format!("<code>{}</code>", part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<String>();
+    format!("<p>{}</p>\n", html)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
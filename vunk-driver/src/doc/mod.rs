@@ -0,0 +1,212 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Documentation of the items of a set of files
+//!
+//! Every file is a module, named after the file. The documentation of an item is the comment
+//! directly in front of it, its signature is its declaration or, for types, the whole type
+//! definition. Fenced code blocks in the documentation are examples, which are also run as tests,
+//! see [`doctests`].
+
+use std::ops::Range;
+use std::path::Path;
+
+use serde::Serialize;
+use vunk_lexer::Token;
+
+use crate::outline::outline;
+use crate::outline::Item;
+use crate::outline::ItemKind;
+use crate::source::tokens;
+use crate::source::Source;
+
+pub mod html;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Module {
+    pub name: String,
+    pub items: Vec<DocItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DocItem {
+    pub name: String,
+    pub kind: DocKind,
+    pub public: bool,
+
+    /// The declared type of a function or value, or the definition of a type
+    pub signature: Option<String>,
+
+    /// The doc comment, without the leading `#`
+    pub doc: String,
+
+    /// The code of the fenced code blocks of the doc comment
+    pub examples: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocKind {
+    Function,
+    Type,
+}
+
+/// An example of a doc comment, run as a test
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Doctest {
+    /// Name of the documented item
+    pub item: String,
+    pub code: String,
+
+    /// Byte range of the doc comment the example is part of
+    pub span: Range<usize>,
+}
+
+pub fn collect(sources: &[Source]) -> Vec<Module> {
+    sources.iter().map(module).collect()
+}
+
+fn module(source: &Source) -> Module {
+    let code = source.code.as_str();
+    let items = outline(code);
+
+    let documented = items.iter().filter(|item| match item.kind {
+        ItemKind::Definition | ItemKind::Type => true,
+        // Declarations without definitions, like the functions of a trait, are documented too
+        ItemKind::Declaration => !items
+            .iter()
+            .any(|other| other.name == item.name && other.kind == ItemKind::Definition),
+        ItemKind::Other => false,
+    });
+
+    let items = documented
+        .map(|item| {
+            let declaration = items
+                .iter()
+                .find(|other| other.name == item.name && other.kind == ItemKind::Declaration);
+
+            // The doc comment can be in front of the declaration or the definition
+            let doc = declaration
+                .map(|declaration| doc_comment(code, declaration))
+                .filter(|doc| !doc.is_empty())
+                .unwrap_or_else(|| doc_comment(code, item));
+
+            let signature = match item.kind {
+                ItemKind::Type => Some(without_comments(code, item).to_string()),
+                _ => declaration
+                    .and_then(|declaration| declaration.declared_type(code))
+                    .map(str::to_string),
+            };
+
+            DocItem {
+                name: item.name.clone(),
+                kind: match item.kind {
+                    ItemKind::Type => DocKind::Type,
+                    _ => DocKind::Function,
+                },
+                public: declaration
+                    .into_iter()
+                    .chain(Some(item))
+                    .any(|item| is_public(code, item)),
+                signature,
+                examples: examples(&doc),
+                doc,
+            }
+        })
+        .collect();
+
+    Module {
+        name: module_name(&source.name),
+        items,
+    }
+}
+
+/// The module name of a file, which is the name of the file without extension
+pub fn module_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// The comment lines in front of an item, without the `#`
+pub fn doc_comment(code: &str, item: &Item) -> String {
+    code[item.span.clone()]
+        .lines()
+        .map_while(|line| line.strip_prefix('#'))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The examples of all doc comments of the code, in order
+pub fn doctests(code: &str) -> Vec<Doctest> {
+    outline(code)
+        .iter()
+        .flat_map(|item| {
+            let span = doc_comment_span(code, item);
+            examples(&doc_comment(code, item))
+                .into_iter()
+                .map(move |example| Doctest {
+                    item: item.name.clone(),
+                    code: example,
+                    span: span.clone(),
+                })
+        })
+        .collect()
+}
+
+/// The code of a doctest: the module, followed by the example
+pub fn doctest_program(code: &str, example: &str) -> String {
+    format!("{}\n{}\n", code.trim_end(), example)
+}
+
+/// The contents of the fenced code blocks of a doc comment
+pub fn examples(doc: &str) -> Vec<String> {
+    let mut examples = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in doc.lines() {
+        match current.as_mut() {
+            None if line.trim_start().starts_with("```") => current = Some(Vec::new()),
+            None => {}
+            Some(example) if line.trim_start().starts_with("```") => {
+                examples.push(example.join("\n"));
+                current = None;
+            }
+            Some(example) => example.push(line),
+        }
+    }
+    examples
+}
+
+// Byte range of the comment lines in front of an item
+fn doc_comment_span(code: &str, item: &Item) -> Range<usize> {
+    let mut offset = item.span.start;
+    let mut end = offset;
+    for line in code[item.span.clone()].split_inclusive('\n') {
+        if !line.starts_with('#') {
+            break;
+        }
+        end = offset + line.trim_end().len();
+        offset += line.len();
+    }
+    item.span.start..end
+}
+
+// The code of an item after its doc comment
+fn without_comments<'a>(code: &'a str, item: &Item) -> &'a str {
+    code[line_start(code, item.name_span.start)..item.span.end].trim_end()
+}
+
+fn line_start(code: &str, offset: usize) -> usize {
+    code[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+fn is_public(code: &str, item: &Item) -> bool {
+    let start = line_start(code, item.name_span.start);
+    matches!(
+        tokens(&code[start..item.name_span.start]).first(),
+        Some((Token::Pub, _))
+    )
+}
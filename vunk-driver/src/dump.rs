@@ -0,0 +1,109 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The intermediate representations of a file, and the grammar, as `vunk dump` prints them
+
+use std::fmt::Write;
+
+use vunk_diagnostics::line_column;
+use vunk_lexer::Spanned;
+use vunk_lexer::Token;
+use vunk_parser::ast::program::Program;
+use vunk_parser::grammar::Node;
+
+/// A stage of the pipeline, named after what it produces
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    Tokens,
+
+    /// The program as it was parsed
+    Ast,
+
+    /// The program after desugaring and constant folding
+    Core,
+
+    /// The types of the definitions
+    Types,
+}
+
+/// The tokens, one per line, with the lines and columns they start and end at
+pub fn tokens(code: &str, tokens: &[Spanned<Token>]) -> String {
+    let mut out = String::new();
+    for (token, span) in tokens {
+        let (line, column) = line_column(code, span.start);
+        let (end_line, end_column) = line_column(code, span.end);
+        let location = format!("{}:{}-{}:{}", line, column, end_line, end_column);
+        let _ = writeln!(out, "{:<12} {:?}", location, token);
+    }
+    out
+}
+
+/// The tokens as a JSON array of objects with their kind, the code they were lexed from and their
+/// byte span
+pub fn tokens_json(code: &str, tokens: &[Spanned<Token>]) -> String {
+    let tokens = tokens
+        .iter()
+        .map(|(token, span)| {
+            let (line, column) = line_column(code, span.start);
+            serde_json::json!({
+                "kind": kind(token),
+                "text": code.get(span.clone()).unwrap_or_default(),
+                "byte_start": span.start,
+                "byte_end": span.end,
+                "line": line,
+                "column": column,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::Value::from(tokens).to_string()
+}
+
+/// A program, pretty printed with `Debug`
+///
+/// The AST cannot be serialized, so its JSON form is an object with the same text as `debug`.
+pub fn program(program: &Program, json: bool) -> String {
+    let debug = format!("{:#?}", program);
+    if json {
+        serde_json::json!({ "debug": debug }).to_string()
+    } else {
+        debug
+    }
+}
+
+/// The grammar as EBNF, or as JSON for drawing railroad diagrams, see [`vunk_parser::grammar`]
+///
+/// In JSON, the rules are an array of objects with their name and their diagram. Diagrams are
+/// objects with a `type` like in the railroad-diagrams library: `terminal`, `token`,
+/// `nonterminal`, `sequence`, `choice`, `optional` or `zero_or_more`.
+pub fn grammar(json: bool) -> String {
+    let rules = vunk_parser::grammar::grammar();
+    if !json {
+        return vunk_parser::grammar::ebnf(&rules);
+    }
+
+    let rules = rules
+        .iter()
+        .map(|rule| serde_json::json!({ "name": rule.name, "diagram": diagram(&rule.node) }))
+        .collect::<Vec<_>>();
+    serde_json::json!({ "start": vunk_parser::grammar::START, "rules": rules }).to_string()
+}
+
+fn diagram(node: &Node) -> serde_json::Value {
+    let items = |nodes: &[Node]| nodes.iter().map(diagram).collect::<Vec<_>>();
+    match node {
+        Node::Literal(text) => serde_json::json!({ "type": "terminal", "text": text }),
+        Node::Token(kind) => serde_json::json!({ "type": "token", "kind": kind }),
+        Node::Rule(name) => serde_json::json!({ "type": "nonterminal", "name": name }),
+        Node::Sequence(nodes) => serde_json::json!({ "type": "sequence", "items": items(nodes) }),
+        Node::Choice(nodes) => serde_json::json!({ "type": "choice", "items": items(nodes) }),
+        Node::Optional(node) => serde_json::json!({ "type": "optional", "item": diagram(node) }),
+        Node::Repeat(node) => serde_json::json!({ "type": "zero_or_more", "item": diagram(node) }),
+    }
+}
+
+// The name of the variant of a token, like `Ident`
+fn kind(token: &Token) -> String {
+    let debug = format!("{:?}", token);
+    debug.split('(').next().unwrap_or_default().to_string()
+}
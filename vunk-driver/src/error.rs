@@ -0,0 +1,495 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use miette::Diagnostic;
+use miette::LabeledSpan;
+use miette::NamedSource;
+use miette::Severity;
+use miette::SourceCode;
+use miette::SourceSpan;
+use vunk_diagnostics::codes::Code;
+use vunk_diagnostics::Applicability;
+use vunk_diagnostics::File;
+use vunk_diagnostics::Label;
+use vunk_parser::desugar::DesugarError;
+use vunk_parser::effects::EffectError;
+use vunk_parser::kinds::KindError;
+use vunk_parser::matching::MatchError;
+use vunk_parser::traits::TraitError;
+use vunk_runtime::error::RuntimeError;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum DriverError {
+    #[error("Could not read {}", path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Could not write {}", path.display())]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid configuration in {}: {message}", path.display())]
+    Config { path: PathBuf, message: String },
+
+    #[error("Invalid manifest {}: {message}", path.display())]
+    Manifest { path: PathBuf, message: String },
+
+    #[error("No vunk.toml in {} or any of its parents", path.display())]
+    NoManifest { path: PathBuf },
+
+    #[error("{} already exists", path.display())]
+    AlreadyExists { path: PathBuf },
+
+    #[error("{0} is already a dependency")]
+    DependencyExists(String),
+
+    #[error("The dependency {name} has version {version}, which does not match {requirement}")]
+    #[diagnostic(code(E0005))]
+    UnsatisfiedVersion {
+        name: String,
+        requirement: String,
+        version: String,
+    },
+
+    #[error("The content of {name} differs from the content it was locked with")]
+    #[diagnostic(
+        code(E0006),
+        help("If the change is expected, run vunk update to update vunk.lock")
+    )]
+    Checksum { name: String },
+
+    #[error("Dependency cycle: {cycle}")]
+    #[diagnostic(code(E0004))]
+    DependencyCycle { cycle: String },
+
+    #[error("Modules use each other in a cycle: {cycle}")]
+    #[diagnostic(
+        code(E0007),
+        help("Move what the modules need from each other into a module they all use")
+    )]
+    ImportCycle {
+        cycle: String,
+        #[related]
+        uses: Vec<vunk_diagnostics::Diagnostic>,
+    },
+
+    #[error("Could not fetch {url}: {message}")]
+    Git { url: String, message: String },
+
+    #[error("Could not watch for changes: {message}")]
+    Watch { message: String },
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnresolvedUse(Box<UnresolvedUse>),
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnresolvedItem(Box<UnresolvedItem>),
+
+    #[error("{name} is ambiguous, it is brought into scope by {globs}")]
+    #[diagnostic(
+        code(E0009),
+        help("Use the item that is meant explicitly, or refer to it by its path")
+    )]
+    AmbiguousName {
+        name: String,
+
+        /// The paths of the globs, like `Std.List.* and Std.Set.*`
+        globs: String,
+
+        #[source_code]
+        file: File,
+
+        #[label("used here")]
+        span: SourceSpan,
+    },
+
+    #[error("Invalid @cfg condition: {message}")]
+    #[diagnostic(code(E0010))]
+    InvalidCfg {
+        message: String,
+
+        #[source_code]
+        file: File,
+
+        #[label("{message}")]
+        span: SourceSpan,
+    },
+
+    #[error("Invalid attribute: {message}")]
+    #[diagnostic(code(E0011))]
+    InvalidAttribute {
+        message: String,
+
+        #[source_code]
+        file: File,
+
+        #[label("{message}")]
+        span: SourceSpan,
+
+        #[help]
+        help: Option<String>,
+    },
+
+    #[error("{feature} is not a feature of {package}")]
+    UnknownFeature { feature: String, package: String },
+
+    #[error("{} is not formatted", path.display())]
+    #[diagnostic(help("Run vunk fmt to format it"))]
+    NotFormatted { path: PathBuf },
+
+    #[error("Could not lex {name}")]
+    Lex {
+        name: String,
+        #[related]
+        errors: Vec<vunk_diagnostics::Diagnostic>,
+    },
+
+    #[error("Could not parse {name}")]
+    Parse {
+        name: String,
+        #[related]
+        errors: Vec<vunk_diagnostics::Diagnostic>,
+    },
+
+    #[error("Lints set to deny found {} problems", .diagnostics.len())]
+    Lints {
+        #[related]
+        diagnostics: Vec<vunk_diagnostics::Diagnostic>,
+    },
+
+    #[error("{} of {} examples failed", .failures.len(), .total)]
+    Doctests {
+        total: usize,
+        #[related]
+        failures: Vec<DoctestFailure>,
+    },
+
+    #[error("{} of {} tests failed", .failures.len(), .total)]
+    Tests {
+        total: usize,
+        #[related]
+        failures: Vec<TestFailure>,
+    },
+
+    #[error(transparent)]
+    #[diagnostic(code(E0003))]
+    Desugar(#[from] DesugarError),
+
+    #[error(transparent)]
+    #[diagnostic(code(E0012))]
+    Match(#[from] MatchError),
+
+    #[error(transparent)]
+    #[diagnostic(code(E0013))]
+    Trait(#[from] TraitError),
+
+    #[error(transparent)]
+    #[diagnostic(code(E0014))]
+    Kind(#[from] KindError),
+
+    #[error(transparent)]
+    #[diagnostic(code(E0015))]
+    Effect(#[from] EffectError),
+
+    #[error(transparent)]
+    Runtime(#[from] RuntimeError),
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Shared(#[from] SharedError),
+
+    #[error("{0} is not loaded")]
+    UnknownSource(String),
+
+    #[error("Unknown command ':{0}'")]
+    #[diagnostic(help("Available commands are :t, :i, :inspect, :load and :reset"))]
+    UnknownCommand(String),
+
+    #[error("{0} is not defined")]
+    UnknownName(String),
+
+    #[error("There is no name at this position")]
+    NoName,
+
+    #[error("{0} is not a valid name")]
+    InvalidName(String),
+
+    #[error("Cannot rename to {name}, {reason}")]
+    RenameConflict { name: String, reason: String },
+
+    /// Parsing printed code did not give back the AST it was printed from
+    #[error("Parsing the printed program does not give it back:\n{code}")]
+    RoundTrip { code: String },
+
+    /// The backends that evaluate integers gave different results for an expression
+    #[error("Evaluating {expr} with Ints gives {int}, but with BigInts gives {big_int}")]
+    Differential {
+        expr: String,
+        int: String,
+        big_int: String,
+    },
+
+    /// A stage of the pipeline that does not exist yet
+    #[error("{stage} is not implemented yet")]
+    NotImplemented { stage: &'static str },
+}
+
+impl DriverError {
+    /// The error as diagnostics of the shared format, one per problem
+    pub fn diagnostics(&self) -> Vec<vunk_diagnostics::Diagnostic> {
+        match self {
+            DriverError::Lex { errors, .. } | DriverError::Parse { errors, .. } => errors.clone(),
+            DriverError::Lints { diagnostics } => diagnostics.clone(),
+            DriverError::ImportCycle { uses, .. } => {
+                let mut cycle =
+                    vunk_diagnostics::Diagnostic::error(self.to_string()).with_code(Code::E0007);
+                if let Some(help) = Diagnostic::help(self) {
+                    cycle = cycle.with_note(help.to_string());
+                }
+                std::iter::once(cycle).chain(uses.iter().cloned()).collect()
+            }
+            DriverError::UnresolvedUse(unresolved) => {
+                let span = unresolved.span;
+                let span = span.offset()..span.offset() + span.len();
+                let message = format!("not a module of {} or its dependencies", unresolved.package);
+                let mut diagnostic = vunk_diagnostics::Diagnostic::error(self.to_string())
+                    .with_code(Code::E0002)
+                    .with_file(unresolved.file.clone())
+                    .with_label(Label::primary(span.clone(), message));
+                if let Some((path, applicability)) = &unresolved.suggestion {
+                    diagnostic = diagnostic.with_applicable_suggestion(
+                        "a module with a similar name exists",
+                        span,
+                        path,
+                        *applicability,
+                    );
+                }
+                vec![diagnostic]
+            }
+            DriverError::UnresolvedItem(unresolved) => {
+                let span = unresolved.span;
+                let span = span.offset()..span.offset() + span.len();
+                let message = format!("not declared or defined in {}", unresolved.module);
+                let mut diagnostic = vunk_diagnostics::Diagnostic::error(self.to_string())
+                    .with_code(Code::E0008)
+                    .with_file(unresolved.file.clone())
+                    .with_label(Label::primary(span.clone(), message));
+                if let Some((item, applicability)) = &unresolved.suggestion {
+                    diagnostic = diagnostic.with_applicable_suggestion(
+                        "an item with a similar name exists",
+                        span,
+                        item,
+                        *applicability,
+                    );
+                }
+                vec![diagnostic]
+            }
+            DriverError::AmbiguousName { file, span, .. } => {
+                let span = span.offset()..span.offset() + span.len();
+                let mut diagnostic = vunk_diagnostics::Diagnostic::error(self.to_string())
+                    .with_code(Code::E0009)
+                    .with_file(file.clone())
+                    .with_label(Label::primary(span, "used here"));
+                if let Some(help) = Diagnostic::help(self) {
+                    diagnostic = diagnostic.with_note(help.to_string());
+                }
+                vec![diagnostic]
+            }
+            DriverError::InvalidCfg {
+                message,
+                file,
+                span,
+            } => {
+                let span = span.offset()..span.offset() + span.len();
+                vec![vunk_diagnostics::Diagnostic::error(self.to_string())
+                    .with_code(Code::E0010)
+                    .with_file(file.clone())
+                    .with_label(Label::primary(span, message.clone()))]
+            }
+            DriverError::InvalidAttribute {
+                message,
+                file,
+                span,
+                help,
+            } => {
+                let span = span.offset()..span.offset() + span.len();
+                let mut diagnostic = vunk_diagnostics::Diagnostic::error(self.to_string())
+                    .with_code(Code::E0011)
+                    .with_file(file.clone())
+                    .with_label(Label::primary(span, message.clone()));
+                if let Some(help) = help {
+                    diagnostic = diagnostic.with_note(help.clone());
+                }
+                vec![diagnostic]
+            }
+            DriverError::Shared(shared) => shared.error().diagnostics(),
+            error => {
+                // The causes and the help are all that other errors have to say
+                let mut diagnostic = vunk_diagnostics::Diagnostic::error(error.to_string());
+                if let Some(code) = Diagnostic::code(error) {
+                    diagnostic = diagnostic.with_code(code.to_string());
+                }
+                let mut source = std::error::Error::source(error);
+                while let Some(cause) = source {
+                    diagnostic = diagnostic.with_note(cause.to_string());
+                    source = cause.source();
+                }
+                if let Some(help) = Diagnostic::help(error) {
+                    diagnostic = diagnostic.with_note(help.to_string());
+                }
+                for related in Diagnostic::related(error).into_iter().flatten() {
+                    diagnostic = diagnostic.with_note(related.to_string());
+                }
+                vec![diagnostic]
+            }
+        }
+    }
+}
+
+/// A `use` of a module that is neither in the package nor in its dependencies
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("{path} cannot be resolved")]
+#[diagnostic(code(E0002))]
+pub struct UnresolvedUse {
+    pub path: String,
+
+    #[source_code]
+    pub file: File,
+
+    #[label("not a module of {package} or its dependencies")]
+    pub span: SourceSpan,
+
+    pub package: String,
+
+    /// The path that was probably meant
+    pub suggestion: Option<(String, Applicability)>,
+
+    #[help]
+    pub help: Option<String>,
+}
+
+/// A `use` of an item that the module it names does not declare or define
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("{item} is not defined in {module}")]
+#[diagnostic(code(E0008))]
+pub struct UnresolvedItem {
+    pub item: String,
+    pub module: String,
+
+    #[source_code]
+    pub file: File,
+
+    #[label("not declared or defined in {module}")]
+    pub span: SourceSpan,
+
+    /// The item that was probably meant
+    pub suggestion: Option<(String, Applicability)>,
+
+    #[help]
+    pub help: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("Example of {item} failed: {reason}")]
+pub struct DoctestFailure {
+    #[source_code]
+    pub src: NamedSource,
+
+    #[label("in this doc comment")]
+    pub span: SourceSpan,
+
+    pub item: String,
+    pub reason: String,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("{name} failed: {reason}")]
+pub struct TestFailure {
+    #[source_code]
+    pub src: NamedSource,
+
+    #[label("in this test")]
+    pub span: SourceSpan,
+
+    pub name: String,
+    pub reason: String,
+}
+
+/// An error that is part of a memoized result, and thus shared by everything that reads it
+#[derive(Clone, Debug)]
+pub struct SharedError(Arc<DriverError>);
+
+impl SharedError {
+    /// The error that caused this one, which may have been shared several times
+    pub fn error(&self) -> &DriverError {
+        match self.0.as_ref() {
+            DriverError::Shared(shared) => shared.error(),
+            error => error,
+        }
+    }
+}
+
+impl From<DriverError> for SharedError {
+    fn from(error: DriverError) -> Self {
+        SharedError(Arc::new(error))
+    }
+}
+
+impl Display for SharedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for SharedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl Diagnostic for SharedError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.0.severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.0.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.0.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.0.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.0.diagnostic_source()
+    }
+}
@@ -0,0 +1,90 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Configuration of the formatter, read from a `vunkfmt.toml`
+//!
+//! ```toml
+//! max_width = 100
+//! indent = 4
+//! trailing_commas = "preserve" # or "always" or "never"
+//! blank_lines_between_items = 1 # if not set, blank lines are kept, but at most one in a row
+//! ```
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::DriverError;
+
+pub const FILE_NAME: &str = "vunkfmt.toml";
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Lines longer than this are wrapped
+    pub max_width: usize,
+
+    /// Number of spaces per level of indentation
+    pub indent: usize,
+
+    pub trailing_commas: TrailingCommas,
+
+    /// Number of blank lines between top level items
+    ///
+    /// A declaration and the definition following it are never separated.
+    pub blank_lines_between_items: Option<usize>,
+}
+
+/// What to do with commas right before a closing bracket
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingCommas {
+    Preserve,
+
+    /// Add a trailing comma if the closing bracket is on a line of its own
+    Always,
+
+    Never,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_width: 100,
+            indent: 4,
+            trailing_commas: TrailingCommas::Preserve,
+            blank_lines_between_items: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, DriverError> {
+        let content = std::fs::read_to_string(path).map_err(|source| DriverError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        toml::from_str(&content).map_err(|error| DriverError::Config {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })
+    }
+
+    /// The configuration for a file, from the closest `vunkfmt.toml` in the directory of the file
+    /// or any of its parents, or the default configuration if there is none
+    pub fn discover(file: &Path) -> Result<Config, DriverError> {
+        match find(file) {
+            Some(path) => Config::load(&path),
+            None => Ok(Config::default()),
+        }
+    }
+}
+
+fn find(file: &Path) -> Option<PathBuf> {
+    let file = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+    file.ancestors()
+        .skip(1)
+        .map(|directory| directory.join(FILE_NAME))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,515 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Formatting of source code
+//!
+//! The formatter works on the tokens and the comments between them, so it keeps the line
+//! structure of the code and all comments, and only changes whitespace:
+//!
+//! * Indentation is normalized to a fixed number of spaces per level, keeping which lines are
+//!   nested deeper than others. Lines that are not indented stay unindented, as they start top
+//!   level items.
+//! * The bindings of a `let` or `where` at the end of a line, and the `in` of a `let`, are one
+//!   level deeper than the line of the `let` or `where`. Constraints following a `where`
+//!   constraint are aligned with it.
+//! * Binary operators are surrounded by a single space, there is no space inside of parentheses
+//!   and lists and before `,`, `:`, `.` and `?`.
+//! * Lines longer than the maximum width are wrapped after `,` or `->` or before a binary operator,
+//!   preferring places in the least nested brackets.
+//! * Trailing whitespace and repeated blank lines are removed.
+//!
+//! Widths, trailing commas and blank lines between items can be configured, see [`Config`].
+//! Formatting formatted code does not change it.
+
+use std::ops::Range;
+
+use vunk_lexer::Spanned;
+use vunk_lexer::Token;
+
+use crate::error::DriverError;
+use crate::format::config::Config;
+use crate::format::config::TrailingCommas;
+use crate::outline::outline;
+use crate::outline::ItemKind;
+use crate::source::tokens;
+use crate::source::Source;
+
+pub mod config;
+
+const TAB_WIDTH: usize = 4;
+
+// How far constraints after the first one of a `where` are indented, to align with it
+const WHERE_ALIGN: usize = "where ".len();
+
+/// Format with the default configuration
+pub fn format(source: &Source) -> Result<String, DriverError> {
+    format_with(source, &Config::default())
+}
+
+pub fn format_with(source: &Source, config: &Config) -> Result<String, DriverError> {
+    source.lex()?;
+    let code = source.code.as_str();
+    let tokens = tokens(code);
+    let lines = lines(code, &tokens);
+    let blank_lines = blank_lines(code, &lines, config);
+
+    let mut formatter = Formatter {
+        config,
+        output: String::new(),
+        indents: Vec::new(),
+        lets: Vec::new(),
+        previous: None,
+    };
+    for (i, line) in lines.iter().enumerate() {
+        // Whether the next line with tokens starts with a closing bracket
+        let closed = lines[i + 1..]
+            .iter()
+            .find_map(|line| line.tokens.first())
+            .map(|first| closes(&tokens[*first].0))
+            .unwrap_or(false);
+
+        formatter.line(code, &tokens, line, blank_lines[i], closed);
+    }
+
+    Ok(formatter.output)
+}
+
+// How many blank lines there are in front of each line
+fn blank_lines(code: &str, lines: &[Line], config: &Config) -> Vec<usize> {
+    let mut blank_lines = lines
+        .iter()
+        .map(|line| usize::from(line.blank_before))
+        .collect::<Vec<_>>();
+
+    let between_items = match config.blank_lines_between_items {
+        Some(between_items) => between_items,
+        None => return blank_lines,
+    };
+
+    let items = outline(code);
+    for (previous, item) in items.iter().zip(items.iter().skip(1)) {
+        let mut start = match lines.iter().position(|line| line.start == item.span.start) {
+            Some(start) => start,
+            None => continue,
+        };
+
+        // Comments in front of an item belong to it, even if there is a blank line in between
+        while start > 0 && lines[start - 1].tokens.is_empty() && lines[start - 1].column == 0 {
+            start -= 1;
+        }
+
+        let defines_declaration = previous.kind == ItemKind::Declaration
+            && item.kind != ItemKind::Declaration
+            && item.name.split_whitespace().next() == Some(previous.name.as_str());
+        blank_lines[start] = if defines_declaration {
+            0
+        } else {
+            between_items
+        };
+    }
+
+    blank_lines
+}
+
+fn closes(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::ParClose | Token::BlockClose | Token::ListClose
+    )
+}
+
+// A line of the code, as the indices of its tokens and an optional comment at its end
+struct Line {
+    /// Byte offset of the first token or comment
+    start: usize,
+    column: usize,
+    blank_before: bool,
+    tokens: Vec<usize>,
+    comment: Option<String>,
+}
+
+fn lines(code: &str, tokens: &[Spanned<Token>]) -> Vec<Line> {
+    let mut lines = Vec::<Line>::new();
+    let mut end_of_previous = 0;
+
+    let spans = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, (_, span))| (Some(i), span.clone()))
+        .chain(std::iter::once((None, code.len()..code.len())));
+    for (i, span) in spans {
+        // Between two tokens, there is only whitespace and comments
+        let mut newlines = 0;
+        let mut offset = end_of_previous;
+        for part in code[end_of_previous..span.start].split_inclusive('\n') {
+            let comment = part.trim();
+            if !comment.is_empty() {
+                if newlines == 0 && !lines.is_empty() {
+                    lines.last_mut().unwrap().comment = Some(comment.to_string());
+                } else {
+                    let start = offset + part.len() - part.trim_start().len();
+                    lines.push(Line {
+                        start,
+                        column: column(code, start),
+                        blank_before: newlines > 1,
+                        tokens: Vec::new(),
+                        comment: Some(comment.to_string()),
+                    });
+                    newlines = 0;
+                }
+            }
+
+            offset += part.len();
+            if part.ends_with('\n') {
+                newlines += 1;
+            }
+        }
+
+        if let Some(i) = i {
+            if newlines > 0 || lines.is_empty() {
+                lines.push(Line {
+                    start: span.start,
+                    column: column(code, span.start),
+                    blank_before: newlines > 1,
+                    tokens: Vec::new(),
+                    comment: None,
+                });
+            }
+            lines.last_mut().unwrap().tokens.push(i);
+            end_of_previous = span.end;
+        }
+    }
+
+    lines
+}
+
+// The column of a byte offset, counting a tab as four columns
+fn column(code: &str, offset: usize) -> usize {
+    let line_start = code[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    code[line_start..offset]
+        .chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+struct Formatter<'a> {
+    config: &'a Config,
+    output: String,
+
+    // Columns of the lines so far, and the indentation they got
+    indents: Vec<(usize, usize)>,
+
+    // Indentation of the lines with a `let` that is still waiting for its `in`
+    lets: Vec<usize>,
+
+    // First and last token and indentation of the last line with tokens
+    previous: Option<(Token, Token, usize)>,
+}
+
+impl Formatter<'_> {
+    // Format a line, followed by a closing bracket on the next line if `closed` is set
+    fn line(
+        &mut self,
+        code: &str,
+        tokens: &[Spanned<Token>],
+        line: &Line,
+        blank_lines: usize,
+        closed: bool,
+    ) {
+        if !self.output.is_empty() {
+            self.output.push_str(&"\n".repeat(blank_lines));
+        }
+
+        let first = line.tokens.first().map(|i| &tokens[*i].0);
+        if line.column == 0 && first.is_some() {
+            self.lets.clear();
+        }
+
+        let indent = self.indent(line.column, first);
+        while matches!(self.indents.last(), Some((column, _)) if *column >= line.column) {
+            self.indents.pop();
+        }
+        self.indents.push((line.column, indent));
+
+        let trailing_comma = |i: &&usize| {
+            tokens[**i].0 == Token::Comma
+                && tokens
+                    .get(**i + 1)
+                    .map(|(next, _)| closes(next))
+                    .unwrap_or(false)
+        };
+
+        let mut pieces = line
+            .tokens
+            .iter()
+            .filter(|i| self.config.trailing_commas != TrailingCommas::Never || !trailing_comma(i))
+            .map(|i| Piece {
+                text: code[tokens[*i].1.clone()].to_string(),
+                token: Some(tokens[*i].0.clone()),
+                space: *i > 0
+                    && line.tokens.first() != Some(i)
+                    && space(
+                        tokens.get(i.wrapping_sub(2)).map(|t| &t.0),
+                        &tokens[i - 1],
+                        &tokens[*i],
+                    ),
+            })
+            .collect::<Vec<_>>();
+        // Lines starting with a comma put their commas in front, not behind
+        let missing_comma = closed
+            && self.config.trailing_commas == TrailingCommas::Always
+            && line.tokens.first().map(|i| &tokens[*i].0) != Some(&Token::Comma)
+            && pieces
+                .last()
+                .and_then(|piece| piece.token.as_ref())
+                .map(ends_value)
+                .unwrap_or(false);
+        if missing_comma {
+            pieces.push(Piece {
+                text: ",".to_string(),
+                token: Some(Token::Comma),
+                space: false,
+            });
+        }
+
+        pieces.extend(line.comment.clone().map(|comment| Piece {
+            text: comment,
+            token: None,
+            space: !line.tokens.is_empty(),
+        }));
+        if pieces.is_empty() {
+            return;
+        }
+
+        let mut indent = indent;
+        let mut rest = &pieces[..];
+        loop {
+            let (current, next) = rest.split_at(wrap(rest, indent, self.config.max_width));
+            self.emit(indent, current);
+            if next.is_empty() {
+                break;
+            }
+
+            // Continuations of a `where` are constraints, which are aligned
+            indent = match current.first().and_then(|piece| piece.token.as_ref()) {
+                Some(Token::Where) => indent + WHERE_ALIGN,
+                _ => indent + self.config.indent,
+            };
+            rest = next;
+        }
+    }
+
+    // The indentation of a line at `column`, starting with `first`
+    fn indent(&self, column: usize, first: Option<&Token>) -> usize {
+        if column == 0 {
+            return 0;
+        }
+
+        if let (Some(Token::In), Some(let_indent)) = (first, self.lets.last()) {
+            return let_indent + self.config.indent;
+        }
+
+        if let Some((previous_first, previous_last, previous_indent)) = &self.previous {
+            if matches!(previous_last, Token::Let | Token::Where) {
+                return previous_indent + self.config.indent;
+            }
+
+            let previous_column = self.indents.last().map(|(column, _)| *column).unwrap_or(0);
+            if *previous_first == Token::Where && column > previous_column && first.is_some() {
+                return previous_indent + WHERE_ALIGN;
+            }
+        }
+
+        self.indents
+            .iter()
+            .rev()
+            .find(|(other, _)| *other <= column)
+            .map(|(other, indent)| {
+                if *other == column {
+                    *indent
+                } else {
+                    indent + self.config.indent
+                }
+            })
+            .unwrap_or(self.config.indent)
+    }
+
+    fn emit(&mut self, indent: usize, pieces: &[Piece]) {
+        self.output.push_str(&" ".repeat(indent));
+        for (i, piece) in pieces.iter().enumerate() {
+            if i > 0 && piece.space {
+                self.output.push(' ');
+            }
+            self.output.push_str(&piece.text);
+        }
+        self.output.push('\n');
+
+        let mut tokens = pieces.iter().filter_map(|piece| piece.token.as_ref());
+        for token in tokens.clone() {
+            match token {
+                Token::Let => self.lets.push(indent),
+                Token::In => {
+                    self.lets.pop();
+                }
+                _ => {}
+            }
+        }
+        if let (Some(first), Some(last)) = (tokens.clone().next(), tokens.next_back()) {
+            self.previous = Some((first.clone(), last.clone(), indent));
+        }
+    }
+}
+
+struct Piece {
+    text: String,
+    token: Option<Token>,
+
+    /// Whether there is a space in front of the piece, if it is not the first on its line
+    space: bool,
+}
+
+// Whether there is a space between the tokens `previous` and `next`, with the token before them
+fn space(
+    before: Option<&Token>,
+    (previous, previous_span): &(Token, Range<usize>),
+    (next, next_span): &(Token, Range<usize>),
+) -> bool {
+    let adjacent = previous_span.end == next_span.start;
+
+    // Operators written as more than one token, like `<=` or `++`
+    if adjacent && is_symbol(previous) && is_symbol(next) {
+        return false;
+    }
+
+    // Unary minus
+    if matches!(previous, Token::Op(op) if op == "-") && !before.map(ends_value).unwrap_or(false) {
+        return false;
+    }
+
+    // Logical not, which is always unary
+    if matches!(previous, Token::Op(op) if op == "!") {
+        return false;
+    }
+
+    match (previous, next) {
+        (
+            Token::ParOpen | Token::ListOpen | Token::Separator | Token::Spread | Token::Ctrl('@'),
+            _,
+        ) => false,
+        (
+            _,
+            Token::ParClose
+            | Token::ListClose
+            | Token::Comma
+            | Token::Separator
+            | Token::Try
+            | Token::Declare,
+        ) => false,
+        (Token::BlockOpen, Token::BlockClose) => false,
+        // Calls written without a space, like `f(x)`, stay that way
+        (_, Token::ParOpen | Token::ListOpen) if adjacent && ends_value(previous) => false,
+        _ => true,
+    }
+}
+
+fn is_symbol(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Op(_)
+            | Token::Plus
+            | Token::Assign
+            | Token::Declare
+            | Token::Arrow
+            | Token::Bind
+            | Token::Alternative
+            | Token::Try
+    )
+}
+
+fn ends_value(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Ident(_)
+            | Token::Num(_)
+            | Token::Str(_)
+            | Token::Bytes(_)
+            | Token::Bool(_)
+            | Token::ParClose
+            | Token::ListClose
+            | Token::BlockClose
+            | Token::Try
+    )
+}
+
+// How many of the pieces fit on the line, or all of them if the line cannot be wrapped
+fn wrap(pieces: &[Piece], indent: usize, max_width: usize) -> usize {
+    let widths = pieces
+        .iter()
+        .enumerate()
+        .scan(indent, |width, (i, piece)| {
+            *width += piece.text.chars().count() + usize::from(i > 0 && piece.space);
+            Some(*width)
+        })
+        .collect::<Vec<_>>();
+
+    let fits = widths
+        .last()
+        .map(|width| *width <= max_width)
+        .unwrap_or(true);
+    if fits || pieces.iter().any(|piece| piece.text.contains('\n')) {
+        return pieces.len();
+    }
+
+    // Places to break at, with how deeply nested in brackets they are
+    let mut depth: i64 = 0;
+    let mut breaks = Vec::new();
+    for i in 1..pieces.len() {
+        match pieces[i - 1].token {
+            Some(Token::ParOpen | Token::BlockOpen | Token::ListOpen) => depth += 1,
+            Some(Token::ParClose | Token::BlockClose | Token::ListClose) => depth -= 1,
+            _ => {}
+        }
+
+        if pieces[i].token.is_none() {
+            continue;
+        }
+
+        let after = matches!(pieces[i - 1].token, Some(Token::Comma | Token::Arrow));
+        let before = pieces[i].space
+            && matches!(
+                pieces[i].token,
+                Some(Token::Op(_) | Token::Plus | Token::Alternative)
+            )
+            && pieces[i - 1]
+                .token
+                .as_ref()
+                .map(ends_value)
+                .unwrap_or(false);
+        if after || before {
+            breaks.push((depth, i));
+        }
+    }
+
+    let shallowest = breaks.iter().map(|(depth, _)| *depth).min();
+    let mut depths = breaks.iter().map(|(depth, _)| *depth).collect::<Vec<_>>();
+    depths.sort_unstable();
+    depths.dedup();
+
+    depths
+        .into_iter()
+        .find_map(|depth| {
+            breaks
+                .iter()
+                .filter(|(other, i)| *other == depth && widths[i - 1] <= max_width)
+                .map(|(_, i)| *i)
+                .next_back()
+        })
+        .or_else(|| {
+            breaks
+                .iter()
+                .find(|(depth, _)| Some(*depth) == shallowest)
+                .map(|(_, i)| *i)
+        })
+        .unwrap_or(pieces.len())
+}
@@ -0,0 +1,246 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Classification of tokens for semantic highlighting
+//!
+//! Without name resolution, the role of an identifier is derived from where it appears:
+//!
+//! * Names of top level items are functions
+//! * Names bound by arguments of the enclosing item are parameters, names bound by `let` and `do`
+//!   are variables
+//! * Identifiers followed by `.` are namespaces, like `Std` in `Std.IO.println`
+//! * Identifiers in declarations and after `type` and `enum` are types
+//! * Other capitalized identifiers are constructors
+//! * Names of attributes, like `test` in `@test`, are attributes, as is the `@`
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use vunk_lexer::Token;
+
+use crate::outline::outline;
+use crate::outline::Item;
+use crate::outline::ItemKind;
+use crate::source::tokens;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticToken {
+    /// Byte range of the token
+    pub span: Range<usize>,
+    pub role: Role,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Function,
+    Type,
+    Constructor,
+    Parameter,
+    Variable,
+    Namespace,
+    Keyword,
+    Operator,
+    Comment,
+    Number,
+    String,
+    Attribute,
+}
+
+impl Role {
+    pub const ALL: [Role; 12] = [
+        Role::Function,
+        Role::Type,
+        Role::Constructor,
+        Role::Parameter,
+        Role::Variable,
+        Role::Namespace,
+        Role::Keyword,
+        Role::Operator,
+        Role::Comment,
+        Role::Number,
+        Role::String,
+        Role::Attribute,
+    ];
+}
+
+/// All classified tokens of the code, in order
+pub fn semantic_tokens(code: &str) -> Vec<SemanticToken> {
+    let tokens = tokens(code);
+
+    let items = outline(code);
+    let top_level = items
+        .iter()
+        .filter(|item| item.kind != ItemKind::Other)
+        .map(|item| item.name.as_str())
+        .collect::<BTreeSet<_>>();
+    let scopes = items
+        .iter()
+        .map(|item| Scope::new(item, &tokens))
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::new();
+    let mut end_of_previous = 0;
+    for (i, (token, span)) in tokens.iter().enumerate() {
+        // The lexer skips comments, so they are found in between the tokens
+        result.extend(comments(code, end_of_previous..span.start));
+        end_of_previous = span.end;
+
+        let previous = i.checked_sub(1).map(|i| &tokens[i].0);
+        let next = tokens.get(i + 1).map(|(token, _)| token);
+        let scope = items
+            .iter()
+            .position(|item| item.span.contains(&span.start))
+            .map(|i| (&items[i], &scopes[i]));
+
+        let role = match token {
+            Token::Ident(name) => Some(ident_role(name, span, previous, next, scope, &top_level)),
+            token => token_role(token),
+        };
+
+        result.extend(role.map(|role| SemanticToken {
+            span: span.clone(),
+            role,
+        }));
+    }
+    result.extend(comments(code, end_of_previous..code.len()));
+
+    result
+}
+
+/// The role of a token that does not depend on where it appears, which is any but identifiers
+pub fn token_role(token: &Token) -> Option<Role> {
+    match token {
+        Token::Num(_) => Some(Role::Number),
+        Token::Str(_) | Token::Bytes(_) => Some(Role::String),
+        Token::Bool(_)
+        | Token::If
+        | Token::Else
+        | Token::Let
+        | Token::In
+        | Token::Lazy
+        | Token::Do
+        | Token::Where
+        | Token::Match
+        | Token::When
+        | Token::Type
+        | Token::Enum
+        | Token::Use
+        | Token::Pub
+        | Token::Mod => Some(Role::Keyword),
+        Token::Arrow
+        | Token::Assign
+        | Token::Plus
+        | Token::Op(_)
+        | Token::Bind
+        | Token::Alternative
+        | Token::Spread
+        | Token::Try => Some(Role::Operator),
+        Token::Comment(_) => Some(Role::Comment),
+        Token::Ctrl('@') => Some(Role::Attribute),
+        _ => None,
+    }
+}
+
+// Names bound in an item
+struct Scope {
+    parameters: BTreeSet<String>,
+    variables: BTreeSet<String>,
+}
+
+impl Scope {
+    fn new(item: &Item, tokens: &[(Token, Range<usize>)]) -> Self {
+        let mut scope = Scope {
+            parameters: BTreeSet::new(),
+            variables: BTreeSet::new(),
+        };
+
+        let tokens = tokens
+            .iter()
+            .filter(|(_, span)| {
+                item.span.contains(&span.start) && span.start != item.name_span.start
+            })
+            .map(|(token, _)| token)
+            .collect::<Vec<_>>();
+
+        if item.kind == ItemKind::Definition {
+            for window in tokens.windows(2) {
+                match (window[0], window[1]) {
+                    (Token::Ident(name), Token::Declare) => {
+                        scope.parameters.insert(name.clone());
+                    }
+                    (Token::Ident(name), Token::Assign | Token::Bind) => {
+                        scope.variables.insert(name.clone());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        scope
+    }
+}
+
+fn ident_role(
+    name: &str,
+    span: &Range<usize>,
+    previous: Option<&Token>,
+    next: Option<&Token>,
+    scope: Option<(&Item, &Scope)>,
+    top_level: &BTreeSet<&str>,
+) -> Role {
+    if matches!(previous, Some(Token::Ctrl('@'))) {
+        return Role::Attribute;
+    }
+
+    if let Some((item, scope)) = scope {
+        if span.start == item.name_span.start {
+            return match item.kind {
+                ItemKind::Type => Role::Type,
+                _ => Role::Function,
+            };
+        }
+
+        if item.kind == ItemKind::Declaration && !matches!(next, Some(Token::Separator)) {
+            return Role::Type;
+        }
+
+        if scope.parameters.contains(name) {
+            return Role::Parameter;
+        }
+
+        if scope.variables.contains(name) {
+            return Role::Variable;
+        }
+    }
+
+    if matches!(next, Some(Token::Separator)) {
+        Role::Namespace
+    } else if matches!(previous, Some(Token::Type | Token::Enum | Token::Declare)) {
+        Role::Type
+    } else if top_level.contains(name) {
+        Role::Function
+    } else if name.starts_with(char::is_uppercase) {
+        Role::Constructor
+    } else {
+        Role::Variable
+    }
+}
+
+// Comments in a stretch of code without tokens
+fn comments(code: &str, gap: Range<usize>) -> Vec<SemanticToken> {
+    let mut comments = Vec::new();
+    let mut rest = gap.start;
+    while let Some(start) = code[rest..gap.end].find('#').map(|i| rest + i) {
+        let end = code[start..gap.end]
+            .find('\n')
+            .map(|i| start + i)
+            .unwrap_or(gap.end);
+        comments.push(SemanticToken {
+            span: start..end,
+            role: Role::Comment,
+        });
+        rest = end;
+    }
+    comments
+}
@@ -0,0 +1,188 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Inlay hints showing the types of names without a type annotation
+//!
+//! Until there is type inference, hints come from what is known without it:
+//!
+//! * Lambda parameters of a definition get their type from the declaration of the definition
+//! * `let` bindings of a literal or of a declared name get the type of that literal or name
+
+use std::ops::Range;
+
+use vunk_lexer::Token;
+
+use crate::outline::outline;
+use crate::outline::ItemKind;
+use crate::source::tokens;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlayHint {
+    /// Byte offset the hint is shown at
+    pub offset: usize,
+    pub label: String,
+}
+
+pub fn inlay_hints(code: &str) -> Vec<InlayHint> {
+    let tokens = tokens(code);
+
+    let items = outline(code);
+    let declared = |name: &str| {
+        items
+            .iter()
+            .find(|item| item.name == name && item.kind == ItemKind::Declaration)
+            .and_then(|item| item.declared_type(code))
+    };
+
+    let mut hints = Vec::new();
+    for item in items
+        .iter()
+        .filter(|item| item.kind == ItemKind::Definition)
+    {
+        let tokens = tokens
+            .iter()
+            .filter(|(token, span)| item.span.contains(&span.start) && *token != Token::Pub)
+            .collect::<Vec<_>>();
+
+        if let Some(ty) = declared(&item.name) {
+            hints.extend(parameter_hints(&tokens, ty));
+        }
+
+        hints.extend(binding_hints(&tokens, &item.name_span, &declared));
+    }
+
+    hints
+}
+
+// Hints for the parameters of `name = (a, b) -> ...`, if the definition is a lambda
+fn parameter_hints(tokens: &[&(Token, Range<usize>)], ty: &str) -> Vec<InlayHint> {
+    let parameters = match tokens {
+        [_, (Token::Assign, _), (Token::ParOpen, _), rest @ ..] => rest,
+        _ => return Vec::new(),
+    };
+
+    let types = parameter_types(ty);
+    let mut hints = Vec::new();
+    let mut depth = 0;
+    let mut parameter: Vec<(&Token, Range<usize>)> = Vec::new();
+    let mut index = 0;
+    for (token, span) in parameters.iter().map(|t| (&t.0, &t.1)) {
+        match token {
+            Token::ParOpen | Token::BlockOpen | Token::ListOpen => depth += 1,
+            Token::ParClose | Token::BlockClose | Token::ListClose if depth > 0 => depth -= 1,
+            Token::Comma | Token::ParClose if depth == 0 => {
+                // Only plain names, patterns and annotated parameters do not need a hint
+                if let [(Token::Ident(_), span)] = &parameter[..] {
+                    hints.extend(types.get(index).map(|ty| InlayHint {
+                        offset: span.end,
+                        label: format!(": {}", ty),
+                    }));
+                }
+                parameter.clear();
+                index += 1;
+
+                if matches!(token, Token::ParClose) {
+                    break;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        parameter.push((token, span.clone()));
+    }
+
+    hints
+}
+
+// The types of the arguments of a function type, `(a, b) -> c` has the arguments `a` and `b`
+fn parameter_types(ty: &str) -> Vec<&str> {
+    let arguments = match split_top_level(ty, "->")[..] {
+        [arguments, _, ..] => arguments.trim(),
+        _ => return Vec::new(),
+    };
+
+    match arguments
+        .strip_prefix('(')
+        .and_then(|inner| inner.strip_suffix(')'))
+    {
+        Some(inner) => split_top_level(inner, ",")
+            .into_iter()
+            .map(str::trim)
+            .filter(|ty| !ty.is_empty())
+            .collect(),
+        None => vec![arguments],
+    }
+}
+
+// Split at every occurrence of `separator` that is not in brackets
+fn split_top_level<'a>(text: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' => depth -= 1,
+            _ if depth == 0 && text[i..].starts_with(separator) && i >= start => {
+                parts.push(&text[start..i]);
+                start = i + separator.len();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+// Hints for `name = value` bindings inside a definition, where the value is a single literal or
+// declared name
+fn binding_hints<'a>(
+    tokens: &[&(Token, Range<usize>)],
+    item_name: &Range<usize>,
+    declared: &dyn Fn(&str) -> Option<&'a str>,
+) -> Vec<InlayHint> {
+    let mut hints = Vec::new();
+    for (i, window) in tokens.windows(3).enumerate() {
+        let (name, value) = match window {
+            [(Token::Ident(_), name), (Token::Assign, _), (value, _)]
+                if name.start != item_name.start =>
+            {
+                (name, value)
+            }
+            _ => continue,
+        };
+
+        // `x: Type = value` is annotated already
+        if i > 0 && matches!(tokens[i - 1].0, Token::Declare) {
+            continue;
+        }
+
+        // The value has to end right after the first token, either with the end of the binding
+        // or because the next binding starts
+        let ends = match tokens.get(i + 3).map(|t| &t.0) {
+            None | Some(Token::In | Token::Comma | Token::BlockClose | Token::ParClose) => true,
+            Some(Token::Ident(_)) => matches!(tokens.get(i + 4), Some((Token::Assign, _))),
+            _ => false,
+        };
+        if !ends {
+            continue;
+        }
+
+        let ty = match value {
+            Token::Num(n) if n.contains('.') => Some("f64"),
+            Token::Num(_) => Some("i64"),
+            Token::Str(_) => Some("String"),
+            Token::Bytes(_) => Some("Bytes"),
+            Token::Bool(_) => Some("Bool"),
+            Token::Ident(other) => declared(other),
+            _ => None,
+        };
+        hints.extend(ty.map(|ty| InlayHint {
+            offset: name.end,
+            label: format!(": {}", ty),
+        }));
+    }
+
+    hints
+}
@@ -0,0 +1,237 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Index of where top level names are defined and used
+//!
+//! A use of a name is an identifier spelled like a top level name, which is not bound inside its
+//! own definition (by arguments, `let` or `do`) and not accessed as a member, like `name` in
+//! `Module.name`. There is no resolution of modules yet, so names are global to all documents.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use vunk_lexer::Spanned;
+use vunk_lexer::Token;
+
+use crate::outline::outline;
+use crate::outline::Item;
+use crate::outline::ItemKind;
+use crate::source::tokens;
+
+pub struct Index {
+    definitions: BTreeMap<String, Vec<Location>>,
+    uses: BTreeMap<String, Vec<Use>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// Index of the document in the documents the index was built from
+    pub document: usize,
+
+    /// Byte range of the name
+    pub span: Range<usize>,
+
+    /// Kind of the item the name is defined or used in
+    pub kind: ItemKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Use {
+    pub location: Location,
+
+    /// Name of the item the name is used in
+    pub item: String,
+}
+
+impl Index {
+    pub fn new(documents: &[&str]) -> Self {
+        let documents = documents
+            .iter()
+            .map(|code| Document::new(code))
+            .collect::<Vec<_>>();
+
+        let mut definitions = BTreeMap::<String, Vec<Location>>::new();
+        for (i, document) in documents.iter().enumerate() {
+            for item in document
+                .items
+                .iter()
+                .filter(|item| item.kind != ItemKind::Other)
+            {
+                definitions
+                    .entry(item.name.clone())
+                    .or_default()
+                    .push(Location {
+                        document: i,
+                        span: item.name_span.clone(),
+                        kind: item.kind,
+                    });
+            }
+        }
+
+        let mut uses = BTreeMap::<String, Vec<Use>>::new();
+        for (i, document) in documents.iter().enumerate() {
+            for item in document.items.iter() {
+                let locals = document.locals(item);
+                for (t, name) in document.idents(item) {
+                    let span = &document.tokens[t].1;
+                    if *span == item.name_span
+                        || document.is_member(t)
+                        || locals.contains(name.as_str())
+                        || !definitions.contains_key(name)
+                    {
+                        continue;
+                    }
+
+                    uses.entry(name.clone()).or_default().push(Use {
+                        location: Location {
+                            document: i,
+                            span: span.clone(),
+                            kind: item.kind,
+                        },
+                        item: item.name.clone(),
+                    });
+                }
+            }
+        }
+
+        Index { definitions, uses }
+    }
+
+    /// Where a name is defined, declared or, for types, introduced
+    pub fn definitions(&self, name: &str) -> &[Location] {
+        self.definitions.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Where a name is used
+    pub fn uses(&self, name: &str) -> &[Use] {
+        self.uses.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The definitions using a name in their body
+    pub fn callers(&self, name: &str) -> BTreeSet<&str> {
+        self.uses(name)
+            .iter()
+            .filter(|use_| use_.location.kind == ItemKind::Definition)
+            .map(|use_| use_.item.as_str())
+            .collect()
+    }
+
+    /// Where a type is used in declarations and other types
+    pub fn type_uses(&self, name: &str) -> Vec<&Use> {
+        self.uses(name)
+            .iter()
+            .filter(|use_| matches!(use_.location.kind, ItemKind::Declaration | ItemKind::Type))
+            .collect()
+    }
+
+    /// Every defined function, with the functions it calls
+    pub fn calls(&self) -> BTreeMap<&str, BTreeSet<&str>> {
+        let is_function = |name: &str| {
+            self.definitions(name)
+                .iter()
+                .any(|location| location.kind == ItemKind::Definition)
+        };
+
+        let mut calls = self
+            .definitions
+            .keys()
+            .filter(|name| is_function(name.as_str()))
+            .map(|name| (name.as_str(), BTreeSet::new()))
+            .collect::<BTreeMap<_, _>>();
+
+        for (callee, uses) in self
+            .uses
+            .iter()
+            .filter(|(name, _)| is_function(name.as_str()))
+        {
+            for use_ in uses {
+                if let Some(callees) = calls.get_mut(use_.item.as_str()) {
+                    if use_.location.kind == ItemKind::Definition {
+                        callees.insert(callee.as_str());
+                    }
+                }
+            }
+        }
+
+        calls
+    }
+
+    /// The call graph in the DOT format of Graphviz
+    pub fn call_graph_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n");
+        for (caller, callees) in self.calls() {
+            dot.push_str(&format!("    {:?};\n", caller));
+            for callee in callees {
+                dot.push_str(&format!("    {:?} -> {:?};\n", caller, callee));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// The tokens and items of a document
+pub(crate) struct Document {
+    pub(crate) tokens: Vec<Spanned<Token>>,
+    pub(crate) items: Vec<Item>,
+}
+
+impl Document {
+    pub(crate) fn new(code: &str) -> Self {
+        Document {
+            tokens: tokens(code),
+            items: outline(code),
+        }
+    }
+
+    pub(crate) fn item_at(&self, token: usize) -> Option<&Item> {
+        let start = self.tokens[token].1.start;
+        self.items.iter().find(|item| item.span.contains(&start))
+    }
+
+    /// Indices and names of the identifiers of an item
+    pub(crate) fn idents<'a>(
+        &'a self,
+        item: &'a Item,
+    ) -> impl Iterator<Item = (usize, &'a String)> + 'a {
+        self.tokens
+            .iter()
+            .enumerate()
+            .filter(move |(_, (_, span))| item.span.contains(&span.start))
+            .filter_map(|(i, (token, _))| match token {
+                Token::Ident(name) => Some((i, name)),
+                _ => None,
+            })
+    }
+
+    /// Names bound by arguments, `let` and `do` in a definition
+    pub(crate) fn locals<'a>(&'a self, item: &'a Item) -> BTreeSet<&'a str> {
+        if item.kind != ItemKind::Definition {
+            return BTreeSet::new();
+        }
+
+        self.idents(item)
+            .filter(|(i, _)| self.tokens[*i].1 != item.name_span)
+            .filter(|(i, _)| {
+                matches!(
+                    self.tokens.get(i + 1),
+                    Some((Token::Declare | Token::Assign | Token::Bind, _))
+                )
+            })
+            // Not the fields of records, like `{ x = 1 }`
+            .filter(|(i, _)| {
+                let previous = i.checked_sub(1).map(|previous| &self.tokens[previous].0);
+                !(matches!(previous, Some(Token::BlockOpen | Token::Comma))
+                    && matches!(self.tokens.get(i + 1), Some((Token::Assign, _))))
+            })
+            .map(|(_, name)| name.as_str())
+            .collect()
+    }
+
+    /// Whether the identifier is accessed through a module or type, like `Module.name`
+    pub(crate) fn is_member(&self, token: usize) -> bool {
+        token > 0 && self.tokens[token - 1].0 == Token::Separator
+    }
+}
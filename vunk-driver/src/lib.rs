@@ -0,0 +1,852 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Orchestrates lexer, parser, desugaring and runtime for the `vunk` command line tool
+//!
+//! Every command goes through the same pipeline of stages, stopping at the first one that
+//! fails. Stages that do not exist yet fail with [`DriverError::NotImplemented`].
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use miette::NamedSource;
+use vunk_diagnostics::codes::Code;
+use vunk_diagnostics::suggest;
+use vunk_diagnostics::Applicability;
+use vunk_diagnostics::Label;
+use vunk_diagnostics::Severity;
+use vunk_parser::ast::program::Program;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::io::run_main;
+use vunk_runtime::value::Value;
+
+pub mod attribute;
+pub mod cache;
+pub mod cfg;
+pub mod complete;
+pub mod context;
+pub mod coverage;
+pub mod database;
+pub mod differential;
+pub mod doc;
+pub mod dump;
+pub mod error;
+pub mod format;
+pub mod highlight;
+pub mod hints;
+pub mod index;
+pub mod lint;
+pub mod outline;
+pub mod package;
+pub mod rename;
+pub mod repl;
+pub mod roundtrip;
+pub mod source;
+pub mod testing;
+pub mod textmate;
+pub mod timing;
+pub mod trace;
+pub mod watch;
+
+use crate::context::DriverContext;
+use crate::context::RunOptions;
+use crate::coverage::Coverage;
+use crate::coverage::Report;
+use crate::database::Database;
+use crate::dump::Stage;
+use crate::error::DoctestFailure;
+use crate::error::DriverError;
+use crate::error::TestFailure;
+use crate::error::UnresolvedItem;
+use crate::error::UnresolvedUse;
+use crate::format::config::Config;
+use crate::index::Index;
+use crate::lint::Environment;
+use crate::lint::Levels;
+use crate::package::deprecation::deprecated_items;
+use crate::package::deprecation::deprecated_uses;
+use crate::package::manifest::Dependency;
+use crate::package::manifest::Manifest;
+use crate::package::modules::ModuleGraph;
+use crate::package::scaffold::Kind;
+use crate::package::scope;
+use crate::package::scope::Scope;
+use crate::package::Graph;
+use crate::package::Import;
+use crate::package::Resolved;
+use crate::source::Source;
+use crate::testing::Summary;
+use crate::testing::Test;
+use crate::testing::TestKind;
+use crate::trace::Trace;
+
+/// Lex, parse and typecheck a file, or all modules of the package in a directory
+pub fn check(path: &Path) -> Result<(), DriverError> {
+    check_with(&mut Database::default(), path)
+}
+
+/// Like [`check`], but only computing again what changed since the last use of the database
+pub fn check_with(database: &mut Database, path: &Path) -> Result<(), DriverError> {
+    let names = load(database, path)?;
+    for result in database.typecheck_all(&names) {
+        result?;
+    }
+    Ok(())
+}
+
+/// Run the lints on a file, or on the modules of the package in a directory, returning the
+/// warnings
+///
+/// Fails with [`DriverError::Lints`] if lints set to deny found something. Files that cannot be
+/// lexed are left out, checking them reports why.
+pub fn lint_with(
+    database: &mut Database,
+    path: &Path,
+) -> Result<Vec<vunk_diagnostics::Diagnostic>, DriverError> {
+    let (denied, warnings) = lints(database, path)?
+        .into_iter()
+        .partition::<Vec<_>, _>(|diagnostic| diagnostic.severity == Severity::Error);
+    if denied.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(DriverError::Lints {
+            diagnostics: denied,
+        })
+    }
+}
+
+// What the lints found in the file, or the modules of the root package in the directory.
+// Dependencies are not linted, as their warnings cannot be fixed where they are used
+fn lints(
+    database: &mut Database,
+    path: &Path,
+) -> Result<Vec<vunk_diagnostics::Diagnostic>, DriverError> {
+    let names = load(database, path)?;
+    let graph = database.graph().filter(|_| path.is_dir());
+    let root = graph.as_ref().map(|graph| graph.root().modules.clone());
+    let levels = Levels::for_path(path)?;
+
+    let mut diagnostics = Vec::new();
+    for name in names {
+        let in_root = root
+            .as_ref()
+            .map(|modules| {
+                modules
+                    .values()
+                    .any(|file| file.display().to_string() == name)
+            })
+            .unwrap_or(true);
+        if !in_root || database.lex(&name).is_err() {
+            continue;
+        }
+        if let Some(source) = database.source(&name) {
+            let environment = match graph.as_ref() {
+                Some(graph) => {
+                    let root = graph.packages.len() - 1;
+                    let exports = glob_exports(graph, root, &source, |file| {
+                        let used = database.source(&file.display().to_string())?;
+                        Some(package::exports(&used.code))
+                    });
+                    let deprecated = deprecated_uses(graph, root, &source, &exports, |file| {
+                        let used = database.source(&file.display().to_string())?;
+                        Some(deprecated_items(&used))
+                    });
+                    Environment {
+                        exports,
+                        deprecated,
+                    }
+                }
+                None => Environment {
+                    exports: scope::std_exports(&source.code),
+                    deprecated: Vec::new(),
+                },
+            };
+            let found = database.time("lint", Some(&name), || {
+                lint::lint_in(&source, &levels, &environment)
+            });
+            diagnostics.extend(found);
+        }
+    }
+    Ok(diagnostics)
+}
+
+// Load a file, or all modules of the package in a directory, into the database, returning their
+// names
+fn load(database: &mut Database, path: &Path) -> Result<Vec<String>, DriverError> {
+    if !path.is_dir() {
+        return load_file(database, path).map(|name| vec![name]);
+    }
+
+    let graph = database.time("load", None, || Graph::load(path))?;
+    let mut names = Vec::new();
+    let mut sources = Vec::new();
+    for file in graph
+        .packages
+        .iter()
+        .flat_map(|package| package.modules.values())
+    {
+        let name = file.display().to_string();
+        let source = database.time("load", Some(&name), || Source::load(file))?;
+        sources.push(source.clone());
+        database.set_source(source);
+        names.push(name);
+    }
+    reject_import_cycles(&graph, &sources)?;
+    database.set_graph(graph);
+    Ok(names)
+}
+
+fn load_file(database: &mut Database, path: &Path) -> Result<String, DriverError> {
+    let name = path.display().to_string();
+    let source = database.time("load", Some(&name), || Source::load(path))?;
+    database.set_source(source);
+    Ok(name)
+}
+
+/// Create a package in the new directory `directory`, named after it
+pub fn new(directory: &Path, kind: Kind) -> Result<(), DriverError> {
+    if directory.exists() {
+        return Err(DriverError::AlreadyExists {
+            path: directory.to_path_buf(),
+        });
+    }
+    init(directory, kind)
+}
+
+/// Create a package in `directory`, named after it
+pub fn init(directory: &Path, kind: Kind) -> Result<(), DriverError> {
+    let absolute = directory
+        .canonicalize()
+        .unwrap_or_else(|_| directory.to_path_buf());
+    let name = absolute
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| DriverError::InvalidName(directory.display().to_string()))?;
+
+    package::scaffold::create(directory, &name, kind)
+}
+
+/// Add a dependency to the package containing `directory`, and lock it
+///
+/// The path of a local dependency is relative to `directory`.
+pub fn add(directory: &Path, name: &str, dependency: Dependency) -> Result<(), DriverError> {
+    let root = package_root(directory)?;
+    let dependency = match dependency {
+        Dependency::Path { path, version } => {
+            let path = directory.join(path);
+            let path = path.canonicalize().map_err(|source| DriverError::Read {
+                path: path.clone(),
+                source,
+            })?;
+            Dependency::Path {
+                path: package::relative_path(&root, &path),
+                version,
+            }
+        }
+        git => git,
+    };
+
+    let manifest = root.join(package::manifest::FILE_NAME);
+    let before = std::fs::read_to_string(&manifest).map_err(|source| DriverError::Read {
+        path: manifest.clone(),
+        source,
+    })?;
+    Manifest::add_dependency(&manifest, name, &dependency)?;
+
+    // A dependency that cannot be resolved is not added
+    if let Err(error) = Graph::load(&root) {
+        std::fs::write(&manifest, before).map_err(|source| DriverError::Write {
+            path: manifest.clone(),
+            source,
+        })?;
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Resolve the dependencies of the package containing `directory` again, updating its lockfile
+pub fn update(directory: &Path) -> Result<(), DriverError> {
+    Graph::update(&package_root(directory)?).map(drop)
+}
+
+fn package_root(directory: &Path) -> Result<PathBuf, DriverError> {
+    Manifest::find_root(directory).ok_or_else(|| DriverError::NoManifest {
+        path: directory.to_path_buf(),
+    })
+}
+
+/// The modules of all packages of the graph, after checking that all their `use`s resolve
+pub fn load_modules(graph: &Graph) -> Result<Vec<Source>, DriverError> {
+    let mut sources = Vec::new();
+    for (index, package) in graph.packages.iter().enumerate() {
+        for file in package.modules.values() {
+            let source = Source::load(file)?;
+            source.lex()?;
+
+            for import in package::uses(&source.code) {
+                if graph.resolve_import(index, &import).is_none() {
+                    return Err(unresolved_use(graph, index, &import, &source));
+                }
+            }
+
+            sources.push(source);
+        }
+    }
+
+    // Cycles are found from the modules the uses resolve to alone, so they are reported even if
+    // an item of the cycle does not exist yet
+    reject_import_cycles(graph, &sources)?;
+
+    // Items are checked once all modules are loaded, as a module can use one loaded after it
+    let packages = graph.packages.iter().enumerate();
+    let modules = packages.flat_map(|(index, package)| package.modules.keys().map(move |_| index));
+    for (index, source) in modules.zip(sources.iter()) {
+        for import in package::uses(&source.code) {
+            if let Some(Resolved::Module {
+                package,
+                module,
+                item: Some(_),
+            }) = graph.resolve(index, &import.path)
+            {
+                let file = graph.packages[package].modules[&module]
+                    .display()
+                    .to_string();
+                let used = sources.iter().find(|source| source.name == file);
+                if let Some(error) = used.and_then(|used| unresolved_item(&import, used, source)) {
+                    return Err(error);
+                }
+            }
+        }
+
+        let exports = glob_exports(graph, index, source, |file| {
+            let name = file.display().to_string();
+            let used = sources.iter().find(|source| source.name == name);
+            used.map(|used| package::exports(&used.code))
+        });
+        if let Some(error) = ambiguous_name(source, &exports) {
+            return Err(error);
+        }
+    }
+    Ok(sources)
+}
+
+// Fail with the shortest cycle of modules using each other, if there is one, with a diagnostic
+// for every `use` of the cycle
+fn reject_import_cycles(graph: &Graph, sources: &[Source]) -> Result<(), DriverError> {
+    let modules = ModuleGraph::new(graph, sources);
+    let cycle = match modules.cycle() {
+        Some(cycle) => cycle,
+        None => return Ok(()),
+    };
+
+    let name = |module: usize| modules.modules[module].as_str();
+    let next = cycle.iter().skip(1).chain(cycle.first());
+    let uses = cycle
+        .iter()
+        .zip(next)
+        .map(|(&from, &to)| {
+            let (source, span) = &modules.spans[&(from, to)];
+            vunk_diagnostics::Diagnostic::error(format!("{} uses {}", name(from), name(to)))
+                .with_code(Code::E0007)
+                .with_file(sources[*source].file())
+                .with_label(Label::primary(span.clone(), format!("uses {}", name(to))))
+        })
+        .collect();
+    let path = cycle
+        .iter()
+        .chain(cycle.first())
+        .map(|&module| name(module));
+    Err(DriverError::ImportCycle {
+        cycle: path.collect::<Vec<_>>().join(" → "),
+        uses,
+    })
+}
+
+// The error of a `use` in `source`, a module of the package with the index `package`, that
+// cannot be resolved
+fn unresolved_use(graph: &Graph, package: usize, import: &Import, source: &Source) -> DriverError {
+    let suggestion = graph.suggest(package, &import.path);
+    DriverError::UnresolvedUse(Box::new(UnresolvedUse {
+        path: import.path.join("."),
+        file: source.file(),
+        span: (import.span.start, import.span.len()).into(),
+        package: graph.packages[package].name.clone(),
+        help: suggestion
+            .as_ref()
+            .map(|(path, _)| format!("A module with a similar name exists: {}", path)),
+        suggestion,
+    }))
+}
+
+// The error of a path used in `source` that names an item the module `used` does not declare or
+// define
+fn unresolved_item(import: &Import, used: &Source, source: &Source) -> Option<DriverError> {
+    let (item, module) = import.path.split_last()?;
+    let items = package::items(&used.code);
+    if items.contains(item) {
+        return None;
+    }
+
+    let suggestion = suggest::closest(item, items.iter().map(String::as_str))
+        .map(|(item, applicability)| (item.to_string(), applicability));
+    Some(DriverError::UnresolvedItem(Box::new(UnresolvedItem {
+        item: item.clone(),
+        module: module.join("."),
+        file: source.file(),
+        span: (import.span.end - item.len(), item.len()).into(),
+        help: suggestion
+            .as_ref()
+            .map(|(item, _)| format!("An item with a similar name exists: {}", item)),
+        suggestion,
+    })))
+}
+
+// The names the globs of `source`, a module of the package with the index `package`, bring into
+// scope, by the path of the module they use. `exports` gives the public items of a module file, if
+// it is loaded
+fn glob_exports(
+    graph: &Graph,
+    package: usize,
+    source: &Source,
+    mut exports: impl FnMut(&Path) -> Option<Vec<String>>,
+) -> BTreeMap<String, Vec<String>> {
+    let mut globs = scope::std_exports(&source.code);
+    for import in package::imports(&source.code)
+        .into_iter()
+        .filter(|import| import.glob)
+    {
+        let file = match graph.resolve_import(package, &import) {
+            Some(Resolved::Module {
+                package, module, ..
+            }) => &graph.packages[package].modules[&module],
+            _ => continue,
+        };
+        if let Some(names) = exports(file) {
+            globs.insert(import.path.join("."), names);
+        }
+    }
+    globs
+}
+
+// The error of the first name used in `source` that more than one of its globs bring into scope
+fn ambiguous_name(source: &Source, exports: &BTreeMap<String, Vec<String>>) -> Option<DriverError> {
+    let scope = Scope::new(&source.code, exports);
+    let used = scope
+        .glob_uses(&source.code)
+        .into_iter()
+        .find(|used| used.globs.len() > 1)?;
+    let globs = used
+        .globs
+        .iter()
+        .map(|glob| format!("{}.*", glob.path.join(".")))
+        .collect::<Vec<_>>();
+    Some(DriverError::AmbiguousName {
+        name: used.name,
+        globs: globs.join(" and "),
+        file: source.file(),
+        span: (used.span.start, used.span.len()).into(),
+    })
+}
+
+/// Run the `main` of a file, returning the exit code of the program
+pub fn run(path: &Path, options: RunOptions) -> Result<i32, DriverError> {
+    run_with(&mut Database::default(), path, options)
+}
+
+/// Like [`run`], but only computing again what changed since the last use of the database
+pub fn run_with(
+    database: &mut Database,
+    path: &Path,
+    options: RunOptions,
+) -> Result<i32, DriverError> {
+    let name = load_file(database, path)?;
+    database.typecheck(&name)?;
+    let program = database.parse(&name)?;
+    let main = database.time("evaluate", Some(&name), || evaluate_main(&program))?;
+
+    let trace = options
+        .trace_eval
+        .as_deref()
+        .map(Trace::create)
+        .transpose()?;
+    let mut context = DriverContext::new(options);
+    if let Some(trace) = trace {
+        context = context.traced(trace);
+    }
+    let result = database.time("run", None, || run_main(&mut context, &main));
+    vunk_runtime::task::shutdown();
+    match result {
+        Ok(_) => Ok(0),
+        Err(RuntimeError::Exit(code)) => Ok(code),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Compile a file to an executable at `output`
+pub fn build(path: &Path, output: &Path) -> Result<(), DriverError> {
+    build_with(&mut Database::default(), path, output)
+}
+
+/// Like [`build`], but only computing again what changed since the last use of the database
+pub fn build_with(database: &mut Database, path: &Path, output: &Path) -> Result<(), DriverError> {
+    let name = load_file(database, path)?;
+    database.typecheck(&name)?;
+    tracing::debug!(output = %output.display(), "Compiling");
+    Err(DriverError::NotImplemented {
+        stage: "Code generation",
+    })
+}
+
+/// Format a file in place, or only check whether it is formatted if `check` is set
+///
+/// The configuration is read from the closest `vunkfmt.toml`, see [`Config::discover`].
+pub fn fmt(path: &Path, check: bool) -> Result<(), DriverError> {
+    let source = Source::load(path)?;
+    let config = Config::discover(path)?;
+    let formatted = format::format_with(&source, &config)?;
+    if formatted == source.code {
+        return Ok(());
+    }
+
+    if check {
+        return Err(DriverError::NotFormatted {
+            path: path.to_path_buf(),
+        });
+    }
+
+    tracing::debug!("Formatting {}", source.name);
+    std::fs::write(path, formatted).map_err(|source| DriverError::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Apply the fixes of the problems of a file, or of the modules of the package in a directory,
+/// returning how many were applied
+///
+/// Only suggestions that are certain to be right are applied, including those of lints. As fixing
+/// a problem can reveal others, the files are checked again until there is nothing left to fix.
+///
+/// The suggestions of lints are only applied to files without errors to fix, as they were made
+/// for code that may change with those fixes, like an unresolved `use` looking unused.
+pub fn fix(path: &Path) -> Result<usize, DriverError> {
+    // Fixes are never undone by later ones, this only guards against fixes that do not fix
+    const PASSES: usize = 10;
+
+    let mut total = 0;
+    for _ in 0..PASSES {
+        let mut database = Database::default();
+        let names = load(&mut database, path)?;
+        let errors = database
+            .typecheck_all(&names)
+            .into_iter()
+            .filter_map(Result::err)
+            .flat_map(|error| error.error().diagnostics())
+            .collect::<Vec<_>>();
+        let lints = lints(&mut database, path)?;
+
+        let mut applied = 0;
+        for name in names {
+            let source = match database.source(&name) {
+                Some(source) => source,
+                None => continue,
+            };
+            let mut suggestions = machine_applicable(&errors, &name);
+            if suggestions.is_empty() {
+                suggestions = machine_applicable(&lints, &name);
+            }
+            let (code, count) = vunk_diagnostics::fix::apply(&source.code, suggestions);
+            if count == 0 {
+                continue;
+            }
+
+            tracing::debug!("Applying {} fixes to {}", count, name);
+            std::fs::write(&name, code).map_err(|source| DriverError::Write {
+                path: PathBuf::from(&name),
+                source,
+            })?;
+            applied += count;
+        }
+
+        if applied == 0 {
+            break;
+        }
+        total += applied;
+    }
+    Ok(total)
+}
+
+// The suggestions of the diagnostics of the file `name` that can be applied without a review
+fn machine_applicable<'a>(
+    diagnostics: &'a [vunk_diagnostics::Diagnostic],
+    name: &str,
+) -> Vec<&'a vunk_diagnostics::Suggestion> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| {
+            let file = diagnostic.file.as_ref();
+            file.map(|file| file.name == name).unwrap_or(false)
+        })
+        .flat_map(|diagnostic| diagnostic.suggestions.iter())
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable)
+        .collect()
+}
+
+/// Write the documentation of the files to `output`, as HTML pages and as `doc.json`
+///
+/// As there is no typechecker yet, only declared types are shown.
+pub fn doc(paths: &[PathBuf], output: &Path) -> Result<(), DriverError> {
+    let sources = paths
+        .iter()
+        .map(|path| Source::load(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    for source in sources.iter() {
+        source.lex()?;
+    }
+
+    let modules = doc::collect(&sources);
+    let write = |name: &str, content: String| {
+        let path = output.join(name);
+        std::fs::write(&path, content).map_err(|source| DriverError::Write { path, source })
+    };
+
+    std::fs::create_dir_all(output).map_err(|source| DriverError::Write {
+        path: output.to_path_buf(),
+        source,
+    })?;
+    write("index.html", doc::html::index(&modules))?;
+    for module in modules.iter() {
+        write(
+            &doc::html::page_name(&module.name),
+            doc::html::module(module, &modules),
+        )?;
+    }
+
+    let json = serde_json::to_string_pretty(&modules).unwrap_or_default();
+    write("doc.json", json)
+}
+
+/// Run the examples in the doc comments of the files
+///
+/// An example passes if it typechecks and evaluates without error to anything but `false`.
+pub fn doctest(paths: &[PathBuf]) -> Result<(), DriverError> {
+    let mut total = 0;
+    let mut failures = Vec::new();
+    for path in paths {
+        let source = Source::load(path)?;
+        source.lex()?;
+
+        for doctest in doc::doctests(&source.code) {
+            total += 1;
+            tracing::debug!("Running example of {} in {}", doctest.item, source.name);
+
+            let program = Source {
+                name: source.name.clone(),
+                code: doc::doctest_program(&source.code, &doctest.code),
+            };
+            let reason = match run_doctest(&program) {
+                Ok(Value::Bool(false)) => "the example evaluated to false".to_string(),
+                Ok(_) => continue,
+                Err(error) => error.to_string(),
+            };
+
+            failures.push(DoctestFailure {
+                src: NamedSource::new(&source.name, source.code.clone()),
+                span: (doctest.span.start, doctest.span.len()).into(),
+                item: doctest.item,
+                reason,
+            });
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(DriverError::Doctests { total, failures })
+    }
+}
+
+/// Run the tests in the files and directories whose name contains `filter`
+///
+/// Failing tests do not make this fail, they are part of the summary.
+pub fn test(
+    paths: &[PathBuf],
+    filter: Option<&str>,
+    options: RunOptions,
+) -> Result<Summary, DriverError> {
+    let mut summary = Summary::default();
+    for path in testing::discover(paths)? {
+        let source = Source::load(&path)?;
+        source.lex()?;
+
+        for test in testing::tests(&source.code) {
+            if filter
+                .map(|filter| !test.name.contains(filter))
+                .unwrap_or(false)
+            {
+                summary.filtered_out += 1;
+                continue;
+            }
+
+            tracing::debug!("Running {} in {}", test.name, source.name);
+            match run_test(&source, &test, options.clone()) {
+                Ok(()) => summary.passed.push(test.name),
+                Err(error) => summary.failures.push(TestFailure {
+                    src: NamedSource::new(&source.name, source.code.clone()),
+                    span: (test.span.start, test.span.len()).into(),
+                    name: test.name,
+                    reason: error.to_string(),
+                }),
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Which code of the files in `paths` was executed according to `coverage`, after running their
+/// tests with it, see [`coverage`](mod@coverage)
+pub fn coverage(paths: &[PathBuf], coverage: &Coverage) -> Result<Report, DriverError> {
+    let sources = testing::discover(paths)?
+        .iter()
+        .map(|path| Source::load(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Report::new(&sources, coverage))
+}
+
+/// The call graph of a file, in the DOT format
+pub fn call_graph(path: &Path) -> Result<String, DriverError> {
+    let source = Source::load(path)?;
+    source.lex()?;
+    Ok(Index::new(&[&source.code]).call_graph_dot())
+}
+
+/// Which modules of the package containing `path`, and of its dependencies, use which
+pub fn module_graph(path: &Path) -> Result<ModuleGraph, DriverError> {
+    let graph = Graph::load(&package_root(path)?)?;
+    let sources = graph
+        .packages
+        .iter()
+        .flat_map(|package| package.modules.values())
+        .map(|file| Source::load(file))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ModuleGraph::new(&graph, &sources))
+}
+
+/// A stage of the pipeline on a file, as JSON if `json` is set, see [`dump`](mod@dump)
+pub fn dump(path: &Path, stage: Stage, json: bool) -> Result<String, DriverError> {
+    let source = Source::load(path)?;
+    let tokens = source.lex()?;
+    match stage {
+        Stage::Tokens => {
+            // With spans in bytes, instead of the chars the lexer counts
+            let tokens = crate::source::tokens(&source.code);
+            if json {
+                Ok(dump::tokens_json(&source.code, &tokens))
+            } else {
+                Ok(dump::tokens(&source.code, &tokens))
+            }
+        }
+        Stage::Ast => Ok(dump::program(&parse(&source, tokens)?, json)),
+        Stage::Core => Ok(dump::program(&desugar(parse(&source, tokens)?)?, json)),
+        Stage::Types => {
+            typecheck(&desugar(parse(&source, tokens)?)?)?;
+            // The typechecker does not infer types yet, so there is nothing to show
+            Err(DriverError::NotImplemented {
+                stage: "Dumping types",
+            })
+        }
+    }
+}
+
+// Everything up to a desugared program
+fn frontend(source: &Source) -> Result<Program, DriverError> {
+    let tokens = source.lex()?;
+    tracing::debug!(tokens = tokens.len(), "Lexed {}", source.name);
+
+    desugar(parse(source, tokens)?)
+}
+
+/// Desugar a parsed program into the core language, with its constant expressions evaluated
+pub fn desugar(program: Program) -> Result<Program, DriverError> {
+    let program = vunk_parser::desugar::desugar_synonyms(program)?;
+    let program = vunk_parser::desugar::desugar_comprehension(program);
+    let program = vunk_parser::desugar::desugar_spread(program);
+    let program = vunk_parser::desugar::desugar_join(program);
+    let program = vunk_parser::desugar::desugar_sections(program);
+    let program = vunk_parser::desugar::desugar_accessors(program);
+    let program = vunk_parser::desugar::desugar_where(program)?;
+    let program = vunk_parser::desugar::desugar_equations(program)?;
+    let program = vunk_parser::desugar::desugar_params(program)?;
+    let program = vunk_parser::desugar::desugar_do(program);
+    let program = vunk_parser::desugar::desugar_try(program)?;
+    vunk_parser::matching::check(&program)?;
+    vunk_parser::traits::check(&program)?;
+    vunk_parser::kinds::check(&program)?;
+    vunk_parser::effects::check(&program)?;
+    Ok(vunk_parser::consteval::fold(program))
+}
+
+/// Parse the tokens of a file, see [`Source::parse`]
+pub fn parse(
+    source: &Source,
+    tokens: Vec<vunk_lexer::Spanned<vunk_lexer::Token>>,
+) -> Result<Program, DriverError> {
+    source.parse(tokens)
+}
+
+/// Typecheck a desugared program
+pub fn typecheck(_program: &Program) -> Result<(), DriverError> {
+    Err(DriverError::NotImplemented {
+        stage: "Typechecking",
+    })
+}
+
+fn run_doctest(program: &Source) -> Result<Value, DriverError> {
+    let program = frontend(program)?;
+    typecheck(&program)?;
+    Err(DriverError::NotImplemented {
+        stage: "Evaluation",
+    })
+}
+
+fn run_test(source: &Source, test: &Test, options: RunOptions) -> Result<(), DriverError> {
+    let value = match &test.kind {
+        TestKind::Definition => {
+            let program = frontend(source)?;
+            typecheck(&program)?;
+            evaluate(&program, &test.name)?
+        }
+        TestKind::Example(code) => run_doctest(&Source {
+            name: source.name.clone(),
+            code: doc::doctest_program(&source.code, code),
+        })?,
+    };
+
+    match value.force()? {
+        Value::Bool(false) => {
+            Err(RuntimeError::AssertionFailed(format!("{} evaluated to false", test.name)).into())
+        }
+        io @ Value::Io(_) => {
+            let result = run_main(&mut DriverContext::new(options), &io);
+            vunk_runtime::task::shutdown();
+            result?;
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn evaluate(_program: &Program, _name: &str) -> Result<Value, DriverError> {
+    Err(DriverError::NotImplemented {
+        stage: "Evaluation",
+    })
+}
+
+fn evaluate_main(_program: &Program) -> Result<Value, DriverError> {
+    Err(DriverError::NotImplemented {
+        stage: "Evaluation",
+    })
+}
@@ -0,0 +1,274 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Lints, warning about code that is valid but probably not what was meant
+//!
+//! Every [`Lint`] has a [`Level`]: allowed lints are not reported, warnings are reported without
+//! failing, and denied lints fail like errors. The level of a lint is, from the most to the least
+//! specific:
+//!
+//! * set for an item by an attribute in front of it, like `@allow(shadowing, unused)`
+//! * set for a module by an attribute in front of its first item, like `@no_prelude`
+//! * set for the package in the `[lints]` table of its `vunk.toml`, like `unused = "deny"`
+//! * the default level of the lint
+//!
+//! Until there is a parser, lints work on the tokens and the outline of a file, see [`rules`].
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+use vunk_diagnostics::Diagnostic;
+use vunk_diagnostics::Label;
+use vunk_diagnostics::Severity;
+
+use crate::error::DriverError;
+use crate::outline::outline;
+use crate::package::deprecation::DeprecatedUse;
+use crate::package::manifest;
+use crate::package::manifest::Manifest;
+use crate::package::scope::std_exports;
+use crate::source::Source;
+
+pub mod rules;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Lint {
+    Shadowing,
+    Unused,
+    RedundantParens,
+    PointlessIf,
+    Deprecated,
+    Todo,
+}
+
+impl Lint {
+    pub const ALL: &'static [Lint] = &[
+        Lint::Shadowing,
+        Lint::Unused,
+        Lint::RedundantParens,
+        Lint::PointlessIf,
+        Lint::Deprecated,
+        Lint::Todo,
+    ];
+
+    /// The name the lint is configured with
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::Shadowing => "shadowing",
+            Lint::Unused => "unused",
+            Lint::RedundantParens => "redundant_parens",
+            Lint::PointlessIf => "pointless_if",
+            Lint::Deprecated => "deprecated",
+            Lint::Todo => "todo",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Lint::Shadowing => "A binding has the name of a binding or definition it hides",
+            Lint::Unused => "A `use` or a `let` binding is never used",
+            Lint::RedundantParens => "Parentheses around something that does not need them",
+            Lint::PointlessIf => "`if c then true else false`, which is just `c`",
+            Lint::Deprecated => "An item marked with `@deprecated` is used",
+            Lint::Todo => "A `todo` or `unimplemented` is left in the code",
+        }
+    }
+
+    /// Shadowing is common in functional code, so it has to be asked for. Placeholders are
+    /// expected while writing code, so listing them has to be asked for too, like with
+    /// `todo = "warn"` in the `[lints]` table
+    pub fn default_level(&self) -> Level {
+        match self {
+            Lint::Shadowing | Lint::Todo => Level::Allow,
+            Lint::Unused | Lint::RedundantParens | Lint::PointlessIf | Lint::Deprecated => {
+                Level::Warn
+            }
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name {
+            "allow" => Some(Level::Allow),
+            "warn" => Some(Level::Warn),
+            "deny" => Some(Level::Deny),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Allow => write!(f, "allow"),
+            Level::Warn => write!(f, "warn"),
+            Level::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+/// The levels of the lints, where they differ from the default
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Levels {
+    levels: BTreeMap<Lint, Level>,
+}
+
+impl Levels {
+    /// The levels of the `[lints]` table of a manifest
+    pub fn from_manifest(manifest: &Manifest) -> Levels {
+        let mut levels = Levels::default();
+        for (name, level) in manifest.lints.iter() {
+            // The names were checked when loading the manifest
+            if let Some(lint) = Lint::from_name(name) {
+                levels.set(lint, *level);
+            }
+        }
+        levels
+    }
+
+    /// The levels of the package containing `path`, or the defaults outside of packages
+    pub fn for_path(path: &Path) -> Result<Levels, DriverError> {
+        let manifest = match Manifest::find_root(path) {
+            Some(root) => Manifest::load(&root.join(manifest::FILE_NAME))?,
+            None => return Ok(Levels::default()),
+        };
+        Ok(Levels::from_manifest(&manifest))
+    }
+
+    pub fn level(&self, lint: Lint) -> Level {
+        self.levels
+            .get(&lint)
+            .copied()
+            .unwrap_or_else(|| lint.default_level())
+    }
+
+    pub fn set(&mut self, lint: Lint, level: Level) {
+        self.levels.insert(lint, level);
+    }
+}
+
+/// What a file is linted against, besides its own code
+#[derive(Clone, Debug, Default)]
+pub struct Environment {
+    /// The names the globs of the file bring into scope, by the path of the module they use
+    pub exports: BTreeMap<String, Vec<String>>,
+
+    /// The uses of deprecated items of other modules, see [`crate::package::deprecation`]
+    pub deprecated: Vec<DeprecatedUse>,
+}
+
+/// Run all lints on a file, returning what they found as warnings, or errors for denied lints
+///
+/// Only the globs of modules of the standard library are checked for being used, and no uses of
+/// deprecated items are found, as the other modules of the package are not known, see
+/// [`lint_in`].
+pub fn lint(source: &Source, levels: &Levels) -> Vec<Diagnostic> {
+    let environment = Environment {
+        exports: std_exports(&source.code),
+        deprecated: Vec::new(),
+    };
+    lint_in(source, levels, &environment)
+}
+
+/// Like [`lint`], with what is known about the other modules
+pub fn lint_in(source: &Source, levels: &Levels, environment: &Environment) -> Vec<Diagnostic> {
+    let items = outline(&source.code);
+    let mut diagnostics = Vec::new();
+
+    // The attributes of the first item set the levels of the whole module
+    let mut levels = levels.clone();
+    let first = items.first().map(|item| item.attributes(&source.code));
+    for attribute in first.into_iter().flatten() {
+        if let Some((level, names)) = parse_attribute(attribute) {
+            names
+                .into_iter()
+                .filter_map(Lint::from_name)
+                .for_each(|lint| levels.set(lint, level));
+        }
+    }
+
+    // The levels of the items, set by their attributes
+    let mut item_levels = Vec::new();
+    for item in items.iter() {
+        let mut levels = levels.clone();
+        for attribute in item.attributes(&source.code) {
+            let (level, names) = match parse_attribute(attribute) {
+                Some(attribute) => attribute,
+                None => continue,
+            };
+            for name in names {
+                match Lint::from_name(name) {
+                    Some(lint) => levels.set(lint, level),
+                    None => diagnostics.push(
+                        Diagnostic::warning(format!("Unknown lint `{}`", name))
+                            .with_file(source.file())
+                            .with_label(Label::primary(item.name_span.clone(), "on this item"))
+                            .with_note(format!(
+                                "the lints are {}",
+                                Lint::ALL
+                                    .iter()
+                                    .map(Lint::name)
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            )),
+                    ),
+                }
+            }
+        }
+        item_levels.push(levels);
+    }
+
+    for (lint, mut diagnostic) in rules::run(source, &items, environment) {
+        let offset = diagnostic
+            .labels
+            .first()
+            .map(|label| label.span.start)
+            .unwrap_or(0);
+        let level = items
+            .iter()
+            .position(|item| item.span.contains(&offset))
+            .map(|index| item_levels[index].level(lint))
+            .unwrap_or_else(|| levels.level(lint));
+
+        diagnostic.severity = match level {
+            Level::Allow => continue,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        diagnostics.push(diagnostic.with_note(format!("`{}` is set to {}", lint, level)));
+    }
+    diagnostics
+}
+
+// The level and the lint names of an attribute like `allow(shadowing, unused)`
+fn parse_attribute(attribute: &str) -> Option<(Level, Vec<&str>)> {
+    let (level, names) = attribute.strip_suffix(')')?.split_once('(')?;
+    let level = Level::from_name(level.trim())?;
+    let names = names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    Some((level, names))
+}
@@ -0,0 +1,493 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The lints themselves, working on the tokens of a file and its outline
+//!
+//! Without a parser, bindings are recognized by the tokens around them:
+//!
+//! * Parameters are the lowercase names in parentheses followed by `->`, except for the types
+//!   after a `:`
+//! * `let` bindings are the names followed by `=` between a `let` and its `in`
+//!
+//! A binding is in scope until the end of the definition it is in. Where that is not precise
+//! enough, the lints rather report nothing than something wrong.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use vunk_diagnostics::Applicability;
+use vunk_diagnostics::Diagnostic;
+use vunk_diagnostics::Label;
+use vunk_lexer::Spanned;
+use vunk_lexer::Token;
+
+use crate::lint::Environment;
+use crate::lint::Lint;
+use crate::outline::Item;
+use crate::outline::ItemKind;
+use crate::package::deprecation::DeprecatedUse;
+use crate::package::imports;
+use crate::package::scope::Scope;
+use crate::source::tokens;
+use crate::source::Source;
+
+/// Run all lints on a file with the items of its outline, regardless of their level
+pub fn run(source: &Source, items: &[Item], environment: &Environment) -> Vec<(Lint, Diagnostic)> {
+    let tokens = code_tokens(&source.code);
+    let lints = [
+        (Lint::Shadowing, shadowing(source, items, &tokens)),
+        (
+            Lint::Unused,
+            unused(source, items, &tokens, &environment.exports),
+        ),
+        (
+            Lint::RedundantParens,
+            redundant_parens(source, items, &tokens),
+        ),
+        (Lint::PointlessIf, pointless_if(source, &tokens)),
+        (
+            Lint::Deprecated,
+            deprecated(source, &environment.deprecated),
+        ),
+        (Lint::Todo, todos(source, &tokens)),
+    ];
+
+    let mut found = lints
+        .into_iter()
+        .flat_map(|(lint, diagnostics)| diagnostics.into_iter().map(move |d| (lint, d)))
+        .collect::<Vec<_>>();
+    found.sort_by_key(|(_, diagnostic)| diagnostic.labels.first().map(|label| label.span.start));
+    found
+}
+
+fn shadowing(source: &Source, items: &[Item], tokens: &[Spanned<Token>]) -> Vec<Diagnostic> {
+    let mut definitions = BTreeMap::new();
+    for item in items
+        .iter()
+        .filter(|item| item.kind == ItemKind::Definition)
+    {
+        definitions
+            .entry(item.name.as_str())
+            .or_insert_with(|| item.name_span.clone());
+    }
+
+    let mut diagnostics = Vec::new();
+    for item in items
+        .iter()
+        .filter(|item| item.kind == ItemKind::Definition)
+    {
+        let mut seen: BTreeMap<String, Range<usize>> = BTreeMap::new();
+        for binding in bindings(item, tokens) {
+            if binding.name.starts_with('_') {
+                continue;
+            }
+
+            let shadowed = match seen.get(binding.name.as_str()) {
+                Some(span) => Some((span.clone(), "an earlier binding")),
+                None => definitions
+                    .get(binding.name.as_str())
+                    .map(|span| (span.clone(), "a definition")),
+            };
+            if let Some((span, what)) = shadowed {
+                diagnostics.push(
+                    Diagnostic::warning(format!("`{}` shadows {}", binding.name, what))
+                        .with_file(source.file())
+                        .with_label(Label::primary(binding.span.clone(), "this binding"))
+                        .with_label(Label::secondary(span, "hides this one")),
+                );
+            }
+            seen.insert(binding.name, binding.span);
+        }
+    }
+    diagnostics
+}
+
+fn unused(
+    source: &Source,
+    items: &[Item],
+    tokens: &[Spanned<Token>],
+    exports: &BTreeMap<String, Vec<String>>,
+) -> Vec<Diagnostic> {
+    let code = source.code.as_str();
+    let imports = imports(code);
+    let scope = Scope::new(code, exports);
+    let unused_globs = scope.unused_globs(code);
+    let unused_globs = unused_globs
+        .into_iter()
+        .map(|glob| glob.path.as_slice())
+        .collect::<BTreeSet<_>>();
+    let is_used = |name: &str| {
+        tokens.iter().any(|(token, span)| {
+            matches!(token, Token::Ident(used) if used == name)
+                && !imports.iter().any(|import| {
+                    import.span.contains(&span.start)
+                        || matches!(&import.alias, Some((_, alias)) if alias.contains(&span.start))
+                })
+        })
+    };
+
+    let mut diagnostics = Vec::new();
+    for import in imports.iter() {
+        // `use Std.$` imports an operator, which is not a name
+        let path = &code[import.span.clone()];
+        let used = match import.name() {
+            _ if import.glob => !unused_globs.contains(import.path.as_slice()),
+            Some(name) if !path.ends_with('.') => is_used(name),
+            _ => continue,
+        };
+        if used {
+            continue;
+        }
+
+        let span = match &import.alias {
+            Some((_, alias)) => import.span.start..alias.end,
+            None => import.span.clone(),
+        };
+        let path = &code[span.clone()];
+        let mut diagnostic = Diagnostic::warning(format!("`{}` is never used", path))
+            .with_file(source.file())
+            .with_label(Label::primary(span.clone(), "unused"));
+
+        // Only a line with nothing but the `use` can be removed, a `pub use` is used elsewhere
+        let start = code[..import.span.start]
+            .rfind('\n')
+            .map(|newline| newline + 1)
+            .unwrap_or(0);
+        let end = code[span.end..]
+            .find('\n')
+            .map(|newline| span.end + newline + 1)
+            .unwrap_or(code.len());
+        if code[start..end].trim() == format!("use {}", path) {
+            diagnostic = diagnostic.with_fix("remove the `use`", start..end, "");
+        } else if code[start..end].trim_start().starts_with("pub") {
+            continue;
+        }
+        diagnostics.push(diagnostic);
+    }
+
+    for item in items
+        .iter()
+        .filter(|item| item.kind == ItemKind::Definition)
+    {
+        let bindings = bindings(item, tokens)
+            .into_iter()
+            .filter(|binding| binding.kind == BindingKind::Let && !binding.name.starts_with('_'));
+        for binding in bindings {
+            let used = tokens.iter().any(|(token, span)| {
+                matches!(token, Token::Ident(used) if *used == binding.name)
+                    && span.start > binding.span.start
+                    && item.span.contains(&span.start)
+            });
+            if used {
+                continue;
+            }
+
+            diagnostics.push(
+                Diagnostic::warning(format!("`{}` is never used", binding.name))
+                    .with_file(source.file())
+                    .with_label(Label::primary(binding.span.clone(), "unused"))
+                    .with_applicable_suggestion(
+                        "if this is intentional, prefix it with an underscore",
+                        binding.span.clone(),
+                        format!("_{}", binding.name),
+                        Applicability::MaybeIncorrect,
+                    ),
+            );
+        }
+    }
+    diagnostics
+}
+
+fn redundant_parens(source: &Source, items: &[Item], tokens: &[Spanned<Token>]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (open, (token, open_span)) in tokens.iter().enumerate() {
+        if *token != Token::ParOpen {
+            continue;
+        }
+        let close = match closing(tokens, open) {
+            Some(close) if close > open + 1 => close,
+            _ => continue,
+        };
+        // Declarations and types have parentheses around types, which are not linted
+        let item = match items
+            .iter()
+            .find(|item| item.span.contains(&open_span.start))
+        {
+            Some(item) if item.kind == ItemKind::Definition => item,
+            _ => continue,
+        };
+
+        let next = tokens.get(close + 1);
+        let parameters = matches!(next, Some((Token::Arrow, _)));
+
+        // `(x: u8)` ascribes a type to `x`, which needs the parentheses
+        let mut depth = 0;
+        let ascription = tokens[open + 1..close].iter().any(|(token, _)| {
+            match token {
+                Token::ParOpen | Token::BlockOpen | Token::ListOpen => depth += 1,
+                Token::ParClose | Token::BlockClose | Token::ListClose => depth -= 1,
+                _ => {}
+            }
+            depth == 0 && *token == Token::Declare
+        });
+
+        // `((a + b))`
+        let doubled =
+            tokens[open + 1].0 == Token::ParOpen && closing(tokens, open + 1) == Some(close - 1);
+
+        // `(x)`, `(1)`, `("text")` or `(true)`
+        let atom = close == open + 2
+            && matches!(
+                tokens[open + 1].0,
+                Token::Ident(_) | Token::Num(_) | Token::Str(_) | Token::Bytes(_) | Token::Bool(_)
+            );
+
+        // `x = (a + b)`, with nothing after the parentheses
+        let whole = open >= 2
+            && tokens[open - 1].0 == Token::Assign
+            && tokens[open - 2].1 == item.name_span
+            && next
+                .map(|(_, span)| !item.span.contains(&span.start))
+                .unwrap_or(true);
+
+        if parameters || ascription || !(doubled || atom || whole) {
+            continue;
+        }
+
+        let close_span = tokens[close].1.clone();
+        diagnostics.push(
+            Diagnostic::warning("Unnecessary parentheses")
+                .with_file(source.file())
+                .with_label(Label::primary(open_span.start..close_span.end, ""))
+                .with_fix("remove these parentheses", open_span.clone(), "")
+                .with_fix("remove these parentheses", close_span, ""),
+        );
+    }
+    diagnostics
+}
+
+fn pointless_if(source: &Source, tokens: &[Spanned<Token>]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (index, (token, if_span)) in tokens.iter().enumerate() {
+        if *token != Token::If {
+            continue;
+        }
+        let then = match then_of(tokens, index) {
+            Some(then) if then > index + 1 => then,
+            _ => continue,
+        };
+        let (tru, end) = match &tokens[then + 1..] {
+            [(Token::Bool(tru), _), (Token::Else, _), (Token::Bool(fals), end), ..]
+                if tru != fals =>
+            {
+                (*tru, end)
+            }
+            _ => continue,
+        };
+
+        let span = if_span.start..end.end;
+        let condition = &source.code[tokens[index + 1].1.start..tokens[then - 1].1.end];
+        let diagnostic = Diagnostic::warning(if tru {
+            "This `if` is the same as its condition"
+        } else {
+            "This `if` is the same as the negation of its condition"
+        })
+        .with_file(source.file())
+        .with_label(Label::primary(span.clone(), ""));
+
+        diagnostics.push(if tru {
+            let condition = if then == index + 2 {
+                condition.to_string()
+            } else {
+                format!("({})", condition)
+            };
+            diagnostic.with_fix("use the condition", span, condition)
+        } else {
+            diagnostic.with_note("use the negation of the condition instead")
+        });
+    }
+    diagnostics
+}
+
+// Every `todo "message"` and `unimplemented`, with the message of the todo
+fn todos(source: &Source, tokens: &[Spanned<Token>]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (index, (token, span)) in tokens.iter().enumerate() {
+        let diagnostic = match (token, tokens.get(index + 1)) {
+            (Token::Ident(name), Some((Token::Str(message), end))) if name == "todo" => {
+                Diagnostic::warning(format!("todo: {}", message))
+                    .with_label(Label::primary(span.start..end.end, ""))
+            }
+            (Token::Ident(name), _) if name == "todo" || name == "unimplemented" => {
+                Diagnostic::warning(format!("`{}` is left in the code", name))
+                    .with_label(Label::primary(span.clone(), ""))
+            }
+            _ => continue,
+        };
+        diagnostics.push(diagnostic.with_file(source.file()));
+    }
+    diagnostics
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BindingKind {
+    Parameter,
+    Let,
+}
+
+pub(crate) struct Binding {
+    pub(crate) name: String,
+    span: Range<usize>,
+    kind: BindingKind,
+}
+
+// The parameters and `let` bindings of a definition, in the order they appear in
+pub(crate) fn bindings(item: &Item, tokens: &[Spanned<Token>]) -> Vec<Binding> {
+    let indices = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, span))| item.span.contains(&span.start))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let mut bindings = Vec::new();
+    let mut lets = 0usize;
+    for index in indices {
+        let (token, span) = &tokens[index];
+        let previous = index.checked_sub(1).map(|previous| &tokens[previous].0);
+        match token {
+            Token::Let => lets += 1,
+            Token::In => lets = lets.saturating_sub(1),
+            Token::ParOpen => {
+                let close = match closing(tokens, index) {
+                    Some(close) => close,
+                    None => continue,
+                };
+                if !matches!(tokens.get(close + 1), Some((Token::Arrow, _))) {
+                    continue;
+                }
+
+                let mut previous = token;
+                for (token, span) in tokens[index + 1..close].iter() {
+                    match token {
+                        Token::Ident(name)
+                            if *previous != Token::Declare
+                                && name.starts_with(char::is_lowercase) =>
+                        {
+                            bindings.push(Binding {
+                                name: name.clone(),
+                                span: span.clone(),
+                                kind: BindingKind::Parameter,
+                            })
+                        }
+                        _ => {}
+                    }
+                    previous = token;
+                }
+            }
+            // Fields of records, like `{ x = 1, y = 2 }`, follow a `{` or a `,`
+            Token::Ident(name)
+                if lets > 0
+                    && !matches!(
+                        previous,
+                        Some(Token::Separator | Token::BlockOpen | Token::Comma)
+                    )
+                    && matches!(tokens.get(index + 1), Some((Token::Assign, _))) =>
+            {
+                bindings.push(Binding {
+                    name: name.clone(),
+                    span: span.clone(),
+                    kind: BindingKind::Let,
+                })
+            }
+            _ => {}
+        }
+    }
+
+    bindings.sort_by_key(|binding| binding.span.start);
+    bindings
+}
+
+// The tokens that are code, without comments and attributes
+pub(crate) fn code_tokens(code: &str) -> Vec<Spanned<Token>> {
+    let mut tokens = tokens(code)
+        .into_iter()
+        .filter(|(token, _)| !matches!(token, Token::Comment(_)))
+        .peekable();
+
+    let mut code_tokens = Vec::new();
+    while let Some((token, span)) = tokens.next() {
+        if token == Token::Ctrl('@') {
+            tokens.next();
+            if let Some((Token::ParOpen, _)) = tokens.peek() {
+                let mut depth = 0usize;
+                for (token, _) in tokens.by_ref() {
+                    match token {
+                        Token::ParOpen => depth += 1,
+                        Token::ParClose if depth == 1 => break,
+                        Token::ParClose => depth -= 1,
+                        _ => {}
+                    }
+                }
+            }
+            continue;
+        }
+        code_tokens.push((token, span));
+    }
+    code_tokens
+}
+
+fn deprecated(source: &Source, uses: &[DeprecatedUse]) -> Vec<Diagnostic> {
+    uses.iter()
+        .map(|used| {
+            let deprecation = &used.deprecation;
+            let message = match &deprecation.note {
+                Some(note) => format!("`{}` is deprecated: {}", used.path, note),
+                None => format!("`{}` is deprecated", used.path),
+            };
+            let mut diagnostic = Diagnostic::warning(message)
+                .with_file(source.file())
+                .with_label(Label::primary(used.span.clone(), "deprecated"));
+            if let Some(since) = &deprecation.since {
+                diagnostic = diagnostic.with_note(format!("deprecated since {}", since));
+            }
+            diagnostic.with_note(format!("defined at {}", deprecation.location))
+        })
+        .collect()
+}
+
+// The index of the parenthesis closing the one at `open`
+fn closing(tokens: &[Spanned<Token>], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, (token, _)) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::ParOpen => depth += 1,
+            Token::ParClose => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// The `then` of the `if` at `index`, skipping those of nested `if`s
+fn then_of(tokens: &[Spanned<Token>], index: usize) -> Option<usize> {
+    let mut nested = 0usize;
+    for (index, (token, _)) in tokens.iter().enumerate().skip(index + 1) {
+        match token {
+            Token::If => nested += 1,
+            Token::Ident(name) if name == "then" => match nested.checked_sub(1) {
+                Some(outer) => nested = outer,
+                None => return Some(index),
+            },
+            _ => {}
+        }
+    }
+    None
+}
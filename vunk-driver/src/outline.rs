@@ -0,0 +1,227 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Top level items of a file, found with the lexer alone
+//!
+//! An item starts at a line that is not indented, once the previous item is complete (see
+//! [`is_complete`]). Comment lines and attributes like `@test` directly in front of an item belong
+//! to it.
+
+use std::ops::Range;
+
+use chumsky::Parser;
+use vunk_lexer::Spanned;
+use vunk_lexer::Token;
+
+use crate::repl::is_complete;
+use crate::source::byte_offset;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Item {
+    pub name: String,
+    pub kind: ItemKind,
+
+    /// Byte range of the item, including its comments
+    pub span: Range<usize>,
+
+    /// Byte range of the name of the item
+    pub name_span: Range<usize>,
+}
+
+impl Item {
+    /// The names of the attributes in front of the item, like `test` for `@test`
+    pub fn attributes<'a>(&self, code: &'a str) -> Vec<&'a str> {
+        code[self.span.start..self.name_span.start]
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix('@'))
+            .map(str::trim)
+            .collect()
+    }
+
+    /// Whether the item is marked with `pub`
+    pub fn is_public(&self, code: &str) -> bool {
+        code[self.span.start..self.name_span.start]
+            .lines()
+            .last()
+            .map(|line| line.trim_start().starts_with("pub "))
+            .unwrap_or(false)
+    }
+
+    /// What comes after the `:` of a declaration
+    pub fn declared_type<'a>(&self, code: &'a str) -> Option<&'a str> {
+        if self.kind != ItemKind::Declaration {
+            return None;
+        }
+
+        Some(
+            code[self.name_span.end..self.span.end]
+                .trim_start()
+                .trim_start_matches(':')
+                .trim(),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    /// `name: Type`
+    Declaration,
+
+    /// `name = expr`
+    Definition,
+
+    /// `type Name` or `enum Name`
+    Type,
+
+    /// Anything else, like `use` or `impl`, named after its first line
+    Other,
+}
+
+pub fn outline(code: &str) -> Vec<Item> {
+    chunks(code)
+        .into_iter()
+        .map(|span| {
+            let chunk = &code[span.clone()];
+            let (tokens, _) = vunk_lexer::lexer().parse_recovery(chunk);
+            match classify(chunk, &tokens.unwrap_or_default()) {
+                Some((name, kind, name_span)) => Item {
+                    name,
+                    kind,
+                    name_span: span.start + name_span.start..span.start + name_span.end,
+                    span,
+                },
+                None => {
+                    let (offset, first) = line_offsets(chunk)
+                        .find(|(_, line)| !line.starts_with(&['#', '@'][..]))
+                        .unwrap_or((0, chunk));
+                    Item {
+                        name: first.trim().to_string(),
+                        kind: ItemKind::Other,
+                        name_span: span.start + offset..span.start + offset + first.len(),
+                        span,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Name, kind and byte range of the name of the item `tokens` were lexed from
+pub fn classify(code: &str, tokens: &[Spanned<Token>]) -> Option<(String, ItemKind, Range<usize>)> {
+    let mut tokens = tokens
+        .iter()
+        .filter(|(token, _)| !matches!(token, Token::Comment(_) | Token::Pub))
+        .peekable();
+
+    // Attributes, like `@test` or `@cfg(not(debug))`, in front of the item
+    while let Some((Token::Ctrl('@'), _)) = tokens.peek() {
+        tokens.nth(1);
+        if let Some((Token::ParOpen, _)) = tokens.peek() {
+            let mut depth = 0;
+            for (token, _) in tokens.by_ref() {
+                match token {
+                    Token::ParOpen => depth += 1,
+                    Token::ParClose if depth == 1 => break,
+                    Token::ParClose => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let ((first, first_span), (second, second_span)) = (tokens.next()?, tokens.next()?);
+    let (name, kind, span) = match (first, second) {
+        (Token::Type | Token::Enum, Token::Ident(name)) => (name, ItemKind::Type, second_span),
+        (Token::Ident(name), Token::Declare) => (name, ItemKind::Declaration, first_span),
+        (Token::Ident(name), Token::Assign) => (name, ItemKind::Definition, first_span),
+        (Token::Ident(name), second)
+            if is_equation(second, tokens.by_ref().map(|(token, _)| token)) =>
+        {
+            (name, ItemKind::Definition, first_span)
+        }
+        _ => return None,
+    };
+
+    Some((
+        name.clone(),
+        kind,
+        byte_offset(code, span.start)..byte_offset(code, span.end),
+    ))
+}
+
+// Whether the tokens after the name of an item are the patterns and the `=` of an equation, like
+// `0 = 1` in `fact 0 = 1`
+fn is_equation<'a>(second: &'a Token, rest: impl Iterator<Item = &'a Token>) -> bool {
+    let mut depth = 0usize;
+    for token in std::iter::once(second).chain(rest) {
+        match token {
+            Token::ParOpen | Token::ListOpen | Token::BlockOpen => depth += 1,
+            Token::ParClose | Token::ListClose | Token::BlockClose => {
+                depth = match depth.checked_sub(1) {
+                    Some(depth) => depth,
+                    None => return false,
+                }
+            }
+            Token::Assign if depth == 0 => return true,
+            _ if depth > 0 => {}
+            Token::Ident(_)
+            | Token::Num(_)
+            | Token::Str(_)
+            | Token::Bytes(_)
+            | Token::Bool(_)
+            | Token::Ctrl(_) => {}
+            _ => return false,
+        }
+    }
+    false
+}
+
+// Byte ranges of the items, trimmed
+fn chunks(code: &str) -> Vec<Range<usize>> {
+    let mut chunks = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    let mut comments: Option<usize> = None;
+
+    for (offset, line) in line_offsets(code) {
+        let end = offset + line.trim_end().len();
+        let complete = current
+            .as_ref()
+            .map(|range| is_complete(&code[range.clone()]))
+            .unwrap_or(true);
+
+        if line.trim().is_empty() {
+            comments = None;
+            continue;
+        }
+
+        // Comments and attributes belong to the item that follows them
+        if line.starts_with(&['#', '@'][..]) && complete {
+            comments.get_or_insert(offset);
+            continue;
+        }
+
+        let starts_item = !line.starts_with(char::is_whitespace);
+        match current.as_mut() {
+            Some(range) if !(starts_item && complete) => {
+                range.end = end;
+                comments = None;
+            }
+            _ => {
+                chunks.extend(current.take());
+                current = Some(comments.take().unwrap_or(offset)..end);
+            }
+        }
+    }
+
+    chunks.extend(current);
+    chunks
+}
+
+fn line_offsets(code: &str) -> impl Iterator<Item = (usize, &str)> {
+    code.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line.trim_end_matches(&['\n', '\r'][..])))
+    })
+}
@@ -0,0 +1,169 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Items marked with `@deprecated`, and where other modules use them
+//!
+//! Every use of a deprecated item is reported by the `deprecated` lint, with the message of the
+//! attribute and where the item is defined. Like other lints, it can be allowed for an item, a
+//! module or the package, see [`crate::lint`].
+//!
+//! The uses are the names an explicit `use` or a glob brings into scope, the paths qualified
+//! with an alias, like `L.map`, and the names of the items of the module itself.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+
+use vunk_parser::ast::attribute::Meta;
+use vunk_parser::ast::attribute::MetaValue;
+
+use crate::attribute::attributes;
+use crate::outline::outline;
+use crate::outline::Item;
+use crate::package::imports;
+use crate::package::scope::Scope;
+use crate::package::uses;
+use crate::package::Graph;
+use crate::package::Resolved;
+use crate::source::Source;
+
+/// What the `@deprecated` attribute of an item says
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Deprecation {
+    /// The message, like `use area instead`
+    pub note: Option<String>,
+
+    /// The version the item was deprecated in
+    pub since: Option<String>,
+
+    /// Where the item is defined, like `lib/vector.vunk:3:1`
+    pub location: String,
+}
+
+/// A use of a deprecated item
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeprecatedUse {
+    /// The path of the item, like `geometry.vector.length`
+    pub path: String,
+
+    pub span: Range<usize>,
+    pub deprecation: Deprecation,
+}
+
+impl Deprecation {
+    /// The deprecation of an item of `source`, if it has a `@deprecated` attribute
+    pub fn of(source: &Source, item: &Item) -> Option<Deprecation> {
+        let attributes = attributes(&source.code, item).ok()?;
+        let (attribute, _) = attributes
+            .into_iter()
+            .find(|(attribute, _)| attribute.name == "deprecated")?;
+
+        let (line, column) = vunk_diagnostics::line_column(&source.code, item.name_span.start);
+        let mut deprecation = Deprecation {
+            location: format!("{}:{}:{}", source.name, line, column),
+            ..Deprecation::default()
+        };
+        for arg in attribute.args {
+            match arg {
+                Meta::Str(note) => deprecation.note = Some(note),
+                Meta::Named {
+                    name,
+                    value: Some(MetaValue::Str(value)),
+                } => match name.as_str() {
+                    "note" => deprecation.note = Some(value),
+                    "since" => deprecation.since = Some(value),
+                    _ => {}
+                },
+                Meta::Named { .. } => {}
+            }
+        }
+        Some(deprecation)
+    }
+}
+
+/// The deprecated items of a module, by name
+pub fn deprecated_items(source: &Source) -> BTreeMap<String, Deprecation> {
+    outline(&source.code)
+        .iter()
+        .filter_map(|item| Some((item.name.clone(), Deprecation::of(source, item)?)))
+        .collect()
+}
+
+/// The uses of deprecated items in `source`, a module of the package with the index `package`,
+/// in the order of the code
+///
+/// `exports` are the names the globs of the module bring into scope, see [`Scope::new`], and
+/// `deprecated` gives the deprecated items of a module file, if it is loaded.
+pub fn deprecated_uses(
+    graph: &Graph,
+    package: usize,
+    source: &Source,
+    exports: &BTreeMap<String, Vec<String>>,
+    mut deprecated: impl FnMut(&Path) -> Option<BTreeMap<String, Deprecation>>,
+) -> Vec<DeprecatedUse> {
+    let code = source.code.as_str();
+    let scope = Scope::new(code, exports);
+
+    let mut paths = Vec::new();
+    for (import, span) in scope.explicit_uses(code) {
+        paths.push((import.path.clone(), span));
+    }
+    // Paths qualified with an alias follow the imports
+    for qualified in uses(code).into_iter().skip(imports(code).len()) {
+        paths.push((qualified.path, qualified.span));
+    }
+    for used in scope.glob_uses(code) {
+        if let [glob] = used.globs.as_slice() {
+            let mut path = glob.path.clone();
+            path.push(used.name);
+            paths.push((path, used.span));
+        }
+    }
+
+    let mut found = Vec::new();
+    for (path, span) in paths {
+        let (used, module, item) = match graph.resolve(package, &path) {
+            Some(Resolved::Module {
+                package,
+                module,
+                item: Some(item),
+            }) => (package, module, item),
+            _ => continue,
+        };
+        let file = &graph.packages[used].modules[&module];
+        if file.display().to_string() == source.name {
+            continue;
+        }
+        if let Some(deprecation) = deprecated(file).and_then(|mut items| items.remove(&item)) {
+            found.push(DeprecatedUse {
+                path: path.join("."),
+                span,
+                deprecation,
+            });
+        }
+    }
+
+    // Uses of the module's own items, with their path like the uses of other modules
+    let own = deprecated_items(source);
+    let module = graph.packages[package]
+        .modules
+        .iter()
+        .find(|(_, file)| file.display().to_string() == source.name)
+        .map(|(module, _)| module);
+    for (name, span) in scope.local_uses(code) {
+        if let Some(deprecation) = own.get(&name) {
+            let path = match module {
+                Some(module) => format!("{}.{}", module, name),
+                None => name,
+            };
+            found.push(DeprecatedUse {
+                path,
+                span,
+                deprecation: deprecation.clone(),
+            });
+        }
+    }
+    found.sort_by_key(|used| used.span.start);
+    found
+}
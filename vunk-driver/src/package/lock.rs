@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The lockfile of a package, `vunk.lock`, next to its manifest
+//!
+//! It records the version of every dependency and, for git dependencies, the commit and a hash of
+//! the content, so that the same code is used on every machine:
+//!
+//! ```toml
+//! [[package]]
+//! name = "json"
+//! version = "1.2.0"
+//! source = "git+https://example.com/json.git#4b825dc642cb6eb9a060e54bf8d69288fbee4904"
+//! checksum = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+//! ```
+
+use std::path::Path;
+
+use semver::Version;
+
+use crate::error::DriverError;
+
+pub const FILE_NAME: &str = "vunk.lock";
+
+const HEADER: &str = "# This file is generated by vunk, do not edit it by hand\n\n";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: Version,
+
+    /// `git+<url>#<commit>` for git dependencies, nothing for local ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    /// Hash of the manifest and modules of git dependencies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl Lockfile {
+    /// The lockfile at `path`, if there is one
+    pub fn load(path: &Path) -> Result<Option<Lockfile>, DriverError> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|source| DriverError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(|error| DriverError::Manifest {
+                path: path.to_path_buf(),
+                message: error.to_string(),
+            })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), DriverError> {
+        let content = toml::to_string(self).map_err(|error| DriverError::Manifest {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })?;
+        std::fs::write(path, format!("{}{}", HEADER, content)).map_err(|source| {
+            DriverError::Write {
+                path: path.to_path_buf(),
+                source,
+            }
+        })
+    }
+
+    /// The commit a git repository is locked at
+    pub fn commit(&self, url: &str) -> Option<&str> {
+        let prefix = format!("git+{}#", url);
+        self.packages
+            .iter()
+            .filter_map(|package| package.source.as_deref())
+            .find_map(|source| source.strip_prefix(&prefix))
+    }
+}
+
+/// The `source` of a git dependency in the lockfile
+pub fn git_source(url: &str, commit: &str) -> String {
+    format!("git+{}#{}", url, commit)
+}
@@ -0,0 +1,237 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The manifest of a package, read from its `vunk.toml`
+//!
+//! ```toml
+//! [package]
+//! name = "shapes"
+//! version = "0.1.0"
+//! sources = ["src"] # the default
+//!
+//! [dependencies]
+//! geometry = { path = "../geometry", version = "^1.2" }
+//! json = { git = "https://example.com/json.git", rev = "v1.2.0" }
+//! http = { git = "https://example.com/http.git", version = "~0.4" }
+//!
+//! [features]
+//! default = ["json"]
+//! json = []
+//! server = ["json"] # enabling server enables json too
+//!
+//! [lints]
+//! shadowing = "warn" # or "allow" or "deny", see `crate::lint`
+//! ```
+//!
+//! A git dependency with a version requirement and without `rev` uses the highest tag matching the
+//! requirement, like `v0.4.2` or `0.4.2`.
+//!
+//! Other tables are allowed and left alone, so tools can keep their configuration in the manifest.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use semver::Version;
+pub use semver::VersionReq;
+
+use crate::error::DriverError;
+use crate::lint::Level;
+use crate::lint::Lint;
+
+pub const FILE_NAME: &str = "vunk.toml";
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+pub struct Manifest {
+    pub package: PackageInfo,
+
+    /// The dependencies, by the name they are used with
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+
+    /// The features of the package, with the features each one enables, see [`crate::cfg`]
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+
+    /// The levels of lints, by their name
+    #[serde(default)]
+    pub lints: BTreeMap<String, Level>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: Version,
+
+    /// Directories containing the modules of the package, relative to the manifest
+    #[serde(default = "default_sources")]
+    pub sources: Vec<PathBuf>,
+}
+
+/// Where a dependency comes from
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum Dependency {
+    /// A directory, relative to the manifest
+    Path {
+        path: PathBuf,
+        version: Option<VersionReq>,
+    },
+
+    /// A git repository, at a commit, tag or branch, or the default branch if there is neither a
+    /// `rev` nor a `version`
+    Git {
+        git: String,
+        rev: Option<String>,
+        version: Option<VersionReq>,
+    },
+}
+
+impl Dependency {
+    /// The version the dependency is required to have
+    pub fn version(&self) -> Option<&VersionReq> {
+        match self {
+            Dependency::Path { version, .. } | Dependency::Git { version, .. } => version.as_ref(),
+        }
+    }
+
+    /// The dependency as an inline table, as it is written in a manifest
+    pub fn to_toml(&self) -> String {
+        let string = |s: &str| toml::Value::String(s.to_string()).to_string();
+        let mut fields = match self {
+            Dependency::Path { path, .. } => {
+                vec![format!("path = {}", string(&path.to_string_lossy()))]
+            }
+            Dependency::Git { git, rev, .. } => std::iter::once(format!("git = {}", string(git)))
+                .chain(rev.iter().map(|rev| format!("rev = {}", string(rev))))
+                .collect(),
+        };
+        fields.extend(
+            self.version()
+                .map(|version| format!("version = {}", string(&version.to_string()))),
+        );
+        format!("{{ {} }}", fields.join(", "))
+    }
+}
+
+fn default_sources() -> Vec<PathBuf> {
+    vec![PathBuf::from("src")]
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Manifest, DriverError> {
+        let content = std::fs::read_to_string(path).map_err(|source| DriverError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let manifest: Manifest =
+            toml::from_str(&content).map_err(|error| DriverError::Manifest {
+                path: path.to_path_buf(),
+                message: error.to_string(),
+            })?;
+
+        // Package names are the first segment of paths in `use`
+        let names = std::iter::once(&manifest.package.name).chain(manifest.dependencies.keys());
+        for name in names {
+            if !is_package_name(name) {
+                return Err(DriverError::Manifest {
+                    path: path.to_path_buf(),
+                    message: format!("{} is not a valid package name", name),
+                });
+            }
+        }
+
+        let mut enabled = manifest.features.values().flatten();
+        if let Some(name) = enabled.find(|name| !manifest.features.contains_key(*name)) {
+            return Err(DriverError::Manifest {
+                path: path.to_path_buf(),
+                message: format!("{} is not a feature", name),
+            });
+        }
+
+        if let Some(name) = manifest
+            .lints
+            .keys()
+            .find(|name| Lint::from_name(name).is_none())
+        {
+            return Err(DriverError::Manifest {
+                path: path.to_path_buf(),
+                message: format!("{} is not a lint", name),
+            });
+        }
+
+        Ok(manifest)
+    }
+
+    /// Add a dependency to the manifest at `path`, keeping the rest of the file as it is
+    pub fn add_dependency(
+        path: &Path,
+        name: &str,
+        dependency: &Dependency,
+    ) -> Result<(), DriverError> {
+        let manifest = Manifest::load(path)?;
+        if manifest.dependencies.contains_key(name) {
+            return Err(DriverError::DependencyExists(name.to_string()));
+        }
+        if !is_package_name(name) {
+            return Err(DriverError::InvalidName(name.to_string()));
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|source| DriverError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let line = format!("{} = {}", name, dependency.to_toml());
+
+        let mut lines = content.lines().collect::<Vec<_>>();
+        match lines
+            .iter()
+            .position(|line| line.trim() == "[dependencies]")
+        {
+            Some(header) => {
+                // After the last entry of the table, before the blank lines separating it from
+                // the next one
+                let end = lines[header + 1..]
+                    .iter()
+                    .position(|line| line.trim_start().starts_with('['))
+                    .map(|end| header + 1 + end)
+                    .unwrap_or(lines.len());
+                let end = (header + 1..end)
+                    .rev()
+                    .find(|i| !lines[*i].trim().is_empty())
+                    .map(|i| i + 1)
+                    .unwrap_or(header + 1);
+                lines.insert(end, &line);
+            }
+            None => {
+                lines.extend(["", "[dependencies]", line.as_str()]);
+            }
+        }
+
+        let content = lines.join("\n") + "\n";
+        std::fs::write(path, content).map_err(|source| DriverError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// The directory containing the closest `vunk.toml`, starting at `path` and going up
+    pub fn find_root(path: &Path) -> Option<PathBuf> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        path.ancestors()
+            .find(|directory| directory.join(FILE_NAME).is_file())
+            .map(Path::to_path_buf)
+    }
+}
+
+pub(crate) fn is_package_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|c| c.is_ascii_alphabetic())
+        .unwrap_or(false)
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,621 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Packages and the graph of their dependencies
+//!
+//! A package is a directory with a `vunk.toml`, see [`manifest`]. Its modules are the `.vunk` files
+//! in its source directories, named after their path relative to the source directory, like
+//! `shapes.circle` for `src/shapes/circle.vunk`.
+//!
+//! A `use` refers to a module of another package by prefixing it with the name of the dependency:
+//! `use geometry.vector.length` uses `length` of the module `vector` of the dependency
+//! `geometry`. Paths starting with `Std` refer to the standard library. `use Std.List as L` gives
+//! the module an alias, so its items are referred to like `L.map`, and `use Std.List.*` brings
+//! all public items of the module into scope, see [`scope`].
+//!
+//! The versions and commits dependencies are resolved to are recorded in the lockfile of the root
+//! package, see [`lock`], and used from then on, until the package is updated.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+use semver::Version;
+use semver::VersionReq;
+use sha2::Digest;
+use sha2::Sha256;
+use vunk_diagnostics::suggest;
+use vunk_diagnostics::Applicability;
+use vunk_lexer::Token;
+
+use crate::error::DriverError;
+use crate::outline::outline;
+use crate::outline::ItemKind;
+use crate::package::lock::git_source;
+use crate::package::lock::LockedPackage;
+use crate::package::lock::Lockfile;
+use crate::package::manifest::Dependency;
+use crate::package::manifest::Manifest;
+use crate::source::tokens;
+use crate::testing::discover;
+
+pub mod deprecation;
+pub mod lock;
+pub mod manifest;
+pub mod modules;
+pub mod scaffold;
+pub mod scope;
+
+/// Directory git dependencies are checked out to, relative to the root package
+pub const GIT_DIRECTORY: &str = "target/git";
+
+#[derive(Clone, Debug)]
+pub struct Package {
+    pub name: String,
+    pub version: Version,
+
+    /// The directory containing the manifest
+    pub root: PathBuf,
+
+    /// The commit of a git dependency, with the URL of its repository
+    pub git: Option<(String, String)>,
+
+    /// Hash of the manifest and the modules, see [`checksum`]
+    pub checksum: String,
+
+    /// The modules, by their name, with the files they are defined in
+    pub modules: BTreeMap<String, PathBuf>,
+
+    /// Indices of the dependencies in the graph, by the name they are used with
+    pub dependencies: BTreeMap<String, usize>,
+
+    /// The features of the `[features]` table of the manifest
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+/// A package and all its direct and indirect dependencies
+#[derive(Clone, Debug)]
+pub struct Graph {
+    /// All packages, each after its dependencies, so the root package is the last one
+    pub packages: Vec<Package>,
+}
+
+/// A `use` of a file, with the segments of its path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+    pub path: Vec<String>,
+
+    /// Byte range of the path
+    pub span: Range<usize>,
+
+    /// The name after `as`, with its byte range
+    pub alias: Option<(String, Range<usize>)>,
+
+    /// Whether the path ends with `.*`, using all public items of the module
+    pub glob: bool,
+}
+
+impl Import {
+    /// The name the code refers to what is used by, the alias or the last segment of the path
+    ///
+    /// A glob does not have a name.
+    pub fn name(&self) -> Option<&str> {
+        if self.glob {
+            return None;
+        }
+        match &self.alias {
+            Some((alias, _)) => Some(alias),
+            None => self.path.last().map(String::as_str),
+        }
+    }
+}
+
+/// What a `use` refers to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Resolved {
+    Std,
+
+    /// An item of a module, or the whole module if there is no item
+    Module {
+        package: usize,
+        module: String,
+        item: Option<String>,
+    },
+}
+
+// State while loading a graph
+struct Loader<'a> {
+    root: &'a Path,
+    lockfile: Option<&'a Lockfile>,
+
+    /// Whether to fetch git dependencies that were cloned before
+    fetch: bool,
+}
+
+impl Graph {
+    /// Load the package at `root` and its dependencies, as they are locked
+    ///
+    /// Git dependencies are cloned into [`GIT_DIRECTORY`] of the root package, if they are not
+    /// there yet. Dependencies that are not locked yet are resolved and added to the lockfile.
+    pub fn load(root: &Path) -> Result<Graph, DriverError> {
+        Graph::load_with(root, false)
+    }
+
+    /// Load the package at `root` and resolve its dependencies again, ignoring the lockfile
+    pub fn update(root: &Path) -> Result<Graph, DriverError> {
+        Graph::load_with(root, true)
+    }
+
+    fn load_with(root: &Path, update: bool) -> Result<Graph, DriverError> {
+        let root = root.canonicalize().map_err(|source| DriverError::Read {
+            path: root.to_path_buf(),
+            source,
+        })?;
+        let lock_path = root.join(lock::FILE_NAME);
+        let locked = if update {
+            None
+        } else {
+            Lockfile::load(&lock_path)?
+        };
+
+        let mut graph = Graph {
+            packages: Vec::new(),
+        };
+        let loader = Loader {
+            root: &root,
+            lockfile: locked.as_ref(),
+            fetch: update,
+        };
+        graph.add(&root, &loader, &mut Vec::new())?;
+
+        // A commit always has the same content, unless the dependency was tampered with
+        let lockfile = graph.lockfile();
+        for package in lockfile.packages.iter() {
+            let before = locked
+                .iter()
+                .flat_map(|locked| locked.packages.iter())
+                .find(|before| before.source.is_some() && before.source == package.source);
+            if let Some(before) = before {
+                if before.checksum != package.checksum {
+                    return Err(DriverError::Checksum {
+                        name: package.name.clone(),
+                    });
+                }
+            }
+        }
+
+        if locked.as_ref() != Some(&lockfile) {
+            lockfile.save(&lock_path)?;
+        }
+        Ok(graph)
+    }
+
+    /// The lockfile describing the dependencies of the graph
+    pub fn lockfile(&self) -> Lockfile {
+        let dependencies = &self.packages[..self.packages.len() - 1];
+        Lockfile {
+            packages: dependencies
+                .iter()
+                .map(|package| LockedPackage {
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                    source: package
+                        .git
+                        .as_ref()
+                        .map(|(url, commit)| git_source(url, commit)),
+                    checksum: package.git.as_ref().map(|_| package.checksum.clone()),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn root(&self) -> &Package {
+        &self.packages[self.packages.len() - 1]
+    }
+
+    /// Resolve the path of a `use` in a module of the package with the index `package`
+    ///
+    /// A path without a package name refers to a module of the same package.
+    pub fn resolve(&self, package: usize, path: &[String]) -> Option<Resolved> {
+        if path.first().map(String::as_str) == Some("Std") {
+            return Some(Resolved::Std);
+        }
+
+        let (first, rest) = path.split_first()?;
+        let (package, path) = match self.packages[package].dependencies.get(first) {
+            Some(dependency) => (*dependency, rest),
+            None if *first == self.packages[package].name => (package, rest),
+            None => (package, path),
+        };
+
+        // The longest prefix that names a module, the rest is an item of it
+        let modules = &self.packages[package].modules;
+        (1..=path.len()).rev().find_map(|length| {
+            let module = path[..length].join(".");
+            let item = match &path[length..] {
+                [] => None,
+                [item] => Some(item.clone()),
+                _ => return None,
+            };
+            modules.contains_key(&module).then_some(Resolved::Module {
+                package,
+                module,
+                item,
+            })
+        })
+    }
+
+    /// Resolve a `use` like [`Graph::resolve`], where the path of a glob has to name a module
+    pub fn resolve_import(&self, package: usize, import: &Import) -> Option<Resolved> {
+        self.resolve(package, &import.path).filter(|resolved| {
+            !import.glob || !matches!(resolved, Resolved::Module { item: Some(_), .. })
+        })
+    }
+
+    /// The path a `use` that cannot be resolved was probably meant to be, if it is a misspelling of
+    /// a module of the package or of its dependencies
+    pub fn suggest(&self, package: usize, path: &[String]) -> Option<(String, Applicability)> {
+        let modules = &self.packages[package].modules;
+        let mut candidates = modules.keys().cloned().collect::<Vec<_>>();
+        for (name, dependency) in self.packages[package].dependencies.iter() {
+            let modules = self.packages[*dependency].modules.keys();
+            candidates.extend(modules.map(|module| format!("{}.{}", name, module)));
+        }
+
+        // Like in `resolve`, the module may be followed by an item
+        (path.len().saturating_sub(1).max(1)..=path.len())
+            .rev()
+            .find_map(|length| {
+                let module = path[..length].join(".");
+                let candidates = candidates.iter().map(String::as_str);
+                let (module, applicability) = suggest::closest(&module, candidates)?;
+                let path = std::iter::once(module.to_string())
+                    .chain(path[length..].iter().cloned())
+                    .collect::<Vec<_>>();
+                Some((path.join("."), applicability))
+            })
+    }
+
+    // Add the package at `directory` and its dependencies, returning its index
+    fn add(
+        &mut self,
+        directory: &Path,
+        loader: &Loader,
+        stack: &mut Vec<PathBuf>,
+    ) -> Result<usize, DriverError> {
+        if let Some(index) = self
+            .packages
+            .iter()
+            .position(|package| package.root == directory)
+        {
+            return Ok(index);
+        }
+
+        if stack.iter().any(|path| path == directory) {
+            let cycle = stack
+                .iter()
+                .chain(Some(&directory.to_path_buf()))
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>();
+            return Err(DriverError::DependencyCycle {
+                cycle: cycle.join(" -> "),
+            });
+        }
+
+        let manifest_path = directory.join(manifest::FILE_NAME);
+        let manifest = Manifest::load(&manifest_path)?;
+        stack.push(directory.to_path_buf());
+
+        let mut dependencies = BTreeMap::new();
+        for (name, dependency) in manifest.dependencies.iter() {
+            let (path, git) = match dependency {
+                Dependency::Path { path, .. } => (directory.join(path), None),
+                Dependency::Git { git, rev, version } => {
+                    let (path, commit) =
+                        checkout(loader, name, git, rev.as_deref(), version.as_ref())?;
+                    (path, Some((git.clone(), commit)))
+                }
+            };
+            let path = path.canonicalize().map_err(|source| DriverError::Read {
+                path: path.clone(),
+                source,
+            })?;
+
+            let index = self.add(&path, loader, stack)?;
+            let package = &mut self.packages[index];
+            if let Some(requirement) = dependency.version() {
+                if !requirement.matches(&package.version) {
+                    return Err(DriverError::UnsatisfiedVersion {
+                        name: name.clone(),
+                        requirement: requirement.to_string(),
+                        version: package.version.to_string(),
+                    });
+                }
+            }
+            package.git = package.git.take().or(git);
+            dependencies.insert(name.clone(), index);
+        }
+
+        stack.pop();
+
+        let mut modules = BTreeMap::new();
+        for sources in manifest.package.sources.iter() {
+            let sources = directory.join(sources);
+            if !sources.is_dir() {
+                continue;
+            }
+            for file in discover(std::slice::from_ref(&sources))? {
+                modules.insert(module_name(&sources, &file), file);
+            }
+        }
+
+        self.packages.push(Package {
+            name: manifest.package.name,
+            version: manifest.package.version,
+            root: directory.to_path_buf(),
+            git: None,
+            checksum: checksum(&manifest_path, &modules)?,
+            modules,
+            dependencies,
+            features: manifest.features,
+        });
+        Ok(self.packages.len() - 1)
+    }
+}
+
+/// The name of the module defined in `file`, relative to the source directory `sources`
+pub fn module_name(sources: &Path, file: &Path) -> String {
+    file.strip_prefix(sources)
+        .unwrap_or(file)
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// `path` relative to the directory `base`, both being absolute
+pub fn relative_path(base: &Path, path: &Path) -> PathBuf {
+    let common = base
+        .components()
+        .zip(path.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let up = base.components().skip(common).map(|_| Path::new(".."));
+    let down = path
+        .components()
+        .skip(common)
+        .map(|component| Path::new(component.as_os_str()));
+    up.chain(down).collect()
+}
+
+/// The paths of all `use`s of the code
+pub fn imports(code: &str) -> Vec<Import> {
+    outline(code)
+        .into_iter()
+        .filter(|item| item.kind == ItemKind::Other)
+        .filter_map(|item| {
+            let tokens = tokens(&code[item.span.clone()]);
+            let start = tokens.iter().position(|(token, _)| *token == Token::Use)?;
+
+            let mut path = Vec::new();
+            let mut span = None::<Range<usize>>;
+            let mut separated = false;
+            let mut rest = tokens[start + 1..].iter();
+            while let Some((token, token_span)) = rest.next() {
+                match token {
+                    // `as` is not a keyword, a name after a segment is an alias
+                    Token::Ident(name) if name == "as" && !path.is_empty() => {
+                        let alias = match rest.next() {
+                            Some((Token::Ident(alias), alias_span)) => {
+                                let start = item.span.start + alias_span.start;
+                                Some((alias.clone(), start..start + alias_span.len()))
+                            }
+                            _ => None,
+                        };
+                        return Some(Import {
+                            path,
+                            span: span?,
+                            alias,
+                            glob: false,
+                        });
+                    }
+                    Token::Op(op) if op == "*" && separated => {
+                        let start = span?.start;
+                        return Some(Import {
+                            path,
+                            span: start..item.span.start + token_span.end,
+                            alias: None,
+                            glob: true,
+                        });
+                    }
+                    Token::Ident(name) => path.push(name.clone()),
+                    Token::Separator => {}
+                    _ => break,
+                }
+                separated = *token == Token::Separator;
+                let path_start = span.as_ref().map(|span| span.start);
+                let path_start = path_start.unwrap_or(item.span.start + token_span.start);
+                span = Some(path_start..item.span.start + token_span.end);
+            }
+
+            Some(Import {
+                path,
+                span: span?,
+                alias: None,
+                glob: false,
+            })
+        })
+        .collect()
+}
+
+/// The `use`s of the code, followed by the paths that refer to items through the alias of a
+/// `use`, like `L.map` after `use Std.List as L`
+///
+/// Paths through an alias are expanded to the path they refer to, `Std.List.map` in the example,
+/// with the byte range of the path in the code and without an alias.
+pub fn uses(code: &str) -> Vec<Import> {
+    let imports = imports(code);
+    let tokens = tokens(code);
+    let in_use = |offset: usize| {
+        imports.iter().any(|import| {
+            import.span.contains(&offset)
+                || matches!(&import.alias, Some((_, span)) if span.contains(&offset))
+        })
+    };
+
+    let mut qualified = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let aliased = match &tokens[i] {
+            (Token::Ident(name), span) if !in_use(span.start) => imports
+                .iter()
+                .find(|import| matches!(&import.alias, Some((alias, _)) if alias == name)),
+            _ => None,
+        };
+        let import = match aliased {
+            Some(import) => import,
+            None => {
+                i += 1;
+                continue;
+            }
+        };
+
+        // The segments following the alias, each after a separator
+        let mut path = import.path.clone();
+        let mut end = tokens[i].1.end;
+        let mut next = i + 1;
+        while let (Some((Token::Separator, _)), Some((Token::Ident(segment), span))) =
+            (tokens.get(next), tokens.get(next + 1))
+        {
+            path.push(segment.clone());
+            end = span.end;
+            next += 2;
+        }
+        if next > i + 1 {
+            qualified.push(Import {
+                path,
+                span: tokens[i].1.start..end,
+                alias: None,
+                glob: false,
+            });
+        }
+        i = next;
+    }
+
+    imports.into_iter().chain(qualified).collect()
+}
+
+/// The names the code of a module declares or defines, which a `use` can refer to
+pub fn items(code: &str) -> Vec<String> {
+    outline(code)
+        .into_iter()
+        .filter(|item| item.kind != ItemKind::Other)
+        .map(|item| item.name)
+        .collect()
+}
+
+/// The names of the public items of the code of a module, which a glob `use` brings into scope
+pub fn exports(code: &str) -> Vec<String> {
+    outline(code)
+        .into_iter()
+        .filter(|item| item.kind != ItemKind::Other && item.is_public(code))
+        .map(|item| item.name)
+        .collect()
+}
+
+/// Hash of the manifest and the modules of a package
+pub fn checksum(
+    manifest: &Path,
+    modules: &BTreeMap<String, PathBuf>,
+) -> Result<String, DriverError> {
+    let mut hasher = Sha256::new();
+    let files = std::iter::once((manifest::FILE_NAME, manifest)).chain(
+        modules
+            .iter()
+            .map(|(name, file)| (name.as_str(), file.as_path())),
+    );
+    for (name, file) in files {
+        let content = std::fs::read(file).map_err(|source| DriverError::Read {
+            path: file.to_path_buf(),
+            source,
+        })?;
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update(&content);
+        hasher.update([0]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Clone a git repository into the directory for git dependencies and check out the commit it is
+// locked at, `rev` or the highest version matching `version`, returning the directory and the
+// commit
+fn checkout(
+    loader: &Loader,
+    name: &str,
+    url: &str,
+    rev: Option<&str>,
+    version: Option<&VersionReq>,
+) -> Result<(PathBuf, String), DriverError> {
+    let parent = loader.root.join(GIT_DIRECTORY);
+    let directory = parent.join(name);
+    if !directory.is_dir() {
+        std::fs::create_dir_all(&parent).map_err(|source| DriverError::Write {
+            path: parent.clone(),
+            source,
+        })?;
+        git(url, &["clone", "--quiet", url, name], &parent)?;
+    } else if loader.fetch {
+        git(url, &["fetch", "--quiet", "--tags", "origin"], &directory)?;
+    }
+
+    let locked = loader.lockfile.and_then(|lockfile| lockfile.commit(url));
+    let target = match (locked, rev, version) {
+        (Some(commit), _, _) => commit.to_string(),
+        (None, Some(rev), _) => rev.to_string(),
+        (None, None, Some(version)) => git(url, &["tag", "--list"], &directory)?
+            .lines()
+            .filter_map(|tag| Some((tag, Version::parse(tag.trim_start_matches('v')).ok()?)))
+            .filter(|(_, tag_version)| version.matches(tag_version))
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+            .map(|(tag, _)| tag.to_string())
+            .ok_or_else(|| DriverError::Git {
+                url: url.to_string(),
+                message: format!("there is no tag with a version matching {}", version),
+            })?,
+        (None, None, None) => "origin/HEAD".to_string(),
+    };
+
+    git(
+        url,
+        &["checkout", "--quiet", "--detach", &target],
+        &directory,
+    )?;
+    let commit = git(url, &["rev-parse", "HEAD"], &directory)?;
+    Ok((directory, commit.trim().to_string()))
+}
+
+// Run git, returning what it prints
+fn git(url: &str, args: &[&str], directory: &Path) -> Result<String, DriverError> {
+    tracing::debug!("Running git {}", args.join(" "));
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(directory)
+        .output()
+        .map_err(|error| DriverError::Git {
+            url: url.to_string(),
+            message: error.to_string(),
+        })?;
+
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    }
+
+    Err(DriverError::Git {
+        url: url.to_string(),
+        message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
@@ -0,0 +1,269 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Which modules use which, for `vunk graph --modules`
+//!
+//! Modules of the root package are named as in the package, modules of dependencies are prefixed
+//! with the name of their package, like `geometry.vector`. Uses of the standard library and uses
+//! that cannot be resolved are left out, checking the package reports the latter.
+//!
+//! Modules that use each other, directly or through other modules, form a cycle. Cycles are
+//! flagged in both outputs, as they are what makes a package hard to split up.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::package::uses;
+use crate::package::Graph;
+use crate::package::Resolved;
+use crate::source::Source;
+
+/// The modules of a package and its dependencies, and the uses between them
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModuleGraph {
+    /// The names of the modules, sorted
+    pub modules: Vec<String>,
+
+    /// The indices of the modules that use each other, the using one first
+    pub uses: BTreeSet<(usize, usize)>,
+
+    /// The index of the source and the byte range of the first `use` of every use
+    pub spans: BTreeMap<(usize, usize), (usize, Range<usize>)>,
+}
+
+impl ModuleGraph {
+    /// The graph of the modules of `graph`, with `sources` holding the code of every module of
+    /// every package, in the order of the packages and their modules
+    pub fn new(graph: &Graph, sources: &[Source]) -> Self {
+        let root = graph.packages.len() - 1;
+        let name = |package: usize, module: &str| {
+            if package == root {
+                module.to_string()
+            } else {
+                format!("{}.{}", graph.packages[package].name, module)
+            }
+        };
+
+        let files = graph
+            .packages
+            .iter()
+            .enumerate()
+            .flat_map(|(index, package)| package.modules.keys().map(move |module| (index, module)))
+            .collect::<Vec<_>>();
+
+        let mut edges = Vec::new();
+        for (index, ((package, module), source)) in files.iter().zip(sources).enumerate() {
+            let (package, module) = (*package, module.as_str());
+            for import in uses(&source.code) {
+                if let Some(Resolved::Module {
+                    package: used,
+                    module: used_module,
+                    ..
+                }) = graph.resolve_import(package, &import)
+                {
+                    let span = (index, import.span);
+                    edges.push((name(package, module), name(used, &used_module), span));
+                }
+            }
+        }
+
+        let mut modules = files
+            .iter()
+            .map(|(package, module)| name(*package, module))
+            .collect::<Vec<_>>();
+        modules.sort();
+        let index = |module: &str| modules.binary_search_by(|name| name.as_str().cmp(module));
+
+        let mut spans = BTreeMap::new();
+        for (from, to, span) in edges {
+            if let (Ok(from), Ok(to)) = (index(from.as_str()), index(to.as_str())) {
+                spans.entry((from, to)).or_insert(span);
+            }
+        }
+        ModuleGraph {
+            uses: spans.keys().copied().collect(),
+            modules,
+            spans,
+        }
+    }
+
+    /// The shortest cycle through the first module of the first cycle, as the modules it goes
+    /// through, each using the next one and the last one using the first one
+    pub fn cycle(&self) -> Option<Vec<usize>> {
+        let first = self.cycles().into_iter().next()?;
+        let start = self.modules.binary_search(&first[0]).ok()?;
+
+        // A breadth first search for the way back to the start
+        let mut previous = vec![None; self.modules.len()];
+        let mut queue = VecDeque::from([start]);
+        while let Some(module) = queue.pop_front() {
+            for &(_, used) in self.uses.range((module, 0)..(module + 1, 0)) {
+                if used == start {
+                    let mut cycle = vec![module];
+                    while let Some(before) = previous[cycle[cycle.len() - 1]] {
+                        cycle.push(before);
+                    }
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+                if previous[used].is_none() && used != start {
+                    previous[used] = Some(module);
+                    queue.push_back(used);
+                }
+            }
+        }
+        None
+    }
+
+    /// The modules that use each other, directly or through other modules, as groups sorted by
+    /// name
+    ///
+    /// A module that uses itself is a cycle too.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let mut cycles = self
+            .components()
+            .into_iter()
+            .filter(|component| match component[..] {
+                [module] => self.uses.contains(&(module, module)),
+                _ => true,
+            })
+            .map(|component| {
+                let mut names = component
+                    .iter()
+                    .map(|module| self.modules[*module].clone())
+                    .collect::<Vec<_>>();
+                names.sort();
+                names
+            })
+            .collect::<Vec<_>>();
+        cycles.sort();
+        cycles
+    }
+
+    /// The graph in the DOT format, with the uses in cycles in red
+    pub fn dot(&self) -> String {
+        let cyclic = self.cyclic();
+        let mut dot = String::from("digraph modules {\n");
+        for module in self.modules.iter() {
+            dot.push_str(&format!("    {:?};\n", module));
+        }
+        for (from, to) in self.uses.iter() {
+            let attributes = if cyclic.contains(&(*from, *to)) {
+                " [color=red]"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "    {:?} -> {:?}{};\n",
+                self.modules[*from], self.modules[*to], attributes
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as JSON, with the modules, the uses and the cycles
+    pub fn json(&self) -> String {
+        let cyclic = self.cyclic();
+        let uses = self
+            .uses
+            .iter()
+            .map(|(from, to)| {
+                serde_json::json!({
+                    "from": self.modules[*from],
+                    "to": self.modules[*to],
+                    "cycle": cyclic.contains(&(*from, *to)),
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({
+            "modules": self.modules,
+            "uses": uses,
+            "cycles": self.cycles(),
+        })
+        .to_string()
+    }
+
+    // The uses between modules of the same cycle
+    fn cyclic(&self) -> BTreeSet<(usize, usize)> {
+        let mut component = vec![0; self.modules.len()];
+        for (index, modules) in self.components().iter().enumerate() {
+            for module in modules {
+                component[*module] = index;
+            }
+        }
+        self.uses
+            .iter()
+            .filter(|(from, to)| component[*from] == component[*to])
+            .copied()
+            .collect()
+    }
+
+    // The strongly connected components, with Tarjan's algorithm
+    fn components(&self) -> Vec<Vec<usize>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            next: 0,
+            index: vec![None; self.modules.len()],
+            low: vec![0; self.modules.len()],
+            stack: Vec::new(),
+            on_stack: vec![false; self.modules.len()],
+            components: Vec::new(),
+        };
+        for module in 0..self.modules.len() {
+            if tarjan.index[module].is_none() {
+                tarjan.visit(module);
+            }
+        }
+        tarjan.components
+    }
+}
+
+struct Tarjan<'a> {
+    graph: &'a ModuleGraph,
+    next: usize,
+    index: Vec<Option<usize>>,
+    low: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, module: usize) {
+        self.index[module] = Some(self.next);
+        self.low[module] = self.next;
+        self.next += 1;
+        self.stack.push(module);
+        self.on_stack[module] = true;
+
+        let used = self.graph.uses.range((module, 0)..(module + 1, 0));
+        for (_, used) in used.copied().collect::<Vec<_>>() {
+            match self.index[used] {
+                None => {
+                    self.visit(used);
+                    self.low[module] = self.low[module].min(self.low[used]);
+                }
+                Some(index) if self.on_stack[used] => {
+                    self.low[module] = self.low[module].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.low[module]) == self.index[module] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == module {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The files of a new package
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::DriverError;
+use crate::format::config;
+use crate::package::manifest;
+use crate::package::manifest::is_package_name;
+
+const MAIN: &str = "\
+# The entry point of {name}
+
+greeting: (String) -> String
+greeting = (name: String) -> Std.String.concat [\"Hello, \" name]
+
+pub main: IO ()
+pub main = Std.IO.println (greeting \"World\")
+
+@test
+greeting_says_hello = assertEq (greeting \"vunk\") \"Hello, vunk\"
+";
+
+const LIB: &str = "\
+# The library {name}
+
+# The sum of two numbers
+#
+# ```
+# assertEq (add 1 2) 3
+# ```
+pub add: (i64, i64) -> i64
+pub add = (a: i64, b: i64) -> a + b
+
+@test
+add_is_commutative = assertEq (add 2 5) (add 5 2)
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A program, with a `main` in `src/main.vunk`
+    Binary,
+
+    /// A library, in `src/lib.vunk`
+    Library,
+}
+
+/// The files of a new package, relative to its directory
+pub fn files(name: &str, kind: Kind) -> Vec<(PathBuf, String)> {
+    let manifest = format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name);
+    let formatter = "max_width = 100\nindent = 4\n".to_string();
+    let (module, code) = match kind {
+        Kind::Binary => ("src/main.vunk", MAIN),
+        Kind::Library => ("src/lib.vunk", LIB),
+    };
+
+    vec![
+        (PathBuf::from(manifest::FILE_NAME), manifest),
+        (PathBuf::from(config::FILE_NAME), formatter),
+        (PathBuf::from(module), code.replace("{name}", name)),
+        // Git dependencies are checked out to `target/git`
+        (PathBuf::from(".gitignore"), "/target\n".to_string()),
+    ]
+}
+
+/// Create a package named `name` in `directory`, which may exist, but must not contain any of the
+/// files of the package yet
+pub fn create(directory: &Path, name: &str, kind: Kind) -> Result<(), DriverError> {
+    if !is_package_name(name) {
+        return Err(DriverError::InvalidName(name.to_string()));
+    }
+
+    let files = files(name, kind);
+    let existing = files
+        .iter()
+        .map(|(path, _)| directory.join(path))
+        .find(|path| path.exists());
+    if let Some(path) = existing {
+        return Err(DriverError::AlreadyExists { path });
+    }
+
+    for (path, content) in files {
+        let path = directory.join(path);
+        std::fs::create_dir_all(path.parent().unwrap_or(directory))
+            .and_then(|_| std::fs::write(&path, content))
+            .map_err(|source| DriverError::Write {
+                path: path.clone(),
+                source,
+            })?;
+    }
+    Ok(())
+}
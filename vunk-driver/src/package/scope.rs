@@ -0,0 +1,285 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The names a module brings into scope with its `use`s
+//!
+//! A name of a module refers to, from the first that applies to the last:
+//!
+//! * an item of the module itself
+//! * what a `use` names explicitly, by the last segment of its path or by its alias
+//! * a public item of a module used with a glob, like `use Std.List.*`
+//! * an item of the prelude, [`PRELUDE`], like `Some` or `assertEq`
+//!
+//! So adding a glob never changes what a name already refers to, and neither does adding an item
+//! to the prelude. A name that more than one glob brings into scope is ambiguous, which is an
+//! error where the name is used rather than at the globs: modules with items of the same name can
+//! be used together as long as those are not.
+//!
+//! A module opts out of the prelude with `@no_prelude` in front of its first item.
+//!
+//! Until there is a parser, the uses of names are found like the lints find bindings, see
+//! [`crate::lint::rules`].
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use vunk_lexer::Token;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::builtin::PRELUDE;
+
+use crate::lint::rules::bindings;
+use crate::lint::rules::code_tokens;
+use crate::outline::outline;
+use crate::outline::Item;
+use crate::outline::ItemKind;
+use crate::package::imports;
+use crate::package::items;
+use crate::package::Import;
+
+/// The names in scope in a module
+#[derive(Clone, Debug)]
+pub struct Scope {
+    /// The items of the module and the names of its explicit `use`s
+    names: BTreeSet<String>,
+
+    /// The explicit `use`s, by the name they bring into scope
+    explicit: BTreeMap<String, Import>,
+
+    /// The globs, with the names they bring into scope if those are known
+    globs: Vec<(Import, Option<BTreeSet<String>>)>,
+
+    /// The items of the prelude, with the fully qualified names they refer to, none if the module
+    /// opted out
+    prelude: BTreeMap<String, String>,
+}
+
+/// A name that refers to what globs brought into scope
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlobUse {
+    pub name: String,
+    pub span: Range<usize>,
+
+    /// The globs bringing the name into scope, it is ambiguous if there is more than one
+    pub globs: Vec<Import>,
+}
+
+impl Scope {
+    /// The scope of the module with the code `code`, with the names the globs bring into scope by
+    /// the path of the module they use
+    ///
+    /// Globs missing from `exports` are of modules that are not known, they are left out of
+    /// [`Scope::unused_globs`].
+    pub fn new(code: &str, exports: &BTreeMap<String, Vec<String>>) -> Self {
+        let mut names = items(code).into_iter().collect::<BTreeSet<_>>();
+        let mut explicit = BTreeMap::new();
+        let mut globs = Vec::new();
+        for import in imports(code) {
+            if import.glob {
+                let exported = exports.get(&import.path.join("."));
+                let exported = exported.map(|names| names.iter().cloned().collect());
+                globs.push((import, exported));
+            } else if let Some(name) = import.name() {
+                names.insert(name.to_string());
+                explicit.insert(name.to_string(), import);
+            }
+        }
+
+        let prelude = if uses_prelude(code) {
+            let builtins = Builtins::std();
+            let prelude = builtins.prelude();
+            prelude
+                .map(|(name, qualified)| (name.to_string(), qualified.to_string()))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+        Scope {
+            names,
+            explicit,
+            globs,
+            prelude,
+        }
+    }
+
+    /// The globs that bring `name` into scope, none if something else does
+    pub fn lookup(&self, name: &str) -> Vec<&Import> {
+        if self.names.contains(name) {
+            return Vec::new();
+        }
+        self.globs
+            .iter()
+            .filter(|(_, exported)| matches!(exported, Some(names) if names.contains(name)))
+            .map(|(import, _)| import)
+            .collect()
+    }
+
+    /// The fully qualified name of the item of the prelude that `name` refers to, if nothing else
+    /// brings it into scope
+    pub fn prelude(&self, name: &str) -> Option<&str> {
+        let in_glob = self
+            .globs
+            .iter()
+            .any(|(_, exported)| matches!(exported, Some(names) if names.contains(name)));
+        if self.names.contains(name) || in_glob {
+            return None;
+        }
+        self.prelude.get(name).map(String::as_str)
+    }
+
+    /// The names used in `code`, the code of the module, that refer to what globs brought into
+    /// scope
+    ///
+    /// Names in paths, like the segments of `use`s, names that are bound, like parameters, and
+    /// names of record fields are not uses.
+    pub fn glob_uses(&self, code: &str) -> Vec<GlobUse> {
+        let mut uses = Vec::new();
+        for (name, span) in name_uses(code) {
+            let globs = self.lookup(&name);
+            if !globs.is_empty() {
+                uses.push(GlobUse {
+                    name,
+                    span,
+                    globs: globs.into_iter().cloned().collect(),
+                });
+            }
+        }
+        uses
+    }
+
+    /// The names used in `code`, the code of the module, that refer to what explicit `use`s
+    /// brought into scope, with the `use` they refer to
+    ///
+    /// Like for [`Scope::glob_uses`], the `use`s themselves are not uses of the names.
+    pub fn explicit_uses(&self, code: &str) -> Vec<(&Import, Range<usize>)> {
+        let items = items(code);
+        name_uses(code)
+            .into_iter()
+            .filter(|(name, _)| !items.contains(name))
+            .filter_map(|(name, span)| self.explicit.get(&name).map(|import| (import, span)))
+            .collect()
+    }
+
+    /// The names used in `code`, the code of the module, that refer to items of the module itself
+    ///
+    /// The names the items are defined with are not uses of them.
+    pub fn local_uses(&self, code: &str) -> Vec<(String, Range<usize>)> {
+        let items = outline(code);
+        let is_item = |name: &str| {
+            items
+                .iter()
+                .any(|item| item.kind != ItemKind::Other && item.name == name)
+        };
+        name_uses(code)
+            .into_iter()
+            .filter(|(name, span)| {
+                is_item(name) && !items.iter().any(|item| item.name_span == *span)
+            })
+            .collect()
+    }
+
+    /// The globs none of whose names are used in `code`, the code of the module
+    pub fn unused_globs(&self, code: &str) -> Vec<&Import> {
+        let uses = self.glob_uses(code);
+        self.globs
+            .iter()
+            .filter(|(import, exported)| {
+                exported.is_some() && !uses.iter().any(|used| used.globs.contains(import))
+            })
+            .map(|(import, _)| import)
+            .collect()
+    }
+}
+
+// The names used in code, without names in paths, bound names, names of record fields and the
+// names in `use`s
+fn name_uses(code: &str) -> Vec<(String, Range<usize>)> {
+    let tokens = code_tokens(code);
+    let items = outline(code);
+    let is_use = |item: &Item| {
+        item.kind == ItemKind::Other
+            && (item.name.starts_with("use ") || item.name.starts_with("pub use "))
+    };
+    let bound = items
+        .iter()
+        .map(|item| {
+            let bindings = bindings(item, &tokens).into_iter();
+            bindings
+                .map(|binding| binding.name)
+                .collect::<BTreeSet<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut uses = Vec::new();
+    for (index, (token, span)) in tokens.iter().enumerate() {
+        let name = match token {
+            Token::Ident(name) => name,
+            _ => continue,
+        };
+        let previous = index.checked_sub(1).map(|previous| &tokens[previous].0);
+        let next = tokens.get(index + 1).map(|(token, _)| token);
+        if previous == Some(&Token::Separator)
+            || matches!(
+                next,
+                Some(Token::Separator | Token::Declare | Token::Assign)
+            )
+        {
+            continue;
+        }
+
+        let item = items
+            .iter()
+            .position(|item| item.span.contains(&span.start));
+        if item
+            .map(|item| bound[item].contains(name) || is_use(&items[item]))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        uses.push((name.clone(), span.clone()));
+    }
+    uses
+}
+
+/// Whether the prelude is in scope in the module with the code `code`, which it is unless the
+/// first item of the module has the attribute `@no_prelude`
+pub fn uses_prelude(code: &str) -> bool {
+    let items = outline(code);
+    let first = items.first();
+    !first
+        .map(|item| item.attributes(code).contains(&"no_prelude"))
+        .unwrap_or(false)
+}
+
+/// The names the globs of `code` using modules of the standard library bring into scope, by the
+/// path of the module
+pub fn std_exports(code: &str) -> BTreeMap<String, Vec<String>> {
+    let globs = imports(code)
+        .into_iter()
+        .filter(|import| import.glob && import.path.first().map(String::as_str) == Some("Std"))
+        .collect::<Vec<_>>();
+    if globs.is_empty() {
+        return BTreeMap::new();
+    }
+
+    let builtins = Builtins::std();
+    globs
+        .into_iter()
+        .map(|import| {
+            let module = import.path.join(".");
+            let prefix = format!("{}.", module);
+            if module == PRELUDE {
+                let names = builtins.prelude().map(|(name, _)| name.to_string());
+                return (module, names.collect());
+            }
+            let names = builtins
+                .iter()
+                .filter_map(|builtin| builtin.name.strip_prefix(&prefix))
+                .filter(|name| !name.contains('.'))
+                .map(str::to_string)
+                .collect();
+            (module, names)
+        })
+        .collect()
+}
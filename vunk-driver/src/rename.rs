@@ -0,0 +1,120 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Renaming a name everywhere it is used
+//!
+//! A name bound inside a definition, like a parameter or a `let` binding, is renamed within that
+//! definition. A top level name is renamed in all documents, except where a definition binds the
+//! same name itself. Qualified members like `name` in `Module.name` are never renamed, as there
+//! is no resolution of modules yet.
+
+use std::ops::Range;
+
+use vunk_lexer::Token;
+
+use crate::error::DriverError;
+use crate::index::Document;
+use crate::outline::ItemKind;
+use crate::source::tokens;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Index of the document in the documents passed to [`rename`]
+    pub document: usize,
+
+    /// Byte range to replace
+    pub span: Range<usize>,
+
+    pub new_text: String,
+}
+
+/// The edits renaming the name at `offset` in `documents[document]` to `new_name`
+pub fn rename(
+    documents: &[&str],
+    document: usize,
+    offset: usize,
+    new_name: &str,
+) -> Result<Vec<TextEdit>, DriverError> {
+    if !matches!(&tokens(new_name)[..], [(Token::Ident(name), _)] if name == new_name) {
+        return Err(DriverError::InvalidName(new_name.to_string()));
+    }
+
+    let documents = documents
+        .iter()
+        .map(|code| Document::new(code))
+        .collect::<Vec<_>>();
+
+    let current = documents.get(document).ok_or(DriverError::NoName)?;
+    let (index, name) = current
+        .tokens
+        .iter()
+        .enumerate()
+        .find_map(|(i, (token, span))| match token {
+            Token::Ident(name) if span.start <= offset && offset <= span.end => Some((i, name)),
+            _ => None,
+        })
+        .ok_or(DriverError::NoName)?;
+
+    let conflict = |reason: String| DriverError::RenameConflict {
+        name: new_name.to_string(),
+        reason,
+    };
+
+    let edit = |document: usize, span: &Range<usize>| TextEdit {
+        document,
+        span: span.clone(),
+        new_text: new_name.to_string(),
+    };
+
+    // A local name of the enclosing definition
+    if let Some(item) = current.item_at(index) {
+        if current.locals(item).contains(name.as_str()) && !current.is_member(index) {
+            if current.idents(item).any(|(_, ident)| ident == new_name) {
+                return Err(conflict(format!("it is already used in {}", item.name)));
+            }
+
+            return Ok(current
+                .idents(item)
+                .filter(|(i, ident)| *ident == name && !current.is_member(*i))
+                .map(|(i, _)| edit(document, &current.tokens[i].1))
+                .collect());
+        }
+    }
+
+    // A top level name
+    let defined = |name: &str| {
+        documents
+            .iter()
+            .flat_map(|document| document.items.iter())
+            .any(|item| item.name == *name && item.kind != ItemKind::Other)
+    };
+    if current.is_member(index) || !defined(name.as_str()) {
+        return Err(DriverError::UnknownName(name.clone()));
+    }
+    if defined(new_name) {
+        return Err(conflict("it is already defined".to_string()));
+    }
+
+    let mut edits = Vec::new();
+    for (i, document) in documents.iter().enumerate() {
+        for item in document.items.iter() {
+            let locals = document.locals(item);
+            if locals.contains(name.as_str()) {
+                continue;
+            }
+
+            let mut occurrences = document
+                .idents(item)
+                .filter(|(t, ident)| *ident == name && !document.is_member(*t))
+                .peekable();
+            if occurrences.peek().is_some() && locals.contains(new_name) {
+                return Err(conflict(format!("it would be shadowed in {}", item.name)));
+            }
+
+            edits.extend(occurrences.map(|(t, _)| edit(i, &document.tokens[t].1)));
+        }
+    }
+
+    Ok(edits)
+}
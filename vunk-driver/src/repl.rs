@@ -0,0 +1,275 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! State of an interactive session
+//!
+//! Every input is either a definition (a declaration `name: Type`, a definition `name = expr` or a
+//! type definition), which is added to the session, or an expression, which is evaluated in the
+//! context of all definitions made so far.
+//!
+//! Inputs starting with `:` are commands:
+//!
+//! * `:t expr` shows the type of an expression
+//! * `:i name` shows the declaration of a name, including its doc comment
+//! * `:inspect expr` shows the value of an expression as a tree, see [`vunk_runtime::inspect`]
+//! * `:load file.vunk` adds all definitions of a file to the session
+//! * `:reset` removes all definitions
+
+use std::path::Path;
+
+use chumsky::Parser;
+use vunk_lexer::Token;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::inspect::inspect;
+use vunk_runtime::inspect::Limits;
+use vunk_runtime::inspect::Node;
+use vunk_runtime::value::Value;
+
+use crate::context::RunOptions;
+use crate::error::DriverError;
+use crate::outline::classify;
+use crate::outline::outline;
+use crate::outline::ItemKind;
+use crate::source::Source;
+
+pub struct Session {
+    entries: Vec<Entry>,
+    builtins: Builtins,
+    options: RunOptions,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    kind: ItemKind,
+    code: String,
+}
+
+/// What the session answers to an input
+#[derive(Debug)]
+pub enum Reply {
+    Defined(String),
+    Value(Value),
+    Type(String),
+    Info(String),
+    Inspection(Node),
+    Loaded(usize),
+    Reset,
+}
+
+impl std::fmt::Display for Reply {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Reply::Defined(name) => write!(f, "Defined {}", name),
+            Reply::Value(value) => write!(f, "{}", value),
+            Reply::Type(ty) => write!(f, "{}", ty),
+            Reply::Info(info) => write!(f, "{}", info),
+            Reply::Inspection(node) => write!(f, "{}", node),
+            Reply::Loaded(count) => write!(f, "Loaded {} definitions", count),
+            Reply::Reset => write!(f, "Removed all definitions"),
+        }
+    }
+}
+
+impl Session {
+    pub fn new(options: RunOptions) -> Self {
+        Session {
+            entries: Vec::new(),
+            builtins: Builtins::std(),
+            options,
+        }
+    }
+
+    pub fn options(&self) -> &RunOptions {
+        &self.options
+    }
+
+    /// The source of all definitions made so far, in the order they were made
+    pub fn definitions(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.code.as_str())
+    }
+
+    pub fn eval(&mut self, input: &str) -> Result<Reply, DriverError> {
+        if let Some(command) = input.trim_start().strip_prefix(':') {
+            return self.command(command.trim());
+        }
+
+        let source = Source {
+            name: "<repl>".to_string(),
+            code: input.to_string(),
+        };
+        let tokens = source.lex()?;
+
+        match classify(input, &tokens) {
+            Some((name, kind, _)) => {
+                self.define(Entry {
+                    name: name.clone(),
+                    kind,
+                    code: input.trim().to_string(),
+                });
+                Ok(Reply::Defined(name))
+            }
+            None => self.value(input).map(Reply::Value),
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Result<Reply, DriverError> {
+        let (name, argument) = command
+            .split_once(char::is_whitespace)
+            .map(|(name, argument)| (name, argument.trim()))
+            .unwrap_or((command, ""));
+
+        match name {
+            "t" | "type" => {
+                Source {
+                    name: "<repl>".to_string(),
+                    code: argument.to_string(),
+                }
+                .lex()?;
+                Err(DriverError::NotImplemented {
+                    stage: "Type inference",
+                })
+            }
+            "i" | "info" => self.info(argument).map(Reply::Info),
+            "inspect" => {
+                let value = self.value(argument)?;
+                Ok(Reply::Inspection(inspect(&value, Limits::default())))
+            }
+            "load" => self.load(Path::new(argument)).map(Reply::Loaded),
+            "reset" => {
+                self.entries.clear();
+                Ok(Reply::Reset)
+            }
+            _ => Err(DriverError::UnknownCommand(name.to_string())),
+        }
+    }
+
+    // The value of an expression
+    fn value(&self, expr: &str) -> Result<Value, DriverError> {
+        Source {
+            name: "<repl>".to_string(),
+            code: expr.to_string(),
+        }
+        .lex()?;
+        Err(DriverError::NotImplemented {
+            stage: "Evaluation",
+        })
+    }
+
+    // The declaration (or definition, if there is no declaration) and the type of a name
+    fn info(&self, name: &str) -> Result<String, DriverError> {
+        let find = |kind| {
+            self.entries
+                .iter()
+                .find(|entry| entry.name == name && entry.kind == kind)
+        };
+
+        let signature = find(ItemKind::Declaration)
+            .or_else(|| find(ItemKind::Definition))
+            .or_else(|| find(ItemKind::Type));
+        if let Some(entry) = signature {
+            return Ok(entry.code.clone());
+        }
+
+        self.builtins
+            .get(name)
+            .map(|builtin| {
+                format!(
+                    "{} is a builtin taking {} arguments",
+                    builtin.name, builtin.arity
+                )
+            })
+            .ok_or_else(|| DriverError::UnknownName(name.to_string()))
+    }
+
+    // Add every top level item of a file, returning how many there were
+    fn load(&mut self, path: &Path) -> Result<usize, DriverError> {
+        let source = Source::load(path)?;
+        source.lex()?;
+
+        let items = outline(&source.code);
+        let count = items.len();
+        for item in items {
+            self.define(Entry {
+                code: source.code[item.span].to_string(),
+                name: item.name,
+                kind: item.kind,
+            });
+        }
+
+        Ok(count)
+    }
+
+    // Redefining a name replaces the old definition. A new declaration also drops the old
+    // definition, as it most likely does not match the new type.
+    fn define(&mut self, entry: Entry) {
+        self.entries.retain(|old| {
+            old.name != entry.name
+                || (old.kind != entry.kind
+                    && !(entry.kind == ItemKind::Declaration && old.kind == ItemKind::Definition))
+        });
+        self.entries.push(entry);
+    }
+}
+
+/// Whether the input is complete, or the user has to continue it on the next line
+///
+/// Input is incomplete if it has unclosed brackets or strings, or ends in a token that cannot end
+/// an expression, like `=` or `->`.
+pub fn is_complete(input: &str) -> bool {
+    let (tokens, errors) = vunk_lexer::lexer().parse_recovery(input);
+    let len = input.chars().count();
+    if errors.iter().any(|error| error.span().end >= len) {
+        return false;
+    }
+
+    let tokens = tokens.unwrap_or_default();
+    let mut depth: i64 = 0;
+    for (token, _) in tokens.iter() {
+        match token {
+            Token::ParOpen | Token::BlockOpen | Token::ListOpen => depth += 1,
+            Token::ParClose | Token::BlockClose | Token::ListClose => depth -= 1,
+            _ => {}
+        }
+    }
+
+    let mut last = tokens
+        .iter()
+        .rev()
+        .map(|(token, _)| token)
+        .filter(|token| !matches!(token, Token::Comment(_)));
+
+    // The `*` of a glob, like `use Std.List.*`, is not an operator
+    let (last, before) = (last.next(), last.next());
+    if matches!((last, before), (Some(Token::Op(op)), Some(Token::Separator)) if op == "*") {
+        return depth <= 0;
+    }
+
+    let dangling = matches!(
+        last,
+        Some(
+            Token::Arrow
+                | Token::Assign
+                | Token::Declare
+                | Token::Plus
+                | Token::Op(_)
+                | Token::If
+                | Token::Else
+                | Token::Let
+                | Token::In
+                | Token::Lazy
+                | Token::Do
+                | Token::Bind
+                | Token::Match
+                | Token::When
+                | Token::Where
+                | Token::Alternative
+                | Token::Comma
+                | Token::Separator
+                | Token::Spread
+        )
+    );
+
+    depth <= 0 && !dangling
+}
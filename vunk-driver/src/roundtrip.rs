@@ -0,0 +1,54 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Round trips of ASTs through code, the property that `parse(print(ast)) == ast`
+//!
+//! The printed code is lexed, formatted and parsed, and so is the formatted code, as formatting
+//! only changes what the parser does not see. A printer and a parser that disagree about the
+//! syntax fail the round trip, with the code that was printed. Generated ASTs are checked with
+//! [`check_generated`], see [`vunk_parser::generate`].
+
+use std::ops::Range;
+
+use vunk_parser::ast::program::Program;
+
+use crate::error::DriverError;
+use crate::source::Source;
+
+/// Check the round trip of a program
+pub fn check(program: &Program) -> Result<(), DriverError> {
+    let printed = Source {
+        name: "roundtrip.vunk".to_string(),
+        code: vunk_parser::print::program(program),
+    };
+    let formatted = Source {
+        name: printed.name.clone(),
+        code: crate::format::format(&printed)?,
+    };
+
+    parses_to(&printed, program)?;
+    parses_to(&formatted, program)
+}
+
+/// Check the round trip of the programs generated from `seeds`, returning the seeds that fail and
+/// why
+pub fn check_generated(seeds: Range<u64>, depth: usize) -> Vec<(u64, DriverError)> {
+    seeds
+        .filter_map(|seed| {
+            let program = vunk_parser::generate::program(seed, depth);
+            check(&program).err().map(|error| (seed, error))
+        })
+        .collect()
+}
+
+fn parses_to(source: &Source, program: &Program) -> Result<(), DriverError> {
+    let parsed = source.parse(source.lex()?)?;
+    if parsed == *program {
+        Ok(())
+    } else {
+        Err(DriverError::RoundTrip {
+            code: source.code.clone(),
+        })
+    }
+}
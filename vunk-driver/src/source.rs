@@ -0,0 +1,143 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+
+use chumsky::error::Simple;
+use chumsky::error::SimpleReason;
+use chumsky::primitive::end;
+use chumsky::Parser;
+use vunk_diagnostics::codes::Code;
+use vunk_diagnostics::Diagnostic;
+use vunk_diagnostics::File;
+use vunk_diagnostics::Label;
+use vunk_lexer::Spanned;
+use vunk_lexer::Token;
+use vunk_parser::ast::program::Program;
+
+use crate::error::DriverError;
+
+/// A source file and its name, as shown in diagnostics
+#[derive(Clone, Debug)]
+pub struct Source {
+    pub name: String,
+    pub code: String,
+}
+
+impl Source {
+    pub fn load(path: &Path) -> Result<Source, DriverError> {
+        std::fs::read_to_string(path)
+            .map(|code| Source {
+                name: path.display().to_string(),
+                code,
+            })
+            .map_err(|source| DriverError::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Lex the whole source, where input the lexer cannot make a token of is an error
+    pub fn lex(&self) -> Result<Vec<Spanned<Token>>, DriverError> {
+        // Without the end, the lexer would stop at the first character it cannot lex and
+        // silently drop the rest of the file
+        let (tokens, errors) = vunk_lexer::lexer()
+            .then_ignore(end())
+            .parse_recovery(self.code.as_str());
+        match tokens {
+            Some(tokens) if errors.is_empty() => Ok(tokens),
+            _ => Err(DriverError::Lex {
+                name: self.name.clone(),
+                errors: vunk_lexer::diagnostics(&self.file(), &errors),
+            }),
+        }
+    }
+
+    /// Parse the tokens of the source, as [`lex`](Source::lex) gives them
+    pub fn parse(&self, tokens: Vec<Spanned<Token>>) -> Result<Program, DriverError> {
+        let line_starts = vunk_parser::parse::line_starts(&self.code, &tokens);
+        vunk_parser::parse::parse(tokens, &line_starts).map_err(|errors| self.parse_error(&errors))
+    }
+
+    /// The errors of parsing the source as diagnostics pointing into it
+    pub fn parse_error(&self, errors: &[Simple<Token>]) -> DriverError {
+        let file = self.file();
+        DriverError::Parse {
+            name: self.name.clone(),
+            errors: errors
+                .iter()
+                .map(|error| diagnostic(&file, error))
+                .collect(),
+        }
+    }
+
+    /// The source as the file of diagnostics
+    pub fn file(&self) -> File {
+        File {
+            name: self.name.clone(),
+            code: self.code.as_str().into(),
+        }
+    }
+}
+
+// An error of the parser, whose spans count chars like those of the lexer
+fn diagnostic(file: &File, error: &Simple<Token>) -> Diagnostic {
+    let start = byte_offset(&file.code, error.span().start);
+    let end = byte_offset(&file.code, error.span().end).max(start);
+
+    Diagnostic::error(message(error))
+        .with_code(Code::E0016)
+        .with_file(file.clone())
+        .with_label(Label::primary(start..end, "here"))
+}
+
+// chumsky shows neither the messages of custom errors nor the expected tokens in a stable order
+fn message(error: &Simple<Token>) -> String {
+    let token = |token: Option<&Token>| match token {
+        Some(token) => format!("{:?}", token.to_string()),
+        None => "end of input".to_string(),
+    };
+    if let SimpleReason::Custom(message) = error.reason() {
+        return message.clone();
+    }
+
+    let found = token(error.found());
+    let mut expected = error
+        .expected()
+        .map(|expected| token(expected.as_ref()))
+        .collect::<Vec<_>>();
+    expected.sort();
+    match expected.as_slice() {
+        [] => format!("found {}", found),
+        [expected] => format!("found {} but expected {}", found, expected),
+        _ => format!(
+            "found {} but expected one of {}",
+            found,
+            expected.join(", ")
+        ),
+    }
+}
+
+/// Convert an offset in chars, as the lexer counts them, into an offset in bytes
+pub fn byte_offset(code: &str, chars: usize) -> usize {
+    code.char_indices()
+        .nth(chars)
+        .map(|(offset, _)| offset)
+        .unwrap_or(code.len())
+}
+
+/// All tokens the lexer recovers from the code, with spans in bytes
+pub fn tokens(code: &str) -> Vec<Spanned<Token>> {
+    let (tokens, _) = vunk_lexer::lexer().parse_recovery(code);
+    tokens
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(token, span)| {
+            (
+                token,
+                byte_offset(code, span.start)..byte_offset(code, span.end),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Tests and their discovery
+//!
+//! A test is a definition marked with the `@test` attribute:
+//!
+//! ```text
+//! @test
+//! addition = assertEq (1 + 2) 3
+//! ```
+//!
+//! The examples in doc comments are tests as well, see [`crate::doc::doctests`]. A test passes if
+//! it evaluates without error to anything but `false`. If it evaluates to an IO action, the action
+//! is run.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::doc::doctests;
+use crate::error::DriverError;
+use crate::error::TestFailure;
+use crate::outline::outline;
+use crate::outline::ItemKind;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Test {
+    pub name: String,
+    pub kind: TestKind,
+
+    /// Byte range of the test, or of the doc comment of an example
+    pub span: Range<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestKind {
+    /// A definition marked with `@test`
+    Definition,
+
+    /// An example in a doc comment, with its code
+    Example(String),
+}
+
+/// What running the tests of a set of files resulted in
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub passed: Vec<String>,
+    pub failures: Vec<TestFailure>,
+
+    /// Number of tests that did not match the filter
+    pub filtered_out: usize,
+}
+
+impl Summary {
+    pub fn into_result(self) -> Result<(), DriverError> {
+        if self.failures.is_empty() {
+            return Ok(());
+        }
+
+        Err(DriverError::Tests {
+            total: self.passed.len() + self.failures.len(),
+            failures: self.failures,
+        })
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for name in self.passed.iter() {
+            writeln!(f, "test {} ... ok", name)?;
+        }
+        for failure in self.failures.iter() {
+            writeln!(f, "test {} ... FAILED", failure.name)?;
+        }
+        writeln!(
+            f,
+            "{} passed, {} failed, {} filtered out",
+            self.passed.len(),
+            self.failures.len(),
+            self.filtered_out
+        )
+    }
+}
+
+/// All tests of the code, in order
+///
+/// Examples are named after the item they document, like `name (example 1)`.
+pub fn tests(code: &str) -> Vec<Test> {
+    let definitions = outline(code)
+        .into_iter()
+        .filter(|item| item.kind == ItemKind::Definition && item.attributes(code).contains(&"test"))
+        .map(|item| Test {
+            name: item.name,
+            kind: TestKind::Definition,
+            span: item.span,
+        });
+
+    let mut counts = BTreeMap::<String, usize>::new();
+    let examples = doctests(code).into_iter().map(|doctest| {
+        let count = counts.entry(doctest.item.clone()).or_default();
+        *count += 1;
+        Test {
+            name: format!("{} (example {})", doctest.item, count),
+            kind: TestKind::Example(doctest.code),
+            span: doctest.span,
+        }
+    });
+
+    definitions.chain(examples).collect()
+}
+
+/// The files to search for tests: the given files, and all `.vunk` files in the given directories
+pub fn discover(paths: &[PathBuf]) -> Result<Vec<PathBuf>, DriverError> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            visit(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn visit(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), DriverError> {
+    let read_error = |source| DriverError::Read {
+        path: dir.to_path_buf(),
+        source,
+    };
+
+    let mut entries = std::fs::read_dir(dir)
+        .map_err(read_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_error)?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            visit(&path, files)?;
+        } else if path
+            .extension()
+            .map(|extension| extension == "vunk")
+            .unwrap_or(false)
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A TextMate grammar for syntax highlighting in editors without a language server
+//!
+//! The keywords and operators are the literals of the [grammar](vunk_parser::grammar), classified
+//! like [`semantic_tokens`](crate::highlight::semantic_tokens) classifies their tokens. Words are
+//! keywords even where the lexer sees identifiers, like `then`. Identifiers are classified by
+//! patterns that approximate the roles semantic highlighting derives from the outline, as TextMate
+//! grammars only see a line at a time.
+
+use std::collections::BTreeSet;
+
+use vunk_lexer::Token;
+use vunk_parser::grammar::Node;
+
+use crate::highlight::token_role;
+use crate::highlight::Role;
+use crate::source::tokens;
+
+/// The name of the scope of vunk files
+pub const SCOPE: &str = "source.vunk";
+
+const IDENT: &str = "[A-Za-z_$][A-Za-z0-9_]*";
+
+/// The TextMate grammar, as JSON
+pub fn textmate() -> String {
+    let (keywords, constants, operators) = literals();
+    let words = |words: &BTreeSet<&str>| {
+        let words = words.iter().copied().collect::<Vec<_>>();
+        format!("\\b(?:{})\\b", words.join("|"))
+    };
+
+    // Longer operators first, so that `==` is not highlighted as two `=`
+    let mut operators = operators.into_iter().collect::<Vec<_>>();
+    operators.sort_by_key(|operator| std::cmp::Reverse(operator.len()));
+    let operators = operators.into_iter().map(escape).collect::<Vec<_>>();
+
+    let patterns = vec![
+        pattern(Role::Comment, "#.*$".to_string()),
+        pattern(Role::String, "(?:\\b[bx])?\"[^\"]*\"".to_string()),
+        pattern(Role::Number, "\\b[0-9]+(?:\\.[0-9]+)?\\b".to_string()),
+        pattern(Role::Attribute, format!("@{}", IDENT)),
+        pattern(Role::Keyword, words(&keywords)),
+        serde_json::json!({ "name": "constant.language.vunk", "match": words(&constants) }),
+        serde_json::json!({
+            "match": format!("^\\s*(?:pub\\s+)?({})\\s*(?=:|=(?!=))", IDENT),
+            "captures": { "1": { "name": scope(Role::Function) } },
+        }),
+        pattern(Role::Namespace, format!("\\b{}(?=\\.)", IDENT)),
+        pattern(Role::Type, "\\b[A-Z][A-Za-z0-9_]*\\b".to_string()),
+        pattern(Role::Operator, operators.join("|")),
+    ];
+
+    serde_json::json!({
+        "$schema": "https://raw.githubusercontent.com/martinring/tmlanguage/master/tmlanguage.json",
+        "name": "vunk",
+        "scopeName": SCOPE,
+        "fileTypes": ["vunk"],
+        "patterns": patterns,
+    })
+    .to_string()
+}
+
+/// The TextMate scope of tokens of a role, like `keyword.control.vunk`
+pub fn scope(role: Role) -> String {
+    let scope = match role {
+        Role::Function => "entity.name.function",
+        Role::Type | Role::Constructor => "entity.name.type",
+        Role::Parameter => "variable.parameter",
+        Role::Variable => "variable.other",
+        Role::Namespace => "entity.name.namespace",
+        Role::Keyword => "keyword.control",
+        Role::Operator => "keyword.operator",
+        Role::Comment => "comment.line.number-sign",
+        Role::Number => "constant.numeric",
+        Role::String => "string.quoted.double",
+        Role::Attribute => "entity.other.attribute-name",
+    };
+    format!("{}.vunk", scope)
+}
+
+fn pattern(role: Role, regex: String) -> serde_json::Value {
+    serde_json::json!({ "name": scope(role), "match": regex })
+}
+
+// The keywords, constants and operators among the literals of the grammar
+fn literals() -> (
+    BTreeSet<&'static str>,
+    BTreeSet<&'static str>,
+    BTreeSet<&'static str>,
+) {
+    let mut literals = Vec::new();
+    for rule in vunk_parser::grammar::grammar() {
+        literals_of(&rule.node, &mut literals);
+    }
+
+    let mut keywords = BTreeSet::new();
+    let mut constants = BTreeSet::new();
+    let mut operators = BTreeSet::new();
+    for literal in literals {
+        let tokens = tokens(literal);
+        if matches!(tokens.as_slice(), [(Token::Bool(_), _)]) {
+            constants.insert(literal);
+        } else if literal.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            keywords.insert(literal);
+        } else if !tokens.is_empty()
+            && tokens
+                .iter()
+                .all(|(token, _)| token_role(token) == Some(Role::Operator))
+        {
+            operators.insert(literal);
+        }
+    }
+    (keywords, constants, operators)
+}
+
+fn literals_of(node: &Node, literals: &mut Vec<&'static str>) {
+    match node {
+        Node::Literal(text) => literals.push(*text),
+        Node::Sequence(nodes) | Node::Choice(nodes) => {
+            nodes.iter().for_each(|node| literals_of(node, literals))
+        }
+        Node::Optional(node) | Node::Repeat(node) => literals_of(node, literals),
+        Node::Token(_) | Node::Rule(_) => {}
+    }
+}
+
+// An operator as a regular expression matching it
+fn escape(operator: &str) -> String {
+    operator
+        .chars()
+        .map(|c| match c {
+            '\\' | '.' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|' | '^' | '$' => {
+                format!("\\{}", c)
+            }
+            c => c.to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,201 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! How long the phases of the pipeline take, like `-Ztime-passes` of rustc
+//!
+//! Every run of a phase on a module is an [`Event`]. Phases are timed exclusively, parsing a file
+//! does not include lexing it, so the times of all phases add up to the time spent in the
+//! pipeline. The events are shown as a table with [`Timings::report`], or written as Chrome trace
+//! events with [`Timings::chrome_trace`], to be looked at in `chrome://tracing` or Perfetto.
+
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// A run of a phase
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub phase: &'static str,
+
+    /// The name of the file the phase ran on, if it ran on a single one
+    pub module: Option<String>,
+
+    /// When the phase started, after the timings were created
+    pub start: Duration,
+    pub duration: Duration,
+
+    /// The index of the rayon thread it ran on plus one, or 0 for the main thread
+    pub thread: usize,
+
+    /// The peak memory use of the process after it, in bytes, see [`peak_memory`]
+    pub peak_memory: Option<u64>,
+}
+
+/// The events of a compilation, which phases running in parallel record concurrently
+pub struct Timings {
+    origin: Instant,
+    events: Mutex<Vec<Event>>,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Timings {
+            origin: Instant::now(),
+            events: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Timings {
+    /// Run `f` as a run of `phase` on `module`
+    pub fn time<T>(&self, phase: &'static str, module: Option<&str>, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let duration = start.elapsed();
+
+        let event = Event {
+            phase,
+            module: module.map(str::to_string),
+            start: start.duration_since(self.origin),
+            duration,
+            thread: rayon::current_thread_index()
+                .map(|index| index + 1)
+                .unwrap_or(0),
+            peak_memory: peak_memory(),
+        };
+        if let Ok(mut events) = self.events.lock() {
+            events.push(event);
+        }
+        result
+    }
+
+    /// The recorded events, in the order they started in
+    pub fn events(&self) -> Vec<Event> {
+        let mut events = self
+            .events
+            .lock()
+            .map(|events| events.clone())
+            .unwrap_or_default();
+        events.sort_by_key(|event| event.start);
+        events
+    }
+
+    /// The total time of every phase and how often it ran, in the order they first ran in
+    pub fn phases(&self) -> Vec<(&'static str, Duration, usize)> {
+        let mut phases: Vec<(&'static str, Duration, usize)> = Vec::new();
+        for event in self.events() {
+            match phases
+                .iter_mut()
+                .find(|(phase, _, _)| *phase == event.phase)
+            {
+                Some((_, duration, runs)) => {
+                    *duration += event.duration;
+                    *runs += 1;
+                }
+                None => phases.push((event.phase, event.duration, 1)),
+            }
+        }
+        phases
+    }
+
+    /// A table of the phases, followed by the time spent on every module and the peak memory use
+    pub fn report(&self) -> String {
+        let events = self.events();
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "{:<12} {:>12} {:>6} {:>12}",
+            "Phase", "Time", "Runs", "Memory"
+        );
+        for (phase, duration, runs) in self.phases() {
+            let memory = events
+                .iter()
+                .filter(|event| event.phase == phase)
+                .filter_map(|event| event.peak_memory)
+                .max();
+            let memory = memory.map(format_memory).unwrap_or_else(|| "-".to_string());
+            let _ = writeln!(
+                out,
+                "{:<12} {:>12.3?} {:>6} {:>12}",
+                phase, duration, runs, memory
+            );
+        }
+        let total = events.iter().map(|event| event.duration).sum::<Duration>();
+        let _ = writeln!(out, "{:<12} {:>12.3?}", "Total", total);
+
+        let mut modules: Vec<(&str, Vec<&Event>)> = Vec::new();
+        for event in events.iter() {
+            let module = match event.module.as_deref() {
+                Some(module) => module,
+                None => continue,
+            };
+            match modules.iter_mut().find(|(name, _)| *name == module) {
+                Some((_, events)) => events.push(event),
+                None => modules.push((module, vec![event])),
+            }
+        }
+        if !modules.is_empty() {
+            let _ = writeln!(out);
+        }
+        for (module, events) in modules {
+            let total = events.iter().map(|event| event.duration).sum::<Duration>();
+            let phases = events
+                .iter()
+                .map(|event| format!("{} {:.3?}", event.phase, event.duration))
+                .collect::<Vec<_>>();
+            let _ = writeln!(out, "{} {:.3?} ({})", module, total, phases.join(", "));
+        }
+
+        if let Some(memory) = peak_memory() {
+            let _ = writeln!(out, "\nPeak memory: {}", format_memory(memory));
+        }
+        out
+    }
+
+    /// The events in the Chrome trace event format, as complete events with times in microseconds
+    pub fn chrome_trace(&self) -> String {
+        let events = self
+            .events()
+            .into_iter()
+            .map(|event| {
+                serde_json::json!({
+                    "name": event.phase,
+                    "cat": "vunk",
+                    "ph": "X",
+                    "ts": event.start.as_secs_f64() * 1e6,
+                    "dur": event.duration.as_secs_f64() * 1e6,
+                    "pid": std::process::id(),
+                    "tid": event.thread,
+                    "args": {
+                        "module": event.module,
+                        "peak_memory": event.peak_memory,
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({ "traceEvents": events, "displayTimeUnit": "ms" }).to_string()
+    }
+}
+
+/// The most memory the process used so far, in bytes
+///
+/// This is the peak resident set size, which is only known on Linux.
+pub fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+fn format_memory(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
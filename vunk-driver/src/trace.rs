@@ -0,0 +1,261 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Tracing the function calls of a program, for `vunk run --trace-eval`
+//!
+//! Every call made through the [`DriverContext`](crate::context::DriverContext) is an
+//! [`Event::Enter`] with its arguments, followed by the events of the calls it makes, and an
+//! [`Event::Exit`] with its result. Events are written as JSON, one per line, as they happen, so
+//! the trace of a program that does not terminate can be read too.
+//!
+//! Traces are deterministic: events are numbered instead of timed, and values are written as
+//! code rather than with their addresses, so traces of two runs can be diffed. Values are cut off
+//! below [`Trace::DEPTH`] and after [`Trace::ELEMENTS`] elements, to keep lines readable.
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::value::Value;
+
+use crate::error::DriverError;
+
+/// A line of a trace
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum Event {
+    /// A function was called
+    Enter {
+        step: u64,
+
+        /// How many calls the call is nested in
+        depth: usize,
+        function: String,
+        args: Vec<String>,
+    },
+
+    /// A function returned, or failed
+    Exit {
+        step: u64,
+        depth: usize,
+        function: String,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<String>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+/// Where the events of a run are written to
+pub struct Trace {
+    output: Box<dyn Write + Send>,
+    step: u64,
+    depth: usize,
+}
+
+impl Trace {
+    /// How deep values nested in arguments and results are written
+    pub const DEPTH: usize = 3;
+
+    /// How many elements, fields or members of a value are written
+    pub const ELEMENTS: usize = 10;
+
+    pub fn new(output: impl Write + Send + 'static) -> Self {
+        Trace {
+            output: Box::new(output),
+            step: 0,
+            depth: 0,
+        }
+    }
+
+    /// A trace written to the file at `path`, which is created or truncated
+    pub fn create(path: &Path) -> Result<Self, DriverError> {
+        let file = File::create(path).map_err(|source| DriverError::Write {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Trace::new(BufWriter::new(file)))
+    }
+
+    pub fn enter(&mut self, function: &Value, args: &[Value]) {
+        let event = Event::Enter {
+            step: self.step,
+            depth: self.depth,
+            function: name(function),
+            args: args.iter().map(|arg| render(arg, Trace::DEPTH)).collect(),
+        };
+        self.write(&event);
+        self.depth += 1;
+    }
+
+    pub fn exit(&mut self, function: &Value, result: &Result<Value, RuntimeError>) {
+        self.depth = self.depth.saturating_sub(1);
+        let (result, error) = match result {
+            Ok(value) => (Some(render(value, Trace::DEPTH)), None),
+            Err(error) => (None, Some(error.to_string())),
+        };
+        let event = Event::Exit {
+            step: self.step,
+            depth: self.depth,
+            function: name(function),
+            result,
+            error,
+        };
+        self.write(&event);
+    }
+
+    // A trace that cannot be written must not change how the program runs, so errors are ignored
+    fn write(&mut self, event: &Event) {
+        self.step += 1;
+        if let Ok(line) = serde_json::to_string(event) {
+            let _ = writeln!(self.output, "{}", line);
+        }
+    }
+}
+
+impl Drop for Trace {
+    fn drop(&mut self) {
+        let _ = self.output.flush();
+    }
+}
+
+/// The name of a called function, or the type of a value that is not one
+pub fn name(function: &Value) -> String {
+    match function {
+        Value::Function(function) => function.name().to_string(),
+        other => other.type_name().to_string(),
+    }
+}
+
+/// A value as code, with what is nested more than `depth` deep and elements beyond
+/// [`Trace::ELEMENTS`] left out as `…`
+///
+/// Lazy values that were not evaluated yet are not forced.
+pub fn render(value: &Value, depth: usize) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, depth);
+    out
+}
+
+fn write_value(out: &mut String, value: &Value, depth: usize) {
+    match value {
+        Value::List(list) => {
+            out.push('[');
+            write_elements(out, list.iter(), " ", depth);
+            out.push(']');
+        }
+        Value::Tuple(tuple) => {
+            out.push('(');
+            write_elements(out, tuple.0.iter(), ", ", depth);
+            out.push(')');
+        }
+        Value::Map(map) => {
+            out.push_str("Std.Map.fromList [");
+            let entries = map
+                .0
+                .iter()
+                .map(|(key, value)| Value::tuple(vec![key.to_value(), value.clone()]))
+                .collect::<Vec<_>>();
+            write_elements(out, entries.iter(), " ", depth);
+            out.push(']');
+        }
+        Value::Set(set) => {
+            out.push_str("Std.Set.fromList [");
+            let keys = set.0.iter().map(|key| key.to_value()).collect::<Vec<_>>();
+            write_elements(out, keys.iter(), " ", depth);
+            out.push(']');
+        }
+        Value::Record(record) => {
+            let assign = match record.type_name.as_ref() {
+                Some(type_name) => {
+                    let _ = write!(out, "{} ", type_name);
+                    ":"
+                }
+                None => " =",
+            };
+            out.push_str("{ ");
+            if depth == 0 {
+                out.push('…');
+            } else {
+                for (i, (name, value)) in record.fields.iter().enumerate() {
+                    if i == Trace::ELEMENTS {
+                        out.push_str(", …");
+                        break;
+                    }
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    let _ = write!(out, "{}{} ", name, assign);
+                    write_value(out, value, depth - 1);
+                }
+            }
+            out.push_str(" }");
+        }
+        Value::Variant(variant) if !variant.members.is_empty() => {
+            out.push_str(&variant.name);
+            if depth == 0 {
+                out.push_str(" …");
+                return;
+            }
+            for member in variant.members.iter().take(Trace::ELEMENTS) {
+                out.push(' ');
+                let nested = matches!(member, Value::Variant(v) if !v.members.is_empty());
+                if nested {
+                    out.push('(');
+                }
+                write_value(out, member, depth - 1);
+                if nested {
+                    out.push(')');
+                }
+            }
+            if variant.members.len() > Trace::ELEMENTS {
+                out.push_str(" …");
+            }
+        }
+        Value::Thunk(thunk) if thunk.is_evaluated() => match thunk.force() {
+            Ok(value) => write_value(out, &value, depth),
+            Err(error) => {
+                let _ = write!(out, "<failed: {}>", error);
+            }
+        },
+        // Scalars, and values that are shown by name
+        other => {
+            let _ = write!(out, "{}", other);
+        }
+    }
+}
+
+fn write_elements<'a>(
+    out: &mut String,
+    elements: impl ExactSizeIterator<Item = &'a Value>,
+    separator: &str,
+    depth: usize,
+) {
+    if elements.len() == 0 {
+        return;
+    }
+    if depth == 0 {
+        out.push('…');
+        return;
+    }
+
+    let total = elements.len();
+    for (i, element) in elements.take(Trace::ELEMENTS).enumerate() {
+        if i > 0 {
+            out.push_str(separator);
+        }
+        write_value(out, element, depth - 1);
+    }
+    if total > Trace::ELEMENTS {
+        out.push_str(separator);
+        out.push('…');
+    }
+}
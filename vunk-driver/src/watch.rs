@@ -0,0 +1,146 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Waiting for changes of the files of a program or a package
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use notify::Event;
+use notify::EventKind;
+use notify::RecommendedWatcher;
+use notify::RecursiveMode;
+use notify::Watcher as _;
+
+use crate::error::DriverError;
+use crate::package::manifest;
+use crate::package::Graph;
+
+/// Changes arriving within this time of each other are handled together, as editors often write a
+/// file in several steps
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+pub struct Watcher {
+    // Watching stops when this is dropped
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    target: Target,
+}
+
+enum Target {
+    File(PathBuf),
+
+    /// The directories of a package and its dependencies
+    Package(Vec<PathBuf>),
+}
+
+impl Watcher {
+    /// Watch a file, or the package in the directory `path` together with its dependencies
+    pub fn new(path: &Path) -> Result<Watcher, DriverError> {
+        let path = path.canonicalize().map_err(|source| DriverError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let target = if path.is_dir() {
+            // If the manifest is broken, at least the package itself is watched so that fixing it
+            // is noticed
+            let directories = Graph::load(&path)
+                .map(|graph| {
+                    graph
+                        .packages
+                        .into_iter()
+                        .map(|package| package.root)
+                        .collect()
+                })
+                .unwrap_or_else(|_| vec![path.clone()]);
+            Target::Package(directories)
+        } else {
+            Target::File(path)
+        };
+
+        let (sender, events) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+        match &target {
+            // Editors often replace files instead of writing them, so the directory is watched
+            Target::File(file) => watcher
+                .watch(file.parent().unwrap_or(file), RecursiveMode::NonRecursive)
+                .map_err(watch_error)?,
+            Target::Package(directories) => {
+                for directory in directories {
+                    watcher
+                        .watch(directory, RecursiveMode::Recursive)
+                        .map_err(watch_error)?;
+                }
+            }
+        }
+
+        Ok(Watcher {
+            _watcher: watcher,
+            events,
+            target,
+        })
+    }
+
+    /// Block until files were changed, returning them
+    pub fn wait(&self) -> Result<Vec<PathBuf>, DriverError> {
+        let mut changed = Vec::new();
+        while changed.is_empty() {
+            let event = self.events.recv().map_err(|error| DriverError::Watch {
+                message: error.to_string(),
+            })?;
+            self.collect(event, &mut changed)?;
+        }
+
+        while let Ok(event) = self.events.recv_timeout(DEBOUNCE) {
+            self.collect(event, &mut changed)?;
+        }
+
+        changed.sort();
+        changed.dedup();
+        Ok(changed)
+    }
+
+    fn collect(
+        &self,
+        event: notify::Result<Event>,
+        changed: &mut Vec<PathBuf>,
+    ) -> Result<(), DriverError> {
+        let event = event.map_err(watch_error)?;
+        if matches!(event.kind, EventKind::Access(_)) {
+            return Ok(());
+        }
+
+        changed.extend(
+            event
+                .paths
+                .into_iter()
+                .filter(|path| self.is_relevant(path)),
+        );
+        Ok(())
+    }
+
+    fn is_relevant(&self, path: &Path) -> bool {
+        match &self.target {
+            Target::File(file) => path == file,
+            Target::Package(_) => {
+                let is_manifest = path.file_name() == Some(manifest::FILE_NAME.as_ref());
+                // Git dependencies are checked out to `target`, but they are watched on their own
+                let is_module = path.extension() == Some("vunk".as_ref())
+                    && !path
+                        .components()
+                        .any(|component| component.as_os_str() == "target");
+                is_manifest || is_module
+            }
+        }
+    }
+}
+
+fn watch_error(error: notify::Error) -> DriverError {
+    DriverError::Watch {
+        message: error.to_string(),
+    }
+}
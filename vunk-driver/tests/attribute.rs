@@ -0,0 +1,105 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::attribute::check;
+use vunk_driver::attribute::REGISTRY;
+use vunk_driver::error::DriverError;
+use vunk_driver::source::Source;
+
+fn source(code: &str) -> Source {
+    Source {
+        name: "main.vunk".to_string(),
+        code: code.to_string(),
+    }
+}
+
+#[test]
+fn registered_attributes_are_valid() {
+    let code = "\
+@derive(Eq, Show)
+type Point = { x: U64, y: U64 }
+
+@inline(always)
+@deprecated(\"use area\", since = \"0.2\")
+@cfg(not(release))
+@allow(shadowing)
+size = (point) -> point.x * point.y
+
+@test
+size_of_origin = assertEq (size { x = 0, y = 0 }) 0
+";
+    check(&source(code)).unwrap();
+}
+
+#[test]
+fn the_registry_is_sorted() {
+    let names = REGISTRY.iter().map(|spec| spec.name).collect::<Vec<_>>();
+    let mut sorted = names.clone();
+    sorted.sort_unstable();
+    assert_eq!(names, sorted);
+}
+
+#[test]
+fn invalid_attributes_are_errors() {
+    for (code, message, label, help) in [
+        (
+            "@tests\nx = 1\n",
+            "@tests is not an attribute",
+            "@tests",
+            Some("@test"),
+        ),
+        (
+            "@test(fast)\nx = 1\n",
+            "@test takes no arguments",
+            "@test(fast)",
+            None,
+        ),
+        (
+            "@inline(sometimes)\nx = 1\n",
+            "@inline takes one of always, never",
+            "@inline(sometimes)",
+            None,
+        ),
+        (
+            "@derive\ntype A = { }\n",
+            "@derive takes names",
+            "@derive",
+            None,
+        ),
+        (
+            "@deprecated(reason = \"old\")\nx = 1\n",
+            "@deprecated takes a message, then since = \"...\", note = \"...\"",
+            "@deprecated(reason = \"old\")",
+            None,
+        ),
+        (
+            "@cfg(debug, release)\nx = 1\n",
+            "@cfg takes one condition",
+            "@cfg(debug, release)",
+            None,
+        ),
+        (
+            "@derive(Eq,)\ntype A = { }\n",
+            "expected an attribute like @name or @name(argument, ...)",
+            ")",
+            None,
+        ),
+    ] {
+        match check(&source(code)) {
+            Err(DriverError::InvalidAttribute {
+                message: actual,
+                span,
+                help: actual_help,
+                ..
+            }) => {
+                assert_eq!(actual, message);
+                assert_eq!(&code[span.offset()..span.offset() + span.len()], label);
+                let suggested =
+                    help.map(|name| format!("An attribute with a similar name exists: {}", name));
+                assert_eq!(actual_help, suggested);
+            }
+            result => panic!("{:?} is not an invalid attribute", result),
+        }
+    }
+}
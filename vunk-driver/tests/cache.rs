@@ -0,0 +1,58 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::cache::key;
+use vunk_driver::cache::Cache;
+use vunk_driver::cache::Statistics;
+use vunk_driver::database::Database;
+use vunk_driver::source::Source;
+
+#[test]
+fn keys_depend_on_all_inputs() {
+    assert_eq!(key(&["a", "b"]), key(&["a", "b"]));
+    assert_ne!(key(&["a", "b"]), key(&["a", "c"]));
+    assert_ne!(key(&["ab", "c"]), key(&["a", "bc"]));
+}
+
+#[test]
+fn entries_are_read_back() {
+    let directory = std::env::temp_dir().join(format!("vunk-cache-{}", std::process::id()));
+    let mut cache = Cache::new(directory.clone());
+    assert_eq!(cache.get::<Vec<String>>("stage", "entry"), None);
+    cache.put("stage", "entry", &vec!["value".to_string()]);
+    assert_eq!(
+        cache.get::<Vec<String>>("stage", "entry"),
+        Some(vec!["value".to_string()])
+    );
+
+    let statistics = Statistics {
+        hits: 1,
+        misses: 1,
+        writes: 1,
+    };
+    assert_eq!(cache.statistics(), statistics);
+    let _ = std::fs::remove_dir_all(directory);
+}
+
+#[test]
+fn cached_files_are_not_typechecked_again() {
+    let directory = std::env::temp_dir().join(format!("vunk-typecheck-{}", std::process::id()));
+    let code = "a = 1\n";
+    Cache::new(directory.clone()).put("typecheck", &key(&[code]), &());
+
+    // Parsing is not even attempted
+    let mut database = Database::with_cache(Cache::new(directory.clone()));
+    database.set_source(Source {
+        name: "a.vunk".to_string(),
+        code: code.to_string(),
+    });
+    database.typecheck("a.vunk").unwrap();
+    assert_eq!(
+        database
+            .cache_statistics()
+            .map(|statistics| statistics.hits),
+        Some(1)
+    );
+    let _ = std::fs::remove_dir_all(directory);
+}
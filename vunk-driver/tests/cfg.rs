@@ -0,0 +1,203 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeSet;
+
+use vunk_driver::cfg::disabled;
+use vunk_driver::cfg::strip;
+use vunk_driver::cfg::Backend;
+use vunk_driver::cfg::Config;
+use vunk_driver::cfg::Features;
+use vunk_driver::error::DriverError;
+use vunk_driver::package::Graph;
+use vunk_driver::source::Source;
+
+const PRINT: &str = "\
+@cfg(backend = \"wasm\")
+print: String -> Io ()
+
+@cfg(not(backend = \"wasm\"))
+print: String -> Io ()
+
+@cfg(all(debug, feature = \"trace\"))
+trace = 1
+
+main = 1
+";
+
+fn source(code: &str) -> Source {
+    Source {
+        name: "main.vunk".to_string(),
+        code: code.to_string(),
+    }
+}
+
+fn features(declared: &[&str], enabled: &[&str]) -> Features {
+    Features {
+        declared: declared.iter().map(|name| name.to_string()).collect(),
+        enabled: enabled.iter().map(|name| name.to_string()).collect(),
+    }
+}
+
+// The first lines of the disabled items
+fn disabled_lines<'a>(code: &'a str, config: &Config, features: Option<&Features>) -> Vec<&'a str> {
+    let disabled = disabled(&source(code), config, features).unwrap();
+    disabled
+        .into_iter()
+        .map(|range| code[range].lines().next().unwrap())
+        .collect()
+}
+
+#[test]
+fn conditions_pick_the_items_of_the_backend() {
+    let interpreter = Config::default();
+    let wasm = Config {
+        backend: Backend::Wasm,
+        ..Config::default()
+    };
+    let trace = features(&["trace"], &["trace"]);
+
+    assert_eq!(
+        disabled_lines(PRINT, &interpreter, Some(&trace)),
+        ["@cfg(backend = \"wasm\")"]
+    );
+    assert_eq!(
+        disabled_lines(PRINT, &wasm, Some(&trace)),
+        ["@cfg(not(backend = \"wasm\"))"]
+    );
+
+    let release = Config {
+        release: true,
+        ..Config::default()
+    };
+    let lines = disabled_lines(PRINT, &release, Some(&trace));
+    assert_eq!(lines[1], "@cfg(all(debug, feature = \"trace\"))");
+
+    // Files on their own have no features
+    let lines = disabled_lines(PRINT, &interpreter, None);
+    assert_eq!(lines[1], "@cfg(all(debug, feature = \"trace\"))");
+}
+
+#[test]
+fn disabled_items_are_stripped_from_the_tokens() {
+    let code = "@cfg(release)\nfast = 1\n\nslow = 2\n";
+    let disabled = disabled(&source(code), &Config::default(), None).unwrap();
+    let tokens = source(code).lex().unwrap();
+    let stripped = strip(code, tokens, &disabled)
+        .into_iter()
+        .map(|(token, _)| token.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(stripped, ["slow", "=", "2"]);
+}
+
+#[test]
+fn invalid_conditions_are_errors() {
+    let declared = features(&["trace"], &[]);
+    for (code, message, label) in [
+        (
+            "@cfg(backend = \"js\")\nx = 1\n",
+            "js is not a backend",
+            "\"js\"",
+        ),
+        (
+            "@cfg(feature = \"fast\")\nx = 1\n",
+            "fast is not a feature",
+            "\"fast\"",
+        ),
+        (
+            "@cfg(nightly)\nx = 1\n",
+            "nightly is not a condition",
+            "nightly",
+        ),
+        (
+            "@cfg(not(debug, release))\nx = 1\n",
+            "not takes exactly one condition",
+            "not(debug, release)",
+        ),
+        ("@cfg\nx = 1\n", "expected '('", "cfg"),
+    ] {
+        match disabled(&source(code), &Config::default(), Some(&declared)) {
+            Err(DriverError::InvalidCfg {
+                message: actual,
+                span,
+                ..
+            }) => {
+                assert_eq!(actual, message);
+                assert_eq!(&code[span.offset()..span.offset() + span.len()], label);
+            }
+            result => panic!("{:?} is not an invalid condition", result),
+        }
+    }
+}
+
+#[test]
+fn features_enable_the_features_they_list() {
+    let root = std::env::temp_dir().join(format!("vunk-cfg-features-{}", std::process::id()));
+    let manifest = "\
+[package]
+name = \"app\"
+version = \"0.1.0\"
+
+[features]
+default = [\"json\"]
+json = []
+server = [\"json\", \"tls\"]
+tls = []
+";
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("vunk.toml"), manifest).unwrap();
+    std::fs::write(root.join("src/main.vunk"), "main = 1\n").unwrap();
+    let graph = Graph::load(&root);
+    let _ = std::fs::remove_dir_all(&root);
+    let graph = graph.unwrap();
+
+    let enabled = |config: Config| {
+        let features = config.features(&graph, 0).unwrap();
+        features.enabled.into_iter().collect::<Vec<_>>()
+    };
+    assert_eq!(enabled(Config::default()), ["json"]);
+
+    let config = Config {
+        features: BTreeSet::from(["tls".to_string()]),
+        default_features: false,
+        ..Config::default()
+    };
+    assert_eq!(enabled(config), ["tls"]);
+
+    let config = Config {
+        features: BTreeSet::from(["server".to_string()]),
+        ..Config::default()
+    };
+    assert_eq!(enabled(config), ["json", "server", "tls"]);
+
+    let config = Config {
+        features: BTreeSet::from(["gzip".to_string()]),
+        ..Config::default()
+    };
+    assert!(matches!(
+        config.features(&graph, 0),
+        Err(DriverError::UnknownFeature { feature, .. }) if feature == "gzip"
+    ));
+}
+
+#[test]
+fn manifests_only_list_declared_features() {
+    let root = std::env::temp_dir().join(format!("vunk-cfg-manifest-{}", std::process::id()));
+    let manifest = "\
+[package]
+name = \"app\"
+version = \"0.1.0\"
+
+[features]
+server = [\"tls\"]
+";
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("vunk.toml"), manifest).unwrap();
+    let graph = Graph::load(&root);
+    let _ = std::fs::remove_dir_all(&root);
+    match graph {
+        Err(DriverError::Manifest { message, .. }) => assert_eq!(message, "tls is not a feature"),
+        result => panic!("{:?} is not a manifest error", result.map(|_| ())),
+    }
+}
@@ -0,0 +1,63 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::complete::complete;
+use vunk_driver::complete::CompletionKind;
+use vunk_runtime::builtin::Builtins;
+
+fn labels(code: &str) -> Vec<(String, CompletionKind)> {
+    let (_, completions) = complete(code, code.len(), &Builtins::std());
+    completions
+        .into_iter()
+        .map(|completion| (completion.label, completion.kind))
+        .collect()
+}
+
+#[test]
+fn module_members() {
+    let code = "a = Std.IO.pr";
+    let (start, _) = complete(code, code.len(), &Builtins::std());
+    assert_eq!(start, code.len() - 2);
+    assert_eq!(
+        labels(code),
+        vec![
+            ("print".to_string(), CompletionKind::Builtin),
+            ("println".to_string(), CompletionKind::Builtin),
+        ]
+    );
+}
+
+#[test]
+fn submodules() {
+    assert!(labels("a = Std.").contains(&("IO".to_string(), CompletionKind::Module)));
+}
+
+#[test]
+fn locals_rank_before_top_level_items() {
+    let code = "value = 1\n\nf = (val: i64) -> va";
+    assert_eq!(
+        labels(code),
+        vec![
+            ("val".to_string(), CompletionKind::Local),
+            ("value".to_string(), CompletionKind::Definition),
+        ]
+    );
+}
+
+#[test]
+fn keywords_and_prelude() {
+    let labels = labels("f = ma");
+    assert!(labels.contains(&("match".to_string(), CompletionKind::Keyword)));
+
+    let code = "f = So";
+    let (_, completions) = complete(code, code.len(), &Builtins::std());
+    assert_eq!(completions[0].label, "Some");
+    assert_eq!(completions[0].detail.as_deref(), Some("Std.Option.Some"));
+
+    let code = "@no_prelude\nf = So";
+    let (_, completions) = complete(code, code.len(), &Builtins::std());
+    assert!(completions
+        .iter()
+        .all(|completion| completion.label != "Some"));
+}
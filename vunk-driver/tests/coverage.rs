@@ -0,0 +1,84 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::coverage::Coverage;
+use vunk_driver::coverage::Region;
+use vunk_driver::coverage::Report;
+use vunk_driver::source::Source;
+
+const CODE: &str = "\
+# Doubles a number
+double: (x: i64) -> i64
+double = (x) ->
+  x * 2
+
+unused = 1
+
+@test
+doubling = double 2
+";
+
+fn source() -> Source {
+    Source {
+        name: "double.vunk".to_string(),
+        code: CODE.to_string(),
+    }
+}
+
+// The byte range of the first occurrence of `code`
+fn span(code: &str) -> std::ops::Range<usize> {
+    let start = CODE.find(code).unwrap();
+    start..start + code.len()
+}
+
+#[test]
+fn executed_lines_and_definitions_are_covered() {
+    let coverage = Coverage::default();
+    coverage.record("double.vunk", span("x * 2"));
+    coverage.record("double.vunk", span("x * 2"));
+    coverage.record("other.vunk", span("unused = 1"));
+
+    let report = Report::new(&[source()], &coverage);
+    let file = &report.files[0];
+    assert_eq!(file.lines, [(3, 0), (4, 2), (6, 0)]);
+    assert_eq!(
+        file.regions,
+        [
+            Region {
+                name: "double".to_string(),
+                line: 3,
+                hits: 2,
+            },
+            Region {
+                name: "unused".to_string(),
+                line: 6,
+                hits: 0,
+            },
+        ]
+    );
+    assert_eq!((file.covered_lines(), file.covered_regions()), (1, 1));
+}
+
+#[test]
+fn reports_are_exported_as_lcov() {
+    let coverage = Coverage::default();
+    coverage.record("double.vunk", span("x * 2"));
+
+    let lcov = Report::new(&[source()], &coverage).lcov();
+    assert_eq!(
+        lcov,
+        "TN:\nSF:double.vunk\nFN:3,double\nFN:6,unused\nFNDA:1,double\nFNDA:0,unused\nFNF:2\n\
+         FNH:1\nDA:3,0\nDA:4,1\nDA:6,0\nLF:3\nLH:1\nend_of_record\n"
+    );
+}
+
+#[test]
+fn tables_show_totals() {
+    let report = Report::new(&[source()], &Coverage::default()).to_string();
+    let lines = report.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("double.vunk"));
+    assert!(lines[1].ends_with("0/3 (0.0%)       0/2 (0.0%)"));
+    assert!(lines[2].starts_with("Total"));
+}
@@ -0,0 +1,123 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::database::Database;
+use vunk_driver::error::DriverError;
+use vunk_driver::source::Source;
+
+fn set(database: &mut Database, name: &str, code: &str) {
+    database.set_source(Source {
+        name: name.to_string(),
+        code: code.to_string(),
+    });
+}
+
+#[test]
+fn only_changed_files_are_lexed_again() {
+    let mut database = Database::default();
+    set(&mut database, "a.vunk", "a = 1\n");
+    set(&mut database, "b.vunk", "b = 2\n");
+    database.lex("a.vunk").unwrap();
+    database.lex("b.vunk").unwrap();
+    assert_eq!(database.computed(), 2);
+
+    // Setting the same code again is not a change
+    set(&mut database, "a.vunk", "a = 1\n");
+    database.lex("a.vunk").unwrap();
+    database.lex("b.vunk").unwrap();
+    assert_eq!(database.computed(), 2);
+
+    set(&mut database, "a.vunk", "a = 3\n");
+    database.lex("a.vunk").unwrap();
+    database.lex("b.vunk").unwrap();
+    assert_eq!(database.computed(), 3);
+}
+
+#[test]
+fn errors_are_memoized() {
+    let mut database = Database::default();
+    set(&mut database, "a.vunk", "a = 1;\n");
+    let error = database.typecheck("a.vunk").unwrap_err();
+    assert!(matches!(error.error(), DriverError::Lex { .. }));
+
+    // Lexing, resolving, parsing and typechecking failed at lexing
+    let computed = database.computed();
+    database.typecheck("a.vunk").unwrap_err();
+    assert_eq!(database.computed(), computed);
+}
+
+#[test]
+fn unchanged_results_do_not_invalidate_their_readers() {
+    let mut database = Database::default();
+    set(&mut database, "a.vunk", "a = 1\n");
+    database.parse("a.vunk").unwrap();
+
+    // The tokens are the same, so only lexing and leaving out disabled items is computed again,
+    // and the parse is reused
+    set(&mut database, "a.vunk", "a = 1\n\n");
+    let computed = database.computed();
+    database.parse("a.vunk").unwrap();
+    assert_eq!(database.computed(), computed + 2);
+}
+
+#[test]
+fn errors_of_the_parser_point_into_the_current_code() {
+    let mut database = Database::default();
+    set(&mut database, "a.vunk", "a = (1\n");
+    let error = database.parse("a.vunk").unwrap_err();
+    assert!(matches!(error.error(), DriverError::Parse { .. }));
+
+    // A tab instead of the space leaves the tokens as they are, the diagnostics show it anyway
+    set(&mut database, "a.vunk", "a =\t(1\n");
+    let computed = database.computed();
+    let error = database.parse("a.vunk").unwrap_err();
+    assert_eq!(database.computed(), computed + 3);
+    let diagnostics = error.error().diagnostics();
+    assert_eq!(&*diagnostics[0].file.as_ref().unwrap().code, "a =\t(1\n");
+}
+
+#[test]
+fn unknown_files_are_errors() {
+    let mut database = Database::default();
+    let error = database.lex("missing.vunk").unwrap_err();
+    assert!(matches!(error.error(), DriverError::UnknownSource(name) if name == "missing.vunk"));
+}
+
+#[test]
+fn files_are_typechecked_together_in_order() {
+    let mut database = Database::default();
+    set(&mut database, "a.vunk", "a = 1;\n");
+    set(&mut database, "b.vunk", "b = 2\n");
+    set(&mut database, "c.vunk", "c = 3;\n");
+    let names = ["a.vunk", "b.vunk", "c.vunk"].map(String::from);
+
+    let errors = database
+        .typecheck_all(&names)
+        .into_iter()
+        .map(|result| result.unwrap_err())
+        .collect::<Vec<_>>();
+    assert!(matches!(errors[0].error(), DriverError::Lex { name, .. } if name == "a.vunk"));
+    assert!(matches!(
+        errors[1].error(),
+        DriverError::NotImplemented { .. }
+    ));
+    assert!(matches!(errors[2].error(), DriverError::Lex { name, .. } if name == "c.vunk"));
+
+    let computed = database.computed();
+    database.typecheck_all(&names);
+    database.typecheck("b.vunk").unwrap_err();
+    assert_eq!(database.computed(), computed);
+}
+
+#[test]
+fn shared_errors_are_diagnostics() {
+    let mut database = Database::default();
+    set(&mut database, "a.vunk", "a = 1;\n");
+    let error = DriverError::from(database.typecheck("a.vunk").unwrap_err());
+
+    let diagnostics = error.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].labels[0].span, 5..6);
+    assert!(diagnostics[0].render(false).contains(" --> a.vunk:1:6\n"));
+}
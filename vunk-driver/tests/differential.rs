@@ -0,0 +1,45 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::differential::check;
+use vunk_driver::differential::check_generated;
+use vunk_driver::differential::evaluate;
+use vunk_driver::differential::Backend;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::literal::Integer;
+use vunk_parser::ast::literal::IntegerValue;
+use vunk_parser::ast::literal::Literal;
+use vunk_parser::ast::op::BinaryOp;
+use vunk_runtime::error::RuntimeError;
+
+fn int(i: i64) -> Box<Expr> {
+    Box::new(Expr::Literal(Literal::Integer(Integer {
+        value: IntegerValue::I64(i),
+    })))
+}
+
+#[test]
+fn generated_expressions_evaluate_alike() {
+    let failures = check_generated(0..1024, 3)
+        .into_iter()
+        .map(|(seed, error)| format!("seed {}: {}", seed, error))
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn results_that_do_not_fit_an_int_overflow_in_both() {
+    let expr = Expr::Binary(
+        BinaryOp::Sub,
+        Box::new(Expr::Binary(BinaryOp::Add, int(i64::MAX), int(1))),
+        int(1),
+    );
+    for backend in [Backend::Int, Backend::BigInt] {
+        assert!(matches!(
+            evaluate(&expr, backend),
+            Some(Err(RuntimeError::IntegerOverflow { op: "+" }))
+        ));
+    }
+    check(&expr).unwrap();
+}
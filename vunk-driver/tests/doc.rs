@@ -0,0 +1,101 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::doc::collect;
+use vunk_driver::doc::doctests;
+use vunk_driver::doc::html;
+use vunk_driver::doc::DocKind;
+use vunk_driver::error::DriverError;
+use vunk_driver::source::Source;
+
+const CODE: &str = "\
+# A point in the plane
+pub type Point = { x: i64, y: i64 }
+
+# The distance to the origin, squared
+#
+# ```
+# norm (Point { x: 3, y: 4 })
+# ```
+norm: (Point) -> i64
+norm = (p: Point) -> p.x * p.x + p.y * p.y
+
+helper = 1
+";
+
+fn modules() -> Vec<vunk_driver::doc::Module> {
+    collect(&[Source {
+        name: "dir/geometry.vunk".to_string(),
+        code: CODE.to_string(),
+    }])
+}
+
+#[test]
+fn items_with_signatures_and_docs() {
+    let modules = modules();
+    assert_eq!(modules.len(), 1);
+    assert_eq!(modules[0].name, "geometry");
+
+    let items = &modules[0].items;
+    let names = items
+        .iter()
+        .map(|item| item.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Point", "norm", "helper"]);
+
+    assert_eq!(items[0].kind, DocKind::Type);
+    assert!(items[0].public);
+    assert_eq!(
+        items[0].signature.as_deref(),
+        Some("pub type Point = { x: i64, y: i64 }")
+    );
+    assert_eq!(items[0].doc, "A point in the plane");
+
+    assert_eq!(items[1].kind, DocKind::Function);
+    assert!(!items[1].public);
+    assert_eq!(items[1].signature.as_deref(), Some("(Point) -> i64"));
+    assert_eq!(items[1].examples, vec!["norm (Point { x: 3, y: 4 })"]);
+
+    assert_eq!(items[2].signature, None);
+    assert_eq!(items[2].doc, "");
+}
+
+#[test]
+fn html_links_types_and_renders_examples() {
+    let modules = modules();
+    let page = html::module(&modules[0], &modules);
+
+    assert!(page.contains("(<a href=\"geometry.html#Point\">Point</a>) -&gt; i64"));
+    assert!(page.contains("<pre class=\"example\"><code>norm (Point { x: 3, y: 4 })</code></pre>"));
+    assert!(html::index(&modules).contains("<a href=\"geometry.html\">geometry</a>"));
+}
+
+#[test]
+fn examples_are_doctests() {
+    let doctests = doctests(CODE);
+    assert_eq!(doctests.len(), 1);
+    assert_eq!(doctests[0].item, "norm");
+    assert_eq!(doctests[0].code, "norm (Point { x: 3, y: 4 })");
+
+    let comment = &CODE[doctests[0].span.clone()];
+    assert!(comment.starts_with("# The distance to the origin"));
+    assert!(comment.ends_with("# ```"));
+}
+
+#[test]
+fn failing_doctests_are_reported() {
+    let path = std::env::temp_dir().join(format!("vunk-doctest-{}.vunk", std::process::id()));
+    std::fs::write(&path, CODE).unwrap();
+    let result = vunk_driver::doctest(std::slice::from_ref(&path));
+    std::fs::remove_file(&path).unwrap();
+
+    match result {
+        Err(DriverError::Doctests { total, failures }) => {
+            assert_eq!(total, 1);
+            assert_eq!(failures.len(), 1);
+            assert_eq!(failures[0].item, "norm");
+        }
+        other => panic!("Expected failing doctests, got {:?}", other),
+    }
+}
@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::dump;
+use vunk_driver::dump::Stage;
+use vunk_driver::error::DriverError;
+use vunk_driver::source::tokens;
+
+#[test]
+fn tokens_are_printed_with_their_location() {
+    let code = "a = 1\nb = \"ä\"\n";
+    let expected = "\
+1:1-1:2      Ident(\"a\")
+1:3-1:4      Assign
+1:5-1:6      Num(\"1\")
+2:1-2:2      Ident(\"b\")
+2:3-2:4      Assign
+2:5-2:8      Str(\"ä\")
+";
+    assert_eq!(dump::tokens(code, &tokens(code)), expected);
+}
+
+#[test]
+fn tokens_are_printed_as_json() {
+    let code = "b = \"ä\"\n";
+    let json: serde_json::Value =
+        serde_json::from_str(&dump::tokens_json(code, &tokens(code))).unwrap();
+    assert_eq!(json.as_array().map(Vec::len), Some(3));
+    assert_eq!(json[0]["kind"], "Ident");
+    assert_eq!(json[2]["kind"], "Str");
+    assert_eq!(json[2]["text"], "\"ä\"");
+    assert_eq!(json[2]["byte_end"], 8);
+    assert_eq!(json[2]["column"], 5);
+}
+
+#[test]
+fn types_need_the_typechecker() {
+    let path = std::env::temp_dir().join(format!("vunk-dump-{}.vunk", std::process::id()));
+    std::fs::write(&path, "a = 1\n").unwrap();
+    let ast = vunk_driver::dump(&path, Stage::Ast, false);
+    let types = vunk_driver::dump(&path, Stage::Types, false);
+    let _ = std::fs::remove_file(&path);
+    assert!(ast.unwrap().contains("Def"));
+    assert!(matches!(
+        types,
+        Err(DriverError::NotImplemented {
+            stage: "Typechecking"
+        })
+    ));
+}
+
+#[test]
+fn the_grammar_is_printed_as_railroad_diagrams() {
+    let json: serde_json::Value = serde_json::from_str(&dump::grammar(true)).unwrap();
+    assert_eq!(json["start"], "program");
+    assert_eq!(json["rules"][0]["name"], "program");
+    assert_eq!(json["rules"][0]["diagram"]["type"], "zero_or_more");
+    assert_eq!(json["rules"][0]["diagram"]["item"]["name"], "item");
+    assert!(dump::grammar(false).starts_with("program ::= item*\n"));
+}
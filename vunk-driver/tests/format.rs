@@ -0,0 +1,205 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::format::config::Config;
+use vunk_driver::format::config::TrailingCommas;
+use vunk_driver::format::format;
+use vunk_driver::format::format_with;
+use vunk_driver::source::Source;
+
+fn fmt(code: &str) -> String {
+    fmt_with(code, &Config::default())
+}
+
+fn fmt_with(code: &str, config: &Config) -> String {
+    let source = Source {
+        name: "test.vunk".to_string(),
+        code: code.to_string(),
+    };
+    format_with(&source, config).unwrap()
+}
+
+#[test]
+fn spacing_and_let_layout() {
+    let code = "\
+add=(a:i64,b:i64)->a+b
+main = let
+  x = 1
+  y = -x
+ in Std.IO.println(add x y)   # print it
+";
+
+    assert_eq!(
+        fmt(code),
+        "\
+add = (a: i64, b: i64) -> a + b
+main = let
+    x = 1
+    y = -x
+    in Std.IO.println(add x y) # print it
+"
+    );
+}
+
+#[test]
+fn logical_not_is_attached() {
+    assert_eq!(
+        fmt("differ = (a, b) -> ! (same a b) && a != b\n"),
+        "differ = (a, b) -> !(same a b) && a != b\n"
+    );
+}
+
+#[test]
+fn comments_and_blank_lines() {
+    let code = "\n# leading\n\n\n# doc\nf = 1   \n\n\n\ng = { x: 1 }\n\n";
+
+    assert_eq!(fmt(code), "# leading\n\n# doc\nf = 1\n\ng = { x: 1 }\n");
+}
+
+#[test]
+fn where_constraints_are_aligned() {
+    let code = "\
+enum Either L R
+  where L: Std.Fmt.Debug
+     R: Std.Fmt.Debug
+  = Left L
+  | Right R
+";
+
+    assert_eq!(
+        fmt(code),
+        "\
+enum Either L R
+    where L: Std.Fmt.Debug
+          R: Std.Fmt.Debug
+    = Left L
+    | Right R
+"
+    );
+}
+
+#[test]
+fn long_lines_are_wrapped() {
+    let code = "numbers = [one_hundred, two_hundred, three_hundred, four_hundred, five_hundred, \
+                six_hundred, seven_hundred, eight_hundred]\n";
+
+    assert_eq!(
+        fmt(code),
+        "\
+numbers = [one_hundred, two_hundred, three_hundred, four_hundred, five_hundred, six_hundred,
+    seven_hundred, eight_hundred]
+"
+    );
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let examples = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../vunk-examples");
+    for entry in std::fs::read_dir(examples).unwrap() {
+        let source = Source::load(&entry.unwrap().path()).unwrap();
+        let once = format(&source).unwrap();
+        let twice = fmt(&once);
+        assert_eq!(once, twice, "Formatting {} is not idempotent", source.name);
+    }
+}
+
+#[test]
+fn lexer_errors_are_reported() {
+    let source = Source {
+        name: "test.vunk".to_string(),
+        code: "a = 1;\n".to_string(),
+    };
+
+    assert!(format(&source).is_err());
+}
+
+#[test]
+fn indent_and_width_are_configurable() {
+    let config = Config {
+        max_width: 30,
+        indent: 2,
+        ..Config::default()
+    };
+    let code = "main = let\n    x = [first, second, third, fourth]\n  in x\n";
+
+    assert_eq!(
+        fmt_with(code, &config),
+        "main = let\n  x = [first, second, third,\n    fourth]\n  in x\n"
+    );
+}
+
+#[test]
+fn trailing_commas() {
+    let code = "list = [\n    1,\n    2\n]\npair = (a, b,)\n";
+
+    let always = Config {
+        trailing_commas: TrailingCommas::Always,
+        ..Config::default()
+    };
+    assert_eq!(
+        fmt_with(code, &always),
+        "list = [\n    1,\n    2,\n]\npair = (a, b,)\n"
+    );
+
+    let never = Config {
+        trailing_commas: TrailingCommas::Never,
+        ..Config::default()
+    };
+    assert_eq!(
+        fmt_with(code, &never),
+        "list = [\n    1,\n    2\n]\npair = (a, b)\n"
+    );
+}
+
+#[test]
+fn blank_lines_between_items() {
+    let config = Config {
+        blank_lines_between_items: Some(1),
+        ..Config::default()
+    };
+    let code = "\
+one: i64
+one = 1
+# Two
+two = 2
+
+
+
+three: i64
+
+three = 3
+";
+
+    assert_eq!(
+        fmt_with(code, &config),
+        "one: i64\none = 1\n\n# Two\ntwo = 2\n\nthree: i64\nthree = 3\n"
+    );
+}
+
+#[test]
+fn config_is_discovered_from_parent_directories() {
+    let root = std::env::temp_dir().join(format!("vunk-fmt-test-{}", std::process::id()));
+    let nested = root.join("src").join("nested");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(
+        root.join("vunkfmt.toml"),
+        "max_width = 80\ntrailing_commas = \"never\"\n",
+    )
+    .unwrap();
+
+    let config = Config::discover(&nested.join("main.vunk")).unwrap();
+    std::fs::write(root.join("vunkfmt.toml"), "indentation = 2\n").unwrap();
+    let invalid = Config::discover(&nested.join("main.vunk"));
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            max_width: 80,
+            trailing_commas: TrailingCommas::Never,
+            ..Config::default()
+        }
+    );
+    assert!(invalid.is_err());
+}
@@ -0,0 +1,130 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::highlight::semantic_tokens;
+use vunk_driver::highlight::Role;
+use vunk_driver::textmate::textmate;
+
+fn roles(code: &str) -> Vec<(&str, Role)> {
+    semantic_tokens(code)
+        .into_iter()
+        .map(|token| (&code[token.span], token.role))
+        .collect()
+}
+
+#[test]
+fn definitions() {
+    let code = "\
+# Adds one
+succ: (i64) -> i64
+succ = (a: i64) -> a + 1
+
+two = succ 1
+";
+
+    assert_eq!(
+        roles(code),
+        vec![
+            ("# Adds one", Role::Comment),
+            ("succ", Role::Function),
+            ("i64", Role::Type),
+            ("->", Role::Operator),
+            ("i64", Role::Type),
+            ("succ", Role::Function),
+            ("=", Role::Operator),
+            ("a", Role::Parameter),
+            ("i64", Role::Type),
+            ("->", Role::Operator),
+            ("a", Role::Parameter),
+            ("+", Role::Operator),
+            ("1", Role::Number),
+            ("two", Role::Function),
+            ("=", Role::Operator),
+            ("succ", Role::Function),
+            ("1", Role::Number),
+        ]
+    );
+}
+
+#[test]
+fn namespaces_and_constructors() {
+    let code = "main = Std.IO.println (Some \"# no comment\") # comment\n";
+
+    assert_eq!(
+        roles(code),
+        vec![
+            ("main", Role::Function),
+            ("=", Role::Operator),
+            ("Std", Role::Namespace),
+            ("IO", Role::Namespace),
+            ("println", Role::Variable),
+            ("Some", Role::Constructor),
+            ("\"# no comment\"", Role::String),
+            ("# comment", Role::Comment),
+        ]
+    );
+}
+
+#[test]
+fn local_variables() {
+    let code = "main = do { line <- Std.IO.readLine, Std.IO.println line }\n";
+
+    let line = roles(code)
+        .into_iter()
+        .filter(|(text, _)| *text == "line")
+        .map(|(_, role)| role)
+        .collect::<Vec<_>>();
+    assert_eq!(line, vec![Role::Variable, Role::Variable]);
+}
+
+#[test]
+fn attributes() {
+    let code = "@test\nzero = assertEq 0 0\n";
+
+    assert_eq!(
+        roles(code),
+        vec![
+            ("@", Role::Attribute),
+            ("test", Role::Attribute),
+            ("zero", Role::Function),
+            ("=", Role::Operator),
+            ("assertEq", Role::Variable),
+            ("0", Role::Number),
+            ("0", Role::Number),
+        ]
+    );
+}
+
+#[test]
+fn textmate_grammar() {
+    let grammar: serde_json::Value = serde_json::from_str(&textmate()).unwrap();
+    assert_eq!(grammar["scopeName"], "source.vunk");
+
+    let regex = |scope: &str| {
+        let patterns = grammar["patterns"].as_array().unwrap();
+        let pattern = patterns
+            .iter()
+            .find(|pattern| pattern["name"] == scope)
+            .unwrap();
+        pattern["match"].as_str().unwrap().to_string()
+    };
+    let keywords = regex("keyword.control.vunk");
+    let words = keywords
+        .trim_start_matches("\\b(?:")
+        .trim_end_matches(")\\b")
+        .split('|')
+        .collect::<Vec<_>>();
+    for keyword in [
+        "if", "then", "else", "let", "in", "match", "when", "do", "use", "impl",
+    ] {
+        assert!(words.contains(&keyword), "{} in {}", keyword, keywords);
+    }
+    assert_eq!(regex("constant.language.vunk"), "\\b(?:false|true)\\b");
+
+    // Longer operators first
+    let operators = regex("keyword.operator.vunk");
+    assert!(operators.find("==").unwrap() < operators.find("|=|").unwrap());
+    assert!(operators.contains("|\\+\\+|"));
+    assert!(!operators.contains("|:|"));
+}
@@ -0,0 +1,65 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::hints::inlay_hints;
+
+// The name each hint follows, with the label
+fn hints(code: &str) -> Vec<(&str, String)> {
+    inlay_hints(code)
+        .into_iter()
+        .map(|hint| {
+            let before = &code[..hint.offset];
+            let name = before
+                .rsplit(|c: char| !c.is_alphanumeric())
+                .next()
+                .unwrap();
+            (name, hint.label)
+        })
+        .collect()
+}
+
+#[test]
+fn parameters_get_types_from_declaration() {
+    let code = "\
+add: (i64, List i64) -> i64
+add = (a, b: List i64) -> a + Std.List.length b
+
+negate: Bool -> Bool
+negate = (b) -> Std.Bool.not b
+";
+
+    assert_eq!(
+        hints(code),
+        vec![("a", ": i64".to_string()), ("b", ": Bool".to_string())]
+    );
+}
+
+#[test]
+fn let_bindings_of_literals_and_declared_names() {
+    let code = "\
+answer: i64
+answer = 42
+
+main = let
+    x = 1
+    y = \"text\"
+    z = answer
+    sum = x + y
+  in Std.IO.println y
+";
+
+    assert_eq!(
+        hints(code),
+        vec![
+            ("x", ": i64".to_string()),
+            ("y", ": String".to_string()),
+            ("z", ": i64".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn no_hints_without_declaration() {
+    assert!(inlay_hints("add = (a, b) -> a + b\n").is_empty());
+}
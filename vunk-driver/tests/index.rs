@@ -0,0 +1,70 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeSet;
+
+use vunk_driver::index::Index;
+
+const CODE: &str = "\
+type Point = { x: i64, y: i64 }
+
+origin: Point
+origin = Point { x: 0, y: 0 }
+
+norm: (Point) -> i64
+norm = (p: Point) -> p.x * p.x + p.y * p.y
+
+main = let
+    norm = 1
+  in Std.IO.println (norm origin)
+";
+
+const OTHER: &str = "size = norm origin\n";
+
+#[test]
+fn uses_skip_shadowed_names_and_members() {
+    let index = Index::new(&[CODE, OTHER]);
+
+    let uses = index
+        .uses("norm")
+        .iter()
+        .map(|use_| (use_.location.document, use_.item.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(uses, vec![(1, "size")]);
+    assert!(index.uses("x").is_empty());
+}
+
+#[test]
+fn callers_and_type_uses() {
+    let index = Index::new(&[CODE, OTHER]);
+
+    assert_eq!(index.callers("origin"), BTreeSet::from(["main", "size"]));
+
+    let type_uses = index
+        .type_uses("Point")
+        .iter()
+        .map(|use_| use_.item.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(type_uses, vec!["origin", "norm"]);
+}
+
+#[test]
+fn call_graph_in_dot() {
+    let index = Index::new(&[CODE, OTHER]);
+
+    assert_eq!(
+        index.call_graph_dot(),
+        "\
+digraph calls {
+    \"main\";
+    \"main\" -> \"origin\";
+    \"norm\";
+    \"origin\";
+    \"size\";
+    \"size\" -> \"norm\";
+    \"size\" -> \"origin\";
+}
+"
+    );
+}
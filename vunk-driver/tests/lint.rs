@@ -0,0 +1,279 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_diagnostics::fix::apply;
+use vunk_diagnostics::Diagnostic;
+use vunk_diagnostics::Severity;
+use vunk_driver::database::Database;
+use vunk_driver::error::DriverError;
+use vunk_driver::lint::lint;
+use vunk_driver::lint::Level;
+use vunk_driver::lint::Levels;
+use vunk_driver::lint::Lint;
+use vunk_driver::source::Source;
+
+fn source(code: &str) -> Source {
+    Source {
+        name: "main.vunk".to_string(),
+        code: code.to_string(),
+    }
+}
+
+fn messages(diagnostics: &[Diagnostic]) -> Vec<&str> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.message.as_str())
+        .collect()
+}
+
+// The code with the fixes of the diagnostics applied
+fn fixed(code: &str, diagnostics: &[Diagnostic]) -> String {
+    let suggestions = diagnostics
+        .iter()
+        .flat_map(|diagnostic| diagnostic.suggestions.iter());
+    apply(code, suggestions).0
+}
+
+#[test]
+fn unused_uses_are_removed() {
+    let code = "\
+use Std.IO.println
+use Std.String.toUpper
+
+main = () -> println \"hello\"
+";
+    let diagnostics = lint(&source(code), &Levels::default());
+    assert_eq!(
+        messages(&diagnostics),
+        ["`Std.String.toUpper` is never used"]
+    );
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(
+        fixed(code, &diagnostics),
+        "use Std.IO.println\n\nmain = () -> println \"hello\"\n"
+    );
+}
+
+#[test]
+fn aliased_uses_are_used_through_their_alias() {
+    let code = "\
+use Std.List as L
+use Std.String as S
+
+main = () -> L.map f xs
+";
+    let diagnostics = lint(&source(code), &Levels::default());
+    assert_eq!(messages(&diagnostics), ["`Std.String as S` is never used"]);
+    assert_eq!(
+        fixed(code, &diagnostics),
+        "use Std.List as L\n\nmain = () -> L.map f xs\n"
+    );
+}
+
+#[test]
+fn unused_globs_are_removed() {
+    let code = "\
+use Std.List.*
+use Std.Map.*
+
+main = () -> reverse [1 2 3]
+";
+    let diagnostics = lint(&source(code), &Levels::default());
+    assert_eq!(messages(&diagnostics), ["`Std.Map.*` is never used"]);
+    assert_eq!(
+        fixed(code, &diagnostics),
+        "use Std.List.*\n\nmain = () -> reverse [1 2 3]\n"
+    );
+}
+
+#[test]
+fn pointless_ifs_are_replaced_by_their_condition() {
+    let code = "positive = (x: i64) -> if x > 0 then true else false\n";
+    let diagnostics = lint(&source(code), &Levels::default());
+    assert_eq!(
+        messages(&diagnostics),
+        ["This `if` is the same as its condition"]
+    );
+    assert_eq!(
+        fixed(code, &diagnostics),
+        "positive = (x: i64) -> (x > 0)\n"
+    );
+}
+
+#[test]
+fn redundant_parentheses_are_removed() {
+    let code = "a = (1 + 2)\nb = f ((a + 1))\nc = (x) -> x\nd = () -> 1\n";
+    let diagnostics = lint(&source(code), &Levels::default());
+    assert_eq!(messages(&diagnostics), ["Unnecessary parentheses"; 2]);
+    assert_eq!(
+        fixed(code, &diagnostics),
+        "a = 1 + 2\nb = f (a + 1)\nc = (x) -> x\nd = () -> 1\n"
+    );
+}
+
+#[test]
+fn parentheses_of_type_ascriptions_are_needed() {
+    let code = "a = (1: u8)\nb = ((2: u8))\n";
+    let diagnostics = lint(&source(code), &Levels::default());
+    assert_eq!(messages(&diagnostics), ["Unnecessary parentheses"]);
+    assert_eq!(fixed(code, &diagnostics), "a = (1: u8)\nb = (2: u8)\n");
+}
+
+#[test]
+fn shadowing_is_reported_when_asked_for() {
+    let code = "\
+f = (x: i64) ->
+    let
+        y = x + 1
+        x = 2
+    in
+    y
+";
+    let diagnostics = lint(&source(code), &Levels::default());
+    assert_eq!(messages(&diagnostics), ["`x` is never used"]);
+
+    let mut levels = Levels::default();
+    levels.set(Lint::Shadowing, Level::Warn);
+    let diagnostics = lint(&source(code), &levels);
+    assert_eq!(
+        messages(&diagnostics),
+        ["`x` shadows an earlier binding", "`x` is never used"]
+    );
+}
+
+#[test]
+fn todos_are_listed_when_asked_for() {
+    let code = "\
+parse = (text: String) -> todo \"parse numbers\"
+
+render = (value: i64) -> unimplemented
+";
+    assert!(lint(&source(code), &Levels::default()).is_empty());
+
+    let mut levels = Levels::default();
+    levels.set(Lint::Todo, Level::Warn);
+    let diagnostics = lint(&source(code), &levels);
+    assert_eq!(
+        messages(&diagnostics),
+        ["todo: parse numbers", "`unimplemented` is left in the code"]
+    );
+}
+
+#[test]
+fn fields_of_records_are_not_bindings() {
+    let code = "\
+f = (x: i64) ->
+    let
+        point = { x = x, y = 1 }
+    in
+    point
+";
+    let mut levels = Levels::default();
+    levels.set(Lint::Shadowing, Level::Warn);
+    assert!(lint(&source(code), &levels).is_empty());
+}
+
+#[test]
+fn attributes_set_the_level_for_their_item() {
+    let code = "\
+@allow(unused)
+f = () ->
+    let
+        y = 1
+    in
+    2
+
+@deny(unused, redundant_parens)
+g = () ->
+    let
+        z = 1
+    in
+    2
+
+@warn(unsued)
+h = 1
+";
+    let diagnostics = lint(&source(code), &Levels::default());
+    assert_eq!(
+        messages(&diagnostics),
+        ["Unknown lint `unsued`", "`z` is never used"]
+    );
+    assert_eq!(diagnostics[1].severity, Severity::Error);
+}
+
+#[test]
+fn denied_lints_fail() {
+    let directory = std::env::temp_dir().join(format!("vunk-lint-{}", std::process::id()));
+    std::fs::create_dir_all(directory.join("src")).unwrap();
+    std::fs::write(
+        directory.join("vunk.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[lints]\nredundant_parens = \"deny\"\n",
+    )
+    .unwrap();
+    std::fs::write(directory.join("src/main.vunk"), "a = (1)\n").unwrap();
+
+    let result = vunk_driver::lint_with(&mut Database::default(), &directory);
+    let _ = std::fs::remove_dir_all(&directory);
+    match result {
+        Err(DriverError::Lints { diagnostics }) => {
+            assert_eq!(messages(&diagnostics), ["Unnecessary parentheses"])
+        }
+        other => panic!("Expected denied lints, got {:?}", other),
+    }
+}
+
+#[test]
+fn uses_of_deprecated_items_are_reported() {
+    let directory = std::env::temp_dir().join(format!("vunk-deprecated-{}", std::process::id()));
+    let files = [
+        (
+            "vunk.toml",
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        ),
+        (
+            "src/geometry.vunk",
+            "@deprecated(\"use area instead\", since = \"0.2\")\npub length = (x) -> x\n\n\
+             pub area = (x) -> x\n\npub perimeter = (x) -> length x\n",
+        ),
+        (
+            "src/main.vunk",
+            "use geometry.length\nuse geometry as G\n\na = length 1\n\nb = G.length 2\n\n\
+             @allow(deprecated)\nc = length 3\n",
+        ),
+        ("src/other.vunk", "use geometry.*\n\nd = length 4\n"),
+        (
+            "src/quiet.vunk",
+            "@allow(deprecated)\nuse geometry.length\n\ne = length 5\n",
+        ),
+    ];
+    for (path, code) in files {
+        std::fs::create_dir_all(directory.join(path).parent().unwrap()).unwrap();
+        std::fs::write(directory.join(path), code).unwrap();
+    }
+
+    let result = vunk_driver::lint_with(&mut Database::default(), &directory);
+    let _ = std::fs::remove_dir_all(&directory);
+    let diagnostics = result.unwrap();
+    assert_eq!(
+        messages(&diagnostics),
+        ["`geometry.length` is deprecated: use area instead"; 4]
+    );
+
+    let files = diagnostics.iter().map(|diagnostic| {
+        let file = diagnostic.file.as_ref().unwrap();
+        let span = diagnostic.labels[0].span.clone();
+        (file.name.rsplit('/').next().unwrap(), &file.code[span])
+    });
+    assert_eq!(
+        files.collect::<Vec<_>>(),
+        [
+            ("geometry.vunk", "length"),
+            ("main.vunk", "length"),
+            ("main.vunk", "G.length"),
+            ("other.vunk", "length"),
+        ]
+    );
+    assert_eq!(diagnostics[0].notes[0], "deprecated since 0.2");
+    assert!(diagnostics[0].notes[1].ends_with("geometry.vunk:2:5"));
+}
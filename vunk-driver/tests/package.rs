@@ -0,0 +1,686 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use vunk_diagnostics::Applicability;
+use vunk_driver::error::DriverError;
+use vunk_driver::format::config::Config;
+use vunk_driver::format::format_with;
+use vunk_driver::package::imports;
+use vunk_driver::package::lock::Lockfile;
+use vunk_driver::package::manifest::Dependency;
+use vunk_driver::package::manifest::Manifest;
+use vunk_driver::package::relative_path;
+use vunk_driver::package::scaffold::Kind;
+use vunk_driver::package::scope::std_exports;
+use vunk_driver::package::scope::Scope;
+use vunk_driver::package::uses;
+use vunk_driver::package::Graph;
+use vunk_driver::package::Resolved;
+use vunk_driver::source::Source;
+use vunk_driver::testing::tests;
+
+// A directory with the files, removed when dropped
+struct Workspace(PathBuf);
+
+impl Workspace {
+    fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let root = std::env::temp_dir().join(format!("vunk-{}-{}", name, std::process::id()));
+        for (path, content) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        Workspace(root)
+    }
+
+    fn path(&self, path: &str) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+const APP: &str = "\
+[package]
+name = \"app\"
+version = \"0.1.0\"
+
+[dependencies]
+geometry = { path = \"../geometry\" }
+";
+
+const GEOMETRY: &str = "\
+[package]
+name = \"geometry\"
+version = \"1.2.0\"
+sources = [\"lib\"]
+";
+
+fn path(path: &str) -> Vec<String> {
+    path.split('.').map(str::to_string).collect()
+}
+
+#[test]
+fn uses_resolve_across_packages() {
+    let workspace = Workspace::new(
+        "package-graph",
+        &[
+            ("app/vunk.toml", APP),
+            (
+                "app/src/main.vunk",
+                "use geometry.vector.length\nuse shapes.circle\n",
+            ),
+            ("app/src/shapes/circle.vunk", "area = 1\n"),
+            ("geometry/vunk.toml", GEOMETRY),
+            ("geometry/lib/vector.vunk", "length = 1\n"),
+        ],
+    );
+
+    let graph = Graph::load(&workspace.path("app")).unwrap();
+    let names = graph
+        .packages
+        .iter()
+        .map(|package| package.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["geometry", "app"]);
+    assert_eq!(graph.root().dependencies["geometry"], 0);
+
+    let modules = graph
+        .root()
+        .modules
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    assert_eq!(modules, vec!["main", "shapes.circle"]);
+
+    assert_eq!(
+        graph.resolve(1, &path("geometry.vector.length")),
+        Some(Resolved::Module {
+            package: 0,
+            module: "vector".to_string(),
+            item: Some("length".to_string()),
+        })
+    );
+    assert_eq!(
+        graph.resolve(1, &path("shapes.circle")),
+        Some(Resolved::Module {
+            package: 1,
+            module: "shapes.circle".to_string(),
+            item: None,
+        })
+    );
+    assert_eq!(
+        graph.resolve(1, &path("Std.IO.println")),
+        Some(Resolved::Std)
+    );
+    assert_eq!(graph.resolve(1, &path("geometry.matrix")), None);
+    assert_eq!(graph.resolve(0, &path("app.main")), None);
+
+    assert_eq!(vunk_driver::load_modules(&graph).unwrap().len(), 3);
+}
+
+#[test]
+fn module_graphs_flag_cycles() {
+    let workspace = Workspace::new(
+        "module-graph",
+        &[
+            ("app/vunk.toml", APP),
+            (
+                "app/src/main.vunk",
+                "use parser.parse\nuse geometry.vector.length\n",
+            ),
+            ("app/src/parser.vunk", "use lexer.lex\n"),
+            ("app/src/lexer.vunk", "use tokens.Token\nuse parser.parse\n"),
+            ("app/src/tokens.vunk", "use Std.List.map\n"),
+            ("geometry/vunk.toml", GEOMETRY),
+            ("geometry/lib/vector.vunk", "length = 1\n"),
+        ],
+    );
+
+    let graph = vunk_driver::module_graph(&workspace.path("app/src/main.vunk")).unwrap();
+    assert_eq!(
+        graph.modules,
+        ["geometry.vector", "lexer", "main", "parser", "tokens"]
+    );
+    let uses = graph.uses.iter().copied().collect::<Vec<_>>();
+    assert_eq!(uses, [(1, 3), (1, 4), (2, 0), (2, 3), (3, 1)]);
+    assert_eq!(graph.cycles(), [["lexer", "parser"]]);
+
+    let dot = graph.dot();
+    assert!(dot.contains("    \"lexer\" -> \"parser\" [color=red];\n"));
+    assert!(dot.contains("    \"main\" -> \"parser\";\n"));
+
+    let json = serde_json::from_str::<serde_json::Value>(&graph.json()).unwrap();
+    assert_eq!(json["cycles"], serde_json::json!([["lexer", "parser"]]));
+    assert_eq!(json["uses"][0]["cycle"], serde_json::json!(true));
+    assert_eq!(json["uses"][1]["cycle"], serde_json::json!(false));
+}
+
+#[test]
+fn import_cycles_are_rejected() {
+    let workspace = Workspace::new(
+        "import-cycle",
+        &[
+            ("app/vunk.toml", APP),
+            ("app/src/main.vunk", "use parser.parse\n"),
+            ("app/src/parser.vunk", "use lexer.lex\n"),
+            ("app/src/lexer.vunk", "use tokens.Token\nuse main.main\n"),
+            ("app/src/tokens.vunk", "Token = 1\n"),
+            ("geometry/vunk.toml", GEOMETRY),
+        ],
+    );
+
+    let graph = Graph::load(&workspace.path("app")).unwrap();
+    let (cycle, uses) = match vunk_driver::load_modules(&graph) {
+        Err(DriverError::ImportCycle { cycle, uses }) => (cycle, uses),
+        other => panic!("Expected an import cycle, got {:?}", other),
+    };
+    assert_eq!(cycle, "lexer → main → parser → lexer");
+
+    let labels = uses
+        .iter()
+        .map(|diagnostic| {
+            let file = diagnostic.file.as_ref().unwrap();
+            let span = diagnostic.labels[0].span.clone();
+            (diagnostic.message.as_str(), &file.code[span])
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            ("lexer uses main", "main.main"),
+            ("main uses parser", "parser.parse"),
+            ("parser uses lexer", "lexer.lex"),
+        ]
+    );
+
+    let error = vunk_driver::check(&workspace.path("app")).unwrap_err();
+    assert!(matches!(error, DriverError::ImportCycle { .. }));
+    assert_eq!(error.diagnostics().len(), 4);
+}
+
+#[test]
+fn unresolved_uses_are_reported() {
+    let workspace = Workspace::new(
+        "package-unresolved",
+        &[
+            ("app/vunk.toml", APP),
+            ("app/src/main.vunk", "use geometry.matrix.identity\n"),
+            ("geometry/vunk.toml", GEOMETRY),
+            ("geometry/lib/vector.vunk", "length = 1\n"),
+        ],
+    );
+
+    let graph = Graph::load(&workspace.path("app")).unwrap();
+    match vunk_driver::load_modules(&graph) {
+        Err(DriverError::UnresolvedUse(unresolved)) => {
+            assert_eq!(unresolved.path, "geometry.matrix.identity");
+            assert_eq!(unresolved.package, "app");
+        }
+        other => panic!("Expected an unresolved use, got {:?}", other),
+    }
+}
+
+#[test]
+fn missing_items_are_told_apart_from_missing_modules() {
+    let workspace = Workspace::new(
+        "package-missing-item",
+        &[
+            ("app/vunk.toml", APP),
+            (
+                "app/src/main.vunk",
+                "use geometry.vector as V\n\nmain = V.lenght 1\n",
+            ),
+            ("geometry/vunk.toml", GEOMETRY),
+            ("geometry/lib/vector.vunk", "length = 1\n"),
+        ],
+    );
+
+    let graph = Graph::load(&workspace.path("app")).unwrap();
+    let error = vunk_driver::load_modules(&graph).unwrap_err();
+    match &error {
+        DriverError::UnresolvedItem(unresolved) => {
+            assert_eq!(unresolved.item, "lenght");
+            assert_eq!(unresolved.module, "geometry.vector");
+            assert_eq!(unresolved.suggestion.as_ref().unwrap().0, "length");
+        }
+        other => panic!("Expected an unresolved item, got {:?}", other),
+    }
+
+    let diagnostics = error.diagnostics();
+    let file = diagnostics[0].file.as_ref().unwrap();
+    assert_eq!(&file.code[diagnostics[0].labels[0].span.clone()], "lenght");
+
+    std::fs::write(
+        workspace.path("app/src/main.vunk"),
+        "use geometry.vectr as V\n",
+    )
+    .unwrap();
+    let graph = Graph::load(&workspace.path("app")).unwrap();
+    assert!(matches!(
+        vunk_driver::load_modules(&graph),
+        Err(DriverError::UnresolvedUse { .. })
+    ));
+}
+
+#[test]
+fn names_of_more_than_one_glob_are_ambiguous_where_used() {
+    let workspace = Workspace::new(
+        "package-globs",
+        &[
+            ("app/vunk.toml", APP),
+            (
+                "app/src/main.vunk",
+                "use geometry.vector.*\nuse geometry.matrix.*\n\nmain = scale 2\n",
+            ),
+            ("geometry/vunk.toml", GEOMETRY),
+            (
+                "geometry/lib/vector.vunk",
+                "pub scale = 1\npub length = 1\n",
+            ),
+            ("geometry/lib/matrix.vunk", "pub scale = 2\nlength = 2\n"),
+        ],
+    );
+
+    let graph = Graph::load(&workspace.path("app")).unwrap();
+    match vunk_driver::load_modules(&graph) {
+        Err(DriverError::AmbiguousName { name, globs, .. }) => {
+            assert_eq!(name, "scale");
+            assert_eq!(globs, "geometry.vector.* and geometry.matrix.*");
+        }
+        other => panic!("Expected an ambiguous name, got {:?}", other),
+    }
+
+    // Private items are not brought into scope, and explicit uses beat globs
+    let main = workspace.path("app/src/main.vunk");
+    let globs = "use geometry.vector.*\nuse geometry.matrix.*\n";
+    std::fs::write(&main, format!("{}\nmain = length 2\n", globs)).unwrap();
+    assert!(vunk_driver::load_modules(&graph).is_ok());
+    let code = format!("{}use geometry.matrix.scale\n\nmain = scale 2\n", globs);
+    std::fs::write(&main, code).unwrap();
+    assert!(vunk_driver::load_modules(&graph).is_ok());
+}
+
+#[test]
+fn misspelled_uses_are_fixed() {
+    let workspace = Workspace::new(
+        "package-misspelled",
+        &[
+            ("app/vunk.toml", APP),
+            (
+                "app/src/main.vunk",
+                "use geometry.vectr.length\n\nmain = length\n",
+            ),
+            ("geometry/vunk.toml", GEOMETRY),
+            ("geometry/lib/vector.vunk", "length = 1\n"),
+        ],
+    );
+
+    let graph = Graph::load(&workspace.path("app")).unwrap();
+    match vunk_driver::load_modules(&graph) {
+        Err(DriverError::UnresolvedUse(unresolved)) => assert_eq!(
+            unresolved.suggestion,
+            Some((
+                "geometry.vector.length".to_string(),
+                Applicability::MachineApplicable
+            ))
+        ),
+        other => panic!("Expected an unresolved use, got {:?}", other),
+    }
+
+    assert_eq!(vunk_driver::fix(&workspace.path("app")).unwrap(), 1);
+    let main = std::fs::read_to_string(workspace.path("app/src/main.vunk")).unwrap();
+    assert_eq!(main, "use geometry.vector.length\n\nmain = length\n");
+}
+
+fn manifest(name: &str, dependency: &str) -> String {
+    format!(
+        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\n\n\
+         [dependencies]\n{} = {{ path = \"../{}\" }}\n",
+        name, dependency, dependency
+    )
+}
+
+#[test]
+fn dependency_cycles_are_rejected() {
+    let workspace = Workspace::new(
+        "package-cycle",
+        &[
+            ("a/vunk.toml", manifest("a", "b").as_str()),
+            ("b/vunk.toml", manifest("b", "a").as_str()),
+        ],
+    );
+
+    assert!(matches!(
+        Graph::load(&workspace.path("a")),
+        Err(DriverError::DependencyCycle { .. })
+    ));
+}
+
+#[test]
+fn invalid_manifests_are_rejected() {
+    let workspace = Workspace::new(
+        "package-invalid",
+        &[
+            (
+                "version/vunk.toml",
+                "[package]\nname = \"a\"\nversion = \"one\"\n",
+            ),
+            (
+                "name/vunk.toml",
+                "[package]\nname = \"my-package\"\nversion = \"0.1.0\"\n",
+            ),
+        ],
+    );
+
+    for package in ["version", "name"] {
+        assert!(matches!(
+            Graph::load(&workspace.path(package)),
+            Err(DriverError::Manifest { .. })
+        ));
+    }
+}
+
+#[test]
+fn imports_of_a_file() {
+    let code = "use Std.IO.println\n\npub use geometry.vector\n\nmain = println \"use\"\n";
+    let imports = imports(code);
+
+    let paths = imports
+        .iter()
+        .map(|import| import.path.join("."))
+        .collect::<Vec<_>>();
+    assert_eq!(paths, vec!["Std.IO.println", "geometry.vector"]);
+    assert_eq!(&code[imports[1].span.clone()], "geometry.vector");
+}
+
+#[test]
+fn aliases_expand_qualified_paths() {
+    let code = "use Std.List as L\n\nmain = L.map f (L.reverse xs)\n";
+    let imports = imports(code);
+    assert_eq!(imports.len(), 1);
+    assert_eq!(imports[0].path, ["Std", "List"]);
+    assert_eq!(imports[0].name(), Some("L"));
+    assert_eq!(&code[imports[0].alias.as_ref().unwrap().1.clone()], "L");
+
+    let paths = uses(code)
+        .iter()
+        .map(|import| (import.path.join("."), &code[import.span.clone()]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            ("Std.List".to_string(), "Std.List"),
+            ("Std.List.map".to_string(), "L.map"),
+            ("Std.List.reverse".to_string(), "L.reverse"),
+        ]
+    );
+}
+
+#[test]
+fn globs_use_modules() {
+    let code = "use Std.List.*\nuse Std.IO.println\n";
+    let imports = imports(code);
+    assert!(imports[0].glob);
+    assert_eq!(imports[0].path, ["Std", "List"]);
+    assert_eq!(imports[0].name(), None);
+    assert_eq!(&code[imports[0].span.clone()], "Std.List.*");
+    assert!(!imports[1].glob);
+}
+
+#[test]
+fn the_prelude_comes_last() {
+    let code = "use Std.Option.*\n\nErr = 1\n";
+    let scope = Scope::new(code, &std_exports(code));
+    assert_eq!(scope.prelude("Ok"), Some("Std.Result.Ok"));
+    assert_eq!(scope.prelude("Err"), None);
+    assert_eq!(scope.prelude("Some"), None);
+    assert_eq!(scope.lookup("Some").len(), 1);
+
+    let code = "use Std.Option.*\nuse geometry.Ok\n";
+    let scope = Scope::new(code, &std_exports(code));
+    assert_eq!(scope.prelude("Ok"), None);
+    assert_eq!(scope.prelude("Err"), Some("Std.Result.Err"));
+
+    let code = "@no_prelude\nuse Std.IO.println\n";
+    let scope = Scope::new(code, &std_exports(code));
+    assert_eq!(scope.prelude("Ok"), None);
+}
+
+#[test]
+fn manifests_are_found_in_parent_directories() {
+    let workspace = Workspace::new(
+        "package-root",
+        &[("app/vunk.toml", APP), ("app/src/main.vunk", "")],
+    );
+
+    let root = Manifest::find_root(&workspace.path("app/src"));
+    assert_eq!(root, Some(workspace.path("app").canonicalize().unwrap()));
+}
+
+#[test]
+fn version_requirements_are_checked() {
+    let app = APP.replace(
+        "path = \"../geometry\"",
+        "path = \"../geometry\", version = \"^2\"",
+    );
+    let workspace = Workspace::new(
+        "package-version",
+        &[
+            ("app/vunk.toml", app.as_str()),
+            ("geometry/vunk.toml", GEOMETRY),
+        ],
+    );
+
+    match Graph::load(&workspace.path("app")) {
+        Err(DriverError::UnsatisfiedVersion { name, version, .. }) => {
+            assert_eq!(name, "geometry");
+            assert_eq!(version, "1.2.0");
+        }
+        other => panic!("Expected an unsatisfied version, got {:?}", other),
+    }
+}
+
+#[test]
+fn local_dependencies_are_locked_by_version() {
+    let workspace = Workspace::new(
+        "package-lock",
+        &[("app/vunk.toml", APP), ("geometry/vunk.toml", GEOMETRY)],
+    );
+
+    Graph::load(&workspace.path("app")).unwrap();
+    let lockfile = Lockfile::load(&workspace.path("app/vunk.lock"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(lockfile.packages.len(), 1);
+    assert_eq!(lockfile.packages[0].name, "geometry");
+    assert_eq!(lockfile.packages[0].version.to_string(), "1.2.0");
+    assert_eq!(lockfile.packages[0].source, None);
+    assert_eq!(lockfile.packages[0].checksum, None);
+}
+
+fn git(directory: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(["-c", "user.name=vunk", "-c", "user.email=vunk@example.com"])
+        .args(args)
+        .current_dir(directory)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+// Commit a version of the package in the repository and tag it
+fn release(repository: &Path, version: &str) {
+    let manifest = format!("[package]\nname = \"json\"\nversion = \"{}\"\n", version);
+    std::fs::write(repository.join("vunk.toml"), manifest).unwrap();
+    git(repository, &["add", "."]);
+    git(repository, &["commit", "--quiet", "-m", version]);
+    git(repository, &["tag", &format!("v{}", version)]);
+}
+
+#[test]
+fn git_dependencies_are_resolved_locked_and_updated() {
+    let workspace = Workspace::new(
+        "package-git",
+        &[
+            ("json/src/value.vunk", "null = 0\n"),
+            ("app/src/main.vunk", "use json.value\n"),
+        ],
+    );
+    let repository = workspace.path("json");
+    git(&repository, &["init", "--quiet"]);
+    release(&repository, "1.0.0");
+    release(&repository, "1.1.0");
+    release(&repository, "2.0.0");
+
+    let app = format!(
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\n\
+         json = {{ git = \"{}\", version = \"^1\" }}\n",
+        repository.display()
+    );
+    std::fs::write(workspace.path("app/vunk.toml"), app).unwrap();
+
+    let version = |graph: &Graph| graph.packages[0].version.to_string();
+    let graph = Graph::load(&workspace.path("app")).unwrap();
+    assert_eq!(version(&graph), "1.1.0");
+    vunk_driver::load_modules(&graph).unwrap();
+
+    let lockfile = Lockfile::load(&workspace.path("app/vunk.lock"))
+        .unwrap()
+        .unwrap();
+    let source = lockfile.packages[0].source.as_deref().unwrap();
+    assert!(source.starts_with(&format!("git+{}#", repository.display())));
+    assert!(lockfile.packages[0].checksum.is_some());
+
+    // The lockfile keeps the version until the package is updated
+    release(&repository, "1.2.0");
+    assert_eq!(
+        version(&Graph::load(&workspace.path("app")).unwrap()),
+        "1.1.0"
+    );
+    assert_eq!(
+        version(&Graph::update(&workspace.path("app")).unwrap()),
+        "1.2.0"
+    );
+
+    // Changing a locked dependency is detected
+    let checkout = workspace.path("app/target/git/json/src/value.vunk");
+    std::fs::write(checkout, "null = 1\n").unwrap();
+    assert!(matches!(
+        Graph::load(&workspace.path("app")),
+        Err(DriverError::Checksum { .. })
+    ));
+}
+
+#[test]
+fn dependencies_are_added_to_the_manifest() {
+    let manifest = "\
+[package]
+name = \"app\"
+version = \"0.1.0\"
+
+[dependencies]
+# Vectors and matrices
+geometry = { path = \"../geometry\" }
+
+[other]
+";
+    let workspace = Workspace::new(
+        "package-add",
+        &[
+            ("app/vunk.toml", manifest),
+            ("app/src/main.vunk", ""),
+            ("geometry/vunk.toml", GEOMETRY),
+            (
+                "shapes/vunk.toml",
+                "[package]\nname = \"shapes\"\nversion = \"0.3.1\"\n",
+            ),
+        ],
+    );
+
+    let dependency = Dependency::Path {
+        path: PathBuf::from("../../shapes"),
+        version: Some("^0.3".parse().unwrap()),
+    };
+    vunk_driver::add(&workspace.path("app/src"), "shapes", dependency.clone()).unwrap();
+
+    let content = std::fs::read_to_string(workspace.path("app/vunk.toml")).unwrap();
+    assert_eq!(
+        content,
+        manifest.replace(
+            "\"../geometry\" }\n",
+            "\"../geometry\" }\nshapes = { path = \"../shapes\", version = \"^0.3\" }\n"
+        )
+    );
+    assert!(workspace.path("app/vunk.lock").is_file());
+
+    assert!(matches!(
+        vunk_driver::add(&workspace.path("app/src"), "shapes", dependency),
+        Err(DriverError::DependencyExists(_))
+    ));
+}
+
+#[test]
+fn relative_paths() {
+    assert_eq!(
+        relative_path(Path::new("/work/app"), Path::new("/work/lib/geometry")),
+        PathBuf::from("../lib/geometry")
+    );
+    assert_eq!(
+        relative_path(Path::new("/work"), Path::new("/work/app")),
+        PathBuf::from("app")
+    );
+}
+
+#[test]
+fn new_packages_are_complete() {
+    let workspace = Workspace::new("package-new", &[]);
+    std::fs::create_dir_all(&workspace.0).unwrap();
+
+    for (name, kind, module) in [
+        ("hello", Kind::Binary, "main"),
+        ("adder", Kind::Library, "lib"),
+    ] {
+        let directory = workspace.path(name);
+        vunk_driver::new(&directory, kind).unwrap();
+
+        let graph = Graph::load(&directory).unwrap();
+        assert_eq!(graph.root().name, name);
+        let modules = graph
+            .root()
+            .modules
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        assert_eq!(modules, vec![module]);
+
+        let source = Source::load(&graph.root().modules[module]).unwrap();
+        let config = Config::discover(&graph.root().modules[module]).unwrap();
+        assert_eq!(format_with(&source, &config).unwrap(), source.code);
+        assert!(!tests(&source.code).is_empty());
+
+        assert!(matches!(
+            vunk_driver::new(&directory, kind),
+            Err(DriverError::AlreadyExists { .. })
+        ));
+        assert!(matches!(
+            vunk_driver::init(&directory, kind),
+            Err(DriverError::AlreadyExists { .. })
+        ));
+    }
+
+    let invalid = workspace.path("not-a-name");
+    assert!(matches!(
+        vunk_driver::new(&invalid, Kind::Binary),
+        Err(DriverError::InvalidName(_))
+    ));
+}
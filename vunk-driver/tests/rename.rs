@@ -0,0 +1,79 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::error::DriverError;
+use vunk_driver::rename::rename;
+
+const LIB: &str = "\
+square: (i64) -> i64
+square = (x: i64) -> x * x
+
+twice = (x: i64) -> let
+    square = x + x
+  in square
+";
+
+const MAIN: &str = "main = Std.IO.println (square 2)\n";
+
+// Apply the edits to the document
+fn apply(documents: &[&str], document: usize, offset: usize, new_name: &str) -> Vec<String> {
+    let mut codes = documents
+        .iter()
+        .map(|code| code.to_string())
+        .collect::<Vec<_>>();
+    let mut edits = rename(documents, document, offset, new_name).unwrap();
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.span.start));
+    for edit in edits {
+        codes[edit.document].replace_range(edit.span, &edit.new_text);
+    }
+    codes
+}
+
+#[test]
+fn renames_top_level_names_in_all_documents() {
+    let codes = apply(&[LIB, MAIN], 1, MAIN.find("square").unwrap(), "sq");
+
+    assert_eq!(
+        codes[0],
+        "\
+sq: (i64) -> i64
+sq = (x: i64) -> x * x
+
+twice = (x: i64) -> let
+    square = x + x
+  in square
+"
+    );
+    assert_eq!(codes[1], "main = Std.IO.println (sq 2)\n");
+}
+
+#[test]
+fn renames_locals_in_their_definition() {
+    let codes = apply(&[LIB, MAIN], 0, LIB.find("x * x").unwrap(), "y");
+
+    assert!(codes[0].starts_with("square: (i64) -> i64\nsquare = (y: i64) -> y * y\n"));
+    assert!(codes[0].ends_with("twice = (x: i64) -> let\n    square = x + x\n  in square\n"));
+}
+
+#[test]
+fn conflicts_are_errors() {
+    let main = MAIN.find("square").unwrap();
+
+    assert!(matches!(
+        rename(&[LIB, MAIN], 1, main, "twice"),
+        Err(DriverError::RenameConflict { .. })
+    ));
+    assert!(matches!(
+        rename(&[LIB, MAIN], 1, main, "x"),
+        Err(DriverError::RenameConflict { .. })
+    ));
+    assert!(matches!(
+        rename(&[LIB, MAIN], 1, main, "not a name"),
+        Err(DriverError::InvalidName(_))
+    ));
+    assert!(matches!(
+        rename(&[LIB, MAIN], 1, MAIN.find("println").unwrap(), "print"),
+        Err(DriverError::UnknownName(_))
+    ));
+}
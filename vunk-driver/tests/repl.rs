@@ -0,0 +1,86 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::context::RunOptions;
+use vunk_driver::repl::is_complete;
+use vunk_driver::repl::Reply;
+use vunk_driver::repl::Session;
+
+#[test]
+fn complete_inputs() {
+    assert!(is_complete("1 + 2"));
+    assert!(is_complete("a = (b: i64) -> b"));
+    assert!(is_complete("x = { a: 1 } # comment"));
+    assert!(is_complete("use Std.List.*"));
+}
+
+#[test]
+fn incomplete_inputs() {
+    assert!(!is_complete("a ="));
+    assert!(!is_complete("a = (b: i64) ->"));
+    assert!(!is_complete("x = { a: 1"));
+    assert!(!is_complete("f [1 2"));
+    assert!(!is_complete("\"unterminated"));
+    assert!(!is_complete("a = let\n    b = 1\n  in"));
+}
+
+#[test]
+fn redefinition_replaces_definition() {
+    let mut session = Session::new(RunOptions::default());
+    for input in ["a: i64", "a = 1", "b = a", "a = 2"] {
+        assert!(matches!(session.eval(input).unwrap(), Reply::Defined(_)));
+    }
+
+    let definitions = session.definitions().collect::<Vec<_>>();
+    assert_eq!(definitions, vec!["a: i64", "b = a", "a = 2"]);
+}
+
+#[test]
+fn redeclaration_drops_definition() {
+    let mut session = Session::new(RunOptions::default());
+    for input in ["a: i64", "a = 1", "a: String"] {
+        session.eval(input).unwrap();
+    }
+
+    let definitions = session.definitions().collect::<Vec<_>>();
+    assert_eq!(definitions, vec!["a: String"]);
+}
+
+#[test]
+fn info_shows_declaration_with_doc_comment() {
+    let mut session = Session::new(RunOptions::default());
+    session.eval("# The answer\na: i64").unwrap();
+    session.eval("a = 42").unwrap();
+
+    let reply = session.eval(":i a").unwrap().to_string();
+    assert_eq!(reply, "# The answer\na: i64");
+    assert!(session.eval(":i b").is_err());
+    assert!(session.eval(":i Std.List.map").is_ok());
+}
+
+#[test]
+fn load_and_reset() {
+    let mut session = Session::new(RunOptions::default());
+    let reply = session.eval(":load ../vunk-examples/0047.vunk").unwrap();
+    assert!(matches!(reply, Reply::Loaded(4)));
+
+    let reply = session.eval(":i greeting").unwrap().to_string();
+    assert_eq!(reply, "greeting: (String) -> String");
+
+    assert!(matches!(session.eval(":reset").unwrap(), Reply::Reset));
+    assert_eq!(session.definitions().count(), 0);
+}
+
+#[test]
+fn unknown_command() {
+    let mut session = Session::new(RunOptions::default());
+    assert!(session.eval(":frobnicate").is_err());
+}
+
+#[test]
+fn inspect_needs_an_expression() {
+    let mut session = Session::new(RunOptions::default());
+    assert!(session.eval(":inspect \"unterminated").is_err());
+    assert!(session.eval(":inspect [1 2 3]").is_err());
+}
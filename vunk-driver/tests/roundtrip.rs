@@ -0,0 +1,47 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::error::DriverError;
+use vunk_driver::roundtrip::check;
+use vunk_driver::roundtrip::check_generated;
+use vunk_parser::ast::def::Def;
+use vunk_parser::ast::def::DefArg;
+use vunk_parser::ast::def::DefRhs;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::program::Program;
+
+#[test]
+fn generated_programs_round_trip() {
+    let failures = check_generated(0..256, 4)
+        .into_iter()
+        .map(|(seed, error)| format!("seed {}: {:?}", seed, error))
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn programs_the_parser_reads_differently_fail() {
+    // `f = (x) -> x` is read as a definition with a parameter, not of a lambda
+    let program = Program {
+        expr: vec![Expr::Def(Def {
+            lhs: VariableName("f".to_string()),
+            rhs: DefRhs {
+                args: Vec::new(),
+                expr: Box::new(Expr::Lambda(DefRhs {
+                    args: vec![DefArg {
+                        name: VariableName("x".to_string()),
+                        ty: None,
+                        pattern: None,
+                    }],
+                    expr: Box::new(Expr::Variable(VariableName("x".to_string()))),
+                })),
+            },
+        })],
+    };
+    assert!(matches!(
+        check(&program),
+        Err(DriverError::RoundTrip { .. })
+    ));
+}
@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+
+use vunk_driver::error::DriverError;
+use vunk_driver::source::Source;
+
+// Found by the `lex` fuzz target: every error held a copy of the file, so a few hundred kilobytes
+// of garbage took gigabytes
+#[test]
+fn errors_of_the_lexer_share_the_file() {
+    let source = Source {
+        name: "garbage.vunk".to_string(),
+        code: "a = \"é\" \0\n".repeat(1_000),
+    };
+    let errors = match source.lex() {
+        Err(DriverError::Lex { errors, .. }) => errors,
+        other => panic!(
+            "expected errors of the lexer, got {:?}",
+            other.map(|tokens| tokens.len())
+        ),
+    };
+    assert_eq!(errors.len(), 1_000);
+
+    let code = &errors[0].file.as_ref().unwrap().code;
+    for error in &errors {
+        assert!(Arc::ptr_eq(&error.file.as_ref().unwrap().code, code));
+        assert_eq!(&source.code[error.labels[0].span.clone()], "\0");
+    }
+}
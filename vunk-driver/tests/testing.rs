@@ -0,0 +1,78 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::context::RunOptions;
+use vunk_driver::outline::outline;
+use vunk_driver::testing::tests;
+use vunk_driver::testing::TestKind;
+
+const CODE: &str = "\
+# Twice the number
+#
+# ```
+# assertEq (twice 2) 4
+# ```
+twice = (n: Int) -> 2 * n
+
+@test
+twice_zero = assertEq (twice 0) 0
+
+# Not a test
+helper = twice 1
+";
+
+#[test]
+fn attributes_belong_to_the_item() {
+    let items = outline(CODE);
+    let names = items
+        .iter()
+        .map(|item| item.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["twice", "twice_zero", "helper"]);
+    assert_eq!(items[1].attributes(CODE), vec!["test"]);
+    assert!(CODE[items[1].span.clone()].starts_with("@test\n"));
+    assert!(items[2].attributes(CODE).is_empty());
+}
+
+#[test]
+fn definitions_and_examples_are_tests() {
+    let tests = tests(CODE);
+    let names = tests
+        .iter()
+        .map(|test| test.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["twice_zero", "twice (example 1)"]);
+
+    assert_eq!(tests[0].kind, TestKind::Definition);
+    assert_eq!(
+        tests[1].kind,
+        TestKind::Example("assertEq (twice 2) 4".to_string())
+    );
+}
+
+#[test]
+fn directories_are_searched_and_tests_filtered() {
+    let root = std::env::temp_dir().join(format!("vunk-test-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("nested")).unwrap();
+    std::fs::write(root.join("nested/twice.vunk"), CODE).unwrap();
+    std::fs::write(root.join("notes.txt"), "@test\nnot_vunk = 1\n").unwrap();
+
+    let roots = std::slice::from_ref(&root);
+    let all = vunk_driver::test(roots, None, RunOptions::default()).unwrap();
+    let filtered = vunk_driver::test(roots, Some("example"), RunOptions::default());
+    std::fs::remove_dir_all(&root).unwrap();
+
+    // Nothing can be evaluated yet, so every test fails
+    assert_eq!(all.failures.len(), 2);
+    assert_eq!(all.failures[0].name, "twice_zero");
+    assert_eq!(all.filtered_out, 0);
+    assert!(all.into_result().is_err());
+
+    let filtered = filtered.unwrap();
+    assert_eq!(filtered.failures.len(), 1);
+    assert_eq!(filtered.filtered_out, 1);
+    assert!(filtered
+        .to_string()
+        .ends_with("0 passed, 1 failed, 1 filtered out\n"));
+}
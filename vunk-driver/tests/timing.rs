@@ -0,0 +1,65 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::database::Database;
+use vunk_driver::source::Source;
+use vunk_driver::timing::Timings;
+
+#[test]
+fn phases_are_timed_per_module() {
+    let mut database = Database::default();
+    database.enable_timings();
+    for name in ["a.vunk", "b.vunk"] {
+        database.set_source(Source {
+            name: name.to_string(),
+            code: "a = 1\n".to_string(),
+        });
+    }
+    let names = ["a.vunk".to_string(), "b.vunk".to_string()];
+    assert!(database.typecheck_all(&names).iter().all(Result::is_err));
+
+    // Typechecking is not implemented, but every phase up to it runs
+    let timings = database.timings().unwrap();
+    let phases = timings
+        .phases()
+        .into_iter()
+        .map(|(phase, _, runs)| (phase, runs))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        phases,
+        [("lex", 2), ("parse", 2), ("desugar", 2), ("typecheck", 2)]
+    );
+
+    let report = timings.report();
+    assert!(report.starts_with("Phase"));
+    assert!(report.contains("\na.vunk "));
+    assert!(report.contains("\nb.vunk "));
+}
+
+#[test]
+fn nothing_is_timed_unless_enabled() {
+    let mut database = Database::default();
+    database.set_source(Source {
+        name: "a.vunk".to_string(),
+        code: "a = 1\n".to_string(),
+    });
+    let _ = database.lex("a.vunk");
+    assert!(database.timings().is_none());
+}
+
+#[test]
+fn traces_are_chrome_trace_events() {
+    let timings = Timings::default();
+    let sum = timings.time("lex", Some("a.vunk"), || 1 + 1);
+    timings.time("load", None, || ());
+    assert_eq!(sum, 2);
+
+    let trace: serde_json::Value = serde_json::from_str(&timings.chrome_trace()).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["name"], "lex");
+    assert_eq!(events[0]["ph"], "X");
+    assert_eq!(events[0]["args"]["module"], "a.vunk");
+    assert_eq!(events[1]["args"]["module"], serde_json::Value::Null);
+}
@@ -0,0 +1,140 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+
+use vunk_driver::context::DriverContext;
+use vunk_driver::context::RunOptions;
+use vunk_driver::trace::render;
+use vunk_driver::trace::Event;
+use vunk_driver::trace::Trace;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::function::Context;
+use vunk_runtime::value::Value;
+
+fn ints(values: &[i64]) -> Value {
+    Value::list(values.iter().copied().map(Value::Integer).collect())
+}
+
+// Run `f` with a traced context, returning the events it traced
+fn traced(name: &str, f: impl FnOnce(&mut DriverContext)) -> Vec<Event> {
+    let path = std::env::temp_dir().join(format!("vunk-trace-{}-{}", name, std::process::id()));
+    let mut context =
+        DriverContext::new(RunOptions::default()).traced(Trace::create(&path).unwrap());
+    f(&mut context);
+    drop(context);
+
+    let trace = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(path);
+    let lines = trace
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap());
+    lines.collect()
+}
+
+#[test]
+fn calls_are_traced_with_their_nesting() {
+    let builtins = Builtins::std();
+    let events = traced("nesting", |context| {
+        let add_one = builtins.value("Std.Int.wrappingAdd").unwrap();
+        let add_one = context.call(&add_one, vec![Value::Integer(1)]).unwrap();
+        let map = builtins.value("Std.List.map").unwrap();
+        context.call(&map, vec![add_one, ints(&[1, 2])]).unwrap();
+    });
+
+    let lines = events.iter().map(line).collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "enter Std.Int.wrappingAdd 1",
+            "exit Std.Int.wrappingAdd = <function Std.Int.wrappingAdd>",
+            "enter Std.List.map <function Std.Int.wrappingAdd> [1 2]",
+            "  enter Std.Int.wrappingAdd 1",
+            "  exit Std.Int.wrappingAdd = 2",
+            "  enter Std.Int.wrappingAdd 2",
+            "  exit Std.Int.wrappingAdd = 3",
+            "exit Std.List.map = [2 3]",
+        ]
+    );
+
+    let steps = events.iter().map(|event| match event {
+        Event::Enter { step, .. } | Event::Exit { step, .. } => *step,
+    });
+    assert!(steps.eq(0..8));
+}
+
+#[test]
+fn failed_calls_are_traced_with_their_error() {
+    let builtins = Builtins::std();
+    let events = traced("error", |context| {
+        let map = builtins.value("Std.List.map").unwrap();
+        assert!(context
+            .call(&map, vec![Value::Integer(1), Value::Integer(2)])
+            .is_err());
+    });
+
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        &events[1],
+        Event::Exit {
+            result: None,
+            error: Some(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn values_are_cut_off() {
+    assert_eq!(render(&ints(&[1, 2, 3]), 1), "[1 2 3]");
+    assert_eq!(render(&Value::list(vec![ints(&[1])]), 1), "[[…]]");
+    assert_eq!(
+        render(&ints(&(0..12).collect::<Vec<_>>()), 1),
+        "[0 1 2 3 4 5 6 7 8 9 …]"
+    );
+    assert_eq!(
+        render(&Value::some(Value::some(Value::Unit)), 3),
+        "Some (Some ())"
+    );
+    assert_eq!(render(&Value::some(Value::Unit), 0), "Some …");
+
+    let mut fields = BTreeMap::new();
+    fields.insert("a".to_string(), ints(&[1]));
+    let record = Value::record(Some("R".to_string()), fields);
+    assert_eq!(render(&record, 2), "R { a: [1] }");
+    assert_eq!(render(&record, 1), "R { a: […] }");
+    assert_eq!(render(&record, 0), "R { … }");
+
+    let mut fields = BTreeMap::new();
+    fields.insert("x".to_string(), Value::Integer(1));
+    assert_eq!(render(&Value::record(None, fields), 1), "{ x = 1 }");
+}
+
+// An event as a line of an indented call tree
+fn line(event: &Event) -> String {
+    match event {
+        Event::Enter {
+            depth,
+            function,
+            args,
+            ..
+        } => format!(
+            "{}enter {} {}",
+            "  ".repeat(*depth),
+            function,
+            args.join(" ")
+        ),
+        Event::Exit {
+            depth,
+            function,
+            result,
+            ..
+        } => format!(
+            "{}exit {} = {}",
+            "  ".repeat(*depth),
+            function,
+            result.as_deref().unwrap_or("error")
+        ),
+    }
+}
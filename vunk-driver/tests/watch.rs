@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_driver::watch::Watcher;
+
+const MANIFEST: &str = "[package]\nname = \"watched\"\nversion = \"0.1.0\"\n";
+
+#[test]
+fn changes_of_modules_are_reported() {
+    let root = std::env::temp_dir().join(format!("vunk-watch-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("target")).unwrap();
+    std::fs::write(root.join("vunk.toml"), MANIFEST).unwrap();
+    std::fs::write(root.join("src/main.vunk"), "pub main = 1\n").unwrap();
+
+    let watcher = Watcher::new(&root).unwrap();
+    // Neither build output nor other files are sources
+    std::fs::write(root.join("target/main"), "").unwrap();
+    std::fs::write(root.join("notes.txt"), "").unwrap();
+    std::fs::write(root.join("src/main.vunk"), "pub main = 2\n").unwrap();
+    let changed = watcher.wait().unwrap();
+
+    let root = root.canonicalize().unwrap();
+    assert_eq!(changed, vec![root.join("src/main.vunk")]);
+    let _ = std::fs::remove_dir_all(root);
+}
@@ -11,11 +11,16 @@ use chumsky::recovery::skip_then_retry_until;
 use chumsky::text;
 use chumsky::text::TextParser;
 use chumsky::Parser;
+use vunk_diagnostics::codes::Code;
+use vunk_diagnostics::Diagnostic;
+use vunk_diagnostics::File;
+use vunk_diagnostics::Label;
 
 pub type Span = std::ops::Range<usize>;
 pub type Spanned<T> = (T, Span);
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Token {
     Ident(String),
 
@@ -29,11 +34,18 @@ pub enum Token {
     Num(String),
     Str(String),
 
+    /// `b"text"`, the bytes of the UTF-8 encoding of the text, or `x"00ff"`, bytes in hex
+    Bytes(Vec<u8>),
+
     If,
     Else,
 
     Let,
     In,
+    Lazy,
+
+    Do,
+    Bind,
 
     ParOpen,
     ParClose,
@@ -55,7 +67,9 @@ pub enum Token {
     Mod,
 
     Separator,
+    Spread,
     Comma,
+    Try,
 
     Comment(String),
 }
@@ -77,13 +91,25 @@ impl std::fmt::Display for Token {
             If => write!(f, "if"),
             In => write!(f, "in"),
             Let => write!(f, "let"),
+            Lazy => write!(f, "lazy"),
+            Do => write!(f, "do"),
+            Bind => write!(f, "<-"),
             Num(n) => write!(f, "{}", n),
             Str(s) => write!(f, "{}", s),
+            Bytes(bytes) => {
+                write!(f, "x\"")?;
+                bytes
+                    .iter()
+                    .try_for_each(|byte| write!(f, "{:02x}", byte))?;
+                write!(f, "\"")
+            }
             Op(s) => write!(f, "{}", s),
             Use => write!(f, "use"),
             Pub => write!(f, "pub"),
             Comma => write!(f, ","),
+            Try => write!(f, "?"),
             Separator => write!(f, "."),
+            Spread => write!(f, ".."),
             ParOpen => write!(f, "("),
             ParClose => write!(f, ")"),
             BlockOpen => write!(f, "{{"),
@@ -114,10 +140,40 @@ pub fn lexer() -> impl Parser<char, Vec<Spanned<Token>>, Error = Simple<char>> {
         .collect::<String>()
         .map(Token::Str);
 
+    // A parser for bytes, as text or in hex, whose digits can be grouped with spaces
+    let text_bytes = just("b\"")
+        .ignore_then(filter(|c| *c != '"').repeated())
+        .then_ignore(just('"'))
+        .collect::<String>()
+        .map(|text| Token::Bytes(text.into_bytes()));
+    let hex_bytes = just("x\"")
+        .ignore_then(filter(|c: &char| c.is_ascii_hexdigit() || *c == ' ').repeated())
+        .then_ignore(just('"'))
+        .collect::<String>()
+        .try_map(|hex, span| {
+            let digits = hex
+                .chars()
+                .filter_map(|c| c.to_digit(16))
+                .collect::<Vec<_>>();
+            if digits.len() % 2 != 0 {
+                return Err(Simple::custom(
+                    span,
+                    "Bytes in hex need two digits per byte",
+                ));
+            }
+            Ok(Token::Bytes(
+                digits
+                    .chunks(2)
+                    .map(|pair| (pair[0] * 16 + pair[1]) as u8)
+                    .collect(),
+            ))
+        });
+    let bytes = text_bytes.or(hex_bytes);
+
     // A parser for control characters (delimiters, semicolons, etc.)
-    let ctrl = one_of("(),").map(|c| Token::Ctrl(c));
+    let ctrl = one_of("(),@").map(Token::Ctrl);
 
-    let operator = {
+    let (long_operator, operator) = {
         let op_add = just('+').map(|c| Token::Op(c.to_string()));
         let op_sub = just('-').map(|c| Token::Op(c.to_string()));
         let op_mul = just('*').map(|c| Token::Op(c.to_string()));
@@ -125,6 +181,7 @@ pub fn lexer() -> impl Parser<char, Vec<Spanned<Token>>, Error = Simple<char>> {
         let op_rem = just('%').map(|c| Token::Op(c.to_string()));
         let op_eq = just("==").map(|c| Token::Op(c.to_string()));
         let op_neq = just("!=").map(|c| Token::Op(c.to_string()));
+        let op_not = just('!').map(|c| Token::Op(c.to_string()));
         let op_less = just('<').map(|c| Token::Op(c.to_string()));
         let op_less_eq = just("<=").map(|c| Token::Op(c.to_string()));
         let op_more = just('>').map(|c| Token::Op(c.to_string()));
@@ -139,45 +196,57 @@ pub fn lexer() -> impl Parser<char, Vec<Spanned<Token>>, Error = Simple<char>> {
 
         let op_join = just("++").map(|c| Token::Op(c.to_string()));
 
-        op_add
+        // Longest first, and ahead of `=`, `+` and `|`, so that `==` is not lexed as two `=`
+        let long_operator = op_join
+            .or(op_eq)
+            .or(op_neq)
+            .or(op_less_eq)
+            .or(op_more_eq)
+            .or(op_logical_and)
+            .or(op_logical_or);
+
+        let operator = op_add
             .or(op_sub)
             .or(op_mul)
             .or(op_div)
             .or(op_rem)
-            .or(op_eq)
-            .or(op_neq)
+            .or(op_not)
             .or(op_less)
-            .or(op_less_eq)
             .or(op_more)
-            .or(op_more_eq)
             .or(op_bit_and)
-            .or(op_logical_and)
             .or(op_bit_or)
-            .or(op_logical_or)
-            .or(op_bit_xor)
-            .or(op_join)
+            .or(op_bit_xor);
+
+        (long_operator, operator)
     };
 
     let assign = just("=").map(|_| Token::Assign);
     let declare = just(":").map(|_| Token::Declare);
     let plus = just("+").map(|_| Token::Plus);
+    let spread = just("..").map(|_| Token::Spread);
     let separator = just(".").map(|_| Token::Separator);
     let comma = just(",").map(|_| Token::Comma);
-    let kw_use = just("use").map(|_| Token::Use);
-    let kw_pub = just("pub").map(|_| Token::Pub);
+    let try_ = just("?").map(|_| Token::Try);
+    // Keywords only match whole words, so that identifiers like "letter", "index" or "types" are
+    // not split into a keyword and the rest
+    let kw_use = text::keyword("use").map(|_| Token::Use);
+    let kw_pub = text::keyword("pub").map(|_| Token::Pub);
     let kw_arrow = just("->").map(|_| Token::Arrow);
-    let kw_let = just("let").map(|_| Token::Let);
-    let kw_in = just("in").map(|_| Token::In);
-    let kw_if = just("if").map(|_| Token::If);
-    let kw_else = just("else").map(|_| Token::Else);
-    let kw_true = just("true").map(|_| Token::Bool(true));
-    let kw_false = just("false").map(|_| Token::Bool(false));
-    let kw_where = just("where").map(|_| Token::Where);
-    let kw_match = just("match").map(|_| Token::Match);
-    let kw_when = just("when").map(|_| Token::When);
-    let kw_type = just("type").map(|_| Token::Type);
-    let kw_enum = just("enum").map(|_| Token::Enum);
-    let kw_mod = just("mod").map(|_| Token::Mod);
+    let kw_let = text::keyword("let").map(|_| Token::Let);
+    let kw_in = text::keyword("in").map(|_| Token::In);
+    let kw_lazy = text::keyword("lazy").map(|_| Token::Lazy);
+    let kw_do = text::keyword("do").map(|_| Token::Do);
+    let bind = just("<-").map(|_| Token::Bind);
+    let kw_if = text::keyword("if").map(|_| Token::If);
+    let kw_else = text::keyword("else").map(|_| Token::Else);
+    let kw_true = text::keyword("true").map(|_| Token::Bool(true));
+    let kw_false = text::keyword("false").map(|_| Token::Bool(false));
+    let kw_where = text::keyword("where").map(|_| Token::Where);
+    let kw_match = text::keyword("match").map(|_| Token::Match);
+    let kw_when = text::keyword("when").map(|_| Token::When);
+    let kw_type = text::keyword("type").map(|_| Token::Type);
+    let kw_enum = text::keyword("enum").map(|_| Token::Enum);
+    let kw_mod = text::keyword("mod").map(|_| Token::Mod);
     let paropen = just("(").map(|_| Token::ParOpen);
     let parclose = just(")").map(|_| Token::ParClose);
     let blockopen = just("{").map(|_| Token::BlockOpen);
@@ -189,17 +258,24 @@ pub fn lexer() -> impl Parser<char, Vec<Spanned<Token>>, Error = Simple<char>> {
 
     // A single token can be one of the above
     let token = num
+        .or(bytes)
         .or(str_)
+        .or(long_operator)
         .or(assign)
         .or(declare)
         .or(plus)
+        .or(spread)
         .or(separator)
         .or(comma)
+        .or(try_)
         .or(kw_use)
         .or(kw_pub)
         .or(kw_arrow)
         .or(kw_let)
         .or(kw_in)
+        .or(kw_lazy)
+        .or(kw_do)
+        .or(bind)
         .or(kw_if)
         .or(kw_else)
         .or(kw_true)
@@ -232,7 +308,7 @@ pub fn lexer() -> impl Parser<char, Vec<Spanned<Token>>, Error = Simple<char>> {
 }
 
 fn ident<C: text::Character, E: chumsky::Error<C>>(
-) -> impl Parser<C, C::Collection, Error = E> + Copy + Clone {
+) -> impl Parser<C, C::Collection, Error = E> + Copy {
     filter(|c: &C| {
         let chr = c.to_char();
         chr.is_ascii_alphabetic() || chr == '_' || chr == '$'
@@ -247,3 +323,28 @@ fn ident<C: text::Character, E: chumsky::Error<C>>(
     )
     .collect()
 }
+
+/// The errors of the lexer as diagnostics pointing into `file`
+pub fn diagnostics(file: &File, errors: &[Simple<char>]) -> Vec<Diagnostic> {
+    // The lexer counts chars, diagnostics count bytes. Garbage gives an error every few chars, so
+    // the offsets are looked up instead of counted for every error.
+    let offsets = file
+        .code
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .collect::<Vec<_>>();
+    let byte_offset = |chars: usize| offsets.get(chars).copied().unwrap_or(file.code.len());
+
+    errors
+        .iter()
+        .map(|error| {
+            let start = byte_offset(error.span().start);
+            let end = byte_offset(error.span().end).max(start);
+
+            Diagnostic::error(error.to_string())
+                .with_code(Code::E0001)
+                .with_file(file.clone())
+                .with_label(Label::primary(start..end, "here"))
+        })
+        .collect()
+}
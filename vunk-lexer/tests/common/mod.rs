@@ -0,0 +1,12 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chumsky::Parser;
+use vunk_lexer::Token;
+
+/// The tokens of `code`, without their spans
+pub fn tokens(code: &str) -> Vec<Token> {
+    let tokens = vunk_lexer::lexer().parse(code).unwrap();
+    tokens.into_iter().map(|(token, _)| token).collect()
+}
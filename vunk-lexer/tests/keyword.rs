@@ -0,0 +1,59 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::tokens;
+use vunk_lexer::Token;
+
+#[test]
+fn lazy_is_only_a_keyword_as_a_whole_word() {
+    assert_eq!(
+        tokens("lazy lazyList"),
+        vec![Token::Lazy, Token::Ident("lazyList".to_string())]
+    );
+}
+
+#[test]
+fn identifiers_starting_with_a_keyword_are_identifiers() {
+    let names = [
+        "letter",
+        "index",
+        "lazyList",
+        "done",
+        "iffy",
+        "elsewhere",
+        "trueish",
+        "falsehood",
+        "useful",
+        "public",
+        "wherever",
+        "matches",
+        "whence",
+        "types",
+        "enumerate",
+        "module",
+    ];
+    for name in names {
+        assert_eq!(
+            tokens(name),
+            vec![Token::Ident(name.to_string())],
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn keywords_are_followed_by_punctuation() {
+    assert_eq!(
+        tokens("if(true)"),
+        vec![
+            Token::If,
+            Token::ParOpen,
+            Token::Bool(true),
+            Token::ParClose
+        ]
+    );
+}
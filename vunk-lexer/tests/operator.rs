@@ -0,0 +1,67 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::tokens;
+use vunk_lexer::Token;
+
+#[test]
+fn logical_not_is_an_operator() {
+    assert_eq!(
+        tokens("!(same a b)"),
+        vec![
+            Token::Op("!".to_string()),
+            Token::ParOpen,
+            Token::Ident("same".to_string()),
+            Token::Ident("a".to_string()),
+            Token::Ident("b".to_string()),
+            Token::ParClose,
+        ]
+    );
+}
+
+#[test]
+fn not_equal_is_one_operator() {
+    assert_eq!(
+        tokens("a != b"),
+        vec![
+            Token::Ident("a".to_string()),
+            Token::Op("!=".to_string()),
+            Token::Ident("b".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn operators_of_two_chars_are_one_token() {
+    for op in ["==", "!=", "<=", ">=", "&&", "||", "++"] {
+        assert_eq!(
+            tokens(&format!("a {} b", op)),
+            vec![
+                Token::Ident("a".to_string()),
+                Token::Op(op.to_string()),
+                Token::Ident("b".to_string()),
+            ],
+            "{}",
+            op
+        );
+    }
+}
+
+#[test]
+fn separated_chars_are_separate_operators() {
+    assert_eq!(
+        tokens("a = = b + + c"),
+        vec![
+            Token::Ident("a".to_string()),
+            Token::Assign,
+            Token::Assign,
+            Token::Ident("b".to_string()),
+            Token::Plus,
+            Token::Plus,
+            Token::Ident("c".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,341 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Answers to LSP requests
+
+use std::collections::HashMap;
+
+use chumsky::Parser;
+use lsp_types::CompletionItem;
+use lsp_types::CompletionItemKind;
+use lsp_types::Diagnostic;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::DocumentSymbol;
+use lsp_types::Hover;
+use lsp_types::HoverContents;
+use lsp_types::InlayHint;
+use lsp_types::InlayHintKind;
+use lsp_types::InlayHintLabel;
+use lsp_types::MarkupContent;
+use lsp_types::MarkupKind;
+use lsp_types::NumberOrString;
+use lsp_types::Position;
+use lsp_types::SemanticToken;
+use lsp_types::SemanticTokenType;
+use lsp_types::SemanticTokens;
+use lsp_types::SemanticTokensLegend;
+use lsp_types::SymbolKind;
+use lsp_types::TextEdit;
+use lsp_types::Url;
+use lsp_types::WorkspaceEdit;
+use vunk_diagnostics::Severity;
+use vunk_driver::complete::complete;
+use vunk_driver::complete::CompletionKind;
+use vunk_driver::database::Database;
+use vunk_driver::error::DriverError;
+use vunk_driver::highlight::semantic_tokens;
+use vunk_driver::highlight::Role;
+use vunk_driver::hints::inlay_hints;
+use vunk_driver::index::Index;
+use vunk_driver::outline::outline;
+use vunk_driver::outline::Item;
+use vunk_driver::outline::ItemKind;
+use vunk_driver::rename;
+use vunk_driver::source::byte_offset;
+use vunk_lexer::Token;
+use vunk_runtime::builtin::Builtins;
+
+use crate::position::offset;
+use crate::position::position;
+use crate::position::range;
+
+/// The errors of a document of the database
+pub fn diagnostics(database: &mut Database, name: &str) -> Vec<Diagnostic> {
+    let (source, error) = match (database.source(name), database.lex(name)) {
+        (Some(source), Err(error)) => (source, error),
+        _ => return Vec::new(),
+    };
+
+    // Diagnostics without a primary label cannot be shown in the document
+    let diagnostics = error.error().diagnostics();
+    diagnostics
+        .into_iter()
+        .filter_map(|diagnostic| {
+            let label = diagnostic.labels.iter().find(|label| label.primary)?;
+            Some(Diagnostic {
+                range: range(&source.code, label.span.clone()),
+                severity: Some(match diagnostic.severity {
+                    Severity::Error => DiagnosticSeverity::ERROR,
+                    Severity::Warning => DiagnosticSeverity::WARNING,
+                    Severity::Note => DiagnosticSeverity::INFORMATION,
+                }),
+                code: diagnostic.code.map(NumberOrString::String),
+                source: Some("vunk".to_string()),
+                message: diagnostic.message,
+                ..Diagnostic::default()
+            })
+        })
+        .collect()
+}
+
+/// The declaration of the name at the position
+pub fn hover(code: &str, position: Position) -> Option<Hover> {
+    let name = ident_at(code, offset(code, position))?;
+    let items = outline(code);
+    let item = find(
+        &items,
+        &name,
+        &[ItemKind::Declaration, ItemKind::Type, ItemKind::Definition],
+    )?;
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("```vunk\n{}\n```", &code[item.span.clone()]),
+        }),
+        range: None,
+    })
+}
+
+/// The range of the name of the definition of the name at the position
+pub fn definition(code: &str, position: Position) -> Option<lsp_types::Range> {
+    let name = ident_at(code, offset(code, position))?;
+    let items = outline(code);
+    let item = find(
+        &items,
+        &name,
+        &[ItemKind::Definition, ItemKind::Type, ItemKind::Declaration],
+    )?;
+    Some(range(code, item.name_span.clone()))
+}
+
+/// One symbol per defined name, declarations only count if there is no definition
+#[allow(deprecated)] // DocumentSymbol::deprecated has to be set, even though it is deprecated
+pub fn symbols(code: &str) -> Vec<DocumentSymbol> {
+    let items = outline(code);
+    items
+        .iter()
+        .filter(|item| match item.kind {
+            ItemKind::Definition | ItemKind::Type => true,
+            ItemKind::Declaration => find(&items, &item.name, &[ItemKind::Definition]).is_none(),
+            ItemKind::Other => false,
+        })
+        .map(|item| DocumentSymbol {
+            name: item.name.clone(),
+            detail: find(&items, &item.name, &[ItemKind::Declaration])
+                .and_then(|declaration| declaration.declared_type(code))
+                .map(str::to_string),
+            kind: match item.kind {
+                ItemKind::Type => SymbolKind::STRUCT,
+                _ => SymbolKind::FUNCTION,
+            },
+            tags: None,
+            deprecated: None,
+            range: range(code, item.span.clone()),
+            selection_range: range(code, item.name_span.clone()),
+            children: None,
+        })
+        .collect()
+}
+
+// The first item with the name, trying the kinds in order
+fn find<'a>(items: &'a [Item], name: &str, kinds: &[ItemKind]) -> Option<&'a Item> {
+    kinds.iter().find_map(|kind| {
+        items
+            .iter()
+            .find(|item| item.name == name && item.kind == *kind)
+    })
+}
+
+fn ident_at(code: &str, offset: usize) -> Option<String> {
+    let (tokens, _) = vunk_lexer::lexer().parse_recovery(code);
+    tokens?.into_iter().find_map(|(token, span)| {
+        let span = byte_offset(code, span.start)..byte_offset(code, span.end);
+        match token {
+            Token::Ident(name) if span.start <= offset && offset <= span.end => Some(name),
+            _ => None,
+        }
+    })
+}
+
+pub fn completions(code: &str, position: Position, builtins: &Builtins) -> Vec<CompletionItem> {
+    let (_, completions) = complete(code, offset(code, position), builtins);
+    completions
+        .into_iter()
+        .map(|completion| CompletionItem {
+            label: completion.label,
+            kind: Some(match completion.kind {
+                CompletionKind::Local => CompletionItemKind::VARIABLE,
+                CompletionKind::Definition => CompletionItemKind::FUNCTION,
+                CompletionKind::Type => CompletionItemKind::STRUCT,
+                CompletionKind::Builtin => CompletionItemKind::FUNCTION,
+                CompletionKind::Module => CompletionItemKind::MODULE,
+                CompletionKind::Keyword => CompletionItemKind::KEYWORD,
+            }),
+            detail: completion.detail,
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+/// The token types of [`semantic_tokens`], the index of a type is its number in the encoding
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: Role::ALL.iter().copied().map(token_type).collect(),
+        token_modifiers: Vec::new(),
+    }
+}
+
+fn token_type(role: Role) -> SemanticTokenType {
+    match role {
+        Role::Function => SemanticTokenType::FUNCTION,
+        Role::Type => SemanticTokenType::TYPE,
+        Role::Constructor => SemanticTokenType::ENUM_MEMBER,
+        Role::Parameter => SemanticTokenType::PARAMETER,
+        Role::Variable => SemanticTokenType::VARIABLE,
+        Role::Namespace => SemanticTokenType::NAMESPACE,
+        Role::Keyword => SemanticTokenType::KEYWORD,
+        Role::Operator => SemanticTokenType::OPERATOR,
+        Role::Comment => SemanticTokenType::COMMENT,
+        Role::Number => SemanticTokenType::NUMBER,
+        Role::String => SemanticTokenType::STRING,
+        Role::Attribute => SemanticTokenType::DECORATOR,
+    }
+}
+
+/// All semantic tokens of the document, relative to each other as the protocol requires
+///
+/// Tokens spanning multiple lines, like multiline strings, are split into one token per line.
+pub fn semantic_tokens_full(code: &str) -> SemanticTokens {
+    let mut data = Vec::new();
+    let mut previous = Position::new(0, 0);
+
+    for token in semantic_tokens(code) {
+        let token_type = Role::ALL
+            .iter()
+            .position(|role| *role == token.role)
+            .unwrap_or_default() as u32;
+
+        let mut start = token.span.start;
+        for line in code[token.span.clone()].split_inclusive('\n') {
+            let end = start + line.trim_end_matches('\n').len();
+            let (from, to) = (position(code, start), position(code, end));
+            start += line.len();
+            if from == to {
+                continue;
+            }
+
+            data.push(SemanticToken {
+                delta_line: from.line - previous.line,
+                delta_start: if from.line == previous.line {
+                    from.character - previous.character
+                } else {
+                    from.character
+                },
+                length: to.character - from.character,
+                token_type,
+                token_modifiers_bitset: 0,
+            });
+            previous = from;
+        }
+    }
+
+    SemanticTokens {
+        result_id: None,
+        data,
+    }
+}
+
+/// Inlay hints for the types of names in the range
+pub fn hints(code: &str, range: lsp_types::Range) -> Vec<InlayHint> {
+    let (start, end) = (offset(code, range.start), offset(code, range.end));
+    inlay_hints(code)
+        .into_iter()
+        .filter(|hint| start <= hint.offset && hint.offset <= end)
+        .map(|hint| InlayHint {
+            position: position(code, hint.offset),
+            label: InlayHintLabel::String(hint.label),
+            kind: Some(InlayHintKind::TYPE),
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        })
+        .collect()
+}
+
+/// Rename the name at the position in all open documents
+pub fn rename(
+    documents: &HashMap<Url, String>,
+    uri: &Url,
+    position: Position,
+    new_name: &str,
+) -> Result<WorkspaceEdit, DriverError> {
+    let (uris, codes): (Vec<&Url>, Vec<&str>) = documents
+        .iter()
+        .map(|(uri, code)| (uri, code.as_str()))
+        .unzip();
+    let document = uris
+        .iter()
+        .position(|other| *other == uri)
+        .ok_or(DriverError::NoName)?;
+
+    let mut changes = HashMap::<Url, Vec<TextEdit>>::new();
+    for edit in rename::rename(
+        &codes,
+        document,
+        offset(codes[document], position),
+        new_name,
+    )? {
+        let code = codes[edit.document];
+        changes
+            .entry(uris[edit.document].clone())
+            .or_default()
+            .push(TextEdit {
+                range: range(code, edit.span),
+                new_text: edit.new_text,
+            });
+    }
+
+    Ok(WorkspaceEdit {
+        changes: Some(changes),
+        ..WorkspaceEdit::default()
+    })
+}
+
+/// All uses of the top level name at the position in the open documents
+pub fn references(
+    documents: &HashMap<Url, String>,
+    uri: &Url,
+    position: Position,
+    include_declaration: bool,
+) -> Vec<lsp_types::Location> {
+    let name = match documents
+        .get(uri)
+        .and_then(|code| ident_at(code, offset(code, position)))
+    {
+        Some(name) => name,
+        None => return Vec::new(),
+    };
+
+    let (uris, codes): (Vec<&Url>, Vec<&str>) = documents
+        .iter()
+        .map(|(uri, code)| (uri, code.as_str()))
+        .unzip();
+    let index = Index::new(&codes);
+
+    let definitions = index
+        .definitions(&name)
+        .iter()
+        .filter(|_| include_declaration);
+    let uses = index.uses(&name).iter().map(|use_| &use_.location);
+    definitions
+        .chain(uses)
+        .map(|location| lsp_types::Location {
+            uri: uris[location.document].clone(),
+            range: range(codes[location.document], location.span.clone()),
+        })
+        .collect()
+}
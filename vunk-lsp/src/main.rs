@@ -0,0 +1,13 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use lsp_server::Connection;
+use vunk_lsp::server::Server;
+
+fn main() -> vunk_lsp::server::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    Server::new(connection).run()?;
+    io_threads.join()?;
+    Ok(())
+}
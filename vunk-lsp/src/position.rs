@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Conversion between byte offsets and LSP positions, which count UTF-16 code units
+
+use std::ops::Range;
+
+use lsp_types::Position;
+
+pub fn position(code: &str, offset: usize) -> Position {
+    let before = &code[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+pub fn range(code: &str, span: Range<usize>) -> lsp_types::Range {
+    lsp_types::Range {
+        start: position(code, span.start),
+        end: position(code, span.end),
+    }
+}
+
+/// The byte offset of a position, clamped to the end of its line
+pub fn offset(code: &str, position: Position) -> usize {
+    let line_start = code
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(str::len)
+        .sum::<usize>();
+
+    let mut units = 0;
+    for (i, c) in code[line_start..].char_indices() {
+        if c == '\n' || units >= position.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    code.len()
+}
@@ -0,0 +1,288 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use lsp_server::Connection;
+use lsp_server::ErrorCode;
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
+use lsp_server::Response;
+use lsp_types::notification::DidChangeTextDocument;
+use lsp_types::notification::DidCloseTextDocument;
+use lsp_types::notification::DidOpenTextDocument;
+use lsp_types::notification::Notification as _;
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::request::Completion;
+use lsp_types::request::DocumentSymbolRequest;
+use lsp_types::request::GotoDefinition;
+use lsp_types::request::HoverRequest;
+use lsp_types::request::InlayHintRequest;
+use lsp_types::request::References;
+use lsp_types::request::Rename;
+use lsp_types::request::Request as _;
+use lsp_types::request::SemanticTokensFullRequest;
+use lsp_types::CompletionOptions;
+use lsp_types::CompletionParams;
+use lsp_types::DidChangeTextDocumentParams;
+use lsp_types::DidCloseTextDocumentParams;
+use lsp_types::DidOpenTextDocumentParams;
+use lsp_types::DocumentSymbolParams;
+use lsp_types::DocumentSymbolResponse;
+use lsp_types::GotoDefinitionParams;
+use lsp_types::GotoDefinitionResponse;
+use lsp_types::HoverParams;
+use lsp_types::HoverProviderCapability;
+use lsp_types::InlayHintParams;
+use lsp_types::Location;
+use lsp_types::OneOf;
+use lsp_types::PublishDiagnosticsParams;
+use lsp_types::ReferenceParams;
+use lsp_types::RenameParams;
+use lsp_types::SemanticTokensFullOptions;
+use lsp_types::SemanticTokensOptions;
+use lsp_types::SemanticTokensParams;
+use lsp_types::SemanticTokensResult;
+use lsp_types::SemanticTokensServerCapabilities;
+use lsp_types::ServerCapabilities;
+use lsp_types::TextDocumentSyncCapability;
+use lsp_types::TextDocumentSyncKind;
+use lsp_types::Url;
+use vunk_driver::database::Database;
+use vunk_driver::source::Source;
+use vunk_runtime::builtin::Builtins;
+
+use crate::analysis;
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        inlay_hint_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: analysis::legend(),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..SemanticTokensOptions::default()
+            },
+        )),
+        ..ServerCapabilities::default()
+    }
+}
+
+pub struct Server {
+    connection: Connection,
+
+    /// Contents of the open documents
+    documents: HashMap<Url, String>,
+
+    /// Results of analyzing the open documents, only updated for the parts that changed
+    database: Database,
+
+    builtins: Builtins,
+}
+
+impl Server {
+    pub fn new(connection: Connection) -> Self {
+        Server {
+            connection,
+            documents: HashMap::new(),
+            database: Database::default(),
+            builtins: Builtins::std(),
+        }
+    }
+
+    pub fn run(mut self) -> Result<()> {
+        self.connection
+            .initialize(serde_json::to_value(capabilities())?)?;
+
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        break;
+                    }
+
+                    let response = self.request(request);
+                    self.connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => self.notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            HoverRequest::METHOD => self.params(request, |this, params: HoverParams| {
+                let position = params.text_document_position_params;
+                this.document(&position.text_document.uri)
+                    .and_then(|code| analysis::hover(code, position.position))
+            }),
+            GotoDefinition::METHOD => self.params(request, |this, params: GotoDefinitionParams| {
+                let position = params.text_document_position_params;
+                let uri = position.text_document.uri;
+                this.document(&uri)
+                    .and_then(|code| analysis::definition(code, position.position))
+                    .map(|range| GotoDefinitionResponse::Scalar(Location { uri, range }))
+            }),
+            DocumentSymbolRequest::METHOD => {
+                self.params(request, |this, params: DocumentSymbolParams| {
+                    this.document(&params.text_document.uri)
+                        .map(|code| DocumentSymbolResponse::Nested(analysis::symbols(code)))
+                })
+            }
+            Completion::METHOD => self.params(request, |this, params: CompletionParams| {
+                let position = params.text_document_position;
+                this.document(&position.text_document.uri)
+                    .map(|code| analysis::completions(code, position.position, &this.builtins))
+            }),
+            SemanticTokensFullRequest::METHOD => {
+                self.params(request, |this, params: SemanticTokensParams| {
+                    this.document(&params.text_document.uri).map(|code| {
+                        SemanticTokensResult::Tokens(analysis::semantic_tokens_full(code))
+                    })
+                })
+            }
+            InlayHintRequest::METHOD => self.params(request, |this, params: InlayHintParams| {
+                this.document(&params.text_document.uri)
+                    .map(|code| analysis::hints(code, params.range))
+            }),
+            References::METHOD => self.params(request, |this, params: ReferenceParams| {
+                let position = params.text_document_position;
+                analysis::references(
+                    &this.documents,
+                    &position.text_document.uri,
+                    position.position,
+                    params.context.include_declaration,
+                )
+            }),
+            Rename::METHOD => {
+                let params: RenameParams = match serde_json::from_value(request.params) {
+                    Ok(params) => params,
+                    Err(error) => {
+                        return Response::new_err(
+                            id,
+                            ErrorCode::InvalidParams as i32,
+                            error.to_string(),
+                        )
+                    }
+                };
+                let position = params.text_document_position;
+
+                // Conflicts are reported to the user instead of renaming
+                return match analysis::rename(
+                    &self.documents,
+                    &position.text_document.uri,
+                    position.position,
+                    &params.new_name,
+                ) {
+                    Ok(edit) => Response::new_ok(id, edit),
+                    Err(error) => {
+                        Response::new_err(id, ErrorCode::RequestFailed as i32, error.to_string())
+                    }
+                };
+            }
+            method => {
+                return Response::new_err(
+                    id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request {}", method),
+                )
+            }
+        };
+
+        match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err(error) => Response::new_err(id, ErrorCode::InvalidParams as i32, error.to_string()),
+        }
+    }
+
+    fn params<P, R>(
+        &self,
+        request: Request,
+        handler: impl FnOnce(&Self, P) -> R,
+    ) -> std::result::Result<serde_json::Value, serde_json::Error>
+    where
+        P: serde::de::DeserializeOwned,
+        R: serde::Serialize,
+    {
+        let params = serde_json::from_value(request.params)?;
+        serde_json::to_value(handler(self, params))
+    }
+
+    fn notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.update(params.text_document.uri, params.text_document.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                // With full synchronization, the last change contains the whole document
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update(params.text_document.uri, change.text),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                self.database.remove_source(params.text_document.uri.path());
+                self.publish(params.text_document.uri, Vec::new())
+            }
+            method => {
+                tracing::debug!("Ignoring notification {}", method);
+                Ok(())
+            }
+        }
+    }
+
+    fn update(&mut self, uri: Url, code: String) -> Result<()> {
+        self.database.set_source(Source {
+            name: uri.path().to_string(),
+            code: code.clone(),
+        });
+        let diagnostics = analysis::diagnostics(&mut self.database, uri.path());
+        self.documents.insert(uri.clone(), code);
+        self.publish(uri, diagnostics)
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        };
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                params,
+            )))?;
+        Ok(())
+    }
+
+    fn document(&self, uri: &Url) -> Option<&str> {
+        self.documents.get(uri).map(String::as_str)
+    }
+}
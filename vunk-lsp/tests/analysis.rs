@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use lsp_types::HoverContents;
+use lsp_types::Position;
+use vunk_driver::database::Database;
+use vunk_driver::source::Source;
+use vunk_lsp::analysis::definition;
+use vunk_lsp::analysis::diagnostics;
+use vunk_lsp::analysis::hover;
+use vunk_lsp::analysis::semantic_tokens_full;
+use vunk_lsp::analysis::symbols;
+
+const CODE: &str = "\
+# Adds one
+succ: (i64) -> i64
+succ = (a: i64) -> a + 1
+
+two = succ 1
+";
+
+fn database(code: &str) -> Database {
+    let mut database = Database::default();
+    database.set_source(Source {
+        name: "test.vunk".to_string(),
+        code: code.to_string(),
+    });
+    database
+}
+
+#[test]
+fn no_diagnostics_for_valid_code() {
+    assert!(diagnostics(&mut database(CODE), "test.vunk").is_empty());
+}
+
+#[test]
+fn lexer_errors_are_diagnostics() {
+    let diagnostics = diagnostics(&mut database("a = 1;\n"), "test.vunk");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start, Position::new(0, 5));
+}
+
+#[test]
+fn hover_shows_declaration() {
+    let hover = hover(CODE, Position::new(4, 8)).unwrap();
+    match hover.contents {
+        HoverContents::Markup(markup) => {
+            assert_eq!(markup.value, "```vunk\n# Adds one\nsucc: (i64) -> i64\n```")
+        }
+        other => panic!("Unexpected hover contents: {:?}", other),
+    }
+}
+
+#[test]
+fn definition_points_to_name() {
+    let range = definition(CODE, Position::new(4, 8)).unwrap();
+    assert_eq!(range.start, Position::new(2, 0));
+    assert_eq!(range.end, Position::new(2, 4));
+}
+
+#[test]
+fn one_symbol_per_definition() {
+    let symbols = symbols(CODE);
+    let names = symbols.iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["succ", "two"]);
+    assert_eq!(symbols[0].detail.as_deref(), Some("(i64) -> i64"));
+}
+
+#[test]
+fn semantic_tokens_are_relative() {
+    let tokens = semantic_tokens_full("a = 1\n  b = \"x\"\n").data;
+    let encoded = tokens
+        .iter()
+        .map(|t| (t.delta_line, t.delta_start, t.length))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        encoded,
+        vec![
+            (0, 0, 1),
+            (0, 2, 1),
+            (0, 2, 1),
+            (1, 2, 1),
+            (0, 2, 1),
+            (0, 2, 3)
+        ]
+    );
+}
@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// An attribute in front of a declaration, like `@test` or `@cfg(not(debug))`
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Attribute {
+    pub name: String,
+    pub args: Vec<Meta>,
+}
+
+/// An argument of an attribute, like `unused`, `"use area"`, `note = "..."` or `not(debug)`
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Meta {
+    Str(String),
+    Named {
+        name: String,
+        value: Option<MetaValue>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum MetaValue {
+    /// `name = "string"`
+    Str(String),
+
+    /// `name(meta, ...)`
+    List(Vec<Meta>),
+}
@@ -0,0 +1,24 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::ast::expr::Expr;
+use crate::ast::name::VariableName;
+
+/// `[expr | qualifier, ...]`, like `[x * 2 | x <- xs, x > 0]`
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Comprehension {
+    pub expr: Box<Expr>,
+    pub qualifiers: Vec<Qualifier>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Qualifier {
+    /// `name <- list`, for every element of the list
+    Generator(VariableName, Expr),
+
+    /// A condition, for the elements it holds for
+    Filter(Expr),
+}
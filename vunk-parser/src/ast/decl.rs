@@ -2,35 +2,45 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use crate::ast::attribute::Attribute;
 use crate::ast::def::Def;
 use crate::ast::generic::WhereClause;
 use crate::ast::name::TypeName;
 use crate::ast::name::VariableName;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Decl {
+    /// The attributes in front of the declaration, see [`crate::attribute`]
+    pub attributes: Vec<Attribute>,
     pub lhs: VariableName,
     pub rhs: DeclType,
     pub whereclause: Option<WhereClause>,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum DeclType {
     TypeName(TypeName),
-    Func { args: Vec<DeclArg>, retty: TypeName },
+    Func {
+        args: Vec<DeclArg>,
+        retty: TypeName,
+
+        /// The effects that calling the function may perform, like `Console` in
+        /// `(String) -> () with Console`
+        effects: Vec<TypeName>,
+    },
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct DeclArg {
     pub name: Option<VariableName>,
     pub ty: DeclType,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct TypeImpl {
     pub name: TypeName,
     pub generics: Option<WhereClause>,
@@ -6,54 +6,105 @@ use crate::ast::expr::Expr;
 use crate::ast::generic::WhereClause;
 use crate::ast::name::TypeName;
 use crate::ast::name::VariableName;
+use crate::ast::pattern::Pattern;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Def {
     pub lhs: VariableName,
     pub rhs: DefRhs,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct DefRhs {
     pub args: Vec<DefArg>,
     pub expr: Box<Expr>,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct DefArg {
     pub name: VariableName,
-    pub ty: DefArgType,
+
+    /// The type of the argument, if annotated
+    pub ty: Option<DefArgType>,
+
+    /// The pattern a parameter is matched against, like `Point { x: x }`, in place of `name`,
+    /// see [`crate::desugar::desugar_params`]
+    pub pattern: Option<Pattern>,
+}
+
+impl DefArg {
+    /// The parameter that is matched against `pattern`, whose name is `_` if it is destructured
+    pub fn from_pattern(pattern: Pattern, ty: Option<DefArgType>) -> DefArg {
+        match pattern {
+            Pattern::Variable(name) => DefArg {
+                name,
+                ty,
+                pattern: None,
+            },
+            Pattern::Wildcard => DefArg {
+                name: VariableName("_".to_string()),
+                ty,
+                pattern: None,
+            },
+            pattern => DefArg {
+                name: VariableName("_".to_string()),
+                ty,
+                pattern: Some(pattern),
+            },
+        }
+    }
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum DefArgType {
     TypeName(TypeName),
     Func { args: Vec<DefArg>, retty: TypeName },
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct TypeDef {
     pub name: TypeName,
+
+    /// The type variables of the type, like `T` in `type Bucket T = { element: T }`
+    pub params: Vec<TypeName>,
+
     pub members: Vec<DefArg>,
     pub generics: Option<WhereClause>,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct EnumDef {
     pub name: TypeName,
+
+    /// The type variables of the enum, like `O` and `E` in `enum Result O E = Ok O | Err E`
+    pub params: Vec<TypeName>,
+
     pub variants: Vec<EnumTypeDef>,
     pub whereclause: Option<WhereClause>,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct EnumTypeDef {
     pub name: TypeName,
     pub members: Vec<DefArg>,
+
+    /// The types of the members without a name, like `O` of `Ok O`
+    pub types: Vec<TypeName>,
+}
+
+/// `fact 0 = 1`, one case of a function defined by equations, see
+/// [`crate::desugar::desugar_equations`]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Equation {
+    pub lhs: VariableName,
+    pub params: Vec<Pattern>,
+    pub expr: Box<Expr>,
 }
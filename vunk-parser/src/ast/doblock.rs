@@ -0,0 +1,26 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::ast::expr::Expr;
+use crate::ast::name::VariableName;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DoBlock {
+    pub statements: Vec<DoStatement>,
+    pub result: Box<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DoStatement {
+    /// `name <- expr`
+    Bind(VariableName, Expr),
+
+    /// `let name = expr`, which binds a value instead of the result of an action
+    Let(VariableName, Expr),
+
+    /// `expr`, whose result is ignored
+    Run(Expr),
+}
@@ -0,0 +1,18 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::ast::decl::Decl;
+use crate::ast::name::TypeName;
+
+/// `effect Console = { print: (String) -> ()  readLine: () -> String }`
+///
+/// The operations of an effect are performed by the functions that have the effect in their
+/// type, like `greet: (String) -> () with Console`, and handled by handlers, see
+/// `vunk_runtime::effect`.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct EffectDef {
+    pub name: TypeName,
+    pub operations: Vec<Decl>,
+}
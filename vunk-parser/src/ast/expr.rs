@@ -2,24 +2,73 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use crate::ast::comprehension::Comprehension;
 use crate::ast::decl::Decl;
+use crate::ast::decl::DeclType;
 use crate::ast::def::Def;
+use crate::ast::def::DefRhs;
+use crate::ast::def::EnumDef;
+use crate::ast::def::Equation;
+use crate::ast::def::TypeDef;
+use crate::ast::doblock::DoBlock;
+use crate::ast::effect::EffectDef;
 use crate::ast::ifelse::IfElse;
+use crate::ast::import::Import;
 use crate::ast::letin::LetIns;
+use crate::ast::letin::WhereBindings;
 use crate::ast::literal::Literal;
+use crate::ast::matchwhen::MatchWhen;
 use crate::ast::name::VariableName;
 use crate::ast::op::BinaryOp;
 use crate::ast::op::UnaryOp;
+use crate::ast::pattern::PatternSynonym;
+use crate::ast::record::Construct;
+use crate::ast::record::Record;
+use crate::ast::traitdef::TraitDef;
+use crate::ast::traitdef::TraitImpl;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Expr {
     Variable(VariableName),
+
+    /// `.name`, the function that returns the field of a record, like in `map .name people`
+    FieldSection(VariableName),
+
     Unary(UnaryOp, Box<Expr>),
     Binary(BinaryOp, Box<Expr>, Box<Expr>),
     Literal(Literal),
+    Apply(Box<Expr>, Vec<Expr>),
+    Lambda(DefRhs),
     LetIn(LetIns),
+
+    /// The body of a definition with the bindings of its `where` block
+    Where(WhereBindings),
+
     IfElse(IfElse),
+    MatchWhen(MatchWhen),
+    Do(DoBlock),
+    Comprehension(Comprehension),
+    Record(Record),
+    Construct(Construct),
+
+    /// `..list`, the elements of a list, as an element of a list literal like `[x ..rest]`
+    Spread(Box<Expr>),
+
+    Lazy(Box<Expr>),
+    Try(Box<Expr>),
+
+    /// `(expr: Type)`, an expression that the typechecker checks to be of the type
+    Ascription(Box<Expr>, DeclType),
+
+    Use(Import),
     Decl(Decl),
     Def(Def),
+    Equation(Equation),
+    Type(TypeDef),
+    Enum(EnumDef),
+    Trait(TraitDef),
+    Impl(TraitImpl),
+    Effect(EffectDef),
+    PatternSynonym(PatternSynonym),
 }
@@ -5,13 +5,15 @@
 use crate::ast::name::TraitName;
 use crate::ast::name::TypeName;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
-pub struct WhereClause(pub Vec<TraitName>);
+/// `where A: Show, B: Eq`, the traits that the type variables of an item implement
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct WhereClause(pub Vec<Generic>);
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+/// `A: Show`
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Generic {
     pub type_name: TypeName,
-    pub where_clause: WhereClause,
+    pub trait_name: TraitName,
 }
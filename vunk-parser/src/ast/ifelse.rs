@@ -4,8 +4,8 @@
 
 use crate::ast::expr::Expr;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct IfElse {
     pub condition: Box<Expr>,
     pub tru: Box<Expr>,
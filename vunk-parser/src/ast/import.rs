@@ -0,0 +1,16 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// `use Std.List`, which brings an item of another module into scope by the last name of its path
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Import {
+    pub path: Vec<String>,
+
+    /// The name the item is in scope by instead, like `L` in `use Std.List as L`
+    pub alias: Option<String>,
+
+    /// Whether all items of the module are brought into scope, as by `use Std.List.*`
+    pub glob: bool,
+}
@@ -3,19 +3,46 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use crate::ast::decl::Decl;
+use crate::ast::decl::DeclType;
 use crate::ast::def::Def;
+use crate::ast::def::Equation;
 use crate::ast::expr::Expr;
+use crate::ast::pattern::Pattern;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct LetIns {
     pub items: Vec<LetIn>,
     pub expr: Box<Expr>,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum LetIn {
     Decl(Decl),
     Def(Def),
+    Equation(Equation),
+    Pattern(PatternBinding),
+}
+
+/// `Point { x: px, y: py }: Point = origin`, a binding of the variables of a pattern, which has
+/// to match every value, see [`crate::desugar::desugar_where`]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PatternBinding {
+    pub pattern: Pattern,
+
+    /// The type of the value, if annotated
+    pub ty: Option<DeclType>,
+
+    pub expr: Box<Expr>,
+}
+
+/// `expr where { items }`, the auxiliary bindings of a definition, which are in scope in the
+/// expression and in each other, like the bindings of a `let`
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct WhereBindings {
+    pub expr: Box<Expr>,
+    pub items: Vec<LetIn>,
 }
@@ -2,30 +2,40 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+use num_bigint::BigInt;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Literal {
+    /// `()`, the only value of the type `()`, which functions return that are only run for their
+    /// effects, like `Std.IO.println`
+    Unit,
+
     Bool(Bool),
     Integer(Integer),
     Float(Float),
     Str(Str),
+
+    /// `b"text"` or `x"00ff"`, of type `Bytes`
+    Bytes(Bytes),
+
     List(Vec<crate::ast::expr::Expr>),
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Bool {
     pub value: bool,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Integer {
     pub value: IntegerValue,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum IntegerValue {
     I8(i8),
     I16(i16),
@@ -35,16 +45,39 @@ pub enum IntegerValue {
     U16(u16),
     U32(u32),
     U64(u64),
+    Big(BigInt),
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+impl IntegerValue {
+    /// Convert the digits of an integer literal to a value
+    ///
+    /// The literal is stored as `I64` if it fits, as `U64` if it is too big for that, and as an
+    /// arbitrary precision `Big` integer otherwise, so no literal is silently truncated.
+    pub fn from_literal(digits: &str) -> Option<IntegerValue> {
+        if let Ok(i) = digits.parse::<i64>() {
+            Some(IntegerValue::I64(i))
+        } else if let Ok(u) = digits.parse::<u64>() {
+            Some(IntegerValue::U64(u))
+        } else {
+            digits.parse::<BigInt>().ok().map(IntegerValue::Big)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Float {
     pub value: f64,
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Str {
     pub value: String,
 }
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Bytes {
+    pub value: Vec<u8>,
+}
@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::ast::expr::Expr;
+use crate::ast::pattern::Pattern;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct MatchWhen {
+    pub expr: Box<Expr>,
+    pub arms: Vec<When>,
+    pub otherwise: Option<Box<Expr>>,
+}
+
+/// An arm, `when pattern -> expr` or `when pattern when guard -> expr`
+///
+/// The arms are tried in order. The guard of an arm is evaluated after its pattern matched, with
+/// the names the pattern binds in scope, and only then. If it is `false`, the next arm is tried.
+/// So the guards of the arms before the one that is taken are evaluated, and no other.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct When {
+    pub pattern: Pattern,
+    pub guard: Option<Box<Expr>>,
+    pub expr: Box<Expr>,
+}
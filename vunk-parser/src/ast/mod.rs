@@ -2,13 +2,22 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+pub mod attribute;
+pub mod comprehension;
 pub mod decl;
 pub mod def;
+pub mod doblock;
+pub mod effect;
 pub mod expr;
 pub mod generic;
 pub mod ifelse;
+pub mod import;
 pub mod letin;
 pub mod literal;
+pub mod matchwhen;
 pub mod name;
 pub mod op;
+pub mod pattern;
 pub mod program;
+pub mod record;
+pub mod traitdef;
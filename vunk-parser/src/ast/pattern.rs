@@ -0,0 +1,56 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::ast::literal::Literal;
+use crate::ast::name::TypeName;
+use crate::ast::name::TypePath;
+use crate::ast::name::VariableName;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Pattern {
+    Wildcard,
+    Variable(VariableName),
+
+    /// A bool, number or string, never a list
+    Literal(Literal),
+
+    Variant {
+        path: TypePath,
+        members: Vec<Pattern>,
+    },
+
+    /// `Shape.Circle { radius: r }`, a variant by some of its named fields
+    Fields {
+        path: TypePath,
+        fields: Vec<FieldPattern>,
+    },
+
+    /// `[first, second, ..rest]`, a list with at least the elements, and with exactly them
+    /// without `rest`
+    List {
+        elements: Vec<Pattern>,
+        rest: Option<Box<Pattern>>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct FieldPattern {
+    pub name: VariableName,
+    pub pattern: Pattern,
+}
+
+/// `pattern Head x = List.Cons { x, _ }`, a name for the shape of a pattern, which is matched
+/// like a variant, as in `when Head first -> first`
+///
+/// The parameters are the variables of the pattern, and are replaced by the patterns the synonym
+/// is used with, see [`crate::desugar::desugar_synonyms`].
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PatternSynonym {
+    pub name: TypeName,
+    pub params: Vec<VariableName>,
+    pub pattern: Pattern,
+}
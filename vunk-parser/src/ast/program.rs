@@ -4,8 +4,8 @@
 
 use crate::ast::expr::Expr;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Program {
     pub expr: Vec<Expr>,
 }
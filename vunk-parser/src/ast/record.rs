@@ -0,0 +1,35 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::ast::expr::Expr;
+use crate::ast::name::TypePath;
+use crate::ast::name::VariableName;
+
+/// `{ name = expr, ... }`, a record without a `type` declaration
+///
+/// Its type is the set of its fields and their types, so it can be passed wherever a record with
+/// at least these fields is expected.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Record {
+    pub fields: Vec<Field>,
+}
+
+/// `Path { name: expr, ... }`, a value of a record type or of a variant with named fields
+///
+/// A field whose value is a variable of its name can be written as just the name, like `name` in
+/// `Person { name, age: 7 }`.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Construct {
+    pub path: TypePath,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Field {
+    pub name: VariableName,
+    pub expr: Expr,
+}
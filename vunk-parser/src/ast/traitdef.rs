@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::ast::decl::Decl;
+use crate::ast::def::Def;
+use crate::ast::name::TraitName;
+use crate::ast::name::TypeName;
+
+/// `trait Eq = { eq: (Self, Self) -> Bool  ne: (Self, Self) -> Bool  ne = (a, b) -> !(a == b) }`
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TraitDef {
+    pub name: TraitName,
+
+    /// The associated types, like `Item` in `type Item`, which signatures name as `Self.Item` or
+    /// `Collection.Item`
+    pub types: Vec<TypeName>,
+    pub methods: Vec<Decl>,
+
+    /// The bodies of methods that `impl`s do not have to define, see [`crate::traits`]
+    pub defaults: Vec<Def>,
+}
+
+/// `impl Eq on Point = { eq = (a, b) -> a.x == b.x }`
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TraitImpl {
+    pub trait_name: TraitName,
+    pub type_name: TypeName,
+    pub types: Vec<AssocType>,
+    pub decls: Vec<Decl>,
+    pub methods: Vec<Def>,
+}
+
+/// `type Item = i64`, an associated type of a trait as an `impl` defines it
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct AssocType {
+    pub name: TypeName,
+    pub ty: TypeName,
+}
@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Parsing attributes, like `@test`, `@deprecated("use area")` or `@cfg(not(debug))`
+//!
+//! Attributes are one syntax for everything that is attached to a declaration, so that a new one
+//! does not need a keyword. What an attribute means, and which arguments it takes, is up to the
+//! registry of the driver. The parser only knows the shape of the arguments, see the `attribute`
+//! and `meta` rules of [`crate::grammar`].
+
+use chumsky::error::Simple;
+use chumsky::primitive::just;
+use chumsky::recursive::recursive;
+use chumsky::select;
+use chumsky::Parser;
+use vunk_lexer::Token;
+
+use crate::ast::attribute::Attribute;
+use crate::ast::attribute::Meta;
+use crate::ast::attribute::MetaValue;
+use crate::Spanned;
+
+/// `@name` or `@name(meta, ...)`
+pub fn attribute() -> impl Parser<Token, Attribute, Error = Simple<Token>> + Clone {
+    just(Token::Ctrl('@'))
+        .ignore_then(name())
+        .then(list(meta()).or_not())
+        .map(|(name, args)| Attribute {
+            name,
+            args: args.unwrap_or_default(),
+        })
+}
+
+/// The attributes in front of a declaration, with their spans
+pub fn attributes() -> impl Parser<Token, Vec<Spanned<Attribute>>, Error = Simple<Token>> + Clone {
+    attribute()
+        .map_with_span(|attribute, span| (attribute, span))
+        .repeated()
+}
+
+/// `"string"`, `name`, `name = "string"` or `name(meta, ...)`
+pub fn meta() -> impl Parser<Token, Meta, Error = Simple<Token>> + Clone {
+    recursive(|meta| {
+        let value = just(Token::Assign)
+            .ignore_then(string().map(MetaValue::Str))
+            .or(list(meta).map(MetaValue::List));
+        let named = name()
+            .then(value.or_not())
+            .map(|(name, value)| Meta::Named { name, value });
+        string().map(Meta::Str).or(named)
+    })
+}
+
+// The error of chumsky's select! is Simple<Token>, which is as large as it is for every parser
+#[allow(clippy::result_large_err)]
+fn string() -> impl Parser<Token, String, Error = Simple<Token>> + Clone {
+    select! { Token::Str(value) => value }
+}
+
+// The large error is allowed for the same reason as for string
+#[allow(clippy::result_large_err)]
+fn name() -> impl Parser<Token, String, Error = Simple<Token>> + Clone {
+    select! { Token::Ident(name) => name }
+}
+
+// `(item, ...)`, with at least one item
+fn list<T>(
+    item: impl Parser<Token, T, Error = Simple<Token>> + Clone,
+) -> impl Parser<Token, Vec<T>, Error = Simple<Token>> + Clone {
+    item.separated_by(just(Token::Comma))
+        .at_least(1)
+        .delimited_by(just(Token::ParOpen), just(Token::ParClose))
+}
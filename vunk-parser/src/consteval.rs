@@ -0,0 +1,680 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Evaluating constant expressions at compile time
+//!
+//! A top-level definition without arguments whose value only depends on literals and other such
+//! definitions is a constant, like `size = 4 * 1024` or `names = ["a", "b"] ++ extra`. Constants
+//! are computed once, by [`constants`], and [`fold`] replaces every use of them and every other
+//! expression that can be evaluated without running the program by its value. So array sizes,
+//! lookup tables and default settings cost nothing at startup.
+//!
+//! The evaluation follows the runtime, see `vunk_runtime::arith`. Where the runtime would fail,
+//! like on an integer overflow or a division by zero, nothing is folded and the program fails
+//! when it runs, as it would have without folding. Floats are only folded if the result is finite,
+//! as there are no literals for infinity and NaN.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use num_bigint::BigInt;
+use num_bigint::Sign;
+
+use crate::ast::comprehension::Comprehension;
+use crate::ast::comprehension::Qualifier;
+use crate::ast::def::Def;
+use crate::ast::def::DefRhs;
+use crate::ast::def::Equation;
+use crate::ast::doblock::DoBlock;
+use crate::ast::doblock::DoStatement;
+use crate::ast::expr::Expr;
+use crate::ast::ifelse::IfElse;
+use crate::ast::letin::LetIn;
+use crate::ast::letin::LetIns;
+use crate::ast::letin::PatternBinding;
+use crate::ast::letin::WhereBindings;
+use crate::ast::literal::Bool;
+use crate::ast::literal::Bytes;
+use crate::ast::literal::Float;
+use crate::ast::literal::Integer;
+use crate::ast::literal::IntegerValue;
+use crate::ast::literal::Literal;
+use crate::ast::literal::Str;
+use crate::ast::matchwhen::MatchWhen;
+use crate::ast::matchwhen::When;
+use crate::ast::op::BinaryOp;
+use crate::ast::op::UnaryOp;
+use crate::ast::pattern::Pattern;
+use crate::ast::program::Program;
+use crate::ast::record::Construct;
+use crate::ast::record::Field;
+use crate::ast::record::Record;
+use crate::ast::traitdef::TraitDef;
+use crate::ast::traitdef::TraitImpl;
+
+/// The value of a constant expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+    Unit,
+    Bool(bool),
+    Int(i64),
+    BigInt(BigInt),
+    Float(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+    List(Vec<Constant>),
+}
+
+impl Constant {
+    /// The value of a literal, if its elements are literals too
+    pub fn from_literal(literal: &Literal) -> Option<Constant> {
+        Some(match literal {
+            Literal::Unit => Constant::Unit,
+            Literal::Bool(Bool { value }) => Constant::Bool(*value),
+            Literal::Integer(Integer { value }) => match value {
+                IntegerValue::I8(i) => Constant::Int(i64::from(*i)),
+                IntegerValue::I16(i) => Constant::Int(i64::from(*i)),
+                IntegerValue::I32(i) => Constant::Int(i64::from(*i)),
+                IntegerValue::I64(i) => Constant::Int(*i),
+                IntegerValue::U8(u) => Constant::Int(i64::from(*u)),
+                IntegerValue::U16(u) => Constant::Int(i64::from(*u)),
+                IntegerValue::U32(u) => Constant::Int(i64::from(*u)),
+                IntegerValue::U64(u) => match i64::try_from(*u) {
+                    Ok(i) => Constant::Int(i),
+                    Err(_) => Constant::BigInt(BigInt::from(*u)),
+                },
+                IntegerValue::Big(i) => Constant::BigInt(i.clone()),
+            },
+            Literal::Float(Float { value }) => Constant::Float(*value),
+            Literal::Str(Str { value }) => Constant::Str(value.clone()),
+            Literal::Bytes(Bytes { value }) => Constant::Bytes(value.clone()),
+            Literal::List(elements) => Constant::List(
+                elements
+                    .iter()
+                    .map(|element| match element {
+                        Expr::Literal(literal) => Constant::from_literal(literal),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?,
+            ),
+        })
+    }
+
+    pub fn into_literal(self) -> Literal {
+        match self {
+            Constant::Unit => Literal::Unit,
+            Constant::Bool(value) => Literal::Bool(Bool { value }),
+            Constant::Int(i) => Literal::Integer(Integer {
+                value: IntegerValue::I64(i),
+            }),
+            Constant::BigInt(i) => Literal::Integer(Integer {
+                value: IntegerValue::Big(i),
+            }),
+            Constant::Float(value) => Literal::Float(Float { value }),
+            Constant::Str(value) => Literal::Str(Str { value }),
+            Constant::Bytes(value) => Literal::Bytes(Bytes { value }),
+            Constant::List(elements) => Literal::List(
+                elements
+                    .into_iter()
+                    .map(|element| Expr::Literal(element.into_literal()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// The constants of a program, by name
+///
+/// Names that are defined more than once are left out, as are definitions that refer to
+/// themselves.
+pub fn constants(program: &Program) -> BTreeMap<String, Constant> {
+    let mut definitions = BTreeMap::new();
+    let mut defined = BTreeMap::new();
+    for expr in program.expr.iter() {
+        if let Expr::Def(def) = expr {
+            *defined.entry(def.lhs.0.as_str()).or_insert(0) += 1;
+            if def.rhs.args.is_empty() {
+                definitions.insert(def.lhs.0.as_str(), &*def.rhs.expr);
+            }
+        }
+    }
+    definitions.retain(|name, _| defined[name] == 1);
+
+    // Constants may use constants that are defined further down
+    let mut constants = BTreeMap::new();
+    loop {
+        let mut evaluator = Evaluator::new(&constants);
+        let found = definitions
+            .iter()
+            .filter_map(|(name, expr)| Some((name.to_string(), evaluator.eval(expr)?)))
+            .collect::<Vec<_>>();
+        if found.is_empty() {
+            return constants;
+        }
+        for (name, _) in found.iter() {
+            definitions.remove(name.as_str());
+        }
+        constants.extend(found);
+    }
+}
+
+/// Evaluate an expression that only depends on literals and `constants`
+pub fn eval(expr: &Expr, constants: &BTreeMap<String, Constant>) -> Option<Constant> {
+    Evaluator::new(constants).eval(expr)
+}
+
+/// Replace the uses of constants and the constant expressions of a program by their values
+pub fn fold(program: Program) -> Program {
+    let constants = constants(&program);
+    let mut evaluator = Evaluator::new(&constants);
+    Program {
+        expr: program
+            .expr
+            .into_iter()
+            .map(|expr| evaluator.fold(expr))
+            .collect(),
+    }
+}
+
+struct Evaluator<'a> {
+    constants: &'a BTreeMap<String, Constant>,
+
+    // The names bound by enclosing functions, `let`s and patterns, innermost last, with their
+    // values if they are constant
+    bound: Vec<(String, Option<Constant>)>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(constants: &'a BTreeMap<String, Constant>) -> Self {
+        Evaluator {
+            constants,
+            bound: Vec::new(),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Constant> {
+        match self.bound.iter().rev().find(|(bound, _)| bound == name) {
+            Some((_, value)) => value.clone(),
+            None => self.constants.get(name).cloned(),
+        }
+    }
+
+    fn bind(&mut self, name: &str) {
+        self.bound.push((name.to_string(), None));
+    }
+
+    fn bind_let(&mut self, items: &[LetIn]) {
+        for item in items.iter() {
+            match item {
+                LetIn::Def(def) => self.bind(&def.lhs.0),
+                LetIn::Decl(decl) => self.bind(&decl.lhs.0),
+                LetIn::Equation(equation) => self.bind(&equation.lhs.0),
+                LetIn::Pattern(binding) => {
+                    pattern_names(&binding.pattern, &mut |name| self.bind(name))
+                }
+            }
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Option<Constant> {
+        match expr {
+            Expr::Literal(Literal::List(elements)) => elements
+                .iter()
+                .map(|element| self.eval(element))
+                .collect::<Option<Vec<_>>>()
+                .map(Constant::List),
+            Expr::Literal(literal) => Constant::from_literal(literal),
+            Expr::Variable(name) => self.lookup(&name.0),
+            Expr::Unary(op, expr) => unary(op, self.eval(expr)?),
+            Expr::Binary(op, lhs, rhs) => binary(op, self.eval(lhs)?, self.eval(rhs)?),
+            Expr::IfElse(IfElse {
+                condition,
+                tru,
+                fals,
+            }) => match self.eval(condition)? {
+                Constant::Bool(true) => self.eval(tru),
+                Constant::Bool(false) => self.eval(fals),
+                _ => None,
+            },
+            Expr::LetIn(LetIns { items, expr }) => {
+                let depth = self.bound.len();
+                self.bind_let(items);
+                for item in items.iter() {
+                    match item {
+                        LetIn::Def(def) if def.rhs.args.is_empty() => {
+                            let value = self.eval(&def.rhs.expr);
+                            self.set(&def.lhs.0, value);
+                        }
+                        _ => {}
+                    }
+                }
+                let value = self.eval(expr);
+                self.bound.truncate(depth);
+                value
+            }
+            _ => None,
+        }
+    }
+
+    // Set the value of the innermost binding of `name`
+    fn set(&mut self, name: &str, value: Option<Constant>) {
+        if let Some(bound) = self.bound.iter_mut().rev().find(|(bound, _)| bound == name) {
+            bound.1 = value;
+        }
+    }
+
+    fn fold(&mut self, expr: Expr) -> Expr {
+        let expr = match expr {
+            Expr::Unary(op, expr) => Expr::Unary(op, Box::new(self.fold(*expr))),
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = Box::new(self.fold(*lhs));
+                Expr::Binary(op, lhs, Box::new(self.fold(*rhs)))
+            }
+            Expr::Apply(function, args) => {
+                let function = Box::new(self.fold(*function));
+                Expr::Apply(
+                    function,
+                    args.into_iter().map(|arg| self.fold(arg)).collect(),
+                )
+            }
+            Expr::Literal(Literal::List(elements)) => Expr::Literal(Literal::List(
+                elements
+                    .into_iter()
+                    .map(|element| self.fold(element))
+                    .collect(),
+            )),
+            Expr::Lambda(rhs) => Expr::Lambda(self.fold_rhs(rhs)),
+            Expr::LetIn(LetIns { items, expr }) => return self.fold_let(items, *expr),
+            Expr::Where(WhereBindings { expr, items }) => {
+                // Without anything to evaluate in the bindings, the body replaces them
+                return match self.fold_let(items, *expr) {
+                    Expr::LetIn(LetIns { items, expr }) => {
+                        Expr::Where(WhereBindings { expr, items })
+                    }
+                    expr => expr,
+                };
+            }
+            Expr::IfElse(IfElse {
+                condition,
+                tru,
+                fals,
+            }) => {
+                let condition = self.fold(*condition);
+                match condition {
+                    // Only the branch that is taken is left
+                    Expr::Literal(Literal::Bool(Bool { value: true })) => return self.fold(*tru),
+                    Expr::Literal(Literal::Bool(Bool { value: false })) => return self.fold(*fals),
+                    condition => Expr::IfElse(IfElse {
+                        condition: Box::new(condition),
+                        tru: Box::new(self.fold(*tru)),
+                        fals: Box::new(self.fold(*fals)),
+                    }),
+                }
+            }
+            Expr::MatchWhen(MatchWhen {
+                expr,
+                arms,
+                otherwise,
+            }) => {
+                let expr = Box::new(self.fold(*expr));
+                let arms = arms
+                    .into_iter()
+                    .filter_map(|arm| {
+                        let depth = self.bound.len();
+                        pattern_names(&arm.pattern, &mut |name| self.bind(name));
+                        let guard = arm.guard.map(|guard| self.fold(*guard));
+                        let expr = Box::new(self.fold(*arm.expr));
+                        self.bound.truncate(depth);
+
+                        // An arm whose guard is always false is never taken
+                        let guard = match guard {
+                            Some(Expr::Literal(Literal::Bool(Bool { value: true }))) => None,
+                            Some(Expr::Literal(Literal::Bool(Bool { value: false }))) => {
+                                return None
+                            }
+                            guard => guard.map(Box::new),
+                        };
+                        Some(When {
+                            pattern: arm.pattern,
+                            guard,
+                            expr,
+                        })
+                    })
+                    .collect();
+                Expr::MatchWhen(MatchWhen {
+                    expr,
+                    arms,
+                    otherwise: otherwise.map(|otherwise| Box::new(self.fold(*otherwise))),
+                })
+            }
+            Expr::Do(DoBlock { statements, result }) => {
+                let depth = self.bound.len();
+                let statements = statements
+                    .into_iter()
+                    .map(|statement| match statement {
+                        DoStatement::Bind(name, expr) => {
+                            let expr = self.fold(expr);
+                            self.bind(&name.0);
+                            DoStatement::Bind(name, expr)
+                        }
+                        DoStatement::Let(name, expr) => {
+                            let expr = self.fold(expr);
+                            self.bind(&name.0);
+                            DoStatement::Let(name, expr)
+                        }
+                        DoStatement::Run(expr) => DoStatement::Run(self.fold(expr)),
+                    })
+                    .collect();
+                let result = Box::new(self.fold(*result));
+                self.bound.truncate(depth);
+                Expr::Do(DoBlock { statements, result })
+            }
+            Expr::Comprehension(Comprehension { expr, qualifiers }) => {
+                let depth = self.bound.len();
+                let qualifiers = qualifiers
+                    .into_iter()
+                    .map(|qualifier| match qualifier {
+                        Qualifier::Generator(name, list) => {
+                            let list = self.fold(list);
+                            self.bind(&name.0);
+                            Qualifier::Generator(name, list)
+                        }
+                        Qualifier::Filter(condition) => Qualifier::Filter(self.fold(condition)),
+                    })
+                    .collect();
+                let expr = Box::new(self.fold(*expr));
+                self.bound.truncate(depth);
+                Expr::Comprehension(Comprehension { expr, qualifiers })
+            }
+            Expr::Record(Record { fields }) => Expr::Record(Record {
+                fields: fields
+                    .into_iter()
+                    .map(|field| Field {
+                        name: field.name,
+                        expr: self.fold(field.expr),
+                    })
+                    .collect(),
+            }),
+            Expr::Construct(Construct { path, fields }) => Expr::Construct(Construct {
+                path,
+                fields: fields
+                    .into_iter()
+                    .map(|field| Field {
+                        name: field.name,
+                        expr: self.fold(field.expr),
+                    })
+                    .collect(),
+            }),
+            Expr::Spread(list) => Expr::Spread(Box::new(self.fold(*list))),
+            Expr::Lazy(expr) => Expr::Lazy(Box::new(self.fold(*expr))),
+            Expr::Try(expr) => Expr::Try(Box::new(self.fold(*expr))),
+            // The type may not be the one of the folded value, like in `(1: u8)`, so it stays
+            Expr::Ascription(expr, ty) => Expr::Ascription(Box::new(self.fold(*expr)), ty),
+            Expr::Def(def) => Expr::Def(self.fold_def(def)),
+            Expr::Equation(equation) => Expr::Equation(self.fold_equation(equation)),
+            Expr::Trait(def) => Expr::Trait(TraitDef {
+                defaults: def
+                    .defaults
+                    .into_iter()
+                    .map(|def| self.fold_def(def))
+                    .collect(),
+                ..def
+            }),
+            Expr::Impl(def) => Expr::Impl(TraitImpl {
+                methods: def
+                    .methods
+                    .into_iter()
+                    .map(|def| self.fold_def(def))
+                    .collect(),
+                ..def
+            }),
+            other @ (Expr::Variable(_)
+            | Expr::FieldSection(_)
+            | Expr::Literal(_)
+            | Expr::Use(_)
+            | Expr::Decl(_)
+            | Expr::Type(_)
+            | Expr::Enum(_)
+            | Expr::Effect(_)
+            | Expr::PatternSynonym(_)) => other,
+        };
+
+        match expr {
+            Expr::Variable(_) | Expr::Unary(..) | Expr::Binary(..) => match self.eval(&expr) {
+                Some(value) => Expr::Literal(value.into_literal()),
+                None => expr,
+            },
+            expr => expr,
+        }
+    }
+
+    fn fold_def(&mut self, def: Def) -> Def {
+        Def {
+            lhs: def.lhs,
+            rhs: self.fold_rhs(def.rhs),
+        }
+    }
+
+    fn fold_equation(&mut self, equation: Equation) -> Equation {
+        let depth = self.bound.len();
+        for param in equation.params.iter() {
+            pattern_names(param, &mut |name| self.bind(name));
+        }
+        let expr = Box::new(self.fold(*equation.expr));
+        self.bound.truncate(depth);
+        Equation { expr, ..equation }
+    }
+
+    fn fold_rhs(&mut self, rhs: DefRhs) -> DefRhs {
+        let depth = self.bound.len();
+        for arg in rhs.args.iter() {
+            match &arg.pattern {
+                Some(pattern) => pattern_names(pattern, &mut |name| self.bind(name)),
+                None => self.bind(&arg.name.0),
+            }
+        }
+        let expr = Box::new(self.fold(*rhs.expr));
+        self.bound.truncate(depth);
+        DefRhs {
+            args: rhs.args,
+            expr,
+        }
+    }
+
+    // The bindings of a `let` are in scope in all of them, so they are bound before any of them
+    // is folded
+    fn fold_let(&mut self, items: Vec<LetIn>, expr: Expr) -> Expr {
+        let depth = self.bound.len();
+        self.bind_let(&items);
+
+        let items = items
+            .into_iter()
+            .map(|item| match item {
+                LetIn::Def(def) => {
+                    let def = self.fold_def(def);
+                    if def.rhs.args.is_empty() {
+                        let value = match &*def.rhs.expr {
+                            Expr::Literal(literal) => Constant::from_literal(literal),
+                            _ => None,
+                        };
+                        self.set(&def.lhs.0, value);
+                    }
+                    LetIn::Def(def)
+                }
+                LetIn::Decl(decl) => LetIn::Decl(decl),
+                LetIn::Equation(equation) => LetIn::Equation(self.fold_equation(equation)),
+                LetIn::Pattern(binding) => LetIn::Pattern(PatternBinding {
+                    expr: Box::new(self.fold(*binding.expr)),
+                    ..binding
+                }),
+            })
+            .collect::<Vec<_>>();
+        let expr = self.fold(expr);
+        self.bound.truncate(depth);
+
+        // Without anything to evaluate in the bindings, a constant body does not need them
+        let evaluated = items.iter().all(|item| match item {
+            LetIn::Def(def) => {
+                !def.rhs.args.is_empty() || matches!(*def.rhs.expr, Expr::Literal(_))
+            }
+            LetIn::Decl(_) | LetIn::Equation(_) => true,
+            LetIn::Pattern(_) => false,
+        });
+        match expr {
+            Expr::Literal(_) if evaluated => expr,
+            expr => Expr::LetIn(LetIns {
+                items,
+                expr: Box::new(expr),
+            }),
+        }
+    }
+}
+
+fn pattern_names(pattern: &Pattern, f: &mut dyn FnMut(&str)) {
+    match pattern {
+        Pattern::Wildcard | Pattern::Literal(_) => {}
+        Pattern::Variable(name) => f(&name.0),
+        Pattern::Variant { members, .. } => {
+            members.iter().for_each(|member| pattern_names(member, f));
+        }
+        Pattern::Fields { fields, .. } => {
+            fields
+                .iter()
+                .for_each(|field| pattern_names(&field.pattern, f));
+        }
+        Pattern::List { elements, rest } => {
+            elements
+                .iter()
+                .for_each(|element| pattern_names(element, f));
+            if let Some(rest) = rest {
+                pattern_names(rest, f);
+            }
+        }
+    }
+}
+
+fn unary(op: &UnaryOp, value: Constant) -> Option<Constant> {
+    match (op, value) {
+        (UnaryOp::LogicalNot, Constant::Bool(b)) => Some(Constant::Bool(!b)),
+        (UnaryOp::BinaryNot, Constant::Int(i)) => Some(Constant::Int(!i)),
+        (UnaryOp::BinaryNot, Constant::BigInt(i)) => Some(Constant::BigInt(!i)),
+        _ => None,
+    }
+}
+
+fn binary(op: &BinaryOp, lhs: Constant, rhs: Constant) -> Option<Constant> {
+    match op {
+        BinaryOp::Add => numeric(lhs, rhs, i64::checked_add, |a, b| a + b, |a, b| a + b),
+        BinaryOp::Sub => numeric(lhs, rhs, i64::checked_sub, |a, b| a - b, |a, b| a - b),
+        BinaryOp::Mul => numeric(lhs, rhs, i64::checked_mul, |a, b| a * b, |a, b| a * b),
+        BinaryOp::Div if !is_zero(&rhs) => {
+            numeric(lhs, rhs, i64::checked_div, |a, b| a / b, |a, b| a / b)
+        }
+        BinaryOp::Rem if !is_zero(&rhs) => {
+            numeric(lhs, rhs, i64::checked_rem, |a, b| a % b, |a, b| a % b)
+        }
+        BinaryOp::Div | BinaryOp::Rem => None,
+        BinaryOp::Eq => equal(&lhs, &rhs).map(Constant::Bool),
+        BinaryOp::NotEq => equal(&lhs, &rhs).map(|equal| Constant::Bool(!equal)),
+        BinaryOp::Less => compare(&lhs, &rhs).map(|o| Constant::Bool(o.is_lt())),
+        BinaryOp::LessEq => compare(&lhs, &rhs).map(|o| Constant::Bool(o.is_le())),
+        BinaryOp::More => compare(&lhs, &rhs).map(|o| Constant::Bool(o.is_gt())),
+        BinaryOp::MoreEq => compare(&lhs, &rhs).map(|o| Constant::Bool(o.is_ge())),
+        BinaryOp::LogicalAnd => match (lhs, rhs) {
+            (Constant::Bool(a), Constant::Bool(b)) => Some(Constant::Bool(a && b)),
+            _ => None,
+        },
+        BinaryOp::LogicalOr => match (lhs, rhs) {
+            (Constant::Bool(a), Constant::Bool(b)) => Some(Constant::Bool(a || b)),
+            _ => None,
+        },
+        BinaryOp::BitAnd => bits(lhs, rhs, |a, b| a & b, |a, b| a & b, |a, b| a & b),
+        BinaryOp::BitOr => bits(lhs, rhs, |a, b| a | b, |a, b| a | b, |a, b| a | b),
+        BinaryOp::BitXor => bits(lhs, rhs, |a, b| a ^ b, |a, b| a ^ b, |a, b| a ^ b),
+        BinaryOp::Join => match (lhs, rhs) {
+            (Constant::Str(a), Constant::Str(b)) => Some(Constant::Str(a + &b)),
+            (Constant::Bytes(mut a), Constant::Bytes(b)) => {
+                a.extend(b);
+                Some(Constant::Bytes(a))
+            }
+            (Constant::List(mut a), Constant::List(b)) => {
+                a.extend(b);
+                Some(Constant::List(a))
+            }
+            _ => None,
+        },
+    }
+}
+
+fn numeric(
+    lhs: Constant,
+    rhs: Constant,
+    int: fn(i64, i64) -> Option<i64>,
+    big: fn(&BigInt, &BigInt) -> BigInt,
+    float: fn(f64, f64) -> f64,
+) -> Option<Constant> {
+    match (lhs, rhs) {
+        (Constant::Int(a), Constant::Int(b)) => int(a, b).map(Constant::Int),
+        (Constant::Int(a), Constant::BigInt(b)) => Some(Constant::BigInt(big(&a.into(), &b))),
+        (Constant::BigInt(a), Constant::Int(b)) => Some(Constant::BigInt(big(&a, &b.into()))),
+        (Constant::BigInt(a), Constant::BigInt(b)) => Some(Constant::BigInt(big(&a, &b))),
+        (Constant::Float(a), Constant::Float(b)) => Some(float(a, b))
+            .filter(|f| f.is_finite())
+            .map(Constant::Float),
+        _ => None,
+    }
+}
+
+fn bits(
+    lhs: Constant,
+    rhs: Constant,
+    boolean: fn(bool, bool) -> bool,
+    int: fn(i64, i64) -> i64,
+    big: fn(&BigInt, &BigInt) -> BigInt,
+) -> Option<Constant> {
+    match (lhs, rhs) {
+        (Constant::Bool(a), Constant::Bool(b)) => Some(Constant::Bool(boolean(a, b))),
+        (Constant::Int(a), Constant::Int(b)) => Some(Constant::Int(int(a, b))),
+        (Constant::Int(a), Constant::BigInt(b)) => Some(Constant::BigInt(big(&a.into(), &b))),
+        (Constant::BigInt(a), Constant::Int(b)) => Some(Constant::BigInt(big(&a, &b.into()))),
+        (Constant::BigInt(a), Constant::BigInt(b)) => Some(Constant::BigInt(big(&a, &b))),
+        _ => None,
+    }
+}
+
+fn is_zero(value: &Constant) -> bool {
+    match value {
+        Constant::Int(i) => *i == 0,
+        Constant::BigInt(i) => i.sign() == Sign::NoSign,
+        _ => false,
+    }
+}
+
+/// Compare two constants of the same primitive type, like `vunk_runtime::cmp::compare`
+pub fn compare(lhs: &Constant, rhs: &Constant) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Constant::Unit, Constant::Unit) => Some(Ordering::Equal),
+        (Constant::Bool(a), Constant::Bool(b)) => Some(a.cmp(b)),
+        (Constant::Int(a), Constant::Int(b)) => Some(a.cmp(b)),
+        (Constant::Int(a), Constant::BigInt(b)) => Some(BigInt::from(*a).cmp(b)),
+        (Constant::BigInt(a), Constant::Int(b)) => Some(a.cmp(&BigInt::from(*b))),
+        (Constant::BigInt(a), Constant::BigInt(b)) => Some(a.cmp(b)),
+        (Constant::Float(a), Constant::Float(b)) => Some(a.total_cmp(b)),
+        (Constant::Str(a), Constant::Str(b)) => Some(a.cmp(b)),
+        (Constant::Bytes(a), Constant::Bytes(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn equal(lhs: &Constant, rhs: &Constant) -> Option<bool> {
+    match (lhs, rhs) {
+        (Constant::List(a), Constant::List(b)) if a.len() == b.len() => a
+            .iter()
+            .zip(b.iter())
+            .map(|(a, b)| equal(a, b))
+            .try_fold(true, |all, equal| Some(all && equal?)),
+        (Constant::List(_), Constant::List(_)) => Some(false),
+        (lhs, rhs) => compare(lhs, rhs).map(Ordering::is_eq),
+    }
+}
@@ -0,0 +1,2016 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Desugaring of syntactic sugar into core expressions
+
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use crate::ast::comprehension::Comprehension;
+use crate::ast::comprehension::Qualifier;
+use crate::ast::decl::Decl;
+use crate::ast::def::Def;
+use crate::ast::def::DefArg;
+use crate::ast::def::DefArgType;
+use crate::ast::def::DefRhs;
+use crate::ast::def::EnumDef;
+use crate::ast::def::Equation;
+use crate::ast::doblock::DoBlock;
+use crate::ast::doblock::DoStatement;
+use crate::ast::expr::Expr;
+use crate::ast::ifelse::IfElse;
+use crate::ast::letin::LetIn;
+use crate::ast::letin::LetIns;
+use crate::ast::letin::PatternBinding;
+use crate::ast::letin::WhereBindings;
+use crate::ast::literal::Literal;
+use crate::ast::literal::Str;
+use crate::ast::matchwhen::MatchWhen;
+use crate::ast::matchwhen::When;
+use crate::ast::name::TypeName;
+use crate::ast::name::TypePath;
+use crate::ast::name::VariableName;
+use crate::ast::op::BinaryOp;
+use crate::ast::pattern::FieldPattern;
+use crate::ast::pattern::Pattern;
+use crate::ast::pattern::PatternSynonym;
+use crate::ast::program::Program;
+use crate::ast::record::Construct;
+use crate::ast::record::Field;
+use crate::ast::record::Record;
+use crate::ast::traitdef::TraitDef;
+use crate::ast::traitdef::TraitImpl;
+use crate::consteval::Constant;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DesugarError {
+    #[error("'?' can only be used where its value determines the result of the function")]
+    TryNotAllowed,
+
+    #[error("The parameter pattern {pattern} does not match every value, use a 'match' instead")]
+    RefutableParameter { pattern: String },
+
+    #[error("The equations of '{name}' have different numbers of parameters")]
+    EquationArity { name: String },
+
+    #[error("The binding pattern {pattern} does not match every value, use a 'match' instead")]
+    RefutableBinding { pattern: String },
+
+    #[error("The pattern synonym {name} is defined more than once")]
+    DuplicateSynonym { name: String },
+
+    #[error("The pattern of the pattern synonym {name} has to bind each of its parameters once")]
+    SynonymParams { name: String },
+
+    #[error("The pattern synonym {name} takes {expected} patterns, but is used with {found}")]
+    SynonymArity {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+// Names that contain a '?' cannot be written in source code, so they never clash
+const ERR_NAME: &str = "err?";
+const VALUE_NAME: &str = "value?";
+const PARAM_NAME: &str = "param?";
+const ARG_NAME: &str = "arg?";
+const CASE_NAME: &str = "case?";
+const BINDING_NAME: &str = "binding?";
+const SECTION_NAME: &str = "section?";
+
+/// Desugar `expr?` into a `match` on the result of `expr`
+///
+/// `expr?` evaluates to the `Ok` value of `expr`, or makes the enclosing function return the
+/// `Err` value of `expr`. For that, the expression containing `expr?` is wrapped as follows:
+///
+/// ```text
+/// match expr
+///     when Ok try?0 -> <expression, with expr? replaced by try?0>
+///     when Err err? -> Err err?
+/// ```
+///
+/// The expression that is wrapped is the result of the function, so `?` can only be used in the
+/// result of the function (including the branches of a resulting `if` or `match` and the body
+/// and bindings of a resulting `let`) but not in nested `let`s or branches whose value is used
+/// otherwise.
+pub fn desugar_try(program: Program) -> Result<Program, DesugarError> {
+    let mut desugarer = TryDesugarer { next: 0 };
+    program
+        .expr
+        .into_iter()
+        .map(|expr| match expr {
+            Expr::Def(def) => desugarer.def(def).map(Expr::Def),
+            other => desugarer.strict(other, None),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|expr| Program { expr })
+}
+
+/// Desugar `do` blocks into calls of `Monad.andThen`
+///
+/// ```text
+/// do { x <- a, let y = f x, b, c }
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// Monad.andThen ((x) -> let y = f x in Monad.andThen ((_) -> c) b) a
+/// ```
+///
+/// so the monad the block runs in is determined by the `Monad` implementation that is picked for
+/// the type of `a`.
+///
+/// This has to run before [`desugar_try`], so a `?` in a `do` block ends up in the lambda it
+/// belongs to.
+pub fn desugar_do(program: Program) -> Program {
+    let mut desugar = |expr: Expr| match expr {
+        Expr::Do(block) => do_block(block),
+        other => other,
+    };
+
+    Program {
+        expr: program
+            .expr
+            .into_iter()
+            .map(|expr| rewrite(expr, &mut desugar))
+            .collect(),
+    }
+}
+
+/// Desugar list comprehensions into calls of `Std.List.map`, `Std.List.filter` and
+/// `Std.List.concatMap`
+///
+/// ```text
+/// [x * y | x <- xs, x > 0, y <- ys]
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// Std.List.concatMap ((x) -> Std.List.map ((y) -> x * y) ys) (Std.List.filter ((x) -> x > 0) xs)
+/// ```
+///
+/// Conditions right after a generator filter its list, other conditions become an `if` with an
+/// empty list in the `else`. Like `do` blocks, this has to run before [`desugar_try`].
+pub fn desugar_comprehension(program: Program) -> Program {
+    let mut desugar = |expr: Expr| match expr {
+        Expr::Comprehension(Comprehension { expr, qualifiers }) => {
+            comprehension(*expr, &mut qualifiers.into_iter().peekable())
+        }
+        other => other,
+    };
+
+    Program {
+        expr: program
+            .expr
+            .into_iter()
+            .map(|expr| rewrite(expr, &mut desugar))
+            .collect(),
+    }
+}
+
+/// Desugar spreads in list literals into a call of `Std.List.concat`
+///
+/// ```text
+/// [a ..xs b c ..ys]
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// Std.List.concat [[a] xs [b c] ys]
+/// ```
+///
+/// so every element is copied once, however many lists are spread, and `[..xs]` becomes `xs`.
+pub fn desugar_spread(program: Program) -> Program {
+    let mut desugar = |expr: Expr| match expr {
+        Expr::Literal(Literal::List(elements))
+            if elements
+                .iter()
+                .any(|element| matches!(element, Expr::Spread(_))) =>
+        {
+            spread(elements)
+        }
+        other => other,
+    };
+
+    Program {
+        expr: program
+            .expr
+            .into_iter()
+            .map(|expr| rewrite(expr, &mut desugar))
+            .collect(),
+    }
+}
+
+fn spread(elements: Vec<Expr>) -> Expr {
+    let mut lists = Vec::new();
+    let mut items = Vec::new();
+    for element in elements {
+        match element {
+            Expr::Spread(list) => {
+                if !items.is_empty() {
+                    lists.push(Expr::Literal(Literal::List(std::mem::take(&mut items))));
+                }
+                lists.push(*list);
+            }
+            item => items.push(item),
+        }
+    }
+    if !items.is_empty() {
+        lists.push(Expr::Literal(Literal::List(items)));
+    }
+
+    if lists.len() == 1 {
+        return lists.remove(0);
+    }
+    Expr::Apply(
+        Box::new(Expr::Variable(VariableName("Std.List.concat".to_string()))),
+        vec![Expr::Literal(Literal::List(lists))],
+    )
+}
+
+const STRING_CONCAT: &str = "Std.String.concat";
+
+/// Desugar chains of `++` on strings into a call of `Std.String.concat`
+///
+/// ```text
+/// "Hello, " ++ name ++ "! You are " ++ age ++ "."
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// Std.String.concat ["Hello, " name "! You are " age "."]
+/// ```
+///
+/// so every piece is copied once, instead of the result of every `++` again. A chain is joined
+/// if one of its operands is a string literal, as the operands of `++` have the same type, and
+/// adjacent literals are joined right away. Other chains are left alone, as `++` is not
+/// necessarily associative on types with their own `impl Concat`.
+pub fn desugar_join(program: Program) -> Program {
+    let mut desugar = |expr: Expr| match expr {
+        Expr::Binary(BinaryOp::Join, lhs, rhs) if joins_strings(&lhs) || joins_strings(&rhs) => {
+            let mut pieces = Vec::new();
+            join_pieces(*lhs, &mut pieces);
+            join_pieces(*rhs, &mut pieces);
+            join(pieces)
+        }
+        other => other,
+    };
+
+    Program {
+        expr: program
+            .expr
+            .into_iter()
+            .map(|expr| rewrite(expr, &mut desugar))
+            .collect(),
+    }
+}
+
+// Whether an operand of `++` is known to be a string, which chains that were joined already are
+fn joins_strings(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(Literal::Str(_)) => true,
+        Expr::Binary(BinaryOp::Join, lhs, rhs) => joins_strings(lhs) || joins_strings(rhs),
+        expr => is_string_concat(expr),
+    }
+}
+
+// Whether an expression is a chain that was joined already
+fn is_string_concat(expr: &Expr) -> bool {
+    match expr {
+        Expr::Apply(function, args) => matches!(
+            (&**function, args.as_slice()),
+            (Expr::Variable(VariableName(name)), [Expr::Literal(Literal::List(_))])
+                if name == STRING_CONCAT
+        ),
+        _ => false,
+    }
+}
+
+// Collect the operands of a chain of `++`, with the pieces of the chains in it that were joined
+// already, and adjacent literals joined
+fn join_pieces(expr: Expr, pieces: &mut Vec<Expr>) {
+    match expr {
+        Expr::Binary(BinaryOp::Join, lhs, rhs) => {
+            join_pieces(*lhs, pieces);
+            join_pieces(*rhs, pieces);
+        }
+        expr if is_string_concat(&expr) => {
+            if let Expr::Apply(_, mut args) = expr {
+                if let Some(Expr::Literal(Literal::List(elements))) = args.pop() {
+                    elements
+                        .into_iter()
+                        .for_each(|element| join_pieces(element, pieces));
+                }
+            }
+        }
+        Expr::Literal(Literal::Str(Str { value })) => match pieces.last_mut() {
+            Some(Expr::Literal(Literal::Str(last))) => last.value.push_str(&value),
+            _ => pieces.push(Expr::Literal(Literal::Str(Str { value }))),
+        },
+        expr => pieces.push(expr),
+    }
+}
+
+fn join(mut pieces: Vec<Expr>) -> Expr {
+    match pieces.len() {
+        1 => pieces.remove(0),
+        2 => {
+            let rhs = pieces.remove(1);
+            Expr::Binary(BinaryOp::Join, Box::new(pieces.remove(0)), Box::new(rhs))
+        }
+        _ => Expr::Apply(
+            Box::new(Expr::Variable(VariableName(STRING_CONCAT.to_string()))),
+            vec![Expr::Literal(Literal::List(pieces))],
+        ),
+    }
+}
+
+/// Replace the uses of pattern synonyms with the patterns they stand for
+///
+/// ```text
+/// pattern Head x = List.Cons { x, _ }
+///
+/// first = (list) -> match list when Head x -> Some x else None
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// first = (list) -> match list when List.Cons { x, _ } -> Some x else None
+/// ```
+///
+/// so `match`es with synonyms are checked for exhaustiveness like any other, and the synonyms
+/// themselves are removed from the program. A synonym can use the synonyms defined before it, so
+/// synonyms cannot be recursive.
+///
+/// This has to run before the other desugarings of patterns, like [`desugar_where`] and
+/// [`desugar_params`].
+pub fn desugar_synonyms(program: Program) -> Result<Program, DesugarError> {
+    let mut synonyms = BTreeMap::new();
+    let mut items = Vec::new();
+    for expr in program.expr {
+        let PatternSynonym {
+            name,
+            params,
+            pattern,
+        } = match expr {
+            Expr::PatternSynonym(synonym) => synonym,
+            expr => {
+                items.push(expr);
+                continue;
+            }
+        };
+
+        let mut variables = Vec::new();
+        pattern_variables(&pattern, &mut variables);
+        variables.sort();
+        let mut names = params
+            .iter()
+            .map(|param| param.0.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        if variables != names || names.len() != params.len() {
+            return Err(DesugarError::SynonymParams { name: name.0 });
+        }
+        if synonyms.contains_key(&name.0) {
+            return Err(DesugarError::DuplicateSynonym { name: name.0 });
+        }
+
+        let pattern = expand(pattern, &synonyms)?;
+        synonyms.insert(
+            name.0.clone(),
+            PatternSynonym {
+                name,
+                params,
+                pattern,
+            },
+        );
+    }
+
+    let mut expander = SynonymExpander {
+        synonyms,
+        error: None,
+    };
+    let mut desugar = |expr: Expr| match expr {
+        Expr::MatchWhen(MatchWhen {
+            expr,
+            arms,
+            otherwise,
+        }) => Expr::MatchWhen(MatchWhen {
+            expr,
+            arms: arms
+                .into_iter()
+                .map(|arm| When {
+                    pattern: expander.pattern(arm.pattern),
+                    ..arm
+                })
+                .collect(),
+            otherwise,
+        }),
+        Expr::Lambda(rhs) => Expr::Lambda(expander.rhs(rhs)),
+        Expr::Def(def) => Expr::Def(expander.def(def)),
+        Expr::Equation(equation) => Expr::Equation(expander.equation(equation)),
+        Expr::Trait(def) => Expr::Trait(TraitDef {
+            defaults: def
+                .defaults
+                .into_iter()
+                .map(|def| expander.def(def))
+                .collect(),
+            ..def
+        }),
+        Expr::Impl(def) => Expr::Impl(TraitImpl {
+            methods: def
+                .methods
+                .into_iter()
+                .map(|def| expander.def(def))
+                .collect(),
+            ..def
+        }),
+        Expr::LetIn(LetIns { items, expr }) => Expr::LetIn(LetIns {
+            items: expander.let_items(items),
+            expr,
+        }),
+        Expr::Where(WhereBindings { expr, items }) => Expr::Where(WhereBindings {
+            expr,
+            items: expander.let_items(items),
+        }),
+        other => other,
+    };
+
+    let expr = items
+        .into_iter()
+        .map(|expr| rewrite(expr, &mut desugar))
+        .collect();
+    match expander.error {
+        Some(error) => Err(error),
+        None => Ok(Program { expr }),
+    }
+}
+
+struct SynonymExpander {
+    /// The pattern synonyms of the program, by name, which do not use other synonyms
+    synonyms: BTreeMap<String, PatternSynonym>,
+
+    /// The first wrong use of a synonym that was found
+    error: Option<DesugarError>,
+}
+
+impl SynonymExpander {
+    fn pattern(&mut self, pattern: Pattern) -> Pattern {
+        match expand(pattern, &self.synonyms) {
+            Ok(pattern) => pattern,
+            Err(error) => {
+                self.error.get_or_insert(error);
+                Pattern::Wildcard
+            }
+        }
+    }
+
+    fn def(&mut self, def: Def) -> Def {
+        Def {
+            lhs: def.lhs,
+            rhs: self.rhs(def.rhs),
+        }
+    }
+
+    fn equation(&mut self, equation: Equation) -> Equation {
+        Equation {
+            params: equation
+                .params
+                .into_iter()
+                .map(|param| self.pattern(param))
+                .collect(),
+            ..equation
+        }
+    }
+
+    fn rhs(&mut self, rhs: DefRhs) -> DefRhs {
+        DefRhs {
+            args: rhs
+                .args
+                .into_iter()
+                .map(|arg| DefArg {
+                    pattern: arg.pattern.map(|pattern| self.pattern(pattern)),
+                    ..arg
+                })
+                .collect(),
+            expr: rhs.expr,
+        }
+    }
+
+    fn let_items(&mut self, items: Vec<LetIn>) -> Vec<LetIn> {
+        items
+            .into_iter()
+            .map(|item| match item {
+                LetIn::Def(def) => LetIn::Def(self.def(def)),
+                LetIn::Equation(equation) => LetIn::Equation(self.equation(equation)),
+                LetIn::Pattern(binding) => LetIn::Pattern(PatternBinding {
+                    pattern: self.pattern(binding.pattern),
+                    ..binding
+                }),
+                LetIn::Decl(decl) => LetIn::Decl(decl),
+            })
+            .collect()
+    }
+}
+
+// A pattern with the synonyms it uses replaced
+fn expand(
+    pattern: Pattern,
+    synonyms: &BTreeMap<String, PatternSynonym>,
+) -> Result<Pattern, DesugarError> {
+    let pattern = match pattern {
+        Pattern::Variant { path, members } => {
+            let members = members
+                .into_iter()
+                .map(|member| expand(member, synonyms))
+                .collect::<Result<Vec<_>, _>>()?;
+            let synonym = match path.0.as_slice() {
+                [name] => synonyms.get(&name.0),
+                _ => None,
+            };
+            let synonym = match synonym {
+                Some(synonym) => synonym,
+                None => return Ok(Pattern::Variant { path, members }),
+            };
+
+            if synonym.params.len() != members.len() {
+                return Err(DesugarError::SynonymArity {
+                    name: synonym.name.0.clone(),
+                    expected: synonym.params.len(),
+                    found: members.len(),
+                });
+            }
+            let mut args = synonym
+                .params
+                .iter()
+                .map(|param| param.0.as_str())
+                .zip(members)
+                .collect::<BTreeMap<_, _>>();
+            substitute(&synonym.pattern, &mut |name| {
+                args.remove(name).unwrap_or(Pattern::Wildcard)
+            })
+        }
+        Pattern::Fields { path, fields } => Pattern::Fields {
+            path,
+            fields: fields
+                .into_iter()
+                .map(|field| {
+                    Ok(FieldPattern {
+                        name: field.name,
+                        pattern: expand(field.pattern, synonyms)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, DesugarError>>()?,
+        },
+        Pattern::List { elements, rest } => Pattern::List {
+            elements: elements
+                .into_iter()
+                .map(|element| expand(element, synonyms))
+                .collect::<Result<Vec<_>, _>>()?,
+            rest: rest
+                .map(|rest| expand(*rest, synonyms).map(Box::new))
+                .transpose()?,
+        },
+        other @ (Pattern::Wildcard | Pattern::Variable(_) | Pattern::Literal(_)) => other,
+    };
+    Ok(pattern)
+}
+
+/// Desugar field sections into lambdas
+///
+/// ```text
+/// map .name people
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// map ((section?) -> section?.name) people
+/// ```
+///
+/// Like any access of a field, the lambda takes every record with the field, whatever its other
+/// fields are.
+pub fn desugar_sections(program: Program) -> Program {
+    let mut desugar = |expr: Expr| match expr {
+        Expr::FieldSection(name) => Expr::Lambda(DefRhs {
+            args: vec![DefArg {
+                name: VariableName(SECTION_NAME.to_string()),
+                ty: None,
+                pattern: None,
+            }],
+            expr: Box::new(Expr::Variable(VariableName(format!(
+                "{}.{}",
+                SECTION_NAME, name.0
+            )))),
+        }),
+        other => other,
+    };
+
+    Program {
+        expr: program
+            .expr
+            .into_iter()
+            .map(|expr| rewrite(expr, &mut desugar))
+            .collect(),
+    }
+}
+
+/// Desugar the `where` block of a definition into a `let`, and pattern bindings into a binding
+/// for each of their variables
+///
+/// ```text
+/// distance = (p) -> sqrt (dx * dx + dy * dy) where
+///     { Point { x: dx, y: dy }: Point = p
+///     }
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// distance = (p) ->
+///     let
+///         binding?0 = (p: Point)
+///         dx = match binding?0 when Point { x: dx, y: _ } -> dx
+///         dy = match binding?0 when Point { x: _, y: dy } -> dy
+///     in
+///     sqrt (dx * dx + dy * dy)
+/// ```
+///
+/// The bindings stay one group, so, like those of a `let`, they are in scope in each other and
+/// can be recursive. As with [`desugar_params`], the patterns have to match every value, or the
+/// desugaring fails with [`DesugarError::RefutableBinding`].
+///
+/// This has to run before [`desugar_equations`], so that the equations of a `where` block are
+/// merged like those of a `let`.
+pub fn desugar_where(program: Program) -> Result<Program, DesugarError> {
+    let variants = enum_variants(&program);
+    let mut next = 0;
+    let mut error = None;
+
+    let mut desugar = |expr: Expr| {
+        let (items, expr) = match expr {
+            Expr::LetIn(LetIns { items, expr }) | Expr::Where(WhereBindings { expr, items }) => {
+                (items, expr)
+            }
+            other => return other,
+        };
+
+        let mut bindings = Vec::new();
+        for item in items {
+            let binding = match item {
+                LetIn::Pattern(binding) => binding,
+                other => {
+                    bindings.push(other);
+                    continue;
+                }
+            };
+            if !irrefutable(&variants, &binding.pattern) {
+                error.get_or_insert(DesugarError::RefutableBinding {
+                    pattern: crate::print::pattern(&binding.pattern),
+                });
+            }
+
+            let name = format!("{}{}", BINDING_NAME, next);
+            next += 1;
+            let value = match binding.ty {
+                Some(ty) => Expr::Ascription(binding.expr, ty),
+                None => *binding.expr,
+            };
+            bindings.push(LetIn::Def(Def {
+                lhs: VariableName(name.clone()),
+                rhs: DefRhs {
+                    args: Vec::new(),
+                    expr: Box::new(value),
+                },
+            }));
+
+            let mut variables = Vec::new();
+            pattern_variables(&binding.pattern, &mut variables);
+            for variable in variables {
+                let matched = Expr::MatchWhen(MatchWhen {
+                    expr: Box::new(Expr::Variable(VariableName(name.clone()))),
+                    arms: vec![When {
+                        pattern: only_variable(&binding.pattern, &variable),
+                        guard: None,
+                        expr: Box::new(Expr::Variable(VariableName(variable.clone()))),
+                    }],
+                    otherwise: None,
+                });
+                bindings.push(LetIn::Def(Def {
+                    lhs: VariableName(variable),
+                    rhs: DefRhs {
+                        args: Vec::new(),
+                        expr: Box::new(matched),
+                    },
+                }));
+            }
+        }
+
+        Expr::LetIn(LetIns {
+            items: bindings,
+            expr,
+        })
+    };
+
+    let expr = program
+        .expr
+        .into_iter()
+        .map(|expr| rewrite(expr, &mut desugar))
+        .collect();
+    match error {
+        Some(error) => Err(error),
+        None => Ok(Program { expr }),
+    }
+}
+
+fn pattern_variables(pattern: &Pattern, variables: &mut Vec<String>) {
+    match pattern {
+        Pattern::Wildcard | Pattern::Literal(_) => {}
+        Pattern::Variable(name) => variables.push(name.0.clone()),
+        Pattern::Variant { members, .. } => {
+            members
+                .iter()
+                .for_each(|member| pattern_variables(member, variables));
+        }
+        Pattern::Fields { fields, .. } => {
+            fields
+                .iter()
+                .for_each(|field| pattern_variables(&field.pattern, variables));
+        }
+        Pattern::List { elements, rest } => {
+            elements
+                .iter()
+                .for_each(|element| pattern_variables(element, variables));
+            if let Some(rest) = rest {
+                pattern_variables(rest, variables);
+            }
+        }
+    }
+}
+
+// A copy of an irrefutable pattern that binds only one of its variables
+fn only_variable(pattern: &Pattern, variable: &str) -> Pattern {
+    substitute(pattern, &mut |name| {
+        if name == variable {
+            Pattern::Variable(VariableName(name.to_string()))
+        } else {
+            Pattern::Wildcard
+        }
+    })
+}
+
+// A copy of a pattern, with its variables replaced
+fn substitute(pattern: &Pattern, f: &mut dyn FnMut(&str) -> Pattern) -> Pattern {
+    let path =
+        |path: &TypePath| TypePath(path.0.iter().map(|name| TypeName(name.0.clone())).collect());
+    match pattern {
+        Pattern::Wildcard => Pattern::Wildcard,
+        Pattern::Variable(name) => f(&name.0),
+        // Literals in patterns are never lists, so they are constants
+        Pattern::Literal(literal) => match Constant::from_literal(literal) {
+            Some(constant) => Pattern::Literal(constant.into_literal()),
+            None => Pattern::Wildcard,
+        },
+        Pattern::Variant {
+            path: variant,
+            members,
+        } => Pattern::Variant {
+            path: path(variant),
+            members: members.iter().map(|member| substitute(member, f)).collect(),
+        },
+        Pattern::Fields {
+            path: variant,
+            fields,
+        } => Pattern::Fields {
+            path: path(variant),
+            fields: fields
+                .iter()
+                .map(|field| FieldPattern {
+                    name: VariableName(field.name.0.clone()),
+                    pattern: substitute(&field.pattern, f),
+                })
+                .collect(),
+        },
+        Pattern::List { elements, rest } => Pattern::List {
+            elements: elements
+                .iter()
+                .map(|element| substitute(element, f))
+                .collect(),
+            rest: rest.as_ref().map(|rest| Box::new(substitute(rest, f))),
+        },
+    }
+}
+
+/// Generate accessors for the named fields of the variants of enums
+///
+/// ```text
+/// enum Shape = Circle { radius: Float } | Rect { w: Float, h: Float }
+/// ```
+///
+/// is followed by
+///
+/// ```text
+/// Shape.radius = (value?: Shape) ->
+///     match value? when Shape.Circle { radius: radius } -> Some radius else None
+/// ```
+///
+/// and likewise `Shape.w` and `Shape.h`. A field that every variant has is returned as it is,
+/// without `Some`, and its `match` has no `else`.
+pub fn desugar_accessors(program: Program) -> Program {
+    let mut expr = Vec::new();
+    for item in program.expr {
+        let accessors = match &item {
+            Expr::Enum(def) => accessors(def),
+            _ => Vec::new(),
+        };
+        expr.push(item);
+        expr.extend(accessors);
+    }
+    Program { expr }
+}
+
+fn accessors(def: &EnumDef) -> Vec<Expr> {
+    let variable = |name: &str| Expr::Variable(VariableName(name.to_string()));
+
+    let mut names = Vec::<&str>::new();
+    for field in def
+        .variants
+        .iter()
+        .flat_map(|variant| variant.members.iter())
+    {
+        if !names.contains(&field.name.0.as_str()) {
+            names.push(&field.name.0);
+        }
+    }
+
+    names
+        .into_iter()
+        .map(|name| {
+            let variants = def
+                .variants
+                .iter()
+                .filter(|variant| variant.members.iter().any(|field| field.name.0 == name))
+                .collect::<Vec<_>>();
+            let total = variants.len() == def.variants.len();
+            let arms = variants
+                .into_iter()
+                .map(|variant| When {
+                    pattern: Pattern::Fields {
+                        path: TypePath(vec![
+                            TypeName(def.name.0.clone()),
+                            TypeName(variant.name.0.clone()),
+                        ]),
+                        fields: vec![FieldPattern {
+                            name: VariableName(name.to_string()),
+                            pattern: Pattern::Variable(VariableName(name.to_string())),
+                        }],
+                    },
+                    guard: None,
+                    expr: Box::new(if total {
+                        variable(name)
+                    } else {
+                        Expr::Apply(Box::new(variable("Some")), vec![variable(name)])
+                    }),
+                })
+                .collect();
+
+            Expr::Def(Def {
+                lhs: VariableName(format!("{}.{}", def.name.0, name)),
+                rhs: DefRhs {
+                    args: vec![DefArg {
+                        name: VariableName(VALUE_NAME.to_string()),
+                        ty: Some(DefArgType::TypeName(TypeName(def.name.0.clone()))),
+                        pattern: None,
+                    }],
+                    expr: Box::new(Expr::MatchWhen(MatchWhen {
+                        expr: Box::new(variable(VALUE_NAME)),
+                        arms,
+                        otherwise: (!total).then(|| Box::new(variable("None"))),
+                    })),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Merge the equations of a function into a single definition
+///
+/// The equations of a function are the [`Equation`]s of its name, one after the other, like
+///
+/// ```text
+/// fact 0 = 1
+/// fact n = n * fact (n - 1)
+/// ```
+///
+/// An equation is taken if its patterns match, so equations with a single parameter become the
+/// arms of a `match`:
+///
+/// ```text
+/// fact = (arg?0) -> match arg?0 when 0 -> 1 when n -> n * fact (n - 1)
+/// ```
+///
+/// With more parameters, every equation but the first becomes a function that the one before it
+/// calls if any of its patterns does not match:
+///
+/// ```text
+/// and true true = true
+/// and _ _ = false
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// and = (arg?0, arg?1) ->
+///     let
+///         case?1 = (arg?0, arg?1) -> match arg?0 when _ -> (match arg?1 when _ -> false)
+///     in
+///     match arg?0
+///         when true -> (match arg?1 when true -> true else case?1 arg?0 arg?1)
+///         else case?1 arg?0 arg?1
+/// ```
+///
+/// A single equation becomes a definition whose parameters are its patterns, which
+/// [`desugar_params`] then desugars like those of any other definition.
+pub fn desugar_equations(program: Program) -> Result<Program, DesugarError> {
+    let mut error = None;
+    let mut desugar = |expr: Expr| match expr {
+        Expr::LetIn(LetIns { items, expr }) => {
+            let split = |item: LetIn| match item {
+                LetIn::Equation(equation) => Ok(equation),
+                other => Err(Box::new(other)),
+            };
+            match merge_equations(items, split, LetIn::Def) {
+                Ok(items) => Expr::LetIn(LetIns { items, expr }),
+                Err(err) => {
+                    error.get_or_insert(err);
+                    Expr::LetIn(LetIns {
+                        items: Vec::new(),
+                        expr,
+                    })
+                }
+            }
+        }
+        other => other,
+    };
+
+    let expr = program
+        .expr
+        .into_iter()
+        .map(|expr| rewrite(expr, &mut desugar))
+        .collect();
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    let split = |expr: Expr| match expr {
+        Expr::Equation(equation) => Ok(equation),
+        other => Err(Box::new(other)),
+    };
+    merge_equations(expr, split, Expr::Def).map(|expr| Program { expr })
+}
+
+// Merge the runs of equations in `items`, which `split` tells from other items. `split` hands the
+// other items back boxed, since they can be much larger than an equation
+fn merge_equations<T>(
+    items: Vec<T>,
+    split: impl Fn(T) -> Result<Equation, Box<T>>,
+    join: impl Fn(Def) -> T,
+) -> Result<Vec<T>, DesugarError> {
+    let mut merged = Vec::new();
+    let mut equations: Vec<Equation> = Vec::new();
+    for item in items {
+        let equation = match split(item) {
+            Ok(equation) => equation,
+            Err(other) => {
+                if !equations.is_empty() {
+                    merged.push(join(equations_def(std::mem::take(&mut equations))?));
+                }
+                merged.push(*other);
+                continue;
+            }
+        };
+
+        let continues = equations
+            .last()
+            .map(|last| last.lhs == equation.lhs)
+            .unwrap_or(true);
+        if !continues {
+            merged.push(join(equations_def(std::mem::take(&mut equations))?));
+        }
+        equations.push(equation);
+    }
+    if !equations.is_empty() {
+        merged.push(join(equations_def(equations)?));
+    }
+    Ok(merged)
+}
+
+// The definition of the function with the equations, which all have the same name
+fn equations_def(mut equations: Vec<Equation>) -> Result<Def, DesugarError> {
+    if equations.len() == 1 {
+        let equation = equations.remove(0);
+        return Ok(Def {
+            lhs: equation.lhs,
+            rhs: DefRhs {
+                args: equation
+                    .params
+                    .into_iter()
+                    .map(|param| DefArg::from_pattern(param, None))
+                    .collect(),
+                expr: equation.expr,
+            },
+        });
+    }
+
+    let arity = equations[0].params.len();
+    if equations
+        .iter()
+        .any(|equation| equation.params.len() != arity)
+    {
+        return Err(DesugarError::EquationArity {
+            name: equations[0].lhs.0.clone(),
+        });
+    }
+
+    let variable = |name: String| Expr::Variable(VariableName(name));
+    let arg_name = |index: usize| format!("{}{}", ARG_NAME, index);
+    let args = || {
+        (0..arity)
+            .map(|index| DefArg {
+                name: VariableName(arg_name(index)),
+                ty: None,
+                pattern: None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let call_case = |index: usize| {
+        Expr::Apply(
+            Box::new(variable(format!("{}{}", CASE_NAME, index))),
+            (0..arity).map(|arg| variable(arg_name(arg))).collect(),
+        )
+    };
+
+    let name = VariableName(equations[0].lhs.0.clone());
+    let mut equations = equations
+        .into_iter()
+        .map(|equation| (equation.params, *equation.expr))
+        .collect::<Vec<_>>();
+
+    let expr = if arity == 1 {
+        let arms = equations
+            .into_iter()
+            .map(|(mut patterns, expr)| When {
+                pattern: patterns.remove(0),
+                guard: None,
+                expr: Box::new(expr),
+            })
+            .collect();
+        Expr::MatchWhen(MatchWhen {
+            expr: Box::new(variable(arg_name(0))),
+            arms,
+            otherwise: None,
+        })
+    } else {
+        let (first_patterns, first_expr) = equations.remove(0);
+        let count = equations.len();
+        // Match the arguments against the patterns one after the other, calling the case with
+        // the next equation if one does not match
+        let case = |patterns: Vec<Pattern>, expr: Expr, next: Option<usize>| {
+            patterns
+                .into_iter()
+                .enumerate()
+                .rev()
+                .fold(expr, |expr, (index, pattern)| {
+                    Expr::MatchWhen(MatchWhen {
+                        expr: Box::new(variable(arg_name(index))),
+                        arms: vec![When {
+                            pattern,
+                            guard: None,
+                            expr: Box::new(expr),
+                        }],
+                        otherwise: next.map(|next| Box::new(call_case(next))),
+                    })
+                })
+        };
+
+        let items = equations
+            .into_iter()
+            .enumerate()
+            .map(|(index, (patterns, expr))| {
+                let index = index + 1;
+                let next = (index < count).then(|| index + 1);
+                LetIn::Def(Def {
+                    lhs: VariableName(format!("{}{}", CASE_NAME, index)),
+                    rhs: DefRhs {
+                        args: args(),
+                        expr: Box::new(case(patterns, expr, next)),
+                    },
+                })
+            })
+            .collect();
+        Expr::LetIn(LetIns {
+            items,
+            expr: Box::new(case(first_patterns, first_expr, Some(1))),
+        })
+    };
+
+    Ok(Def {
+        lhs: name,
+        rhs: DefRhs {
+            args: args(),
+            expr: Box::new(expr),
+        },
+    })
+}
+
+/// Desugar patterns in parameters into a `match` on the argument
+///
+/// ```text
+/// (Point { x: x }, Size { w: w }) -> x + w
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// (param?0, param?1) ->
+///     match param?0 when Point { x: x } -> (match param?1 when Size { w: w } -> x + w)
+/// ```
+///
+/// The `match`es have a single arm, so the patterns have to match every value, like the only
+/// variant of an enum or `[..rest]`. A pattern that does not, like a literal, a list of some
+/// length or a variant of an enum with more than one variant, fails with
+/// [`DesugarError::RefutableParameter`].
+pub fn desugar_params(program: Program) -> Result<Program, DesugarError> {
+    let mut desugarer = ParamDesugarer {
+        variants: enum_variants(&program),
+        next: 0,
+        error: None,
+    };
+
+    let mut desugar = |expr: Expr| match expr {
+        Expr::Lambda(rhs) => Expr::Lambda(desugarer.rhs(rhs)),
+        Expr::Def(def) => Expr::Def(desugarer.def(def)),
+        Expr::Trait(def) => Expr::Trait(TraitDef {
+            defaults: desugarer.defs(def.defaults),
+            ..def
+        }),
+        Expr::Impl(def) => Expr::Impl(TraitImpl {
+            methods: desugarer.defs(def.methods),
+            ..def
+        }),
+        Expr::LetIn(LetIns { items, expr }) => Expr::LetIn(LetIns {
+            items: items
+                .into_iter()
+                .map(|item| match item {
+                    LetIn::Def(def) => LetIn::Def(desugarer.def(def)),
+                    other => other,
+                })
+                .collect(),
+            expr,
+        }),
+        other => other,
+    };
+
+    let expr = program
+        .expr
+        .into_iter()
+        .map(|expr| rewrite(expr, &mut desugar))
+        .collect();
+    match desugarer.error {
+        Some(error) => Err(error),
+        None => Ok(Program { expr }),
+    }
+}
+
+struct ParamDesugarer {
+    /// The number of variants of the enums of the program, by name
+    variants: BTreeMap<String, usize>,
+
+    next: usize,
+
+    /// The first refutable pattern that was found
+    error: Option<DesugarError>,
+}
+
+impl ParamDesugarer {
+    fn def(&mut self, def: Def) -> Def {
+        Def {
+            lhs: def.lhs,
+            rhs: self.rhs(def.rhs),
+        }
+    }
+
+    fn defs(&mut self, defs: Vec<Def>) -> Vec<Def> {
+        defs.into_iter().map(|def| self.def(def)).collect()
+    }
+
+    fn rhs(&mut self, rhs: DefRhs) -> DefRhs {
+        let mut args = Vec::new();
+        let mut matched = Vec::new();
+        for arg in rhs.args {
+            let pattern = match arg.pattern {
+                Some(pattern) => pattern,
+                None => {
+                    args.push(arg);
+                    continue;
+                }
+            };
+
+            if !irrefutable(&self.variants, &pattern) && self.error.is_none() {
+                self.error = Some(DesugarError::RefutableParameter {
+                    pattern: crate::print::pattern(&pattern),
+                });
+            }
+            let name = format!("{}{}", PARAM_NAME, self.next);
+            self.next += 1;
+            args.push(DefArg {
+                name: VariableName(name.clone()),
+                ty: arg.ty,
+                pattern: None,
+            });
+            matched.push((name, pattern));
+        }
+
+        let expr = matched
+            .into_iter()
+            .rev()
+            .fold(*rhs.expr, |expr, (name, pattern)| {
+                Expr::MatchWhen(MatchWhen {
+                    expr: Box::new(Expr::Variable(VariableName(name))),
+                    arms: vec![When {
+                        pattern,
+                        guard: None,
+                        expr: Box::new(expr),
+                    }],
+                    otherwise: None,
+                })
+            });
+        DefRhs {
+            args,
+            expr: Box::new(expr),
+        }
+    }
+}
+
+// The number of variants of the enums of the program, by name
+fn enum_variants(program: &Program) -> BTreeMap<String, usize> {
+    program
+        .expr
+        .iter()
+        .filter_map(|expr| match expr {
+            Expr::Enum(def) => Some((def.name.0.clone(), def.variants.len())),
+            _ => None,
+        })
+        .collect()
+}
+
+// Whether a pattern matches every value of its type
+//
+// Variants of enums that are not defined in the program, like `Some`, are taken to be one of
+// several.
+fn irrefutable(variants: &BTreeMap<String, usize>, pattern: &Pattern) -> bool {
+    let single = |path: &TypePath| {
+        let (_, enum_path) = match path.0.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        let name = enum_path
+            .iter()
+            .map(|name| name.0.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+        variants.get(&name) == Some(&1)
+    };
+
+    match pattern {
+        Pattern::Wildcard | Pattern::Variable(_) | Pattern::Literal(Literal::Unit) => true,
+        Pattern::Literal(_) => false,
+        Pattern::Variant { path, members } => {
+            single(path) && members.iter().all(|member| irrefutable(variants, member))
+        }
+        Pattern::Fields { path, fields } => {
+            single(path)
+                && fields
+                    .iter()
+                    .all(|field| irrefutable(variants, &field.pattern))
+        }
+        Pattern::List { elements, rest } => match rest {
+            Some(rest) => elements.is_empty() && irrefutable(variants, rest),
+            None => false,
+        },
+    }
+}
+
+fn comprehension(expr: Expr, qualifiers: &mut Peekable<IntoIter<Qualifier>>) -> Expr {
+    let call = |function: &str, name: &VariableName, body: Expr, list: Expr| {
+        let lambda = Expr::Lambda(DefRhs {
+            args: vec![DefArg {
+                name: VariableName(name.0.clone()),
+                ty: None,
+                pattern: None,
+            }],
+            expr: Box::new(body),
+        });
+        Expr::Apply(
+            Box::new(Expr::Variable(VariableName(function.to_string()))),
+            vec![lambda, list],
+        )
+    };
+
+    match qualifiers.next() {
+        None => Expr::Literal(Literal::List(vec![expr])),
+        Some(Qualifier::Filter(condition)) => Expr::IfElse(IfElse {
+            condition: Box::new(condition),
+            tru: Box::new(comprehension(expr, qualifiers)),
+            fals: Box::new(Expr::Literal(Literal::List(Vec::new()))),
+        }),
+        Some(Qualifier::Generator(name, mut list)) => {
+            let filter = |qualifier: &Qualifier| matches!(qualifier, Qualifier::Filter(_));
+            while let Some(Qualifier::Filter(condition)) = qualifiers.next_if(filter) {
+                list = call("Std.List.filter", &name, condition, list);
+            }
+            if qualifiers.peek().is_none() {
+                call("Std.List.map", &name, expr, list)
+            } else {
+                let body = comprehension(expr, qualifiers);
+                call("Std.List.concatMap", &name, body, list)
+            }
+        }
+    }
+}
+
+fn do_block(block: DoBlock) -> Expr {
+    block
+        .statements
+        .into_iter()
+        .rev()
+        .fold(*block.result, |rest, statement| {
+            let (name, action) = match statement {
+                DoStatement::Bind(name, action) => (name, action),
+                DoStatement::Let(name, expr) => {
+                    return Expr::LetIn(LetIns {
+                        items: vec![LetIn::Def(Def {
+                            lhs: name,
+                            rhs: DefRhs {
+                                args: Vec::new(),
+                                expr: Box::new(expr),
+                            },
+                        })],
+                        expr: Box::new(rest),
+                    })
+                }
+                DoStatement::Run(action) => (VariableName("_".to_string()), action),
+            };
+
+            let continuation = Expr::Lambda(DefRhs {
+                args: vec![DefArg {
+                    name,
+                    ty: None,
+                    pattern: None,
+                }],
+                expr: Box::new(rest),
+            });
+
+            Expr::Apply(
+                Box::new(Expr::Variable(VariableName("Monad.andThen".to_string()))),
+                vec![continuation, action],
+            )
+        })
+}
+
+/// Rewrite an expression bottom up, calling `f` on every subexpression after its own
+/// subexpressions have been rewritten
+fn rewrite(expr: Expr, f: &mut dyn FnMut(Expr) -> Expr) -> Expr {
+    let expr = match expr {
+        Expr::Unary(op, expr) => Expr::Unary(op, Box::new(rewrite(*expr, f))),
+        Expr::Binary(op, lhs, rhs) => {
+            let lhs = Box::new(rewrite(*lhs, f));
+            Expr::Binary(op, lhs, Box::new(rewrite(*rhs, f)))
+        }
+        Expr::Apply(function, args) => {
+            let function = Box::new(rewrite(*function, f));
+            Expr::Apply(
+                function,
+                args.into_iter().map(|arg| rewrite(arg, f)).collect(),
+            )
+        }
+        Expr::Literal(Literal::List(elements)) => Expr::Literal(Literal::List(
+            elements
+                .into_iter()
+                .map(|element| rewrite(element, f))
+                .collect(),
+        )),
+        Expr::Lambda(rhs) => Expr::Lambda(rewrite_rhs(rhs, f)),
+        Expr::LetIn(LetIns { items, expr }) => Expr::LetIn(LetIns {
+            items: rewrite_let(items, f),
+            expr: Box::new(rewrite(*expr, f)),
+        }),
+        Expr::Where(WhereBindings { expr, items }) => Expr::Where(WhereBindings {
+            expr: Box::new(rewrite(*expr, f)),
+            items: rewrite_let(items, f),
+        }),
+        Expr::IfElse(IfElse {
+            condition,
+            tru,
+            fals,
+        }) => {
+            let condition = Box::new(rewrite(*condition, f));
+            let tru = Box::new(rewrite(*tru, f));
+            Expr::IfElse(IfElse {
+                condition,
+                tru,
+                fals: Box::new(rewrite(*fals, f)),
+            })
+        }
+        Expr::MatchWhen(MatchWhen {
+            expr,
+            arms,
+            otherwise,
+        }) => {
+            let expr = Box::new(rewrite(*expr, f));
+            let arms = arms
+                .into_iter()
+                .map(|arm| When {
+                    pattern: arm.pattern,
+                    guard: arm.guard.map(|guard| Box::new(rewrite(*guard, f))),
+                    expr: Box::new(rewrite(*arm.expr, f)),
+                })
+                .collect();
+            Expr::MatchWhen(MatchWhen {
+                expr,
+                arms,
+                otherwise: otherwise.map(|otherwise| Box::new(rewrite(*otherwise, f))),
+            })
+        }
+        Expr::Do(DoBlock { statements, result }) => {
+            let statements = statements
+                .into_iter()
+                .map(|statement| match statement {
+                    DoStatement::Bind(name, expr) => DoStatement::Bind(name, rewrite(expr, f)),
+                    DoStatement::Let(name, expr) => DoStatement::Let(name, rewrite(expr, f)),
+                    DoStatement::Run(expr) => DoStatement::Run(rewrite(expr, f)),
+                })
+                .collect();
+            Expr::Do(DoBlock {
+                statements,
+                result: Box::new(rewrite(*result, f)),
+            })
+        }
+        Expr::Comprehension(Comprehension { expr, qualifiers }) => {
+            let expr = Box::new(rewrite(*expr, f));
+            let qualifiers = qualifiers
+                .into_iter()
+                .map(|qualifier| match qualifier {
+                    Qualifier::Generator(name, list) => {
+                        Qualifier::Generator(name, rewrite(list, f))
+                    }
+                    Qualifier::Filter(condition) => Qualifier::Filter(rewrite(condition, f)),
+                })
+                .collect();
+            Expr::Comprehension(Comprehension { expr, qualifiers })
+        }
+        Expr::Record(Record { fields }) => Expr::Record(Record {
+            fields: fields
+                .into_iter()
+                .map(|field| Field {
+                    name: field.name,
+                    expr: rewrite(field.expr, f),
+                })
+                .collect(),
+        }),
+        Expr::Construct(Construct { path, fields }) => Expr::Construct(Construct {
+            path,
+            fields: fields
+                .into_iter()
+                .map(|field| Field {
+                    name: field.name,
+                    expr: rewrite(field.expr, f),
+                })
+                .collect(),
+        }),
+        Expr::Spread(list) => Expr::Spread(Box::new(rewrite(*list, f))),
+        Expr::Lazy(expr) => Expr::Lazy(Box::new(rewrite(*expr, f))),
+        Expr::Try(expr) => Expr::Try(Box::new(rewrite(*expr, f))),
+        Expr::Ascription(expr, ty) => Expr::Ascription(Box::new(rewrite(*expr, f)), ty),
+        Expr::Def(def) => Expr::Def(rewrite_def(def, f)),
+        Expr::Equation(equation) => Expr::Equation(rewrite_equation(equation, f)),
+        Expr::Trait(TraitDef {
+            name,
+            types,
+            methods,
+            defaults,
+        }) => Expr::Trait(TraitDef {
+            name,
+            types,
+            methods,
+            defaults: defaults
+                .into_iter()
+                .map(|def| rewrite_def(def, f))
+                .collect(),
+        }),
+        Expr::Impl(TraitImpl {
+            trait_name,
+            type_name,
+            types,
+            decls,
+            methods,
+        }) => Expr::Impl(TraitImpl {
+            trait_name,
+            type_name,
+            types,
+            decls,
+            methods: methods.into_iter().map(|def| rewrite_def(def, f)).collect(),
+        }),
+        other @ (Expr::Variable(_)
+        | Expr::FieldSection(_)
+        | Expr::Literal(_)
+        | Expr::Use(_)
+        | Expr::Decl(_)
+        | Expr::Type(_)
+        | Expr::Enum(_)
+        | Expr::Effect(_)
+        | Expr::PatternSynonym(_)) => other,
+    };
+
+    f(expr)
+}
+
+fn rewrite_let(items: Vec<LetIn>, f: &mut dyn FnMut(Expr) -> Expr) -> Vec<LetIn> {
+    items
+        .into_iter()
+        .map(|item| match item {
+            LetIn::Def(def) => LetIn::Def(rewrite_def(def, f)),
+            LetIn::Equation(equation) => LetIn::Equation(rewrite_equation(equation, f)),
+            LetIn::Decl(decl) => LetIn::Decl(decl),
+            LetIn::Pattern(binding) => LetIn::Pattern(PatternBinding {
+                expr: Box::new(rewrite(*binding.expr, f)),
+                ..binding
+            }),
+        })
+        .collect()
+}
+
+fn rewrite_equation(equation: Equation, f: &mut dyn FnMut(Expr) -> Expr) -> Equation {
+    Equation {
+        expr: Box::new(rewrite(*equation.expr, f)),
+        ..equation
+    }
+}
+
+fn rewrite_def(def: Def, f: &mut dyn FnMut(Expr) -> Expr) -> Def {
+    Def {
+        lhs: def.lhs,
+        rhs: rewrite_rhs(def.rhs, f),
+    }
+}
+
+fn rewrite_rhs(rhs: DefRhs, f: &mut dyn FnMut(Expr) -> Expr) -> DefRhs {
+    DefRhs {
+        args: rhs.args,
+        expr: Box::new(rewrite(*rhs.expr, f)),
+    }
+}
+
+type Hoisted = Vec<(VariableName, Expr)>;
+
+struct TryDesugarer {
+    next: usize,
+}
+
+impl TryDesugarer {
+    fn fresh(&mut self) -> VariableName {
+        let name = VariableName(format!("try?{}", self.next));
+        self.next += 1;
+        name
+    }
+
+    fn def(&mut self, def: Def) -> Result<Def, DesugarError> {
+        let Def { lhs, rhs } = def;
+        let DefRhs { args, expr } = rhs;
+
+        let expr = if args.is_empty() {
+            self.strict(*expr, None)?
+        } else {
+            self.tail(*expr)?
+        };
+
+        Ok(Def {
+            lhs,
+            rhs: DefRhs {
+                args,
+                expr: Box::new(expr),
+            },
+        })
+    }
+
+    fn equation(&mut self, equation: Equation) -> Result<Equation, DesugarError> {
+        Ok(Equation {
+            expr: Box::new(self.tail(*equation.expr)?),
+            ..equation
+        })
+    }
+
+    // Desugar an expression whose value is the result of the function
+    fn tail(&mut self, expr: Expr) -> Result<Expr, DesugarError> {
+        let mut hoisted = Hoisted::new();
+
+        let expr = match expr {
+            Expr::IfElse(IfElse {
+                condition,
+                tru,
+                fals,
+            }) => Expr::IfElse(IfElse {
+                condition: Box::new(self.strict(*condition, Some(&mut hoisted))?),
+                tru: Box::new(self.tail(*tru)?),
+                fals: Box::new(self.tail(*fals)?),
+            }),
+
+            Expr::MatchWhen(MatchWhen {
+                expr,
+                arms,
+                otherwise,
+            }) => Expr::MatchWhen(MatchWhen {
+                expr: Box::new(self.strict(*expr, Some(&mut hoisted))?),
+                arms: arms
+                    .into_iter()
+                    .map(|arm| {
+                        Ok::<_, DesugarError>(When {
+                            pattern: arm.pattern,
+                            guard: self.guard(arm.guard)?,
+                            expr: Box::new(self.tail(*arm.expr)?),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+                otherwise: otherwise
+                    .map(|otherwise| self.tail(*otherwise).map(Box::new))
+                    .transpose()?,
+            }),
+
+            Expr::LetIn(LetIns { items, expr }) => return self.tail_let(items, *expr),
+
+            other => self.strict(other, Some(&mut hoisted))?,
+        };
+
+        Ok(wrap(hoisted, expr))
+    }
+
+    // A `let` whose body is the result of the function
+    //
+    // The bindings are split at the first binding that uses '?', so that the bindings before it
+    // are in scope for the hoisted expressions, and the rest of the bindings are only evaluated if
+    // the hoisted expressions did not fail.
+    fn tail_let(&mut self, items: Vec<LetIn>, body: Expr) -> Result<Expr, DesugarError> {
+        let split = items.iter().position(|item| match item {
+            LetIn::Def(def) => def.rhs.args.is_empty() && contains_try(&def.rhs.expr),
+            LetIn::Decl(_) | LetIn::Equation(_) | LetIn::Pattern(_) => false,
+        });
+
+        let mut items = items;
+        let (split_def, rest) = match split {
+            Some(split) => {
+                let rest = items.split_off(split + 1);
+                match items.pop() {
+                    Some(LetIn::Def(def)) => (Some(def), rest),
+                    _ => unreachable!(),
+                }
+            }
+            None => (None, Vec::new()),
+        };
+
+        let split_def = match split_def {
+            Some(def) => def,
+            None => {
+                return Ok(Expr::LetIn(LetIns {
+                    items: self.let_items(items)?,
+                    expr: Box::new(self.tail(body)?),
+                }))
+            }
+        };
+
+        // The declaration of the split binding moves with it
+        let (split_decls, before): (Vec<LetIn>, Vec<LetIn>) =
+            items.into_iter().partition(|item| match item {
+                LetIn::Decl(Decl { lhs, .. }) => lhs.0 == split_def.lhs.0,
+                LetIn::Def(_) | LetIn::Equation(_) | LetIn::Pattern(_) => false,
+            });
+
+        let rest = if rest.is_empty() {
+            self.tail(body)?
+        } else {
+            self.tail_let(rest, body)?
+        };
+
+        let mut hoisted = Hoisted::new();
+        let value = self.strict(*split_def.rhs.expr, Some(&mut hoisted))?;
+
+        let mut split_items = split_decls;
+        split_items.push(LetIn::Def(Def {
+            lhs: split_def.lhs,
+            rhs: DefRhs {
+                args: split_def.rhs.args,
+                expr: Box::new(value),
+            },
+        }));
+
+        let split = wrap(
+            hoisted,
+            Expr::LetIn(LetIns {
+                items: split_items,
+                expr: Box::new(rest),
+            }),
+        );
+
+        if before.is_empty() {
+            Ok(split)
+        } else {
+            Ok(Expr::LetIn(LetIns {
+                items: self.let_items(before)?,
+                expr: Box::new(split),
+            }))
+        }
+    }
+
+    fn defs(&mut self, defs: Vec<Def>) -> Result<Vec<Def>, DesugarError> {
+        defs.into_iter().map(|def| self.def(def)).collect()
+    }
+
+    // A guard decides which arm is taken, not the result of the function, so it cannot use '?'
+    fn guard(&mut self, guard: Option<Box<Expr>>) -> Result<Option<Box<Expr>>, DesugarError> {
+        guard
+            .map(|guard| self.strict(*guard, None).map(Box::new))
+            .transpose()
+    }
+
+    fn let_items(&mut self, items: Vec<LetIn>) -> Result<Vec<LetIn>, DesugarError> {
+        items
+            .into_iter()
+            .map(|item| match item {
+                LetIn::Def(def) => self.def(def).map(LetIn::Def),
+                LetIn::Equation(equation) => self.equation(equation).map(LetIn::Equation),
+                LetIn::Decl(decl) => Ok(LetIn::Decl(decl)),
+                LetIn::Pattern(binding) => Ok(LetIn::Pattern(PatternBinding {
+                    expr: Box::new(self.strict(*binding.expr, None)?),
+                    ..binding
+                })),
+            })
+            .collect()
+    }
+
+    // Desugar an expression that is evaluated before the result of the function is known
+    //
+    // If `hoisted` is given, uses of '?' are replaced by fresh variables and the expressions are
+    // moved into `hoisted`, otherwise using '?' is an error.
+    fn strict(
+        &mut self,
+        expr: Expr,
+        mut hoisted: Option<&mut Hoisted>,
+    ) -> Result<Expr, DesugarError> {
+        let expr = match expr {
+            Expr::Try(inner) => {
+                let inner = self.strict(*inner, hoisted.as_deref_mut())?;
+                let hoisted = hoisted.ok_or(DesugarError::TryNotAllowed)?;
+                let name = self.fresh();
+                hoisted.push((VariableName(name.0.clone()), inner));
+                Expr::Variable(name)
+            }
+
+            Expr::Unary(op, expr) => {
+                Expr::Unary(op, Box::new(self.strict(*expr, hoisted.as_deref_mut())?))
+            }
+
+            Expr::Spread(list) => {
+                Expr::Spread(Box::new(self.strict(*list, hoisted.as_deref_mut())?))
+            }
+
+            Expr::Ascription(expr, ty) => {
+                Expr::Ascription(Box::new(self.strict(*expr, hoisted.as_deref_mut())?), ty)
+            }
+
+            Expr::Record(Record { fields }) => Expr::Record(Record {
+                fields: self.fields(fields, hoisted.as_deref_mut())?,
+            }),
+
+            Expr::Construct(Construct { path, fields }) => Expr::Construct(Construct {
+                path,
+                fields: self.fields(fields, hoisted.as_deref_mut())?,
+            }),
+
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.strict(*lhs, hoisted.as_deref_mut())?;
+                let rhs = self.strict(*rhs, hoisted.as_deref_mut())?;
+                Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+            }
+
+            Expr::Apply(function, args) => {
+                let function = self.strict(*function, hoisted.as_deref_mut())?;
+                let args = args
+                    .into_iter()
+                    .map(|arg| self.strict(arg, hoisted.as_deref_mut()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Expr::Apply(Box::new(function), args)
+            }
+
+            Expr::Literal(Literal::List(elements)) => Expr::Literal(Literal::List(
+                elements
+                    .into_iter()
+                    .map(|element| self.strict(element, hoisted.as_deref_mut()))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+
+            Expr::IfElse(IfElse {
+                condition,
+                tru,
+                fals,
+            }) => Expr::IfElse(IfElse {
+                condition: Box::new(self.strict(*condition, hoisted.as_deref_mut())?),
+                tru: Box::new(self.strict(*tru, None)?),
+                fals: Box::new(self.strict(*fals, None)?),
+            }),
+
+            Expr::MatchWhen(MatchWhen {
+                expr,
+                arms,
+                otherwise,
+            }) => Expr::MatchWhen(MatchWhen {
+                expr: Box::new(self.strict(*expr, hoisted)?),
+                arms: arms
+                    .into_iter()
+                    .map(|arm| {
+                        Ok::<_, DesugarError>(When {
+                            pattern: arm.pattern,
+                            guard: self.guard(arm.guard)?,
+                            expr: Box::new(self.strict(*arm.expr, None)?),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+                otherwise: otherwise
+                    .map(|otherwise| self.strict(*otherwise, None).map(Box::new))
+                    .transpose()?,
+            }),
+
+            Expr::LetIn(LetIns { items, expr }) => Expr::LetIn(LetIns {
+                items: self.let_items(items)?,
+                expr: Box::new(self.strict(*expr, None)?),
+            }),
+            Expr::Where(WhereBindings { expr, items }) => Expr::Where(WhereBindings {
+                expr: Box::new(self.strict(*expr, None)?),
+                items: self.let_items(items)?,
+            }),
+
+            Expr::Lazy(expr) => Expr::Lazy(Box::new(self.strict(*expr, None)?)),
+
+            // A function of its own
+            Expr::Def(def) => Expr::Def(self.def(def)?),
+            Expr::Equation(equation) => Expr::Equation(self.equation(equation)?),
+            Expr::Trait(TraitDef {
+                name,
+                types,
+                methods,
+                defaults,
+            }) => Expr::Trait(TraitDef {
+                name,
+                types,
+                methods,
+                defaults: self.defs(defaults)?,
+            }),
+            Expr::Impl(TraitImpl {
+                trait_name,
+                type_name,
+                types,
+                decls,
+                methods,
+            }) => Expr::Impl(TraitImpl {
+                trait_name,
+                type_name,
+                types,
+                decls,
+                methods: self.defs(methods)?,
+            }),
+            Expr::Lambda(DefRhs { args, expr }) => Expr::Lambda(DefRhs {
+                args,
+                expr: Box::new(self.tail(*expr)?),
+            }),
+
+            Expr::Do(DoBlock { statements, result }) => Expr::Do(DoBlock {
+                statements: statements
+                    .into_iter()
+                    .map(|statement| match statement {
+                        DoStatement::Bind(name, expr) => self
+                            .strict(expr, None)
+                            .map(|expr| DoStatement::Bind(name, expr)),
+                        DoStatement::Let(name, expr) => self
+                            .strict(expr, None)
+                            .map(|expr| DoStatement::Let(name, expr)),
+                        DoStatement::Run(expr) => self.strict(expr, None).map(DoStatement::Run),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+                result: Box::new(self.strict(*result, None)?),
+            }),
+
+            Expr::Comprehension(Comprehension { expr, qualifiers }) => {
+                Expr::Comprehension(Comprehension {
+                    expr: Box::new(self.strict(*expr, None)?),
+                    qualifiers: qualifiers
+                        .into_iter()
+                        .map(|qualifier| match qualifier {
+                            Qualifier::Generator(name, list) => self
+                                .strict(list, None)
+                                .map(|list| Qualifier::Generator(name, list)),
+                            Qualifier::Filter(condition) => {
+                                self.strict(condition, None).map(Qualifier::Filter)
+                            }
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                })
+            }
+
+            other @ (Expr::Variable(_)
+            | Expr::FieldSection(_)
+            | Expr::Literal(_)
+            | Expr::Use(_)
+            | Expr::Decl(_)
+            | Expr::Type(_)
+            | Expr::Enum(_)
+            | Expr::Effect(_)
+            | Expr::PatternSynonym(_)) => other,
+        };
+
+        Ok(expr)
+    }
+
+    fn fields(
+        &mut self,
+        fields: Vec<Field>,
+        mut hoisted: Option<&mut Hoisted>,
+    ) -> Result<Vec<Field>, DesugarError> {
+        fields
+            .into_iter()
+            .map(|field| {
+                let expr = self.strict(field.expr, hoisted.as_deref_mut())?;
+                Ok(Field {
+                    name: field.name,
+                    expr,
+                })
+            })
+            .collect()
+    }
+}
+
+// Whether an expression uses '?' in a position that is evaluated strictly
+fn contains_try(expr: &Expr) -> bool {
+    match expr {
+        Expr::Try(_) => true,
+        Expr::Unary(_, expr) | Expr::Spread(expr) | Expr::Ascription(expr, _) => contains_try(expr),
+        Expr::Binary(_, lhs, rhs) => contains_try(lhs) || contains_try(rhs),
+        Expr::Apply(function, args) => contains_try(function) || args.iter().any(contains_try),
+        Expr::Literal(Literal::List(elements)) => elements.iter().any(contains_try),
+        Expr::Record(Record { fields }) | Expr::Construct(Construct { fields, .. }) => {
+            fields.iter().any(|field| contains_try(&field.expr))
+        }
+        Expr::IfElse(ifelse) => contains_try(&ifelse.condition),
+        Expr::MatchWhen(matchwhen) => contains_try(&matchwhen.expr),
+        _ => false,
+    }
+}
+
+fn variant_pattern(name: &str, binding: VariableName) -> Pattern {
+    Pattern::Variant {
+        path: TypePath(vec![TypeName(name.to_string())]),
+        members: vec![Pattern::Variable(binding)],
+    }
+}
+
+fn wrap(hoisted: Hoisted, expr: Expr) -> Expr {
+    hoisted.into_iter().rev().fold(expr, |expr, (name, tried)| {
+        let reraise = Expr::Apply(
+            Box::new(Expr::Variable(VariableName("Err".to_string()))),
+            vec![Expr::Variable(VariableName(ERR_NAME.to_string()))],
+        );
+
+        Expr::MatchWhen(MatchWhen {
+            expr: Box::new(tried),
+            arms: vec![
+                When {
+                    pattern: variant_pattern("Ok", name),
+                    guard: None,
+                    expr: Box::new(expr),
+                },
+                When {
+                    pattern: variant_pattern("Err", VariableName(ERR_NAME.to_string())),
+                    guard: None,
+                    expr: Box::new(reraise),
+                },
+            ],
+            otherwise: None,
+        })
+    })
+}
@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Checking the effects that function types name
+//!
+//! An effect declares the operations that code can perform, and a function type names the
+//! effects that calling the function may perform:
+//!
+//! ```text
+//! effect Log =
+//!     { log: (String) -> ()
+//!     }
+//!
+//! greet: (String) -> () with Log, Console
+//! ```
+//!
+//! The effects have to be declared in the program, or be one of the [`DEFAULT_EFFECTS`], which
+//! the interpreter has default handlers for.
+
+use std::collections::BTreeSet;
+
+use crate::ast::decl::Decl;
+use crate::ast::decl::DeclType;
+use crate::ast::expr::Expr;
+use crate::ast::program::Program;
+
+/// The effects the interpreter handles by default, as `vunk_runtime::effect::DEFAULTS` does
+pub const DEFAULT_EFFECTS: &[&str] = &["Console", "Files"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum EffectError {
+    #[error("The type of '{function}' names the effect {effect}, which is not declared")]
+    Unknown { function: String, effect: String },
+
+    #[error("The effect {effect} declares '{operation}' more than once")]
+    DuplicateOperation { effect: String, operation: String },
+}
+
+/// Check that the effects in the function types of the program are declared, and that effects
+/// declare each of their operations once
+pub fn check(program: &Program) -> Result<(), EffectError> {
+    let mut effects = DEFAULT_EFFECTS.iter().copied().collect::<BTreeSet<_>>();
+    for expr in program.expr.iter() {
+        if let Expr::Effect(def) = expr {
+            let mut operations = BTreeSet::new();
+            for operation in def.operations.iter() {
+                if !operations.insert(operation.lhs.0.as_str()) {
+                    return Err(EffectError::DuplicateOperation {
+                        effect: def.name.0.clone(),
+                        operation: operation.lhs.0.clone(),
+                    });
+                }
+            }
+            effects.insert(def.name.0.as_str());
+        }
+    }
+
+    let decls = program.expr.iter().flat_map(|expr| match expr {
+        Expr::Decl(decl) => std::slice::from_ref(decl),
+        Expr::Trait(def) => def.methods.as_slice(),
+        Expr::Impl(def) => def.decls.as_slice(),
+        Expr::Effect(def) => def.operations.as_slice(),
+        _ => &[],
+    });
+    for decl in decls {
+        check_decl(&effects, decl, &decl.rhs)?;
+    }
+    Ok(())
+}
+
+fn check_decl(effects: &BTreeSet<&str>, decl: &Decl, ty: &DeclType) -> Result<(), EffectError> {
+    let (args, named) = match ty {
+        DeclType::TypeName(_) => return Ok(()),
+        DeclType::Func { args, effects, .. } => (args, effects),
+    };
+    if let Some(effect) = named
+        .iter()
+        .find(|effect| !effects.contains(effect.0.as_str()))
+    {
+        return Err(EffectError::Unknown {
+            function: decl.lhs.0.clone(),
+            effect: effect.0.clone(),
+        });
+    }
+    args.iter()
+        .try_for_each(|arg| check_decl(effects, decl, &arg.ty))
+}
@@ -0,0 +1,543 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Generating random ASTs for property tests, like `parse(print(ast)) == ast`
+//!
+//! Unlike the ASTs of the `arbitrary` feature, which may contain anything the types allow, the
+//! generated ASTs are those the parser can produce, and that have a single way to print them:
+//!
+//! - names are identifiers the lexer does not take for keywords
+//! - integers are non-negative and stored as `I64`, like their literals are
+//! - applications have arguments and apply a name, so they are not nested
+//! - definitions of functions are definitions with arguments, not of a lambda
+//! - there are no unary operators, as the parser reads none but the `-` of a number
+//!
+//! Generating is deterministic, so a seed that fails keeps failing until it is fixed.
+
+use crate::ast::attribute::Attribute;
+use crate::ast::attribute::Meta;
+use crate::ast::attribute::MetaValue;
+use crate::ast::comprehension::Comprehension;
+use crate::ast::comprehension::Qualifier;
+use crate::ast::decl::Decl;
+use crate::ast::decl::DeclArg;
+use crate::ast::decl::DeclType;
+use crate::ast::def::Def;
+use crate::ast::def::DefArg;
+use crate::ast::def::DefArgType;
+use crate::ast::def::DefRhs;
+use crate::ast::def::Equation;
+use crate::ast::def::TypeDef;
+use crate::ast::doblock::DoBlock;
+use crate::ast::doblock::DoStatement;
+use crate::ast::expr::Expr;
+use crate::ast::generic::Generic;
+use crate::ast::generic::WhereClause;
+use crate::ast::ifelse::IfElse;
+use crate::ast::import::Import;
+use crate::ast::letin::LetIn;
+use crate::ast::letin::LetIns;
+use crate::ast::literal::Bool;
+use crate::ast::literal::Float;
+use crate::ast::literal::Integer;
+use crate::ast::literal::IntegerValue;
+use crate::ast::literal::Literal;
+use crate::ast::literal::Str;
+use crate::ast::matchwhen::MatchWhen;
+use crate::ast::matchwhen::When;
+use crate::ast::name::TraitName;
+use crate::ast::name::TypeName;
+use crate::ast::name::TypePath;
+use crate::ast::name::VariableName;
+use crate::ast::op::BinaryOp;
+use crate::ast::pattern::FieldPattern;
+use crate::ast::pattern::Pattern;
+use crate::ast::program::Program;
+use crate::ast::record::Construct;
+use crate::ast::record::Field;
+use crate::ast::record::Record;
+
+const VARIABLES: &[&str] = &["a", "b", "x", "y", "value", "count", "name", "total"];
+const TYPES: &[&str] = &["U64", "String", "Bool", "Person", "Age"];
+const VARIANTS: &[&[&str]] = &[&["Ok"], &["Err"], &["Age", "Value"], &["Age", "Unknown"]];
+const PARAMS: &[&str] = &["A", "B", "T"];
+const TRAITS: &[&str] = &["Show", "Eq", "Std.Ord"];
+const MODULES: &[&[&str]] = &[&["Std"], &["Std", "List"], &["app", "config"]];
+const INTEGERS: &[i64] = &[0, 1, 2, 7, 255, 1 << 32, i64::MAX];
+const FLOATS: &[f64] = &[0.0, 0.25, 1.5, 1e300];
+const ATTRIBUTES: &[&str] = &["inline", "deprecated", "derive", "cfg"];
+
+/// A program of uses, types, declarations, definitions and equations, with expressions nested
+/// at most `depth` deep
+pub fn program(seed: u64, depth: usize) -> Program {
+    let mut generator = Generator::new(seed);
+    let items = 1 + generator.below(4);
+    Program {
+        expr: (0..items)
+            .map(|_| match generator.below(8) {
+                0 => Expr::Decl(generator.decl()),
+                1 => Expr::Equation(generator.equation(depth)),
+                2 => Expr::Use(generator.import()),
+                3 => Expr::Type(generator.type_def()),
+                _ => Expr::Def(generator.def(depth)),
+            })
+            .collect(),
+    }
+}
+
+/// An expression nested at most `depth` deep
+pub fn expr(seed: u64, depth: usize) -> Expr {
+    Generator::new(seed).expr(depth)
+}
+
+/// An expression of numbers, booleans, operators and `if`s nested at most `depth` deep, which the
+/// runtime can evaluate without a program around it
+///
+/// The operands are of mixed types and include the largest `Int`, so that overflows, divisions by
+/// zero and operands of the wrong type are generated too.
+pub fn constant(seed: u64, depth: usize) -> Expr {
+    Generator::new(seed).constant(depth)
+}
+
+// A xorshift random number generator, good enough for picking and free of dependencies
+struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    fn new(seed: u64) -> Generator {
+        // Xorshift gets stuck at 0, and similar seeds should not start similarly
+        Generator {
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<T: Clone>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())].clone()
+    }
+
+    fn many<T>(&mut self, min: usize, max: usize, mut f: impl FnMut(&mut Self) -> T) -> Vec<T> {
+        let count = min + self.below(max - min + 1);
+        (0..count).map(|_| f(self)).collect()
+    }
+
+    fn variable(&mut self) -> VariableName {
+        VariableName(self.pick(VARIABLES).to_string())
+    }
+
+    fn type_name(&mut self) -> TypeName {
+        TypeName(self.pick(TYPES).to_string())
+    }
+
+    fn def(&mut self, depth: usize) -> Def {
+        let args = if self.below(2) == 0 {
+            Vec::new()
+        } else {
+            self.many(1, 3, Self::def_arg)
+        };
+        let mut expr = self.expr(depth);
+        if args.is_empty() {
+            // `f = (x) -> x` is a definition with arguments
+            while matches!(expr, Expr::Lambda(_)) {
+                expr = self.expr(depth);
+            }
+        }
+
+        Def {
+            lhs: self.variable(),
+            rhs: DefRhs {
+                args,
+                expr: Box::new(expr),
+            },
+        }
+    }
+
+    fn equation(&mut self, depth: usize) -> Equation {
+        Equation {
+            lhs: self.variable(),
+            params: self.many(1, 3, |generator| generator.pattern(1)),
+            expr: Box::new(self.expr(depth)),
+        }
+    }
+
+    fn import(&mut self) -> Import {
+        Import {
+            path: self
+                .pick(MODULES)
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            alias: (self.below(3) == 0).then(|| "m".to_string()),
+            glob: self.below(3) == 0,
+        }
+    }
+
+    fn type_def(&mut self) -> TypeDef {
+        TypeDef {
+            name: self.type_name(),
+            params: self.many(0, 2, |generator| {
+                TypeName(generator.pick(PARAMS).to_string())
+            }),
+            members: self.many(0, 3, |generator| DefArg {
+                name: generator.variable(),
+                ty: Some(DefArgType::TypeName(generator.type_name())),
+                pattern: None,
+            }),
+            generics: self.where_clause(),
+        }
+    }
+
+    fn where_clause(&mut self) -> Option<WhereClause> {
+        if self.below(3) != 0 {
+            return None;
+        }
+        let bounds = self.many(1, 2, |generator| Generic {
+            type_name: TypeName(generator.pick(PARAMS).to_string()),
+            trait_name: TraitName(generator.pick(TRAITS).to_string()),
+        });
+        Some(WhereClause(bounds))
+    }
+
+    fn def_arg(&mut self) -> DefArg {
+        let ty = match self.below(4) {
+            0 => None,
+            1 => Some(DefArgType::Func {
+                args: vec![DefArg {
+                    name: self.variable(),
+                    ty: Some(DefArgType::TypeName(self.type_name())),
+                    pattern: None,
+                }],
+                retty: self.type_name(),
+            }),
+            _ => Some(DefArgType::TypeName(self.type_name())),
+        };
+
+        if self.below(4) == 0 {
+            DefArg {
+                name: VariableName("_".to_string()),
+                ty,
+                pattern: Some(self.destructuring()),
+            }
+        } else {
+            DefArg {
+                name: self.variable(),
+                ty,
+                pattern: None,
+            }
+        }
+    }
+
+    // A pattern of a parameter, which is not a name, as `(x) -> x` and `(_) -> 1` have one
+    fn destructuring(&mut self) -> Pattern {
+        loop {
+            match self.pattern(1) {
+                Pattern::Variable(_) | Pattern::Wildcard => continue,
+                pattern => return pattern,
+            }
+        }
+    }
+
+    fn decl(&mut self) -> Decl {
+        let rhs = if self.below(2) == 0 {
+            DeclType::TypeName(self.type_name())
+        } else {
+            DeclType::Func {
+                args: self.many(0, 3, |generator| DeclArg {
+                    name: (generator.below(2) == 0).then(|| generator.variable()),
+                    ty: DeclType::TypeName(generator.type_name()),
+                }),
+                retty: self.type_name(),
+                effects: self.many(0, 2, Self::type_name),
+            }
+        };
+
+        Decl {
+            attributes: self.many(0, 2, Self::attribute),
+            lhs: self.variable(),
+            rhs,
+            whereclause: self.where_clause(),
+        }
+    }
+
+    fn attribute(&mut self) -> Attribute {
+        let name = self.pick(ATTRIBUTES).to_string();
+        let args = self.many(0, 2, |generator| generator.meta(1));
+        Attribute { name, args }
+    }
+
+    fn meta(&mut self, depth: usize) -> Meta {
+        let value = match self.below(if depth == 0 { 3 } else { 4 }) {
+            0 => return Meta::Str(self.pick(VARIABLES).to_string()),
+            1 => None,
+            2 => Some(MetaValue::Str(self.pick(VARIABLES).to_string())),
+            _ => Some(MetaValue::List(
+                self.many(1, 2, |generator| generator.meta(depth - 1)),
+            )),
+        };
+        Meta::Named {
+            name: self.pick(VARIABLES).to_string(),
+            value,
+        }
+    }
+
+    fn expr(&mut self, depth: usize) -> Expr {
+        if depth == 0 {
+            return match self.below(2) {
+                0 => Expr::Variable(self.variable()),
+                _ => Expr::Literal(self.literal(0)),
+            };
+        }
+
+        let depth = depth - 1;
+        let boxed = |generator: &mut Self| Box::new(generator.expr(depth));
+        match self.below(16) {
+            0 => Expr::Variable(self.variable()),
+            1 => Expr::Literal(self.literal(depth)),
+            2 => Expr::Binary(self.operator(), boxed(self), boxed(self)),
+            3 => Expr::Apply(
+                Box::new(Expr::Variable(self.variable())),
+                self.many(1, 3, |generator| generator.expr(depth)),
+            ),
+            4 => Expr::Lambda(DefRhs {
+                args: self.many(0, 2, Self::def_arg),
+                expr: boxed(self),
+            }),
+            5 => Expr::LetIn(LetIns {
+                items: self.many(1, 3, |generator| match generator.below(4) {
+                    0 => LetIn::Decl(generator.decl()),
+                    1 => LetIn::Equation(generator.equation(depth)),
+                    _ => LetIn::Def(generator.def(depth)),
+                }),
+                expr: boxed(self),
+            }),
+            6 => Expr::IfElse(IfElse {
+                condition: boxed(self),
+                tru: boxed(self),
+                fals: boxed(self),
+            }),
+            7 => Expr::MatchWhen(MatchWhen {
+                expr: boxed(self),
+                arms: self.many(1, 3, |generator| When {
+                    pattern: generator.pattern(2),
+                    guard: (generator.below(3) == 0).then(|| boxed(generator)),
+                    expr: boxed(generator),
+                }),
+                otherwise: (self.below(2) == 0).then(|| boxed(self)),
+            }),
+            8 => Expr::Do(DoBlock {
+                statements: self.many(0, 3, |generator| match generator.below(3) {
+                    0 => DoStatement::Bind(generator.variable(), generator.expr(depth)),
+                    1 => DoStatement::Let(generator.variable(), generator.expr(depth)),
+                    _ => DoStatement::Run(generator.expr(depth)),
+                }),
+                result: boxed(self),
+            }),
+            9 => Expr::Lazy(boxed(self)),
+            10 => Expr::Try(boxed(self)),
+            11 => Expr::Comprehension(Comprehension {
+                expr: boxed(self),
+                qualifiers: self.many(1, 3, |generator| match generator.below(2) {
+                    0 => Qualifier::Generator(generator.variable(), generator.expr(depth)),
+                    _ => Qualifier::Filter(generator.expr(depth)),
+                }),
+            }),
+            12 => Expr::Record(Record {
+                fields: self.many(0, 3, |generator| Field {
+                    name: generator.variable(),
+                    expr: generator.expr(depth),
+                }),
+            }),
+            13 => Expr::Construct(Construct {
+                path: self.path(),
+                fields: self.many(0, 3, |generator| Field {
+                    name: generator.variable(),
+                    expr: generator.expr(depth),
+                }),
+            }),
+            14 => Expr::Ascription(boxed(self), DeclType::TypeName(self.type_name())),
+            _ => {
+                let elements = self.many(0, 3, |generator| generator.element(depth));
+                Expr::Literal(Literal::List(elements))
+            }
+        }
+    }
+
+    // An element of a list literal, which may spread another list
+    fn element(&mut self, depth: usize) -> Expr {
+        match self.below(4) {
+            0 => Expr::Spread(Box::new(self.expr(depth))),
+            _ => self.expr(depth),
+        }
+    }
+
+    fn literal(&mut self, depth: usize) -> Literal {
+        match self.below(if depth == 0 { 5 } else { 6 }) {
+            0 => Literal::Bool(Bool {
+                value: self.below(2) == 0,
+            }),
+            1 => Literal::Integer(Integer {
+                value: IntegerValue::I64(self.below(1000) as i64),
+            }),
+            2 => Literal::Float(Float {
+                // Quarters are exact in binary, so printing them loses nothing
+                value: self.below(1000) as f64 + 0.25 * (1 + self.below(3)) as f64,
+            }),
+            3 => Literal::Str(Str {
+                value: self
+                    .many(0, 3, Self::variable)
+                    .into_iter()
+                    .map(|name| name.0)
+                    .collect(),
+            }),
+            4 => Literal::Unit,
+            _ => Literal::List(self.many(0, 3, |generator| generator.element(depth - 1))),
+        }
+    }
+
+    fn pattern(&mut self, depth: usize) -> Pattern {
+        match self.below(if depth == 0 { 4 } else { 7 }) {
+            0 => Pattern::Wildcard,
+            1 => Pattern::Variable(self.variable()),
+            // Without lists
+            2 => Pattern::Literal(self.literal(0)),
+            3 => Pattern::Variant {
+                path: self.path(),
+                members: Vec::new(),
+            },
+            4 => Pattern::Variant {
+                path: self.path(),
+                members: self.many(1, 2, |generator| generator.pattern(depth - 1)),
+            },
+            5 => Pattern::Fields {
+                path: self.path(),
+                fields: self.many(1, 2, |generator| FieldPattern {
+                    name: generator.variable(),
+                    pattern: generator.pattern(depth - 1),
+                }),
+            },
+            _ => Pattern::List {
+                elements: self.many(0, 2, |generator| generator.pattern(depth - 1)),
+                rest: (self.below(2) == 0).then(|| Box::new(self.pattern(depth - 1))),
+            },
+        }
+    }
+
+    fn constant(&mut self, depth: usize) -> Expr {
+        let ty = self.constant_type();
+        self.typed_constant(ty, depth)
+    }
+
+    fn constant_type(&mut self) -> ConstantType {
+        self.pick(&[ConstantType::Bool, ConstantType::Int, ConstantType::Float])
+    }
+
+    // An expression of the type, but with an operand of another type now and then
+    fn typed_constant(&mut self, ty: ConstantType, depth: usize) -> Expr {
+        if depth == 0 || self.below(4) == 0 {
+            return Expr::Literal(self.typed_literal(ty));
+        }
+        let operand = |generator: &mut Self, ty| {
+            let ty = if generator.below(16) == 0 {
+                generator.constant_type()
+            } else {
+                ty
+            };
+            Box::new(generator.typed_constant(ty, depth - 1))
+        };
+
+        if self.below(5) == 0 {
+            return Expr::IfElse(IfElse {
+                condition: operand(self, ConstantType::Bool),
+                tru: operand(self, ty),
+                fals: operand(self, ty),
+            });
+        }
+        let (op, operands) = match ty {
+            ConstantType::Int | ConstantType::Float => (self.arithmetic(), ty),
+            ConstantType::Bool => (self.comparison(), self.constant_type()),
+        };
+        Expr::Binary(op, operand(self, operands), operand(self, operands))
+    }
+
+    fn typed_literal(&mut self, ty: ConstantType) -> Literal {
+        match ty {
+            ConstantType::Bool => Literal::Bool(Bool {
+                value: self.below(2) == 0,
+            }),
+            ConstantType::Int => Literal::Integer(Integer {
+                value: IntegerValue::I64(self.pick(INTEGERS)),
+            }),
+            ConstantType::Float => Literal::Float(Float {
+                value: self.pick(FLOATS),
+            }),
+        }
+    }
+
+    fn arithmetic(&mut self) -> BinaryOp {
+        match self.below(5) {
+            0 => BinaryOp::Add,
+            1 => BinaryOp::Sub,
+            2 => BinaryOp::Mul,
+            3 => BinaryOp::Div,
+            _ => BinaryOp::Rem,
+        }
+    }
+
+    fn comparison(&mut self) -> BinaryOp {
+        match self.below(6) {
+            0 => BinaryOp::Eq,
+            1 => BinaryOp::NotEq,
+            2 => BinaryOp::Less,
+            3 => BinaryOp::LessEq,
+            4 => BinaryOp::More,
+            _ => BinaryOp::MoreEq,
+        }
+    }
+
+    fn operator(&mut self) -> BinaryOp {
+        match self.below(17) {
+            0 => BinaryOp::Add,
+            1 => BinaryOp::Sub,
+            2 => BinaryOp::Mul,
+            3 => BinaryOp::Div,
+            4 => BinaryOp::Rem,
+            5 => BinaryOp::Eq,
+            6 => BinaryOp::NotEq,
+            7 => BinaryOp::Less,
+            8 => BinaryOp::LessEq,
+            9 => BinaryOp::More,
+            10 => BinaryOp::MoreEq,
+            11 => BinaryOp::BitAnd,
+            12 => BinaryOp::LogicalAnd,
+            13 => BinaryOp::BitOr,
+            14 => BinaryOp::LogicalOr,
+            15 => BinaryOp::BitXor,
+            _ => BinaryOp::Join,
+        }
+    }
+
+    fn path(&mut self) -> TypePath {
+        let path = self.pick(VARIANTS);
+        TypePath(path.iter().map(|name| TypeName(name.to_string())).collect())
+    }
+}
+
+// The types of the constants that are generated
+#[derive(Clone, Copy)]
+enum ConstantType {
+    Bool,
+    Int,
+    Float,
+}
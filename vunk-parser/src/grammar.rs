@@ -0,0 +1,535 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The grammar of vunk, as data, for tools like syntax highlighters and railroad diagrams
+//!
+//! It is maintained by hand, next to the AST it describes. Where the AST and the examples differ,
+//! it follows the examples. Layout is not part of it: besides where the grammar ends it, an item
+//! ends where a line starts the next one, like `fact n = ...` after `fact 0 = 1`, see
+//! [`crate::parse`]. Printed with [`ebnf`], it looks like this:
+//!
+//! ```text
+//! ifelse ::= "if" expr "then" expr "else" expr
+//! ```
+
+use std::fmt::Display;
+
+/// A part of a rule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    /// Code that is written as it is, like `->`
+    Literal(&'static str),
+
+    /// A token of a kind, like an identifier
+    Token(&'static str),
+
+    /// A reference to a rule, by name
+    Rule(&'static str),
+
+    Sequence(Vec<Node>),
+    Choice(Vec<Node>),
+    Optional(Box<Node>),
+
+    /// Zero or more repetitions
+    Repeat(Box<Node>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub name: &'static str,
+    pub node: Node,
+}
+
+/// The name of the rule a file is parsed with
+pub const START: &str = "program";
+
+/// The kinds of tokens of [`Node::Token`]
+pub const TOKENS: &[&str] = &["IDENT", "NUMBER", "STRING", "BYTES"];
+
+/// The rules of the grammar, starting with [`START`]
+pub fn grammar() -> Vec<Rule> {
+    // `x: Int = 1` declares and defines `x`
+    let declaration = seq([rule("declaration"), opt(seq([lit("="), rule("expr")]))]);
+    let item_kinds = choice([
+        rule("use"),
+        declaration.clone(),
+        rule("definition"),
+        rule("typedef"),
+        rule("enumdef"),
+        rule("traitdef"),
+        rule("impl"),
+        rule("effectdef"),
+        rule("patternsynonym"),
+    ]);
+    let operators = [
+        "+", "-", "*", "/", "%", "==", "!=", "<", "<=", ">", ">=", "&", "&&", "|", "||", "^", "++",
+    ];
+
+    vec![
+        define("program", many(rule("item"))),
+        define(
+            "item",
+            seq([many(rule("attribute")), opt(lit("pub")), item_kinds]),
+        ),
+        define(
+            "attribute",
+            seq([
+                lit("@"),
+                ident(),
+                opt(seq([lit("("), list(rule("meta")), lit(")")])),
+            ]),
+        ),
+        // Like `unused`, `"use area"`, `backend = "wasm"` or `not(debug)`
+        define(
+            "meta",
+            choice([
+                Node::Token("STRING"),
+                seq([
+                    ident(),
+                    opt(choice([
+                        seq([lit("="), Node::Token("STRING")]),
+                        seq([lit("("), list(rule("meta")), lit(")")]),
+                    ])),
+                ]),
+            ]),
+        ),
+        define(
+            "use",
+            seq([
+                lit("use"),
+                rule("path"),
+                opt(seq([lit("."), lit("*")])),
+                opt(seq([lit("as"), ident()])),
+            ]),
+        ),
+        define("path", seq([ident(), many(seq([lit("."), ident()]))])),
+        define(
+            "declaration",
+            seq([ident(), lit(":"), rule("type"), opt(rule("where"))]),
+        ),
+        // The patterns make it an equation, like `fact 0 = 1`, one of the cases of a function
+        define(
+            "definition",
+            seq([
+                ident(),
+                many(rule("patternatom")),
+                lit("="),
+                rule("expr"),
+                opt(seq([
+                    lit("where"),
+                    lit("{"),
+                    many(rule("binding")),
+                    lit("}"),
+                ])),
+            ]),
+        ),
+        // The bindings of a `let` or of the `where` block of a definition
+        define(
+            "binding",
+            choice([
+                declaration,
+                rule("definition"),
+                seq([
+                    rule("pattern"),
+                    opt(seq([lit(":"), rule("type")])),
+                    lit("="),
+                    rule("expr"),
+                ]),
+            ]),
+        ),
+        define("where", seq([lit("where"), list(rule("bound"))])),
+        // A type variable and the trait it implements, like `A: Show`
+        define("bound", seq([ident(), lit(":"), rule("path")])),
+        // Types
+        define("type", choice([rule("functype"), rule("typeapp")])),
+        // The effects of a function follow its return type, like `() -> String with Console`
+        define(
+            "functype",
+            seq([
+                lit("("),
+                opt(list(rule("argtype"))),
+                lit(")"),
+                lit("->"),
+                rule("type"),
+                opt(seq([lit("with"), list(rule("path"))])),
+            ]),
+        ),
+        define(
+            "argtype",
+            seq([opt(seq([ident(), lit(":")])), rule("type")]),
+        ),
+        define("typeapp", seq([rule("typeatom"), many(rule("typeatom"))])),
+        define(
+            "typeatom",
+            choice([
+                seq([opt(lit("dyn")), rule("path")]),
+                seq([lit("("), opt(rule("type")), lit(")")]),
+            ]),
+        ),
+        define(
+            "typedef",
+            seq([
+                lit("type"),
+                ident(),
+                many(ident()),
+                opt(rule("where")),
+                lit("="),
+                rule("record"),
+            ]),
+        ),
+        define(
+            "record",
+            seq([lit("{"), opt(list(rule("field"))), lit("}")]),
+        ),
+        define("field", seq([ident(), lit(":"), rule("type")])),
+        define(
+            "enumdef",
+            seq([
+                lit("enum"),
+                ident(),
+                many(ident()),
+                opt(rule("where")),
+                lit("="),
+                rule("variant"),
+                many(seq([lit("|"), rule("variant")])),
+            ]),
+        ),
+        // The members of a variant are named, like `Circle { radius: Float }`, or only typed, like
+        // `Ok T`
+        define(
+            "variant",
+            seq([ident(), choice([rule("record"), many(rule("typeatom"))])]),
+        ),
+        // The definitions of a trait are the defaults of its methods
+        define(
+            "traitdef",
+            seq([
+                lit("trait"),
+                ident(),
+                lit("="),
+                lit("{"),
+                many(choice([
+                    rule("assoctype"),
+                    rule("declaration"),
+                    rule("definition"),
+                ])),
+                lit("}"),
+            ]),
+        ),
+        // Declared by a trait, like `type Item`, and defined by its `impl`s, like `type Item = i64`
+        define(
+            "assoctype",
+            seq([lit("type"), ident(), opt(seq([lit("="), rule("type")]))]),
+        ),
+        define(
+            "impl",
+            seq([
+                lit("impl"),
+                ident(),
+                lit("on"),
+                ident(),
+                lit("="),
+                lit("{"),
+                many(choice([
+                    rule("assoctype"),
+                    rule("declaration"),
+                    rule("definition"),
+                ])),
+                lit("}"),
+            ]),
+        ),
+        define(
+            "effectdef",
+            seq([
+                lit("effect"),
+                ident(),
+                lit("="),
+                lit("{"),
+                many(rule("declaration")),
+                lit("}"),
+            ]),
+        ),
+        // Its parameters are the variables of the pattern, like `pattern Head x = Cons { x, _ }`
+        define(
+            "patternsynonym",
+            seq([
+                lit("pattern"),
+                ident(),
+                many(ident()),
+                lit("="),
+                rule("pattern"),
+            ]),
+        ),
+        // Expressions
+        define(
+            "expr",
+            choice([
+                rule("lambda"),
+                rule("letin"),
+                rule("ifelse"),
+                rule("matchwhen"),
+                rule("doblock"),
+                seq([lit("lazy"), rule("expr")]),
+                rule("binary"),
+            ]),
+        ),
+        define(
+            "lambda",
+            seq([
+                lit("("),
+                opt(list(rule("param"))),
+                lit(")"),
+                lit("->"),
+                rule("expr"),
+            ]),
+        ),
+        // A name is the simplest pattern
+        define(
+            "param",
+            seq([rule("pattern"), opt(seq([lit(":"), rule("type")]))]),
+        ),
+        define(
+            "letin",
+            seq([lit("let"), many(rule("binding")), lit("in"), rule("expr")]),
+        ),
+        define(
+            "ifelse",
+            seq([
+                lit("if"),
+                rule("expr"),
+                lit("then"),
+                rule("expr"),
+                lit("else"),
+                rule("expr"),
+            ]),
+        ),
+        define(
+            "matchwhen",
+            seq([
+                lit("match"),
+                rule("expr"),
+                many(rule("arm")),
+                opt(seq([lit("else"), rule("expr")])),
+            ]),
+        ),
+        define(
+            "arm",
+            seq([
+                lit("when"),
+                rule("pattern"),
+                opt(seq([lit("when"), rule("expr")])),
+                lit("->"),
+                rule("expr"),
+            ]),
+        ),
+        // A variant with its members as arguments, like `Some x`
+        define(
+            "pattern",
+            choice([
+                seq([rule("path"), rule("patternatom"), many(rule("patternatom"))]),
+                rule("patternatom"),
+            ]),
+        ),
+        define(
+            "patternatom",
+            choice([
+                lit("_"),
+                seq([opt(lit("-")), Node::Token("NUMBER")]),
+                Node::Token("STRING"),
+                Node::Token("BYTES"),
+                lit("true"),
+                lit("false"),
+                seq([lit("("), lit(")")]),
+                seq([
+                    rule("path"),
+                    opt(seq([lit("{"), opt(list(rule("member"))), lit("}")])),
+                ]),
+                seq([
+                    lit("["),
+                    opt(choice([
+                        seq([list(rule("pattern")), opt(seq([lit(","), rule("rest")]))]),
+                        rule("rest"),
+                    ])),
+                    lit("]"),
+                ]),
+                seq([lit("("), rule("pattern"), lit(")")]),
+            ]),
+        ),
+        // The members of a variant are all named, like `Circle { radius: r }`, or none are
+        define(
+            "member",
+            seq([opt(seq([ident(), lit(":")])), rule("pattern")]),
+        ),
+        define("rest", seq([lit(".."), rule("pattern")])),
+        // The expression cannot have a `|` outside of parentheses, which would end it
+        define(
+            "comprehension",
+            seq([
+                lit("["),
+                rule("expr"),
+                lit("|"),
+                list(rule("qualifier")),
+                lit("]"),
+            ]),
+        ),
+        define(
+            "qualifier",
+            seq([opt(seq([ident(), lit("<-")])), rule("expr")]),
+        ),
+        define(
+            "doblock",
+            seq([lit("do"), lit("{"), list(rule("statement")), lit("}")]),
+        ),
+        define(
+            "statement",
+            choice([
+                seq([lit("let"), ident(), lit("="), rule("expr")]),
+                seq([opt(seq([ident(), lit("<-")])), rule("expr")]),
+            ]),
+        ),
+        define(
+            "binary",
+            seq([
+                rule("unary"),
+                many(seq([choice(operators.map(lit)), rule("unary")])),
+            ]),
+        ),
+        // Only numbers are negated, like `-1`, and `!` takes a single operand, like `!(same a b)`
+        define(
+            "unary",
+            choice([
+                seq([lit("-"), Node::Token("NUMBER")]),
+                seq([lit("!"), rule("postfix")]),
+                rule("application"),
+            ]),
+        ),
+        define("application", seq([rule("postfix"), many(rule("postfix"))])),
+        define("postfix", seq([rule("atom"), opt(lit("?"))])),
+        define(
+            "atom",
+            choice([
+                seq([
+                    rule("path"),
+                    opt(seq([lit("{"), opt(list(rule("fieldinit"))), lit("}")])),
+                ]),
+                // A field as a function, like `.name`
+                seq([lit("."), ident()]),
+                Node::Token("NUMBER"),
+                Node::Token("STRING"),
+                Node::Token("BYTES"),
+                lit("true"),
+                lit("false"),
+                // The unit value, unless it is followed by `->` and so a lambda without parameters
+                seq([lit("("), lit(")")]),
+                rule("comprehension"),
+                // The elements are separated by spaces, or by commas
+                seq([
+                    lit("["),
+                    choice([list(rule("element")), many(rule("element"))]),
+                    lit("]"),
+                ]),
+                seq([lit("{"), opt(list(rule("fieldvalue"))), lit("}")]),
+                // A lambda of a single parameter, like `(x: x + 1)`, so a name is ascribed a type
+                // in parentheses of its own, like `((x): Int)`
+                seq([lit("("), ident(), lit(":"), rule("expr"), lit(")")]),
+                seq([
+                    lit("("),
+                    rule("expr"),
+                    opt(seq([lit(":"), rule("type")])),
+                    lit(")"),
+                ]),
+            ]),
+        ),
+        // An element of a list, or the elements of another list
+        define(
+            "element",
+            choice([rule("expr"), seq([lit(".."), rule("postfix")])]),
+        ),
+        define("fieldvalue", seq([ident(), lit("="), rule("expr")])),
+        // Just the name for a variable of that name
+        define(
+            "fieldinit",
+            seq([ident(), opt(seq([lit(":"), rule("expr")]))]),
+        ),
+    ]
+}
+
+/// The rules as EBNF in the notation of the XML specification, one rule per line
+pub fn ebnf(rules: &[Rule]) -> String {
+    rules
+        .iter()
+        .map(|rule| format!("{} ::= {}\n", rule.name, rule.node))
+        .collect()
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Node::Literal(text) => write!(f, "{:?}", text),
+            Node::Token(kind) | Node::Rule(kind) => write!(f, "{}", kind),
+            Node::Sequence(nodes) => {
+                let nodes = nodes.iter().map(|node| match node {
+                    Node::Choice(_) => format!("( {} )", node),
+                    _ => node.to_string(),
+                });
+                write!(f, "{}", nodes.collect::<Vec<_>>().join(" "))
+            }
+            Node::Choice(nodes) => {
+                let nodes = nodes.iter().map(Node::to_string);
+                write!(f, "{}", nodes.collect::<Vec<_>>().join(" | "))
+            }
+            Node::Optional(node) => write!(f, "{}?", Grouped(node)),
+            Node::Repeat(node) => write!(f, "{}*", Grouped(node)),
+        }
+    }
+}
+
+// A node in parentheses if it consists of several
+struct Grouped<'a>(&'a Node);
+
+impl Display for Grouped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Node::Sequence(_) | Node::Choice(_) => write!(f, "( {} )", self.0),
+            node => write!(f, "{}", node),
+        }
+    }
+}
+
+fn define(name: &'static str, node: Node) -> Rule {
+    Rule { name, node }
+}
+
+fn lit(text: &'static str) -> Node {
+    Node::Literal(text)
+}
+
+fn ident() -> Node {
+    Node::Token("IDENT")
+}
+
+fn rule(name: &'static str) -> Node {
+    Node::Rule(name)
+}
+
+fn seq<const N: usize>(nodes: [Node; N]) -> Node {
+    Node::Sequence(nodes.into())
+}
+
+fn choice<const N: usize>(nodes: [Node; N]) -> Node {
+    Node::Choice(nodes.into())
+}
+
+fn opt(node: Node) -> Node {
+    Node::Optional(Box::new(node))
+}
+
+fn many(node: Node) -> Node {
+    Node::Repeat(Box::new(node))
+}
+
+// One or more, separated by commas
+fn list(node: Node) -> Node {
+    seq([node.clone(), many(seq([lit(","), node]))])
+}
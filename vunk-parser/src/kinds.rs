@@ -0,0 +1,409 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Inferring the kinds of type variables, so they can stand for type constructors
+//!
+//! The kind of a type says how many type arguments it takes: `i64` is of kind `*`, `Option` of
+//! kind `* -> *` and `Result` of kind `* -> * -> *`. A type variable gets its kind from how it is
+//! used, so `Self` of this trait is of kind `* -> *`, and only such types can implement it:
+//!
+//! ```text
+//! trait Functor =
+//!     { fmap: ((A) -> B, Self A) -> Self B
+//!     }
+//!
+//! impl Functor on Option =
+//!     { fmap = (f, option) -> Option.map f option
+//!     }
+//! ```
+//!
+//! The kinds are inferred by unification over the type applications of a declaration, or of all
+//! the methods of a trait, as these share `Self`. Kinds that are not constrained are `*`.
+//!
+//! Names that are neither types of the standard library nor primitive types nor defined in the
+//! program are type variables. The type parameters of the types defined in the program are not
+//! part of the AST, so applications of these types are not checked.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use crate::ast::decl::DeclType;
+use crate::ast::expr::Expr;
+use crate::ast::name::TypeName;
+use crate::ast::program::Program;
+use crate::ast::traitdef::TraitDef;
+
+/// The types of the standard library that take type arguments, with the number they take
+const CONSTRUCTORS: &[(&str, usize)] = &[
+    ("Lazy", 1),
+    ("List", 1),
+    ("Option", 1),
+    ("Ref", 1),
+    ("Set", 1),
+    ("Task", 1),
+    ("Map", 2),
+    ("Result", 2),
+];
+
+const PRIMITIVES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64", "u128", "f32", "f64", "Int",
+    "BigInt", "Float", "Bool", "String", "Bytes",
+];
+
+/// The kind of a type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// `*`, the kind of the types of values
+    Type,
+
+    /// `k1 -> k2`, the kind of type constructors
+    Arrow(Box<Kind>, Box<Kind>),
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::Type => write!(f, "*"),
+            Kind::Arrow(arg, result) => match **arg {
+                Kind::Type => write!(f, "* -> {}", result),
+                Kind::Arrow(..) => write!(f, "({}) -> {}", arg, result),
+            },
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum KindError {
+    #[error("'{ty}' is applied to '{arg}' in '{context}', but it is of kind {kind}")]
+    Applied {
+        ty: String,
+        arg: String,
+        kind: Kind,
+        context: String,
+    },
+
+    #[error("'{ty}' is of kind {kind}, so it is missing type arguments")]
+    Unapplied { ty: String, kind: Kind },
+
+    #[error("'{ty}' is applied to itself in '{context}', so it has no kind")]
+    Infinite { ty: String, context: String },
+
+    #[error("impl {trait_name} on {type_name} needs a type of kind {expected}, not of {found}")]
+    Impl {
+        trait_name: String,
+        type_name: String,
+        expected: Kind,
+        found: Kind,
+    },
+}
+
+/// Check that the types in the declarations, traits and `impl`s of the program are applied to as
+/// many type arguments as their kinds take
+pub fn check(program: &Program) -> Result<(), KindError> {
+    let defined = defined(program);
+    let mut traits = BTreeMap::new();
+
+    for expr in program.expr.iter() {
+        match expr {
+            Expr::Decl(decl) => {
+                Inference::new(&defined).decl_type(&decl.rhs)?;
+            }
+            Expr::Trait(def) => {
+                traits.insert(def.name.0.as_str(), self_kind(&defined, def)?);
+            }
+            _ => {}
+        }
+    }
+
+    for expr in program.expr.iter() {
+        let def = match expr {
+            Expr::Impl(def) => def,
+            _ => continue,
+        };
+        let expected = match traits.get(def.trait_name.0.as_str()) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let found = match known(&def.type_name.0) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        if *expected != found {
+            return Err(KindError::Impl {
+                trait_name: def.trait_name.0.clone(),
+                type_name: def.type_name.0.clone(),
+                expected: expected.clone(),
+                found,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The kind of `Self` in a trait of the program, inferred from its methods
+pub fn of_trait(program: &Program, trait_name: &str) -> Result<Option<Kind>, KindError> {
+    let defined = defined(program);
+    for expr in program.expr.iter() {
+        if let Expr::Trait(def) = expr {
+            if def.name.0 == trait_name {
+                return self_kind(&defined, def).map(Some);
+            }
+        }
+    }
+    Ok(None)
+}
+
+// The methods of a trait share `Self`, so their kinds are inferred together
+fn self_kind(defined: &BTreeSet<&str>, def: &TraitDef) -> Result<Kind, KindError> {
+    let mut inference = Inference::new(defined);
+    let this = inference.variable("Self");
+    def.methods
+        .iter()
+        .try_for_each(|decl| inference.decl_type(&decl.rhs))?;
+    Ok(inference.resolve(&this))
+}
+
+fn defined(program: &Program) -> BTreeSet<&str> {
+    program
+        .expr
+        .iter()
+        .filter_map(|expr| match expr {
+            Expr::Enum(def) => Some(def.name.0.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+// The kind of a type of the standard library or a primitive type
+fn known(name: &str) -> Option<Kind> {
+    if let Some((_, arity)) = CONSTRUCTORS
+        .iter()
+        .find(|(constructor, _)| *constructor == name)
+    {
+        let kind = (0..*arity).fold(Kind::Type, |kind, _| {
+            Kind::Arrow(Box::new(Kind::Type), Box::new(kind))
+        });
+        Some(kind)
+    } else if PRIMITIVES.contains(&name) {
+        Some(Kind::Type)
+    } else {
+        None
+    }
+}
+
+// A type as it is written in a signature, like `Self (List A)`
+enum Type {
+    Name(String),
+    Apply(Box<Type>, Box<Type>),
+
+    // A function type, a tuple or `()`, all of kind `*`
+    Other,
+}
+
+impl Type {
+    fn parse(text: &str) -> Type {
+        let text = text
+            .replace("->", " -> ")
+            .replace('(', " ( ")
+            .replace(')', " ) ")
+            .replace(',', " , ");
+        let tokens = text.split_whitespace().collect::<Vec<_>>();
+        Type::application(&tokens, &mut 0)
+    }
+
+    // The application up to the closing parenthesis, which is consumed, or the end
+    fn application(tokens: &[&str], pos: &mut usize) -> Type {
+        let mut atoms = Vec::new();
+        let mut other = false;
+        while let Some(token) = tokens.get(*pos) {
+            *pos += 1;
+            match *token {
+                ")" => break,
+                "," | "->" => other = true,
+                "(" => atoms.push(Type::application(tokens, pos)),
+                name => atoms.push(Type::Name(name.to_string())),
+            }
+        }
+
+        let mut atoms = atoms.into_iter();
+        match atoms.next() {
+            Some(head) if !other => atoms.fold(head, |function, arg| {
+                Type::Apply(Box::new(function), Box::new(arg))
+            }),
+            _ => Type::Other,
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Name(name) => write!(f, "{}", name),
+            Type::Apply(function, arg) => match **arg {
+                Type::Apply(..) => write!(f, "{} ({})", function, arg),
+                _ => write!(f, "{} {}", function, arg),
+            },
+            Type::Other => write!(f, "(..)"),
+        }
+    }
+}
+
+// A kind with variables for the kinds that are not inferred yet
+#[derive(Clone, Debug)]
+enum Term {
+    Type,
+    Arrow(Box<Term>, Box<Term>),
+    Var(usize),
+}
+
+impl From<Kind> for Term {
+    fn from(kind: Kind) -> Term {
+        match kind {
+            Kind::Type => Term::Type,
+            Kind::Arrow(arg, result) => {
+                Term::Arrow(Box::new(Term::from(*arg)), Box::new(Term::from(*result)))
+            }
+        }
+    }
+}
+
+// Why two kinds do not unify
+enum Clash {
+    Mismatch,
+    Infinite,
+}
+
+struct Inference<'a> {
+    // The types of the program, whose kinds are not known
+    defined: &'a BTreeSet<&'a str>,
+    variables: BTreeMap<String, Term>,
+    solved: Vec<Option<Term>>,
+}
+
+impl<'a> Inference<'a> {
+    fn new(defined: &'a BTreeSet<&'a str>) -> Self {
+        Inference {
+            defined,
+            variables: BTreeMap::new(),
+            solved: Vec::new(),
+        }
+    }
+
+    fn fresh(&mut self) -> Term {
+        self.solved.push(None);
+        Term::Var(self.solved.len() - 1)
+    }
+
+    fn variable(&mut self, name: &str) -> Term {
+        if let Some(term) = self.variables.get(name) {
+            return term.clone();
+        }
+        let term = self.fresh();
+        self.variables.insert(name.to_string(), term.clone());
+        term
+    }
+
+    fn decl_type(&mut self, ty: &DeclType) -> Result<(), KindError> {
+        match ty {
+            DeclType::TypeName(name) => self.value_type(name),
+            DeclType::Func { args, retty, .. } => {
+                args.iter().try_for_each(|arg| self.decl_type(&arg.ty))?;
+                self.value_type(retty)
+            }
+        }
+    }
+
+    // A type of values, which is of kind `*`
+    fn value_type(&mut self, name: &TypeName) -> Result<(), KindError> {
+        let ty = Type::parse(&name.0);
+        let term = self.infer(&ty, &name.0)?;
+        self.unify(&term, &Term::Type)
+            .map_err(|_| KindError::Unapplied {
+                ty: name.0.clone(),
+                kind: self.resolve(&term),
+            })
+    }
+
+    fn infer(&mut self, ty: &Type, context: &str) -> Result<Term, KindError> {
+        match ty {
+            // A path, like `Self.Item` or `Std.Fmt.Debug`, is a type of its own
+            Type::Name(name) if name.contains('.') => Ok(Term::Type),
+            Type::Name(name) => match known(name) {
+                Some(kind) => Ok(Term::from(kind)),
+                None if self.defined.contains(name.as_str()) => Ok(self.fresh()),
+                None => Ok(self.variable(name)),
+            },
+            Type::Apply(function, arg) => {
+                let function_term = self.infer(function, context)?;
+                let arg_term = self.infer(arg, context)?;
+                let result = self.fresh();
+                let expected = Term::Arrow(Box::new(arg_term), Box::new(result.clone()));
+                match self.unify(&function_term, &expected) {
+                    Ok(()) => Ok(result),
+                    Err(Clash::Infinite) => Err(KindError::Infinite {
+                        ty: function.to_string(),
+                        context: context.to_string(),
+                    }),
+                    Err(Clash::Mismatch) => Err(KindError::Applied {
+                        ty: function.to_string(),
+                        arg: arg.to_string(),
+                        kind: self.resolve(&function_term),
+                        context: context.to_string(),
+                    }),
+                }
+            }
+            Type::Other => Ok(Term::Type),
+        }
+    }
+
+    // The term with the solved variables replaced, one level deep
+    fn shallow(&self, term: &Term) -> Term {
+        match term {
+            Term::Var(var) => match &self.solved[*var] {
+                Some(solved) => self.shallow(solved),
+                None => term.clone(),
+            },
+            _ => term.clone(),
+        }
+    }
+
+    fn occurs(&self, var: usize, term: &Term) -> bool {
+        match self.shallow(term) {
+            Term::Var(other) => var == other,
+            Term::Arrow(arg, result) => self.occurs(var, &arg) || self.occurs(var, &result),
+            Term::Type => false,
+        }
+    }
+
+    fn unify(&mut self, a: &Term, b: &Term) -> Result<(), Clash> {
+        match (self.shallow(a), self.shallow(b)) {
+            (Term::Var(x), Term::Var(y)) if x == y => Ok(()),
+            (Term::Var(var), term) | (term, Term::Var(var)) => {
+                if self.occurs(var, &term) {
+                    return Err(Clash::Infinite);
+                }
+                self.solved[var] = Some(term);
+                Ok(())
+            }
+            (Term::Type, Term::Type) => Ok(()),
+            (Term::Arrow(a_arg, a_result), Term::Arrow(b_arg, b_result)) => {
+                self.unify(&a_arg, &b_arg)?;
+                self.unify(&a_result, &b_result)
+            }
+            _ => Err(Clash::Mismatch),
+        }
+    }
+
+    // The kind of a term, with `*` for the variables that are not constrained
+    fn resolve(&self, term: &Term) -> Kind {
+        match self.shallow(term) {
+            Term::Type | Term::Var(_) => Kind::Type,
+            Term::Arrow(arg, result) => Kind::Arrow(
+                Box::new(self.resolve(&arg)),
+                Box::new(self.resolve(&result)),
+            ),
+        }
+    }
+}
@@ -3,6 +3,17 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 pub mod ast;
+pub mod attribute;
+pub mod consteval;
+pub mod desugar;
+pub mod effects;
+pub mod generate;
+pub mod grammar;
+pub mod kinds;
+pub mod matching;
+pub mod parse;
+pub mod print;
+pub mod traits;
 
 use vunk_lexer::Span;
 
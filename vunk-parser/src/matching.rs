@@ -0,0 +1,338 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Compiling `match` expressions on literals, like `match method when "get" -> ...`
+//!
+//! The arms with literal patterns are not tried one after the other. [`compile`] turns them into
+//! a [`Switch`] once: integers that are close together index a jump table, strings are looked up
+//! byte by byte in a trie, and other literals are found by binary search. The arms are still
+//! picked as if they were tried in order, so the first arm with a literal wins, and arms after a
+//! `_` or a variable are never taken.
+//!
+//! Strings and numbers have too many values to list them all, so a `match` with literal patterns
+//! needs an arm with `_` or a variable, or an `else`, see [`check`]. Bools and `()` are the
+//! exception, `true` and `false` are all there is, and `()` is the only value of its type.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::ast::comprehension::Qualifier;
+use crate::ast::doblock::DoStatement;
+use crate::ast::expr::Expr;
+use crate::ast::letin::LetIn;
+use crate::ast::literal::Literal;
+use crate::ast::matchwhen::MatchWhen;
+use crate::ast::pattern::Pattern;
+use crate::ast::program::Program;
+use crate::ast::record::Construct;
+use crate::ast::record::Record;
+use crate::consteval::compare;
+use crate::consteval::Constant;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MatchError {
+    #[error("A match on literals like {pattern} needs a '_' arm or an 'else'")]
+    NotExhaustive { pattern: String },
+}
+
+// A jump table is used if it has at most this many entries per arm
+const JUMP_TABLE_DENSITY: i128 = 2;
+
+/// How the arm of a `match` on literals is picked
+#[derive(Clone, Debug, PartialEq)]
+pub struct Switch {
+    pub table: Table,
+
+    /// The arm that is taken if no literal matches, or `None` for the `else` of the `match`
+    pub fallback: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Table {
+    /// The arms for the integers from `start` on
+    Jump {
+        start: i64,
+        arms: Vec<Option<usize>>,
+    },
+
+    Trie(Trie),
+
+    /// The arms by value, sorted for binary search
+    Sorted(Vec<(Constant, usize)>),
+}
+
+/// The arms for strings, by their bytes
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trie {
+    /// The arm for the string that ends here
+    pub arm: Option<usize>,
+
+    pub children: BTreeMap<u8, Trie>,
+}
+
+impl Trie {
+    fn insert(&mut self, key: &str, arm: usize) {
+        let node = key
+            .bytes()
+            .fold(self, |node, byte| node.children.entry(byte).or_default());
+        node.arm.get_or_insert(arm);
+    }
+
+    fn get(&self, key: &str) -> Option<usize> {
+        key.bytes()
+            .try_fold(self, |node, byte| node.children.get(&byte))
+            .and_then(|node| node.arm)
+    }
+}
+
+impl Switch {
+    /// The index of the arm that is taken for `value`, or `None` for the `else` of the `match`
+    pub fn select(&self, value: &Constant) -> Option<usize> {
+        let arm = match (&self.table, value) {
+            (Table::Jump { start, arms }, Constant::Int(i)) => {
+                let index = usize::try_from(*i as i128 - *start as i128).ok();
+                index.and_then(|index| arms.get(index).copied().flatten())
+            }
+            (Table::Trie(trie), Constant::Str(s)) => trie.get(s),
+            (Table::Sorted(arms), value) => arms
+                .binary_search_by(|(key, _)| compare(key, value).unwrap_or(Ordering::Less))
+                .ok()
+                .map(|index| arms[index].1),
+            _ => None,
+        };
+        arm.or(self.fallback)
+    }
+}
+
+/// The switch for a `match` whose arms have literal patterns, if they are all of one type
+///
+/// Arms with other patterns than literals, `_` and variables, like variants or lists, and arms
+/// with guards are matched one after the other, so there is no switch for them.
+pub fn compile(matchwhen: &MatchWhen) -> Option<Switch> {
+    let mut literals = Vec::<(Constant, usize)>::new();
+    let mut fallback = None;
+    for (index, arm) in matchwhen.arms.iter().enumerate() {
+        if arm.guard.is_some() {
+            return None;
+        }
+        match &arm.pattern {
+            Pattern::Literal(literal) => {
+                let value = Constant::from_literal(literal)?;
+                let seen = literals
+                    .iter()
+                    .any(|(seen, _)| compare(seen, &value) == Some(Ordering::Equal));
+                if !seen {
+                    literals.push((value, index));
+                }
+            }
+            Pattern::Wildcard | Pattern::Variable(_) => {
+                fallback = Some(index);
+                break;
+            }
+            Pattern::Variant { .. } | Pattern::Fields { .. } | Pattern::List { .. } => return None,
+        }
+    }
+
+    if literals.is_empty() {
+        return None;
+    }
+    let table = if let Some((Constant::Str(_), _)) = literals.first() {
+        let mut trie = Trie::default();
+        for (value, arm) in literals {
+            match value {
+                Constant::Str(s) => trie.insert(&s, arm),
+                _ => return None,
+            }
+        }
+        Table::Trie(trie)
+    } else {
+        let ints = literals
+            .iter()
+            .map(|(value, _)| match value {
+                Constant::Int(i) => Some(*i),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        match ints {
+            Some(ints) if dense(&ints) => jump_table(&literals, &ints),
+            _ => sorted(literals)?,
+        }
+    };
+    Some(Switch { table, fallback })
+}
+
+/// Check that every `match` of `program` with literal patterns has an arm for the other values
+///
+/// Arms with guards do not count, as they may not be taken for any value.
+pub fn check(program: &Program) -> Result<(), MatchError> {
+    let mut found = Vec::new();
+    program
+        .expr
+        .iter()
+        .for_each(|expr| matches(expr, &mut found));
+
+    for matchwhen in found {
+        let first = matchwhen
+            .arms
+            .iter()
+            .map(|arm| &arm.pattern)
+            .find(|pattern| matches!(pattern, Pattern::Literal(_)));
+        let patterns = matchwhen
+            .arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .map(|arm| &arm.pattern)
+            .collect::<Vec<_>>();
+        let fallback = patterns
+            .iter()
+            .any(|pattern| matches!(pattern, Pattern::Wildcard | Pattern::Variable(_)));
+        let first = match first {
+            Some(first) if !fallback && matchwhen.otherwise.is_none() => first,
+            _ => continue,
+        };
+
+        let bools = [true, false].iter().all(|value| {
+            patterns.iter().any(|pattern| {
+                matches!(pattern, Pattern::Literal(Literal::Bool(b)) if b.value == *value)
+            })
+        });
+        let unit = patterns
+            .iter()
+            .any(|pattern| matches!(pattern, Pattern::Literal(Literal::Unit)));
+        if !bools && !unit {
+            return Err(MatchError::NotExhaustive {
+                pattern: crate::print::pattern(first),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn dense(ints: &[i64]) -> bool {
+    let min = ints.iter().min().copied().unwrap_or(0) as i128;
+    let max = ints.iter().max().copied().unwrap_or(0) as i128;
+    max - min < JUMP_TABLE_DENSITY * ints.len() as i128
+}
+
+fn jump_table(literals: &[(Constant, usize)], ints: &[i64]) -> Table {
+    let start = ints.iter().min().copied().unwrap_or(0);
+    let len = ints.iter().max().copied().unwrap_or(0) as i128 - start as i128 + 1;
+    let mut arms = vec![None; len as usize];
+    for (i, (_, arm)) in ints.iter().zip(literals) {
+        arms[(*i as i128 - start as i128) as usize] = Some(*arm);
+    }
+    Table::Jump { start, arms }
+}
+
+fn sorted(mut literals: Vec<(Constant, usize)>) -> Option<Table> {
+    // Values of different types cannot be compared, the typechecker rejects them
+    let comparable = literals
+        .windows(2)
+        .all(|pair| compare(&pair[0].0, &pair[1].0).is_some());
+    if !comparable {
+        return None;
+    }
+    literals.sort_by(|(a, _), (b, _)| compare(a, b).unwrap_or(Ordering::Equal));
+    Some(Table::Sorted(literals))
+}
+
+// The `match` expressions in `expr`, outermost first
+fn matches<'a>(expr: &'a Expr, found: &mut Vec<&'a MatchWhen>) {
+    match expr {
+        Expr::Unary(_, expr)
+        | Expr::Spread(expr)
+        | Expr::Lazy(expr)
+        | Expr::Try(expr)
+        | Expr::Ascription(expr, _) => matches(expr, found),
+        Expr::Binary(_, lhs, rhs) => {
+            matches(lhs, found);
+            matches(rhs, found);
+        }
+        Expr::Apply(function, args) => {
+            matches(function, found);
+            args.iter().for_each(|arg| matches(arg, found));
+        }
+        Expr::Literal(Literal::List(elements)) => {
+            elements.iter().for_each(|element| matches(element, found))
+        }
+        Expr::Record(Record { fields }) | Expr::Construct(Construct { fields, .. }) => {
+            fields.iter().for_each(|field| matches(&field.expr, found))
+        }
+        Expr::Lambda(lambda) => matches(&lambda.expr, found),
+        Expr::Def(def) => matches(&def.rhs.expr, found),
+        Expr::Equation(equation) => matches(&equation.expr, found),
+        Expr::LetIn(letin) => {
+            let_matches(&letin.items, found);
+            matches(&letin.expr, found);
+        }
+        Expr::Where(bindings) => {
+            matches(&bindings.expr, found);
+            let_matches(&bindings.items, found);
+        }
+        Expr::IfElse(ifelse) => {
+            matches(&ifelse.condition, found);
+            matches(&ifelse.tru, found);
+            matches(&ifelse.fals, found);
+        }
+        Expr::MatchWhen(matchwhen) => {
+            found.push(matchwhen);
+            matches(&matchwhen.expr, found);
+            for arm in matchwhen.arms.iter() {
+                if let Some(guard) = &arm.guard {
+                    matches(guard, found);
+                }
+                matches(&arm.expr, found);
+            }
+            if let Some(otherwise) = &matchwhen.otherwise {
+                matches(otherwise, found);
+            }
+        }
+        Expr::Do(block) => {
+            for statement in block.statements.iter() {
+                match statement {
+                    DoStatement::Bind(_, expr)
+                    | DoStatement::Let(_, expr)
+                    | DoStatement::Run(expr) => matches(expr, found),
+                }
+            }
+            matches(&block.result, found);
+        }
+        Expr::Comprehension(comprehension) => {
+            for qualifier in comprehension.qualifiers.iter() {
+                match qualifier {
+                    Qualifier::Generator(_, expr) | Qualifier::Filter(expr) => matches(expr, found),
+                }
+            }
+            matches(&comprehension.expr, found);
+        }
+        Expr::Trait(def) => def
+            .defaults
+            .iter()
+            .for_each(|def| matches(&def.rhs.expr, found)),
+        Expr::Impl(def) => def
+            .methods
+            .iter()
+            .for_each(|def| matches(&def.rhs.expr, found)),
+        Expr::Variable(_)
+        | Expr::FieldSection(_)
+        | Expr::Literal(_)
+        | Expr::Use(_)
+        | Expr::Decl(_)
+        | Expr::Type(_)
+        | Expr::Enum(_)
+        | Expr::Effect(_)
+        | Expr::PatternSynonym(_) => {}
+    }
+}
+
+fn let_matches<'a>(items: &'a [LetIn], found: &mut Vec<&'a MatchWhen>) {
+    for item in items.iter() {
+        match item {
+            LetIn::Def(def) => matches(&def.rhs.expr, found),
+            LetIn::Equation(equation) => matches(&equation.expr, found),
+            LetIn::Pattern(binding) => matches(&binding.expr, found),
+            LetIn::Decl(_) => {}
+        }
+    }
+}
@@ -0,0 +1,1546 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Parsing tokens into the AST
+//!
+//! The parser reads the syntax of the programs in `vunk-examples`.
+//!
+//! The lexer drops whitespace, so the parser is told which tokens start a line. An item ends
+//! where the next one starts: the body of `x = f a` does not take `y` as an argument if `y =` or
+//! `y :` follows, and neither does it take a line that starts with a name or a list followed by
+//! `=` or `:` on the same line, like `fact 0 = 1` or `[x, y]: List Int = pair`. In brackets, only
+//! `y =` ends it. A `.` after a space starts a field section, like `.name` in `map .name people`.
+//!
+//! `pub` is read and dropped, the driver reads the exports of a module from its tokens. So are the
+//! attributes of items other than declarations, as `@cfg` applies to the tokens before they are
+//! parsed. Types are kept as text, like `List i64` or `(i64) -> Option i64`, with the spaces and
+//! parentheses between their parts normalized.
+//!
+//! The lexer takes `then`, `trait`, `impl`, `effect` and `pattern` for identifiers, the parser
+//! does not accept them as names. A pattern of a single name is a variable if it starts with a
+//! lowercase letter, and a variant without members otherwise, like `None`.
+
+// The closures of `select!` and `try_map` return chumsky's `Simple<Token>`, which is as large as
+// it is for every parser
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use chumsky::combinator::SeparatedBy;
+use chumsky::error::Simple;
+use chumsky::primitive::choice;
+use chumsky::primitive::end;
+use chumsky::primitive::filter;
+use chumsky::primitive::filter_map;
+use chumsky::primitive::just;
+use chumsky::primitive::one_of;
+use chumsky::primitive::Just;
+use chumsky::recursive::recursive;
+use chumsky::recursive::Recursive;
+use chumsky::select;
+use chumsky::stream::Stream;
+use chumsky::BoxedParser;
+use chumsky::Error;
+use chumsky::Parser;
+use vunk_lexer::Span;
+use vunk_lexer::Token;
+
+use crate::ast::comprehension::Comprehension;
+use crate::ast::comprehension::Qualifier;
+use crate::ast::decl::Decl;
+use crate::ast::decl::DeclArg;
+use crate::ast::decl::DeclType;
+use crate::ast::def::Def;
+use crate::ast::def::DefArg;
+use crate::ast::def::DefArgType;
+use crate::ast::def::DefRhs;
+use crate::ast::def::EnumDef;
+use crate::ast::def::EnumTypeDef;
+use crate::ast::def::Equation;
+use crate::ast::def::TypeDef;
+use crate::ast::doblock::DoBlock;
+use crate::ast::doblock::DoStatement;
+use crate::ast::effect::EffectDef;
+use crate::ast::expr::Expr;
+use crate::ast::generic::Generic;
+use crate::ast::generic::WhereClause;
+use crate::ast::ifelse::IfElse;
+use crate::ast::import::Import;
+use crate::ast::letin::LetIn;
+use crate::ast::letin::LetIns;
+use crate::ast::letin::PatternBinding;
+use crate::ast::letin::WhereBindings;
+use crate::ast::literal::Bool;
+use crate::ast::literal::Bytes;
+use crate::ast::literal::Float;
+use crate::ast::literal::Integer;
+use crate::ast::literal::IntegerValue;
+use crate::ast::literal::Literal;
+use crate::ast::literal::Str;
+use crate::ast::matchwhen::MatchWhen;
+use crate::ast::matchwhen::When;
+use crate::ast::name::TraitName;
+use crate::ast::name::TypeName;
+use crate::ast::name::TypePath;
+use crate::ast::name::VariableName;
+use crate::ast::op::BinaryOp;
+use crate::ast::op::UnaryOp;
+use crate::ast::pattern::FieldPattern;
+use crate::ast::pattern::Pattern;
+use crate::ast::pattern::PatternSynonym;
+use crate::ast::program::Program;
+use crate::ast::record::Construct;
+use crate::ast::record::Field;
+use crate::ast::record::Record;
+use crate::ast::traitdef::AssocType;
+use crate::ast::traitdef::TraitDef;
+use crate::ast::traitdef::TraitImpl;
+use crate::Spanned;
+
+// Identifiers the parser gives a meaning, which cannot be names
+const KEYWORDS: &[&str] = &["then", "trait", "impl", "effect", "pattern"];
+
+// Brackets nested deeper are an error, as every `[` looks ahead through the brackets in it
+const MAX_DEPTH: usize = 256;
+
+// chumsky moves to a new stack for every nested parser once less than 1 MiB of the stack is left,
+// which makes nested code slow
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+type Expression = Recursive<'static, Token, Expr, Simple<Token>>;
+
+/// Parse the tokens of a file, skipping comments. `line_starts` are the starts of the spans of
+/// the tokens that start a line, see [`line_starts`].
+pub fn parse(
+    tokens: Vec<Spanned<Token>>,
+    line_starts: &[usize],
+) -> Result<Program, Vec<Simple<Token>>> {
+    let end = tokens.last().map(|(_, span)| span.end).unwrap_or(0);
+    let tokens = tokens
+        .into_iter()
+        .filter(|(token, _)| !matches!(token, Token::Comment(_)))
+        .collect::<Vec<_>>();
+    nesting(&tokens)?;
+
+    let joined = tokens
+        .windows(2)
+        .filter(|pair| pair[0].1.end == pair[1].1.start)
+        .map(|pair| pair[1].1.start)
+        .collect();
+    let lines = Lines {
+        starts: Rc::new(line_starts.iter().copied().collect()),
+        joined: Rc::new(joined),
+    };
+    stacker::grow(STACK_SIZE, || {
+        program(&lines).parse(Stream::from_iter(end..end + 1, tokens.into_iter()))
+    })
+}
+
+/// The starts of the spans of the tokens that start a line of `code`, which the tokens were
+/// lexed from. Like the spans of the lexer, they count chars.
+pub fn line_starts(code: &str, tokens: &[Spanned<Token>]) -> Vec<usize> {
+    let newlines = code
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| *c == '\n')
+        .map(|(offset, _)| offset)
+        .collect::<Vec<_>>();
+
+    let mut previous_end = None;
+    let mut starts = Vec::new();
+    for (_, span) in tokens {
+        let starts_line = match previous_end {
+            None => true,
+            Some(end) => {
+                let next_newline = newlines.partition_point(|newline| *newline < end);
+                matches!(newlines.get(next_newline), Some(newline) if *newline < span.start)
+            }
+        };
+        if starts_line {
+            starts.push(span.start);
+        }
+        previous_end = Some(span.end);
+    }
+    starts
+}
+
+// Whether the brackets are nested at most `MAX_DEPTH` deep
+fn nesting(tokens: &[Spanned<Token>]) -> Result<(), Vec<Simple<Token>>> {
+    let mut depth = 0usize;
+    for (token, span) in tokens {
+        match token {
+            Token::ParOpen | Token::ListOpen | Token::BlockOpen => depth += 1,
+            Token::ParClose | Token::ListClose | Token::BlockClose => {
+                depth = depth.saturating_sub(1)
+            }
+            _ => continue,
+        }
+        if depth > MAX_DEPTH {
+            return Err(vec![Simple::custom(
+                span.clone(),
+                "Brackets are nested too deeply",
+            )]);
+        }
+    }
+    Ok(())
+}
+
+// The starts of the tokens that start a line, and of the tokens right after the token before them
+#[derive(Clone)]
+struct Lines {
+    starts: Rc<HashSet<usize>>,
+    joined: Rc<HashSet<usize>>,
+}
+
+impl Lines {
+    // Whether the next token starts a line, or does not, without consuming it
+    fn at_start(&self, start: bool) -> impl Parser<Token, (), Error = Simple<Token>> + Clone {
+        let starts = self.starts.clone();
+        filter_map(move |span: Span, token| {
+            if starts.contains(&span.start) == start {
+                Ok(())
+            } else {
+                Err(Simple::expected_input_found(span, None, Some(token)))
+            }
+        })
+        .rewind()
+    }
+
+    // Whether the next token follows the token before it without a space, without consuming it
+    fn joined(&self) -> impl Parser<Token, (), Error = Simple<Token>> + Clone {
+        let joined = self.joined.clone();
+        filter_map(move |span: Span, token| {
+            if joined.contains(&span.start) {
+                Ok(())
+            } else {
+                Err(Simple::expected_input_found(span, None, Some(token)))
+            }
+        })
+        .rewind()
+    }
+}
+
+// The items of a file, up to its end
+fn program(lines: &Lines) -> impl Parser<Token, Program, Error = Simple<Token>> + Clone {
+    item(expressions(lines), lines)
+        .repeated()
+        .flatten()
+        .then_ignore(end())
+        .map(|expr| Program { expr })
+}
+
+// An `use`, a declaration, a definition, an equation, or a definition of a type, enum, trait,
+// `impl`, effect or pattern, with attributes and a `pub` in front. `x: Int = 1` is both a
+// declaration and a definition.
+fn item(
+    expr: Expression,
+    lines: &Lines,
+) -> impl Parser<Token, Vec<Expr>, Error = Simple<Token>> + Clone {
+    let decl = decl(lines)
+        .then(just(Token::Assign).ignore_then(expr.clone()).or_not())
+        .map(|(decl, expr)| {
+            let def = expr.map(|expr| Expr::Def(def_of(decl.lhs.0.clone(), expr)));
+            std::iter::once(Expr::Decl(decl)).chain(def).collect()
+        });
+    let item = choice((
+        use_item().map(|import| vec![Expr::Use(import)]),
+        type_def(lines).map(|def| vec![Expr::Type(def)]),
+        enum_def(lines).map(|def| vec![Expr::Enum(def)]),
+        trait_def(expr.clone(), lines).map(|def| vec![Expr::Trait(def)]),
+        impl_def(expr.clone(), lines).map(|def| vec![Expr::Impl(def)]),
+        effect_def(lines).map(|def| vec![Expr::Effect(def)]),
+        synonym().map(|synonym| vec![Expr::PatternSynonym(synonym)]),
+        decl,
+        def(expr.clone()).map(|def| vec![Expr::Def(def)]),
+        equation(expr).map(|equation| vec![Expr::Equation(equation)]),
+    ));
+
+    crate::attribute::attribute()
+        .repeated()
+        .then_ignore(just(Token::Pub).or_not())
+        .then(item)
+        .map(|(attributes, mut items)| {
+            if let Some(Expr::Decl(decl)) = items.first_mut() {
+                decl.attributes = attributes;
+            }
+            items
+        })
+}
+
+// Where an expression is, which decides what ends it
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Context {
+    // The body of an item, which ends where the next item starts
+    Item,
+    // In brackets, where only the end of the brackets and a `,` end it
+    Nested,
+    // The expression of a comprehension, which ends at the first `|`
+    Head,
+}
+
+// The expressions of each context, which contain each other
+struct Expressions {
+    item: Expression,
+    nested: Expression,
+    head: Expression,
+    lines: Lines,
+}
+
+// The expressions of items. Only the outermost parser holds the others, the rest refer to them
+// weakly, so that dropping the parser frees them.
+fn expressions(lines: &Lines) -> Expression {
+    recursive(|item| {
+        let mut head = None;
+        let nested = recursive(|nested| {
+            let inner = recursive(|this| {
+                let expressions = Expressions {
+                    item: item.clone(),
+                    nested: nested.clone(),
+                    head: this,
+                    lines: lines.clone(),
+                };
+                expr(Context::Head, &expressions)
+            });
+            head = Some(inner.clone());
+            let expressions = Expressions {
+                item: item.clone(),
+                nested,
+                head: inner,
+                lines: lines.clone(),
+            };
+            expr(Context::Nested, &expressions)
+        });
+        let head = head.expect("the expressions of comprehensions are defined with nested ones");
+        let expressions = Expressions {
+            item,
+            nested,
+            head,
+            lines: lines.clone(),
+        };
+        expr(Context::Item, &expressions)
+    })
+}
+
+fn expr(
+    context: Context,
+    expressions: &Expressions,
+) -> BoxedParser<'static, Token, Expr, Simple<Token>> {
+    let this = match context {
+        Context::Item => expressions.item.clone(),
+        Context::Nested => expressions.nested.clone(),
+        Context::Head => expressions.head.clone(),
+    };
+    let operand = operand(context, expressions);
+
+    let lambda = params(&expressions.lines)
+        .then_ignore(just(Token::Arrow))
+        .then(this.clone())
+        .map(|(args, expr)| {
+            Expr::Lambda(DefRhs {
+                args,
+                expr: Box::new(expr),
+            })
+        });
+    let letin = just(Token::Let)
+        .ignore_then(let_items(expressions))
+        .then_ignore(just(Token::In))
+        .then(this.clone())
+        .map(|(items, expr)| {
+            Expr::LetIn(LetIns {
+                items,
+                expr: Box::new(expr),
+            })
+        });
+    let ifelse = just(Token::If)
+        .ignore_then(this.clone())
+        .then_ignore(keyword("then"))
+        .then(this.clone())
+        .then_ignore(just(Token::Else))
+        .then(this.clone())
+        .map(|((condition, tru), fals)| {
+            Expr::IfElse(IfElse {
+                condition: Box::new(condition),
+                tru: Box::new(tru),
+                fals: Box::new(fals),
+            })
+        });
+    // A guard is never a lambda, so that `when () -> e` is guarded by `()`
+    let arm = just(Token::When)
+        .ignore_then(pattern())
+        .then(just(Token::When).ignore_then(operand.clone()).or_not())
+        .then_ignore(just(Token::Arrow))
+        .then(this.clone())
+        .map(|((pattern, guard), expr)| When {
+            pattern,
+            guard: guard.map(Box::new),
+            expr: Box::new(expr),
+        });
+    let matchwhen = just(Token::Match)
+        .ignore_then(this.clone())
+        .then(arm.repeated())
+        .then(just(Token::Else).ignore_then(this).or_not())
+        .map(|((expr, arms), otherwise)| {
+            Expr::MatchWhen(MatchWhen {
+                expr: Box::new(expr),
+                arms,
+                otherwise: otherwise.map(Box::new),
+            })
+        });
+
+    choice((lambda, letin, ifelse, matchwhen, operand)).boxed()
+}
+
+// An expression without a keyword in front, followed by the bindings of its `where`s
+fn operand(
+    context: Context,
+    expressions: &Expressions,
+) -> BoxedParser<'static, Token, Expr, Simple<Token>> {
+    let postfix = postfix(expressions);
+
+    let application = postfix
+        .clone()
+        .then(
+            argument(context, &expressions.lines)
+                .ignore_then(postfix.clone())
+                .repeated(),
+        )
+        .map(|(function, args)| {
+            if args.is_empty() {
+                function
+            } else {
+                Expr::Apply(Box::new(function), args)
+            }
+        });
+    let lazy = just(Token::Lazy)
+        .ignore_then(postfix.clone())
+        .map(|expr| Expr::Lazy(Box::new(expr)));
+    let bindings = just(Token::Where).ignore_then(
+        let_items(expressions).delimited_by(just(Token::BlockOpen), just(Token::BlockClose)),
+    );
+
+    lazy.or(binary(
+        unary(postfix, application),
+        context != Context::Head,
+    ))
+    .then(bindings.repeated())
+    .foldl(|expr, items| {
+        Expr::Where(WhereBindings {
+            expr: Box::new(expr),
+            items,
+        })
+    })
+    .boxed()
+}
+
+// A negative number, or `!` in front of an operand
+fn unary(
+    postfix: Expression,
+    operand: impl Parser<Token, Expr, Error = Simple<Token>> + Clone + 'static,
+) -> BoxedParser<'static, Token, Expr, Simple<Token>> {
+    choice((
+        just(Token::Op("!".to_string()))
+            .ignore_then(postfix)
+            .map(|expr| Expr::Unary(UnaryOp::LogicalNot, Box::new(expr))),
+        negative().map(Expr::Literal),
+        operand,
+    ))
+    .boxed()
+}
+
+// Operators by precedence, the loosest last, where the operators of a level associate to the
+// left. In the expression of a comprehension, `|` is not an operator.
+fn binary(
+    unary: BoxedParser<'static, Token, Expr, Simple<Token>>,
+    bit_or: bool,
+) -> BoxedParser<'static, Token, Expr, Simple<Token>> {
+    // The AST is not Clone, so the operators are made for every use
+    let op = |token: Token, op: fn() -> BinaryOp| just(token).map(move |_| op()).boxed();
+    let symbol = |symbol: &str| Token::Op(symbol.to_string());
+    let level = |operand: BoxedParser<'static, Token, Expr, Simple<Token>>,
+                 ops: BoxedParser<'static, Token, BinaryOp, Simple<Token>>| {
+        operand
+            .clone()
+            .then(ops.then(operand).repeated())
+            .foldl(|lhs, (op, rhs)| Expr::Binary(op, Box::new(lhs), Box::new(rhs)))
+            .boxed()
+    };
+
+    let product = level(
+        unary,
+        choice((
+            op(symbol("*"), || BinaryOp::Mul),
+            op(symbol("/"), || BinaryOp::Div),
+            op(symbol("%"), || BinaryOp::Rem),
+        ))
+        .boxed(),
+    );
+    let sum = level(
+        product,
+        choice((
+            op(symbol("++"), || BinaryOp::Join),
+            op(Token::Plus, || BinaryOp::Add),
+            op(symbol("-"), || BinaryOp::Sub),
+        ))
+        .boxed(),
+    );
+    let bit_and = level(sum, op(symbol("&"), || BinaryOp::BitAnd));
+    let bit_xor = level(bit_and, op(symbol("^"), || BinaryOp::BitXor));
+    let bit_or = if bit_or {
+        level(bit_xor, op(Token::Alternative, || BinaryOp::BitOr))
+    } else {
+        bit_xor
+    };
+    let comparison = level(
+        bit_or,
+        choice((
+            op(symbol("=="), || BinaryOp::Eq),
+            op(symbol("!="), || BinaryOp::NotEq),
+            op(symbol("<="), || BinaryOp::LessEq),
+            op(symbol(">="), || BinaryOp::MoreEq),
+            op(symbol("<"), || BinaryOp::Less),
+            op(symbol(">"), || BinaryOp::More),
+        ))
+        .boxed(),
+    );
+    let and = level(comparison, op(symbol("&&"), || BinaryOp::LogicalAnd));
+    level(and, op(symbol("||"), || BinaryOp::LogicalOr))
+}
+
+// Whether the next tokens are an argument, and not the start of the next item
+fn argument(context: Context, lines: &Lines) -> BoxedParser<'static, Token, (), Simple<Token>> {
+    let next_item = move |token: &Token| match token {
+        Token::Assign => true,
+        Token::Declare => context == Context::Item,
+        _ => false,
+    };
+    let after_name = end().or(filter(move |token| !next_item(token)).ignored());
+    let other = filter(|token| !matches!(token, Token::Ident(_))).ignored();
+    let argument = name().ignore_then(after_name).or(other).rewind();
+
+    match context {
+        Context::Item => not(head(lines)).ignore_then(argument).boxed(),
+        Context::Nested | Context::Head => argument.boxed(),
+    }
+}
+
+// A primary expression, followed by any number of `?`
+fn postfix(expressions: &Expressions) -> Expression {
+    recursive(|postfix| {
+        primary(expressions, postfix)
+            .then(just(Token::Try).repeated())
+            .foldl(|expr, _| Expr::Try(Box::new(expr)))
+    })
+}
+
+fn primary(
+    expressions: &Expressions,
+    postfix: Expression,
+) -> BoxedParser<'static, Token, Expr, Simple<Token>> {
+    let nested = expressions.nested.clone();
+
+    // Just the name for a variable of that name
+    let field = name()
+        .then(
+            one_of([Token::Declare, Token::Assign])
+                .ignore_then(nested.clone())
+                .or_not(),
+        )
+        .map(|(name, expr)| Field {
+            expr: expr.unwrap_or_else(|| Expr::Variable(VariableName(name.clone()))),
+            name: VariableName(name),
+        });
+    let construct = constructor().then(braces(field)).map(|(path, fields)| {
+        Expr::Construct(Construct {
+            path: type_path(path),
+            fields,
+        })
+    });
+    // `map .name` applies `map` to the field section `.name`, where `map.name` is a path
+    let segment = expressions
+        .lines
+        .joined()
+        .ignore_then(just(Token::Separator))
+        .ignore_then(name().or(just(Token::Mod).to("mod".to_string())));
+    let variable = name().then(segment.repeated()).map(|(first, rest)| {
+        let path = std::iter::once(first).chain(rest).collect::<Vec<_>>();
+        Expr::Variable(VariableName(path.join(".")))
+    });
+    let section = just(Token::Separator)
+        .ignore_then(name())
+        .map(|name| Expr::FieldSection(VariableName(name)));
+
+    // `(x: f x)` is a lambda of a single parameter, `(f x: Int)` an ascription
+    let shorthand = variable_name()
+        .then_ignore(just(Token::Declare))
+        .then(nested.clone())
+        .map(|(name, expr)| {
+            Expr::Lambda(DefRhs {
+                args: vec![DefArg {
+                    name: VariableName(name),
+                    ty: None,
+                    pattern: None,
+                }],
+                expr: Box::new(expr),
+            })
+        });
+    let ascription = nested
+        .clone()
+        .then(
+            just(Token::Declare)
+                .ignore_then(ty(&expressions.lines).map(decl_type))
+                .or_not(),
+        )
+        .map(|(expr, ty)| match ty {
+            Some(ty) => Expr::Ascription(Box::new(expr), ty),
+            None => expr,
+        });
+    let parens = shorthand
+        .or(ascription)
+        .delimited_by(just(Token::ParOpen), just(Token::ParClose));
+
+    // The elements of a list are separated by spaces, or by commas if there is one, where they
+    // can be any expression
+    let spread = just(Token::Spread)
+        .ignore_then(postfix.clone())
+        .map(|list| Expr::Spread(Box::new(list)));
+    let element = spread
+        .clone()
+        .or(binary(unary(postfix.clone(), postfix.clone()), true));
+    let list = element
+        .repeated()
+        .delimited_by(just(Token::ListOpen), just(Token::ListClose))
+        .map(|elements| Expr::Literal(Literal::List(elements)));
+    let comma_separated = separated_ahead(Token::Comma)
+        .ignore_then(
+            comma_list(spread.or(nested.clone()))
+                .delimited_by(just(Token::ListOpen), just(Token::ListClose)),
+        )
+        .map(|elements| Expr::Literal(Literal::List(elements)));
+    let qualifier = name()
+        .then_ignore(just(Token::Bind))
+        .then(nested.clone())
+        .map(|(name, list)| Qualifier::Generator(VariableName(name), list))
+        .or(nested.clone().map(Qualifier::Filter));
+    let comprehension = separated_ahead(Token::Alternative)
+        .ignore_then(just(Token::ListOpen))
+        .ignore_then(expressions.head.clone())
+        .then_ignore(just(Token::Alternative))
+        .then(comma_list(qualifier).at_least(1))
+        .then_ignore(just(Token::ListClose))
+        .map(|(expr, qualifiers)| {
+            Expr::Comprehension(Comprehension {
+                expr: Box::new(expr),
+                qualifiers,
+            })
+        });
+
+    let record = braces(
+        name()
+            .then_ignore(just(Token::Assign))
+            .then(nested.clone())
+            .map(|(name, expr)| Field {
+                name: VariableName(name),
+                expr,
+            }),
+    )
+    .map(|fields| Expr::Record(Record { fields }));
+
+    // `let x = 1` binds for the rest of the block, an `in` makes it an expression. Both are read
+    // by one parser, as trying one after the other parses nested blocks exponentially often.
+    let binding = just(Token::Let)
+        .ignore_then(let_items(expressions))
+        .then(just(Token::In).ignore_then(nested.clone()).or_not())
+        .try_map(|(mut items, expr), span| match expr {
+            Some(expr) => Ok(DoStatement::Run(Expr::LetIn(LetIns {
+                items,
+                expr: Box::new(expr),
+            }))),
+            None => match (items.pop(), items.is_empty()) {
+                (Some(LetIn::Def(def)), true) => {
+                    let expr = if def.rhs.args.is_empty() {
+                        *def.rhs.expr
+                    } else {
+                        Expr::Lambda(def.rhs)
+                    };
+                    Ok(DoStatement::Let(def.lhs, expr))
+                }
+                _ => Err(Simple::custom(span, "A let statement binds a single name")),
+            },
+        });
+    let statement = choice((
+        binding,
+        name()
+            .then_ignore(just(Token::Bind))
+            .then(nested.clone())
+            .map(|(name, expr)| DoStatement::Bind(VariableName(name), expr)),
+        filter(|token| *token != Token::Let)
+            .rewind()
+            .ignore_then(nested)
+            .map(DoStatement::Run),
+    ));
+    let block = just(Token::Do)
+        .ignore_then(
+            comma_list(statement)
+                .at_least(1)
+                .delimited_by(just(Token::BlockOpen), just(Token::BlockClose)),
+        )
+        .try_map(|mut statements, span| match statements.pop() {
+            Some(DoStatement::Run(result)) => Ok(Expr::Do(DoBlock {
+                statements,
+                result: Box::new(result),
+            })),
+            _ => Err(Simple::custom(span, "A do block ends with an expression")),
+        });
+
+    choice((
+        construct,
+        variable,
+        section,
+        literal().map(Expr::Literal),
+        unit().map(Expr::Literal),
+        parens,
+        comprehension,
+        comma_separated,
+        list,
+        record,
+        block,
+    ))
+    .boxed()
+}
+
+// Whether the tokens ahead are a `[`, followed by `separator` outside of brackets. Other tokens
+// are checked first, as the lookahead runs for every `[`.
+fn separated_ahead(separator: Token) -> impl Parser<Token, (), Error = Simple<Token>> + Clone {
+    let top = {
+        let separator = separator.clone();
+        filter(move |token| !bracket(token) && *token != separator)
+    };
+
+    just(Token::ListOpen)
+        .then(top.ignored().or(group(tree())).repeated())
+        .then(just(separator))
+        .ignored()
+        .rewind()
+}
+
+// Whether the tokens ahead start an item on a line of their own: a name or a list at the start of
+// a line, followed on the same line by names, literals and brackets up to a `=` or `:`, like
+// `fact 0 = 1`, `x: Int` or `[x, y]: List Int = pair`
+fn head(lines: &Lines) -> impl Parser<Token, (), Error = Simple<Token>> + Clone {
+    let token = filter(|token| {
+        matches!(
+            token,
+            Token::Ident(_)
+                | Token::Num(_)
+                | Token::Str(_)
+                | Token::Bytes(_)
+                | Token::Bool(_)
+                | Token::Separator
+        ) || *token == Token::Op("-".to_string())
+    });
+    let first = name().ignored().or(tree()
+        .repeated()
+        .delimited_by(just(Token::ListOpen), just(Token::ListClose))
+        .ignored());
+
+    lines
+        .at_start(true)
+        .ignore_then(first)
+        .then(
+            lines
+                .at_start(false)
+                .ignore_then(token.ignored().or(group(tree())))
+                .repeated(),
+        )
+        .then(one_of([Token::Assign, Token::Declare]))
+        .ignored()
+        .rewind()
+}
+
+// Any token, where brackets are read up to the bracket closing them
+fn tree() -> Recursive<'static, Token, (), Simple<Token>> {
+    recursive(|tree| filter(|token| !bracket(token)).ignored().or(group(tree)))
+}
+
+// Brackets and what is in them
+fn group(
+    tree: Recursive<'static, Token, (), Simple<Token>>,
+) -> impl Parser<Token, (), Error = Simple<Token>> + Clone {
+    let group = |tree: Recursive<'static, Token, (), Simple<Token>>, open, close| {
+        tree.repeated()
+            .delimited_by(just(open), just(close))
+            .ignored()
+    };
+    choice((
+        group(tree.clone(), Token::ParOpen, Token::ParClose),
+        group(tree.clone(), Token::ListOpen, Token::ListClose),
+        group(tree, Token::BlockOpen, Token::BlockClose),
+    ))
+}
+
+fn bracket(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::ParOpen
+            | Token::ParClose
+            | Token::ListOpen
+            | Token::ListClose
+            | Token::BlockOpen
+            | Token::BlockClose
+    )
+}
+
+// Succeeds without consuming anything where `parser` fails
+fn not<T>(
+    parser: impl Parser<Token, T, Error = Simple<Token>> + Clone,
+) -> impl Parser<Token, (), Error = Simple<Token>> + Clone {
+    parser.rewind().or_not().try_map(|found, span| match found {
+        Some(_) => Err(Simple::custom(span, "The next item starts here")),
+        None => Ok(()),
+    })
+}
+
+// The items of a `let` or of a `where`
+fn let_items(
+    expressions: &Expressions,
+) -> impl Parser<Token, Vec<LetIn>, Error = Simple<Token>> + Clone {
+    let expr = expressions.item.clone();
+    let lines = &expressions.lines;
+
+    let decl = crate::attribute::attribute()
+        .repeated()
+        .then(decl(lines))
+        .map(|(attributes, decl)| Decl { attributes, ..decl })
+        .then(just(Token::Assign).ignore_then(expr.clone()).or_not())
+        .map(|(decl, expr)| {
+            let def = expr.map(|expr| LetIn::Def(def_of(decl.lhs.0.clone(), expr)));
+            std::iter::once(LetIn::Decl(decl)).chain(def).collect()
+        });
+    let lhs = pattern()
+        .then(
+            just(Token::Declare)
+                .ignore_then(ty(lines).map(decl_type))
+                .or_not(),
+        )
+        .then_ignore(just(Token::Assign))
+        .try_map(|(pattern, ty), span| match (&pattern, &ty) {
+            (Pattern::Variable(_), None) => Err(Simple::custom(span, "Not a pattern binding")),
+            _ => Ok((pattern, ty)),
+        });
+    let binding = lhs.then(expr.clone()).map(|((pattern, ty), expr)| {
+        vec![LetIn::Pattern(PatternBinding {
+            pattern,
+            ty,
+            expr: Box::new(expr),
+        })]
+    });
+
+    choice((
+        decl,
+        def(expr.clone()).map(|def| vec![LetIn::Def(def)]),
+        equation(expr).map(|equation| vec![LetIn::Equation(equation)]),
+        binding,
+    ))
+    .repeated()
+    .flatten()
+}
+
+// `name : type`, with a where clause after
+fn decl(lines: &Lines) -> impl Parser<Token, Decl, Error = Simple<Token>> + Clone {
+    name()
+        .then_ignore(just(Token::Declare))
+        .then(ty(lines).map(decl_type))
+        .then(where_clause(false).or_not())
+        .map(|((lhs, rhs), whereclause)| Decl {
+            attributes: Vec::new(),
+            lhs: VariableName(lhs),
+            rhs,
+            whereclause,
+        })
+}
+
+// `where A: Show, B: Eq`. In front of the `=` of a type, the commas can be left out, so that
+// each bound can be on a line of its own.
+fn where_clause(
+    until_assign: bool,
+) -> impl Parser<Token, WhereClause, Error = Simple<Token>> + Clone {
+    let bound =
+        name()
+            .then_ignore(just(Token::Declare))
+            .then(path())
+            .map(|(type_name, trait_name)| Generic {
+                type_name: TypeName(type_name),
+                trait_name: TraitName(trait_name.join(".")),
+            });
+    let bounds = if until_assign {
+        bound
+            .then_ignore(just(Token::Comma).or_not())
+            .repeated()
+            .at_least(1)
+            .boxed()
+    } else {
+        bound.separated_by(just(Token::Comma)).at_least(1).boxed()
+    };
+
+    just(Token::Where).ignore_then(bounds).map(WhereClause)
+}
+
+// `name = expr`, where a lambda with parameters makes it a definition of a function
+fn def(expr: Expression) -> impl Parser<Token, Def, Error = Simple<Token>> + Clone {
+    name()
+        .then_ignore(just(Token::Assign))
+        .then(expr)
+        .map(|(name, expr)| def_of(name, expr))
+}
+
+fn def_of(name: String, expr: Expr) -> Def {
+    Def {
+        lhs: VariableName(name),
+        rhs: match expr {
+            Expr::Lambda(rhs) if !rhs.args.is_empty() => rhs,
+            expr => DefRhs {
+                args: Vec::new(),
+                expr: Box::new(expr),
+            },
+        },
+    }
+}
+
+// `fact 0 = 1`, where the patterns of the parameters need no parentheses if they are a single
+// name, literal, list or variant without members
+fn equation(expr: Expression) -> impl Parser<Token, Equation, Error = Simple<Token>> + Clone {
+    variable_name()
+        .then(pattern_atom().repeated().at_least(1))
+        .then_ignore(just(Token::Assign))
+        .then(expr)
+        .map(|((name, params), expr)| Equation {
+            lhs: VariableName(name),
+            params,
+            expr: Box::new(expr),
+        })
+}
+
+// `(pattern: type, ...)`
+fn params(lines: &Lines) -> impl Parser<Token, Vec<DefArg>, Error = Simple<Token>> + Clone {
+    parens(
+        pattern()
+            .then(just(Token::Declare).ignore_then(ty(lines)).or_not())
+            .map(|(pattern, ty)| DefArg::from_pattern(pattern, ty.map(def_arg_type))),
+    )
+}
+
+// A type as it is written, which the AST keeps as text
+enum Type {
+    // A name or path, or `()`
+    Name(String),
+    Apply(Box<Type>, Vec<Type>),
+    Func {
+        args: Vec<(Option<String>, Type)>,
+        retty: Box<Type>,
+        effects: Vec<String>,
+    },
+}
+
+impl Type {
+    fn text(&self) -> String {
+        match self {
+            Type::Name(name) => name.clone(),
+            Type::Apply(ty, args) => std::iter::once(ty.as_ref())
+                .chain(args)
+                .map(Type::argument)
+                .collect::<Vec<_>>()
+                .join(" "),
+            Type::Func {
+                args,
+                retty,
+                effects,
+            } => {
+                let args = args.iter().map(|(name, ty)| match name {
+                    Some(name) => format!("{}: {}", name, ty.text()),
+                    None => ty.text(),
+                });
+                let mut text = format!(
+                    "({}) -> {}",
+                    args.collect::<Vec<_>>().join(", "),
+                    retty.text()
+                );
+                if !effects.is_empty() {
+                    text = format!("{} with {}", text, effects.join(", "));
+                }
+                text
+            }
+        }
+    }
+
+    // The text of the type as an argument of another, in parentheses if it has parts
+    fn argument(&self) -> String {
+        match self {
+            Type::Name(name) if !name.contains(' ') => name.clone(),
+            ty => format!("({})", ty.text()),
+        }
+    }
+}
+
+fn decl_type(ty: Type) -> DeclType {
+    match ty {
+        Type::Func {
+            args,
+            retty,
+            effects,
+        } => DeclType::Func {
+            args: args
+                .into_iter()
+                .map(|(name, ty)| DeclArg {
+                    name: name.map(VariableName),
+                    ty: decl_type(ty),
+                })
+                .collect(),
+            retty: TypeName(retty.text()),
+            effects: effects.into_iter().map(TypeName).collect(),
+        },
+        ty => DeclType::TypeName(TypeName(ty.text())),
+    }
+}
+
+// The type of a parameter, which is a function type in the AST if all its parameters have a name
+fn def_arg_type(ty: Type) -> DefArgType {
+    match ty {
+        Type::Func {
+            args,
+            retty,
+            effects,
+        } if effects.is_empty() && args.iter().all(|(name, _)| name.is_some()) => {
+            DefArgType::Func {
+                args: args
+                    .into_iter()
+                    .filter_map(|(name, ty)| {
+                        Some(DefArg {
+                            name: VariableName(name?),
+                            ty: Some(def_arg_type(ty)),
+                            pattern: None,
+                        })
+                    })
+                    .collect(),
+                retty: TypeName(retty.text()),
+            }
+        }
+        ty => DefArgType::TypeName(TypeName(ty.text())),
+    }
+}
+
+// A type, like `List i64`, `()` or `(x: i64, String) -> Option i64 with Console`
+fn ty(lines: &Lines) -> Recursive<'static, Token, Type, Simple<Token>> {
+    let lines = lines.clone();
+    recursive(move |ty| {
+        let arrow = type_arrow(ty.clone());
+        type_atom(ty.clone())
+            .then(type_argument(&lines, ty).repeated())
+            .map(|(ty, args)| {
+                if args.is_empty() {
+                    ty
+                } else {
+                    Type::Apply(Box::new(ty), args)
+                }
+            })
+            .then(arrow.or_not())
+            .map(|(ty, arrow)| match arrow {
+                Some((retty, effects)) => Type::Func {
+                    args: vec![(None, ty)],
+                    retty: Box::new(retty),
+                    effects,
+                },
+                None => ty,
+            })
+    })
+}
+
+// `-> type with Effect, ...`, the return type of a function type and its effects
+fn type_arrow(
+    ty: Recursive<'static, Token, Type, Simple<Token>>,
+) -> impl Parser<Token, (Type, Vec<String>), Error = Simple<Token>> + Clone {
+    let effects = keyword("with").ignore_then(
+        path()
+            .map(|path| path.join("."))
+            .separated_by(just(Token::Comma))
+            .at_least(1),
+    );
+    just(Token::Arrow)
+        .ignore_then(ty)
+        .then(effects.or_not().map(Option::unwrap_or_default))
+}
+
+// A type that is an argument of another, and does not start the next item
+fn type_argument(
+    lines: &Lines,
+    ty: Recursive<'static, Token, Type, Simple<Token>>,
+) -> impl Parser<Token, Type, Error = Simple<Token>> + Clone {
+    not(head(lines))
+        .ignore_then(not(name().then(one_of([Token::Assign, Token::Declare]))))
+        .ignore_then(type_atom(ty))
+}
+
+// A type by name, or in parentheses: `()`, the parameters of a function type with its return
+// type, or a type on its own
+fn type_atom(
+    ty: Recursive<'static, Token, Type, Simple<Token>>,
+) -> impl Parser<Token, Type, Error = Simple<Token>> + Clone {
+    let arg = name()
+        .then_ignore(just(Token::Declare))
+        .or_not()
+        .then(ty.clone());
+    let parens =
+        parens(arg)
+            .then(type_arrow(ty).or_not())
+            .validate(|(mut args, arrow), span, emit| match arrow {
+                Some((retty, effects)) => Type::Func {
+                    args,
+                    retty: Box::new(retty),
+                    effects,
+                },
+                None => match (args.pop(), args.is_empty()) {
+                    (Some((None, ty)), true) => ty,
+                    (None, _) => Type::Name("()".to_string()),
+                    (Some(_), _) => {
+                        emit(Simple::custom(
+                            span,
+                            "Only the parameters of a function type are a list of types",
+                        ));
+                        Type::Name("()".to_string())
+                    }
+                },
+            });
+    // `with` starts the effects of a function type
+    let named = path().try_map(|path, span| match path[0].as_str() {
+        "with" => Err(Simple::custom(span, "Not a type")),
+        _ => Ok(Type::Name(path.join("."))),
+    });
+
+    named.or(parens)
+}
+
+// `use Std.List`, `use Std.List as L` or `use Std.List.*`
+fn use_item() -> impl Parser<Token, Import, Error = Simple<Token>> + Clone {
+    just(Token::Use)
+        .ignore_then(path())
+        .then(
+            just(Token::Separator)
+                .then(just(Token::Op("*".to_string())))
+                .or_not(),
+        )
+        .then(keyword("as").ignore_then(name()).or_not())
+        .map(|((path, glob), alias)| Import {
+            path,
+            alias,
+            glob: glob.is_some(),
+        })
+}
+
+// `type Bucket T where T: Debug = { element: T }`
+fn type_def(lines: &Lines) -> impl Parser<Token, TypeDef, Error = Simple<Token>> + Clone {
+    just(Token::Type)
+        .ignore_then(name())
+        .then(name().map(TypeName).repeated())
+        .then(where_clause(true).or_not())
+        .then_ignore(just(Token::Assign))
+        .then(braces(member(lines)))
+        .map(|(((name, params), generics), members)| TypeDef {
+            name: TypeName(name),
+            params,
+            members,
+            generics,
+        })
+}
+
+// `name: type`, a member of a type
+fn member(lines: &Lines) -> impl Parser<Token, DefArg, Error = Simple<Token>> + Clone {
+    name()
+        .then_ignore(just(Token::Declare))
+        .then(ty(lines))
+        .map(|(name, ty)| DefArg {
+            name: VariableName(name),
+            ty: Some(def_arg_type(ty)),
+            pattern: None,
+        })
+}
+
+// `enum Result O E = Ok O | Err E`, or with named members, like `Circle { radius: f64 }`
+fn enum_def(lines: &Lines) -> impl Parser<Token, EnumDef, Error = Simple<Token>> + Clone {
+    let members = braces(member(lines))
+        .map(|members| (members, Vec::new()))
+        .or(type_argument(lines, ty(lines))
+            .map(|ty| TypeName(ty.text()))
+            .repeated()
+            .map(|types| (Vec::new(), types)));
+    let variant = path()
+        .then(members)
+        .map(|(name, (members, types))| EnumTypeDef {
+            name: TypeName(name.join(".")),
+            members,
+            types,
+        });
+
+    just(Token::Enum)
+        .ignore_then(path())
+        .then(name().map(TypeName).repeated())
+        .then(where_clause(true).or_not())
+        .then_ignore(just(Token::Assign))
+        .then(variant.separated_by(just(Token::Alternative)).at_least(1))
+        .map(|(((name, params), whereclause), variants)| EnumDef {
+            name: TypeName(name.join(".")),
+            params,
+            variants,
+            whereclause,
+        })
+}
+
+// An item of a trait or an `impl`
+enum Member {
+    Type(TypeName),
+    AssocType(AssocType),
+    Decl(Decl),
+    Def(Def),
+}
+
+// `trait Eq = { eq: (Self, Self) -> Bool  ne = (a, b) -> !(eq a b) }`, with associated types like
+// `type Item`
+fn trait_def(
+    expr: Expression,
+    lines: &Lines,
+) -> impl Parser<Token, TraitDef, Error = Simple<Token>> + Clone {
+    let member = choice((
+        just(Token::Type)
+            .ignore_then(path())
+            .map(|name| Member::Type(TypeName(name.join(".")))),
+        decl(lines).map(Member::Decl),
+        def(expr).map(Member::Def),
+    ));
+
+    keyword("trait")
+        .ignore_then(path())
+        .then_ignore(just(Token::Assign))
+        .then(block(member))
+        .map(|(name, members)| {
+            let mut def = TraitDef {
+                name: TraitName(name.join(".")),
+                types: Vec::new(),
+                methods: Vec::new(),
+                defaults: Vec::new(),
+            };
+            for member in members {
+                match member {
+                    Member::Type(ty) => def.types.push(ty),
+                    Member::Decl(decl) => def.methods.push(decl),
+                    Member::Def(default) => def.defaults.push(default),
+                    Member::AssocType(_) => unreachable!("a trait declares its types"),
+                }
+            }
+            def
+        })
+}
+
+// `impl Eq on Point = { eq = (a, b) -> a.x == b.x }`, with associated types like
+// `type Item = i64`
+fn impl_def(
+    expr: Expression,
+    lines: &Lines,
+) -> impl Parser<Token, TraitImpl, Error = Simple<Token>> + Clone {
+    let member = choice((
+        just(Token::Type)
+            .ignore_then(path())
+            .then_ignore(just(Token::Assign))
+            .then(ty(lines))
+            .map(|(name, ty)| {
+                Member::AssocType(AssocType {
+                    name: TypeName(name.join(".")),
+                    ty: TypeName(ty.text()),
+                })
+            }),
+        decl(lines).map(Member::Decl),
+        def(expr).map(Member::Def),
+    ));
+
+    keyword("impl")
+        .ignore_then(path())
+        .then_ignore(keyword("on"))
+        .then(path())
+        .then_ignore(just(Token::Assign))
+        .then(block(member))
+        .map(|((trait_name, type_name), members)| {
+            let mut def = TraitImpl {
+                trait_name: TraitName(trait_name.join(".")),
+                type_name: TypeName(type_name.join(".")),
+                types: Vec::new(),
+                decls: Vec::new(),
+                methods: Vec::new(),
+            };
+            for member in members {
+                match member {
+                    Member::AssocType(ty) => def.types.push(ty),
+                    Member::Decl(decl) => def.decls.push(decl),
+                    Member::Def(method) => def.methods.push(method),
+                    Member::Type(_) => unreachable!("an impl defines its types"),
+                }
+            }
+            def
+        })
+}
+
+// `effect Console = { print: (String) -> ()  readLine: () -> String }`
+fn effect_def(lines: &Lines) -> impl Parser<Token, EffectDef, Error = Simple<Token>> + Clone {
+    keyword("effect")
+        .ignore_then(path())
+        .then_ignore(just(Token::Assign))
+        .then(block(decl(lines)))
+        .map(|(name, operations)| EffectDef {
+            name: TypeName(name.join(".")),
+            operations,
+        })
+}
+
+// `pattern Head x = List.Cons { x, _ }`
+fn synonym() -> impl Parser<Token, PatternSynonym, Error = Simple<Token>> + Clone {
+    keyword("pattern")
+        .ignore_then(path())
+        .then(name().map(VariableName).repeated())
+        .then_ignore(just(Token::Assign))
+        .then(pattern())
+        .map(|((name, params), pattern)| PatternSynonym {
+            name: TypeName(name.join(".")),
+            params,
+            pattern,
+        })
+}
+
+// A field of a pattern of a variant, or one of its members
+enum Entry {
+    Field(FieldPattern),
+    Member(Pattern),
+}
+
+/// A pattern, like `Some x`, `Circle { radius: r }` or `[x, ..rest]`
+pub fn pattern() -> impl Parser<Token, Pattern, Error = Simple<Token>> + Clone {
+    applied(pattern_atom())
+}
+
+// A variant with its members as arguments, like `Some x` or `Pair (Some x) _`, or an atom
+fn applied(
+    atom: impl Parser<Token, Pattern, Error = Simple<Token>> + Clone,
+) -> impl Parser<Token, Pattern, Error = Simple<Token>> + Clone {
+    constructor()
+        .then(atom.clone().repeated().at_least(1))
+        .map(|(path, members)| Pattern::Variant {
+            path: type_path(path),
+            members,
+        })
+        .or(atom)
+}
+
+// A pattern that is an argument without parentheses: `_`, a literal, a name, a variant with its
+// members in braces, a list, or any pattern in parentheses
+fn pattern_atom() -> Recursive<'static, Token, Pattern, Simple<Token>> {
+    recursive(|atom| {
+        let pattern = applied(atom);
+        let entry = name()
+            .then_ignore(just(Token::Declare))
+            .then(pattern.clone())
+            .map(|(name, pattern)| {
+                Entry::Field(FieldPattern {
+                    name: VariableName(name),
+                    pattern,
+                })
+            })
+            .or(pattern.clone().map(Entry::Member));
+        let variant = path()
+            .then(braces(entry).or_not())
+            .try_map(|(path, entries), span| variant_pattern(path, entries, span));
+        let element = just(Token::Spread)
+            .ignore_then(pattern.clone())
+            .map(|rest| (true, rest))
+            .or(pattern.clone().map(|element| (false, element)));
+        let list = comma_list(element)
+            .delimited_by(just(Token::ListOpen), just(Token::ListClose))
+            .try_map(list_pattern);
+
+        choice((
+            keyword("_").map(|_| Pattern::Wildcard),
+            literal().or(negative()).or(unit()).map(Pattern::Literal),
+            variant,
+            list,
+            pattern.delimited_by(just(Token::ParOpen), just(Token::ParClose)),
+        ))
+    })
+}
+
+fn variant_pattern(
+    path: Vec<String>,
+    entries: Option<Vec<Entry>>,
+    span: Span,
+) -> Result<Pattern, Simple<Token>> {
+    if entries.is_none() && path.len() == 1 && !path[0].starts_with(|c: char| c.is_uppercase()) {
+        return Ok(Pattern::Variable(VariableName(path.join("."))));
+    }
+
+    let path = type_path(path);
+    match entries {
+        None => Ok(Pattern::Variant {
+            path,
+            members: Vec::new(),
+        }),
+        // `Origin {}`, as the printer writes fields, not members
+        Some(entries) if entries.iter().all(|entry| matches!(entry, Entry::Field(_))) => {
+            let fields = entries.into_iter().filter_map(|entry| match entry {
+                Entry::Field(field) => Some(field),
+                Entry::Member(_) => None,
+            });
+            Ok(Pattern::Fields {
+                path,
+                fields: fields.collect(),
+            })
+        }
+        Some(entries) => {
+            let members = entries.into_iter().map(|entry| match entry {
+                Entry::Member(member) => Ok(member),
+                Entry::Field(_) => Err(Simple::custom(
+                    span.clone(),
+                    "The members of a variant are either all named or none are",
+                )),
+            });
+            Ok(Pattern::Variant {
+                path,
+                members: members.collect::<Result<_, _>>()?,
+            })
+        }
+    }
+}
+
+// The elements of a list pattern, each with whether it is the rest, which only the last can be
+fn list_pattern(mut elements: Vec<(bool, Pattern)>, span: Span) -> Result<Pattern, Simple<Token>> {
+    let rest = match elements.last() {
+        Some((true, _)) => elements.pop().map(|(_, rest)| Box::new(rest)),
+        _ => None,
+    };
+    if elements.iter().any(|(rest, _)| *rest) {
+        return Err(Simple::custom(
+            span,
+            "Only the last element of a list can be the rest",
+        ));
+    }
+
+    Ok(Pattern::List {
+        elements: elements.into_iter().map(|(_, element)| element).collect(),
+        rest,
+    })
+}
+
+// A literal that is a single token
+fn literal() -> impl Parser<Token, Literal, Error = Simple<Token>> + Clone {
+    let number = select! { Token::Num(digits) => digits }
+        .try_map(|digits, span| number(&digits).ok_or_else(|| invalid_number(span)));
+    let other = select! {
+        Token::Str(value) => Literal::Str(Str { value }),
+        Token::Bytes(value) => Literal::Bytes(Bytes { value }),
+        Token::Bool(value) => Literal::Bool(Bool { value }),
+    };
+    number.or(other)
+}
+
+// A number with a `-` in front, which is a literal, and not the operator
+fn negative() -> impl Parser<Token, Literal, Error = Simple<Token>> + Clone {
+    just(Token::Op("-".to_string()))
+        .ignore_then(select! { Token::Num(digits) => digits })
+        .try_map(|digits, span| number(&format!("-{}", digits)).ok_or_else(|| invalid_number(span)))
+}
+
+fn unit() -> impl Parser<Token, Literal, Error = Simple<Token>> + Clone {
+    just(Token::ParOpen)
+        .then(just(Token::ParClose))
+        .map(|_| Literal::Unit)
+}
+
+fn number(digits: &str) -> Option<Literal> {
+    if digits.contains('.') {
+        let value = digits.parse().ok()?;
+        Some(Literal::Float(Float { value }))
+    } else {
+        let value = IntegerValue::from_literal(digits)?;
+        Some(Literal::Integer(Integer { value }))
+    }
+}
+
+fn invalid_number(span: Span) -> Simple<Token> {
+    Simple::custom(span, "Not a number")
+}
+
+// A path whose last name starts uppercase, like `Shape.Circle`, which names a variant
+fn constructor() -> impl Parser<Token, Vec<String>, Error = Simple<Token>> + Clone {
+    path().try_map(|path, span| match path.last() {
+        Some(name) if name.starts_with(|c: char| c.is_uppercase()) => Ok(path),
+        _ => Err(Simple::custom(span, "Not a variant")),
+    })
+}
+
+fn type_path(path: Vec<String>) -> TypePath {
+    TypePath(path.into_iter().map(TypeName).collect())
+}
+
+// Names separated by `.`, like `Std.Int.add`, where the last can also be `mod`
+fn path() -> impl Parser<Token, Vec<String>, Error = Simple<Token>> + Clone {
+    let segment = name().or(just(Token::Mod).to("mod".to_string()));
+    name()
+        .then(just(Token::Separator).ignore_then(segment).repeated())
+        .map(|(first, mut rest)| {
+            rest.insert(0, first);
+            rest
+        })
+}
+
+fn name() -> impl Parser<Token, String, Error = Simple<Token>> + Clone {
+    select! {
+        Token::Ident(name) if name != "_" && !KEYWORDS.contains(&name.as_str()) => name,
+    }
+}
+
+// A name that starts lowercase, which is a variable and not a type or a variant
+fn variable_name() -> impl Parser<Token, String, Error = Simple<Token>> + Clone {
+    name().try_map(
+        |name, span| match name.starts_with(|c: char| c.is_lowercase()) {
+            true => Ok(name),
+            false => Err(Simple::custom(span, "Not a variable")),
+        },
+    )
+}
+
+fn keyword(keyword: &str) -> impl Parser<Token, Token, Error = Simple<Token>> + Clone {
+    just(Token::Ident(keyword.to_string()))
+}
+
+// Items separated by `,`, which may follow the last one
+fn comma_list<T, P: Parser<Token, T, Error = Simple<Token>> + Clone>(
+    item: P,
+) -> SeparatedBy<P, Just<Token, Token, Simple<Token>>, Token> {
+    item.separated_by(just(Token::Comma)).allow_trailing()
+}
+
+// `(item, ...)`
+fn parens<T>(
+    item: impl Parser<Token, T, Error = Simple<Token>> + Clone,
+) -> impl Parser<Token, Vec<T>, Error = Simple<Token>> + Clone {
+    comma_list(item).delimited_by(just(Token::ParOpen), just(Token::ParClose))
+}
+
+// `{ item, ... }`
+fn braces<T>(
+    item: impl Parser<Token, T, Error = Simple<Token>> + Clone,
+) -> impl Parser<Token, Vec<T>, Error = Simple<Token>> + Clone {
+    comma_list(item).delimited_by(just(Token::BlockOpen), just(Token::BlockClose))
+}
+
+// `{ item item ... }`, the items of a trait, an `impl` or an effect
+fn block<T>(
+    item: impl Parser<Token, T, Error = Simple<Token>> + Clone,
+) -> impl Parser<Token, Vec<T>, Error = Simple<Token>> + Clone {
+    item.repeated()
+        .delimited_by(just(Token::BlockOpen), just(Token::BlockClose))
+}
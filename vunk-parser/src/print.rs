@@ -0,0 +1,570 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Printing ASTs as code, so that parsing the code gives the AST back
+//!
+//! Items are printed one per line, and every expression on the line of its item, as laying code
+//! out is the job of the formatter. The items of a `let` and of a `where` are the exception, each
+//! is put on a line of its own without indenting it, as an item ends where the next one starts a
+//! line, like `f n = ...` after `f 0 = 1`. Subexpressions are put in parentheses where they would
+//! otherwise extend into the code after them, like a `match` in the condition of an `if`, or
+//! where the order of operations would be unclear, like an application in an operand.
+//!
+//! The binary not cannot be written in code yet, it is printed as `~`, which the lexer has no
+//! token for.
+
+use std::fmt::Write;
+
+use crate::ast::attribute::Attribute;
+use crate::ast::attribute::Meta;
+use crate::ast::attribute::MetaValue;
+use crate::ast::comprehension::Qualifier;
+use crate::ast::decl::Decl;
+use crate::ast::decl::DeclArg;
+use crate::ast::decl::DeclType;
+use crate::ast::def::Def;
+use crate::ast::def::DefArg;
+use crate::ast::def::DefArgType;
+use crate::ast::def::DefRhs;
+use crate::ast::def::EnumDef;
+use crate::ast::def::Equation;
+use crate::ast::def::TypeDef;
+use crate::ast::doblock::DoStatement;
+use crate::ast::expr::Expr;
+use crate::ast::generic::WhereClause;
+use crate::ast::import::Import;
+use crate::ast::letin::LetIn;
+use crate::ast::literal::IntegerValue;
+use crate::ast::literal::Literal;
+use crate::ast::name::TypePath;
+use crate::ast::op::BinaryOp;
+use crate::ast::op::UnaryOp;
+use crate::ast::pattern::Pattern;
+use crate::ast::program::Program;
+use crate::ast::record::Field;
+
+/// A program as code, its items separated by empty lines
+pub fn program(program: &Program) -> String {
+    program
+        .expr
+        .iter()
+        .map(|expr| format!("{}\n", self::expr(expr)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An expression as code, on a single line
+pub fn expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Variable(name) => name.0.clone(),
+        Expr::FieldSection(name) => format!(".{}", name.0),
+        Expr::Unary(op, expr) => format!("{}{}", unary_op(op), atom(expr)),
+        Expr::Binary(op, lhs, rhs) => format!("{} {} {}", atom(lhs), binary_op(op), atom(rhs)),
+        Expr::Literal(literal) => self::literal(literal),
+        Expr::Apply(function, args) => {
+            let args = args.iter().map(atom).collect::<Vec<_>>();
+            format!("{} {}", atom(function), args.join(" "))
+        }
+        Expr::Lambda(rhs) => lambda(rhs),
+        Expr::LetIn(letin) => {
+            format!(
+                "let{}\nin {}",
+                let_items(&letin.items),
+                self::expr(&letin.expr)
+            )
+        }
+        Expr::Where(bindings) => {
+            format!(
+                "{} where {{{}\n}}",
+                closed(&bindings.expr),
+                let_items(&bindings.items)
+            )
+        }
+        Expr::IfElse(ifelse) => format!(
+            "if {} then {} else {}",
+            closed(&ifelse.condition),
+            closed(&ifelse.tru),
+            self::expr(&ifelse.fals)
+        ),
+        Expr::MatchWhen(matchwhen) => {
+            let mut code = format!("match {}", closed(&matchwhen.expr));
+            for arm in matchwhen.arms.iter() {
+                let _ = write!(code, " when {}", pattern(&arm.pattern));
+                if let Some(guard) = &arm.guard {
+                    let _ = write!(code, " when {}", closed(guard));
+                }
+                let _ = write!(code, " -> {}", closed(&arm.expr));
+            }
+            if let Some(otherwise) = &matchwhen.otherwise {
+                let _ = write!(code, " else {}", self::expr(otherwise));
+            }
+            code
+        }
+        Expr::Do(block) => {
+            let statements = block
+                .statements
+                .iter()
+                .map(|statement| match statement {
+                    DoStatement::Bind(name, expr) => format!("{} <- {}", name.0, self::expr(expr)),
+                    DoStatement::Let(name, expr) => {
+                        format!("let {} = {}", name.0, self::expr(expr))
+                    }
+                    DoStatement::Run(expr) => self::expr(expr),
+                })
+                .chain(std::iter::once(self::expr(&block.result)))
+                .collect::<Vec<_>>();
+            format!("do {{ {} }}", statements.join(", "))
+        }
+        Expr::Comprehension(comprehension) => {
+            let qualifiers = comprehension
+                .qualifiers
+                .iter()
+                .map(|qualifier| match qualifier {
+                    Qualifier::Generator(name, list) => {
+                        format!("{} <- {}", name.0, self::expr(list))
+                    }
+                    Qualifier::Filter(condition) => self::expr(condition),
+                })
+                .collect::<Vec<_>>();
+            // A `|` in the expression would end it
+            let expr = match &*comprehension.expr {
+                expr @ Expr::Binary(BinaryOp::BitOr, ..) => format!("({})", self::expr(expr)),
+                expr => closed(expr),
+            };
+            format!("[{} | {}]", expr, qualifiers.join(", "))
+        }
+        Expr::Record(record) if record.fields.is_empty() => "{}".to_string(),
+        Expr::Record(record) => {
+            let fields = record
+                .fields
+                .iter()
+                .map(|field| format!("{} = {}", field.name.0, self::expr(&field.expr)))
+                .collect::<Vec<_>>();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        Expr::Construct(construct) => {
+            let path = type_path(&construct.path);
+            if construct.fields.is_empty() {
+                format!("{} {{}}", path)
+            } else {
+                let fields = construct.fields.iter().map(field).collect::<Vec<_>>();
+                format!("{} {{ {} }}", path, fields.join(", "))
+            }
+        }
+        Expr::Spread(list) => format!("..{}", atom(list)),
+        Expr::Lazy(expr) => format!("lazy {}", atom(expr)),
+        Expr::Try(expr) => format!("{}?", atom(expr)),
+        // `(x: T)` would be a lambda
+        Expr::Ascription(expr, ty) => match &**expr {
+            Expr::Variable(name) if !name.0.contains('.') => {
+                format!("(({}): {})", name.0, decl_type(ty))
+            }
+            expr => format!("({}: {})", self::expr(expr), decl_type(ty)),
+        },
+        Expr::Use(import) => self::import(import),
+        Expr::Decl(decl) => self::decl(decl),
+        Expr::Def(def) => self::def(def, self::expr),
+        Expr::Equation(equation) => self::equation(equation, self::expr),
+        Expr::Type(def) => type_def(def),
+        Expr::Enum(def) => enum_def(def),
+        Expr::Trait(def) => {
+            let items = def
+                .types
+                .iter()
+                .map(|ty| format!("type {}", ty.0))
+                .chain(def.methods.iter().map(self::decl))
+                .chain(
+                    def.defaults
+                        .iter()
+                        .map(|default| self::def(default, closed)),
+                );
+            format!("trait {} = {}", def.name.0, block(items))
+        }
+        Expr::Impl(def) => {
+            let items = def
+                .types
+                .iter()
+                .map(|ty| format!("type {} = {}", ty.name.0, ty.ty.0))
+                .chain(def.decls.iter().map(self::decl))
+                .chain(def.methods.iter().map(|method| self::def(method, closed)));
+            format!(
+                "impl {} on {} = {}",
+                def.trait_name.0,
+                def.type_name.0,
+                block(items)
+            )
+        }
+        Expr::Effect(def) => {
+            let items = def.operations.iter().map(self::decl);
+            format!("effect {} = {}", def.name.0, block(items))
+        }
+        Expr::PatternSynonym(synonym) => {
+            let mut code = format!("pattern {}", synonym.name.0);
+            for param in synonym.params.iter() {
+                let _ = write!(code, " {}", param.0);
+            }
+            let _ = write!(code, " = {}", pattern(&synonym.pattern));
+            code
+        }
+    }
+}
+
+// The items of a trait or an `impl`, in braces
+fn block(items: impl Iterator<Item = String>) -> String {
+    let items = items.collect::<Vec<_>>();
+    if items.is_empty() {
+        "{}".to_string()
+    } else {
+        format!("{{ {} }}", items.join(" "))
+    }
+}
+
+fn import(import: &Import) -> String {
+    let mut code = format!("use {}", import.path.join("."));
+    if import.glob {
+        code.push_str(".*");
+    }
+    if let Some(alias) = &import.alias {
+        let _ = write!(code, " as {}", alias);
+    }
+    code
+}
+
+fn type_def(def: &TypeDef) -> String {
+    let mut code = format!("type {}", def.name.0);
+    for param in def.params.iter() {
+        let _ = write!(code, " {}", param.0);
+    }
+    if let Some(whereclause) = &def.generics {
+        let _ = write!(code, " {}", where_clause(whereclause));
+    }
+    if def.members.is_empty() {
+        code.push_str(" = {}");
+    } else {
+        let _ = write!(code, " = {{ {} }}", def_args(&def.members));
+    }
+    code
+}
+
+// A field of a value of a type, just its name if its value is a variable of that name
+fn field(field: &Field) -> String {
+    match &field.expr {
+        Expr::Variable(name) if name.0 == field.name.0 => name.0.clone(),
+        expr => format!("{}: {}", field.name.0, self::expr(expr)),
+    }
+}
+
+fn enum_def(def: &EnumDef) -> String {
+    let variants = def.variants.iter().map(|variant| {
+        if !variant.members.is_empty() {
+            format!("{} {{ {} }}", variant.name.0, def_args(&variant.members))
+        } else {
+            let types = variant
+                .types
+                .iter()
+                .map(|ty| format!(" {}", type_argument(&ty.0)));
+            format!("{}{}", variant.name.0, types.collect::<String>())
+        }
+    });
+    let mut code = format!("enum {}", def.name.0);
+    for param in def.params.iter() {
+        let _ = write!(code, " {}", param.0);
+    }
+    if let Some(whereclause) = &def.whereclause {
+        let _ = write!(code, " {}", where_clause(whereclause));
+    }
+    let _ = write!(code, " = {}", variants.collect::<Vec<_>>().join(" | "));
+    code
+}
+
+// An expression that is not followed by an operator or an argument
+fn atom(expr: &Expr) -> String {
+    match expr {
+        Expr::Variable(_) | Expr::FieldSection(_) | Expr::Ascription(..) => self::expr(expr),
+        Expr::Literal(literal) if !negative(literal) => self::expr(expr),
+        _ => format!("({})", self::expr(expr)),
+    }
+}
+
+// An expression that does not extend into the code after it
+fn closed(expr: &Expr) -> String {
+    match expr {
+        Expr::Lambda(_)
+        | Expr::LetIn(_)
+        | Expr::IfElse(_)
+        | Expr::MatchWhen(_)
+        | Expr::Use(_)
+        | Expr::Decl(_)
+        | Expr::Def(_)
+        | Expr::Equation(_)
+        | Expr::Enum(_)
+        | Expr::Trait(_)
+        | Expr::Impl(_)
+        | Expr::Effect(_)
+        | Expr::PatternSynonym(_) => format!("({})", self::expr(expr)),
+        _ => self::expr(expr),
+    }
+}
+
+// A definition, whose body is printed with `body`
+fn def(def: &Def, body: fn(&Expr) -> String) -> String {
+    if def.rhs.args.is_empty() {
+        format!("{} = {}", def.lhs.0, body(&def.rhs.expr))
+    } else {
+        let args = def_args(&def.rhs.args);
+        format!("{} = ({}) -> {}", def.lhs.0, args, body(&def.rhs.expr))
+    }
+}
+
+// An equation, whose body is printed with `body`
+fn equation(equation: &Equation, body: fn(&Expr) -> String) -> String {
+    let params = equation.params.iter().map(pattern_atom).collect::<Vec<_>>();
+    format!(
+        "{} {} = {}",
+        equation.lhs.0,
+        params.join(" "),
+        body(&equation.expr)
+    )
+}
+
+// The items of a `let` or a `where`, each on a line of its own
+fn let_items(items: &[LetIn]) -> String {
+    items
+        .iter()
+        .map(|item| format!("\n{}", let_item(item)))
+        .collect()
+}
+
+fn let_item(item: &LetIn) -> String {
+    match item {
+        LetIn::Decl(decl) => self::decl(decl),
+        LetIn::Def(def) => self::def(def, closed),
+        LetIn::Equation(equation) => self::equation(equation, closed),
+        LetIn::Pattern(binding) => {
+            let lhs = pattern(&binding.pattern);
+            match &binding.ty {
+                Some(ty) => format!("{}: {} = {}", lhs, decl_type(ty), closed(&binding.expr)),
+                None => format!("{} = {}", lhs, closed(&binding.expr)),
+            }
+        }
+    }
+}
+
+fn lambda(rhs: &DefRhs) -> String {
+    format!("({}) -> {}", def_args(&rhs.args), expr(&rhs.expr))
+}
+
+fn def_args(args: &[DefArg]) -> String {
+    let args = args.iter().map(|arg| {
+        let name = match &arg.pattern {
+            Some(destructured) => pattern(destructured),
+            None => arg.name.0.clone(),
+        };
+        match &arg.ty {
+            Some(ty) => format!("{}: {}", name, def_arg_type(ty)),
+            None => name,
+        }
+    });
+    args.collect::<Vec<_>>().join(", ")
+}
+
+fn def_arg_type(ty: &DefArgType) -> String {
+    match ty {
+        DefArgType::TypeName(name) => name.0.clone(),
+        DefArgType::Func { args, retty } => format!("({}) -> {}", def_args(args), retty.0),
+    }
+}
+
+/// An attribute as code, like `@cfg(not(debug))`
+pub fn attribute(attribute: &Attribute) -> String {
+    if attribute.args.is_empty() {
+        return format!("@{}", attribute.name);
+    }
+    format!("@{}({})", attribute.name, metas(&attribute.args))
+}
+
+fn metas(metas: &[Meta]) -> String {
+    metas.iter().map(meta).collect::<Vec<_>>().join(", ")
+}
+
+fn meta(meta: &Meta) -> String {
+    match meta {
+        Meta::Str(value) => format!("\"{}\"", value),
+        Meta::Named { name, value: None } => name.clone(),
+        Meta::Named {
+            name,
+            value: Some(MetaValue::Str(value)),
+        } => format!("{} = \"{}\"", name, value),
+        Meta::Named {
+            name,
+            value: Some(MetaValue::List(list)),
+        } => format!("{}({})", name, metas(list)),
+    }
+}
+
+fn decl(decl: &Decl) -> String {
+    let mut code = String::new();
+    for attribute in decl.attributes.iter() {
+        let _ = write!(code, "{} ", self::attribute(attribute));
+    }
+    let _ = write!(code, "{} : {}", decl.lhs.0, decl_type(&decl.rhs));
+    if let Some(whereclause) = &decl.whereclause {
+        let _ = write!(code, " {}", where_clause(whereclause));
+    }
+    code
+}
+
+fn decl_type(ty: &DeclType) -> String {
+    match ty {
+        DeclType::TypeName(name) => name.0.clone(),
+        DeclType::Func {
+            args,
+            retty,
+            effects,
+        } => {
+            let args = args.iter().map(decl_arg).collect::<Vec<_>>();
+            let mut code = format!("({}) -> {}", args.join(", "), retty.0);
+            if !effects.is_empty() {
+                let effects = effects.iter().map(|effect| effect.0.as_str());
+                let _ = write!(code, " with {}", effects.collect::<Vec<_>>().join(", "));
+            }
+            code
+        }
+    }
+}
+
+fn decl_arg(arg: &DeclArg) -> String {
+    match &arg.name {
+        Some(name) => format!("{}: {}", name.0, decl_type(&arg.ty)),
+        None => decl_type(&arg.ty),
+    }
+}
+
+fn where_clause(whereclause: &WhereClause) -> String {
+    let bounds = whereclause
+        .0
+        .iter()
+        .map(|generic| format!("{}: {}", generic.type_name.0, generic.trait_name.0));
+    format!("where {}", bounds.collect::<Vec<_>>().join(", "))
+}
+
+pub fn pattern(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Variant { path, members } if !members.is_empty() => {
+            let members = members.iter().map(pattern_atom).collect::<Vec<_>>();
+            format!("{} {}", type_path(path), members.join(" "))
+        }
+        pattern => pattern_atom(pattern),
+    }
+}
+
+// A pattern that is a member of a variant, in parentheses if it has members itself
+fn pattern_atom(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Variable(name) => name.0.clone(),
+        Pattern::Literal(value) => literal(value),
+        Pattern::Variant { path, members } if members.is_empty() => type_path(path),
+        Pattern::Fields { path, fields } => {
+            let fields = fields
+                .iter()
+                .map(|field| format!("{}: {}", field.name.0, self::pattern(&field.pattern)))
+                .collect::<Vec<_>>();
+            format!("{} {{ {} }}", type_path(path), fields.join(", "))
+        }
+        Pattern::List { elements, rest } => {
+            let elements = elements
+                .iter()
+                .map(self::pattern)
+                .chain(rest.iter().map(|rest| format!("..{}", self::pattern(rest))))
+                .collect::<Vec<_>>();
+            format!("[{}]", elements.join(", "))
+        }
+        pattern => format!("({})", self::pattern(pattern)),
+    }
+}
+
+// A type as the argument of another, in parentheses if it has parts
+fn type_argument(ty: &str) -> String {
+    if ty.contains(' ') {
+        format!("({})", ty)
+    } else {
+        ty.to_string()
+    }
+}
+
+fn type_path(path: &TypePath) -> String {
+    let path = path.0.iter().map(|name| name.0.as_str());
+    path.collect::<Vec<_>>().join(".")
+}
+
+fn literal(literal: &Literal) -> String {
+    match literal {
+        Literal::Unit => "()".to_string(),
+        Literal::Bool(b) => b.value.to_string(),
+        Literal::Integer(integer) => match &integer.value {
+            IntegerValue::I8(i) => i.to_string(),
+            IntegerValue::I16(i) => i.to_string(),
+            IntegerValue::I32(i) => i.to_string(),
+            IntegerValue::I64(i) => i.to_string(),
+            IntegerValue::U8(u) => u.to_string(),
+            IntegerValue::U16(u) => u.to_string(),
+            IntegerValue::U32(u) => u.to_string(),
+            IntegerValue::U64(u) => u.to_string(),
+            IntegerValue::Big(big) => big.to_string(),
+        },
+        // Debug, as Display prints `1.0` as `1`
+        Literal::Float(float) => format!("{:?}", float.value),
+        Literal::Str(s) => format!("\"{}\"", s.value),
+        Literal::Bytes(bytes) => {
+            let hex = bytes.value.iter().map(|byte| format!("{:02x}", byte));
+            format!("x\"{}\"", hex.collect::<String>())
+        }
+        Literal::List(elements) => {
+            let elements = elements
+                .iter()
+                .map(|element| match element {
+                    Expr::Spread(_) => expr(element),
+                    element => atom(element),
+                })
+                .collect::<Vec<_>>();
+            format!("[{}]", elements.join(" "))
+        }
+    }
+}
+
+// Whether a literal starts with a `-`, which would be taken for an operator
+fn negative(literal: &Literal) -> bool {
+    matches!(literal, Literal::Integer(_) | Literal::Float(_))
+        && self::literal(literal).starts_with('-')
+}
+
+fn unary_op(op: &UnaryOp) -> &'static str {
+    match op {
+        UnaryOp::BinaryNot => "~",
+        UnaryOp::LogicalNot => "!",
+    }
+}
+
+fn binary_op(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Rem => "%",
+        BinaryOp::Eq => "==",
+        BinaryOp::NotEq => "!=",
+        BinaryOp::Less => "<",
+        BinaryOp::LessEq => "<=",
+        BinaryOp::More => ">",
+        BinaryOp::MoreEq => ">=",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::LogicalAnd => "&&",
+        BinaryOp::BitOr => "|",
+        BinaryOp::LogicalOr => "||",
+        BinaryOp::BitXor => "^",
+        BinaryOp::Join => "++",
+    }
+}
@@ -0,0 +1,257 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Checking `impl`s against the traits they implement
+//!
+//! A trait declares its methods, and may define some of them in terms of the others, like `ne` in
+//! terms of `eq`:
+//!
+//! ```text
+//! trait Eq =
+//!     { eq: (Self, Self) -> Bool
+//!       ne: (Self, Self) -> Bool
+//!       ne = (a, b) -> !(eq a b)
+//!     }
+//! ```
+//!
+//! An `impl` has to define the methods without such a default, and may define the others.
+//! Traits that are not defined in the program, like the ones of the standard library, are not
+//! checked.
+//!
+//! A trait may also declare associated types, which every `impl` defines, and which signatures
+//! name by projection, like `Self.Item` in the trait and `Collection.Item` outside of it:
+//!
+//! ```text
+//! trait Collection =
+//!     { type Item
+//!       first: (Self) -> Option Self.Item
+//!     }
+//!
+//! impl Collection on Bag =
+//!     { type Item = i64
+//!       first = (bag) -> List.head bag.items
+//!     }
+//! ```
+//!
+//! [`project`] resolves a projection for the type of an `impl`, so `Collection.Item` on `Bag` is
+//! `i64`.
+
+use std::collections::BTreeMap;
+
+use crate::ast::decl::Decl;
+use crate::ast::decl::DeclType;
+use crate::ast::expr::Expr;
+use crate::ast::name::TypeName;
+use crate::ast::program::Program;
+use crate::ast::traitdef::TraitDef;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TraitError {
+    #[error("impl {trait_name} on {type_name} does not define {methods}, which have no default")]
+    MissingMethods {
+        trait_name: String,
+        type_name: String,
+        methods: String,
+    },
+
+    #[error("impl {trait_name} on {type_name} defines '{method}', which is not a method of it")]
+    UnknownMethod {
+        trait_name: String,
+        type_name: String,
+        method: String,
+    },
+
+    #[error("The default of '{method}' of {trait_name} is not declared as a method")]
+    UndeclaredDefault { trait_name: String, method: String },
+
+    #[error("impl {trait_name} on {type_name} does not define the associated type {ty}")]
+    MissingType {
+        trait_name: String,
+        type_name: String,
+        ty: String,
+    },
+
+    #[error("impl {trait_name} on {type_name} defines {ty}, which is not a type of the trait")]
+    UnknownType {
+        trait_name: String,
+        type_name: String,
+        ty: String,
+    },
+
+    #[error("{projection} is not an associated type of {trait_name}")]
+    UnknownProjection {
+        trait_name: String,
+        projection: String,
+    },
+}
+
+/// Check that every `impl` of a trait of the program defines the methods of the trait that have
+/// no default and its associated types, and nothing the trait does not have
+///
+/// Projections of the traits of the program, like `Collection.Item`, have to name one of their
+/// associated types.
+pub fn check(program: &Program) -> Result<(), TraitError> {
+    let mut traits = BTreeMap::new();
+    for expr in program.expr.iter() {
+        if let Expr::Trait(def) = expr {
+            check_defaults(def)?;
+            traits.insert(def.name.0.as_str(), def);
+        }
+    }
+
+    for expr in program.expr.iter() {
+        match expr {
+            Expr::Decl(decl) => check_projections(&traits, None, decl)?,
+            Expr::Trait(def) => def
+                .methods
+                .iter()
+                .try_for_each(|decl| check_projections(&traits, Some(def), decl))?,
+            Expr::Impl(def) => def
+                .decls
+                .iter()
+                .try_for_each(|decl| check_projections(&traits, None, decl))?,
+            _ => {}
+        }
+    }
+
+    for expr in program.expr.iter() {
+        let def = match expr {
+            Expr::Impl(def) => def,
+            _ => continue,
+        };
+        let trait_def = match traits.get(def.trait_name.0.as_str()) {
+            Some(trait_def) => trait_def,
+            None => continue,
+        };
+
+        if let Some(ty) = def
+            .types
+            .iter()
+            .find(|ty| !trait_def.types.contains(&ty.name))
+        {
+            return Err(TraitError::UnknownType {
+                trait_name: def.trait_name.0.clone(),
+                type_name: def.type_name.0.clone(),
+                ty: ty.name.0.clone(),
+            });
+        }
+        if let Some(ty) = trait_def
+            .types
+            .iter()
+            .find(|name| !def.types.iter().any(|ty| ty.name == **name))
+        {
+            return Err(TraitError::MissingType {
+                trait_name: def.trait_name.0.clone(),
+                type_name: def.type_name.0.clone(),
+                ty: ty.0.clone(),
+            });
+        }
+
+        let defined = |name: &str| def.methods.iter().any(|method| method.lhs.0 == name);
+        if let Some(method) = def.methods.iter().find(|method| {
+            !trait_def
+                .methods
+                .iter()
+                .any(|decl| decl.lhs.0 == method.lhs.0)
+        }) {
+            return Err(TraitError::UnknownMethod {
+                trait_name: def.trait_name.0.clone(),
+                type_name: def.type_name.0.clone(),
+                method: method.lhs.0.clone(),
+            });
+        }
+
+        let missing = trait_def
+            .methods
+            .iter()
+            .map(|decl| decl.lhs.0.as_str())
+            .filter(|name| !defined(name) && !has_default(trait_def, name))
+            .map(|name| format!("'{}'", name))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(TraitError::MissingMethods {
+                trait_name: def.trait_name.0.clone(),
+                type_name: def.type_name.0.clone(),
+                methods: missing.join(", "),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_defaults(def: &TraitDef) -> Result<(), TraitError> {
+    for default in def.defaults.iter() {
+        if !def.methods.iter().any(|decl| decl.lhs.0 == default.lhs.0) {
+            return Err(TraitError::UndeclaredDefault {
+                trait_name: def.name.0.clone(),
+                method: default.lhs.0.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn has_default(def: &TraitDef, name: &str) -> bool {
+    def.defaults.iter().any(|default| default.lhs.0 == name)
+}
+
+/// The type a projection, like `Collection.Item`, stands for on a type, like `i64` for the
+/// `impl Collection on Bag` with `type Item = i64`
+///
+/// `None` if the type has no `impl` of the trait in the program, or it does not define the type.
+pub fn project<'a>(
+    program: &'a Program,
+    projection: &str,
+    type_name: &str,
+) -> Option<&'a TypeName> {
+    let (trait_name, item) = projection.rsplit_once('.')?;
+    program.expr.iter().find_map(|expr| match expr {
+        Expr::Impl(def) if def.trait_name.0 == trait_name && def.type_name.0 == type_name => def
+            .types
+            .iter()
+            .find(|ty| ty.name.0 == item)
+            .map(|ty| &ty.ty),
+        _ => None,
+    })
+}
+
+// Check the projections in a declaration, where `Self` is the trait being defined, if any
+fn check_projections(
+    traits: &BTreeMap<&str, &TraitDef>,
+    this: Option<&TraitDef>,
+    decl: &Decl,
+) -> Result<(), TraitError> {
+    let mut names = Vec::new();
+    type_names(&decl.rhs, &mut names);
+    for name in names {
+        let (trait_name, item) = match name.0.rsplit_once('.') {
+            Some(projection) => projection,
+            None => continue,
+        };
+        let def = match (trait_name, this) {
+            ("Self", Some(def)) => def,
+            _ => match traits.get(trait_name) {
+                Some(def) => *def,
+                None => continue,
+            },
+        };
+        if !def.types.iter().any(|ty| ty.0 == item) {
+            return Err(TraitError::UnknownProjection {
+                trait_name: def.name.0.clone(),
+                projection: name.0.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn type_names<'a>(ty: &'a DeclType, names: &mut Vec<&'a TypeName>) {
+    match ty {
+        DeclType::TypeName(name) => names.push(name),
+        DeclType::Func { args, retty, .. } => {
+            args.iter().for_each(|arg| type_names(&arg.ty, names));
+            names.push(retty);
+        }
+    }
+}
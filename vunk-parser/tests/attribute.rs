@@ -0,0 +1,79 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chumsky::Parser;
+use vunk_parser::ast::attribute::Attribute;
+use vunk_parser::ast::attribute::Meta;
+use vunk_parser::ast::attribute::MetaValue;
+use vunk_parser::attribute::attributes;
+use vunk_parser::print;
+
+fn parse(code: &str) -> Result<Vec<Attribute>, ()> {
+    let tokens = vunk_lexer::lexer().parse(code).unwrap();
+    let tokens = tokens
+        .into_iter()
+        .map(|(token, _)| token)
+        .collect::<Vec<_>>();
+    let parsed = attributes()
+        .then_ignore(chumsky::primitive::end())
+        .parse(tokens);
+    parsed
+        .map(|attributes| {
+            attributes
+                .into_iter()
+                .map(|(attribute, _)| attribute)
+                .collect()
+        })
+        .map_err(drop)
+}
+
+fn meta(name: &str, value: Option<MetaValue>) -> Meta {
+    Meta::Named {
+        name: name.to_string(),
+        value,
+    }
+}
+
+#[test]
+fn attributes_take_names_strings_and_lists() {
+    let code = "@inline @deprecated(\"use area\", since = \"0.2\") \
+                @cfg(all(debug, feature = \"trace\"))";
+    let parsed = parse(code).unwrap();
+    assert_eq!(
+        parsed,
+        [
+            Attribute {
+                name: "inline".to_string(),
+                args: Vec::new(),
+            },
+            Attribute {
+                name: "deprecated".to_string(),
+                args: vec![
+                    Meta::Str("use area".to_string()),
+                    meta("since", Some(MetaValue::Str("0.2".to_string()))),
+                ],
+            },
+            Attribute {
+                name: "cfg".to_string(),
+                args: vec![meta(
+                    "all",
+                    Some(MetaValue::List(vec![
+                        meta("debug", None),
+                        meta("feature", Some(MetaValue::Str("trace".to_string()))),
+                    ])),
+                )],
+            },
+        ]
+    );
+
+    let printed = parsed.iter().map(print::attribute).collect::<Vec<_>>();
+    assert_eq!(printed.join(" "), code);
+}
+
+#[test]
+fn argument_lists_are_not_empty() {
+    assert!(parse("@derive()").is_err());
+    assert!(parse("@derive(Eq,)").is_err());
+    assert!(parse("@deprecated(note = 1)").is_err());
+}
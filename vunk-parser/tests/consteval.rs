@@ -0,0 +1,207 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+
+use vunk_parser::ast::def::Def;
+use vunk_parser::ast::def::DefArg;
+use vunk_parser::ast::def::DefRhs;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::ifelse::IfElse;
+use vunk_parser::ast::letin::LetIn;
+use vunk_parser::ast::letin::LetIns;
+use vunk_parser::ast::literal::Integer;
+use vunk_parser::ast::literal::IntegerValue;
+use vunk_parser::ast::literal::Literal;
+use vunk_parser::ast::literal::Str;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::op::BinaryOp;
+use vunk_parser::ast::program::Program;
+use vunk_parser::consteval::constants;
+use vunk_parser::consteval::eval;
+use vunk_parser::consteval::fold;
+use vunk_parser::consteval::Constant;
+
+fn variable(name: &str) -> Expr {
+    Expr::Variable(VariableName(name.to_string()))
+}
+
+fn integer(value: i64) -> Expr {
+    Expr::Literal(Literal::Integer(Integer {
+        value: IntegerValue::I64(value),
+    }))
+}
+
+fn string(value: &str) -> Expr {
+    Expr::Literal(Literal::Str(Str {
+        value: value.to_string(),
+    }))
+}
+
+fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+}
+
+fn def(name: &str, args: &[&str], expr: Expr) -> Expr {
+    Expr::Def(Def {
+        lhs: VariableName(name.to_string()),
+        rhs: DefRhs {
+            args: args
+                .iter()
+                .map(|arg| DefArg {
+                    name: VariableName(arg.to_string()),
+                    ty: None,
+                    pattern: None,
+                })
+                .collect(),
+            expr: Box::new(expr),
+        },
+    })
+}
+
+#[test]
+fn top_level_values_are_constants() {
+    // area = width * height
+    // width = 4 * 1024
+    // height = if width > 100 then 2 else 1
+    // greeting = "Hello, " ++ name
+    // name = "World"
+    // double = (x) -> x * 2
+    let program = Program {
+        expr: vec![
+            def(
+                "area",
+                &[],
+                binary(BinaryOp::Mul, variable("width"), variable("height")),
+            ),
+            def(
+                "width",
+                &[],
+                binary(BinaryOp::Mul, integer(4), integer(1024)),
+            ),
+            def(
+                "height",
+                &[],
+                Expr::IfElse(IfElse {
+                    condition: Box::new(binary(BinaryOp::More, variable("width"), integer(100))),
+                    tru: Box::new(integer(2)),
+                    fals: Box::new(integer(1)),
+                }),
+            ),
+            def(
+                "greeting",
+                &[],
+                binary(BinaryOp::Join, string("Hello, "), variable("name")),
+            ),
+            def("name", &[], string("World")),
+            def(
+                "double",
+                &["x"],
+                binary(BinaryOp::Mul, variable("x"), integer(2)),
+            ),
+        ],
+    };
+
+    assert_eq!(
+        constants(&program),
+        BTreeMap::from([
+            ("area".to_string(), Constant::Int(8192)),
+            (
+                "greeting".to_string(),
+                Constant::Str("Hello, World".to_string())
+            ),
+            ("height".to_string(), Constant::Int(2)),
+            ("name".to_string(), Constant::Str("World".to_string())),
+            ("width".to_string(), Constant::Int(4096)),
+        ])
+    );
+}
+
+#[test]
+fn what_fails_at_runtime_is_not_evaluated() {
+    let known = BTreeMap::new();
+    let overflow = binary(BinaryOp::Add, integer(i64::MAX), integer(1));
+    let by_zero = binary(BinaryOp::Div, integer(1), integer(0));
+    let mismatch = binary(BinaryOp::Add, integer(1), string("1"));
+    for expr in [overflow, by_zero, mismatch, variable("unknown")] {
+        assert_eq!(eval(&expr, &known), None, "{:?}", expr);
+    }
+
+    // Cycles are not constants
+    let program = Program {
+        expr: vec![
+            def("a", &[], binary(BinaryOp::Add, variable("b"), integer(1))),
+            def("b", &[], variable("a")),
+        ],
+    };
+    assert!(constants(&program).is_empty());
+}
+
+#[test]
+fn division_truncates_like_at_runtime() {
+    let known = BTreeMap::new();
+    for (a, b, quotient, remainder) in [(-7, 2, -3, -1), (7, -2, -3, 1), (-7, -2, 3, -1)] {
+        let div = binary(BinaryOp::Div, integer(a), integer(b));
+        let rem = binary(BinaryOp::Rem, integer(a), integer(b));
+        assert_eq!(eval(&div, &known), Some(Constant::Int(quotient)));
+        assert_eq!(eval(&rem, &known), Some(Constant::Int(remainder)));
+    }
+}
+
+#[test]
+fn folding_replaces_constants_unless_they_are_shadowed() {
+    // size = 2 * 8
+    // f = (x) -> x + size
+    // g = (size) -> size + 1
+    // h = let n = size in n * 2
+    let program = Program {
+        expr: vec![
+            def("size", &[], binary(BinaryOp::Mul, integer(2), integer(8))),
+            def(
+                "f",
+                &["x"],
+                binary(BinaryOp::Add, variable("x"), variable("size")),
+            ),
+            def(
+                "g",
+                &["size"],
+                binary(BinaryOp::Add, variable("size"), integer(1)),
+            ),
+            def(
+                "h",
+                &[],
+                Expr::LetIn(LetIns {
+                    items: vec![LetIn::Def(Def {
+                        lhs: VariableName("n".to_string()),
+                        rhs: DefRhs {
+                            args: Vec::new(),
+                            expr: Box::new(variable("size")),
+                        },
+                    })],
+                    expr: Box::new(binary(BinaryOp::Mul, variable("n"), integer(2))),
+                }),
+            ),
+        ],
+    };
+
+    assert_eq!(
+        fold(program),
+        Program {
+            expr: vec![
+                def("size", &[], integer(16)),
+                def(
+                    "f",
+                    &["x"],
+                    binary(BinaryOp::Add, variable("x"), integer(16))
+                ),
+                def(
+                    "g",
+                    &["size"],
+                    binary(BinaryOp::Add, variable("size"), integer(1))
+                ),
+                def("h", &[], integer(32)),
+            ],
+        }
+    );
+}
@@ -0,0 +1,742 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chumsky::Parser;
+use vunk_parser::ast::comprehension::Comprehension;
+use vunk_parser::ast::comprehension::Qualifier;
+use vunk_parser::ast::decl::DeclType;
+use vunk_parser::ast::def::Def;
+use vunk_parser::ast::def::DefArg;
+use vunk_parser::ast::def::DefArgType;
+use vunk_parser::ast::def::DefRhs;
+use vunk_parser::ast::def::EnumDef;
+use vunk_parser::ast::def::EnumTypeDef;
+use vunk_parser::ast::def::Equation;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::letin::LetIn;
+use vunk_parser::ast::letin::LetIns;
+use vunk_parser::ast::letin::PatternBinding;
+use vunk_parser::ast::letin::WhereBindings;
+use vunk_parser::ast::literal::Bool;
+use vunk_parser::ast::literal::Integer;
+use vunk_parser::ast::literal::IntegerValue;
+use vunk_parser::ast::literal::Literal;
+use vunk_parser::ast::literal::Str;
+use vunk_parser::ast::matchwhen::MatchWhen;
+use vunk_parser::ast::matchwhen::When;
+use vunk_parser::ast::name::TypeName;
+use vunk_parser::ast::name::TypePath;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::op::BinaryOp;
+use vunk_parser::ast::pattern::FieldPattern;
+use vunk_parser::ast::pattern::Pattern;
+use vunk_parser::ast::pattern::PatternSynonym;
+use vunk_parser::ast::program::Program;
+use vunk_parser::desugar::desugar_accessors;
+use vunk_parser::desugar::desugar_comprehension;
+use vunk_parser::desugar::desugar_do;
+use vunk_parser::desugar::desugar_equations;
+use vunk_parser::desugar::desugar_join;
+use vunk_parser::desugar::desugar_params;
+use vunk_parser::desugar::desugar_sections;
+use vunk_parser::desugar::desugar_spread;
+use vunk_parser::desugar::desugar_synonyms;
+use vunk_parser::desugar::desugar_try;
+use vunk_parser::desugar::desugar_where;
+use vunk_parser::desugar::DesugarError;
+use vunk_parser::parse;
+use vunk_parser::print;
+
+fn parse(code: &str) -> Program {
+    let tokens = vunk_lexer::lexer().parse(code).unwrap();
+    let line_starts = parse::line_starts(code, &tokens);
+    parse::parse(tokens, &line_starts).unwrap()
+}
+
+fn variable(name: &str) -> Expr {
+    Expr::Variable(VariableName(name.to_string()))
+}
+
+fn integer(value: i64) -> Expr {
+    Expr::Literal(Literal::Integer(Integer {
+        value: IntegerValue::I64(value),
+    }))
+}
+
+fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+}
+
+fn generator(name: &str, list: &str) -> Qualifier {
+    Qualifier::Generator(VariableName(name.to_string()), variable(list))
+}
+
+// Desugar `r = comprehension` and print the result
+fn desugared(comprehension: Comprehension) -> String {
+    let program = Program {
+        expr: vec![Expr::Def(Def {
+            lhs: VariableName("r".to_string()),
+            rhs: DefRhs {
+                args: Vec::new(),
+                expr: Box::new(Expr::Comprehension(comprehension)),
+            },
+        })],
+    };
+    print::program(&desugar_comprehension(program))
+}
+
+#[test]
+fn comprehensions_are_printed_like_they_are_written() {
+    let comprehension = Expr::Comprehension(Comprehension {
+        expr: Box::new(binary(BinaryOp::BitOr, variable("x"), integer(1))),
+        qualifiers: vec![
+            generator("x", "xs"),
+            Qualifier::Filter(binary(BinaryOp::More, variable("x"), integer(0))),
+        ],
+    });
+    assert_eq!(print::expr(&comprehension), "[(x | 1) | x <- xs, x > 0]");
+}
+
+#[test]
+fn generators_become_maps_and_filters() {
+    // [x * 2 | x <- xs, x > 0]
+    let comprehension = Comprehension {
+        expr: Box::new(binary(BinaryOp::Mul, variable("x"), integer(2))),
+        qualifiers: vec![
+            generator("x", "xs"),
+            Qualifier::Filter(binary(BinaryOp::More, variable("x"), integer(0))),
+        ],
+    };
+    assert_eq!(
+        desugared(comprehension),
+        "r = Std.List.map ((x) -> x * 2) (Std.List.filter ((x) -> x > 0) xs)\n"
+    );
+}
+
+#[test]
+fn nested_generators_become_concat_maps() {
+    // [x * y | x <- xs, y <- ys, x < y]
+    let comprehension = Comprehension {
+        expr: Box::new(binary(BinaryOp::Mul, variable("x"), variable("y"))),
+        qualifiers: vec![
+            generator("x", "xs"),
+            generator("y", "ys"),
+            Qualifier::Filter(binary(BinaryOp::Less, variable("x"), variable("y"))),
+        ],
+    };
+    assert_eq!(
+        desugared(comprehension),
+        "r = Std.List.concatMap ((x) -> Std.List.map ((y) -> x * y) \
+         (Std.List.filter ((y) -> x < y) ys)) xs\n"
+    );
+
+    // [1 | ready]
+    let comprehension = Comprehension {
+        expr: Box::new(integer(1)),
+        qualifiers: vec![Qualifier::Filter(variable("ready"))],
+    };
+    assert_eq!(desugared(comprehension), "r = if ready then [1] else []\n");
+}
+
+#[test]
+fn comprehensions_are_parsed_from_source() {
+    let program = parse("pairs = (xs, ys) -> [x * y | x <- xs, y <- ys, x < y]\n");
+    assert_eq!(
+        print::program(&desugar_comprehension(program)),
+        "pairs = (xs, ys) -> Std.List.concatMap \
+         ((x) -> Std.List.map ((y) -> x * y) (Std.List.filter ((y) -> x < y) ys)) xs\n"
+    );
+}
+
+#[test]
+fn spreads_are_concatenated_at_once() {
+    let spread = |name| Expr::Spread(Box::new(variable(name)));
+    let list = |elements| {
+        let program = Program {
+            expr: vec![Expr::Def(Def {
+                lhs: VariableName("r".to_string()),
+                rhs: DefRhs {
+                    args: Vec::new(),
+                    expr: Box::new(Expr::Literal(Literal::List(elements))),
+                },
+            })],
+        };
+        print::program(&desugar_spread(program))
+    };
+
+    assert_eq!(
+        list(vec![
+            integer(1),
+            spread("xs"),
+            integer(2),
+            integer(3),
+            spread("ys")
+        ]),
+        "r = Std.List.concat [[1] xs [2 3] ys]\n"
+    );
+    assert_eq!(list(vec![spread("xs")]), "r = xs\n");
+    assert_eq!(list(vec![integer(1), integer(2)]), "r = [1 2]\n");
+}
+
+#[test]
+fn spreads_are_parsed_from_source() {
+    let program = parse("r = [0, ..xs, (f 1)]\n\ns = [..xs 1 ..(f ys)]\n");
+    assert_eq!(
+        print::program(&desugar_spread(program)),
+        "r = Std.List.concat [[0] xs [(f 1)]]\n\ns = Std.List.concat [xs [1] (f ys)]\n"
+    );
+}
+
+#[test]
+fn tries_are_parsed_from_source() {
+    let program = parse("sum = (a, b) -> Ok ((parse a)? + (parse b)?)\n");
+    assert_eq!(
+        print::program(&desugar_try(program).unwrap()),
+        "sum = (a, b) -> match parse a when Ok try?0 -> \
+         (match parse b when Ok try?1 -> Ok (try?0 + try?1) when Err err? -> Err err?) \
+         when Err err? -> Err err?\n"
+    );
+}
+
+#[test]
+fn do_blocks_are_parsed_from_source() {
+    let program = parse("main = do\n    { name <- readLine\n    , println name\n    }\n");
+    assert_eq!(
+        print::program(&desugar_do(program)),
+        "main = Monad.andThen ((name) -> println name) readLine\n"
+    );
+}
+
+#[test]
+fn chains_of_joined_strings_are_concatenated_at_once() {
+    let string = |value: &str| {
+        Expr::Literal(Literal::Str(Str {
+            value: value.to_string(),
+        }))
+    };
+    let join = |lhs, rhs| binary(BinaryOp::Join, lhs, rhs);
+    let joined = |expr| {
+        let program = Program {
+            expr: vec![Expr::Def(Def {
+                lhs: VariableName("r".to_string()),
+                rhs: DefRhs {
+                    args: Vec::new(),
+                    expr: Box::new(expr),
+                },
+            })],
+        };
+        print::program(&desugar_join(program))
+    };
+
+    // "Hello, " ++ (name ++ ("!" ++ "!")) and ("a" ++ b) ++ (c ++ d)
+    assert_eq!(
+        joined(join(
+            string("Hello, "),
+            join(variable("name"), join(string("!"), string("!")))
+        )),
+        "r = Std.String.concat [\"Hello, \" name \"!!\"]\n"
+    );
+    assert_eq!(
+        joined(join(
+            join(string("a"), variable("b")),
+            join(variable("c"), variable("d"))
+        )),
+        "r = Std.String.concat [\"a\" b c d]\n"
+    );
+    assert_eq!(joined(join(string("a"), variable("b"))), "r = \"a\" ++ b\n");
+
+    // Without a string literal, the operands could be lists or of a type with an `impl Concat`
+    assert_eq!(
+        joined(join(variable("a"), join(variable("b"), variable("c")))),
+        "r = a ++ (b ++ c)\n"
+    );
+}
+
+#[test]
+fn joined_strings_are_parsed_from_source() {
+    let program = parse("greeting = \"Hello, \" ++ name ++ \"!\"\n");
+    assert_eq!(
+        print::program(&desugar_join(program)),
+        "greeting = Std.String.concat [\"Hello, \" name \"!\"]\n"
+    );
+}
+
+#[test]
+fn named_fields_get_accessors() {
+    let variant = |name: &str, fields: &[&str]| EnumTypeDef {
+        name: TypeName(name.to_string()),
+        members: fields
+            .iter()
+            .map(|field| DefArg {
+                name: VariableName(field.to_string()),
+                ty: Some(DefArgType::TypeName(TypeName("Float".to_string()))),
+                pattern: None,
+            })
+            .collect(),
+        types: Vec::new(),
+    };
+    // enum Shape = Circle { radius: Float, z: Float } | Rect { w: Float, z: Float }
+    let program = Program {
+        expr: vec![Expr::Enum(EnumDef {
+            name: TypeName("Shape".to_string()),
+            params: Vec::new(),
+            variants: vec![
+                variant("Circle", &["radius", "z"]),
+                variant("Rect", &["w", "z"]),
+            ],
+            whereclause: None,
+        })],
+    };
+
+    let printed = print::program(&desugar_accessors(program));
+    let items = printed.split("\n\n").collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        [
+            "enum Shape = Circle { radius: Float, z: Float } | Rect { w: Float, z: Float }",
+            "Shape.radius = (value?: Shape) -> match value? \
+             when Shape.Circle { radius: radius } -> Some radius else None",
+            "Shape.z = (value?: Shape) -> match value? \
+             when Shape.Circle { z: z } -> z when Shape.Rect { z: z } -> z",
+            "Shape.w = (value?: Shape) -> match value? \
+             when Shape.Rect { w: w } -> Some w else None\n",
+        ]
+    );
+}
+
+#[test]
+fn field_sections_become_lambdas() {
+    let program = Program {
+        expr: vec![Expr::Apply(
+            Box::new(variable("map")),
+            vec![
+                Expr::FieldSection(VariableName("name".to_string())),
+                variable("people"),
+            ],
+        )],
+    };
+    assert_eq!(print::program(&program), "map .name people\n");
+
+    assert_eq!(
+        print::program(&desugar_sections(program)),
+        "map ((section?) -> section?.name) people\n"
+    );
+}
+
+#[test]
+fn field_sections_are_parsed_from_source() {
+    let program = parse("names = map .name people\n");
+    assert_eq!(
+        print::program(&desugar_sections(program)),
+        "names = map ((section?) -> section?.name) people\n"
+    );
+}
+
+// Desugar `enum Point = Point { x: Float }` and a lambda with the parameters, returning the lambda
+fn with_params(patterns: Vec<Pattern>) -> Result<String, DesugarError> {
+    let point = EnumDef {
+        name: TypeName("Point".to_string()),
+        params: Vec::new(),
+        variants: vec![EnumTypeDef {
+            name: TypeName("Point".to_string()),
+            members: vec![DefArg {
+                name: VariableName("x".to_string()),
+                ty: Some(DefArgType::TypeName(TypeName("Float".to_string()))),
+                pattern: None,
+            }],
+            types: Vec::new(),
+        }],
+        whereclause: None,
+    };
+    let args = patterns
+        .into_iter()
+        .map(|pattern| DefArg {
+            name: VariableName("_".to_string()),
+            ty: None,
+            pattern: Some(pattern),
+        })
+        .collect();
+    let program = Program {
+        expr: vec![
+            Expr::Enum(point),
+            Expr::Lambda(DefRhs {
+                args,
+                expr: Box::new(binary(BinaryOp::Add, variable("x"), variable("y"))),
+            }),
+        ],
+    };
+
+    let printed = print::program(&desugar_params(program)?);
+    Ok(printed.split("\n\n").nth(1).unwrap_or_default().to_string())
+}
+
+#[test]
+fn parameter_patterns_become_matches() {
+    let point = Pattern::Fields {
+        path: TypePath(vec![
+            TypeName("Point".to_string()),
+            TypeName("Point".to_string()),
+        ]),
+        fields: vec![FieldPattern {
+            name: VariableName("x".to_string()),
+            pattern: Pattern::Variable(VariableName("x".to_string())),
+        }],
+    };
+    let rest = Pattern::List {
+        elements: Vec::new(),
+        rest: Some(Box::new(Pattern::Variable(VariableName("y".to_string())))),
+    };
+
+    assert_eq!(
+        with_params(vec![point, rest]).unwrap(),
+        "(param?0, param?1) -> match param?0 when Point.Point { x: x } -> \
+         (match param?1 when [..y] -> x + y)\n"
+    );
+}
+
+#[test]
+fn refutable_parameter_patterns_are_rejected() {
+    let first = Pattern::List {
+        elements: vec![Pattern::Variable(VariableName("x".to_string()))],
+        rest: Some(Box::new(Pattern::Variable(VariableName("y".to_string())))),
+    };
+    assert!(matches!(
+        with_params(vec![first]),
+        Err(DesugarError::RefutableParameter { pattern }) if pattern == "[x, ..y]"
+    ));
+}
+
+#[test]
+fn parameter_patterns_are_parsed_from_source() {
+    let program = parse(
+        "enum Point = Point { x: i64, y: i64 }\n\n\
+         sum = (Point.Point { x: x, y: y }, [..rest]: List i64) -> x + y\n",
+    );
+    let printed = print::program(&desugar_params(program).unwrap());
+    assert_eq!(
+        printed.split("\n\n").nth(1).unwrap(),
+        "sum = (param?0, param?1: List i64) -> match param?0 \
+         when Point.Point { x: x, y: y } -> (match param?1 when [..rest] -> x + y)\n"
+    );
+}
+
+// `name patterns = expr`
+fn equation(name: &str, patterns: Vec<Pattern>, expr: Expr) -> Expr {
+    Expr::Equation(Equation {
+        lhs: VariableName(name.to_string()),
+        params: patterns,
+        expr: Box::new(expr),
+    })
+}
+
+#[test]
+fn equations_with_one_parameter_become_arms() {
+    let zero = Pattern::Literal(Literal::Integer(Integer {
+        value: IntegerValue::I64(0),
+    }));
+    let fact = Expr::Apply(
+        Box::new(variable("fact")),
+        vec![binary(BinaryOp::Sub, variable("n"), integer(1))],
+    );
+    let program = Program {
+        expr: vec![
+            equation("fact", vec![zero], integer(1)),
+            equation(
+                "fact",
+                vec![Pattern::Variable(VariableName("n".to_string()))],
+                binary(BinaryOp::Mul, variable("n"), fact),
+            ),
+        ],
+    };
+
+    assert_eq!(
+        print::program(&desugar_equations(program).unwrap()),
+        "fact = (arg?0) -> match arg?0 when 0 -> 1 when n -> n * (fact (n - 1))\n"
+    );
+}
+
+#[test]
+fn equations_with_more_parameters_fall_through() {
+    let bool = |value| Pattern::Literal(Literal::Bool(Bool { value }));
+    let program = Program {
+        expr: vec![
+            equation(
+                "and",
+                vec![bool(true), bool(true)],
+                Expr::Literal(Literal::Bool(Bool { value: true })),
+            ),
+            equation(
+                "and",
+                vec![Pattern::Wildcard, Pattern::Wildcard],
+                Expr::Literal(Literal::Bool(Bool { value: false })),
+            ),
+        ],
+    };
+
+    assert_eq!(
+        print::program(&desugar_equations(program).unwrap()),
+        "and = (arg?0, arg?1) -> let\n\
+         case?1 = (arg?0, arg?1) -> (match arg?0 when _ -> (match arg?1 when _ -> false))\n\
+         in match arg?0 \
+         when true -> (match arg?1 when true -> true else case?1 arg?0 arg?1) \
+         else case?1 arg?0 arg?1\n"
+    );
+}
+
+#[test]
+fn equations_are_parsed_from_source() {
+    let program = parse("fact 0 = 1\nfact n = n * fact (n - 1)\n");
+    assert_eq!(
+        print::program(&desugar_equations(program).unwrap()),
+        "fact = (arg?0) -> match arg?0 when 0 -> 1 when n -> n * (fact (n - 1))\n"
+    );
+}
+
+#[test]
+fn equations_have_the_same_number_of_parameters() {
+    let program = Program {
+        expr: vec![
+            equation("f", vec![Pattern::Wildcard], integer(1)),
+            equation("f", vec![Pattern::Wildcard, Pattern::Wildcard], integer(2)),
+        ],
+    };
+    assert!(matches!(
+        desugar_equations(program),
+        Err(DesugarError::EquationArity { name }) if name == "f"
+    ));
+}
+
+// `r = expr`
+fn binding(expr: Expr) -> Expr {
+    Expr::Def(Def {
+        lhs: VariableName("r".to_string()),
+        rhs: DefRhs {
+            args: Vec::new(),
+            expr: Box::new(expr),
+        },
+    })
+}
+
+// `[..name]`
+fn rest(name: &str) -> Pattern {
+    Pattern::List {
+        elements: Vec::new(),
+        rest: Some(Box::new(Pattern::Variable(VariableName(name.to_string())))),
+    }
+}
+
+#[test]
+fn where_blocks_become_lets() {
+    let xs = LetIn::Pattern(PatternBinding {
+        pattern: rest("xs"),
+        ty: Some(DeclType::TypeName(TypeName("List Int".to_string()))),
+        expr: Box::new(variable("list")),
+    });
+    let n = LetIn::Def(Def {
+        lhs: VariableName("n".to_string()),
+        rhs: DefRhs {
+            args: Vec::new(),
+            expr: Box::new(integer(1)),
+        },
+    });
+    let program = Program {
+        expr: vec![binding(Expr::Where(WhereBindings {
+            expr: Box::new(binary(BinaryOp::Add, variable("n"), variable("xs"))),
+            items: vec![xs, n],
+        }))],
+    };
+    assert_eq!(
+        print::program(&program),
+        "r = n + xs where {\n[..xs]: List Int = list\nn = 1\n}\n"
+    );
+
+    assert_eq!(
+        print::program(&desugar_where(program).unwrap()),
+        "r = let\n\
+         binding?0 = ((list): List Int)\n\
+         xs = (match binding?0 when [..xs] -> xs)\n\
+         n = 1\n\
+         in n + xs\n"
+    );
+}
+
+#[test]
+fn pattern_bindings_bind_each_variable() {
+    let pair = EnumDef {
+        name: TypeName("Pair".to_string()),
+        params: Vec::new(),
+        variants: vec![EnumTypeDef {
+            name: TypeName("Pair".to_string()),
+            members: Vec::new(),
+            types: vec![TypeName("i64".to_string()), TypeName("i64".to_string())],
+        }],
+        whereclause: None,
+    };
+    let pattern = Pattern::Variant {
+        path: TypePath(vec![
+            TypeName("Pair".to_string()),
+            TypeName("Pair".to_string()),
+        ]),
+        members: vec![
+            Pattern::Variable(VariableName("a".to_string())),
+            Pattern::Variable(VariableName("b".to_string())),
+        ],
+    };
+    let program = Program {
+        expr: vec![
+            Expr::Enum(pair),
+            binding(Expr::LetIn(LetIns {
+                items: vec![LetIn::Pattern(PatternBinding {
+                    pattern,
+                    ty: None,
+                    expr: Box::new(variable("pair")),
+                })],
+                expr: Box::new(binary(BinaryOp::Mul, variable("a"), variable("b"))),
+            })),
+        ],
+    };
+
+    let printed = print::program(&desugar_where(program).unwrap());
+    assert_eq!(
+        printed.split("\n\n").nth(1).unwrap_or_default(),
+        "r = let\n\
+         binding?0 = pair\n\
+         a = (match binding?0 when Pair.Pair a _ -> a)\n\
+         b = (match binding?0 when Pair.Pair _ b -> b)\n\
+         in a * b\n"
+    );
+}
+
+#[test]
+fn where_blocks_are_parsed_from_source() {
+    let program = parse(
+        "enum Point =\n    Point { x: i64 }\n\n\
+         distance = (from, to) -> dx * dx where\n    \
+         { Point.Point { x: x1 }: Point = from\n      \
+           Point.Point { x: x2 }: Point = to\n      \
+           dx = x2 - x1\n    \
+         }\n",
+    );
+    let printed = print::program(&desugar_where(program).unwrap());
+    assert_eq!(
+        printed.split("\n\n").nth(1).unwrap_or_default(),
+        "distance = (from, to) -> let\n\
+         binding?0 = ((from): Point)\n\
+         x1 = (match binding?0 when Point.Point { x: x1 } -> x1)\n\
+         binding?1 = ((to): Point)\n\
+         x2 = (match binding?1 when Point.Point { x: x2 } -> x2)\n\
+         dx = x2 - x1\n\
+         in dx * dx\n"
+    );
+}
+
+#[test]
+fn refutable_binding_patterns_are_rejected() {
+    let first = Pattern::List {
+        elements: vec![Pattern::Variable(VariableName("x".to_string()))],
+        rest: None,
+    };
+    let program = Program {
+        expr: vec![binding(Expr::Where(WhereBindings {
+            expr: Box::new(variable("x")),
+            items: vec![LetIn::Pattern(PatternBinding {
+                pattern: first,
+                ty: None,
+                expr: Box::new(variable("list")),
+            })],
+        }))],
+    };
+    assert!(matches!(
+        desugar_where(program),
+        Err(DesugarError::RefutableBinding { pattern }) if pattern == "[x]"
+    ));
+}
+
+// `pattern Head x = List.Cons { x, _ }`, with the parameters
+fn head(params: &[&str]) -> Expr {
+    Expr::PatternSynonym(PatternSynonym {
+        name: TypeName("Head".to_string()),
+        params: params
+            .iter()
+            .map(|param| VariableName(param.to_string()))
+            .collect(),
+        pattern: Pattern::Variant {
+            path: TypePath(vec![
+                TypeName("List".to_string()),
+                TypeName("Cons".to_string()),
+            ]),
+            members: vec![
+                Pattern::Variable(VariableName("x".to_string())),
+                Pattern::Wildcard,
+            ],
+        },
+    })
+}
+
+// `r = match list when Head members -> 1`
+fn match_head(members: Vec<Pattern>) -> Expr {
+    binding(Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("list")),
+        arms: vec![When {
+            pattern: Pattern::Variant {
+                path: TypePath(vec![TypeName("Head".to_string())]),
+                members,
+            },
+            guard: None,
+            expr: Box::new(integer(1)),
+        }],
+        otherwise: None,
+    }))
+}
+
+#[test]
+fn pattern_synonyms_are_replaced_by_their_patterns() {
+    let first = Pattern::Variable(VariableName("first".to_string()));
+    let program = Program {
+        expr: vec![head(&["x"]), match_head(vec![first])],
+    };
+    assert_eq!(
+        print::program(&program),
+        "pattern Head x = List.Cons x _\n\nr = match list when Head first -> 1\n"
+    );
+
+    assert_eq!(
+        print::program(&desugar_synonyms(program).unwrap()),
+        "r = match list when List.Cons first _ -> 1\n"
+    );
+}
+
+#[test]
+fn pattern_synonyms_are_parsed_from_source() {
+    let program = parse(
+        "pattern Head x = List.Cons { x, _ }\n\n\
+         r = match list\n    when Head first -> 1\n",
+    );
+    assert_eq!(
+        print::program(&desugar_synonyms(program).unwrap()),
+        "r = match list when List.Cons first _ -> 1\n"
+    );
+}
+
+#[test]
+fn pattern_synonyms_are_used_with_a_pattern_per_parameter() {
+    let program = Program {
+        expr: vec![head(&["x"]), match_head(Vec::new())],
+    };
+    assert!(matches!(
+        desugar_synonyms(program),
+        Err(DesugarError::SynonymArity { name, expected: 1, found: 0 }) if name == "Head"
+    ));
+}
+
+#[test]
+fn pattern_synonyms_bind_their_parameters() {
+    let program = Program {
+        expr: vec![head(&["x", "y"])],
+    };
+    assert!(matches!(
+        desugar_synonyms(program),
+        Err(DesugarError::SynonymParams { name }) if name == "Head"
+    ));
+}
@@ -0,0 +1,95 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chumsky::Parser;
+use vunk_parser::ast::decl::Decl;
+use vunk_parser::ast::decl::DeclArg;
+use vunk_parser::ast::decl::DeclType;
+use vunk_parser::ast::effect::EffectDef;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::name::TypeName;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::program::Program;
+use vunk_parser::effects::check;
+use vunk_parser::effects::EffectError;
+use vunk_parser::parse;
+use vunk_parser::print;
+
+// `name: (String) -> () with effects`
+fn decl(name: &str, effects: &[&str]) -> Decl {
+    Decl {
+        attributes: Vec::new(),
+        lhs: VariableName(name.to_string()),
+        rhs: DeclType::Func {
+            args: vec![DeclArg {
+                name: None,
+                ty: DeclType::TypeName(TypeName("String".to_string())),
+            }],
+            retty: TypeName("()".to_string()),
+            effects: effects
+                .iter()
+                .map(|effect| TypeName(effect.to_string()))
+                .collect(),
+        },
+        whereclause: None,
+    }
+}
+
+// `effect Log = { operations }`, and `greet` with the effects
+fn program(operations: &[&str], effects: &[&str]) -> Program {
+    Program {
+        expr: vec![
+            Expr::Effect(EffectDef {
+                name: TypeName("Log".to_string()),
+                operations: operations.iter().map(|name| decl(name, &[])).collect(),
+            }),
+            Expr::Decl(decl("greet", effects)),
+        ],
+    }
+}
+
+#[test]
+fn effects_are_printed_after_the_return_type() {
+    assert_eq!(
+        print::program(&program(&["log"], &["Log", "Console"])),
+        "effect Log = { log : (String) -> () }\n\ngreet : (String) -> () with Log, Console\n"
+    );
+}
+
+#[test]
+fn effects_are_parsed_from_source() {
+    let code =
+        "effect Log = { log : (String) -> () }\n\ngreet : (String) -> () with Log, Console\n";
+    let tokens = vunk_lexer::lexer().parse(code).unwrap();
+    let line_starts = parse::line_starts(code, &tokens);
+    assert_eq!(
+        parse::parse(tokens, &line_starts).unwrap(),
+        program(&["log"], &["Log", "Console"])
+    );
+}
+
+#[test]
+fn declared_and_default_effects_can_be_named() {
+    assert!(check(&program(&["log"], &["Log"])).is_ok());
+    assert!(check(&program(&["log"], &["Console", "Files"])).is_ok());
+}
+
+#[test]
+fn effects_that_are_not_declared_cannot_be_named() {
+    match check(&program(&["log"], &["Log", "Random"])) {
+        Err(error @ EffectError::Unknown { .. }) => assert_eq!(
+            error.to_string(),
+            "The type of 'greet' names the effect Random, which is not declared"
+        ),
+        other => panic!("Expected an unknown effect, got {:?}", other),
+    }
+}
+
+#[test]
+fn effects_declare_their_operations_once() {
+    assert!(matches!(
+        check(&program(&["log", "flush", "log"], &[])),
+        Err(EffectError::DuplicateOperation { operation, .. }) if operation == "log"
+    ));
+}
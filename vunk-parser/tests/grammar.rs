@@ -0,0 +1,68 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeSet;
+
+use chumsky::Parser;
+use vunk_parser::grammar::ebnf;
+use vunk_parser::grammar::grammar;
+use vunk_parser::grammar::Node;
+use vunk_parser::grammar::START;
+use vunk_parser::grammar::TOKENS;
+
+// Calls `f` with every node within `node`, and `node` itself
+fn visit<'a>(node: &'a Node, f: &mut impl FnMut(&'a Node)) {
+    f(node);
+    match node {
+        Node::Sequence(nodes) | Node::Choice(nodes) => nodes.iter().for_each(|node| visit(node, f)),
+        Node::Optional(node) | Node::Repeat(node) => visit(node, f),
+        Node::Literal(_) | Node::Token(_) | Node::Rule(_) => {}
+    }
+}
+
+#[test]
+fn rules_are_defined_once_and_used() {
+    let rules = grammar();
+    let names = rules.iter().map(|rule| rule.name).collect::<BTreeSet<_>>();
+    assert_eq!(names.len(), rules.len());
+    assert_eq!(rules[0].name, START);
+
+    let mut used = BTreeSet::from([START]);
+    for rule in rules.iter() {
+        visit(&rule.node, &mut |node| match node {
+            Node::Rule(name) => {
+                assert!(names.contains(name), "{} is not defined", name);
+                used.insert(*name);
+            }
+            Node::Token(kind) => assert!(TOKENS.contains(kind), "{} is not a token", kind),
+            _ => {}
+        });
+    }
+    assert_eq!(used, names);
+}
+
+#[test]
+fn literals_can_be_lexed() {
+    for rule in grammar() {
+        visit(&rule.node, &mut |node| {
+            if let Node::Literal(text) = node {
+                let lexed = vunk_lexer::lexer().parse(*text);
+                assert!(
+                    lexed.map(|tokens| !tokens.is_empty()).unwrap_or(false),
+                    "{}",
+                    text
+                );
+            }
+        });
+    }
+}
+
+#[test]
+fn rules_are_printed_as_ebnf() {
+    let ebnf = ebnf(&grammar());
+    assert!(ebnf.starts_with("program ::= item*\n"));
+    assert!(ebnf.contains("\nifelse ::= \"if\" expr \"then\" expr \"else\" expr\n"));
+    assert!(ebnf.contains("\npath ::= IDENT ( \".\" IDENT )*\n"));
+    assert!(ebnf.contains("\ntype ::= functype | typeapp\n"));
+}
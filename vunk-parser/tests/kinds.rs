@@ -0,0 +1,138 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_parser::ast::decl::Decl;
+use vunk_parser::ast::decl::DeclArg;
+use vunk_parser::ast::decl::DeclType;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::name::TraitName;
+use vunk_parser::ast::name::TypeName;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::program::Program;
+use vunk_parser::ast::traitdef::TraitDef;
+use vunk_parser::ast::traitdef::TraitImpl;
+use vunk_parser::kinds::check;
+use vunk_parser::kinds::of_trait;
+use vunk_parser::kinds::Kind;
+use vunk_parser::kinds::KindError;
+
+// `name: (args) -> retty`
+fn decl(name: &str, args: &[&str], retty: &str) -> Decl {
+    Decl {
+        attributes: Vec::new(),
+        lhs: VariableName(name.to_string()),
+        rhs: DeclType::Func {
+            args: args
+                .iter()
+                .map(|arg| DeclArg {
+                    name: None,
+                    ty: DeclType::TypeName(TypeName(arg.to_string())),
+                })
+                .collect(),
+            retty: TypeName(retty.to_string()),
+            effects: Vec::new(),
+        },
+        whereclause: None,
+    }
+}
+
+fn functor(impl_on: &str) -> Program {
+    Program {
+        expr: vec![
+            Expr::Trait(TraitDef {
+                name: TraitName("Functor".to_string()),
+                types: Vec::new(),
+                methods: vec![decl("fmap", &["(A) -> B", "Self A"], "Self B")],
+                defaults: Vec::new(),
+            }),
+            Expr::Impl(TraitImpl {
+                trait_name: TraitName("Functor".to_string()),
+                type_name: TypeName(impl_on.to_string()),
+                types: Vec::new(),
+                decls: Vec::new(),
+                methods: Vec::new(),
+            }),
+        ],
+    }
+}
+
+fn program(decl: Decl) -> Program {
+    Program {
+        expr: vec![Expr::Decl(decl)],
+    }
+}
+
+fn arrow(arg: Kind, result: Kind) -> Kind {
+    Kind::Arrow(Box::new(arg), Box::new(result))
+}
+
+#[test]
+fn kinds_are_printed_with_arrows() {
+    let constructor = arrow(Kind::Type, Kind::Type);
+    assert_eq!(constructor.to_string(), "* -> *");
+    assert_eq!(
+        arrow(Kind::Type, constructor.clone()).to_string(),
+        "* -> * -> *"
+    );
+    assert_eq!(arrow(constructor, Kind::Type).to_string(), "(* -> *) -> *");
+}
+
+#[test]
+fn self_gets_its_kind_from_the_methods() {
+    assert_eq!(
+        of_trait(&functor("Option"), "Functor").unwrap(),
+        Some(arrow(Kind::Type, Kind::Type))
+    );
+}
+
+#[test]
+fn impls_are_on_types_of_the_kind_of_self() {
+    assert!(check(&functor("Option")).is_ok());
+    match check(&functor("i64")) {
+        Err(error @ KindError::Impl { .. }) => assert_eq!(
+            error.to_string(),
+            "impl Functor on i64 needs a type of kind * -> *, not of *"
+        ),
+        other => panic!("Expected a kind error, got {:?}", other),
+    }
+    assert!(check(&functor("Result")).is_err());
+}
+
+#[test]
+fn type_variables_can_be_applied() {
+    let program = program(decl("traverse", &["(A) -> F B", "List A"], "F (List B)"));
+    assert!(check(&program).is_ok());
+}
+
+#[test]
+fn type_variables_are_applied_the_same_way_everywhere() {
+    let program = program(decl("both", &["F A", "F A B"], "A"));
+    match check(&program) {
+        Err(error @ KindError::Applied { .. }) => assert_eq!(
+            error.to_string(),
+            "'F A' is applied to 'B' in 'F A B', but it is of kind *"
+        ),
+        other => panic!("Expected a kind error, got {:?}", other),
+    }
+}
+
+#[test]
+fn types_of_values_take_all_their_arguments() {
+    assert!(matches!(
+        check(&program(decl("get", &["Map String"], "i64"))),
+        Err(KindError::Unapplied { ty, .. }) if ty == "Map String"
+    ));
+    assert!(matches!(
+        check(&program(decl("wrap", &["i64 A"], "i64"))),
+        Err(KindError::Applied { ty, .. }) if ty == "i64"
+    ));
+}
+
+#[test]
+fn type_variables_are_not_applied_to_themselves() {
+    assert!(matches!(
+        check(&program(decl("strange", &["F F"], "i64"))),
+        Err(KindError::Infinite { ty, .. }) if ty == "F"
+    ));
+}
@@ -0,0 +1,166 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_parser::ast::def::Def;
+use vunk_parser::ast::def::DefArg;
+use vunk_parser::ast::def::DefRhs;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::literal::Bool;
+use vunk_parser::ast::literal::Integer;
+use vunk_parser::ast::literal::IntegerValue;
+use vunk_parser::ast::literal::Literal;
+use vunk_parser::ast::literal::Str;
+use vunk_parser::ast::matchwhen::MatchWhen;
+use vunk_parser::ast::matchwhen::When;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::pattern::Pattern;
+use vunk_parser::ast::program::Program;
+use vunk_parser::consteval::Constant;
+use vunk_parser::matching::check;
+use vunk_parser::matching::compile;
+use vunk_parser::matching::MatchError;
+use vunk_parser::matching::Table;
+
+fn integer(value: i64) -> Literal {
+    Literal::Integer(Integer {
+        value: IntegerValue::I64(value),
+    })
+}
+
+fn string(value: &str) -> Literal {
+    Literal::Str(Str {
+        value: value.to_string(),
+    })
+}
+
+fn boolean(value: bool) -> Literal {
+    Literal::Bool(Bool { value })
+}
+
+// `match x when pattern -> index ...`
+fn matchwhen(patterns: Vec<Pattern>, otherwise: bool) -> MatchWhen {
+    MatchWhen {
+        expr: Box::new(Expr::Variable(VariableName("x".to_string()))),
+        arms: patterns
+            .into_iter()
+            .enumerate()
+            .map(|(index, pattern)| When {
+                pattern,
+                guard: None,
+                expr: Box::new(Expr::Literal(integer(index as i64))),
+            })
+            .collect(),
+        otherwise: otherwise.then(|| Box::new(Expr::Literal(integer(-1)))),
+    }
+}
+
+// `f = (x) -> match ...`
+fn program(matchwhen: MatchWhen) -> Program {
+    Program {
+        expr: vec![Expr::Def(Def {
+            lhs: VariableName("f".to_string()),
+            rhs: DefRhs {
+                args: vec![DefArg {
+                    name: VariableName("x".to_string()),
+                    ty: None,
+                    pattern: None,
+                }],
+                expr: Box::new(Expr::MatchWhen(matchwhen)),
+            },
+        })],
+    }
+}
+
+#[test]
+fn strings_are_looked_up_in_a_trie() {
+    let patterns = ["get", "getAll", "put", "get"]
+        .into_iter()
+        .map(|method| Pattern::Literal(string(method)))
+        .chain([Pattern::Wildcard, Pattern::Literal(string("delete"))])
+        .collect();
+    let switch = compile(&matchwhen(patterns, false)).unwrap();
+    assert!(matches!(switch.table, Table::Trie(_)));
+
+    let select = |method: &str| switch.select(&Constant::Str(method.to_string()));
+    assert_eq!(select("get"), Some(0));
+    assert_eq!(select("getAll"), Some(1));
+    assert_eq!(select("put"), Some(2));
+    // After the wildcard, and a prefix of an arm
+    assert_eq!(select("delete"), Some(4));
+    assert_eq!(select("ge"), Some(4));
+}
+
+#[test]
+fn integers_close_together_use_a_jump_table() {
+    let patterns = [3, 1, 2, 5]
+        .into_iter()
+        .map(|i| Pattern::Literal(integer(i)))
+        .collect();
+    let switch = compile(&matchwhen(patterns, true)).unwrap();
+    assert_eq!(
+        switch.table,
+        Table::Jump {
+            start: 1,
+            arms: vec![Some(1), Some(2), Some(0), None, Some(3)],
+        }
+    );
+    assert_eq!(switch.select(&Constant::Int(5)), Some(3));
+    assert_eq!(switch.select(&Constant::Int(4)), None);
+    assert_eq!(switch.select(&Constant::Int(i64::MIN)), None);
+
+    let patterns = [1000, 1, -7]
+        .into_iter()
+        .map(|i| Pattern::Literal(integer(i)))
+        .collect();
+    let switch = compile(&matchwhen(patterns, true)).unwrap();
+    assert!(matches!(switch.table, Table::Sorted(_)));
+    assert_eq!(switch.select(&Constant::Int(-7)), Some(2));
+    assert_eq!(switch.select(&Constant::Int(2)), None);
+}
+
+#[test]
+fn matches_on_literals_need_a_fallback() {
+    let numbers = || {
+        vec![
+            Pattern::Literal(integer(200)),
+            Pattern::Literal(integer(404)),
+        ]
+    };
+    match check(&program(matchwhen(numbers(), false))) {
+        Err(MatchError::NotExhaustive { pattern }) => assert_eq!(pattern, "200"),
+        result => panic!("{:?} is not an error about exhaustiveness", result),
+    }
+
+    let mut with_variable = numbers();
+    with_variable.push(Pattern::Variable(VariableName("other".to_string())));
+    assert!(check(&program(matchwhen(with_variable, false))).is_ok());
+    assert!(check(&program(matchwhen(numbers(), true))).is_ok());
+
+    let bools = vec![
+        Pattern::Literal(boolean(false)),
+        Pattern::Literal(boolean(true)),
+    ];
+    assert!(check(&program(matchwhen(bools, false))).is_ok());
+    let only_true = vec![Pattern::Literal(boolean(true))];
+    assert!(check(&program(matchwhen(only_true, false))).is_err());
+
+    let unit = vec![Pattern::Literal(Literal::Unit)];
+    assert!(check(&program(matchwhen(unit, false))).is_ok());
+}
+
+#[test]
+fn guarded_arms_do_not_count() {
+    let guarded = |pattern: Pattern| {
+        let mut matchwhen = matchwhen(vec![Pattern::Literal(integer(1)), pattern], false);
+        matchwhen.arms[1].guard = Some(Box::new(Expr::Literal(boolean(true))));
+        matchwhen
+    };
+
+    assert!(check(&program(guarded(Pattern::Wildcard))).is_err());
+    let variable = Pattern::Variable(VariableName("n".to_string()));
+    assert!(check(&program(guarded(variable))).is_err());
+
+    // The arms are tried in order, so there is no switch
+    assert_eq!(compile(&guarded(Pattern::Literal(integer(2)))), None);
+}
@@ -0,0 +1,592 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chumsky::error::Simple;
+use chumsky::error::SimpleReason;
+use chumsky::Parser;
+use vunk_lexer::Token;
+use vunk_parser::ast::attribute::Attribute;
+use vunk_parser::ast::decl::Decl;
+use vunk_parser::ast::decl::DeclType;
+use vunk_parser::ast::def::Def;
+use vunk_parser::ast::def::DefArg;
+use vunk_parser::ast::def::DefArgType;
+use vunk_parser::ast::def::DefRhs;
+use vunk_parser::ast::def::EnumDef;
+use vunk_parser::ast::def::EnumTypeDef;
+use vunk_parser::ast::def::Equation;
+use vunk_parser::ast::def::TypeDef;
+use vunk_parser::ast::doblock::DoBlock;
+use vunk_parser::ast::doblock::DoStatement;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::import::Import;
+use vunk_parser::ast::letin::LetIn;
+use vunk_parser::ast::letin::LetIns;
+use vunk_parser::ast::literal::Bytes;
+use vunk_parser::ast::literal::Integer;
+use vunk_parser::ast::literal::IntegerValue;
+use vunk_parser::ast::literal::Literal;
+use vunk_parser::ast::matchwhen::MatchWhen;
+use vunk_parser::ast::matchwhen::When;
+use vunk_parser::ast::name::TypeName;
+use vunk_parser::ast::name::TypePath;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::op::BinaryOp;
+use vunk_parser::ast::pattern::FieldPattern;
+use vunk_parser::ast::pattern::Pattern;
+use vunk_parser::ast::program::Program;
+use vunk_parser::ast::record::Construct;
+use vunk_parser::ast::record::Field;
+use vunk_parser::ast::record::Record;
+use vunk_parser::parse;
+use vunk_parser::print;
+
+fn parse(code: &str) -> Result<Program, Vec<Simple<Token>>> {
+    let tokens = vunk_lexer::lexer().parse(code).unwrap();
+    let line_starts = parse::line_starts(code, &tokens);
+    parse::parse(tokens, &line_starts)
+}
+
+fn assert_parsed(code: &str, expr: Vec<Expr>) {
+    assert_eq!(parse(code).unwrap(), Program { expr });
+}
+
+fn variable(name: &str) -> Expr {
+    Expr::Variable(VariableName(name.to_string()))
+}
+
+fn integer(value: i64) -> Expr {
+    Expr::Literal(Literal::Integer(Integer {
+        value: IntegerValue::I64(value),
+    }))
+}
+
+fn def(name: &str, expr: Expr) -> Def {
+    Def {
+        lhs: VariableName(name.to_string()),
+        rhs: DefRhs {
+            args: Vec::new(),
+            expr: Box::new(expr),
+        },
+    }
+}
+
+// The messages of the errors of custom parsers, which `Simple` does not display
+fn messages(errors: Vec<Simple<Token>>) -> Vec<String> {
+    errors
+        .iter()
+        .filter_map(|error| match error.reason() {
+            SimpleReason::Custom(message) => Some(message.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn items_end_where_the_next_one_starts() {
+    let apply = Expr::Apply(Box::new(variable("f")), vec![variable("a")]);
+    assert_parsed(
+        "x = f a\n\ny = 2",
+        vec![Expr::Def(def("x", apply)), Expr::Def(def("y", integer(2)))],
+    );
+}
+
+#[test]
+fn equations_end_where_the_next_line_starts_one() {
+    let code = "fact 0 = 1\nfact n = n * fact (n - 1)\n";
+    let program = parse(code).unwrap();
+    let equation = |param, expr| Equation {
+        lhs: VariableName("fact".to_string()),
+        params: vec![param],
+        expr: Box::new(expr),
+    };
+    let n = || variable("n");
+    let recursion = Expr::Apply(
+        Box::new(variable("fact")),
+        vec![Expr::Binary(
+            BinaryOp::Sub,
+            Box::new(n()),
+            Box::new(integer(1)),
+        )],
+    );
+    let zero = Pattern::Literal(Literal::Integer(Integer {
+        value: IntegerValue::I64(0),
+    }));
+    let equations = vec![
+        Expr::Equation(equation(zero, integer(1))),
+        Expr::Equation(equation(
+            Pattern::Variable(VariableName("n".to_string())),
+            Expr::Binary(BinaryOp::Mul, Box::new(n()), Box::new(recursion)),
+        )),
+    ];
+    assert_eq!(program.expr, equations);
+
+    // On a single line, the second `fact` is an argument of `1`
+    let program = parse(&code.replace('\n', " ")).unwrap();
+    assert_ne!(program.expr, equations);
+}
+
+#[test]
+fn equations_are_printed_as_they_are_written() {
+    let code = "f 0 = 1\n\nf (Some x) = let\ng [] = x\ng [_, ..rest] = g rest\nin g\n";
+    let program = parse(code).unwrap();
+    assert_eq!(parse(&print::program(&program)).unwrap(), program);
+    assert!(matches!(program.expr[1], Expr::Equation(_)));
+}
+
+#[test]
+fn operators_bind_by_precedence() {
+    let eq = Expr::Binary(
+        BinaryOp::Eq,
+        Box::new(variable("a")),
+        Box::new(Expr::Binary(
+            BinaryOp::Mul,
+            Box::new(variable("b")),
+            Box::new(integer(2)),
+        )),
+    );
+    let or = Expr::Binary(BinaryOp::LogicalOr, Box::new(eq), Box::new(variable("c")));
+    assert_parsed("x = a == b * 2 || c", vec![Expr::Def(def("x", or))]);
+}
+
+#[test]
+fn operators_are_single_tokens() {
+    let less_eq = Expr::Binary(
+        BinaryOp::LessEq,
+        Box::new(variable("a")),
+        Box::new(variable("b")),
+    );
+    assert_parsed("x = a <= b", vec![Expr::Def(def("x", less_eq))]);
+
+    // `<` and `=` with a space between them are not `<=`
+    assert!(parse("x = a < = b").is_err());
+}
+
+#[test]
+fn tries_follow_their_expression() {
+    let tried = Expr::Try(Box::new(Expr::Apply(
+        Box::new(variable("f")),
+        vec![variable("a")],
+    )));
+    let sum = Expr::Binary(BinaryOp::Add, Box::new(tried), Box::new(integer(1)));
+    assert_parsed("x = (f a)? + 1", vec![Expr::Def(def("x", sum))]);
+}
+
+#[test]
+fn single_names_in_patterns_are_variables_or_variants() {
+    let arm = |pattern, expr| When {
+        pattern,
+        guard: None,
+        expr: Box::new(expr),
+    };
+    let none = Pattern::Variant {
+        path: TypePath(vec![TypeName("None".to_string())]),
+        members: Vec::new(),
+    };
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("y")),
+        arms: vec![
+            arm(none, integer(0)),
+            arm(
+                Pattern::Variable(VariableName("n".to_string())),
+                variable("n"),
+            ),
+        ],
+        otherwise: None,
+    });
+    assert_parsed(
+        "x = match y when None -> 0 when n -> n",
+        vec![Expr::Def(def("x", matchwhen))],
+    );
+}
+
+#[test]
+fn literals_in_patterns_are_matched_by_value() {
+    let arm = |pattern, expr| When {
+        pattern,
+        guard: None,
+        expr: Box::new(expr),
+    };
+    let literal = |value| match value {
+        Expr::Literal(literal) => Pattern::Literal(literal),
+        _ => unreachable!(),
+    };
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("y")),
+        arms: vec![
+            arm(literal(integer(0)), integer(1)),
+            arm(literal(integer(-1)), integer(2)),
+            arm(
+                Pattern::Variable(VariableName("n".to_string())),
+                variable("n"),
+            ),
+        ],
+        otherwise: None,
+    });
+    assert_parsed(
+        "x = match y when 0 -> 1 when -1 -> 2 when n -> n",
+        vec![Expr::Def(def("x", matchwhen))],
+    );
+}
+
+#[test]
+fn list_patterns_end_with_the_rest() {
+    let arm = |pattern, expr| When {
+        pattern,
+        guard: None,
+        expr: Box::new(expr),
+    };
+    let first = Pattern::List {
+        elements: vec![Pattern::Variable(VariableName("a".to_string()))],
+        rest: Some(Box::new(Pattern::Wildcard)),
+    };
+    let empty = Pattern::List {
+        elements: Vec::new(),
+        rest: None,
+    };
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("y")),
+        arms: vec![arm(first, variable("a")), arm(empty, integer(0))],
+        otherwise: None,
+    });
+    assert_parsed(
+        "x = match y when [a, .._] -> a when [] -> 0",
+        vec![Expr::Def(def("x", matchwhen))],
+    );
+
+    let errors = parse("x = match y when [..a, b] -> b").unwrap_err();
+    assert_eq!(
+        messages(errors),
+        ["Only the last element of a list can be the rest"]
+    );
+}
+
+#[test]
+fn guards_follow_the_pattern() {
+    let n = || Pattern::Variable(VariableName("n".to_string()));
+    let positive = Expr::Binary(
+        BinaryOp::More,
+        Box::new(variable("n")),
+        Box::new(integer(0)),
+    );
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("y")),
+        arms: vec![
+            When {
+                pattern: n(),
+                guard: Some(Box::new(positive)),
+                expr: Box::new(variable("n")),
+            },
+            When {
+                pattern: n(),
+                guard: None,
+                expr: Box::new(integer(0)),
+            },
+        ],
+        otherwise: None,
+    });
+    assert_parsed(
+        "x = match y when n when n > 0 -> n when n -> 0",
+        vec![Expr::Def(def("x", matchwhen))],
+    );
+}
+
+#[test]
+fn lets_in_do_blocks_are_statements_without_in() {
+    let letin = Expr::LetIn(LetIns {
+        items: vec![LetIn::Def(def("b", integer(2)))],
+        expr: Box::new(variable("b")),
+    });
+    let block = Expr::Do(DoBlock {
+        statements: vec![
+            DoStatement::Let(VariableName("a".to_string()), integer(1)),
+            DoStatement::Run(letin),
+        ],
+        result: Box::new(variable("a")),
+    });
+    assert_parsed(
+        "x = do { let a = 1, let b = 2 in b, a }",
+        vec![Expr::Def(def("x", block))],
+    );
+
+    let errors = parse("x = do { let a = 1 }").unwrap_err();
+    assert_eq!(messages(errors), ["A do block ends with an expression"]);
+}
+
+#[test]
+fn comments_are_skipped() {
+    assert_parsed(
+        "# one\nx = 1 # two\n",
+        vec![Expr::Def(def("x", integer(1)))],
+    );
+}
+
+#[test]
+fn attributes_of_items_are_skipped() {
+    assert_parsed("@test\npub x = 1", vec![Expr::Def(def("x", integer(1)))]);
+    assert_parsed(
+        "@cfg(not(debug), feature = \"json\")\nx = 1",
+        vec![Expr::Def(def("x", integer(1)))],
+    );
+}
+
+#[test]
+fn attributes_of_declarations_are_kept() {
+    let decl = Decl {
+        attributes: vec![Attribute {
+            name: "inline".to_string(),
+            args: Vec::new(),
+        }],
+        lhs: VariableName("x".to_string()),
+        rhs: DeclType::TypeName(TypeName("i64".to_string())),
+        whereclause: None,
+    };
+    assert_parsed(
+        "@inline\nx : i64 = 1",
+        vec![Expr::Decl(decl), Expr::Def(def("x", integer(1)))],
+    );
+}
+
+#[test]
+fn records_are_written_in_braces() {
+    let field = |name: &str, expr| Field {
+        name: VariableName(name.to_string()),
+        expr,
+    };
+    let record = Expr::Record(Record {
+        fields: vec![field("x", integer(0)), field("y", variable("b"))],
+    });
+    let empty = Expr::Record(Record { fields: Vec::new() });
+    assert_parsed(
+        "a = { x = 0, y = b }\n\nc = {}",
+        vec![Expr::Def(def("a", record)), Expr::Def(def("c", empty))],
+    );
+}
+
+#[test]
+fn variants_with_named_fields_are_parsed() {
+    let f64 = || Some(DefArgType::TypeName(TypeName("f64".to_string())));
+    let variant = |name: &str, members: &[&str]| EnumTypeDef {
+        name: TypeName(name.to_string()),
+        members: members
+            .iter()
+            .map(|member| DefArg {
+                name: VariableName(member.to_string()),
+                ty: f64(),
+                pattern: None,
+            })
+            .collect(),
+        types: Vec::new(),
+    };
+    let shape = EnumDef {
+        name: TypeName("Shape".to_string()),
+        params: Vec::new(),
+        variants: vec![variant("Circle", &["radius"]), variant("Rect", &["w", "h"])],
+        whereclause: None,
+    };
+    let path = |variant: &str| {
+        TypePath(vec![
+            TypeName("Shape".to_string()),
+            TypeName(variant.to_string()),
+        ])
+    };
+    let field = |name: &str, expr| Field {
+        name: VariableName(name.to_string()),
+        expr,
+    };
+    let rect = Expr::Construct(Construct {
+        path: path("Rect"),
+        fields: vec![field("w", variable("w")), field("h", integer(1))],
+    });
+    let circle = Pattern::Fields {
+        path: path("Circle"),
+        fields: vec![FieldPattern {
+            name: VariableName("radius".to_string()),
+            pattern: Pattern::Variable(VariableName("r".to_string())),
+        }],
+    };
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("s")),
+        arms: vec![When {
+            pattern: circle,
+            guard: None,
+            expr: Box::new(variable("r")),
+        }],
+        otherwise: Some(Box::new(integer(0))),
+    });
+    assert_parsed(
+        "enum Shape =\n    Circle { radius: f64 }\n    | Rect { w: f64, h: f64 }\n\n\
+         x = Shape.Rect { w, h: 1 }\n\n\
+         y = match s when Shape.Circle { radius: r } -> r else 0",
+        vec![
+            Expr::Enum(shape),
+            Expr::Def(def("x", rect)),
+            Expr::Def(def("y", matchwhen)),
+        ],
+    );
+
+    let errors = parse("y = match s when Shape.Rect { w: a, b } -> a").unwrap_err();
+    assert_eq!(
+        messages(errors),
+        ["The members of a variant are either all named or none are"]
+    );
+}
+
+#[test]
+fn ascriptions_are_in_parentheses() {
+    let ascribed = |expr, ty: &str| {
+        Expr::Ascription(Box::new(expr), DeclType::TypeName(TypeName(ty.to_string())))
+    };
+    let product = Expr::Binary(BinaryOp::Mul, Box::new(variable("x")), Box::new(integer(2)));
+    let lambda = Expr::Lambda(DefRhs {
+        args: vec![DefArg {
+            name: VariableName("x".to_string()),
+            ty: None,
+            pattern: None,
+        }],
+        expr: Box::new(variable("u8")),
+    });
+    assert_parsed(
+        "a = (x * 2: i64)\n\nb = ((x): u8)\n\nc = f (x: u8)",
+        vec![
+            Expr::Def(def("a", ascribed(product, "i64"))),
+            Expr::Def(def("b", ascribed(variable("x"), "u8"))),
+            Expr::Def(def("c", Expr::Apply(Box::new(variable("f")), vec![lambda]))),
+        ],
+    );
+}
+
+#[test]
+fn unit_is_a_value_unless_an_arrow_follows() {
+    let unit = || Expr::Literal(Literal::Unit);
+    let later = Def {
+        lhs: VariableName("later".to_string()),
+        rhs: DefRhs {
+            args: Vec::new(),
+            expr: Box::new(Expr::Lambda(DefRhs {
+                args: Vec::new(),
+                expr: Box::new(unit()),
+            })),
+        },
+    };
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("x")),
+        arms: vec![When {
+            pattern: Pattern::Literal(Literal::Unit),
+            guard: Some(Box::new(unit())),
+            expr: Box::new(unit()),
+        }],
+        otherwise: None,
+    });
+    assert_parsed(
+        "nothing = ()\n\nlater = () -> ()\n\ny = match x when () when () -> ()",
+        vec![
+            Expr::Def(def("nothing", unit())),
+            Expr::Def(later),
+            Expr::Def(def("y", matchwhen)),
+        ],
+    );
+}
+
+#[test]
+fn bytes_are_literals_and_patterns() {
+    let bytes = || {
+        Literal::Bytes(Bytes {
+            value: b"vunk".to_vec(),
+        })
+    };
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("header")),
+        arms: vec![When {
+            pattern: Pattern::Literal(bytes()),
+            guard: None,
+            expr: Box::new(integer(1)),
+        }],
+        otherwise: None,
+    });
+    assert_parsed(
+        "magic = b\"vunk\"\n\nversion = match header when b\"vunk\" -> 1",
+        vec![
+            Expr::Def(def("magic", Expr::Literal(bytes()))),
+            Expr::Def(def("version", matchwhen)),
+        ],
+    );
+}
+
+#[test]
+fn the_examples_are_parsed() {
+    let examples = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../vunk-examples");
+    for entry in std::fs::read_dir(examples).unwrap() {
+        let path = entry.unwrap().path();
+        let code = std::fs::read_to_string(&path).unwrap();
+        assert!(parse(&code).is_ok(), "{} is not parsed", path.display());
+    }
+}
+
+#[test]
+fn uses_types_and_public_items_are_parsed() {
+    let import = Import {
+        path: vec!["Std".to_string(), "List".to_string()],
+        alias: None,
+        glob: false,
+    };
+    let point = TypeDef {
+        name: TypeName("Point".to_string()),
+        params: Vec::new(),
+        members: vec![DefArg {
+            name: VariableName("x".to_string()),
+            ty: Some(DefArgType::TypeName(TypeName("i64".to_string()))),
+            pattern: None,
+        }],
+        generics: None,
+    };
+    assert_parsed(
+        "use Std.List
+
+type Point = { x: i64 }
+
+pub y = 1",
+        vec![
+            Expr::Use(import),
+            Expr::Type(point),
+            Expr::Def(def("y", integer(1))),
+        ],
+    );
+}
+
+#[test]
+fn uses_are_aliased_with_as() {
+    let import = Import {
+        path: vec!["Std".to_string(), "List".to_string()],
+        alias: Some("L".to_string()),
+        glob: false,
+    };
+    let map = Expr::Apply(Box::new(variable("L.map")), vec![variable("f")]);
+    assert_parsed(
+        "use Std.List as L\n\nx = L.map f",
+        vec![Expr::Use(import), Expr::Def(def("x", map))],
+    );
+}
+
+#[test]
+fn glob_uses_end_in_a_star() {
+    let import = Import {
+        path: vec!["Std".to_string(), "List".to_string()],
+        alias: None,
+        glob: true,
+    };
+    assert_parsed(
+        "use Std.List.*\n\nx = map",
+        vec![Expr::Use(import), Expr::Def(def("x", variable("map")))],
+    );
+}
+
+#[test]
+fn deeply_nested_brackets_are_errors() {
+    let nested = |depth| format!("x = {}1{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(parse(&nested(256)).is_ok());
+
+    let errors = parse(&nested(257)).unwrap_err();
+    assert_eq!(messages(errors), ["Brackets are nested too deeply"]);
+}
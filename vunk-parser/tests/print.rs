@@ -0,0 +1,225 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_parser::ast::decl::DeclType;
+use vunk_parser::ast::def::Def;
+use vunk_parser::ast::def::DefArg;
+use vunk_parser::ast::def::DefRhs;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::ifelse::IfElse;
+use vunk_parser::ast::literal::Integer;
+use vunk_parser::ast::literal::IntegerValue;
+use vunk_parser::ast::literal::Literal;
+use vunk_parser::ast::matchwhen::MatchWhen;
+use vunk_parser::ast::matchwhen::When;
+use vunk_parser::ast::name::TypeName;
+use vunk_parser::ast::name::TypePath;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::op::BinaryOp;
+use vunk_parser::ast::pattern::FieldPattern;
+use vunk_parser::ast::pattern::Pattern;
+use vunk_parser::ast::program::Program;
+use vunk_parser::ast::record::Construct;
+use vunk_parser::ast::record::Field;
+use vunk_parser::ast::record::Record;
+use vunk_parser::generate;
+use vunk_parser::print;
+
+fn variable(name: &str) -> Expr {
+    Expr::Variable(VariableName(name.to_string()))
+}
+
+fn integer(value: i64) -> Expr {
+    Expr::Literal(Literal::Integer(Integer {
+        value: IntegerValue::I64(value),
+    }))
+}
+
+#[test]
+fn subexpressions_are_parenthesized_where_needed() {
+    // f = (x) -> if g x then x - -1 else x
+    let program = Program {
+        expr: vec![Expr::Def(Def {
+            lhs: VariableName("f".to_string()),
+            rhs: DefRhs {
+                args: vec![DefArg {
+                    name: VariableName("x".to_string()),
+                    ty: None,
+                    pattern: None,
+                }],
+                expr: Box::new(Expr::IfElse(IfElse {
+                    condition: Box::new(Expr::Apply(Box::new(variable("g")), vec![variable("x")])),
+                    tru: Box::new(Expr::Binary(
+                        BinaryOp::Sub,
+                        Box::new(variable("x")),
+                        Box::new(integer(-1)),
+                    )),
+                    fals: Box::new(variable("x")),
+                })),
+            },
+        })],
+    };
+
+    assert_eq!(
+        print::program(&program),
+        "f = (x) -> if g x then x - (-1) else x\n"
+    );
+}
+
+#[test]
+fn items_are_separated_by_empty_lines() {
+    let def = |name: &str, value| {
+        Expr::Def(Def {
+            lhs: VariableName(name.to_string()),
+            rhs: DefRhs {
+                args: Vec::new(),
+                expr: Box::new(integer(value)),
+            },
+        })
+    };
+    let program = Program {
+        expr: vec![def("a", 1), def("b", 2)],
+    };
+
+    assert_eq!(print::program(&program), "a = 1\n\nb = 2\n");
+}
+
+#[test]
+fn guards_follow_the_pattern() {
+    // match x when n when n > 0 -> n else 0
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("x")),
+        arms: vec![When {
+            pattern: Pattern::Variable(VariableName("n".to_string())),
+            guard: Some(Box::new(Expr::Binary(
+                BinaryOp::More,
+                Box::new(variable("n")),
+                Box::new(integer(0)),
+            ))),
+            expr: Box::new(variable("n")),
+        }],
+        otherwise: Some(Box::new(integer(0))),
+    });
+
+    assert_eq!(
+        print::expr(&matchwhen),
+        "match x when n when n > 0 -> n else 0"
+    );
+}
+
+#[test]
+fn spreads_are_written_with_dots() {
+    let spread = |name| Expr::Spread(Box::new(variable(name)));
+    let list = Expr::Literal(Literal::List(vec![spread("xs"), integer(1), spread("ys")]));
+    assert_eq!(print::expr(&list), "[..xs 1 ..ys]");
+
+    let pattern = |rest: Option<Pattern>| Pattern::List {
+        elements: vec![
+            Pattern::Variable(VariableName("first".to_string())),
+            Pattern::Wildcard,
+        ],
+        rest: rest.map(Box::new),
+    };
+    assert_eq!(print::pattern(&pattern(None)), "[first, _]");
+    let rest = Pattern::Variable(VariableName("rest".to_string()));
+    assert_eq!(print::pattern(&pattern(Some(rest))), "[first, _, ..rest]");
+}
+
+#[test]
+fn records_assign_their_fields() {
+    let field = |name: &str, expr| Field {
+        name: VariableName(name.to_string()),
+        expr,
+    };
+    let point = Expr::Record(Record {
+        fields: vec![field("x", integer(1)), field("y", variable("y"))],
+    });
+    assert_eq!(print::expr(&point), "{ x = 1, y = y }");
+    assert_eq!(
+        print::expr(&Expr::Record(Record { fields: Vec::new() })),
+        "{}"
+    );
+}
+
+#[test]
+fn variants_with_named_fields_name_them() {
+    let path = || {
+        TypePath(vec![
+            TypeName("Shape".to_string()),
+            TypeName("Rect".to_string()),
+        ])
+    };
+    let field = |name: &str, expr| Field {
+        name: VariableName(name.to_string()),
+        expr,
+    };
+    let rect = Expr::Construct(Construct {
+        path: path(),
+        fields: vec![field("w", integer(2)), field("h", variable("h"))],
+    });
+    assert_eq!(print::expr(&rect), "Shape.Rect { w: 2, h }");
+
+    let pattern = Pattern::Fields {
+        path: path(),
+        fields: vec![FieldPattern {
+            name: VariableName("w".to_string()),
+            pattern: Pattern::Wildcard,
+        }],
+    };
+    assert_eq!(print::pattern(&pattern), "Shape.Rect { w: _ }");
+}
+
+#[test]
+fn ascriptions_keep_their_parentheses() {
+    let ascribed = |expr, ty: &str| {
+        Expr::Ascription(Box::new(expr), DeclType::TypeName(TypeName(ty.to_string())))
+    };
+    let sum = Expr::Binary(
+        BinaryOp::Add,
+        Box::new(ascribed(integer(1), "u8")),
+        Box::new(integer(2)),
+    );
+    assert_eq!(print::expr(&sum), "(1: u8) + 2");
+
+    // Not a lambda with the parameter `ready`
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("x")),
+        arms: vec![When {
+            pattern: Pattern::Wildcard,
+            guard: Some(Box::new(ascribed(variable("ready"), "Bool"))),
+            expr: Box::new(integer(1)),
+        }],
+        otherwise: None,
+    });
+    assert_eq!(
+        print::expr(&matchwhen),
+        "match x when _ when ((ready): Bool) -> 1"
+    );
+}
+
+#[test]
+fn unit_is_empty_parentheses() {
+    let unit = || Box::new(Expr::Literal(Literal::Unit));
+    let apply = Expr::Apply(
+        Box::new(variable("run")),
+        vec![Expr::Literal(Literal::Unit)],
+    );
+    assert_eq!(print::expr(&apply), "run ()");
+
+    let matchwhen = Expr::MatchWhen(MatchWhen {
+        expr: Box::new(variable("x")),
+        arms: vec![When {
+            pattern: Pattern::Literal(Literal::Unit),
+            guard: Some(unit()),
+            expr: unit(),
+        }],
+        otherwise: None,
+    });
+    assert_eq!(print::expr(&matchwhen), "match x when () when () -> ()");
+}
+
+#[test]
+fn generating_is_deterministic() {
+    assert_eq!(generate::program(7, 4), generate::program(7, 4));
+}
@@ -0,0 +1,198 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use chumsky::Parser;
+use vunk_parser::ast::decl::Decl;
+use vunk_parser::ast::decl::DeclType;
+use vunk_parser::ast::def::Def;
+use vunk_parser::ast::def::DefRhs;
+use vunk_parser::ast::expr::Expr;
+use vunk_parser::ast::literal::Bool;
+use vunk_parser::ast::literal::Literal;
+use vunk_parser::ast::name::TraitName;
+use vunk_parser::ast::name::TypeName;
+use vunk_parser::ast::name::VariableName;
+use vunk_parser::ast::program::Program;
+use vunk_parser::ast::traitdef::AssocType;
+use vunk_parser::ast::traitdef::TraitDef;
+use vunk_parser::ast::traitdef::TraitImpl;
+use vunk_parser::parse;
+use vunk_parser::print;
+use vunk_parser::traits::check;
+use vunk_parser::traits::project;
+use vunk_parser::traits::TraitError;
+
+fn decl(name: &str) -> Decl {
+    typed_decl(name, "Bool")
+}
+
+fn typed_decl(name: &str, ty: &str) -> Decl {
+    Decl {
+        attributes: Vec::new(),
+        lhs: VariableName(name.to_string()),
+        rhs: DeclType::TypeName(TypeName(ty.to_string())),
+        whereclause: None,
+    }
+}
+
+fn def(name: &str) -> Def {
+    Def {
+        lhs: VariableName(name.to_string()),
+        rhs: DefRhs {
+            args: Vec::new(),
+            expr: Box::new(Expr::Literal(Literal::Bool(Bool { value: true }))),
+        },
+    }
+}
+
+// `trait Eq` with the methods `eq` and `ne`, of which `ne` has a default, and an `impl` of it
+fn program(methods: &[&str]) -> Program {
+    Program {
+        expr: vec![
+            Expr::Trait(TraitDef {
+                name: TraitName("Eq".to_string()),
+                types: Vec::new(),
+                methods: vec![decl("eq"), decl("ne")],
+                defaults: vec![def("ne")],
+            }),
+            Expr::Impl(TraitImpl {
+                trait_name: TraitName("Eq".to_string()),
+                type_name: TypeName("Point".to_string()),
+                types: Vec::new(),
+                decls: Vec::new(),
+                methods: methods.iter().map(|name| def(name)).collect(),
+            }),
+        ],
+    }
+}
+
+// `trait Collection` with the associated type `Item`, an `impl` of it on `Bag` with the given
+// types, and a declaration of the given type
+fn collection(types: &[&str], ty: &str) -> Program {
+    Program {
+        expr: vec![
+            Expr::Trait(TraitDef {
+                name: TraitName("Collection".to_string()),
+                types: vec![TypeName("Item".to_string())],
+                methods: vec![typed_decl("first", "Self.Item")],
+                defaults: Vec::new(),
+            }),
+            Expr::Impl(TraitImpl {
+                trait_name: TraitName("Collection".to_string()),
+                type_name: TypeName("Bag".to_string()),
+                types: types
+                    .iter()
+                    .map(|name| AssocType {
+                        name: TypeName(name.to_string()),
+                        ty: TypeName("i64".to_string()),
+                    })
+                    .collect(),
+                decls: Vec::new(),
+                methods: vec![def("first")],
+            }),
+            Expr::Decl(typed_decl("item", ty)),
+        ],
+    }
+}
+
+#[test]
+fn traits_and_impls_are_printed_with_their_items() {
+    assert_eq!(
+        print::program(&program(&["eq"])),
+        "trait Eq = { eq : Bool ne : Bool ne = true }\n\nimpl Eq on Point = { eq = true }\n"
+    );
+}
+
+#[test]
+fn traits_and_impls_are_parsed_from_source() {
+    let code = "trait Eq =\n    { eq: Bool\n      ne: Bool\n      ne = true\n    }\n\n\
+                impl Eq on Point =\n    { eq = true\n    }\n";
+    let tokens = vunk_lexer::lexer().parse(code).unwrap();
+    let line_starts = parse::line_starts(code, &tokens);
+    assert_eq!(
+        parse::parse(tokens, &line_starts).unwrap(),
+        program(&["eq"])
+    );
+}
+
+#[test]
+fn impls_can_leave_out_methods_with_defaults() {
+    assert!(check(&program(&["eq"])).is_ok());
+    assert!(check(&program(&["eq", "ne"])).is_ok());
+}
+
+#[test]
+fn impls_define_the_methods_without_defaults() {
+    match check(&program(&["ne"])) {
+        Err(error @ TraitError::MissingMethods { .. }) => assert_eq!(
+            error.to_string(),
+            "impl Eq on Point does not define 'eq', which have no default"
+        ),
+        other => panic!("Expected missing methods, got {:?}", other),
+    }
+}
+
+#[test]
+fn impls_only_define_methods_of_the_trait() {
+    assert!(matches!(
+        check(&program(&["eq", "hash"])),
+        Err(TraitError::UnknownMethod { method, .. }) if method == "hash"
+    ));
+}
+
+#[test]
+fn associated_types_are_printed_first() {
+    assert_eq!(
+        print::expr(&collection(&["Item"], "i64").expr[1]),
+        "impl Collection on Bag = { type Item = i64 first = true }"
+    );
+}
+
+#[test]
+fn impls_define_the_associated_types() {
+    assert!(check(&collection(&["Item"], "Collection.Item")).is_ok());
+    assert!(matches!(
+        check(&collection(&[], "i64")),
+        Err(TraitError::MissingType { ty, .. }) if ty == "Item"
+    ));
+    assert!(matches!(
+        check(&collection(&["Item", "Key"], "i64")),
+        Err(TraitError::UnknownType { ty, .. }) if ty == "Key"
+    ));
+}
+
+#[test]
+fn projections_name_associated_types() {
+    match check(&collection(&["Item"], "Collection.Key")) {
+        Err(error @ TraitError::UnknownProjection { .. }) => assert_eq!(
+            error.to_string(),
+            "Collection.Key is not an associated type of Collection"
+        ),
+        other => panic!("Expected an unknown projection, got {:?}", other),
+    }
+}
+
+#[test]
+fn associated_types_are_parsed_from_source() {
+    let code = "trait Collection = { type Item first: Self.Item }\n\n\
+                impl Collection on Bag = { type Item = i64 first = true }\n\n\
+                item: Collection.Item\n";
+    let tokens = vunk_lexer::lexer().parse(code).unwrap();
+    let line_starts = parse::line_starts(code, &tokens);
+    assert_eq!(
+        parse::parse(tokens, &line_starts).unwrap(),
+        collection(&["Item"], "Collection.Item")
+    );
+}
+
+#[test]
+fn projections_resolve_to_the_type_of_the_impl() {
+    let program = collection(&["Item"], "Collection.Item");
+    assert_eq!(
+        project(&program, "Collection.Item", "Bag"),
+        Some(&TypeName("i64".to_string()))
+    );
+    assert_eq!(project(&program, "Collection.Item", "List"), None);
+    assert_eq!(project(&program, "Collection.Key", "Bag"), None);
+}
@@ -0,0 +1,194 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Arithmetic on numeric values
+//!
+//! `Int` and `BigInt` can be mixed freely. An operation involving a `BigInt` gives an `Int` when the
+//! result fits into one, and a `BigInt` only when it does not, so `big 5 - big 4` is the `Int` `1`.
+//!
+//! `Int` is a 64 bit two's complement integer. Whenever the mathematical result of an operation
+//! on `Int`s is not representable as such (including `MIN / -1`), the operation fails with
+//! [`RuntimeError::IntegerOverflow`]. The remainder of `MIN` by `-1` is `0`, like any remainder by
+//! `-1`. Dividing by zero fails with
+//! [`RuntimeError::DivisionByZero`]. This does not depend on how the runtime itself was compiled,
+//! as only the `checked_*` integer methods are used here. Wrapping arithmetic is available
+//! explicitly via `Std.Int` (see [`crate::stdlib::int`]).
+//!
+//! Integer division comes in two kinds, which differ for negative operands:
+//!
+//! | `a`  | `b`  | `a / b`, `quot` | `a % b`, `rem` | `div` | `mod` |
+//! |------|------|-----------------|----------------|-------|-------|
+//! | `7`  | `2`  | `3`             | `1`            | `3`   | `1`   |
+//! | `-7` | `2`  | `-3`            | `-1`           | `-4`  | `1`   |
+//! | `7`  | `-2` | `-3`            | `1`            | `-4`  | `-1`  |
+//! | `-7` | `-2` | `3`             | `-1`           | `3`   | `-1`  |
+//!
+//! `/` and `%` truncate: the quotient is rounded towards zero, and the remainder has the sign of
+//! the dividend, as with the integers of most targets and in constant folding. The floored
+//! [`div_floor`] and [`mod_floor`] round the quotient towards negative infinity, so the remainder
+//! has the sign of the divisor, which is what wrapping an index around needs. In both kinds,
+//! `quotient * b + remainder == a`. For `Float`s, `/` is the division of real numbers.
+
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+use num_bigint::Sign;
+
+use crate::error::RuntimeError;
+use crate::value::Value;
+
+pub fn add(lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    numeric("+", lhs, rhs, i64::checked_add, |a, b| a + b, |a, b| a + b)
+}
+
+pub fn sub(lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    numeric("-", lhs, rhs, i64::checked_sub, |a, b| a - b, |a, b| a - b)
+}
+
+pub fn mul(lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    numeric("*", lhs, rhs, i64::checked_mul, |a, b| a * b, |a, b| a * b)
+}
+
+pub fn div(lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    check_divisor(rhs)?;
+    numeric("/", lhs, rhs, i64::checked_div, |a, b| a / b, |a, b| a / b)
+}
+
+pub fn rem(lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    check_divisor(rhs)?;
+    numeric("%", lhs, rhs, checked_rem, |a, b| a % b, |a, b| a % b)
+}
+
+/// `Std.Int.div`, the quotient rounded towards negative infinity
+pub fn div_floor(lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    check_divisor(rhs)?;
+    numeric("div", lhs, rhs, int_div_floor, big_div_floor, |a, b| {
+        (a / b).floor()
+    })
+}
+
+/// `Std.Int.mod`, the remainder of [`div_floor`], which has the sign of the divisor
+pub fn mod_floor(lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    check_divisor(rhs)?;
+    numeric("mod", lhs, rhs, int_mod_floor, big_mod_floor, |a, b| {
+        a - b * (a / b).floor()
+    })
+}
+
+pub fn neg(value: &Value) -> Result<Value, RuntimeError> {
+    match value {
+        Value::Integer(i) => i
+            .checked_neg()
+            .map(Value::Integer)
+            .ok_or(RuntimeError::IntegerOverflow { op: "-" }),
+        Value::BigInt(i) => Ok(integer(-&***i)),
+        Value::Float(f) => Ok(Value::Float(-f)),
+        other => Err(RuntimeError::TypeMismatch {
+            op: "-",
+            lhs: other.type_name().to_string(),
+            rhs: other.type_name().to_string(),
+        }),
+    }
+}
+
+// Whether the truncated quotient is one more than the floored one, which it is when the remainder
+// is not zero and its sign differs from the sign of the divisor
+fn rounded_up(remainder: Sign, divisor: Sign) -> bool {
+    remainder != Sign::NoSign && remainder != divisor
+}
+
+fn sign(i: i64) -> Sign {
+    match i.cmp(&0) {
+        Ordering::Less => Sign::Minus,
+        Ordering::Equal => Sign::NoSign,
+        Ordering::Greater => Sign::Plus,
+    }
+}
+
+/// The truncated remainder, or `None` when dividing by zero
+///
+/// Unlike [`i64::checked_rem`], this is `Some(0)` for `MIN % -1`. Computing it overflows on most
+/// targets, but the remainder itself is representable.
+pub(crate) fn checked_rem(a: i64, b: i64) -> Option<i64> {
+    if b == -1 {
+        Some(0)
+    } else {
+        a.checked_rem(b)
+    }
+}
+
+fn int_div_floor(a: i64, b: i64) -> Option<i64> {
+    let quotient = a.checked_div(b)?;
+    if rounded_up(sign(checked_rem(a, b)?), sign(b)) {
+        quotient.checked_sub(1)
+    } else {
+        Some(quotient)
+    }
+}
+
+fn int_mod_floor(a: i64, b: i64) -> Option<i64> {
+    let remainder = checked_rem(a, b)?;
+    if rounded_up(sign(remainder), sign(b)) {
+        remainder.checked_add(b)
+    } else {
+        Some(remainder)
+    }
+}
+
+fn big_div_floor(a: &BigInt, b: &BigInt) -> BigInt {
+    if rounded_up((a % b).sign(), b.sign()) {
+        a / b - 1
+    } else {
+        a / b
+    }
+}
+
+fn big_mod_floor(a: &BigInt, b: &BigInt) -> BigInt {
+    let remainder = a % b;
+    if rounded_up(remainder.sign(), b.sign()) {
+        remainder + b
+    } else {
+        remainder
+    }
+}
+
+// The result of an operation on big integers, as an `Int` if it fits into one
+fn integer(i: BigInt) -> Value {
+    match i64::try_from(&i) {
+        Ok(i) => Value::Integer(i),
+        Err(_) => Value::big_int(i),
+    }
+}
+
+fn check_divisor(rhs: &Value) -> Result<(), RuntimeError> {
+    match rhs {
+        Value::Integer(0) => Err(RuntimeError::DivisionByZero),
+        Value::BigInt(i) if i.sign() == Sign::NoSign => Err(RuntimeError::DivisionByZero),
+        _ => Ok(()),
+    }
+}
+
+fn numeric(
+    op: &'static str,
+    lhs: &Value,
+    rhs: &Value,
+    int: fn(i64, i64) -> Option<i64>,
+    big: fn(&BigInt, &BigInt) -> BigInt,
+    float: fn(f64, f64) -> f64,
+) -> Result<Value, RuntimeError> {
+    match (lhs, rhs) {
+        (Value::Integer(a), Value::Integer(b)) => int(*a, *b)
+            .map(Value::Integer)
+            .ok_or(RuntimeError::IntegerOverflow { op }),
+        (Value::Integer(a), Value::BigInt(b)) => Ok(integer(big(&BigInt::from(*a), b))),
+        (Value::BigInt(a), Value::Integer(b)) => Ok(integer(big(a, &BigInt::from(*b)))),
+        (Value::BigInt(a), Value::BigInt(b)) => Ok(integer(big(a, b))),
+        (Value::Float(a), Value::Float(b)) => Ok(Value::Float(float(*a, *b))),
+        (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+            op,
+            lhs: lhs.type_name().to_string(),
+            rhs: rhs.type_name().to_string(),
+        }),
+    }
+}
@@ -0,0 +1,54 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! String builders, values of type `StringBuilder`
+//!
+//! Joining strings with `++` copies both of them, so building a string of `n` pieces one by one
+//! takes `O(n²)`. A builder is a rope: it holds the pieces pushed to it without copying them, in
+//! a persistent vector, and only copies them once, into a single string, when it is built.
+//!
+//! Builders are values like any other, so pushing a piece returns a new builder and the old one
+//! can still be used. They share their pieces, so that is cheap too.
+
+use im::Vector;
+
+use crate::heap::HeapObject;
+use crate::heap::ObjectKind;
+use crate::heap::Ref;
+
+#[derive(Clone, Debug, Default)]
+pub struct Builder {
+    pieces: Vector<Ref<String>>,
+
+    /// The length of the built string in bytes, to allocate it at once
+    len: usize,
+}
+
+impl HeapObject for Builder {
+    const KIND: ObjectKind = ObjectKind::Builder;
+}
+
+impl Builder {
+    pub fn push(&mut self, piece: Ref<String>) {
+        self.len += piece.len();
+        self.pieces.push_back(piece);
+    }
+
+    /// The length of the built string in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn build(&self) -> String {
+        let mut built = String::with_capacity(self.len);
+        for piece in self.pieces.iter() {
+            built.push_str(piece);
+        }
+        built
+    }
+}
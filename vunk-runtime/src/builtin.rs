@@ -0,0 +1,253 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use regex::Regex;
+
+use crate::builder::Builder;
+use crate::cell::Cell;
+use crate::collection::Map;
+use crate::collection::Set;
+use crate::error::RuntimeError;
+use crate::function::Context;
+use crate::function::Function;
+use crate::heap::Ref;
+use crate::io::Io;
+use crate::task::Channel;
+use crate::task::Task;
+use crate::value::Record;
+use crate::value::Tuple;
+use crate::value::Value;
+use crate::value::Variant;
+
+pub type BuiltinFn =
+    Arc<dyn Fn(&mut dyn Context, Vec<Value>) -> Result<Value, RuntimeError> + Send + Sync>;
+
+/// A function that is implemented natively in the runtime
+#[derive(Clone)]
+pub struct Builtin {
+    pub name: String,
+
+    /// Number of arguments the builtin takes
+    ///
+    /// Builtins that take no arguments are constants, they are called when they are referenced.
+    pub arity: usize,
+
+    pub func: BuiltinFn,
+}
+
+impl std::fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "<builtin {}/{}>", self.name, self.arity)
+    }
+}
+
+/// The module of the prelude, whose items are in scope in every module that does not opt out
+pub const PRELUDE: &str = "Std.Prelude";
+
+/// Registry of all builtins, by their fully qualified name
+///
+/// Some builtins are part of the prelude, which means that they can also be referred to by an
+/// unqualified name, or as members of [`PRELUDE`], like `Std.Prelude.Some`.
+#[derive(Clone, Debug, Default)]
+pub struct Builtins {
+    functions: BTreeMap<String, Builtin>,
+    prelude: BTreeMap<String, String>,
+}
+
+impl Builtins {
+    /// All builtins of the standard library
+    pub fn std() -> Self {
+        let mut builtins = Builtins::default();
+        crate::stdlib::register(&mut builtins);
+        builtins
+    }
+
+    pub fn register<F>(&mut self, name: impl Into<String>, arity: usize, func: F)
+    where
+        F: Fn(&mut dyn Context, Vec<Value>) -> Result<Value, RuntimeError> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        self.functions.insert(
+            name.clone(),
+            Builtin {
+                name,
+                arity,
+                func: Arc::new(func),
+            },
+        );
+    }
+
+    /// Make a registered builtin available under an unqualified name
+    pub fn add_to_prelude(&mut self, name: impl Into<String>, qualified: impl Into<String>) {
+        self.prelude.insert(name.into(), qualified.into());
+    }
+
+    /// Get a builtin by its fully qualified name, or by its name in the prelude
+    pub fn get(&self, name: &str) -> Option<&Builtin> {
+        self.functions.get(name).or_else(|| {
+            let name = name
+                .strip_prefix(PRELUDE)
+                .and_then(|name| name.strip_prefix('.'))
+                .unwrap_or(name);
+            self.prelude
+                .get(name)
+                .and_then(|qualified| self.functions.get(qualified))
+        })
+    }
+
+    /// Get a builtin as a function value
+    pub fn value(&self, name: &str) -> Option<Value> {
+        self.get(name).map(|builtin| {
+            Value::function(Function::Builtin {
+                builtin: builtin.clone(),
+                args: Vec::new(),
+            })
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Builtin> {
+        self.functions.values()
+    }
+
+    /// The names in the prelude, with the fully qualified names they refer to
+    pub fn prelude(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.prelude
+            .iter()
+            .map(|(name, qualified)| (name.as_str(), qualified.as_str()))
+    }
+}
+
+fn invalid_argument(builtin: &str, expected: &'static str, found: &Value) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        builtin: builtin.to_string(),
+        expected,
+        found: found.type_name().to_string(),
+    }
+}
+
+pub fn int_arg(builtin: &str, value: &Value) -> Result<i64, RuntimeError> {
+    match value.force()? {
+        Value::Integer(i) => Ok(i),
+        other => Err(invalid_argument(builtin, "Int", &other)),
+    }
+}
+
+pub fn float_arg(builtin: &str, value: &Value) -> Result<f64, RuntimeError> {
+    match value.force()? {
+        Value::Float(x) => Ok(x),
+        other => Err(invalid_argument(builtin, "Float", &other)),
+    }
+}
+
+pub fn bool_arg(builtin: &str, value: &Value) -> Result<bool, RuntimeError> {
+    match value.force()? {
+        Value::Bool(b) => Ok(b),
+        other => Err(invalid_argument(builtin, "Bool", &other)),
+    }
+}
+
+pub fn list_arg(builtin: &str, value: &Value) -> Result<Ref<Vec<Value>>, RuntimeError> {
+    match value.force()? {
+        Value::List(list) => Ok(list),
+        other => Err(invalid_argument(builtin, "List", &other)),
+    }
+}
+
+pub fn str_arg(builtin: &str, value: &Value) -> Result<Ref<String>, RuntimeError> {
+    match value.force()? {
+        Value::Str(s) => Ok(s),
+        other => Err(invalid_argument(builtin, "String", &other)),
+    }
+}
+
+pub fn bytes_arg(builtin: &str, value: &Value) -> Result<Ref<Vec<u8>>, RuntimeError> {
+    match value.force()? {
+        Value::Bytes(bytes) => Ok(bytes),
+        other => Err(invalid_argument(builtin, "Bytes", &other)),
+    }
+}
+
+pub fn tuple_arg(builtin: &str, value: &Value, len: usize) -> Result<Ref<Tuple>, RuntimeError> {
+    match value.force()? {
+        Value::Tuple(t) if t.0.len() == len => Ok(t),
+        other => Err(invalid_argument(builtin, "Tuple", &other)),
+    }
+}
+
+pub fn record_arg(builtin: &str, value: &Value) -> Result<Ref<Record>, RuntimeError> {
+    match value.force()? {
+        Value::Record(record) => Ok(record),
+        other => Err(invalid_argument(builtin, "Record", &other)),
+    }
+}
+
+pub fn map_arg(builtin: &str, value: &Value) -> Result<Ref<Map>, RuntimeError> {
+    match value.force()? {
+        Value::Map(map) => Ok(map),
+        other => Err(invalid_argument(builtin, "Map", &other)),
+    }
+}
+
+pub fn set_arg(builtin: &str, value: &Value) -> Result<Ref<Set>, RuntimeError> {
+    match value.force()? {
+        Value::Set(set) => Ok(set),
+        other => Err(invalid_argument(builtin, "Set", &other)),
+    }
+}
+
+pub fn variant_arg(
+    builtin: &str,
+    value: &Value,
+    type_name: &'static str,
+) -> Result<Ref<Variant>, RuntimeError> {
+    match value.force()? {
+        Value::Variant(v) if v.type_name == type_name => Ok(v),
+        other => Err(invalid_argument(builtin, type_name, &other)),
+    }
+}
+
+pub fn io_arg(builtin: &str, value: &Value) -> Result<Ref<Io>, RuntimeError> {
+    match value.force()? {
+        Value::Io(io) => Ok(io),
+        other => Err(invalid_argument(builtin, "IO", &other)),
+    }
+}
+
+pub fn task_arg(builtin: &str, value: &Value) -> Result<Ref<Task>, RuntimeError> {
+    match value.force()? {
+        Value::Task(task) => Ok(task),
+        other => Err(invalid_argument(builtin, "Task", &other)),
+    }
+}
+
+pub fn channel_arg(builtin: &str, value: &Value) -> Result<Ref<Channel>, RuntimeError> {
+    match value.force()? {
+        Value::Channel(channel) => Ok(channel),
+        other => Err(invalid_argument(builtin, "Channel", &other)),
+    }
+}
+
+pub fn cell_arg(builtin: &str, value: &Value) -> Result<Ref<Cell>, RuntimeError> {
+    match value.force()? {
+        Value::Cell(cell) => Ok(cell),
+        other => Err(invalid_argument(builtin, "Ref", &other)),
+    }
+}
+
+pub fn builder_arg(builtin: &str, value: &Value) -> Result<Ref<Builder>, RuntimeError> {
+    match value.force()? {
+        Value::Builder(builder) => Ok(builder),
+        other => Err(invalid_argument(builtin, "StringBuilder", &other)),
+    }
+}
+
+pub fn regex_arg(builtin: &str, value: &Value) -> Result<Ref<Regex>, RuntimeError> {
+    match value.force()? {
+        Value::Regex(regex) => Ok(regex),
+        other => Err(invalid_argument(builtin, "Regex", &other)),
+    }
+}
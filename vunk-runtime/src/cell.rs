@@ -0,0 +1,49 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Mutable reference cells, values of type `Ref A`
+//!
+//! A cell holds a value that can be replaced. Creating, reading and writing a cell are IO actions
+//! (see `Std.Ref`), so evaluating an expression still never has side effects, and the order of
+//! the reads and writes is the order of the actions. Cells are shared between tasks, and every
+//! read and write is atomic.
+//!
+//! A cell can be written a value that refers to the cell itself, like `set r (Some r)`. The cell
+//! is then never freed, unless it is written another value first (see [`crate::heap`]).
+
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use crate::heap::HeapObject;
+use crate::heap::ObjectKind;
+use crate::value::Value;
+
+#[derive(Debug)]
+pub struct Cell {
+    value: Mutex<Value>,
+}
+
+impl HeapObject for Cell {
+    const KIND: ObjectKind = ObjectKind::Cell;
+}
+
+impl Cell {
+    pub fn new(value: Value) -> Self {
+        Cell {
+            value: Mutex::new(value),
+        }
+    }
+
+    pub fn get(&self) -> Value {
+        self.lock().clone()
+    }
+
+    pub fn set(&self, value: Value) {
+        *self.lock() = value;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Value> {
+        self.value.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
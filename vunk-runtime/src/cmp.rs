@@ -0,0 +1,120 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Structural equality and ordering, the one definition of `==` and `<` on values
+//!
+//! It is used by the built-in instances of `Eq` and `Ord`, by sorting and `Std.Debug.assertEq`,
+//! and by the instances `@derive(Eq, Ord)` gives named types (see `Std.Cmp`). Two values are
+//! equal when [`compare`] finds them equal, so equality and ordering never disagree.
+//!
+//! | Values                  | Ordered by                                                     |
+//! |-------------------------|----------------------------------------------------------------|
+//! | `()`, `Bool`, `String`  | `false < true`, strings by their code points                   |
+//! | `Int`, `BigInt`         | their numeric value, so they can be mixed                      |
+//! | `Float`                 | the IEEE total order: `-0.0 < 0.0`, and `NaN` equals itself    |
+//! | `Bytes`                 | their bytes, lexicographically                                 |
+//! | Lists, tuples           | their elements, lexicographically, so a prefix comes first     |
+//! | Maps, sets              | their entries, in the order of their keys, lexicographically   |
+//! | Records                 | their fields, in the order of the names of the fields          |
+//! | Variants                | their names, then their members, lexicographically             |
+//! | Lazy values             | the values they evaluate to                                    |
+//! | String builders         | the strings they build                                         |
+//! | Regexes                 | their patterns                                                 |
+//!
+//! Using the total order for floats makes equality an equivalence, so floats can be found in
+//! lists and sorted deterministically. Variants carry no declaration order at runtime, so
+//! different variants are ordered by name; a type that needs another order implements `Ord`.
+//!
+//! Values of different types, and records with different fields, fail with
+//! [`RuntimeError::TypeMismatch`]. Functions, IO actions, tasks, channels and cells fail with
+//! [`RuntimeError::NotComparable`]: comparing them by identity would make the result depend on
+//! how the program is evaluated, like whether a closure was copied or shared.
+
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+
+use crate::error::RuntimeError;
+use crate::value::Value;
+
+/// Compare two values of the same type, structurally
+pub fn compare(lhs: &Value, rhs: &Value) -> Result<Ordering, RuntimeError> {
+    let (lhs, rhs) = (lhs.force()?, rhs.force()?);
+    match (&lhs, &rhs) {
+        (Value::Unit, Value::Unit) => Ok(Ordering::Equal),
+        (Value::Bool(a), Value::Bool(b)) => Ok(a.cmp(b)),
+        (Value::Integer(a), Value::Integer(b)) => Ok(a.cmp(b)),
+        (Value::Integer(a), Value::BigInt(b)) => Ok(BigInt::from(*a).cmp(&***b)),
+        (Value::BigInt(a), Value::Integer(b)) => Ok((***a).cmp(&BigInt::from(*b))),
+        (Value::BigInt(a), Value::BigInt(b)) => Ok((***a).cmp(&***b)),
+        (Value::Float(a), Value::Float(b)) => Ok(a.total_cmp(b)),
+        (Value::Str(a), Value::Str(b)) => Ok(a.as_str().cmp(b.as_str())),
+        (Value::Bytes(a), Value::Bytes(b)) => Ok(a.as_slice().cmp(b.as_slice())),
+        (Value::Builder(a), Value::Builder(b)) => Ok(a.build().cmp(&b.build())),
+        (Value::Regex(a), Value::Regex(b)) => Ok(a.as_str().cmp(b.as_str())),
+        (Value::List(a), Value::List(b)) => lexicographic(a.iter(), b.iter()),
+        (Value::Tuple(a), Value::Tuple(b)) => lexicographic(a.0.iter(), b.0.iter()),
+        (Value::Set(a), Value::Set(b)) => Ok(a.0.iter().cmp(b.0.iter())),
+        (Value::Map(a), Value::Map(b)) => {
+            let mut a = a.0.iter();
+            let mut b = b.0.iter();
+            loop {
+                let ordering = match (a.next(), b.next()) {
+                    (Some((ka, va)), Some((kb, vb))) => match ka.cmp(kb) {
+                        Ordering::Equal => compare(va, vb)?,
+                        ordering => ordering,
+                    },
+                    (left, right) => return Ok(left.is_some().cmp(&right.is_some())),
+                };
+                if ordering != Ordering::Equal {
+                    return Ok(ordering);
+                }
+            }
+        }
+        (Value::Record(a), Value::Record(b))
+            if a.type_name == b.type_name && a.fields.keys().eq(b.fields.keys()) =>
+        {
+            lexicographic(a.fields.values(), b.fields.values())
+        }
+        (Value::Variant(a), Value::Variant(b)) if a.type_name == b.type_name => {
+            match a.name.cmp(&b.name) {
+                Ordering::Equal => lexicographic(a.members.iter(), b.members.iter()),
+                ordering => Ok(ordering),
+            }
+        }
+        (
+            value @ (Value::Function(_)
+            | Value::Io(_)
+            | Value::Task(_)
+            | Value::Channel(_)
+            | Value::Cell(_)),
+            _,
+        ) => Err(RuntimeError::NotComparable(value.type_name().to_string())),
+        (lhs, rhs) => Err(RuntimeError::TypeMismatch {
+            op: "compare",
+            lhs: lhs.type_name().to_string(),
+            rhs: rhs.type_name().to_string(),
+        }),
+    }
+}
+
+/// Whether two values of the same type are equal, which they are if [`compare`] finds them equal
+pub fn equal(lhs: &Value, rhs: &Value) -> Result<bool, RuntimeError> {
+    compare(lhs, rhs).map(|ordering| ordering == Ordering::Equal)
+}
+
+fn lexicographic<'a>(
+    mut lhs: impl Iterator<Item = &'a Value>,
+    mut rhs: impl Iterator<Item = &'a Value>,
+) -> Result<Ordering, RuntimeError> {
+    loop {
+        match (lhs.next(), rhs.next()) {
+            (Some(a), Some(b)) => match compare(a, b)? {
+                Ordering::Equal => {}
+                ordering => return Ok(ordering),
+            },
+            (a, b) => return Ok(a.is_some().cmp(&b.is_some())),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Persistent maps and sets
+//!
+//! Both are balanced trees with structural sharing, so "modifying" a map or set is cheap even if
+//! the old version is still in use.
+//!
+//! Keys have to be orderable: `()`, `Bool`, `Int`, `BigInt`, `String` and tuples of those. Like
+//! [`compare`](crate::cmp::compare), keys order `Int`s and `BigInt`s by their numeric value, and a
+//! `BigInt` that fits into an `Int` is stored as one, so `5` and `big 5` are the same key.
+
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+
+use crate::error::RuntimeError;
+use crate::heap::HeapObject;
+use crate::heap::ObjectKind;
+use crate::value::Value;
+
+#[derive(Clone, Debug)]
+pub enum Key {
+    Unit,
+    Bool(bool),
+    Integer(i64),
+    BigInt(BigInt),
+    Str(String),
+    Tuple(Vec<Key>),
+}
+
+impl Key {
+    pub fn from_value(value: &Value) -> Result<Key, RuntimeError> {
+        match value.force()? {
+            Value::Unit => Ok(Key::Unit),
+            Value::Bool(b) => Ok(Key::Bool(b)),
+            Value::Integer(i) => Ok(Key::Integer(i)),
+            Value::BigInt(i) => Ok(Key::big_int((**i).clone())),
+            Value::Str(s) => Ok(Key::Str((**s).clone())),
+            Value::Tuple(t) => {
+                t.0.iter()
+                    .map(Key::from_value)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Key::Tuple)
+            }
+            other => Err(RuntimeError::InvalidKey(other.type_name().to_string())),
+        }
+    }
+
+    /// The key of an integer, which is a `Key::Integer` if it fits into one
+    pub fn big_int(i: BigInt) -> Key {
+        match i64::try_from(&i) {
+            Ok(i) => Key::Integer(i),
+            Err(_) => Key::BigInt(i),
+        }
+    }
+
+    // The position of the kind of key in the order of keys of different kinds, where integers are
+    // one kind
+    fn rank(&self) -> u8 {
+        match self {
+            Key::Unit => 0,
+            Key::Bool(_) => 1,
+            Key::Integer(_) | Key::BigInt(_) => 2,
+            Key::Str(_) => 3,
+            Key::Tuple(_) => 4,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            Key::Unit => Value::Unit,
+            Key::Bool(b) => Value::Bool(*b),
+            Key::Integer(i) => Value::Integer(*i),
+            Key::BigInt(i) => Value::big_int(i.clone()),
+            Key::Str(s) => Value::string(s.clone()),
+            Key::Tuple(keys) => Value::tuple(keys.iter().map(Key::to_value).collect()),
+        }
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Key::Unit, Key::Unit) => Ordering::Equal,
+            (Key::Bool(a), Key::Bool(b)) => a.cmp(b),
+            (Key::Integer(a), Key::Integer(b)) => a.cmp(b),
+            (Key::Integer(a), Key::BigInt(b)) => BigInt::from(*a).cmp(b),
+            (Key::BigInt(a), Key::Integer(b)) => a.cmp(&BigInt::from(*b)),
+            (Key::BigInt(a), Key::BigInt(b)) => a.cmp(b),
+            (Key::Str(a), Key::Str(b)) => a.cmp(b),
+            (Key::Tuple(a), Key::Tuple(b)) => a.cmp(b),
+            (a, b) => a.rank().cmp(&b.rank()),
+        }
+    }
+}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Key {}
+
+#[derive(Clone, Debug, Default)]
+pub struct Map(pub im::OrdMap<Key, Value>);
+
+#[derive(Clone, Debug, Default)]
+pub struct Set(pub im::OrdSet<Key>);
+
+impl HeapObject for Map {
+    const KIND: ObjectKind = ObjectKind::Map;
+}
+
+impl HeapObject for Set {
+    const KIND: ObjectKind = ObjectKind::Set;
+}
@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Algebraic effects: operations that code performs, and handlers that give them their meaning
+//!
+//! Performing an operation, like `Std.Effect.perform "Console" "println" ["Hello"]`, is an IO
+//! action. When it is run, the handler of the innermost `Std.Effect.handle` of the effect around
+//! it is called with the arguments, and the action it returns is run, outside of that handler.
+//! Its result is the result of the operation, so code continues where it performed the operation.
+//!
+//! ```text
+//! quiet = Std.Effect.handle "Console" { println = (line) -> Std.IO.pure () } greet
+//! ```
+//!
+//! Operations without a handler around them use the [`DEFAULTS`] of the interpreter, which are
+//! the IO builtins, or fail with [`RuntimeError::Unhandled`]. Tasks spawned by an action do not
+//! inherit the handlers around it.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::builtin::io_arg;
+use crate::error::RuntimeError;
+use crate::function::Context;
+use crate::heap::Ref;
+use crate::sandbox::Sandbox;
+use crate::task::Scheduling;
+use crate::value::Record;
+use crate::value::Value;
+
+/// The default handlers of the interpreter: the operations of effects, with the builtins that
+/// handle them
+pub const DEFAULTS: &[(&str, &str, &str)] = &[
+    ("Console", "print", "Std.IO.print"),
+    ("Console", "println", "Std.IO.println"),
+    ("Console", "readLine", "Std.IO.readLine"),
+    ("Files", "readFile", "Std.IO.readFile"),
+    ("Files", "writeFile", "Std.IO.writeFile"),
+    ("Files", "appendFile", "Std.IO.appendFile"),
+];
+
+/// The context an action is run in by `Std.Effect.handle`, which handles the operations of one
+/// effect with the functions of a record, and passes everything else on to the context around it
+pub struct Handled<'a> {
+    pub outer: &'a mut dyn Context,
+    pub effect: String,
+    pub handlers: Ref<Record>,
+}
+
+impl Context for Handled<'_> {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        self.outer.call(function, args)
+    }
+
+    fn sandbox(&self) -> &Sandbox {
+        self.outer.sandbox()
+    }
+
+    fn program_args(&self) -> &[String] {
+        self.outer.program_args()
+    }
+
+    fn call_site(&self) -> Option<String> {
+        self.outer.call_site()
+    }
+
+    fn scheduling(&self) -> Scheduling {
+        self.outer.scheduling()
+    }
+
+    fn fork(&self, cancelled: Arc<AtomicBool>) -> Box<dyn Context + Send> {
+        self.outer.fork(cancelled)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.outer.is_cancelled()
+    }
+
+    fn handle(
+        &mut self,
+        effect: &str,
+        operation: &str,
+        args: &[Value],
+    ) -> Option<Result<Value, RuntimeError>> {
+        let handler = match self.handlers.fields.get(operation) {
+            Some(handler) if effect == self.effect => handler.clone(),
+            _ => return self.outer.handle(effect, operation, args),
+        };
+
+        // The handler runs outside of itself, so it can perform the effect it handles
+        let result = self
+            .outer
+            .call(&handler, args.to_vec())
+            .and_then(|action| io_arg("Std.Effect.handle", &action))
+            .and_then(|action| action.run(self.outer));
+        Some(result)
+    }
+}
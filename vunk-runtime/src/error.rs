@@ -0,0 +1,95 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::sandbox::Permission;
+use crate::value::Value;
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum RuntimeError {
+    #[error("Lazy value depends on itself")]
+    BlackHole,
+
+    #[error("Cannot apply '{op}' to {lhs} and {rhs}")]
+    TypeMismatch {
+        op: &'static str,
+        lhs: String,
+        rhs: String,
+    },
+
+    /// An operator on a named type that does not implement the trait of the operator, see
+    /// [`crate::operator`]
+    #[error(
+        "'{op}' needs an instance of {trait_name} for {type_name}, like 'impl {trait_name} on \
+             {type_name}'"
+    )]
+    NoInstance {
+        op: &'static str,
+        trait_name: &'static str,
+        type_name: String,
+    },
+
+    /// An effect operation performed outside of handlers of its effect, see [`crate::effect`]
+    #[error("'{effect}.{operation}' is performed, but there is no handler of {effect} around it")]
+    Unhandled { effect: String, operation: String },
+
+    #[error("Integer overflow in '{op}'")]
+    IntegerOverflow { op: &'static str },
+
+    #[error("Division by zero")]
+    DivisionByZero,
+
+    #[error("{builtin} expects an argument of type {expected}, found {found}")]
+    InvalidArgument {
+        builtin: String,
+        expected: &'static str,
+        found: String,
+    },
+
+    #[error("{0} is not a function")]
+    NotAFunction(String),
+
+    #[error("{0} cannot be used as a key of a map or set")]
+    InvalidKey(String),
+
+    /// Comparing values without a structure, like functions, see [`crate::cmp`]
+    #[error("Values of type {0} cannot be compared")]
+    NotComparable(String),
+
+    #[error("{builtin} needs the '{permission}' permission, which the sandbox does not grant")]
+    PermissionDenied {
+        builtin: &'static str,
+        permission: Permission,
+    },
+
+    /// An IO error, which is kept as a message because `std::io::Error` cannot be cloned
+    #[error("{builtin} failed: {message}")]
+    Io {
+        builtin: &'static str,
+        message: String,
+    },
+
+    #[error("Assertion failed: {0}")]
+    AssertionFailed(String),
+
+    /// `panic`, `todo` or `unreachable` was evaluated, or the evaluation of a lazy value panicked
+    #[error("Panicked: {0}")]
+    Panic(String),
+
+    /// A value raised with `raise` and not rescued, see [`crate::stdlib::exception`]
+    #[error("Uncaught exception: {0}")]
+    Raised(Value),
+
+    #[error("Task was cancelled")]
+    Cancelled,
+
+    #[error("Deadlock: no task can make progress")]
+    Deadlock,
+
+    /// Not an error, but `Std.Env.exit` was run, which stops the program
+    #[error("Program exited with code {0}")]
+    Exit(i32),
+
+    #[error("main has to be of type IO (), but is {0}")]
+    InvalidMain(String),
+}
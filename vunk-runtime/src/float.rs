@@ -0,0 +1,77 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Showing and parsing `Float`s, so that every float survives being shown and parsed again
+//!
+//! [`show`] writes the shortest digits that parse back to the same float, like `0.1` rather than
+//! `0.1000000000000000055511151231257827`, always with a `.` or an exponent, so the result is a
+//! float literal: `1.0`, `-0.0`, `1e20`, `1.5e-7`. Floats that are not finite are shown as `NaN`,
+//! `Infinity` and `-Infinity`.
+//!
+//! [`parse`] accepts exactly what [`show`] writes, and also digits without a fraction, like `3`,
+//! and exponents with a sign or an upper case `E`. It rounds to the nearest float. Everything
+//! else, like `.5`, `5.`, `+5`, `inf` or `0x10`, is not a float.
+
+/// The shortest representation of a float that parses back to it
+pub fn show(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_string()
+    } else if f == f64::INFINITY {
+        "Infinity".to_string()
+    } else if f == f64::NEG_INFINITY {
+        "-Infinity".to_string()
+    } else {
+        // The `Debug` format of the standard library is the shortest representation that round
+        // trips, with a `.0` for integral values, and an exponent for very large or small ones
+        format!("{:?}", f)
+    }
+}
+
+/// Parse a float, strictly
+pub fn parse(s: &str) -> Option<f64> {
+    match s {
+        "NaN" => return Some(f64::NAN),
+        "Infinity" => return Some(f64::INFINITY),
+        "-Infinity" => return Some(f64::NEG_INFINITY),
+        _ => {}
+    }
+
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while matches!(bytes.get(*i), Some(b) if b.is_ascii_digit()) {
+            *i += 1;
+        }
+        *i > start
+    };
+
+    if bytes.first() == Some(&b'-') {
+        i += 1;
+    }
+    if !digits(&mut i) {
+        return None;
+    }
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        if !digits(&mut i) {
+            return None;
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        if !digits(&mut i) {
+            return None;
+        }
+    }
+    if i != bytes.len() {
+        return None;
+    }
+
+    // What is left is a subset of what the standard library parses, with correct rounding
+    s.parse().ok()
+}
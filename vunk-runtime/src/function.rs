@@ -0,0 +1,142 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::builtin::Builtin;
+use crate::error::RuntimeError;
+use crate::heap::HeapObject;
+use crate::heap::ObjectKind;
+use crate::sandbox::Sandbox;
+use crate::task::Scheduling;
+use crate::task::TaskContext;
+use crate::value::Value;
+
+#[derive(Clone, Debug)]
+pub enum Function {
+    /// A builtin, possibly partially applied to some arguments already
+    Builtin { builtin: Builtin, args: Vec<Value> },
+}
+
+impl Function {
+    /// The name of the function, for profiles and traces
+    pub fn name(&self) -> &str {
+        match self {
+            Function::Builtin { builtin, .. } => &builtin.name,
+        }
+    }
+}
+
+impl HeapObject for Function {
+    const KIND: ObjectKind = ObjectKind::Function;
+}
+
+/// The environment builtins are called in
+///
+/// Builtins that take functions as arguments (like `Std.List.map`) call them through the context,
+/// so that the interpreter can evaluate functions that are not builtins.
+pub trait Context {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError>;
+
+    /// The permissions of IO actions run in this context
+    fn sandbox(&self) -> &Sandbox {
+        &Sandbox::DENY_ALL
+    }
+
+    /// The command line arguments passed to the program
+    fn program_args(&self) -> &[String] {
+        &[]
+    }
+
+    /// Where the builtin currently being called is called from, as `file:line:column`
+    fn call_site(&self) -> Option<String> {
+        None
+    }
+
+    fn scheduling(&self) -> Scheduling {
+        Scheduling::Threads
+    }
+
+    /// A context for a task spawned from this context, which is cancelled via `cancelled`
+    ///
+    /// By default, the task can only call builtins. It inherits sandbox, arguments and scheduling.
+    fn fork(&self, cancelled: Arc<AtomicBool>) -> Box<dyn Context + Send> {
+        Box::new(TaskContext {
+            sandbox: *self.sandbox(),
+            args: self.program_args().to_vec(),
+            scheduling: self.scheduling(),
+            cancelled,
+        })
+    }
+
+    /// Whether the task this context belongs to was cancelled
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+
+    /// Run the handler of an effect operation around the action being run, see [`crate::effect`]
+    ///
+    /// `None` if there is no handler of the effect, so the default handler is used.
+    fn handle(
+        &mut self,
+        _effect: &str,
+        _operation: &str,
+        _args: &[Value],
+    ) -> Option<Result<Value, RuntimeError>> {
+        None
+    }
+}
+
+/// A context that can only call builtins, and which does not allow any IO
+#[derive(Debug, Default)]
+pub struct BuiltinContext;
+
+impl Context for BuiltinContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        apply(self, function, args)
+    }
+}
+
+/// Apply a builtin function value to arguments
+///
+/// Functions are curried: Applying a function to fewer arguments than it takes results in a
+/// partially applied function, applying it to more arguments applies the result of the call to
+/// the remaining arguments.
+pub fn apply(
+    ctx: &mut dyn Context,
+    function: &Value,
+    args: Vec<Value>,
+) -> Result<Value, RuntimeError> {
+    let function = function.force()?;
+    let func = match &function {
+        Value::Function(func) => func,
+        other => return Err(RuntimeError::NotAFunction(other.type_name().to_string())),
+    };
+
+    match &***func {
+        Function::Builtin {
+            builtin,
+            args: bound,
+        } => {
+            let mut all = bound.clone();
+            all.extend(args);
+
+            if all.len() < builtin.arity {
+                return Ok(Value::function(Function::Builtin {
+                    builtin: builtin.clone(),
+                    args: all,
+                }));
+            }
+
+            let rest = all.split_off(builtin.arity);
+            let result = (builtin.func)(ctx, all)?;
+            if rest.is_empty() {
+                Ok(result)
+            } else {
+                ctx.call(&result, rest)
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Memory model of runtime values
+//!
+//! Every value that does not fit into a machine word lives on the heap behind a [`Ref`], an
+//! atomically reference counted pointer. Values are immutable, so sharing a `Ref` is always
+//! fine. Functions that want to "modify" a value go through [`std::sync::Arc::make_mut`], which
+//! only copies the object if it is actually shared (copy-on-write).
+//!
+//! Immutable values form an acyclic graph: a value can only ever refer to values that existed
+//! before it was constructed, and recursive functions refer to themselves by name through their
+//! environment rather than by holding a reference to their own closure. Thus, reference counting
+//! alone frees them and there is no cycle collector.
+//!
+//! Cells and channels are the exception, as they can be given a value after they were
+//! constructed. A cell written a value that refers to the cell, like `set r (Some r)`, directly or
+//! through other cells, and a channel holding a message that refers to the channel form a cycle.
+//! Such a cycle is leaked: it is never freed, even after nothing else refers to it. Programs can
+//! break it by writing another value to a cell of the cycle, or receiving the message, before the
+//! last reference outside of it goes away.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+pub type Ref<T> = Arc<Obj<T>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    Str,
+    List,
+    Record,
+    Variant,
+    Thunk,
+    BigInt,
+    Function,
+    Tuple,
+    Map,
+    Set,
+    Io,
+    Task,
+    Channel,
+    Cell,
+    Builder,
+    Bytes,
+    Regex,
+}
+
+impl ObjectKind {
+    pub const ALL: [ObjectKind; 17] = [
+        ObjectKind::Str,
+        ObjectKind::List,
+        ObjectKind::Record,
+        ObjectKind::Variant,
+        ObjectKind::Thunk,
+        ObjectKind::BigInt,
+        ObjectKind::Function,
+        ObjectKind::Tuple,
+        ObjectKind::Map,
+        ObjectKind::Set,
+        ObjectKind::Io,
+        ObjectKind::Task,
+        ObjectKind::Channel,
+        ObjectKind::Cell,
+        ObjectKind::Builder,
+        ObjectKind::Bytes,
+        ObjectKind::Regex,
+    ];
+
+    fn counters(self) -> &'static Counters {
+        &COUNTERS[self as usize]
+    }
+}
+
+impl std::fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ObjectKind::Str => write!(f, "str"),
+            ObjectKind::List => write!(f, "list"),
+            ObjectKind::Record => write!(f, "record"),
+            ObjectKind::Variant => write!(f, "variant"),
+            ObjectKind::Thunk => write!(f, "thunk"),
+            ObjectKind::BigInt => write!(f, "bigint"),
+            ObjectKind::Function => write!(f, "function"),
+            ObjectKind::Tuple => write!(f, "tuple"),
+            ObjectKind::Map => write!(f, "map"),
+            ObjectKind::Set => write!(f, "set"),
+            ObjectKind::Io => write!(f, "io"),
+            ObjectKind::Task => write!(f, "task"),
+            ObjectKind::Channel => write!(f, "channel"),
+            ObjectKind::Cell => write!(f, "cell"),
+            ObjectKind::Builder => write!(f, "builder"),
+            ObjectKind::Bytes => write!(f, "bytes"),
+            ObjectKind::Regex => write!(f, "regex"),
+        }
+    }
+}
+
+/// Types that can be stored on the heap
+pub trait HeapObject {
+    const KIND: ObjectKind;
+}
+
+/// A heap object, which is tracked in the heap statistics
+#[derive(Debug)]
+pub struct Obj<T: HeapObject>(T);
+
+impl<T: HeapObject> Obj<T> {
+    pub fn alloc(inner: T) -> Ref<T> {
+        T::KIND.counters().allocated.fetch_add(1, Ordering::Relaxed);
+        Arc::new(Obj(inner))
+    }
+}
+
+impl<T: HeapObject + Clone> Clone for Obj<T> {
+    // Only ever called by `Arc::make_mut()` if the object is shared
+    fn clone(&self) -> Self {
+        let counters = T::KIND.counters();
+        counters.allocated.fetch_add(1, Ordering::Relaxed);
+        counters.copied.fetch_add(1, Ordering::Relaxed);
+        Obj(self.0.clone())
+    }
+}
+
+impl<T: HeapObject> Drop for Obj<T> {
+    fn drop(&mut self) {
+        T::KIND.counters().freed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<T: HeapObject> std::ops::Deref for Obj<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: HeapObject> std::ops::DerefMut for Obj<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+struct Counters {
+    allocated: AtomicUsize,
+    freed: AtomicUsize,
+    copied: AtomicUsize,
+}
+
+impl Counters {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW: Counters = Counters {
+        allocated: AtomicUsize::new(0),
+        freed: AtomicUsize::new(0),
+        copied: AtomicUsize::new(0),
+    };
+
+    fn snapshot(&self) -> KindStats {
+        KindStats {
+            allocated: self.allocated.load(Ordering::Relaxed),
+            freed: self.freed.load(Ordering::Relaxed),
+            copied: self.copied.load(Ordering::Relaxed),
+        }
+    }
+}
+
+static COUNTERS: [Counters; 17] = [Counters::NEW; 17];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KindStats {
+    /// Number of objects allocated so far, including copies
+    pub allocated: usize,
+
+    /// Number of objects freed so far
+    pub freed: usize,
+
+    /// Number of objects that were copied because they were shared when being modified
+    pub copied: usize,
+}
+
+impl KindStats {
+    pub fn live(&self) -> usize {
+        self.allocated.saturating_sub(self.freed)
+    }
+}
+
+impl std::ops::Add for KindStats {
+    type Output = KindStats;
+
+    fn add(self, other: KindStats) -> KindStats {
+        KindStats {
+            allocated: self.allocated + other.allocated,
+            freed: self.freed + other.freed,
+            copied: self.copied + other.copied,
+        }
+    }
+}
+
+/// Snapshot of the heap statistics of the process
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeapStats {
+    kinds: Vec<(ObjectKind, KindStats)>,
+}
+
+impl HeapStats {
+    pub fn get(&self, kind: ObjectKind) -> KindStats {
+        self.kinds
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, stats)| *stats)
+            .unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(ObjectKind, KindStats)> {
+        self.kinds.iter()
+    }
+
+    pub fn total(&self) -> KindStats {
+        self.kinds
+            .iter()
+            .map(|(_, stats)| *stats)
+            .fold(KindStats::default(), std::ops::Add::add)
+    }
+}
+
+impl std::fmt::Display for HeapStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<10} {:>12} {:>12} {:>12} {:>12}",
+            "kind", "allocated", "freed", "copied", "live"
+        )?;
+
+        for (kind, stats) in self.kinds.iter() {
+            writeln!(
+                f,
+                "{:<10} {:>12} {:>12} {:>12} {:>12}",
+                kind.to_string(),
+                stats.allocated,
+                stats.freed,
+                stats.copied,
+                stats.live()
+            )?;
+        }
+
+        let total = self.total();
+        write!(
+            f,
+            "{:<10} {:>12} {:>12} {:>12} {:>12}",
+            "total",
+            total.allocated,
+            total.freed,
+            total.copied,
+            total.live()
+        )
+    }
+}
+
+pub fn heap_stats() -> HeapStats {
+    HeapStats {
+        kinds: ObjectKind::ALL
+            .iter()
+            .map(|kind| (*kind, kind.counters().snapshot()))
+            .collect(),
+    }
+}
@@ -0,0 +1,367 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Inspecting values as trees, for values too large to read on one line
+//!
+//! Every node of an inspection is a value, with the elements, fields or members it consists of as
+//! children. Heap objects that occur more than once in the value are numbered, like `#1`, and
+//! their children are only shown where they occur first, so sharing is visible and shared values
+//! are not repeated. Values cannot refer to themselves, see [`crate::heap`], so there are no
+//! cycles to show.
+//!
+//! Nodes deeper than [`Limits::depth`] and children beyond [`Limits::children`] are collapsed into
+//! a count, which is where a user interface would let users expand them.
+//!
+//! Sizes are estimates of the memory a value takes, including what it refers to. Heap objects that
+//! occur more than once are counted where they occur first, so the size of the root is the size
+//! of the whole value.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::mem::size_of;
+use std::sync::Arc;
+
+use crate::builder::Builder;
+use crate::cell::Cell;
+use crate::collection::Key;
+use crate::function::Function;
+use crate::thunk::Thunk;
+use crate::value::Record;
+use crate::value::Value;
+use crate::value::Variant;
+
+/// How much of a value an inspection shows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// How deep nodes are shown, the root being at depth 0
+    pub depth: usize,
+
+    /// How many children of a node are shown
+    pub children: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            depth: 4,
+            children: 10,
+        }
+    }
+}
+
+/// A value in an inspection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    /// How the value is reached from its parent, like `[0]` or the name of a field
+    pub edge: Option<String>,
+
+    /// The type of the value, and the value itself if it is small, like `Int 42`
+    pub label: String,
+
+    /// The estimated size of the value in bytes
+    pub size: usize,
+
+    /// The number of a heap object that occurs more than once
+    pub shared: Option<usize>,
+
+    /// Whether the heap object occurred before, so its children are not shown again
+    pub repeated: bool,
+
+    pub children: Vec<Node>,
+
+    /// How many children are not shown
+    pub collapsed: usize,
+}
+
+/// Inspect a value, without forcing it
+pub fn inspect(value: &Value, limits: Limits) -> Node {
+    let mut inspector = Inspector {
+        limits,
+        occurrences: HashMap::new(),
+        numbers: HashMap::new(),
+        seen: HashSet::new(),
+    };
+    inspector.count(value);
+    inspector.node(None, value, 0)
+}
+
+struct Inspector {
+    limits: Limits,
+
+    /// How often every heap object occurs, by address
+    occurrences: HashMap<usize, usize>,
+
+    /// The numbers of the shared heap objects, in the order they were shown
+    numbers: HashMap<usize, usize>,
+
+    /// The heap objects that were shown or counted already
+    seen: HashSet<usize>,
+}
+
+impl Inspector {
+    fn count(&mut self, value: &Value) {
+        if let Some(address) = address(value) {
+            let occurrences = self.occurrences.entry(address).or_insert(0);
+            *occurrences += 1;
+            if *occurrences > 1 {
+                return;
+            }
+        }
+        for (_, child) in children(value) {
+            self.count(&child);
+        }
+    }
+
+    fn node(&mut self, edge: Option<String>, value: &Value, depth: usize) -> Node {
+        let address = address(value);
+        let shared = address
+            .filter(|address| self.occurrences.get(address).copied().unwrap_or(0) > 1)
+            .map(|address| {
+                let next = self.numbers.len() + 1;
+                *self.numbers.entry(address).or_insert(next)
+            });
+        let repeated = address
+            .map(|address| !self.seen.insert(address))
+            .unwrap_or(false);
+
+        let mut node = Node {
+            edge,
+            label: label(value),
+            size: 0,
+            shared,
+            repeated,
+            children: Vec::new(),
+            collapsed: 0,
+        };
+        if repeated {
+            return node;
+        }
+
+        node.size = shallow_size(value);
+        for (i, (edge, child)) in children(value).into_iter().enumerate() {
+            if depth < self.limits.depth && i < self.limits.children {
+                let child = self.node(Some(edge), &child, depth + 1);
+                node.size += child.size;
+                node.children.push(child);
+            } else {
+                node.size += self.size(&child);
+                node.collapsed += 1;
+            }
+        }
+        node
+    }
+
+    // The size of a value that is not shown
+    fn size(&mut self, value: &Value) -> usize {
+        if let Some(address) = address(value) {
+            if !self.seen.insert(address) {
+                return 0;
+            }
+        }
+        let children = children(value);
+        shallow_size(value)
+            + children
+                .iter()
+                .map(|(_, child)| self.size(child))
+                .sum::<usize>()
+    }
+}
+
+impl std::fmt::Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.line(f)?;
+        self.tree(f, "")
+    }
+}
+
+impl Node {
+    fn line(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(edge) = &self.edge {
+            write!(f, "{}: ", edge)?;
+        }
+        write!(f, "{}", self.label)?;
+        if let Some(number) = self.shared {
+            write!(f, " #{}", number)?;
+        }
+        if self.repeated {
+            write!(f, " again")
+        } else {
+            write!(f, " ({})", format_size(self.size))
+        }
+    }
+
+    // The children below a node, with `prefix` in front of their lines
+    fn tree(&self, f: &mut std::fmt::Formatter, prefix: &str) -> std::fmt::Result {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len() && self.collapsed == 0;
+            write!(f, "\n{}{}", prefix, if last { "└── " } else { "├── " })?;
+            child.line(f)?;
+            child.tree(
+                f,
+                &format!("{}{}", prefix, if last { "    " } else { "│   " }),
+            )?;
+        }
+        if self.collapsed > 0 {
+            write!(f, "\n{}└── … {} more", prefix, self.collapsed)?;
+        }
+        Ok(())
+    }
+}
+
+// The address of the heap object of a value, which identifies it
+fn address(value: &Value) -> Option<usize> {
+    let address = match value {
+        Value::BigInt(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Str(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Bytes(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::List(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Tuple(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Map(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Set(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Record(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Variant(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Thunk(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Function(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Io(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Task(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Channel(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Cell(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Builder(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Regex(object) => Arc::as_ptr(object) as *const () as usize,
+        Value::Unit | Value::Bool(_) | Value::Integer(_) | Value::Float(_) => return None,
+    };
+    Some(address)
+}
+
+// The values a value consists of, and how they are reached from it
+fn children(value: &Value) -> Vec<(String, Value)> {
+    match value {
+        Value::List(list) => indexed(list),
+        Value::Tuple(tuple) => indexed(&tuple.0),
+        Value::Variant(variant) => indexed(&variant.members),
+        Value::Map(map) => map.0.iter().map(|(k, v)| (key(k), v.clone())).collect(),
+        Value::Record(record) => {
+            let fields = record.fields.iter();
+            fields
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect()
+        }
+        // Forcing an evaluated thunk only returns its value
+        Value::Thunk(thunk) if thunk.is_evaluated() => match thunk.force() {
+            Ok(value) => vec![("value".to_string(), value)],
+            Err(_) => Vec::new(),
+        },
+        Value::Function(function) => match &***function {
+            Function::Builtin { args, .. } => indexed(args),
+        },
+        Value::Cell(cell) => vec![("value".to_string(), cell.get())],
+        _ => Vec::new(),
+    }
+}
+
+fn indexed(values: &[Value]) -> Vec<(String, Value)> {
+    let values = values.iter().enumerate();
+    values
+        .map(|(i, value)| (format!("[{}]", i), value.clone()))
+        .collect()
+}
+
+fn label(value: &Value) -> String {
+    match value {
+        Value::Unit => "()".to_string(),
+        Value::Bool(_) | Value::Integer(_) | Value::BigInt(_) | Value::Float(_) => {
+            format!("{} {}", value.type_name(), value)
+        }
+        Value::Str(s) => {
+            let preview = s.chars().take(40).collect::<String>();
+            if preview.len() < s.len() {
+                format!("String ({} chars) {:?}…", s.chars().count(), preview)
+            } else {
+                format!("String {:?}", preview)
+            }
+        }
+        Value::Bytes(bytes) => format!("Bytes ({} bytes)", bytes.len()),
+        Value::List(list) => format!("List ({} elements)", list.len()),
+        Value::Tuple(tuple) => format!("Tuple ({} elements)", tuple.0.len()),
+        Value::Map(map) => format!("Map ({} entries)", map.0.len()),
+        Value::Set(set) => {
+            let keys = set.0.iter().take(Limits::default().children).map(key);
+            let more = if set.0.len() > Limits::default().children {
+                ", …"
+            } else {
+                ""
+            };
+            let keys = keys.collect::<Vec<_>>().join(", ");
+            format!("Set ({} elements) {{{}{}}}", set.0.len(), keys, more)
+        }
+        Value::Record(record) => format!("{} ({} fields)", value.type_name(), record.fields.len()),
+        Value::Variant(variant) => format!("{}.{}", variant.type_name, variant.name),
+        Value::Thunk(thunk) if thunk.is_evaluated() => "Lazy".to_string(),
+        Value::Thunk(_) => "Lazy (not evaluated)".to_string(),
+        Value::Function(_) | Value::Io(_) | Value::Task(_) | Value::Channel(_) => value.to_string(),
+        Value::Cell(_) => "Ref".to_string(),
+        Value::Builder(builder) => format!("StringBuilder ({} bytes)", builder.len()),
+        Value::Regex(regex) => format!("Regex {:?}", regex.as_str()),
+    }
+}
+
+// A key as it would be written in source code, without allocating it as a value
+fn key(key: &Key) -> String {
+    match key {
+        Key::Unit => "()".to_string(),
+        Key::Bool(b) => b.to_string(),
+        Key::Integer(i) => i.to_string(),
+        Key::BigInt(i) => i.to_string(),
+        Key::Str(s) => format!("{:?}", s),
+        Key::Tuple(keys) => {
+            let keys = keys.iter().map(self::key).collect::<Vec<_>>();
+            format!("({})", keys.join(", "))
+        }
+    }
+}
+
+// The size of a value without the values it consists of, which count themselves
+fn shallow_size(value: &Value) -> usize {
+    let slot = size_of::<Value>();
+    // The reference counts in front of every heap object
+    let object = 2 * size_of::<usize>();
+    let heap = match value {
+        Value::Unit | Value::Bool(_) | Value::Integer(_) | Value::Float(_) => return slot,
+        Value::BigInt(i) => size_of::<num_bigint::BigInt>() + i.iter_u64_digits().len() * 8,
+        Value::Str(s) => size_of::<String>() + s.capacity(),
+        Value::Bytes(bytes) => size_of::<Vec<u8>>() + bytes.capacity(),
+        Value::List(list) => size_of::<Vec<Value>>() + (list.capacity() - list.len()) * slot,
+        Value::Tuple(_) => size_of::<Vec<Value>>(),
+        Value::Map(map) => map.0.len() * size_of::<Key>(),
+        Value::Set(set) => set.0.len() * size_of::<Key>(),
+        Value::Record(record) => {
+            let names = record
+                .fields
+                .keys()
+                .map(|name| size_of::<String>() + name.len());
+            size_of::<Record>() + names.sum::<usize>()
+        }
+        Value::Variant(variant) => {
+            size_of::<Variant>() + variant.type_name.len() + variant.name.len()
+        }
+        Value::Thunk(_) => size_of::<Thunk>(),
+        Value::Function(_) => size_of::<Function>(),
+        Value::Cell(_) => size_of::<Cell>(),
+        Value::Builder(_) => size_of::<Builder>(),
+        // The compiled program is shared by all regexes of the same pattern, see `Std.Regex`
+        Value::Regex(regex) => size_of::<regex::Regex>() + regex.as_str().len(),
+        Value::Io(_) | Value::Task(_) | Value::Channel(_) => 0,
+    };
+    slot + object + heap
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
@@ -0,0 +1,67 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The boundary between pure code and side effects
+//!
+//! Evaluating an expression never has side effects. Builtins that interact with the outside world
+//! do not do so when they are called, but return an IO action (a value of type `IO A`) which
+//! describes the side effect. IO actions are combined with `Std.IO.andThen` and friends, but
+//! they are only ever run by [`run_main`]:
+//!
+//! The entry point of a program is a top level `main: IO ()`, which the driver evaluates and
+//! then runs.
+
+use std::sync::Arc;
+
+use crate::error::RuntimeError;
+use crate::function::Context;
+use crate::heap::HeapObject;
+use crate::heap::ObjectKind;
+use crate::value::Value;
+
+pub type IoFn = Arc<dyn Fn(&mut dyn Context) -> Result<Value, RuntimeError> + Send + Sync>;
+
+/// An IO action, which produces a value of type `A` when run
+#[derive(Clone)]
+pub struct Io {
+    run: IoFn,
+}
+
+impl HeapObject for Io {
+    const KIND: ObjectKind = ObjectKind::Io;
+}
+
+impl Io {
+    pub fn new<F>(run: F) -> Self
+    where
+        F: Fn(&mut dyn Context) -> Result<Value, RuntimeError> + Send + Sync + 'static,
+    {
+        Io { run: Arc::new(run) }
+    }
+
+    /// Run the action, unless the task it runs in was cancelled
+    pub fn run(&self, ctx: &mut dyn Context) -> Result<Value, RuntimeError> {
+        if ctx.is_cancelled() {
+            return Err(RuntimeError::Cancelled);
+        }
+
+        (self.run)(ctx)
+    }
+}
+
+impl std::fmt::Debug for Io {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "<io>")
+    }
+}
+
+/// Run the `main` IO action of a program
+///
+/// If the program calls `Std.Env.exit`, this returns [`RuntimeError::Exit`] with the exit code.
+pub fn run_main(ctx: &mut dyn Context, main: &Value) -> Result<Value, RuntimeError> {
+    match main.force()? {
+        Value::Io(io) => io.run(ctx),
+        other => Err(RuntimeError::InvalidMain(other.type_name().to_string())),
+    }
+}
@@ -0,0 +1,24 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+pub mod arith;
+pub mod builder;
+pub mod builtin;
+pub mod cell;
+pub mod cmp;
+pub mod collection;
+pub mod effect;
+pub mod error;
+pub mod float;
+pub mod function;
+pub mod heap;
+pub mod inspect;
+pub mod io;
+pub mod operator;
+pub mod profile;
+pub mod sandbox;
+pub mod stdlib;
+pub mod task;
+pub mod thunk;
+pub mod value;
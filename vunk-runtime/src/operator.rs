@@ -0,0 +1,228 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! The overloadable binary operators, and the traits user types implement them with
+//!
+//! | Operators           | Trait    | Method                        |
+//! |---------------------|----------|-------------------------------|
+//! | `+` `-` `*` `/` `%` | `Num`    | `add` `sub` `mul` `div` `rem` |
+//! | `==` `!=`           | `Eq`     | `eq`                          |
+//! | `<` `<=` `>` `>=`   | `Ord`    | `compare`                     |
+//! | `++`                | `Concat` | `concat`                      |
+//!
+//! Primitive values have built-in instances: numbers use [`crate::arith`], equality and ordering
+//! are structural (see [`crate::cmp`]), and `++` joins strings, bytes and lists. Records and variants of
+//! named types use the methods of the `impl`s of their type, like `impl Num on Vec2`, which are
+//! registered in [`Instances`], or the defaults of the trait for methods the `impl` leaves out.
+//! `@derive(Eq, Ord)` registers the structural comparison as their `impl`, with
+//! [`Instances::derive`].
+//!
+//! `Eq.eq` returns a `Bool` and `Ord.compare` an `Ordering`, so `!=`, `<`, `<=`, `>` and `>=`
+//! come with them. The instance is picked by the type of the left operand, as the operands of an
+//! operator have the same type.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::arith;
+use crate::builtin::bool_arg;
+use crate::builtin::variant_arg;
+use crate::builtin::Builtins;
+use crate::cmp::compare;
+use crate::error::RuntimeError;
+use crate::function::Context;
+use crate::value::Value;
+
+/// The operators, with the trait and the method that implement them
+pub const OPERATORS: &[(&str, &str, &str)] = &[
+    ("+", "Num", "add"),
+    ("-", "Num", "sub"),
+    ("*", "Num", "mul"),
+    ("/", "Num", "div"),
+    ("%", "Num", "rem"),
+    ("==", "Eq", "eq"),
+    ("!=", "Eq", "eq"),
+    ("<", "Ord", "compare"),
+    ("<=", "Ord", "compare"),
+    (">", "Ord", "compare"),
+    (">=", "Ord", "compare"),
+    ("++", "Concat", "concat"),
+];
+
+/// The traits that can be derived, with their method and the builtin that implements it
+pub const DERIVABLE: &[(&str, &str, &str)] = &[
+    ("Eq", "eq", "Std.Cmp.equal"),
+    ("Ord", "compare", "Std.Cmp.compare"),
+];
+
+/// The methods of the `impl`s of traits on named types
+#[derive(Clone, Debug, Default)]
+pub struct Instances {
+    /// The methods by name, by trait and type
+    impls: BTreeMap<(String, String), BTreeMap<String, Value>>,
+
+    /// The default methods by name, by trait
+    defaults: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+impl Instances {
+    /// Register the methods of `impl trait_name on type_name`
+    pub fn register(
+        &mut self,
+        trait_name: impl Into<String>,
+        type_name: impl Into<String>,
+        methods: impl IntoIterator<Item = (String, Value)>,
+    ) {
+        self.impls
+            .entry((trait_name.into(), type_name.into()))
+            .or_default()
+            .extend(methods);
+    }
+
+    /// Register the default methods of a trait, for the `impl`s that do not define them
+    pub fn register_defaults(
+        &mut self,
+        trait_name: impl Into<String>,
+        methods: impl IntoIterator<Item = (String, Value)>,
+    ) {
+        self.defaults
+            .entry(trait_name.into())
+            .or_default()
+            .extend(methods);
+    }
+
+    /// Register the `impl` of one of the [`DERIVABLE`] traits on the type, which compares its
+    /// values structurally
+    ///
+    /// Returns `false`, and registers nothing, if the trait cannot be derived.
+    pub fn derive(
+        &mut self,
+        builtins: &Builtins,
+        trait_name: &str,
+        type_name: impl Into<String>,
+    ) -> bool {
+        let derived = DERIVABLE
+            .iter()
+            .find(|(name, _, _)| *name == trait_name)
+            .and_then(|(_, method, builtin)| Some((method.to_string(), builtins.value(builtin)?)));
+        match derived {
+            Some(method) => {
+                self.register(trait_name, type_name, [method]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The method of the `impl` of the trait on the type, or the default of the trait if the
+    /// `impl` does not define it
+    pub fn method(&self, trait_name: &str, type_name: &str, method: &str) -> Option<&Value> {
+        let methods = self
+            .impls
+            .get(&(trait_name.to_string(), type_name.to_string()))?;
+        methods.get(method).or_else(|| {
+            self.defaults
+                .get(trait_name)
+                .and_then(|defaults| defaults.get(method))
+        })
+    }
+}
+
+/// Apply one of the [`OPERATORS`] to two values
+///
+/// Fails with [`RuntimeError::NoInstance`] if the operands are of a named type without an `impl`
+/// of the trait of the operator.
+pub fn binary(
+    ctx: &mut dyn Context,
+    instances: &Instances,
+    op: &'static str,
+    lhs: &Value,
+    rhs: &Value,
+) -> Result<Value, RuntimeError> {
+    let lhs = lhs.force()?;
+    let rhs = rhs.force()?;
+    let (trait_name, method) = match OPERATORS.iter().find(|(name, _, _)| *name == op) {
+        Some((_, trait_name, method)) => (*trait_name, *method),
+        None => return Err(mismatch(op, &lhs, &rhs)),
+    };
+
+    let type_name = match &lhs {
+        Value::Record(record) => match &record.type_name {
+            Some(type_name) => type_name.clone(),
+            None => return primitive(op, &lhs, &rhs),
+        },
+        Value::Variant(variant) => variant.type_name.clone(),
+        _ => return primitive(op, &lhs, &rhs),
+    };
+
+    let function = instances
+        .method(trait_name, &type_name, method)
+        .ok_or_else(|| RuntimeError::NoInstance {
+            op,
+            trait_name,
+            type_name: type_name.clone(),
+        })?;
+    let result = ctx.call(function, vec![lhs, rhs])?;
+
+    match trait_name {
+        "Eq" => {
+            let equal = bool_arg("Eq.eq", &result)?;
+            Ok(Value::Bool(if op == "!=" { !equal } else { equal }))
+        }
+        "Ord" => {
+            let ordering = variant_arg("Ord.compare", &result, "Ordering")?;
+            let ordering = match ordering.name.as_str() {
+                "Less" => Ordering::Less,
+                "Equal" => Ordering::Equal,
+                _ => Ordering::Greater,
+            };
+            Ok(Value::Bool(holds(op, ordering)))
+        }
+        _ => Ok(result),
+    }
+}
+
+// The built-in instances of the primitive types
+fn primitive(op: &'static str, lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    match op {
+        "+" => arith::add(lhs, rhs),
+        "-" => arith::sub(lhs, rhs),
+        "*" => arith::mul(lhs, rhs),
+        "/" => arith::div(lhs, rhs),
+        "%" => arith::rem(lhs, rhs),
+        "++" => match (lhs, rhs) {
+            (Value::Str(a), Value::Str(b)) => {
+                Ok(Value::string(format!("{}{}", a.as_str(), b.as_str())))
+            }
+            (Value::Bytes(a), Value::Bytes(b)) => {
+                Ok(Value::bytes([a.as_slice(), b.as_slice()].concat()))
+            }
+            (Value::List(a), Value::List(b)) => {
+                Ok(Value::list(a.iter().chain(b.iter()).cloned().collect()))
+            }
+            _ => Err(mismatch(op, lhs, rhs)),
+        },
+        _ => Ok(Value::Bool(holds(op, compare(lhs, rhs)?))),
+    }
+}
+
+// Whether a comparison holds for the ordering of its operands
+fn holds(op: &str, ordering: Ordering) -> bool {
+    match op {
+        "==" => ordering == Ordering::Equal,
+        "!=" => ordering != Ordering::Equal,
+        "<" => ordering == Ordering::Less,
+        "<=" => ordering != Ordering::Greater,
+        ">" => ordering == Ordering::Greater,
+        _ => ordering != Ordering::Less,
+    }
+}
+
+fn mismatch(op: &'static str, lhs: &Value, rhs: &Value) -> RuntimeError {
+    RuntimeError::TypeMismatch {
+        op,
+        lhs: lhs.type_name().to_string(),
+        rhs: rhs.type_name().to_string(),
+    }
+}
@@ -0,0 +1,173 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A counting profiler, which measures every call instead of sampling
+//!
+//! A context that profiles calls [`Profile::enter`] before and [`Profile::exit`] after every
+//! function it calls. For every function, the profile counts its calls, the time spent in it and
+//! the heap objects allocated in it, both in total and without the functions it calls ("self").
+//! Time a recursive function spends in itself is only counted once in its total.
+//!
+//! Allocations are read from the counters of the whole process, see [`crate::heap`], so tasks
+//! running at the same time add to the allocations of whatever function is being profiled.
+//!
+//! Besides the table of [`Profile::report`], the profile is available as collapsed stacks with
+//! [`Profile::collapsed`], which tools like `inferno-flamegraph` or speedscope draw as flame
+//! graphs.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::heap::heap_stats;
+
+/// What was measured of a function
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FunctionStats {
+    pub calls: u64,
+
+    /// Time spent in the function, including the functions it called
+    pub total: Duration,
+
+    /// Time spent in the function itself
+    pub own: Duration,
+
+    /// Heap objects allocated in the function, including the functions it called
+    pub allocated: usize,
+
+    /// Heap objects allocated in the function itself
+    pub own_allocated: usize,
+}
+
+/// The measurements of a run, which can be shared with the contexts of tasks
+#[derive(Debug, Default)]
+pub struct Profile {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    stack: Vec<Frame>,
+    functions: BTreeMap<String, FunctionStats>,
+
+    /// The time spent in every stack itself, with the names of its functions joined by `;`
+    stacks: BTreeMap<String, Duration>,
+}
+
+#[derive(Debug)]
+struct Frame {
+    name: String,
+    start: Instant,
+    allocated: usize,
+
+    /// Time and allocations of the functions called from this one
+    children: Duration,
+    children_allocated: usize,
+}
+
+impl Profile {
+    /// Start measuring a call of the function `name`
+    pub fn enter(&self, name: &str) {
+        let allocated = heap_stats().total().allocated;
+        if let Ok(mut state) = self.state.lock() {
+            state.stack.push(Frame {
+                name: name.to_string(),
+                start: Instant::now(),
+                allocated,
+                children: Duration::ZERO,
+                children_allocated: 0,
+            });
+        }
+    }
+
+    /// Stop measuring the call started last
+    pub fn exit(&self) {
+        let end = Instant::now();
+        let allocated = heap_stats().total().allocated;
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        let frame = match state.stack.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let total = end.duration_since(frame.start);
+        let total_allocated = allocated.saturating_sub(frame.allocated);
+        let own = total.saturating_sub(frame.children);
+        let own_allocated = total_allocated.saturating_sub(frame.children_allocated);
+        let recursive = state.stack.iter().any(|outer| outer.name == frame.name);
+
+        let path = state
+            .stack
+            .iter()
+            .map(|outer| outer.name.as_str())
+            .chain(std::iter::once(frame.name.as_str()))
+            .collect::<Vec<_>>()
+            .join(";");
+        *state.stacks.entry(path).or_default() += own;
+
+        if let Some(parent) = state.stack.last_mut() {
+            parent.children += total;
+            parent.children_allocated += total_allocated;
+        }
+
+        let stats = state.functions.entry(frame.name).or_default();
+        stats.calls += 1;
+        stats.own += own;
+        stats.own_allocated += own_allocated;
+        if !recursive {
+            stats.total += total;
+            stats.allocated += total_allocated;
+        }
+    }
+
+    /// The measurements of every function, by name
+    pub fn functions(&self) -> BTreeMap<String, FunctionStats> {
+        self.state
+            .lock()
+            .map(|state| state.functions.clone())
+            .unwrap_or_default()
+    }
+
+    /// A table of the functions, the ones that took the most time themselves first
+    pub fn report(&self) -> String {
+        let mut functions = self.functions().into_iter().collect::<Vec<_>>();
+        functions.sort_by(|(a_name, a), (b_name, b)| b.own.cmp(&a.own).then(a_name.cmp(b_name)));
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:>8} {:>12} {:>12} {:>10} {:>10}  Function",
+            "Calls", "Self", "Total", "Self alloc", "Alloc"
+        );
+        for (name, stats) in functions {
+            let _ = writeln!(
+                out,
+                "{:>8} {:>12.3?} {:>12.3?} {:>10} {:>10}  {}",
+                stats.calls, stats.own, stats.total, stats.own_allocated, stats.allocated, name
+            );
+        }
+        out
+    }
+
+    /// The stacks in the collapsed format of flamegraph.pl, one per line, with the time spent in
+    /// the stack itself in microseconds
+    pub fn collapsed(&self) -> String {
+        let stacks = self
+            .state
+            .lock()
+            .map(|state| state.stacks.clone())
+            .unwrap_or_default();
+
+        let mut out = String::new();
+        for (stack, time) in stacks {
+            let _ = writeln!(out, "{} {}", stack, time.as_micros());
+        }
+        out
+    }
+}
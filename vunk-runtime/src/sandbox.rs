@@ -0,0 +1,81 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// What IO actions are allowed to do when they are run
+///
+/// Permissions are checked when an action is run, not when it is constructed, so code that only
+/// builds actions works in every sandbox.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sandbox {
+    /// Reading from stdin and writing to stdout
+    pub console: bool,
+
+    pub read_files: bool,
+
+    /// Creating, overwriting and appending to files
+    pub write_files: bool,
+
+    /// Reading environment variables
+    pub environment: bool,
+
+    /// Sending requests to other hosts
+    pub network: bool,
+
+    /// Running other programs
+    pub run_processes: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    Console,
+    ReadFiles,
+    WriteFiles,
+    Environment,
+    Network,
+    RunProcesses,
+}
+
+impl Sandbox {
+    pub const DENY_ALL: Sandbox = Sandbox {
+        console: false,
+        read_files: false,
+        write_files: false,
+        environment: false,
+        network: false,
+        run_processes: false,
+    };
+
+    pub const ALLOW_ALL: Sandbox = Sandbox {
+        console: true,
+        read_files: true,
+        write_files: true,
+        environment: true,
+        network: true,
+        run_processes: true,
+    };
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::Console => self.console,
+            Permission::ReadFiles => self.read_files,
+            Permission::WriteFiles => self.write_files,
+            Permission::Environment => self.environment,
+            Permission::Network => self.network,
+            Permission::RunProcesses => self.run_processes,
+        }
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Permission::Console => write!(f, "console"),
+            Permission::ReadFiles => write!(f, "read-files"),
+            Permission::WriteFiles => write!(f, "write-files"),
+            Permission::Environment => write!(f, "environment"),
+            Permission::Network => write!(f, "network"),
+            Permission::RunProcesses => write!(f, "run-processes"),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Bytes`: Binary data
+//!
+//! `Bytes` are sequences of bytes, written as `b"text"` for the UTF-8 encoding of the text or as
+//! `x"89 50 4e 47"` in hex. Unlike strings, they can hold any data, like the contents of files
+//! or messages of network protocols. Bytes are joined with `++`.
+//!
+//! Like `Std.List.take` and `drop`, `slice start end bytes` clamps its bounds to the bytes, so
+//! it never fails. The bytes of lists and strings are converted with `fromList` and `toList`,
+//! `encodeUtf8` and `decodeUtf8`, and bytes are written as text with `toHex` and `toBase64`.
+//! Decoding returns `None` for input that is not valid.
+
+use crate::builtin::bytes_arg;
+use crate::builtin::int_arg;
+use crate::builtin::list_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::error::RuntimeError;
+use crate::value::Value;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Bytes.empty", 0, |_, _| Ok(Value::bytes(Vec::new())));
+
+    builtins.register("Std.Bytes.length", 1, |_, args| {
+        let bytes = bytes_arg("Std.Bytes.length", &args[0])?;
+        Ok(Value::Integer(bytes.len() as i64))
+    });
+
+    builtins.register("Std.Bytes.get", 2, |_, args| {
+        let index = int_arg("Std.Bytes.get", &args[0])?;
+        let bytes = bytes_arg("Std.Bytes.get", &args[1])?;
+        Ok(usize::try_from(index)
+            .ok()
+            .and_then(|index| bytes.get(index))
+            .map(|byte| Value::some(Value::Integer(i64::from(*byte))))
+            .unwrap_or_else(Value::none))
+    });
+
+    builtins.register("Std.Bytes.slice", 3, |_, args| {
+        let start = int_arg("Std.Bytes.slice", &args[0])?;
+        let end = int_arg("Std.Bytes.slice", &args[1])?;
+        let bytes = bytes_arg("Std.Bytes.slice", &args[2])?;
+        let clamp = |i: i64| usize::try_from(i).unwrap_or(0).min(bytes.len());
+        let (start, end) = (clamp(start), clamp(end));
+        Ok(Value::bytes(bytes.get(start..end).unwrap_or_default()))
+    });
+
+    builtins.register("Std.Bytes.concat", 1, |_, args| {
+        let list = list_arg("Std.Bytes.concat", &args[0])?;
+        let mut concatenated = Vec::new();
+        for part in list.iter() {
+            concatenated.extend_from_slice(&bytes_arg("Std.Bytes.concat", part)?);
+        }
+        Ok(Value::bytes(concatenated))
+    });
+
+    // Fails for integers that are not between 0 and 255
+    builtins.register("Std.Bytes.fromList", 1, |_, args| {
+        let list = list_arg("Std.Bytes.fromList", &args[0])?;
+        let bytes = list
+            .iter()
+            .map(|byte| {
+                let byte = int_arg("Std.Bytes.fromList", byte)?;
+                u8::try_from(byte).map_err(|_| RuntimeError::InvalidArgument {
+                    builtin: "Std.Bytes.fromList".to_string(),
+                    expected: "List of Ints between 0 and 255",
+                    found: byte.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Value::bytes(bytes))
+    });
+
+    builtins.register("Std.Bytes.toList", 1, |_, args| {
+        let bytes = bytes_arg("Std.Bytes.toList", &args[0])?;
+        Ok(Value::list(
+            bytes
+                .iter()
+                .map(|byte| Value::Integer(i64::from(*byte)))
+                .collect(),
+        ))
+    });
+
+    builtins.register("Std.Bytes.encodeUtf8", 1, |_, args| {
+        let s = str_arg("Std.Bytes.encodeUtf8", &args[0])?;
+        Ok(Value::bytes(s.as_bytes()))
+    });
+
+    builtins.register("Std.Bytes.decodeUtf8", 1, |_, args| {
+        let bytes = bytes_arg("Std.Bytes.decodeUtf8", &args[0])?;
+        Ok(std::str::from_utf8(&bytes)
+            .map(|s| Value::some(Value::string(s)))
+            .unwrap_or_else(|_| Value::none()))
+    });
+
+    builtins.register("Std.Bytes.toHex", 1, |_, args| {
+        let bytes = bytes_arg("Std.Bytes.toHex", &args[0])?;
+        Ok(Value::string(
+            bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>(),
+        ))
+    });
+
+    builtins.register("Std.Bytes.fromHex", 1, |_, args| {
+        let s = str_arg("Std.Bytes.fromHex", &args[0])?;
+        Ok(option(from_hex(&s)))
+    });
+
+    builtins.register("Std.Bytes.toBase64", 1, |_, args| {
+        let bytes = bytes_arg("Std.Bytes.toBase64", &args[0])?;
+        Ok(Value::string(to_base64(&bytes)))
+    });
+
+    builtins.register("Std.Bytes.fromBase64", 1, |_, args| {
+        let s = str_arg("Std.Bytes.fromBase64", &args[0])?;
+        Ok(option(from_base64(&s)))
+    });
+}
+
+fn option(bytes: Option<Vec<u8>>) -> Value {
+    bytes
+        .map(|bytes| Value::some(Value::bytes(bytes)))
+        .unwrap_or_else(Value::none)
+}
+
+// Upper and lower case digits, two per byte
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    let digits = s
+        .chars()
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<_>>>()?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect(),
+    )
+}
+
+// The standard alphabet of RFC 4648, with padding
+fn to_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.chunks(3).len() * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (u32::from(*byte) << (16 - 8 * i))
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[((group >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Only padded input is valid, as `to_base64` writes it
+fn from_base64(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+    let chunks = s.chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(s.len() / 4 * 3);
+    for (n, chunk) in chunks.enumerate() {
+        let last = n == s.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+
+        let mut group = 0u32;
+        for (i, c) in chunk[..4 - padding].iter().enumerate() {
+            let digit = BASE64.iter().position(|digit| digit == c)? as u32;
+            group |= digit << (18 - 6 * i);
+        }
+        let bytes = group.to_be_bytes();
+        decoded.extend_from_slice(&bytes[1..4 - padding]);
+    }
+    Some(decoded)
+}
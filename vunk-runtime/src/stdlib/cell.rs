@@ -0,0 +1,47 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Ref`: Mutable reference cells, see [`crate::cell`]
+//!
+//! `ref`, which creates a cell, is part of the prelude.
+
+use crate::builtin::cell_arg;
+use crate::builtin::Builtins;
+use crate::io::Io;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    // An action creating a new cell, so that every run of it creates another one
+    builtins.register("Std.Ref.new", 1, |_, args| {
+        let value = args[0].clone();
+        Ok(Value::io(Io::new(move |_| Ok(Value::cell(value.clone())))))
+    });
+    builtins.add_to_prelude("ref", "Std.Ref.new");
+
+    builtins.register("Std.Ref.get", 1, |_, args| {
+        let cell = cell_arg("Std.Ref.get", &args[0])?;
+        Ok(Value::io(Io::new(move |_| Ok(cell.get()))))
+    });
+
+    builtins.register("Std.Ref.set", 2, |_, args| {
+        let cell = cell_arg("Std.Ref.set", &args[0])?;
+        let value = args[1].clone();
+        Ok(Value::io(Io::new(move |_| {
+            cell.set(value.clone());
+            Ok(Value::Unit)
+        })))
+    });
+
+    // Replace the value of a cell with the result of a function applied to it, which is not atomic:
+    // another task can set the cell while the function runs
+    builtins.register("Std.Ref.modify", 2, |_, args| {
+        let function = args[0].clone();
+        let cell = cell_arg("Std.Ref.modify", &args[1])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            let value = ctx.call(&function, vec![cell.get()])?;
+            cell.set(value);
+            Ok(Value::Unit)
+        })))
+    });
+}
@@ -0,0 +1,33 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Cmp`: Structural equality and ordering
+//!
+//! `equal a b` and `compare a b` compare values by their structure, as `==` and `<` do for values
+//! without an `impl` of `Eq` or `Ord`, see [`crate::cmp`]. `compare` returns an `Ordering`.
+//!
+//! They are the methods of the instances that `@derive(Eq, Ord)` gives a named type, see
+//! [`crate::operator::Instances::derive`].
+
+use std::cmp::Ordering;
+
+use crate::builtin::Builtins;
+use crate::cmp::compare;
+use crate::cmp::equal;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Cmp.equal", 2, |_, args| {
+        equal(&args[0], &args[1]).map(Value::Bool)
+    });
+
+    builtins.register("Std.Cmp.compare", 2, |_, args| {
+        let name = match compare(&args[0], &args[1])? {
+            Ordering::Less => "Less",
+            Ordering::Equal => "Equal",
+            Ordering::Greater => "Greater",
+        };
+        Ok(Value::variant("Ordering", name, Vec::new()))
+    });
+}
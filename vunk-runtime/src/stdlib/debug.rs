@@ -0,0 +1,121 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Debug`: Tracing, assertions and panics
+//!
+//! These are escape hatches for debugging: `trace` and `dbg` write to stderr as soon as they are
+//! evaluated, without going through IO actions and regardless of the sandbox. Output is prefixed
+//! with the call site, if the interpreter knows it.
+//!
+//! `assertEq actual expected` compares values structurally, see [`crate::cmp`].
+//!
+//! `panic message`, `todo message`, `unimplemented` and `unreachable` fail as soon as they are
+//! evaluated. They never have a value, so their type is `Never`, which fits wherever any other
+//! type is expected, like in a branch that cannot be taken or in a function that is not written
+//! yet. The `todo` lint lists the placeholders left in a package.
+//!
+//! `trace`, `assert`, `assertEq`, `dbg`, `panic`, `todo`, `unimplemented` and `unreachable` are
+//! part of the prelude.
+
+use std::cmp::Ordering;
+
+use crate::builtin::bool_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::cmp::compare;
+use crate::error::RuntimeError;
+use crate::function::Context;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    // Print a message, then return the second argument
+    builtins.register("Std.Debug.trace", 2, |ctx, args| {
+        let message = str_arg("Std.Debug.trace", &args[0])?;
+        eprintln!("{}{}", prefix(ctx), message.as_str());
+        Ok(args[1].clone())
+    });
+
+    builtins.register("Std.Debug.assert", 2, |ctx, args| {
+        if bool_arg("Std.Debug.assert", &args[0])? {
+            return Ok(Value::Unit);
+        }
+
+        let message = str_arg("Std.Debug.assert", &args[1])?;
+        Err(RuntimeError::AssertionFailed(format!(
+            "{}{}",
+            prefix(ctx),
+            message.as_str()
+        )))
+    });
+
+    builtins.register("Std.Debug.assertEq", 2, |ctx, args| {
+        let actual = args[0].force()?;
+        let expected = args[1].force()?;
+        if compare(&actual, &expected)? == Ordering::Equal {
+            return Ok(Value::Unit);
+        }
+
+        Err(RuntimeError::AssertionFailed(format!(
+            "{}expected {}, found {}",
+            prefix(ctx),
+            expected,
+            actual
+        )))
+    });
+
+    // Print a value, then return it
+    builtins.register("Std.Debug.dbg", 1, |ctx, args| {
+        let value = args[0].force()?;
+        eprintln!("{}{}", prefix(ctx), value);
+        Ok(value)
+    });
+
+    builtins.register("Std.Debug.panic", 1, |ctx, args| {
+        let message = str_arg("Std.Debug.panic", &args[0])?;
+        Err(RuntimeError::Panic(format!(
+            "{}{}",
+            prefix(ctx),
+            message.as_str()
+        )))
+    });
+
+    builtins.register("Std.Debug.todo", 1, |ctx, args| {
+        let message = str_arg("Std.Debug.todo", &args[0])?;
+        Err(RuntimeError::Panic(format!(
+            "{}not implemented yet: {}",
+            prefix(ctx),
+            message.as_str()
+        )))
+    });
+
+    // Constants, which fail when they are referenced
+    builtins.register("Std.Debug.unimplemented", 0, |ctx, _| {
+        Err(RuntimeError::Panic(format!(
+            "{}not implemented",
+            prefix(ctx)
+        )))
+    });
+
+    builtins.register("Std.Debug.unreachable", 0, |ctx, _| {
+        Err(RuntimeError::Panic(format!(
+            "{}entered unreachable code",
+            prefix(ctx)
+        )))
+    });
+
+    builtins.add_to_prelude("trace", "Std.Debug.trace");
+    builtins.add_to_prelude("assert", "Std.Debug.assert");
+    builtins.add_to_prelude("assertEq", "Std.Debug.assertEq");
+    builtins.add_to_prelude("dbg", "Std.Debug.dbg");
+    builtins.add_to_prelude("panic", "Std.Debug.panic");
+    builtins.add_to_prelude("todo", "Std.Debug.todo");
+    builtins.add_to_prelude("unimplemented", "Std.Debug.unimplemented");
+    builtins.add_to_prelude("unreachable", "Std.Debug.unreachable");
+}
+
+fn prefix(ctx: &dyn Context) -> String {
+    ctx.call_site()
+        .map(|site| format!("[{}] ", site))
+        .unwrap_or_default()
+}
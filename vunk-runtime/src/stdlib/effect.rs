@@ -0,0 +1,69 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Effect`: Performing the operations of effects, and handling them, see [`crate::effect`]
+
+use crate::builtin::io_arg;
+use crate::builtin::list_arg;
+use crate::builtin::record_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::effect::Handled;
+use crate::effect::DEFAULTS;
+use crate::error::RuntimeError;
+use crate::function::apply;
+use crate::io::Io;
+use crate::value::Value;
+
+/// Registers after [`crate::stdlib::io`], whose builtins are the default handlers
+pub fn register(builtins: &mut Builtins) {
+    let defaults = DEFAULTS
+        .iter()
+        .filter_map(|(effect, operation, builtin)| {
+            Some((*effect, *operation, builtins.value(builtin)?))
+        })
+        .collect::<Vec<_>>();
+
+    // An action that performs an operation of an effect with a list of arguments
+    builtins.register("Std.Effect.perform", 3, move |_, args| {
+        let effect = str_arg("Std.Effect.perform", &args[0])?;
+        let operation = str_arg("Std.Effect.perform", &args[1])?;
+        let arguments = list_arg("Std.Effect.perform", &args[2])?;
+        let defaults = defaults.clone();
+        Ok(Value::io(Io::new(move |ctx| {
+            if let Some(result) = ctx.handle(&effect, &operation, &arguments) {
+                return result;
+            }
+
+            let default = defaults
+                .iter()
+                .find(|(name, op, _)| *name == effect.as_str() && *op == operation.as_str());
+            match default {
+                Some((_, _, builtin)) => {
+                    let action = apply(ctx, builtin, arguments.to_vec())?;
+                    io_arg("Std.Effect.perform", &action)?.run(ctx)
+                }
+                None => Err(RuntimeError::Unhandled {
+                    effect: effect.as_str().to_string(),
+                    operation: operation.as_str().to_string(),
+                }),
+            }
+        })))
+    });
+
+    // Run an action, handling the operations of an effect with the functions of a record
+    builtins.register("Std.Effect.handle", 3, |_, args| {
+        let effect = str_arg("Std.Effect.handle", &args[0])?;
+        let handlers = record_arg("Std.Effect.handle", &args[1])?;
+        let action = io_arg("Std.Effect.handle", &args[2])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            let mut handled = Handled {
+                outer: ctx,
+                effect: effect.as_str().to_string(),
+                handlers: handlers.clone(),
+            };
+            action.run(&mut handled)
+        })))
+    });
+}
@@ -0,0 +1,52 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Env`: Command line arguments, environment variables and exit codes
+
+use crate::builtin::int_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::error::RuntimeError;
+use crate::io::Io;
+use crate::sandbox::Permission;
+use crate::stdlib::io::permit;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    // The arguments passed to the program, without the program itself
+    builtins.register("Std.Env.args", 0, |_, _| {
+        Ok(Value::io(Io::new(|ctx| {
+            Ok(Value::list(
+                ctx.program_args()
+                    .iter()
+                    .map(|arg| Value::string(arg.as_str()))
+                    .collect(),
+            ))
+        })))
+    });
+
+    builtins.register("Std.Env.var", 1, |_, args| {
+        let name = str_arg("Std.Env.var", &args[0])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.Env.var", Permission::Environment)?;
+            Ok(std::env::var(name.as_str())
+                .map(Value::string)
+                .map(Value::some)
+                .unwrap_or_else(|_| Value::none()))
+        })))
+    });
+
+    // Stop the program with an exit code between 0 and 255
+    builtins.register("Std.Env.exit", 1, |_, args| {
+        let code = int_arg("Std.Env.exit", &args[0])?;
+        let code = u8::try_from(code).map_err(|_| RuntimeError::InvalidArgument {
+            builtin: "Std.Env.exit".to_string(),
+            expected: "exit code between 0 and 255",
+            found: code.to_string(),
+        })?;
+        Ok(Value::io(Io::new(move |_| {
+            Err(RuntimeError::Exit(i32::from(code)))
+        })))
+    });
+}
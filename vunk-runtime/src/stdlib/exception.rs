@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Exception`: Raising values out of a computation, and rescuing them
+//!
+//! `raise` stops the evaluation of the code around it, up to the innermost `rescue` whose action
+//! it was raised in, which passes the raised value to its handler:
+//!
+//! ```text
+//! rescue ((error) -> Std.IO.println error) (Std.IO.println (lookup "key"))
+//! ```
+//!
+//! Values can be raised anywhere, but only rescued by running an action, so pure code does not
+//! depend on where exceptions are rescued. Unlike `Result`, exceptions do not show up in the
+//! types of the functions they pass. Panics and the errors of the runtime are not exceptions
+//! and cannot be rescued. `raise` and `rescue` are part of the prelude.
+
+use crate::builtin::io_arg;
+use crate::builtin::Builtins;
+use crate::error::RuntimeError;
+use crate::io::Io;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Exception.raise", 1, |_, args| {
+        Err(RuntimeError::Raised(args[0].clone()))
+    });
+    builtins.add_to_prelude("raise", "Std.Exception.raise");
+
+    // The action is evaluated when the result is run, so values raised by evaluating it are
+    // rescued as well
+    builtins.register("Std.Exception.rescue", 2, |_, args| {
+        let handler = args[0].clone();
+        let action = args[1].clone();
+        Ok(Value::io(Io::new(move |ctx| {
+            let result = io_arg("Std.Exception.rescue", &action).and_then(|io| io.run(ctx));
+            match result {
+                Err(RuntimeError::Raised(value)) => {
+                    let rescue = ctx.call(&handler, vec![value])?;
+                    io_arg("Std.Exception.rescue", &rescue)?.run(ctx)
+                }
+                other => other,
+            }
+        })))
+    });
+    builtins.add_to_prelude("rescue", "Std.Exception.rescue");
+
+    // Run an action, with `Ok` of its result or `Err` of the value it raised
+    builtins.register("Std.Exception.toResult", 1, |_, args| {
+        let action = args[0].clone();
+        Ok(Value::io(Io::new(move |ctx| {
+            let result = io_arg("Std.Exception.toResult", &action).and_then(|io| io.run(ctx));
+            match result {
+                Ok(value) => Ok(Value::ok(value)),
+                Err(RuntimeError::Raised(value)) => Ok(Value::err(value)),
+                Err(error) => Err(error),
+            }
+        })))
+    });
+}
@@ -0,0 +1,190 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Http`: An HTTP client, with the `http` feature
+//!
+//! `get url`, `post url body` and `request request` are IO actions, which need the `network`
+//! permission of the sandbox. They return a `Response`, or an error message if no response was
+//! received, like when the host cannot be reached:
+//!
+//! ```text
+//! type Response = { status: Int, headers: Map String String, body: Bytes }
+//! ```
+//!
+//! A status that is not a success, like `404`, is still a response and not an error. The names
+//! of headers are in lower case, and headers that occur more than once are joined with `", "`.
+//! The body is read as bytes; `Std.Bytes.decodeUtf8 response.body` is the text of it.
+//!
+//! The body of `post` is a `String` or `Bytes`. `request` takes a record with the fields
+//! `method` and `url`, and optionally `headers`, a `Map String String`, and `body`, for
+//! everything else.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use reqwest::blocking::Client;
+use reqwest::Method;
+
+use crate::builtin::map_arg;
+use crate::builtin::record_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::collection::Key;
+use crate::collection::Map;
+use crate::error::RuntimeError;
+use crate::io::Io;
+use crate::sandbox::Permission;
+use crate::stdlib::io::permit;
+use crate::value::Value;
+
+// Shared by all requests, so connections are reused
+static CLIENT: Mutex<Option<Client>> = Mutex::new(None);
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Http.get", 1, |_, args| {
+        let url = str_arg("Std.Http.get", &args[0])?;
+        let request = Request {
+            method: Method::GET,
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+        };
+        Ok(action("Std.Http.get", request))
+    });
+
+    builtins.register("Std.Http.post", 2, |_, args| {
+        let url = str_arg("Std.Http.post", &args[0])?;
+        let request = Request {
+            method: Method::POST,
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: Some(body_arg("Std.Http.post", &args[1])?),
+        };
+        Ok(action("Std.Http.post", request))
+    });
+
+    builtins.register("Std.Http.request", 1, |_, args| {
+        let record = record_arg("Std.Http.request", &args[0])?;
+        let field = |name: &str| {
+            record
+                .get(name)
+                .ok_or_else(|| RuntimeError::InvalidArgument {
+                    builtin: "Std.Http.request".to_string(),
+                    expected: "record with the fields method and url",
+                    found: args[0].type_name().to_string(),
+                })
+        };
+
+        let method = str_arg("Std.Http.request", field("method")?)?;
+        let method = Method::from_bytes(method.to_uppercase().as_bytes()).map_err(|_| {
+            RuntimeError::InvalidArgument {
+                builtin: "Std.Http.request".to_string(),
+                expected: "HTTP method",
+                found: method.to_string(),
+            }
+        })?;
+        let headers = match record.get("headers") {
+            Some(headers) => map_arg("Std.Http.request", headers)?
+                .0
+                .iter()
+                .map(|(name, value)| {
+                    let name = str_arg("Std.Http.request", &name.to_value())?;
+                    let value = str_arg("Std.Http.request", value)?;
+                    Ok((name.to_string(), value.to_string()))
+                })
+                .collect::<Result<_, RuntimeError>>()?,
+            None => Vec::new(),
+        };
+        let body = match record.get("body") {
+            Some(body) => Some(body_arg("Std.Http.request", body)?),
+            None => None,
+        };
+
+        let request = Request {
+            method,
+            url: str_arg("Std.Http.request", field("url")?)?.to_string(),
+            headers,
+            body,
+        };
+        Ok(action("Std.Http.request", request))
+    });
+}
+
+struct Request {
+    method: Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+}
+
+fn action(builtin: &'static str, request: Request) -> Value {
+    let request = Arc::new(request);
+    Value::io(Io::new(move |ctx| {
+        permit(ctx, builtin, Permission::Network)?;
+        let request = request.clone();
+        // The blocking client runs its own async runtime, which cannot be started on a thread
+        // that is part of another one, like the one of a host that embeds the runtime
+        let response = std::thread::spawn(move || send(&request))
+            .join()
+            .unwrap_or_else(|_| Err("the request panicked".to_string()));
+        Ok(match response {
+            Ok(response) => Value::ok(response),
+            Err(message) => Value::err(Value::string(message)),
+        })
+    }))
+}
+
+fn send(request: &Request) -> Result<Value, String> {
+    let client = CLIENT
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(Client::new)
+        .clone();
+
+    let builder = request.headers.iter().fold(
+        client.request(request.method.clone(), &request.url),
+        |builder, (name, value)| builder.header(name, value),
+    );
+    let builder = match &request.body {
+        Some(body) => builder.body(body.clone()),
+        None => builder,
+    };
+    let response = builder.send().map_err(|error| error.to_string())?;
+
+    let status = Value::Integer(i64::from(response.status().as_u16()));
+    let mut headers = im::OrdMap::<Key, Value>::new();
+    for (name, value) in response.headers() {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        let value = match headers.get(&Key::Str(name.to_string())) {
+            Some(Value::Str(previous)) => format!("{}, {}", previous.as_str(), value),
+            _ => value,
+        };
+        headers.insert(Key::Str(name.to_string()), Value::string(value));
+    }
+    let body = response.bytes().map_err(|error| error.to_string())?;
+
+    let fields = [
+        ("status".to_string(), status),
+        ("headers".to_string(), Value::map(Map(headers))),
+        ("body".to_string(), Value::bytes(body.to_vec())),
+    ];
+    Ok(Value::record(
+        Some("Response".to_string()),
+        fields.into_iter().collect(),
+    ))
+}
+
+// The body of a request, which is sent as it is
+fn body_arg(builtin: &str, value: &Value) -> Result<Vec<u8>, RuntimeError> {
+    match value.force()? {
+        Value::Str(s) => Ok(s.as_bytes().to_vec()),
+        Value::Bytes(bytes) => Ok(bytes.to_vec()),
+        other => Err(RuntimeError::InvalidArgument {
+            builtin: builtin.to_string(),
+            expected: "String or Bytes",
+            found: other.type_name().to_string(),
+        }),
+    }
+}
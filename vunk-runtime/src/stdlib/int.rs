@@ -0,0 +1,72 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Int`: Explicit overflow behaviour for `Int` arithmetic
+//!
+//! The arithmetic operators fail on overflow (see [`crate::arith`]). Code that wants something
+//! else has to say so, by using the `wrapping*` functions, which wrap around in two's complement,
+//! or the `checked*` functions, which return `None` on overflow and division by zero.
+//!
+//! Integer division is explicit as well: `quot` and `rem` truncate like `/` and `%`, and `div` and
+//! `mod` are floored, see [`crate::arith`] for how they differ on negative operands.
+
+use crate::arith;
+use crate::builtin::int_arg;
+use crate::builtin::Builtins;
+use crate::error::RuntimeError;
+use crate::value::Value;
+
+type Wrapping = fn(i64, i64) -> i64;
+type Checked = fn(i64, i64) -> Option<i64>;
+type Division = fn(&Value, &Value) -> Result<Value, RuntimeError>;
+
+const WRAPPING: [(&str, Wrapping); 3] = [
+    ("wrappingAdd", i64::wrapping_add),
+    ("wrappingSub", i64::wrapping_sub),
+    ("wrappingMul", i64::wrapping_mul),
+];
+
+const CHECKED: [(&str, Checked); 5] = [
+    ("checkedAdd", i64::checked_add),
+    ("checkedSub", i64::checked_sub),
+    ("checkedMul", i64::checked_mul),
+    ("checkedDiv", i64::checked_div),
+    ("checkedRem", arith::checked_rem),
+];
+
+const DIVISION: [(&str, Division); 4] = [
+    ("quot", arith::div),
+    ("rem", arith::rem),
+    ("div", arith::div_floor),
+    ("mod", arith::mod_floor),
+];
+
+pub fn register(builtins: &mut Builtins) {
+    for (name, op) in WRAPPING {
+        let name = format!("Std.Int.{}", name);
+        builtins.register(name.clone(), 2, move |_, args| {
+            let a = int_arg(&name, &args[0])?;
+            let b = int_arg(&name, &args[1])?;
+            Ok(Value::Integer(op(a, b)))
+        });
+    }
+
+    for (name, op) in CHECKED {
+        let name = format!("Std.Int.{}", name);
+        builtins.register(name.clone(), 2, move |_, args| {
+            let a = int_arg(&name, &args[0])?;
+            let b = int_arg(&name, &args[1])?;
+            Ok(op(a, b)
+                .map(Value::Integer)
+                .map(Value::some)
+                .unwrap_or_else(Value::none))
+        });
+    }
+
+    for (name, op) in DIVISION {
+        builtins.register(format!("Std.Int.{}", name), 2, move |_, args| {
+            op(&args[0], &args[1])
+        });
+    }
+}
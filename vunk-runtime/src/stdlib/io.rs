@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.IO`: Combining IO actions, and actions for the console and files
+//!
+//! Actions that touch the outside world check the [`crate::sandbox::Sandbox`] of the context they are run in.
+
+use std::io::BufRead;
+use std::io::Write;
+
+use crate::builtin::io_arg;
+use crate::builtin::list_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::error::RuntimeError;
+use crate::function::Context;
+use crate::io::Io;
+use crate::sandbox::Permission;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    // An action that does nothing but produce a value
+    builtins.register("Std.IO.pure", 1, |_, args| {
+        let value = args[0].clone();
+        Ok(Value::io(Io::new(move |_| Ok(value.clone()))))
+    });
+
+    builtins.register("Std.IO.map", 2, |_, args| {
+        let function = args[0].clone();
+        let io = io_arg("Std.IO.map", &args[1])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            let value = io.run(ctx)?;
+            ctx.call(&function, vec![value])
+        })))
+    });
+
+    // Run an action, then pass its result to a function returning the next action and run that
+    builtins.register("Std.IO.andThen", 2, |_, args| {
+        let function = args[0].clone();
+        let io = io_arg("Std.IO.andThen", &args[1])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            let value = io.run(ctx)?;
+            let next = ctx.call(&function, vec![value])?;
+            io_arg("Std.IO.andThen", &next)?.run(ctx)
+        })))
+    });
+
+    // Run a list of actions one after another, collecting their results
+    builtins.register("Std.IO.sequence", 1, |_, args| {
+        let ios = list_arg("Std.IO.sequence", &args[0])?
+            .iter()
+            .map(|io| io_arg("Std.IO.sequence", io))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Value::io(Io::new(move |ctx| {
+            ios.iter()
+                .map(|io| io.run(ctx))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::list)
+        })))
+    });
+
+    builtins.register("Std.IO.print", 1, |_, args| {
+        let s = str_arg("Std.IO.print", &args[0])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.IO.print", Permission::Console)?;
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(s.as_bytes())
+                .and_then(|_| stdout.flush())
+                .map_err(|e| io_error("Std.IO.print", e))?;
+            Ok(Value::Unit)
+        })))
+    });
+
+    builtins.register("Std.IO.println", 1, |_, args| {
+        let s = str_arg("Std.IO.println", &args[0])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.IO.println", Permission::Console)?;
+            writeln!(std::io::stdout().lock(), "{}", s.as_str())
+                .map_err(|e| io_error("Std.IO.println", e))?;
+            Ok(Value::Unit)
+        })))
+    });
+
+    // Read a line from stdin, without the line terminator
+    builtins.register("Std.IO.readLine", 0, |_, _| {
+        Ok(Value::io(Io::new(|ctx| {
+            permit(ctx, "Std.IO.readLine", Permission::Console)?;
+            let mut line = String::new();
+            let read = std::io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .map_err(|e| io_error("Std.IO.readLine", e))?;
+            if read == 0 {
+                return Err(RuntimeError::Io {
+                    builtin: "Std.IO.readLine",
+                    message: "end of input".to_string(),
+                });
+            }
+
+            let len = line.trim_end_matches(&['\n', '\r'][..]).len();
+            line.truncate(len);
+            Ok(Value::string(line))
+        })))
+    });
+
+    builtins.register("Std.IO.readFile", 1, |_, args| {
+        let path = str_arg("Std.IO.readFile", &args[0])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.IO.readFile", Permission::ReadFiles)?;
+            std::fs::read_to_string(path.as_str())
+                .map(Value::string)
+                .map_err(|e| io_error("Std.IO.readFile", e))
+        })))
+    });
+
+    // Create or truncate a file and write the string to it
+    builtins.register("Std.IO.writeFile", 2, |_, args| {
+        let path = str_arg("Std.IO.writeFile", &args[0])?;
+        let contents = str_arg("Std.IO.writeFile", &args[1])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.IO.writeFile", Permission::WriteFiles)?;
+            std::fs::write(path.as_str(), contents.as_bytes())
+                .map(|_| Value::Unit)
+                .map_err(|e| io_error("Std.IO.writeFile", e))
+        })))
+    });
+
+    // Append the string to a file, creating it if it does not exist
+    builtins.register("Std.IO.appendFile", 2, |_, args| {
+        let path = str_arg("Std.IO.appendFile", &args[0])?;
+        let contents = str_arg("Std.IO.appendFile", &args[1])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.IO.appendFile", Permission::WriteFiles)?;
+            std::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(path.as_str())
+                .and_then(|mut file| file.write_all(contents.as_bytes()))
+                .map(|_| Value::Unit)
+                .map_err(|e| io_error("Std.IO.appendFile", e))
+        })))
+    });
+}
+
+pub(crate) fn permit(
+    ctx: &dyn Context,
+    builtin: &'static str,
+    permission: Permission,
+) -> Result<(), RuntimeError> {
+    if ctx.sandbox().allows(permission) {
+        Ok(())
+    } else {
+        Err(RuntimeError::PermissionDenied {
+            builtin,
+            permission,
+        })
+    }
+}
+
+pub(crate) fn io_error(builtin: &'static str, error: std::io::Error) -> RuntimeError {
+    RuntimeError::Io {
+        builtin,
+        message: error.to_string(),
+    }
+}
@@ -0,0 +1,390 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Json`: Parsing and serializing JSON
+//!
+//! JSON documents are represented by the `Json` type:
+//!
+//! ```text
+//! type Json = Null | Bool Bool | Int Int | Float Float | String String
+//!           | Array (List Json) | Object (Map String Json)
+//! ```
+//!
+//! Numbers without fraction and exponent that fit into an `Int` are parsed as `Json.Int`, all
+//! others as `Json.Float`.
+
+use crate::builtin::list_arg;
+use crate::builtin::map_arg;
+use crate::builtin::str_arg;
+use crate::builtin::variant_arg;
+use crate::builtin::Builtins;
+use crate::collection::Key;
+use crate::collection::Map;
+use crate::error::RuntimeError;
+use crate::float;
+use crate::value::Value;
+
+// Deeper documents are rejected instead of overflowing the stack
+pub(crate) const MAX_DEPTH: usize = 512;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Json.Null", 0, |_, _| Ok(json("Null", Vec::new())));
+    for name in ["Bool", "Int", "Float", "String", "Array", "Object"] {
+        builtins.register(format!("Std.Json.{}", name), 1, move |_, args| {
+            Ok(json(name, vec![args[0].clone()]))
+        });
+    }
+
+    // Parse a JSON document, returning an error message if it is not valid
+    builtins.register("Std.Json.parse", 1, |_, args| {
+        let s = str_arg("Std.Json.parse", &args[0])?;
+        let mut parser = Parser {
+            input: s.as_str(),
+            pos: 0,
+        };
+
+        Ok(match parser.document() {
+            Ok(value) => Value::ok(value),
+            Err(message) => Value::err(Value::string(message)),
+        })
+    });
+
+    // Serialize to JSON without any whitespace
+    builtins.register("Std.Json.encode", 1, |_, args| {
+        let mut out = String::new();
+        encode(&args[0], 0, &mut out)?;
+        Ok(Value::string(out))
+    });
+}
+
+pub(crate) fn json(name: &str, members: Vec<Value>) -> Value {
+    Value::variant("Json", name, members)
+}
+
+fn encode(value: &Value, depth: usize, out: &mut String) -> Result<(), RuntimeError> {
+    if depth > MAX_DEPTH {
+        return Err(RuntimeError::InvalidArgument {
+            builtin: "Std.Json.encode".to_string(),
+            expected: "Json nested at most 512 levels deep",
+            found: "Json".to_string(),
+        });
+    }
+
+    let json = variant_arg("Std.Json.encode", value, "Json")?;
+    let member = || json.members[0].force();
+
+    match json.name.as_str() {
+        "Null" => out.push_str("null"),
+        "Bool" => match member()? {
+            Value::Bool(b) => out.push_str(if b { "true" } else { "false" }),
+            other => return Err(invalid_member("Bool", &other)),
+        },
+        "Int" => match member()? {
+            Value::Integer(i) => out.push_str(&i.to_string()),
+            Value::BigInt(i) => out.push_str(&i.to_string()),
+            other => return Err(invalid_member("Int", &other)),
+        },
+        "Float" => match member()? {
+            // JSON has no representation for NaN and infinity
+            Value::Float(f) if !f.is_finite() => out.push_str("null"),
+            Value::Float(f) => out.push_str(&float::show(f)),
+            other => return Err(invalid_member("Float", &other)),
+        },
+        "String" => encode_str(&str_arg("Std.Json.encode", &json.members[0])?, out),
+        "Array" => {
+            out.push('[');
+            for (i, element) in list_arg("Std.Json.encode", &json.members[0])?
+                .iter()
+                .enumerate()
+            {
+                if i > 0 {
+                    out.push(',');
+                }
+                encode(element, depth + 1, out)?;
+            }
+            out.push(']');
+        }
+        "Object" => {
+            out.push('{');
+            for (i, (key, element)) in map_arg("Std.Json.encode", &json.members[0])?
+                .0
+                .iter()
+                .enumerate()
+            {
+                if i > 0 {
+                    out.push(',');
+                }
+                match key {
+                    Key::Str(key) => encode_str(key, out),
+                    _ => {
+                        return Err(RuntimeError::InvalidArgument {
+                            builtin: "Std.Json.encode".to_string(),
+                            expected: "Map String Json",
+                            found: key.to_value().type_name().to_string(),
+                        })
+                    }
+                }
+                out.push(':');
+                encode(element, depth + 1, out)?;
+            }
+            out.push('}');
+        }
+        _ => unreachable!("Json variant {}", json.name),
+    }
+
+    Ok(())
+}
+
+fn invalid_member(expected: &'static str, found: &Value) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        builtin: "Std.Json.encode".to_string(),
+        expected,
+        found: found.type_name().to_string(),
+    }
+}
+
+fn encode_str(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn document(&mut self) -> Result<Value, String> {
+        let value = self.value(0)?;
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(value),
+            Some(c) => Err(self.error(&format!("unexpected '{}' after the document", c))),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("document is nested too deeply"));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.literal("null", json("Null", Vec::new())),
+            Some('t') => self.literal("true", json("Bool", vec![Value::Bool(true)])),
+            Some('f') => self.literal("false", json("Bool", vec![Value::Bool(false)])),
+            Some('"') => Ok(json("String", vec![Value::string(self.string()?)])),
+            Some('[') => self.array(depth),
+            Some('{') => self.object(depth),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        if self.input[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error(&format!("expected '{}'", word)))
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut elements = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(json("Array", vec![Value::list(elements)]));
+        }
+
+        loop {
+            elements.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(json("Array", vec![Value::list(elements)])),
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut map = Map::default();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(json("Object", vec![Value::map(map)]));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value(depth + 1)?;
+            map.0.insert(Key::Str(key), value);
+
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => return Ok(json("Object", vec![Value::map(map)])),
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        let mut integral = true;
+
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        self.digits()?;
+        if self.peek() == Some('.') {
+            integral = false;
+            self.pos += 1;
+            self.digits()?;
+        }
+        if let Some('e' | 'E') = self.peek() {
+            integral = false;
+            self.pos += 1;
+            if let Some('+' | '-') = self.peek() {
+                self.pos += 1;
+            }
+            self.digits()?;
+        }
+
+        let number = &self.input[start..self.pos];
+        if integral {
+            if let Ok(i) = number.parse::<i64>() {
+                return Ok(json("Int", vec![Value::Integer(i)]));
+            }
+        }
+
+        number
+            .parse::<f64>()
+            .map(|f| json("Float", vec![Value::Float(f)]))
+            .map_err(|_| self.error("invalid number"))
+    }
+
+    fn digits(&mut self) -> Result<(), String> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        if self.pos == start {
+            Err(self.error("expected a digit"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.bump() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => s.push(self.unicode_escape()?),
+                    _ => return Err(self.error("invalid escape sequence")),
+                },
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error("control character in string"))
+                }
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    // The part after "\u", which may be followed by a second escape for a surrogate pair
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.input[self.pos..].starts_with("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        let code = u32::from_str_radix(digits, 16).unwrap();
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("expected '{}'", expected))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at offset {}", message, self.pos)
+    }
+}
@@ -0,0 +1,132 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.List`: Functions on lists
+
+use std::cmp::Ordering;
+
+use crate::builtin::bool_arg;
+use crate::builtin::int_arg;
+use crate::builtin::list_arg;
+use crate::builtin::Builtins;
+use crate::cmp::compare;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.List.map", 2, |ctx, args| {
+        let list = list_arg("Std.List.map", &args[1])?;
+        list.iter()
+            .map(|element| ctx.call(&args[0], vec![element.clone()]))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::list)
+    });
+
+    builtins.register("Std.List.filter", 2, |ctx, args| {
+        let list = list_arg("Std.List.filter", &args[1])?;
+        let mut filtered = Vec::new();
+        for element in list.iter() {
+            let keep = ctx.call(&args[0], vec![element.clone()])?;
+            if bool_arg("Std.List.filter", &keep)? {
+                filtered.push(element.clone());
+            }
+        }
+        Ok(Value::list(filtered))
+    });
+
+    builtins.register("Std.List.fold", 3, |ctx, args| {
+        let list = list_arg("Std.List.fold", &args[2])?;
+        list.iter().try_fold(args[1].clone(), |acc, element| {
+            ctx.call(&args[0], vec![acc, element.clone()])
+        })
+    });
+
+    builtins.register("Std.List.length", 1, |_, args| {
+        let list = list_arg("Std.List.length", &args[0])?;
+        Ok(Value::Integer(list.len() as i64))
+    });
+
+    builtins.register("Std.List.isEmpty", 1, |_, args| {
+        let list = list_arg("Std.List.isEmpty", &args[0])?;
+        Ok(Value::Bool(list.is_empty()))
+    });
+
+    builtins.register("Std.List.head", 1, |_, args| {
+        let list = list_arg("Std.List.head", &args[0])?;
+        Ok(list
+            .first()
+            .cloned()
+            .map(Value::some)
+            .unwrap_or_else(Value::none))
+    });
+
+    builtins.register("Std.List.reverse", 1, |_, args| {
+        let mut list = Value::List(list_arg("Std.List.reverse", &args[0])?);
+        if let Some(elements) = list.list_mut() {
+            elements.reverse();
+        }
+        Ok(list)
+    });
+
+    builtins.register("Std.List.concat", 1, |_, args| {
+        let lists = list_arg("Std.List.concat", &args[0])?;
+        let mut concatenated = Vec::new();
+        for list in lists.iter() {
+            concatenated.extend(list_arg("Std.List.concat", list)?.iter().cloned());
+        }
+        Ok(Value::list(concatenated))
+    });
+
+    builtins.register("Std.List.concatMap", 2, |ctx, args| {
+        let list = list_arg("Std.List.concatMap", &args[1])?;
+        let mut concatenated = Vec::new();
+        for element in list.iter() {
+            let mapped = ctx.call(&args[0], vec![element.clone()])?;
+            concatenated.extend(list_arg("Std.List.concatMap", &mapped)?.iter().cloned());
+        }
+        Ok(Value::list(concatenated))
+    });
+
+    builtins.register("Std.List.zip", 2, |_, args| {
+        let a = list_arg("Std.List.zip", &args[0])?;
+        let b = list_arg("Std.List.zip", &args[1])?;
+        Ok(Value::list(
+            a.iter()
+                .zip(b.iter())
+                .map(|(a, b)| Value::tuple(vec![a.clone(), b.clone()]))
+                .collect(),
+        ))
+    });
+
+    builtins.register("Std.List.sort", 1, |_, args| {
+        let mut list = Value::List(list_arg("Std.List.sort", &args[0])?);
+        let mut error = None;
+        if let Some(elements) = list.list_mut() {
+            elements.sort_by(|a, b| {
+                compare(a, b).unwrap_or_else(|e| {
+                    error.get_or_insert(e);
+                    Ordering::Equal
+                })
+            });
+        }
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(list),
+        }
+    });
+
+    builtins.register("Std.List.take", 2, |_, args| {
+        let n = int_arg("Std.List.take", &args[0])?;
+        let list = list_arg("Std.List.take", &args[1])?;
+        let n = usize::try_from(n).unwrap_or(0);
+        Ok(Value::list(list.iter().take(n).cloned().collect()))
+    });
+
+    builtins.register("Std.List.drop", 2, |_, args| {
+        let n = int_arg("Std.List.drop", &args[0])?;
+        let list = list_arg("Std.List.drop", &args[1])?;
+        let n = usize::try_from(n).unwrap_or(0);
+        Ok(Value::list(list.iter().skip(n).cloned().collect()))
+    });
+}
@@ -0,0 +1,96 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Map`: Persistent maps from keys to values
+
+use crate::builtin::list_arg;
+use crate::builtin::map_arg;
+use crate::builtin::tuple_arg;
+use crate::builtin::Builtins;
+use crate::collection::Key;
+use crate::collection::Map;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Map.empty", 0, |_, _| Ok(Value::map(Map::default())));
+
+    builtins.register("Std.Map.fromList", 1, |_, args| {
+        let list = list_arg("Std.Map.fromList", &args[0])?;
+        let mut map = im::OrdMap::new();
+        for pair in list.iter() {
+            let pair = tuple_arg("Std.Map.fromList", pair, 2)?;
+            map.insert(Key::from_value(&pair.0[0])?, pair.0[1].clone());
+        }
+        Ok(Value::map(Map(map)))
+    });
+
+    builtins.register("Std.Map.insert", 3, |_, args| {
+        let key = Key::from_value(&args[0])?;
+        let map = map_arg("Std.Map.insert", &args[2])?;
+        Ok(Value::map(Map(map.0.update(key, args[1].clone()))))
+    });
+
+    builtins.register("Std.Map.remove", 2, |_, args| {
+        let key = Key::from_value(&args[0])?;
+        let map = map_arg("Std.Map.remove", &args[1])?;
+        Ok(Value::map(Map(map.0.without(&key))))
+    });
+
+    builtins.register("Std.Map.get", 2, |_, args| {
+        let key = Key::from_value(&args[0])?;
+        let map = map_arg("Std.Map.get", &args[1])?;
+        Ok(map
+            .0
+            .get(&key)
+            .cloned()
+            .map(Value::some)
+            .unwrap_or_else(Value::none))
+    });
+
+    builtins.register("Std.Map.member", 2, |_, args| {
+        let key = Key::from_value(&args[0])?;
+        let map = map_arg("Std.Map.member", &args[1])?;
+        Ok(Value::Bool(map.0.contains_key(&key)))
+    });
+
+    builtins.register("Std.Map.size", 1, |_, args| {
+        let map = map_arg("Std.Map.size", &args[0])?;
+        Ok(Value::Integer(map.0.len() as i64))
+    });
+
+    // Left biased: if both maps contain a key, the value from the first map is used
+    builtins.register("Std.Map.union", 2, |_, args| {
+        let a = map_arg("Std.Map.union", &args[0])?;
+        let b = map_arg("Std.Map.union", &args[1])?;
+        Ok(Value::map(Map(a.0.clone().union(b.0.clone()))))
+    });
+
+    builtins.register("Std.Map.keys", 1, |_, args| {
+        let map = map_arg("Std.Map.keys", &args[0])?;
+        Ok(Value::list(map.0.keys().map(Key::to_value).collect()))
+    });
+
+    builtins.register("Std.Map.values", 1, |_, args| {
+        let map = map_arg("Std.Map.values", &args[0])?;
+        Ok(Value::list(map.0.values().cloned().collect()))
+    });
+
+    builtins.register("Std.Map.toList", 1, |_, args| {
+        let map = map_arg("Std.Map.toList", &args[0])?;
+        Ok(Value::list(
+            map.0
+                .iter()
+                .map(|(key, value)| Value::tuple(vec![key.to_value(), value.clone()]))
+                .collect(),
+        ))
+    });
+
+    // Folds in ascending key order, calling the function with the accumulator, key and value
+    builtins.register("Std.Map.fold", 3, |ctx, args| {
+        let map = map_arg("Std.Map.fold", &args[2])?;
+        map.0.iter().try_fold(args[1].clone(), |acc, (key, value)| {
+            ctx.call(&args[0], vec![acc, key.to_value(), value.clone()])
+        })
+    });
+}
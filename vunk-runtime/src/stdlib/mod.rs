@@ -0,0 +1,59 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::builtin::Builtins;
+
+pub mod bytes;
+pub mod cell;
+pub mod cmp;
+pub mod debug;
+pub mod effect;
+pub mod env;
+pub mod exception;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod int;
+pub mod io;
+pub mod json;
+pub mod list;
+pub mod map;
+pub mod option;
+pub mod process;
+pub mod random;
+pub mod regex;
+pub mod result;
+pub mod set;
+pub mod string;
+pub mod task;
+pub mod time;
+pub mod toml;
+pub mod yaml;
+
+pub fn register(builtins: &mut Builtins) {
+    bytes::register(builtins);
+    cell::register(builtins);
+    cmp::register(builtins);
+    debug::register(builtins);
+    env::register(builtins);
+    exception::register(builtins);
+    #[cfg(feature = "http")]
+    http::register(builtins);
+    int::register(builtins);
+    io::register(builtins);
+    effect::register(builtins);
+    json::register(builtins);
+    list::register(builtins);
+    map::register(builtins);
+    option::register(builtins);
+    process::register(builtins);
+    random::register(builtins);
+    regex::register(builtins);
+    result::register(builtins);
+    set::register(builtins);
+    string::register(builtins);
+    task::register(builtins);
+    time::register(builtins);
+    toml::register(builtins);
+    yaml::register(builtins);
+}
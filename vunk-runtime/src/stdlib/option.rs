@@ -0,0 +1,63 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Option`: An optional value, either `Some A` or `None`
+//!
+//! The constructors are part of the prelude.
+
+use crate::builtin::variant_arg;
+use crate::builtin::Builtins;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Option.Some", 1, |_, args| {
+        Ok(Value::some(args[0].clone()))
+    });
+    builtins.register("Std.Option.None", 0, |_, _| Ok(Value::none()));
+    builtins.add_to_prelude("Some", "Std.Option.Some");
+    builtins.add_to_prelude("None", "Std.Option.None");
+
+    builtins.register("Std.Option.isSome", 1, |_, args| {
+        let option = variant_arg("Std.Option.isSome", &args[0], "Option")?;
+        Ok(Value::Bool(option.name == "Some"))
+    });
+
+    builtins.register("Std.Option.isNone", 1, |_, args| {
+        let option = variant_arg("Std.Option.isNone", &args[0], "Option")?;
+        Ok(Value::Bool(option.name == "None"))
+    });
+
+    builtins.register("Std.Option.map", 2, |ctx, args| {
+        let option = variant_arg("Std.Option.map", &args[1], "Option")?;
+        match option.members.first() {
+            Some(value) => ctx.call(&args[0], vec![value.clone()]).map(Value::some),
+            None => Ok(Value::none()),
+        }
+    });
+
+    builtins.register("Std.Option.andThen", 2, |ctx, args| {
+        let option = variant_arg("Std.Option.andThen", &args[1], "Option")?;
+        match option.members.first() {
+            Some(value) => ctx.call(&args[0], vec![value.clone()]),
+            None => Ok(Value::none()),
+        }
+    });
+
+    builtins.register("Std.Option.unwrapOr", 2, |_, args| {
+        let option = variant_arg("Std.Option.unwrapOr", &args[1], "Option")?;
+        Ok(option
+            .members
+            .first()
+            .cloned()
+            .unwrap_or_else(|| args[0].clone()))
+    });
+
+    builtins.register("Std.Option.okOr", 2, |_, args| {
+        let option = variant_arg("Std.Option.okOr", &args[1], "Option")?;
+        Ok(match option.members.first() {
+            Some(value) => Value::ok(value.clone()),
+            None => Value::err(args[0].clone()),
+        })
+    });
+}
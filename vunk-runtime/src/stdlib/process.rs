@@ -0,0 +1,160 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Process`: Running other programs
+//!
+//! All actions of this module need the `run-processes` permission of the sandbox, so embedders
+//! that do not grant it can be sure no program is started. Programs are looked up in the `PATH`
+//! and their arguments passed as they are, without a shell in between.
+//!
+//! `run command args` waits for the program to finish and returns its output:
+//!
+//! ```text
+//! type Output = { status: Option Int, stdout: String, stderr: String }
+//! ```
+//!
+//! The status is the exit code, or `None` if the program was killed by a signal. Output that is
+//! not valid UTF-8 is decoded with replacement characters. `runWithInput command args input`
+//! writes the input to the standard input of the program first.
+//!
+//! `stream command args onLine` runs the action `onLine line` for every line the program
+//! writes, as soon as it is written, and returns the status. The errors of the program go
+//! straight to the standard error of this one.
+//!
+//! Failing to start a program, like when it does not exist, fails with [`RuntimeError::Io`].
+
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Stdio;
+
+use crate::builtin::io_arg;
+use crate::builtin::list_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::error::RuntimeError;
+use crate::io::Io;
+use crate::sandbox::Permission;
+use crate::stdlib::io::io_error;
+use crate::stdlib::io::permit;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Process.run", 2, |_, args| {
+        let command = command_args("Std.Process.run", &args[0], &args[1])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.Process.run", Permission::RunProcesses)?;
+            let output = command()
+                .stdin(Stdio::null())
+                .output()
+                .map_err(|e| io_error("Std.Process.run", e))?;
+            Ok(output_record(output))
+        })))
+    });
+
+    builtins.register("Std.Process.runWithInput", 3, |_, args| {
+        let command = command_args("Std.Process.runWithInput", &args[0], &args[1])?;
+        let input = str_arg("Std.Process.runWithInput", &args[2])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.Process.runWithInput", Permission::RunProcesses)?;
+            let mut child = command()
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| io_error("Std.Process.runWithInput", e))?;
+
+            // Written on a thread of its own, so a program that writes a lot before it reads
+            // all of its input cannot block on a full pipe while this one blocks on its input
+            let mut stdin = child.stdin.take().expect("stdin is piped");
+            let input = input.clone();
+            let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+            let output = child
+                .wait_with_output()
+                .map_err(|e| io_error("Std.Process.runWithInput", e))?;
+            // The program does not have to read all of its input
+            let _ = writer.join();
+            Ok(output_record(output))
+        })))
+    });
+
+    builtins.register("Std.Process.stream", 3, |_, args| {
+        let command = command_args("Std.Process.stream", &args[0], &args[1])?;
+        let on_line = args[2].clone();
+        Ok(Value::io(Io::new(move |ctx| {
+            permit(ctx, "Std.Process.stream", Permission::RunProcesses)?;
+            let mut child = command()
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(|e| io_error("Std.Process.stream", e))?;
+
+            let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+            for line in stdout.split(b'\n') {
+                let mut line = line.map_err(|e| io_error("Std.Process.stream", e))?;
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                let line = Value::string(String::from_utf8_lossy(&line));
+                let action = ctx.call(&on_line, vec![line]);
+                if let Err(error) =
+                    action.and_then(|action| io_arg("Std.Process.stream", &action)?.run(ctx))
+                {
+                    // Do not leave the program running without anyone reading its output
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(error);
+                }
+            }
+
+            let status = child
+                .wait()
+                .map_err(|e| io_error("Std.Process.stream", e))?;
+            Ok(status_value(status))
+        })))
+    });
+}
+
+// A function making a new `Command` for the program and its arguments every time it is run
+fn command_args(
+    builtin: &str,
+    program: &Value,
+    args: &Value,
+) -> Result<impl Fn() -> Command + Send + Sync + 'static, RuntimeError> {
+    let program = str_arg(builtin, program)?;
+    let args = list_arg(builtin, args)?
+        .iter()
+        .map(|arg| str_arg(builtin, arg).map(|arg| arg.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(move || {
+        let mut command = Command::new(program.as_str());
+        command.args(&args);
+        command
+    })
+}
+
+fn output_record(output: std::process::Output) -> Value {
+    let fields = BTreeMap::from([
+        ("status".to_string(), status_value(output.status)),
+        (
+            "stdout".to_string(),
+            Value::string(String::from_utf8_lossy(&output.stdout)),
+        ),
+        (
+            "stderr".to_string(),
+            Value::string(String::from_utf8_lossy(&output.stderr)),
+        ),
+    ]);
+    Value::record(Some("Output".to_string()), fields)
+}
+
+fn status_value(status: ExitStatus) -> Value {
+    match status.code() {
+        Some(code) => Value::some(Value::Integer(i64::from(code))),
+        None => Value::none(),
+    }
+}
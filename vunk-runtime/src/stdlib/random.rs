@@ -0,0 +1,197 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Random`: Pseudo-random numbers
+//!
+//! Random numbers come in two variants, which share the generator:
+//!
+//! - `Std.Random.int low high`, `float`, `bool`, `shuffle` and `choice` are IO actions that use
+//!   a generator of the process, which is seeded from the entropy of the operating system.
+//! - `Std.Random.Seed` has the same functions as pure functions, which take a `Seed` as their
+//!   last argument and return a tuple of their result and the next seed. The same seed always
+//!   gives the same results, which makes programs and tests that use them deterministic.
+//!   `Std.Random.Seed.new 42` is a seed, `Std.Random.seed` an action that returns a fresh one.
+//!
+//! ```text
+//! let (roll, seed) = Std.Random.Seed.int 1 6 (Std.Random.Seed.new 42)
+//!     (coin, seed) = Std.Random.Seed.bool seed
+//! ```
+//!
+//! The generator is SplitMix64, which is fast and good enough for simulations and games, but
+//! not for cryptography. Integers are between `low` and `high` inclusive, without bias, and
+//! floats between `low` inclusive and `high` exclusive.
+
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use crate::builtin::float_arg;
+use crate::builtin::int_arg;
+use crate::builtin::list_arg;
+use crate::builtin::record_arg;
+use crate::builtin::Builtins;
+use crate::error::RuntimeError;
+use crate::io::Io;
+use crate::value::Value;
+
+type Generate = fn(&str, &[Value], &mut Rng) -> Result<Value, RuntimeError>;
+
+/// The functions of both variants, with the number of arguments they take besides the seed
+const FUNCTIONS: [(&str, usize, Generate); 5] = [
+    ("int", 2, int),
+    ("float", 2, float),
+    ("bool", 0, bool),
+    ("shuffle", 1, shuffle),
+    ("choice", 1, choice),
+];
+
+// The state of the generator of the process, which is seeded when it is first used
+static STATE: Mutex<Option<u64>> = Mutex::new(None);
+
+pub fn register(builtins: &mut Builtins) {
+    for (name, arity, generate) in FUNCTIONS {
+        let io_name = format!("Std.Random.{}", name);
+        builtins.register(io_name.clone(), arity, move |_, args| {
+            let io_name = io_name.clone();
+            Ok(Value::io(Io::new(move |_| {
+                let mut state = STATE.lock().unwrap_or_else(PoisonError::into_inner);
+                let mut rng = Rng(*state.get_or_insert_with(entropy));
+                let value = generate(&io_name, &args, &mut rng)?;
+                *state = Some(rng.0);
+                Ok(value)
+            })))
+        });
+
+        let pure_name = format!("Std.Random.Seed.{}", name);
+        builtins.register(pure_name.clone(), arity + 1, move |_, args| {
+            let mut rng = Rng(seed_arg(&pure_name, &args[arity])?);
+            let value = generate(&pure_name, &args[..arity], &mut rng)?;
+            Ok(Value::tuple(vec![value, seed(rng.0)]))
+        });
+    }
+
+    builtins.register("Std.Random.Seed.new", 1, |_, args| {
+        let n = int_arg("Std.Random.Seed.new", &args[0])?;
+        Ok(seed(n as u64))
+    });
+
+    // Two seeds that are independent of each other, to pass to functions that use their own
+    builtins.register("Std.Random.Seed.split", 1, |_, args| {
+        let mut rng = Rng(seed_arg("Std.Random.Seed.split", &args[0])?);
+        let other = rng.next();
+        Ok(Value::tuple(vec![seed(rng.0), seed(other)]))
+    });
+
+    builtins.register("Std.Random.seed", 0, |_, _| {
+        Ok(Value::io(Io::new(|_| Ok(seed(entropy())))))
+    });
+}
+
+fn int(builtin: &str, args: &[Value], rng: &mut Rng) -> Result<Value, RuntimeError> {
+    let low = int_arg(builtin, &args[0])?;
+    let high = int_arg(builtin, &args[1])?;
+    if low > high {
+        return Err(invalid_range(builtin, low, high));
+    }
+    // The number of values in the range, where 0 stands for all 2^64 of them
+    let range = (high as u64).wrapping_sub(low as u64).wrapping_add(1);
+    Ok(Value::Integer(low.wrapping_add(rng.below(range) as i64)))
+}
+
+fn float(builtin: &str, args: &[Value], rng: &mut Rng) -> Result<Value, RuntimeError> {
+    let low = float_arg(builtin, &args[0])?;
+    let high = float_arg(builtin, &args[1])?;
+    if !(low <= high && (high - low).is_finite()) {
+        return Err(invalid_range(builtin, low, high));
+    }
+    // The 53 bits of precision of a float, scaled to [0, 1)
+    let unit = (rng.next() >> 11) as f64 / (1u64 << 53) as f64;
+    let x = low + unit * (high - low);
+    // Rounding can reach `high`, which happens so rarely that `low` can stand in for it
+    Ok(Value::Float(if x < high { x } else { low }))
+}
+
+fn bool(_: &str, _: &[Value], rng: &mut Rng) -> Result<Value, RuntimeError> {
+    Ok(Value::Bool(rng.next() >> 63 == 1))
+}
+
+// Fisher-Yates
+fn shuffle(builtin: &str, args: &[Value], rng: &mut Rng) -> Result<Value, RuntimeError> {
+    let mut list = list_arg(builtin, &args[0])?.to_vec();
+    for i in (1..list.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        list.swap(i, j);
+    }
+    Ok(Value::list(list))
+}
+
+fn choice(builtin: &str, args: &[Value], rng: &mut Rng) -> Result<Value, RuntimeError> {
+    let list = list_arg(builtin, &args[0])?;
+    if list.is_empty() {
+        return Ok(Value::none());
+    }
+    let i = rng.below(list.len() as u64) as usize;
+    Ok(Value::some(list[i].clone()))
+}
+
+fn invalid_range(builtin: &str, low: impl ToString, high: impl ToString) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        builtin: builtin.to_string(),
+        expected: "range with low <= high",
+        found: format!("{} and {}", low.to_string(), high.to_string()),
+    }
+}
+
+fn seed(state: u64) -> Value {
+    let fields = BTreeMap::from([("state".to_string(), Value::Integer(state as i64))]);
+    Value::record(Some("Seed".to_string()), fields)
+}
+
+fn seed_arg(builtin: &str, value: &Value) -> Result<u64, RuntimeError> {
+    let record = record_arg(builtin, value)?;
+    match (record.type_name.as_deref(), record.get("state")) {
+        (Some("Seed"), Some(Value::Integer(state))) => Ok(*state as u64),
+        _ => Err(RuntimeError::InvalidArgument {
+            builtin: builtin.to_string(),
+            expected: "Seed",
+            found: value.type_name().to_string(),
+        }),
+    }
+}
+
+// The keys of hash maps are random, so they are a source of entropy without more dependencies
+fn entropy() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// SplitMix64, after Steele, Lea and Flood, "Fast Splittable Pseudorandom Number Generators"
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number below `range`, where 0 stands for 2^64. Numbers from the last, incomplete copy
+    // of the range are rejected, so that all numbers are equally likely.
+    fn below(&mut self, range: u64) -> u64 {
+        if range == 0 {
+            return self.next();
+        }
+        let zone = u64::MAX - u64::MAX % range;
+        loop {
+            let n = self.next();
+            if n < zone {
+                return n % range;
+            }
+        }
+    }
+}
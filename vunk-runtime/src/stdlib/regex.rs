@@ -0,0 +1,208 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Regex`: Regular expressions
+//!
+//! `compile pattern` returns a `Regex`, or an error message if the pattern is not valid. The
+//! syntax is the one of the `regex` crate, which guarantees matching in linear time. Strings
+//! are written without escapes, so `"\d+"` is a pattern for digits.
+//!
+//! Compiled patterns are cached, so compiling the same pattern again, like in a function that
+//! is called in a loop, only looks it up. The cache holds at most [`CACHE_SIZE`] patterns and
+//! starts over when it is full.
+//!
+//! Matches are records `{ text, start, end }`, with the offsets counted in unicode scalar
+//! values like `Std.String.length` does. `captures regex s` returns a record with a field for
+//! every named group of the regex, which is `None` if the group did not take part in the
+//! match, and `groups regex s` all groups by their index, where `0` is the whole match:
+//!
+//! ```text
+//! date = Std.Regex.compile "(?P<year>\d{4})-(?P<month>\d{2})"
+//! Std.Regex.captures date "2024-05"  # Some { month = Some "05", year = Some "2024" }
+//! ```
+//!
+//! In the replacement of `replace` and `replaceAll`, `$1` or `${name}` stand for the text of
+//! a group, and `$$` for a `$`.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use regex::Captures;
+use regex::Match;
+use regex::Regex;
+
+use crate::builtin::regex_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::value::Value;
+
+/// The number of compiled patterns that are cached
+pub const CACHE_SIZE: usize = 256;
+
+// A `Regex` shares its compiled program with its clones
+static CACHE: Mutex<BTreeMap<String, Regex>> = Mutex::new(BTreeMap::new());
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Regex.compile", 1, |_, args| {
+        let pattern = str_arg("Std.Regex.compile", &args[0])?;
+        Ok(match compile(&pattern) {
+            Ok(regex) => Value::ok(Value::regex(regex)),
+            Err(error) => Value::err(Value::string(error.to_string())),
+        })
+    });
+
+    builtins.register("Std.Regex.pattern", 1, |_, args| {
+        let regex = regex_arg("Std.Regex.pattern", &args[0])?;
+        Ok(Value::string(regex.as_str()))
+    });
+
+    // Escape all characters of a string that have a meaning in patterns
+    builtins.register("Std.Regex.escape", 1, |_, args| {
+        let s = str_arg("Std.Regex.escape", &args[0])?;
+        Ok(Value::string(regex::escape(&s)))
+    });
+
+    builtins.register("Std.Regex.isMatch", 2, |_, args| {
+        let regex = regex_arg("Std.Regex.isMatch", &args[0])?;
+        let s = str_arg("Std.Regex.isMatch", &args[1])?;
+        Ok(Value::Bool(regex.is_match(&s)))
+    });
+
+    builtins.register("Std.Regex.find", 2, |_, args| {
+        let regex = regex_arg("Std.Regex.find", &args[0])?;
+        let s = str_arg("Std.Regex.find", &args[1])?;
+        let mut offsets = Offsets::new(&s);
+        Ok(option(regex.find(&s).map(|m| offsets.record(m))))
+    });
+
+    builtins.register("Std.Regex.findAll", 2, |_, args| {
+        let regex = regex_arg("Std.Regex.findAll", &args[0])?;
+        let s = str_arg("Std.Regex.findAll", &args[1])?;
+        let mut offsets = Offsets::new(&s);
+        Ok(Value::list(
+            regex.find_iter(&s).map(|m| offsets.record(m)).collect(),
+        ))
+    });
+
+    builtins.register("Std.Regex.captures", 2, |_, args| {
+        let regex = regex_arg("Std.Regex.captures", &args[0])?;
+        let s = str_arg("Std.Regex.captures", &args[1])?;
+        Ok(option(regex.captures(&s).map(|c| named(&regex, &c))))
+    });
+
+    builtins.register("Std.Regex.capturesAll", 2, |_, args| {
+        let regex = regex_arg("Std.Regex.capturesAll", &args[0])?;
+        let s = str_arg("Std.Regex.capturesAll", &args[1])?;
+        Ok(Value::list(
+            regex.captures_iter(&s).map(|c| named(&regex, &c)).collect(),
+        ))
+    });
+
+    builtins.register("Std.Regex.groups", 2, |_, args| {
+        let regex = regex_arg("Std.Regex.groups", &args[0])?;
+        let s = str_arg("Std.Regex.groups", &args[1])?;
+        Ok(option(regex.captures(&s).map(|captures| {
+            Value::list(captures.iter().map(text).collect())
+        })))
+    });
+
+    // Replace the first match
+    builtins.register("Std.Regex.replace", 3, |_, args| {
+        let regex = regex_arg("Std.Regex.replace", &args[0])?;
+        let replacement = str_arg("Std.Regex.replace", &args[1])?;
+        let s = str_arg("Std.Regex.replace", &args[2])?;
+        Ok(Value::string(regex.replace(&s, replacement.as_str())))
+    });
+
+    builtins.register("Std.Regex.replaceAll", 3, |_, args| {
+        let regex = regex_arg("Std.Regex.replaceAll", &args[0])?;
+        let replacement = str_arg("Std.Regex.replaceAll", &args[1])?;
+        let s = str_arg("Std.Regex.replaceAll", &args[2])?;
+        Ok(Value::string(regex.replace_all(&s, replacement.as_str())))
+    });
+
+    builtins.register("Std.Regex.split", 2, |_, args| {
+        let regex = regex_arg("Std.Regex.split", &args[0])?;
+        let s = str_arg("Std.Regex.split", &args[1])?;
+        Ok(Value::list(regex.split(&s).map(Value::string).collect()))
+    });
+}
+
+/// Compile a pattern, or look it up if it was compiled before
+pub fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    if let Some(regex) = cache().get(pattern) {
+        return Ok(regex.clone());
+    }
+
+    // Other threads can use the cache while the pattern is compiled
+    let regex = Regex::new(pattern)?;
+    let mut cache = cache();
+    if cache.len() >= CACHE_SIZE {
+        cache.clear();
+    }
+    cache.insert(pattern.to_string(), regex.clone());
+    Ok(regex)
+}
+
+// The cache only ever holds compiled patterns, so it is fine to use it after a panic
+fn cache() -> std::sync::MutexGuard<'static, BTreeMap<String, Regex>> {
+    CACHE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn option(value: Option<Value>) -> Value {
+    value.map(Value::some).unwrap_or_else(Value::none)
+}
+
+fn text(group: Option<Match>) -> Value {
+    option(group.map(|group| Value::string(group.as_str())))
+}
+
+// The named groups of a match as a record
+fn named(regex: &Regex, captures: &Captures) -> Value {
+    let fields = regex
+        .capture_names()
+        .flatten()
+        .map(|name| (name.to_string(), text(captures.name(name))))
+        .collect();
+    Value::record(None, fields)
+}
+
+/// Converts byte offsets into a string to offsets in unicode scalar values
+///
+/// The matches of a regex come in order, so each one is only counted from the previous one.
+struct Offsets<'a> {
+    s: &'a str,
+    byte: usize,
+    char: usize,
+}
+
+impl<'a> Offsets<'a> {
+    fn new(s: &'a str) -> Self {
+        Offsets {
+            s,
+            byte: 0,
+            char: 0,
+        }
+    }
+
+    fn at(&mut self, byte: usize) -> i64 {
+        if byte < self.byte {
+            self.byte = 0;
+            self.char = 0;
+        }
+        self.char += self.s[self.byte..byte].chars().count();
+        self.byte = byte;
+        self.char as i64
+    }
+
+    fn record(&mut self, m: Match) -> Value {
+        let fields = BTreeMap::from([
+            ("text".to_string(), Value::string(m.as_str())),
+            ("start".to_string(), Value::Integer(self.at(m.start()))),
+            ("end".to_string(), Value::Integer(self.at(m.end()))),
+        ]);
+        Value::record(None, fields)
+    }
+}
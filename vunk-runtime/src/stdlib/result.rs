@@ -0,0 +1,75 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Result`: The result of a computation that can fail, either `Ok A` or `Err E`
+//!
+//! The constructors are part of the prelude.
+
+use crate::builtin::variant_arg;
+use crate::builtin::Builtins;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Result.Ok", 1, |_, args| Ok(Value::ok(args[0].clone())));
+    builtins.register("Std.Result.Err", 1, |_, args| {
+        Ok(Value::err(args[0].clone()))
+    });
+    builtins.add_to_prelude("Ok", "Std.Result.Ok");
+    builtins.add_to_prelude("Err", "Std.Result.Err");
+
+    builtins.register("Std.Result.isOk", 1, |_, args| {
+        let result = variant_arg("Std.Result.isOk", &args[0], "Result")?;
+        Ok(Value::Bool(result.name == "Ok"))
+    });
+
+    builtins.register("Std.Result.isErr", 1, |_, args| {
+        let result = variant_arg("Std.Result.isErr", &args[0], "Result")?;
+        Ok(Value::Bool(result.name == "Err"))
+    });
+
+    builtins.register("Std.Result.map", 2, |ctx, args| {
+        let result = variant_arg("Std.Result.map", &args[1], "Result")?;
+        if result.name == "Ok" {
+            ctx.call(&args[0], result.members.clone()).map(Value::ok)
+        } else {
+            Ok(args[1].clone())
+        }
+    });
+
+    builtins.register("Std.Result.mapErr", 2, |ctx, args| {
+        let result = variant_arg("Std.Result.mapErr", &args[1], "Result")?;
+        if result.name == "Err" {
+            ctx.call(&args[0], result.members.clone()).map(Value::err)
+        } else {
+            Ok(args[1].clone())
+        }
+    });
+
+    builtins.register("Std.Result.andThen", 2, |ctx, args| {
+        let result = variant_arg("Std.Result.andThen", &args[1], "Result")?;
+        if result.name == "Ok" {
+            ctx.call(&args[0], result.members.clone())
+        } else {
+            Ok(args[1].clone())
+        }
+    });
+
+    builtins.register("Std.Result.unwrapOr", 2, |_, args| {
+        let result = variant_arg("Std.Result.unwrapOr", &args[1], "Result")?;
+        if result.name == "Ok" {
+            Ok(result.members[0].clone())
+        } else {
+            Ok(args[0].clone())
+        }
+    });
+
+    builtins.register("Std.Result.ok", 1, |_, args| {
+        let result = variant_arg("Std.Result.ok", &args[0], "Result")?;
+        if result.name == "Ok" {
+            Ok(Value::some(result.members[0].clone()))
+        } else {
+            Ok(Value::none())
+        }
+    });
+}
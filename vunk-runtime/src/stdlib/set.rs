@@ -0,0 +1,81 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Set`: Persistent sets
+
+use crate::builtin::list_arg;
+use crate::builtin::set_arg;
+use crate::builtin::Builtins;
+use crate::collection::Key;
+use crate::collection::Set;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.Set.empty", 0, |_, _| Ok(Value::set(Set::default())));
+
+    builtins.register("Std.Set.fromList", 1, |_, args| {
+        let list = list_arg("Std.Set.fromList", &args[0])?;
+        list.iter()
+            .map(Key::from_value)
+            .collect::<Result<im::OrdSet<_>, _>>()
+            .map(|set| Value::set(Set(set)))
+    });
+
+    builtins.register("Std.Set.insert", 2, |_, args| {
+        let key = Key::from_value(&args[0])?;
+        let set = set_arg("Std.Set.insert", &args[1])?;
+        Ok(Value::set(Set(set.0.update(key))))
+    });
+
+    builtins.register("Std.Set.remove", 2, |_, args| {
+        let key = Key::from_value(&args[0])?;
+        let set = set_arg("Std.Set.remove", &args[1])?;
+        Ok(Value::set(Set(set.0.without(&key))))
+    });
+
+    builtins.register("Std.Set.member", 2, |_, args| {
+        let key = Key::from_value(&args[0])?;
+        let set = set_arg("Std.Set.member", &args[1])?;
+        Ok(Value::Bool(set.0.contains(&key)))
+    });
+
+    builtins.register("Std.Set.size", 1, |_, args| {
+        let set = set_arg("Std.Set.size", &args[0])?;
+        Ok(Value::Integer(set.0.len() as i64))
+    });
+
+    builtins.register("Std.Set.union", 2, |_, args| {
+        let a = set_arg("Std.Set.union", &args[0])?;
+        let b = set_arg("Std.Set.union", &args[1])?;
+        Ok(Value::set(Set(a.0.clone().union(b.0.clone()))))
+    });
+
+    builtins.register("Std.Set.intersection", 2, |_, args| {
+        let a = set_arg("Std.Set.intersection", &args[0])?;
+        let b = set_arg("Std.Set.intersection", &args[1])?;
+        Ok(Value::set(Set(a.0.clone().intersection(b.0.clone()))))
+    });
+
+    builtins.register("Std.Set.difference", 2, |_, args| {
+        let a = set_arg("Std.Set.difference", &args[0])?;
+        let b = set_arg("Std.Set.difference", &args[1])?;
+        Ok(Value::set(Set(a
+            .0
+            .clone()
+            .relative_complement(b.0.clone()))))
+    });
+
+    builtins.register("Std.Set.toList", 1, |_, args| {
+        let set = set_arg("Std.Set.toList", &args[0])?;
+        Ok(Value::list(set.0.iter().map(Key::to_value).collect()))
+    });
+
+    // Folds in ascending order, calling the function with the accumulator and the element
+    builtins.register("Std.Set.fold", 3, |ctx, args| {
+        let set = set_arg("Std.Set.fold", &args[2])?;
+        set.0.iter().try_fold(args[1].clone(), |acc, key| {
+            ctx.call(&args[0], vec![acc, key.to_value()])
+        })
+    });
+}
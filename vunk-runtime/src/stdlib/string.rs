@@ -0,0 +1,128 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.String`: Functions on strings
+//!
+//! Strings are sequences of unicode scalar values, so `length` and `chars` count those and not
+//! bytes.
+//!
+//! `Std.String.Builder` builds a string from many pieces in linear time, see [`crate::builder`]:
+//! `push piece builder` returns a builder with the piece appended, and `build builder` the
+//! string. Chains of `++` on strings are joined at once already, see
+//! `vunk_parser::desugar::desugar_join`.
+
+use std::sync::Arc;
+
+use crate::builder::Builder;
+use crate::builtin::builder_arg;
+use crate::builtin::list_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::float;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    builtins.register("Std.String.length", 1, |_, args| {
+        let s = str_arg("Std.String.length", &args[0])?;
+        Ok(Value::Integer(s.chars().count() as i64))
+    });
+
+    builtins.register("Std.String.split", 2, |_, args| {
+        let separator = str_arg("Std.String.split", &args[0])?;
+        let s = str_arg("Std.String.split", &args[1])?;
+        Ok(Value::list(
+            s.split(separator.as_str()).map(Value::string).collect(),
+        ))
+    });
+
+    builtins.register("Std.String.join", 2, |_, args| {
+        let separator = str_arg("Std.String.join", &args[0])?;
+        let list = list_arg("Std.String.join", &args[1])?;
+        let parts = list
+            .iter()
+            .map(|part| str_arg("Std.String.join", part))
+            .collect::<Result<Vec<_>, _>>()?;
+        let parts = parts.iter().map(|part| part.as_str()).collect::<Vec<_>>();
+        Ok(Value::string(parts.join(separator.as_str())))
+    });
+
+    builtins.register("Std.String.concat", 1, |_, args| {
+        let list = list_arg("Std.String.concat", &args[0])?;
+        let mut concatenated = String::new();
+        for part in list.iter() {
+            concatenated.push_str(&str_arg("Std.String.concat", part)?);
+        }
+        Ok(Value::string(concatenated))
+    });
+
+    builtins.register("Std.String.trim", 1, |_, args| {
+        let s = str_arg("Std.String.trim", &args[0])?;
+        Ok(Value::string(s.trim()))
+    });
+
+    builtins.register("Std.String.toUpper", 1, |_, args| {
+        let s = str_arg("Std.String.toUpper", &args[0])?;
+        Ok(Value::string(s.to_uppercase()))
+    });
+
+    builtins.register("Std.String.toLower", 1, |_, args| {
+        let s = str_arg("Std.String.toLower", &args[0])?;
+        Ok(Value::string(s.to_lowercase()))
+    });
+
+    builtins.register("Std.String.contains", 2, |_, args| {
+        let needle = str_arg("Std.String.contains", &args[0])?;
+        let s = str_arg("Std.String.contains", &args[1])?;
+        Ok(Value::Bool(s.contains(needle.as_str())))
+    });
+
+    builtins.register("Std.String.replace", 3, |_, args| {
+        let from = str_arg("Std.String.replace", &args[0])?;
+        let to = str_arg("Std.String.replace", &args[1])?;
+        let s = str_arg("Std.String.replace", &args[2])?;
+        Ok(Value::string(s.replace(from.as_str(), to.as_str())))
+    });
+
+    builtins.register("Std.String.chars", 1, |_, args| {
+        let s = str_arg("Std.String.chars", &args[0])?;
+        Ok(Value::list(
+            s.chars().map(|c| Value::string(c.to_string())).collect(),
+        ))
+    });
+
+    builtins.register("Std.String.parseInt", 1, |_, args| {
+        let s = str_arg("Std.String.parseInt", &args[0])?;
+        Ok(s.trim()
+            .parse::<i64>()
+            .map(Value::Integer)
+            .map(Value::some)
+            .unwrap_or_else(|_| Value::none()))
+    });
+
+    builtins.register("Std.String.parseFloat", 1, |_, args| {
+        let s = str_arg("Std.String.parseFloat", &args[0])?;
+        Ok(float::parse(s.trim())
+            .map(Value::Float)
+            .map(Value::some)
+            .unwrap_or_else(Value::none))
+    });
+
+    builtins.register("Std.String.Builder.new", 0, |_, _| {
+        Ok(Value::builder(Builder::default()))
+    });
+
+    builtins.register("Std.String.Builder.push", 2, |_, args| {
+        let piece = str_arg("Std.String.Builder.push", &args[0])?;
+        let mut builder = builder_arg("Std.String.Builder.push", &args[1])?;
+        // Without the arguments, the builder is not copied if it is not shared otherwise
+        drop(args);
+        Arc::make_mut(&mut builder).push(piece);
+        Ok(Value::Builder(builder))
+    });
+
+    builtins.register("Std.String.Builder.build", 1, |_, args| {
+        let builder = builder_arg("Std.String.Builder.build", &args[0])?;
+        Ok(Value::string(builder.build()))
+    });
+}
@@ -0,0 +1,62 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Task`: Running IO actions concurrently
+//!
+//! See [`crate::task`] for how tasks are scheduled.
+
+use crate::builtin::channel_arg;
+use crate::builtin::io_arg;
+use crate::builtin::task_arg;
+use crate::builtin::Builtins;
+use crate::heap::Obj;
+use crate::io::Io;
+use crate::task::spawn;
+use crate::task::Channel;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    // Start running an action concurrently, returning a handle to join or cancel it
+    builtins.register("Std.Task.spawn", 1, |_, args| {
+        let io = io_arg("Std.Task.spawn", &args[0])?;
+        Ok(Value::io(Io::new(move |ctx| {
+            Ok(Value::Task(spawn(ctx, io.clone())))
+        })))
+    });
+
+    // Wait for a task and return its result, failing if the task failed
+    builtins.register("Std.Task.join", 1, |_, args| {
+        let task = task_arg("Std.Task.join", &args[0])?;
+        Ok(Value::io(Io::new(move |ctx| task.join(ctx))))
+    });
+
+    builtins.register("Std.Task.cancel", 1, |_, args| {
+        let task = task_arg("Std.Task.cancel", &args[0])?;
+        Ok(Value::io(Io::new(move |_| {
+            task.cancel();
+            Ok(Value::Unit)
+        })))
+    });
+
+    builtins.register("Std.Task.channel", 0, |_, _| {
+        Ok(Value::io(Io::new(|_| {
+            Ok(Value::Channel(Obj::alloc(Channel::default())))
+        })))
+    });
+
+    builtins.register("Std.Task.send", 2, |_, args| {
+        let channel = channel_arg("Std.Task.send", &args[0])?;
+        let value = args[1].clone();
+        Ok(Value::io(Io::new(move |_| {
+            channel.send(value.clone());
+            Ok(Value::Unit)
+        })))
+    });
+
+    // Wait for a message on a channel
+    builtins.register("Std.Task.receive", 1, |_, args| {
+        let channel = channel_arg("Std.Task.receive", &args[0])?;
+        Ok(Value::io(Io::new(move |ctx| channel.receive(ctx))))
+    });
+}
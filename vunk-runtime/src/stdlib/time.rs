@@ -0,0 +1,465 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Time`: Clocks, timestamps and durations
+//!
+//! Times are of three distinct types, so they cannot be mixed up with each other or with plain
+//! numbers:
+//!
+//! - `Duration`, a span of time, made with `Std.Time.seconds 2` or `Std.Time.milliseconds 500`
+//!   and read with `Std.Time.Duration.toMilliseconds` and the like, so the unit is always
+//!   spelled out. Durations are added, subtracted and scaled with the functions of
+//!   `Std.Time.Duration`.
+//! - `Instant`, a reading of the monotonic clock, `Std.Time.Instant.now`. Instants only mean
+//!   something relative to each other: `Std.Time.Instant.between start end` is the `Duration`
+//!   from one to the other, which never goes backwards.
+//! - `Timestamp`, a point in time on the wall clock, `Std.Time.now`, in UTC. Timestamps are
+//!   written and read in the format of RFC 3339, like `"2024-05-01T12:30:00.25Z"`, by `format`
+//!   and `parse` of `Std.Time.Timestamp`. Parsing accepts any offset from UTC, but leap
+//!   seconds are not supported.
+//!
+//! All three count nanoseconds in an `Int`, which is enough for about 292 years in either
+//! direction; timestamps count them from 1970-01-01 UTC. Arithmetic that goes beyond fails with
+//! [`RuntimeError::IntegerOverflow`].
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use crate::builtin::int_arg;
+use crate::builtin::record_arg;
+use crate::builtin::str_arg;
+use crate::builtin::Builtins;
+use crate::error::RuntimeError;
+use crate::io::Io;
+use crate::value::Value;
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The units durations are made of and converted to, with their length in nanoseconds
+const UNITS: [(&str, &str, i64); 6] = [
+    ("Std.Time.nanoseconds", "Std.Time.Duration.toNanoseconds", 1),
+    (
+        "Std.Time.microseconds",
+        "Std.Time.Duration.toMicroseconds",
+        1_000,
+    ),
+    (
+        "Std.Time.milliseconds",
+        "Std.Time.Duration.toMilliseconds",
+        1_000_000,
+    ),
+    (
+        "Std.Time.seconds",
+        "Std.Time.Duration.toSeconds",
+        NANOS_PER_SECOND,
+    ),
+    (
+        "Std.Time.minutes",
+        "Std.Time.Duration.toMinutes",
+        60 * NANOS_PER_SECOND,
+    ),
+    (
+        "Std.Time.hours",
+        "Std.Time.Duration.toHours",
+        60 * 60 * NANOS_PER_SECOND,
+    ),
+];
+
+// The monotonic clock counts from the first time it is read
+static ORIGIN: Mutex<Option<Instant>> = Mutex::new(None);
+
+pub fn register(builtins: &mut Builtins) {
+    for (name, to_unit, nanos) in UNITS {
+        builtins.register(name, 1, move |_, args| {
+            let n = int_arg(name, &args[0])?;
+            checked("Duration", name, n.checked_mul(nanos))
+        });
+
+        // Truncates towards zero, like `/`
+        builtins.register(to_unit, 1, move |_, args| {
+            let duration = nanos_arg(to_unit, &args[0], "Duration")?;
+            Ok(Value::Integer(duration / nanos))
+        });
+    }
+
+    builtins.register("Std.Time.Duration.zero", 0, |_, _| Ok(time("Duration", 0)));
+
+    builtins.register("Std.Time.Duration.add", 2, |_, args| {
+        let a = nanos_arg("Std.Time.Duration.add", &args[0], "Duration")?;
+        let b = nanos_arg("Std.Time.Duration.add", &args[1], "Duration")?;
+        checked("Duration", "Std.Time.Duration.add", a.checked_add(b))
+    });
+
+    // `sub a b` is `a - b`
+    builtins.register("Std.Time.Duration.sub", 2, |_, args| {
+        let a = nanos_arg("Std.Time.Duration.sub", &args[0], "Duration")?;
+        let b = nanos_arg("Std.Time.Duration.sub", &args[1], "Duration")?;
+        checked("Duration", "Std.Time.Duration.sub", a.checked_sub(b))
+    });
+
+    builtins.register("Std.Time.Duration.scale", 2, |_, args| {
+        let factor = int_arg("Std.Time.Duration.scale", &args[0])?;
+        let duration = nanos_arg("Std.Time.Duration.scale", &args[1], "Duration")?;
+        checked(
+            "Duration",
+            "Std.Time.Duration.scale",
+            duration.checked_mul(factor),
+        )
+    });
+
+    builtins.register("Std.Time.Duration.negate", 1, |_, args| {
+        let duration = nanos_arg("Std.Time.Duration.negate", &args[0], "Duration")?;
+        checked(
+            "Duration",
+            "Std.Time.Duration.negate",
+            duration.checked_neg(),
+        )
+    });
+
+    builtins.register("Std.Time.Instant.now", 0, |_, _| {
+        Ok(Value::io(Io::new(|_| {
+            let now = Instant::now();
+            let origin = *ORIGIN
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get_or_insert(now);
+            let nanos = i64::try_from(now.duration_since(origin).as_nanos()).map_err(|_| {
+                RuntimeError::IntegerOverflow {
+                    op: "Std.Time.Instant.now",
+                }
+            })?;
+            Ok(time("Instant", nanos))
+        })))
+    });
+
+    // The duration from the first instant to the second
+    builtins.register("Std.Time.Instant.between", 2, |_, args| {
+        let start = nanos_arg("Std.Time.Instant.between", &args[0], "Instant")?;
+        let end = nanos_arg("Std.Time.Instant.between", &args[1], "Instant")?;
+        checked(
+            "Duration",
+            "Std.Time.Instant.between",
+            end.checked_sub(start),
+        )
+    });
+
+    builtins.register("Std.Time.Instant.add", 2, |_, args| {
+        let duration = nanos_arg("Std.Time.Instant.add", &args[0], "Duration")?;
+        let instant = nanos_arg("Std.Time.Instant.add", &args[1], "Instant")?;
+        checked(
+            "Instant",
+            "Std.Time.Instant.add",
+            instant.checked_add(duration),
+        )
+    });
+
+    builtins.register("Std.Time.now", 0, |_, _| {
+        Ok(Value::io(Io::new(|_| {
+            // The clock may be set to before 1970
+            let nanos = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+                Ok(since) => i64::try_from(since.as_nanos()).ok(),
+                Err(before) => i64::try_from(before.duration().as_nanos()).ok().map(|n| -n),
+            };
+            let nanos = nanos.ok_or(RuntimeError::IntegerOverflow { op: "Std.Time.now" })?;
+            Ok(time("Timestamp", nanos))
+        })))
+    });
+
+    // The duration from the first timestamp to the second
+    builtins.register("Std.Time.Timestamp.between", 2, |_, args| {
+        let start = nanos_arg("Std.Time.Timestamp.between", &args[0], "Timestamp")?;
+        let end = nanos_arg("Std.Time.Timestamp.between", &args[1], "Timestamp")?;
+        checked(
+            "Duration",
+            "Std.Time.Timestamp.between",
+            end.checked_sub(start),
+        )
+    });
+
+    builtins.register("Std.Time.Timestamp.add", 2, |_, args| {
+        let duration = nanos_arg("Std.Time.Timestamp.add", &args[0], "Duration")?;
+        let timestamp = nanos_arg("Std.Time.Timestamp.add", &args[1], "Timestamp")?;
+        checked(
+            "Timestamp",
+            "Std.Time.Timestamp.add",
+            timestamp.checked_add(duration),
+        )
+    });
+
+    // The timestamp that is the duration after 1970-01-01 UTC
+    builtins.register("Std.Time.Timestamp.fromUnix", 1, |_, args| {
+        let duration = nanos_arg("Std.Time.Timestamp.fromUnix", &args[0], "Duration")?;
+        Ok(time("Timestamp", duration))
+    });
+
+    builtins.register("Std.Time.Timestamp.toUnix", 1, |_, args| {
+        let timestamp = nanos_arg("Std.Time.Timestamp.toUnix", &args[0], "Timestamp")?;
+        Ok(time("Duration", timestamp))
+    });
+
+    builtins.register("Std.Time.Timestamp.format", 1, |_, args| {
+        let timestamp = nanos_arg("Std.Time.Timestamp.format", &args[0], "Timestamp")?;
+        Ok(Value::string(format(timestamp)))
+    });
+
+    builtins.register("Std.Time.Timestamp.parse", 1, |_, args| {
+        let s = str_arg("Std.Time.Timestamp.parse", &args[0])?;
+        Ok(parse(&s)
+            .map(|nanos| Value::some(time("Timestamp", nanos)))
+            .unwrap_or_else(Value::none))
+    });
+
+    // The date and time of day of a timestamp in UTC, as a record of `Int`s
+    builtins.register("Std.Time.Timestamp.toDate", 1, |_, args| {
+        let timestamp = nanos_arg("Std.Time.Timestamp.toDate", &args[0], "Timestamp")?;
+        let date = Date::from_nanos(timestamp);
+        let fields = [
+            ("year", date.year),
+            ("month", date.month),
+            ("day", date.day),
+            ("hour", date.hour),
+            ("minute", date.minute),
+            ("second", date.second),
+            ("nanosecond", date.nanosecond),
+        ];
+        Ok(Value::record(
+            None,
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), Value::Integer(value)))
+                .collect(),
+        ))
+    });
+}
+
+fn time(type_name: &str, nanos: i64) -> Value {
+    let fields = BTreeMap::from([("nanos".to_string(), Value::Integer(nanos))]);
+    Value::record(Some(type_name.to_string()), fields)
+}
+
+fn checked(type_name: &str, op: &'static str, nanos: Option<i64>) -> Result<Value, RuntimeError> {
+    nanos
+        .map(|nanos| time(type_name, nanos))
+        .ok_or(RuntimeError::IntegerOverflow { op })
+}
+
+// The nanoseconds of a `Duration`, `Instant` or `Timestamp`, which has to be of the given type
+fn nanos_arg(builtin: &str, value: &Value, type_name: &'static str) -> Result<i64, RuntimeError> {
+    let record = record_arg(builtin, value)?;
+    match (record.type_name.as_deref(), record.get("nanos")) {
+        (Some(name), Some(Value::Integer(nanos))) if name == type_name => Ok(*nanos),
+        _ => Err(RuntimeError::InvalidArgument {
+            builtin: builtin.to_string(),
+            expected: type_name,
+            found: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// A point in time as a date and time of day in UTC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Date {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    nanosecond: i64,
+}
+
+impl Date {
+    fn from_nanos(nanos: i64) -> Date {
+        let seconds = nanos.div_euclid(NANOS_PER_SECOND);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+        let time = seconds.rem_euclid(SECONDS_PER_DAY);
+        Date {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+            nanosecond: nanos.rem_euclid(NANOS_PER_SECOND),
+        }
+    }
+
+    fn to_nanos(self) -> Option<i64> {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds = days * SECONDS_PER_DAY + self.hour * 3600 + self.minute * 60 + self.second;
+        seconds
+            .checked_mul(NANOS_PER_SECOND)?
+            .checked_add(self.nanosecond)
+    }
+}
+
+// Written like `2024-05-01T12:30:00.25Z`, with as many digits of the fraction as needed
+fn format(nanos: i64) -> String {
+    let date = Date::from_nanos(nanos);
+    let mut formatted = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        date.year, date.month, date.day, date.hour, date.minute, date.second
+    );
+    if date.nanosecond > 0 {
+        let fraction = format!("{:09}", date.nanosecond);
+        formatted.push('.');
+        formatted.push_str(fraction.trim_end_matches('0'));
+    }
+    formatted.push('Z');
+    formatted
+}
+
+// `date-time` of RFC 3339, with a `T`, `t` or space between date and time
+fn parse(s: &str) -> Option<i64> {
+    let mut input = Input(s.as_bytes());
+    let year = input.number(4)?;
+    input.expect(b"-")?;
+    let month = input.number(2)?;
+    input.expect(b"-")?;
+    let day = input.number(2)?;
+    input.expect(b"Tt ")?;
+    let hour = input.number(2)?;
+    input.expect(b":")?;
+    let minute = input.number(2)?;
+    input.expect(b":")?;
+    let second = input.number(2)?;
+
+    // Digits beyond nanoseconds are cut off
+    let mut nanosecond = 0;
+    if input.expect(b".").is_some() {
+        let digits = input.digits();
+        if digits.is_empty() {
+            return None;
+        }
+        for i in 0..9 {
+            nanosecond = nanosecond * 10 + digits.get(i).map_or(0, |d| i64::from(d - b'0'));
+        }
+    }
+
+    let offset = match input.next()? {
+        b'Z' | b'z' => 0,
+        sign @ (b'+' | b'-') => {
+            let hours = input.number(2)?;
+            input.expect(b":")?;
+            let minutes = input.number(2)?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = (hours * 60 + minutes) * 60 * NANOS_PER_SECOND;
+            if sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return None,
+    };
+    if !input.0.is_empty() {
+        return None;
+    }
+
+    let valid = (1..=12).contains(&month)
+        && (1..=days_in_month(year, month)).contains(&day)
+        && hour < 24
+        && minute < 60
+        && second < 60;
+    if !valid {
+        return None;
+    }
+    let date = Date {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        nanosecond,
+    };
+    date.to_nanos()?.checked_sub(offset)
+}
+
+struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+    fn next(&mut self) -> Option<u8> {
+        let (first, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(*first)
+    }
+
+    // One of the given characters
+    fn expect(&mut self, any_of: &[u8]) -> Option<()> {
+        match self.0.first() {
+            Some(c) if any_of.contains(c) => {
+                self.0 = &self.0[1..];
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn digits(&mut self) -> &'a [u8] {
+        let len = self.0.iter().take_while(|c| c.is_ascii_digit()).count();
+        let (digits, rest) = self.0.split_at(len);
+        self.0 = rest;
+        digits
+    }
+
+    // A number of exactly `len` digits
+    fn number(&mut self, len: usize) -> Option<i64> {
+        let digits = self.0.get(..len)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        self.0 = &self.0[len..];
+        Some(digits.iter().fold(0, |n, d| n * 10 + i64::from(d - b'0')))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The days since 1970-01-01 of a date of the proleptic Gregorian calendar, after Howard
+// Hinnant's `days_from_civil`, which counts in eras of 400 years that start on March 1st
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// The inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
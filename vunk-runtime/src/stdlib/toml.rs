@@ -0,0 +1,130 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Toml`: Parsing and serializing TOML
+//!
+//! TOML documents are represented by the `Json` type of [`Std.Json`](super::json), so the same
+//! functions work on configuration of either format. A document is always a `Json.Object`.
+//! Dates and times are parsed as `Json.String`s of their TOML representation.
+//!
+//! TOML has no `null`, so `encode` fails for a `Json.Null` anywhere in the document, as it does
+//! for a document that is not an object.
+
+use toml::value::Table;
+
+use crate::builtin::list_arg;
+use crate::builtin::map_arg;
+use crate::builtin::str_arg;
+use crate::builtin::variant_arg;
+use crate::builtin::Builtins;
+use crate::collection::Key;
+use crate::collection::Map;
+use crate::error::RuntimeError;
+use crate::stdlib::json::json;
+use crate::stdlib::json::MAX_DEPTH;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    // Parse a TOML document, returning an error message if it is not valid
+    builtins.register("Std.Toml.parse", 1, |_, args| {
+        let s = str_arg("Std.Toml.parse", &args[0])?;
+        Ok(match s.parse::<Table>() {
+            Ok(table) => Value::ok(decode(toml::Value::Table(table))),
+            Err(error) => Value::err(Value::string(error.to_string().trim_end())),
+        })
+    });
+
+    // Serialize to TOML, with nested objects as tables of their own
+    builtins.register("Std.Toml.encode", 1, |_, args| {
+        let table = match encode(&args[0], 0)? {
+            toml::Value::Table(table) => table,
+            _ => return Err(invalid("Json.Object", &args[0])),
+        };
+        toml::to_string(&table)
+            .map(Value::string)
+            .map_err(|error| RuntimeError::InvalidArgument {
+                builtin: "Std.Toml.encode".to_string(),
+                expected: "Json that can be written as TOML",
+                found: error.to_string(),
+            })
+    });
+}
+
+fn decode(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => json("String", vec![Value::string(s)]),
+        toml::Value::Integer(i) => json("Int", vec![Value::Integer(i)]),
+        toml::Value::Float(f) => json("Float", vec![Value::Float(f)]),
+        toml::Value::Boolean(b) => json("Bool", vec![Value::Bool(b)]),
+        toml::Value::Datetime(datetime) => {
+            json("String", vec![Value::string(datetime.to_string())])
+        }
+        toml::Value::Array(elements) => json(
+            "Array",
+            vec![Value::list(elements.into_iter().map(decode).collect())],
+        ),
+        toml::Value::Table(table) => {
+            let mut map = Map::default();
+            for (key, value) in table {
+                map.0.insert(Key::Str(key), decode(value));
+            }
+            json("Object", vec![Value::map(map)])
+        }
+    }
+}
+
+fn encode(value: &Value, depth: usize) -> Result<toml::Value, RuntimeError> {
+    if depth > MAX_DEPTH {
+        return Err(RuntimeError::InvalidArgument {
+            builtin: "Std.Toml.encode".to_string(),
+            expected: "Json nested at most 512 levels deep",
+            found: "Json".to_string(),
+        });
+    }
+
+    let json = variant_arg("Std.Toml.encode", value, "Json")?;
+    let member = || json.members[0].force();
+
+    Ok(match json.name.as_str() {
+        "Null" => return Err(invalid("Json without Null", value)),
+        "Bool" => match member()? {
+            Value::Bool(b) => toml::Value::Boolean(b),
+            other => return Err(invalid("Bool", &other)),
+        },
+        "Int" => match member()? {
+            Value::Integer(i) => toml::Value::Integer(i),
+            other => return Err(invalid("Int that fits into 64 bits", &other)),
+        },
+        "Float" => match member()? {
+            Value::Float(f) => toml::Value::Float(f),
+            other => return Err(invalid("Float", &other)),
+        },
+        "String" => toml::Value::String(str_arg("Std.Toml.encode", &json.members[0])?.to_string()),
+        "Array" => toml::Value::Array(
+            list_arg("Std.Toml.encode", &json.members[0])?
+                .iter()
+                .map(|element| encode(element, depth + 1))
+                .collect::<Result<_, _>>()?,
+        ),
+        "Object" => {
+            let mut table = Table::new();
+            for (key, element) in map_arg("Std.Toml.encode", &json.members[0])?.0.iter() {
+                match key {
+                    Key::Str(key) => table.insert(key.clone(), encode(element, depth + 1)?),
+                    _ => return Err(invalid("Map String Json", &key.to_value())),
+                };
+            }
+            toml::Value::Table(table)
+        }
+        _ => unreachable!("Json variant {}", json.name),
+    })
+}
+
+fn invalid(expected: &'static str, found: &Value) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        builtin: "Std.Toml.encode".to_string(),
+        expected,
+        found: found.type_name().to_string(),
+    }
+}
@@ -0,0 +1,145 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `Std.Yaml`: Parsing and serializing YAML
+//!
+//! YAML documents are represented by the `Json` type of [`Std.Json`](super::json), so the same
+//! functions work on configuration of either format. `parse` reads a single document, follows
+//! aliases and applies merge keys (`<<`). Tags are dropped, leaving the values they are on.
+//!
+//! Keys of mappings that are numbers or booleans become the strings they are written as; other
+//! keys that are not strings, like sequences, are an error. Integers that do not fit into an
+//! `Int` are parsed as `Json.Float`, as in JSON.
+
+use serde_yaml::Mapping;
+use serde_yaml::Number;
+
+use crate::builtin::list_arg;
+use crate::builtin::map_arg;
+use crate::builtin::str_arg;
+use crate::builtin::variant_arg;
+use crate::builtin::Builtins;
+use crate::collection::Key;
+use crate::collection::Map;
+use crate::error::RuntimeError;
+use crate::stdlib::json::json;
+use crate::stdlib::json::MAX_DEPTH;
+use crate::value::Value;
+
+pub fn register(builtins: &mut Builtins) {
+    // Parse a YAML document, returning an error message if it is not valid
+    builtins.register("Std.Yaml.parse", 1, |_, args| {
+        let s = str_arg("Std.Yaml.parse", &args[0])?;
+        let document = serde_yaml::from_str::<serde_yaml::Value>(&s).and_then(|mut document| {
+            document.apply_merge()?;
+            Ok(document)
+        });
+        Ok(match document.map_err(|e| e.to_string()).and_then(decode) {
+            Ok(value) => Value::ok(value),
+            Err(message) => Value::err(Value::string(message)),
+        })
+    });
+
+    // Serialize to YAML in block style
+    builtins.register("Std.Yaml.encode", 1, |_, args| {
+        let document = encode(&args[0], 0)?;
+        serde_yaml::to_string(&document)
+            .map(Value::string)
+            .map_err(|error| RuntimeError::InvalidArgument {
+                builtin: "Std.Yaml.encode".to_string(),
+                expected: "Json that can be written as YAML",
+                found: error.to_string(),
+            })
+    });
+}
+
+fn decode(value: serde_yaml::Value) -> Result<Value, String> {
+    Ok(match value {
+        serde_yaml::Value::Null => json("Null", Vec::new()),
+        serde_yaml::Value::Bool(b) => json("Bool", vec![Value::Bool(b)]),
+        serde_yaml::Value::Number(n) => match n.as_i64() {
+            Some(i) => json("Int", vec![Value::Integer(i)]),
+            None => json("Float", vec![Value::Float(n.as_f64().unwrap_or(f64::NAN))]),
+        },
+        serde_yaml::Value::String(s) => json("String", vec![Value::string(s)]),
+        serde_yaml::Value::Sequence(elements) => {
+            let elements = elements.into_iter().map(decode).collect::<Result<_, _>>()?;
+            json("Array", vec![Value::list(elements)])
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut map = Map::default();
+            for (key, value) in mapping {
+                let key = match key {
+                    serde_yaml::Value::String(s) => s,
+                    serde_yaml::Value::Bool(b) => b.to_string(),
+                    serde_yaml::Value::Number(n) => n.to_string(),
+                    _ => return Err("keys must be strings, numbers or booleans".to_string()),
+                };
+                map.0.insert(Key::Str(key), decode(value)?);
+            }
+            json("Object", vec![Value::map(map)])
+        }
+        serde_yaml::Value::Tagged(tagged) => decode(tagged.value)?,
+    })
+}
+
+fn encode(value: &Value, depth: usize) -> Result<serde_yaml::Value, RuntimeError> {
+    if depth > MAX_DEPTH {
+        return Err(RuntimeError::InvalidArgument {
+            builtin: "Std.Yaml.encode".to_string(),
+            expected: "Json nested at most 512 levels deep",
+            found: "Json".to_string(),
+        });
+    }
+
+    let json = variant_arg("Std.Yaml.encode", value, "Json")?;
+    let member = || json.members[0].force();
+
+    Ok(match json.name.as_str() {
+        "Null" => serde_yaml::Value::Null,
+        "Bool" => match member()? {
+            Value::Bool(b) => serde_yaml::Value::Bool(b),
+            other => return Err(invalid("Bool", &other)),
+        },
+        "Int" => match member()? {
+            Value::Integer(i) => serde_yaml::Value::Number(Number::from(i)),
+            other => return Err(invalid("Int that fits into 64 bits", &other)),
+        },
+        "Float" => match member()? {
+            Value::Float(f) => serde_yaml::Value::Number(Number::from(f)),
+            other => return Err(invalid("Float", &other)),
+        },
+        "String" => {
+            serde_yaml::Value::String(str_arg("Std.Yaml.encode", &json.members[0])?.to_string())
+        }
+        "Array" => serde_yaml::Value::Sequence(
+            list_arg("Std.Yaml.encode", &json.members[0])?
+                .iter()
+                .map(|element| encode(element, depth + 1))
+                .collect::<Result<_, _>>()?,
+        ),
+        "Object" => {
+            let mut mapping = Mapping::new();
+            for (key, element) in map_arg("Std.Yaml.encode", &json.members[0])?.0.iter() {
+                match key {
+                    Key::Str(key) => mapping.insert(
+                        serde_yaml::Value::String(key.clone()),
+                        encode(element, depth + 1)?,
+                    ),
+                    _ => return Err(invalid("Map String Json", &key.to_value())),
+                };
+            }
+            serde_yaml::Value::Mapping(mapping)
+        }
+        _ => unreachable!("Json variant {}", json.name),
+    })
+}
+
+fn invalid(expected: &'static str, found: &Value) -> RuntimeError {
+    RuntimeError::InvalidArgument {
+        builtin: "Std.Yaml.encode".to_string(),
+        expected,
+        found: found.type_name().to_string(),
+    }
+}
@@ -0,0 +1,357 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Tasks and channels
+//!
+//! A task runs an IO action concurrently to the task that spawned it. Its result is obtained by
+//! joining it. Tasks communicate through unbounded channels.
+//!
+//! How tasks are run depends on the [`Scheduling`] of the context they are spawned from:
+//!
+//! * With [`Scheduling::Threads`], tasks are queued for a pool of at most as many threads as
+//!   there are CPUs. A task waiting for another task or for a message runs queued tasks meanwhile,
+//!   so that tasks waiting for each other cannot take all threads of the pool.
+//! * With [`Scheduling::Deterministic`], tasks do not run until something waits for them: joining
+//!   a task runs it to completion on the current thread, receiving from an empty channel runs
+//!   pending tasks in the order they were spawned until there is a message. Thus, a program always
+//!   behaves the same, which is what tests want. If nothing can make progress anymore,
+//!   [`RuntimeError::Deadlock`] is returned instead of hanging.
+//!
+//! [`shutdown`] drops the tasks which never ran when a program ends.
+//!
+//! Cancelling a task is cooperative: the task fails with [`RuntimeError::Cancelled`] the next
+//! time it runs an IO action, or while it waits for a task or channel.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+use crate::error::RuntimeError;
+use crate::function::apply;
+use crate::function::Context;
+use crate::heap::HeapObject;
+use crate::heap::Obj;
+use crate::heap::ObjectKind;
+use crate::heap::Ref;
+use crate::io::Io;
+use crate::sandbox::Sandbox;
+use crate::value::Value;
+
+// How often waiting tasks check whether they were cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// How long a thread of the pool waits for a task before it exits
+const IDLE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheduling {
+    Threads,
+    Deterministic,
+}
+
+thread_local! {
+    // Tasks spawned with deterministic scheduling which did not run yet
+    static PENDING: RefCell<VecDeque<Ref<Task>>> = const { RefCell::new(VecDeque::new()) };
+}
+
+// Tasks spawned with thread scheduling which no thread took yet
+static POOL: Pool = Pool {
+    queue: Mutex::new(Queue {
+        tasks: VecDeque::new(),
+        threads: 0,
+    }),
+    queued: Condvar::new(),
+};
+
+struct Pool {
+    queue: Mutex<Queue>,
+    queued: Condvar,
+}
+
+// The threads are counted under the same lock as the tasks, so that no task is queued while the
+// last thread decides to exit
+struct Queue {
+    tasks: VecDeque<Ref<Task>>,
+    threads: usize,
+}
+
+impl Pool {
+    fn submit(&'static self, task: Ref<Task>) {
+        let mut queue = self.lock();
+        queue.tasks.push_back(task);
+        let threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        if queue.threads < threads {
+            queue.threads += 1;
+            std::thread::spawn(move || self.work());
+        }
+        drop(queue);
+        self.queued.notify_one();
+    }
+
+    fn work(&self) {
+        let mut queue = self.lock();
+        loop {
+            if let Some(task) = queue.tasks.pop_front() {
+                drop(queue);
+                task.run();
+                queue = self.lock();
+                continue;
+            }
+
+            let (next, timeout) = self
+                .queued
+                .wait_timeout(queue, IDLE_TIMEOUT)
+                .unwrap_or_else(PoisonError::into_inner);
+            queue = next;
+            if timeout.timed_out() && queue.tasks.is_empty() {
+                queue.threads -= 1;
+                return;
+            }
+        }
+    }
+
+    // Run a queued task on the current thread, if there is one
+    fn help(&self) -> bool {
+        let task = self.lock().tasks.pop_front();
+        match task {
+            Some(task) => {
+                task.run();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub struct Task {
+    state: Mutex<TaskState>,
+    finished: Condvar,
+    cancelled: Arc<AtomicBool>,
+}
+
+enum TaskState {
+    Pending(Ref<Io>, Box<dyn Context + Send>),
+    Running,
+    Finished(Result<Value, RuntimeError>),
+}
+
+impl HeapObject for Task {
+    const KIND: ObjectKind = ObjectKind::Task;
+}
+
+/// Spawn a task running `io`
+pub fn spawn(ctx: &dyn Context, io: Ref<Io>) -> Ref<Task> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let task = Obj::alloc(Task {
+        state: Mutex::new(TaskState::Pending(io, ctx.fork(cancelled.clone()))),
+        finished: Condvar::new(),
+        cancelled,
+    });
+
+    match ctx.scheduling() {
+        Scheduling::Threads => POOL.submit(task.clone()),
+        Scheduling::Deterministic => {
+            PENDING.with(|pending| pending.borrow_mut().push_back(task.clone()));
+        }
+    }
+
+    task
+}
+
+/// Drop the tasks spawned with deterministic scheduling on this thread which never ran
+///
+/// They fail with [`RuntimeError::Cancelled`], should anything still join them.
+pub fn shutdown() {
+    let pending = PENDING.with(|pending| std::mem::take(&mut *pending.borrow_mut()));
+    for task in pending {
+        let mut state = task.lock();
+        if let TaskState::Pending(..) = *state {
+            *state = TaskState::Finished(Err(RuntimeError::Cancelled));
+            task.finished.notify_all();
+        }
+    }
+}
+
+impl Task {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    // Does nothing if the task is already running or finished
+    fn run(&self) {
+        let mut state = self.lock();
+        let (io, mut ctx) = match std::mem::replace(&mut *state, TaskState::Running) {
+            TaskState::Pending(io, ctx) => (io, ctx),
+            other => {
+                *state = other;
+                return;
+            }
+        };
+        drop(state);
+
+        let result = io.run(&mut *ctx);
+
+        *self.lock() = TaskState::Finished(result);
+        self.finished.notify_all();
+    }
+
+    /// Wait for the task to finish and return its result
+    pub fn join(&self, ctx: &dyn Context) -> Result<Value, RuntimeError> {
+        if ctx.scheduling() == Scheduling::Deterministic {
+            self.run();
+            return match &*self.lock() {
+                TaskState::Finished(result) => result.clone(),
+                // The task is still running on this thread, so it (indirectly) joins itself
+                _ => Err(RuntimeError::Deadlock),
+            };
+        }
+
+        // Run the task right here if no thread of the pool took it yet
+        self.run();
+
+        let mut state = self.lock();
+        loop {
+            if let TaskState::Finished(result) = &*state {
+                return result.clone();
+            }
+
+            if ctx.is_cancelled() {
+                return Err(RuntimeError::Cancelled);
+            }
+
+            // The task may wait for one of the queued tasks
+            drop(state);
+            let helped = POOL.help();
+            state = self.lock();
+            if helped || matches!(*state, TaskState::Finished(_)) {
+                continue;
+            }
+
+            state = self
+                .finished
+                .wait_timeout(state, CANCEL_POLL_INTERVAL)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TaskState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl std::fmt::Debug for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &*self.lock() {
+            TaskState::Pending(..) => write!(f, "Task(<pending>)"),
+            TaskState::Running => write!(f, "Task(<running>)"),
+            TaskState::Finished(Ok(value)) => write!(f, "Task({:?})", value),
+            TaskState::Finished(Err(error)) => write!(f, "Task(<failed: {}>)", error),
+        }
+    }
+}
+
+/// An unbounded channel, which any number of tasks can send to and receive from
+#[derive(Debug, Default)]
+pub struct Channel {
+    messages: Mutex<VecDeque<Value>>,
+    available: Condvar,
+}
+
+impl HeapObject for Channel {
+    const KIND: ObjectKind = ObjectKind::Channel;
+}
+
+impl Channel {
+    pub fn send(&self, value: Value) {
+        self.lock().push_back(value);
+        self.available.notify_one();
+    }
+
+    /// Wait for a message and return it
+    pub fn receive(&self, ctx: &dyn Context) -> Result<Value, RuntimeError> {
+        if ctx.scheduling() == Scheduling::Deterministic {
+            loop {
+                if let Some(value) = self.lock().pop_front() {
+                    return Ok(value);
+                }
+
+                match PENDING.with(|pending| pending.borrow_mut().pop_front()) {
+                    Some(task) => task.run(),
+                    None => return Err(RuntimeError::Deadlock),
+                }
+            }
+        }
+
+        let mut messages = self.lock();
+        loop {
+            if let Some(value) = messages.pop_front() {
+                return Ok(value);
+            }
+
+            if ctx.is_cancelled() {
+                return Err(RuntimeError::Cancelled);
+            }
+
+            // The sender may be one of the queued tasks
+            drop(messages);
+            let helped = POOL.help();
+            messages = self.lock();
+            if helped || !messages.is_empty() {
+                continue;
+            }
+
+            messages = self
+                .available
+                .wait_timeout(messages, CANCEL_POLL_INTERVAL)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Value>> {
+        self.messages.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The context tasks run in if the spawning context does not provide one
+pub struct TaskContext {
+    pub(crate) sandbox: Sandbox,
+    pub(crate) args: Vec<String>,
+    pub(crate) scheduling: Scheduling,
+    pub(crate) cancelled: Arc<AtomicBool>,
+}
+
+impl Context for TaskContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        apply(self, function, args)
+    }
+
+    fn sandbox(&self) -> &Sandbox {
+        &self.sandbox
+    }
+
+    fn program_args(&self) -> &[String] {
+        &self.args
+    }
+
+    fn scheduling(&self) -> Scheduling {
+        self.scheduling
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,126 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::thread::ThreadId;
+
+use crate::error::RuntimeError;
+use crate::heap::HeapObject;
+use crate::heap::ObjectKind;
+use crate::value::Value;
+
+pub type Deferred = Box<dyn FnOnce() -> Result<Value, RuntimeError> + Send>;
+
+/// A deferred computation, which is evaluated at most once
+///
+/// While a thunk is being evaluated, it is "blackholed": forcing it again from the same thread
+/// means that the value depends on itself, which is reported as [`RuntimeError::BlackHole`]
+/// instead of recursing forever. Other threads forcing the thunk meanwhile wait for the result.
+/// If the evaluation panics, the thunk fails with [`RuntimeError::Panic`] for everyone forcing it.
+pub struct Thunk {
+    state: Mutex<State>,
+    evaluated: Condvar,
+}
+
+enum State {
+    Deferred(Deferred),
+    Evaluating(ThreadId),
+    Evaluated(Value),
+    Failed(RuntimeError),
+}
+
+impl HeapObject for Thunk {
+    const KIND: ObjectKind = ObjectKind::Thunk;
+}
+
+impl Thunk {
+    pub fn new(deferred: Deferred) -> Self {
+        Thunk {
+            state: Mutex::new(State::Deferred(deferred)),
+            evaluated: Condvar::new(),
+        }
+    }
+
+    pub fn is_evaluated(&self) -> bool {
+        matches!(
+            *self.state.lock().unwrap_or_else(PoisonError::into_inner),
+            State::Evaluated(_) | State::Failed(_)
+        )
+    }
+
+    pub fn force(&self) -> Result<Value, RuntimeError> {
+        let current = std::thread::current().id();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        loop {
+            match &*state {
+                State::Evaluated(value) => return Ok(value.clone()),
+                State::Failed(error) => return Err(error.clone()),
+                State::Evaluating(thread) if *thread == current => {
+                    return Err(RuntimeError::BlackHole)
+                }
+                State::Evaluating(_) => {}
+                State::Deferred(_) => break,
+            }
+
+            state = self
+                .evaluated
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        let deferred = match std::mem::replace(&mut *state, State::Evaluating(current)) {
+            State::Deferred(deferred) => deferred,
+            _ => unreachable!(),
+        };
+        drop(state);
+
+        let evaluation = Evaluation(self);
+        let result = deferred().and_then(|value| value.force());
+        evaluation.finish(match result.as_ref() {
+            Ok(value) => State::Evaluated(value.clone()),
+            Err(error) => State::Failed(error.clone()),
+        });
+        result
+    }
+
+    // Replace the state of an evaluation, and wake the threads waiting for it
+    fn settle(&self, settled: State) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        *state = settled;
+        self.evaluated.notify_all();
+    }
+}
+
+// A running evaluation of a thunk, which fails the thunk when it is dropped without finishing,
+// because the evaluation panicked. Otherwise, forcing the thunk again would report a black hole
+// on the same thread and wait forever on the others.
+struct Evaluation<'a>(&'a Thunk);
+
+impl Evaluation<'_> {
+    fn finish(self, state: State) {
+        self.0.settle(state);
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Evaluation<'_> {
+    fn drop(&mut self) {
+        let error = RuntimeError::Panic("in the evaluation of a lazy value".to_string());
+        self.0.settle(State::Failed(error));
+    }
+}
+
+impl std::fmt::Debug for Thunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &*self.state.lock().unwrap_or_else(PoisonError::into_inner) {
+            State::Deferred(_) => write!(f, "Thunk(<deferred>)"),
+            State::Evaluating(_) => write!(f, "Thunk(<evaluating>)"),
+            State::Evaluated(value) => write!(f, "Thunk({:?})", value),
+            State::Failed(error) => write!(f, "Thunk(<failed: {}>)", error),
+        }
+    }
+}
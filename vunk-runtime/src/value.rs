@@ -0,0 +1,394 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+use regex::Regex;
+
+use crate::builder::Builder;
+use crate::cell::Cell;
+use crate::collection::Map;
+use crate::collection::Set;
+use crate::error::RuntimeError;
+use crate::float;
+use crate::function::Function;
+use crate::heap::HeapObject;
+use crate::heap::Obj;
+use crate::heap::ObjectKind;
+use crate::heap::Ref;
+use crate::io::Io;
+use crate::task::Channel;
+use crate::task::Task;
+use crate::thunk::Deferred;
+use crate::thunk::Thunk;
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    Integer(i64),
+    BigInt(Ref<BigInt>),
+    Float(f64),
+    Str(Ref<String>),
+
+    /// Binary data, of type `Bytes`, see [`crate::stdlib::bytes`]
+    Bytes(Ref<Vec<u8>>),
+
+    List(Ref<Vec<Value>>),
+    Tuple(Ref<Tuple>),
+    Map(Ref<Map>),
+    Set(Ref<Set>),
+    Record(Ref<Record>),
+    Variant(Ref<Variant>),
+    Thunk(Ref<Thunk>),
+    Function(Ref<Function>),
+    Io(Ref<Io>),
+    Task(Ref<Task>),
+    Channel(Ref<Channel>),
+
+    /// A mutable reference cell, of type `Ref A`, see [`crate::cell`]
+    Cell(Ref<Cell>),
+
+    /// A string that is being built, of type `StringBuilder`, see [`crate::builder`]
+    Builder(Ref<Builder>),
+
+    /// A compiled regular expression, of type `Regex`, see [`crate::stdlib::regex`]
+    Regex(Ref<Regex>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Tuple(pub Vec<Value>);
+
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub type_name: Option<String>,
+    pub fields: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Variant {
+    pub type_name: String,
+    pub name: String,
+    pub members: Vec<Value>,
+}
+
+impl HeapObject for String {
+    const KIND: ObjectKind = ObjectKind::Str;
+}
+
+impl HeapObject for BigInt {
+    const KIND: ObjectKind = ObjectKind::BigInt;
+}
+
+impl HeapObject for Vec<u8> {
+    const KIND: ObjectKind = ObjectKind::Bytes;
+}
+
+impl HeapObject for Regex {
+    const KIND: ObjectKind = ObjectKind::Regex;
+}
+
+impl HeapObject for Vec<Value> {
+    const KIND: ObjectKind = ObjectKind::List;
+}
+
+impl HeapObject for Tuple {
+    const KIND: ObjectKind = ObjectKind::Tuple;
+}
+
+impl HeapObject for Record {
+    const KIND: ObjectKind = ObjectKind::Record;
+}
+
+impl HeapObject for Variant {
+    const KIND: ObjectKind = ObjectKind::Variant;
+}
+
+impl Value {
+    pub fn big_int(i: BigInt) -> Value {
+        Value::BigInt(Obj::alloc(i))
+    }
+
+    pub fn string(s: impl Into<String>) -> Value {
+        Value::Str(Obj::alloc(s.into()))
+    }
+
+    pub fn bytes(bytes: impl Into<Vec<u8>>) -> Value {
+        Value::Bytes(Obj::alloc(bytes.into()))
+    }
+
+    pub fn list(elements: Vec<Value>) -> Value {
+        Value::List(Obj::alloc(elements))
+    }
+
+    pub fn tuple(elements: Vec<Value>) -> Value {
+        Value::Tuple(Obj::alloc(Tuple(elements)))
+    }
+
+    pub fn map(map: Map) -> Value {
+        Value::Map(Obj::alloc(map))
+    }
+
+    pub fn set(set: Set) -> Value {
+        Value::Set(Obj::alloc(set))
+    }
+
+    pub fn record(type_name: Option<String>, fields: BTreeMap<String, Value>) -> Value {
+        Value::Record(Obj::alloc(Record { type_name, fields }))
+    }
+
+    pub fn variant(
+        type_name: impl Into<String>,
+        name: impl Into<String>,
+        members: Vec<Value>,
+    ) -> Value {
+        Value::Variant(Obj::alloc(Variant {
+            type_name: type_name.into(),
+            name: name.into(),
+            members,
+        }))
+    }
+
+    pub fn some(value: Value) -> Value {
+        Value::variant("Option", "Some", vec![value])
+    }
+
+    pub fn none() -> Value {
+        Value::variant("Option", "None", Vec::new())
+    }
+
+    pub fn ok(value: Value) -> Value {
+        Value::variant("Result", "Ok", vec![value])
+    }
+
+    pub fn err(error: Value) -> Value {
+        Value::variant("Result", "Err", vec![error])
+    }
+
+    pub fn function(function: Function) -> Value {
+        Value::Function(Obj::alloc(function))
+    }
+
+    pub fn io(io: Io) -> Value {
+        Value::Io(Obj::alloc(io))
+    }
+
+    pub fn cell(value: Value) -> Value {
+        Value::Cell(Obj::alloc(Cell::new(value)))
+    }
+
+    pub fn builder(builder: Builder) -> Value {
+        Value::Builder(Obj::alloc(builder))
+    }
+
+    pub fn regex(regex: Regex) -> Value {
+        Value::Regex(Obj::alloc(regex))
+    }
+
+    pub fn lazy(deferred: Deferred) -> Value {
+        Value::Thunk(Obj::alloc(Thunk::new(deferred)))
+    }
+
+    /// Evaluate a lazy value, or return the value itself if it is not lazy
+    pub fn force(&self) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Thunk(thunk) => thunk.force(),
+            other => Ok(other.clone()),
+        }
+    }
+
+    pub fn type_name(&self) -> &str {
+        match self {
+            Value::Unit => "()",
+            Value::Bool(_) => "Bool",
+            Value::Integer(_) => "Int",
+            Value::BigInt(_) => "BigInt",
+            Value::Float(_) => "Float",
+            Value::Str(_) => "String",
+            Value::Bytes(_) => "Bytes",
+            Value::List(_) => "List",
+            Value::Tuple(_) => "Tuple",
+            Value::Map(_) => "Map",
+            Value::Set(_) => "Set",
+            Value::Record(r) => r.type_name.as_deref().unwrap_or("Record"),
+            Value::Variant(v) => &v.type_name,
+            Value::Thunk(_) => "Lazy",
+            Value::Function(_) => "Function",
+            Value::Io(_) => "IO",
+            Value::Task(_) => "Task",
+            Value::Channel(_) => "Channel",
+            Value::Cell(_) => "Ref",
+            Value::Builder(_) => "StringBuilder",
+            Value::Regex(_) => "Regex",
+        }
+    }
+
+    /// Get mutable access to the elements of a list value
+    ///
+    /// The list is copied if it is shared with other values.
+    pub fn list_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::List(list) => Some(&mut **Arc::make_mut(list)),
+            _ => None,
+        }
+    }
+
+    /// Get mutable access to a record value
+    ///
+    /// The record is copied if it is shared with other values.
+    pub fn record_mut(&mut self) -> Option<&mut Record> {
+        match self {
+            Value::Record(record) => Some(&mut **Arc::make_mut(record)),
+            _ => None,
+        }
+    }
+
+    /// Whether two values are the very same heap object
+    pub fn ptr_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::BigInt(a), Value::BigInt(b)) => Arc::ptr_eq(a, b),
+            (Value::Str(a), Value::Str(b)) => Arc::ptr_eq(a, b),
+            (Value::Bytes(a), Value::Bytes(b)) => Arc::ptr_eq(a, b),
+            (Value::List(a), Value::List(b)) => Arc::ptr_eq(a, b),
+            (Value::Tuple(a), Value::Tuple(b)) => Arc::ptr_eq(a, b),
+            (Value::Map(a), Value::Map(b)) => Arc::ptr_eq(a, b),
+            (Value::Set(a), Value::Set(b)) => Arc::ptr_eq(a, b),
+            (Value::Record(a), Value::Record(b)) => Arc::ptr_eq(a, b),
+            (Value::Variant(a), Value::Variant(b)) => Arc::ptr_eq(a, b),
+            (Value::Thunk(a), Value::Thunk(b)) => Arc::ptr_eq(a, b),
+            (Value::Function(a), Value::Function(b)) => Arc::ptr_eq(a, b),
+            (Value::Io(a), Value::Io(b)) => Arc::ptr_eq(a, b),
+            (Value::Task(a), Value::Task(b)) => Arc::ptr_eq(a, b),
+            (Value::Channel(a), Value::Channel(b)) => Arc::ptr_eq(a, b),
+            (Value::Cell(a), Value::Cell(b)) => Arc::ptr_eq(a, b),
+            (Value::Builder(a), Value::Builder(b)) => Arc::ptr_eq(a, b),
+            (Value::Regex(a), Value::Regex(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Record {
+    /// Build a new record from this one, with one field replaced
+    pub fn with_field(record: &Ref<Record>, name: impl Into<String>, value: Value) -> Value {
+        let mut record = record.clone();
+        Arc::make_mut(&mut record).fields.insert(name.into(), value);
+        Value::Record(record)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.fields.get(name)
+    }
+}
+
+/// Renders values the way they would be written in source code
+///
+/// Lazy values are only shown if they were evaluated already, displaying a value never forces it.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::BigInt(i) => write!(f, "{}", ***i),
+            Value::Float(x) => write!(f, "{}", float::show(*x)),
+            Value::Str(s) => write!(f, "{:?}", s.as_str()),
+            Value::Bytes(bytes) => {
+                write!(f, "x\"")?;
+                bytes
+                    .iter()
+                    .try_for_each(|byte| write!(f, "{:02x}", byte))?;
+                write!(f, "\"")
+            }
+            Value::List(list) => {
+                write!(f, "[")?;
+                separated(f, list.iter(), " ")?;
+                write!(f, "]")
+            }
+            Value::Tuple(tuple) => {
+                write!(f, "(")?;
+                separated(f, tuple.0.iter(), ", ")?;
+                write!(f, ")")
+            }
+            Value::Map(map) => {
+                write!(f, "Std.Map.fromList [")?;
+                for (i, (key, value)) in map.0.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "({}, {})", key.to_value(), value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Set(set) => {
+                write!(f, "Std.Set.fromList [")?;
+                separated(f, set.0.iter().map(|key| key.to_value()), " ")?;
+                write!(f, "]")
+            }
+            Value::Record(record) => {
+                // Records without a type are written like `{ x = 1 }`
+                let assign = match record.type_name.as_ref() {
+                    Some(type_name) => {
+                        write!(f, "{} ", type_name)?;
+                        ":"
+                    }
+                    None => " =",
+                };
+                write!(f, "{{ ")?;
+                for (i, (name, value)) in record.fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}{} {}", name, assign, value)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Variant(variant) => {
+                write!(f, "{}", variant.name)?;
+                for member in variant.members.iter() {
+                    match member {
+                        Value::Variant(v) if !v.members.is_empty() => write!(f, " ({})", member)?,
+                        _ => write!(f, " {}", member)?,
+                    }
+                }
+                Ok(())
+            }
+            Value::Thunk(thunk) if thunk.is_evaluated() => match thunk.force() {
+                Ok(value) => write!(f, "{}", value),
+                Err(error) => write!(f, "<failed: {}>", error),
+            },
+            Value::Thunk(_) => write!(f, "<lazy>"),
+            Value::Function(function) => match &***function {
+                Function::Builtin { builtin, .. } => {
+                    write!(f, "<function {}>", builtin.name)
+                }
+            },
+            Value::Io(_) => write!(f, "<io>"),
+            Value::Task(_) => write!(f, "<task>"),
+            Value::Channel(_) => write!(f, "<channel>"),
+            Value::Cell(_) => write!(f, "<ref>"),
+            Value::Builder(_) => write!(f, "<builder>"),
+            Value::Regex(regex) => write!(f, "<regex {:?}>", regex.as_str()),
+        }
+    }
+}
+
+fn separated<V>(
+    f: &mut std::fmt::Formatter,
+    values: impl Iterator<Item = V>,
+    separator: &str,
+) -> std::fmt::Result
+where
+    V: std::borrow::Borrow<Value>,
+{
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write!(f, "{}", value.borrow())?;
+    }
+    Ok(())
+}
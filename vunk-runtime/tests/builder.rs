@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::value::Value;
+
+fn push(builtins: &Builtins, piece: &str, builder: Value) -> Value {
+    call(
+        builtins,
+        "Std.String.Builder.push",
+        vec![Value::string(piece), builder],
+    )
+}
+
+fn build(builtins: &Builtins, builder: Value) -> String {
+    match call(builtins, "Std.String.Builder.build", vec![builder]) {
+        Value::Str(s) => s.to_string(),
+        other => panic!("Not a string: {:?}", other),
+    }
+}
+
+#[test]
+fn builders_join_the_pieces_pushed_to_them() {
+    let builtins = Builtins::std();
+    let builder = call(&builtins, "Std.String.Builder.new", Vec::new());
+    assert_eq!(builder.type_name(), "StringBuilder");
+    assert_eq!(build(&builtins, builder.clone()), "");
+
+    let builder = ["Hello", ", ", "world"]
+        .iter()
+        .fold(builder, |builder, piece| push(&builtins, piece, builder));
+    assert_eq!(build(&builtins, builder), "Hello, world");
+}
+
+#[test]
+fn pushing_keeps_the_old_builder() {
+    let builtins = Builtins::std();
+    let empty = call(&builtins, "Std.String.Builder.new", Vec::new());
+    let a = push(&builtins, "a", empty);
+    let ab = push(&builtins, "b", a.clone());
+    let ac = push(&builtins, "c", a.clone());
+
+    assert_eq!(build(&builtins, a), "a");
+    assert_eq!(build(&builtins, ab), "ab");
+    assert_eq!(build(&builtins, ac), "ac");
+}
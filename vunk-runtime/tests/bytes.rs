@@ -0,0 +1,104 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::operator::binary;
+use vunk_runtime::operator::Instances;
+use vunk_runtime::value::Value;
+
+fn bytes(value: &Value) -> Vec<u8> {
+    match value {
+        Value::Bytes(bytes) => bytes.to_vec(),
+        other => panic!("Not bytes: {:?}", other),
+    }
+}
+
+// The bytes in a `Some`, or `None`
+fn some_bytes(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::Variant(v) if v.name == "Some" => Some(bytes(&v.members[0])),
+        _ => None,
+    }
+}
+
+#[test]
+fn bytes_are_sliced_with_clamped_bounds() {
+    let builtins = Builtins::std();
+    let data = Value::bytes(b"vunk".to_vec());
+    let slice = |start, end| {
+        let slice = call(
+            &builtins,
+            "Std.Bytes.slice",
+            vec![Value::Integer(start), Value::Integer(end), data.clone()],
+        );
+        bytes(&slice)
+    };
+
+    assert_eq!(slice(1, 3), b"un");
+    assert_eq!(slice(-5, 2), b"vu");
+    assert_eq!(slice(2, 100), b"nk");
+    assert_eq!(slice(3, 1), b"");
+
+    let joined = binary(
+        &mut BuiltinContext,
+        &Instances::default(),
+        "++",
+        &data,
+        &data,
+    );
+    assert_eq!(bytes(&joined.unwrap()), b"vunkvunk");
+}
+
+#[test]
+fn hex_and_base64_round_trip() {
+    let builtins = Builtins::std();
+    for data in [&b""[..], b"f", b"fo", b"foo", b"foob", &[0x00, 0xff, 0x7f]] {
+        let data = Value::bytes(data);
+        let hex = call(&builtins, "Std.Bytes.toHex", vec![data.clone()]);
+        let base64 = call(&builtins, "Std.Bytes.toBase64", vec![data.clone()]);
+        assert_eq!(
+            some_bytes(&call(&builtins, "Std.Bytes.fromHex", vec![hex])),
+            Some(bytes(&data))
+        );
+        assert_eq!(
+            some_bytes(&call(&builtins, "Std.Bytes.fromBase64", vec![base64])),
+            Some(bytes(&data))
+        );
+    }
+
+    let base64 = call(
+        &builtins,
+        "Std.Bytes.toBase64",
+        vec![Value::bytes(b"foob".to_vec())],
+    );
+    assert_eq!(base64.to_string(), "\"Zm9vYg==\"");
+    let hex = call(
+        &builtins,
+        "Std.Bytes.toHex",
+        vec![Value::bytes(vec![0x89, 0x50])],
+    );
+    assert_eq!(hex.to_string(), "\"8950\"");
+}
+
+#[test]
+fn invalid_encodings_are_not_decoded() {
+    let builtins = Builtins::std();
+    let decode = |name, s: &str| some_bytes(&call(&builtins, name, vec![Value::string(s)]));
+    assert_eq!(decode("Std.Bytes.fromHex", "abc"), None);
+    assert_eq!(decode("Std.Bytes.fromHex", "zz"), None);
+    assert_eq!(decode("Std.Bytes.fromBase64", "Zm9"), None);
+    assert_eq!(decode("Std.Bytes.fromBase64", "Zg==Zg=="), None);
+    assert_eq!(decode("Std.Bytes.fromBase64", "Z!=="), None);
+
+    let invalid = call(
+        &builtins,
+        "Std.Bytes.decodeUtf8",
+        vec![Value::bytes(vec![0xff])],
+    );
+    assert_eq!(invalid.to_string(), "None");
+}
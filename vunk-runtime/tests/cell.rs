@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::io::run_main;
+use vunk_runtime::value::Value;
+
+// Run the action a builtin returns for the arguments
+fn run(builtins: &Builtins, name: &str, args: Vec<Value>) -> Value {
+    let function = builtins.value(name).unwrap();
+    let action = apply(&mut BuiltinContext, &function, args).unwrap();
+    run_main(&mut BuiltinContext, &action).unwrap()
+}
+
+fn int(value: &Value) -> i64 {
+    match value {
+        Value::Integer(i) => *i,
+        other => panic!("Not an integer: {:?}", other),
+    }
+}
+
+#[test]
+fn cells_are_created_read_and_written_by_actions() {
+    let builtins = Builtins::std();
+    let cell = run(&builtins, "ref", vec![Value::Integer(1)]);
+    assert_eq!(cell.type_name(), "Ref");
+    assert_eq!(int(&run(&builtins, "Std.Ref.get", vec![cell.clone()])), 1);
+
+    run(
+        &builtins,
+        "Std.Ref.set",
+        vec![cell.clone(), Value::Integer(2)],
+    );
+    assert_eq!(int(&run(&builtins, "Std.Ref.get", vec![cell])), 2);
+}
+
+#[test]
+fn every_run_creates_another_cell() {
+    let builtins = Builtins::std();
+    let function = builtins.value("Std.Ref.new").unwrap();
+    let action = apply(&mut BuiltinContext, &function, vec![Value::Unit]).unwrap();
+    let first = run_main(&mut BuiltinContext, &action).unwrap();
+    let second = run_main(&mut BuiltinContext, &action).unwrap();
+    assert!(!first.ptr_eq(&second));
+    assert!(first.ptr_eq(&first.clone()));
+}
+
+#[test]
+fn cells_are_modified_with_a_function() {
+    let builtins = Builtins::std();
+    let list = Value::list(vec![Value::Integer(1), Value::Integer(2)]);
+    let cell = run(&builtins, "Std.Ref.new", vec![list]);
+    let reverse = builtins.value("Std.List.reverse").unwrap();
+    run(&builtins, "Std.Ref.modify", vec![reverse, cell.clone()]);
+
+    match run(&builtins, "Std.Ref.get", vec![cell]) {
+        Value::List(list) => assert_eq!(list.iter().map(int).collect::<Vec<_>>(), [2, 1]),
+        other => panic!("Not a list: {:?}", other),
+    }
+}
+
+#[test]
+fn cells_that_refer_to_themselves_leak_until_written_again() {
+    let builtins = Builtins::std();
+    let cycle = |broken: bool| {
+        let cell = run(&builtins, "ref", vec![Value::none()]);
+        let weak = match &cell {
+            Value::Cell(cell) => Arc::downgrade(cell),
+            other => panic!("Not a cell: {:?}", other),
+        };
+        run(
+            &builtins,
+            "Std.Ref.set",
+            vec![cell.clone(), Value::some(cell.clone())],
+        );
+        if broken {
+            run(&builtins, "Std.Ref.set", vec![cell.clone(), Value::none()]);
+        }
+        weak
+    };
+
+    assert!(cycle(false).upgrade().is_some());
+    assert!(cycle(true).upgrade().is_none());
+}
@@ -0,0 +1,134 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use num_bigint::BigInt;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::cmp::compare;
+use vunk_runtime::cmp::equal;
+use vunk_runtime::collection::Key;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::operator::binary;
+use vunk_runtime::operator::Instances;
+use vunk_runtime::value::Value;
+
+fn ints(values: &[i64]) -> Value {
+    Value::list(values.iter().map(|i| Value::Integer(*i)).collect())
+}
+
+fn point(type_name: Option<&str>, x: i64, y: i64) -> Value {
+    Value::record(
+        type_name.map(str::to_string),
+        BTreeMap::from([
+            ("x".to_string(), Value::Integer(x)),
+            ("y".to_string(), Value::Integer(y)),
+        ]),
+    )
+}
+
+#[test]
+fn lists_and_tuples_are_ordered_lexicographically() {
+    assert_eq!(
+        compare(&ints(&[1, 2]), &ints(&[1, 3])).unwrap(),
+        Ordering::Less
+    );
+    assert_eq!(
+        compare(&ints(&[1, 2]), &ints(&[1])).unwrap(),
+        Ordering::Greater
+    );
+    assert_eq!(compare(&ints(&[]), &ints(&[])).unwrap(), Ordering::Equal);
+
+    let pair = |a, b: &str| Value::tuple(vec![Value::Integer(a), Value::string(b)]);
+    assert_eq!(
+        compare(&pair(1, "b"), &pair(2, "a")).unwrap(),
+        Ordering::Less
+    );
+    assert!(equal(&pair(1, "a"), &pair(1, "a")).unwrap());
+}
+
+#[test]
+fn records_and_variants_are_compared_by_their_contents() {
+    assert_eq!(
+        compare(&point(None, 1, 5), &point(None, 2, 0)).unwrap(),
+        Ordering::Less
+    );
+    assert!(equal(&point(None, 1, 2), &point(None, 1, 2)).unwrap());
+    assert!(compare(&point(None, 1, 2), &point(Some("Point"), 1, 2)).is_err());
+
+    let some = |i| Value::some(Value::Integer(i));
+    assert_eq!(compare(&some(1), &some(2)).unwrap(), Ordering::Less);
+    assert_eq!(compare(&Value::none(), &some(0)).unwrap(), Ordering::Less);
+    assert!(!equal(&Value::none(), &some(0)).unwrap());
+}
+
+#[test]
+fn floats_use_the_total_order() {
+    assert!(equal(&Value::Float(f64::NAN), &Value::Float(f64::NAN)).unwrap());
+    let zero = compare(&Value::Float(-0.0), &Value::Float(0.0)).unwrap();
+    assert_eq!(zero, Ordering::Less);
+}
+
+#[test]
+fn functions_cannot_be_compared() {
+    let builtins = Builtins::std();
+    let function = builtins.value("Std.Cmp.equal").unwrap();
+    assert!(matches!(
+        equal(&function, &function),
+        Err(RuntimeError::NotComparable(type_name)) if type_name == "Function"
+    ));
+    assert!(matches!(
+        compare(&ints(&[1]), &Value::list(vec![function])),
+        Err(RuntimeError::TypeMismatch { .. })
+    ));
+}
+
+#[test]
+fn derived_instances_compare_structurally() {
+    let builtins = Builtins::std();
+    let mut instances = Instances::default();
+    assert!(instances.derive(&builtins, "Eq", "Point"));
+    assert!(instances.derive(&builtins, "Ord", "Point"));
+    assert!(!instances.derive(&builtins, "Num", "Point"));
+
+    let apply = |op, lhs: (i64, i64), rhs: (i64, i64)| {
+        let lhs = point(Some("Point"), lhs.0, lhs.1);
+        let rhs = point(Some("Point"), rhs.0, rhs.1);
+        match binary(&mut BuiltinContext, &instances, op, &lhs, &rhs).unwrap() {
+            Value::Bool(b) => b,
+            other => panic!("Expected a Bool, got {:?}", other),
+        }
+    };
+    assert!(apply("==", (1, 2), (1, 2)));
+    assert!(apply("<", (1, 2), (1, 3)));
+    assert!(!apply(">=", (0, 9), (1, 0)));
+}
+
+#[test]
+fn integer_keys_are_ordered_by_their_value() {
+    let big = |i: i64| Value::big_int(BigInt::from(i));
+    assert!(matches!(Key::from_value(&big(5)).unwrap(), Key::Integer(5)));
+    assert_eq!(Key::Integer(5), Key::BigInt(BigInt::from(5)));
+    assert!(Key::Integer(i64::MAX) < Key::big_int(BigInt::from(i64::MAX) + 1));
+    assert!(Key::big_int(BigInt::from(i64::MIN) - 1) < Key::Integer(-1));
+
+    let builtins = Builtins::std();
+    let from_list = |elements| {
+        let function = builtins.value("Std.Set.fromList").unwrap();
+        apply(&mut BuiltinContext, &function, vec![Value::list(elements)]).unwrap()
+    };
+    let set = from_list(vec![Value::Integer(5), big(5), big(1)]);
+    match &set {
+        Value::Set(set) => assert_eq!(set.0.len(), 2),
+        other => panic!("Expected a Set, got {:?}", other),
+    }
+
+    let huge = Value::big_int(BigInt::from(i64::MAX) * 2);
+    let smaller = from_list(vec![Value::Integer(1), huge.clone()]);
+    let larger = from_list(vec![Value::Integer(2), huge]);
+    assert_eq!(compare(&smaller, &larger).unwrap(), Ordering::Less);
+}
@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+// Every test binary includes this module, but none uses all of it
+#![allow(dead_code)]
+
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::value::Value;
+
+/// Call the builtin `name` with `args`
+pub fn try_call(builtins: &Builtins, name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let function = builtins.value(name).unwrap();
+    apply(&mut BuiltinContext, &function, args)
+}
+
+/// Call the builtin `name` with `args`, which must not fail
+pub fn call(builtins: &Builtins, name: &str, args: Vec<Value>) -> Value {
+    try_call(builtins, name, args).unwrap()
+}
+
+/// Call the builtin `name` with `args` and show the result, like `Some 2`
+pub fn show(builtins: &Builtins, name: &str, args: Vec<Value>) -> String {
+    shown(&call(builtins, name, args))
+}
+
+fn shown(value: &Value) -> String {
+    match value {
+        Value::Bool(b) => b.to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Variant(variant) => variant
+            .members
+            .iter()
+            .fold(variant.name.clone(), |shown, member| {
+                format!("{} {}", shown, self::shown(member))
+            }),
+        other => panic!("Cannot show {:?}", other),
+    }
+}
@@ -0,0 +1,146 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::function::Context;
+use vunk_runtime::value::Value;
+
+fn assert_equal(actual: Value, expected: Value) -> Result<Value, RuntimeError> {
+    let builtins = Builtins::std();
+    let function = builtins.value("assertEq").unwrap();
+    apply(&mut BuiltinContext, &function, vec![actual, expected])
+}
+
+#[test]
+fn assert_eq_passes_for_equal_values() {
+    assert!(matches!(
+        assert_equal(Value::string("vunk"), Value::string("vunk")),
+        Ok(Value::Unit)
+    ));
+}
+
+#[test]
+fn assert_eq_reports_both_values() {
+    match assert_equal(Value::Integer(3), Value::Integer(4)) {
+        Err(RuntimeError::AssertionFailed(message)) => assert_eq!(message, "expected 4, found 3"),
+        other => panic!("Expected a failed assertion, got {:?}", other),
+    }
+}
+
+#[test]
+fn assert_eq_rejects_different_types() {
+    assert!(matches!(
+        assert_equal(Value::Integer(3), Value::Bool(true)),
+        Err(RuntimeError::TypeMismatch { .. })
+    ));
+}
+
+// A context that calls builtins from a known place
+struct SiteContext;
+
+impl Context for SiteContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        apply(self, function, args)
+    }
+
+    fn call_site(&self) -> Option<String> {
+        Some("main.vunk:3:5".to_string())
+    }
+}
+
+#[test]
+fn trace_returns_its_value_unevaluated() {
+    let builtins = Builtins::std();
+    let trace = builtins.value("trace").unwrap();
+    let evaluated = Arc::new(AtomicBool::new(false));
+    let flag = evaluated.clone();
+    let value = Value::lazy(Box::new(move || {
+        flag.store(true, Ordering::SeqCst);
+        Ok(Value::Integer(1))
+    }));
+
+    let traced = apply(
+        &mut SiteContext,
+        &trace,
+        vec![Value::string("here"), value.clone()],
+    );
+    assert!(traced.unwrap().ptr_eq(&value));
+    assert!(!evaluated.load(Ordering::SeqCst));
+}
+
+#[test]
+fn dbg_returns_its_value_evaluated() {
+    let builtins = Builtins::std();
+    let dbg = builtins.value("dbg").unwrap();
+    let value = Value::lazy(Box::new(|| Ok(Value::Integer(1))));
+    assert!(matches!(
+        apply(&mut SiteContext, &dbg, vec![value]),
+        Ok(Value::Integer(1))
+    ));
+}
+
+#[test]
+fn failed_assertions_report_their_call_site() {
+    let builtins = Builtins::std();
+    let assert = builtins.value("assert").unwrap();
+    let check = |ctx: &mut dyn Context, condition| {
+        apply(
+            ctx,
+            &assert,
+            vec![Value::Bool(condition), Value::string("no users")],
+        )
+    };
+
+    assert!(matches!(check(&mut SiteContext, true), Ok(Value::Unit)));
+    match check(&mut BuiltinContext, false) {
+        Err(RuntimeError::AssertionFailed(message)) => assert_eq!(message, "no users"),
+        other => panic!("Expected a failed assertion, got {:?}", other),
+    }
+    match check(&mut SiteContext, false) {
+        Err(RuntimeError::AssertionFailed(message)) => {
+            assert_eq!(message, "[main.vunk:3:5] no users")
+        }
+        other => panic!("Expected a failed assertion, got {:?}", other),
+    }
+}
+
+#[test]
+fn panics_fail_with_their_message() {
+    let builtins = Builtins::std();
+    let panic = builtins.value("panic").unwrap();
+    match apply(
+        &mut BuiltinContext,
+        &panic,
+        vec![Value::string("no config")],
+    ) {
+        Err(RuntimeError::Panic(message)) => assert_eq!(message, "no config"),
+        other => panic!("Expected a panic, got {:?}", other),
+    }
+
+    let todo = builtins.value("todo").unwrap();
+    match apply(
+        &mut BuiltinContext,
+        &todo,
+        vec![Value::string("parse the header")],
+    ) {
+        Err(RuntimeError::Panic(message)) => {
+            assert_eq!(message, "not implemented yet: parse the header")
+        }
+        other => panic!("Expected a panic, got {:?}", other),
+    }
+
+    let unimplemented = builtins.get("unimplemented").unwrap();
+    assert_eq!(unimplemented.arity, 0);
+    match (unimplemented.func)(&mut BuiltinContext, Vec::new()) {
+        Err(RuntimeError::Panic(message)) => assert_eq!(message, "not implemented"),
+        other => panic!("Expected a panic, got {:?}", other),
+    }
+}
@@ -0,0 +1,86 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use std::collections::BTreeMap;
+
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::io::run_main;
+use vunk_runtime::value::Value;
+
+fn perform(builtins: &Builtins, effect: &str, operation: &str, args: Vec<Value>) -> Value {
+    let args = vec![
+        Value::string(effect),
+        Value::string(operation),
+        Value::list(args),
+    ];
+    call(builtins, "Std.Effect.perform", args)
+}
+
+// Handle the operation of an effect with `Std.IO.pure`, so it results in its argument
+fn handle(builtins: &Builtins, effect: &str, operation: &str, action: Value) -> Value {
+    let mut handlers = BTreeMap::new();
+    handlers.insert(
+        operation.to_string(),
+        builtins.value("Std.IO.pure").unwrap(),
+    );
+    let args = vec![Value::string(effect), Value::record(None, handlers), action];
+    call(builtins, "Std.Effect.handle", args)
+}
+
+#[test]
+fn operations_are_handled_by_the_handler_around_them() {
+    let builtins = Builtins::std();
+    let println = perform(
+        &builtins,
+        "Console",
+        "println",
+        vec![Value::string("Hello")],
+    );
+    let handled = handle(&builtins, "Console", "println", println);
+
+    match run_main(&mut BuiltinContext, &handled) {
+        Ok(Value::Str(s)) => assert_eq!(s.as_str(), "Hello"),
+        other => panic!("Expected the argument, got {:?}", other),
+    }
+}
+
+#[test]
+fn operations_without_a_handler_use_the_default() {
+    let builtins = Builtins::std();
+    let println = perform(
+        &builtins,
+        "Console",
+        "println",
+        vec![Value::string("Hello")],
+    );
+    let handled = handle(&builtins, "Log", "log", println);
+
+    // The default is `Std.IO.println`, which the sandbox of the context does not allow
+    assert!(matches!(
+        run_main(&mut BuiltinContext, &handled),
+        Err(RuntimeError::PermissionDenied {
+            builtin: "Std.IO.println",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn operations_without_a_handler_or_default_fail() {
+    let builtins = Builtins::std();
+    let log = perform(&builtins, "Log", "log", vec![Value::string("Hello")]);
+
+    match run_main(&mut BuiltinContext, &log) {
+        Err(error @ RuntimeError::Unhandled { .. }) => assert_eq!(
+            error.to_string(),
+            "'Log.log' is performed, but there is no handler of Log around it"
+        ),
+        other => panic!("Expected an unhandled effect, got {:?}", other),
+    }
+}
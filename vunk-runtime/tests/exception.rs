@@ -0,0 +1,82 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::io::run_main;
+use vunk_runtime::value::Value;
+
+// An action that raises the value when it is run
+fn raising(builtins: &Builtins, value: Value) -> Value {
+    let raise = builtins.value("raise").unwrap();
+    let then = call(builtins, "Std.IO.andThen", vec![raise]);
+    let pure = call(builtins, "Std.IO.pure", vec![value]);
+    apply(&mut BuiltinContext, &then, vec![pure]).unwrap()
+}
+
+fn string(value: &Value) -> &str {
+    match value {
+        Value::Str(s) => s.as_str(),
+        other => panic!("Not a string: {:?}", other),
+    }
+}
+
+#[test]
+fn raised_values_are_uncaught_errors() {
+    let builtins = Builtins::std();
+    let action = raising(&builtins, Value::string("not found"));
+    match run_main(&mut BuiltinContext, &action) {
+        Err(error @ RuntimeError::Raised(_)) => {
+            assert_eq!(error.to_string(), "Uncaught exception: \"not found\"")
+        }
+        other => panic!("Expected an uncaught exception, got {:?}", other),
+    }
+}
+
+#[test]
+fn rescue_passes_raised_values_to_its_handler() {
+    let builtins = Builtins::std();
+    let action = raising(&builtins, Value::string("not found"));
+    let pure = builtins.value("Std.IO.pure").unwrap();
+    let rescued = call(&builtins, "rescue", vec![pure, action]);
+
+    let value = run_main(&mut BuiltinContext, &rescued).unwrap();
+    assert_eq!(string(&value), "not found");
+}
+
+#[test]
+fn rescue_does_not_catch_errors_of_the_runtime() {
+    let builtins = Builtins::std();
+    let panic = builtins.value("Std.Debug.panic").unwrap();
+    let then = call(&builtins, "Std.IO.andThen", vec![panic]);
+    let pure = call(&builtins, "Std.IO.pure", vec![Value::string("boom")]);
+    let action = apply(&mut BuiltinContext, &then, vec![pure]).unwrap();
+    let pure = builtins.value("Std.IO.pure").unwrap();
+    let rescued = call(&builtins, "rescue", vec![pure, action]);
+
+    assert!(matches!(
+        run_main(&mut BuiltinContext, &rescued),
+        Err(RuntimeError::Panic(_))
+    ));
+}
+
+#[test]
+fn raised_values_can_be_turned_into_results() {
+    let builtins = Builtins::std();
+    let action = raising(&builtins, Value::Integer(404));
+    let result = call(&builtins, "Std.Exception.toResult", vec![action]);
+
+    match run_main(&mut BuiltinContext, &result) {
+        Ok(Value::Variant(variant)) => {
+            assert_eq!(variant.name, "Err");
+            assert!(matches!(variant.members[..], [Value::Integer(404)]));
+        }
+        other => panic!("Expected a result, got {:?}", other),
+    }
+}
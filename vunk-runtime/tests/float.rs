@@ -0,0 +1,52 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_runtime::float::parse;
+use vunk_runtime::float::show;
+use vunk_runtime::value::Value;
+
+#[test]
+fn floats_are_shown_with_the_shortest_digits() {
+    assert_eq!(show(0.1), "0.1");
+    assert_eq!(show(0.1 + 0.2), "0.30000000000000004");
+    assert_eq!(show(1.0), "1.0");
+    assert_eq!(show(-0.0), "-0.0");
+    assert_eq!(show(1e20), "1e20");
+    assert_eq!(show(1.5e-7), "1.5e-7");
+    assert_eq!(show(f64::NAN), "NaN");
+    assert_eq!(show(f64::NEG_INFINITY), "-Infinity");
+    assert_eq!(Value::Float(2.5).to_string(), "2.5");
+}
+
+#[test]
+fn shown_floats_parse_to_the_same_float() {
+    let floats = [
+        0.1,
+        0.1 + 0.2,
+        -0.0,
+        1.0 / 3.0,
+        1e300,
+        f64::MAX,
+        f64::MIN_POSITIVE,
+        f64::EPSILON,
+        5e-324,
+        f64::INFINITY,
+    ];
+    for f in floats {
+        let parsed = parse(&show(f)).unwrap();
+        assert_eq!(parsed.to_bits(), f.to_bits(), "{}", show(f));
+    }
+    assert!(parse(&show(f64::NAN)).unwrap().is_nan());
+}
+
+#[test]
+fn only_float_literals_are_parsed() {
+    assert_eq!(parse("3"), Some(3.0));
+    assert_eq!(parse("-2.5E+3"), Some(-2500.0));
+    for invalid in [
+        "", "-", ".5", "5.", "+5", "1e", "inf", "nan", "infinity", " 1.0", "0x10",
+    ] {
+        assert_eq!(parse(invalid), None, "{:?}", invalid);
+    }
+}
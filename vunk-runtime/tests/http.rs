@@ -0,0 +1,137 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "http")]
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::function::Context;
+use vunk_runtime::io::run_main;
+use vunk_runtime::sandbox::Permission;
+use vunk_runtime::sandbox::Sandbox;
+use vunk_runtime::value::Value;
+
+struct SandboxedContext(Sandbox);
+
+impl Context for SandboxedContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        apply(self, function, args)
+    }
+
+    fn sandbox(&self) -> &Sandbox {
+        &self.0
+    }
+}
+
+fn action(builtins: &Builtins, name: &str, args: Vec<Value>) -> Value {
+    let function = builtins.value(name).unwrap();
+    apply(&mut BuiltinContext, &function, args).unwrap()
+}
+
+// Answer one request with a fixed response, returning the request line and body it got
+fn serve(response: &'static str) -> (String, JoinHandle<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/path", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+        (
+            request_line.trim().to_string(),
+            String::from_utf8(body).unwrap(),
+        )
+    });
+    (url, server)
+}
+
+#[test]
+fn responses_are_records() {
+    let builtins = Builtins::std();
+    let (url, server) =
+        serve("HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\nX-Test: a\r\nX-Test: b\r\n\r\nnope");
+    let get = action(&builtins, "Std.Http.get", vec![Value::string(url)]);
+    let response = run_main(&mut SandboxedContext(Sandbox::ALLOW_ALL), &get).unwrap();
+
+    assert_eq!(server.join().unwrap().0, "GET /path HTTP/1.1");
+    let response = match response {
+        Value::Variant(v) if v.name == "Ok" => v.members[0].clone(),
+        other => panic!("No response: {}", other),
+    };
+    let response = match response {
+        Value::Record(record) => record,
+        other => panic!("Not a record: {}", other),
+    };
+    assert_eq!(response.type_name.as_deref(), Some("Response"));
+    assert_eq!(response.get("status").unwrap().to_string(), "404");
+    assert_eq!(response.get("body").unwrap().to_string(), "x\"6e6f7065\"");
+    let headers = response.get("headers").unwrap().to_string();
+    assert!(headers.contains(r#"("x-test", "a, b")"#), "{}", headers);
+}
+
+#[test]
+fn bodies_are_posted() {
+    let builtins = Builtins::std();
+    let (url, server) = serve("HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n");
+    let post = action(
+        &builtins,
+        "Std.Http.post",
+        vec![Value::string(url), Value::string("{}")],
+    );
+    let response = run_main(&mut SandboxedContext(Sandbox::ALLOW_ALL), &post).unwrap();
+
+    let (request_line, body) = server.join().unwrap();
+    assert_eq!(request_line, "POST /path HTTP/1.1");
+    assert_eq!(body, "{}");
+    assert!(response
+        .to_string()
+        .starts_with("Ok Response { body: x\"\""));
+}
+
+#[test]
+fn requests_need_the_network_permission() {
+    let builtins = Builtins::std();
+    let get = action(
+        &builtins,
+        "Std.Http.get",
+        vec![Value::string("http://127.0.0.1:1/")],
+    );
+    let mut ctx = SandboxedContext(Sandbox {
+        network: false,
+        ..Sandbox::ALLOW_ALL
+    });
+    match run_main(&mut ctx, &get) {
+        Err(RuntimeError::PermissionDenied { permission, .. }) => {
+            assert_eq!(permission, Permission::Network)
+        }
+        other => panic!("Expected permission to be denied: {:?}", other),
+    }
+}
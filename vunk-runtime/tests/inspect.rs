@@ -0,0 +1,106 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+
+use vunk_runtime::inspect::inspect;
+use vunk_runtime::inspect::Limits;
+use vunk_runtime::value::Value;
+
+#[test]
+fn values_are_trees() {
+    let mut fields = BTreeMap::new();
+    fields.insert("name".to_string(), Value::string("Ferris"));
+    fields.insert("age".to_string(), Value::Integer(7));
+    let person = Value::record(Some("Person".to_string()), fields);
+
+    let node = inspect(&Value::list(vec![person, Value::none()]), Limits::default());
+    let tree = node.to_string();
+    let lines = tree
+        .lines()
+        .map(|line| line.split(" (").next().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        lines,
+        [
+            "List",
+            "├── [0]: Person",
+            "│   ├── age: Int 7",
+            "│   └── name: String \"Ferris\"",
+            "└── [1]: Option.None",
+        ]
+    );
+}
+
+#[test]
+fn shared_objects_are_numbered_and_shown_once() {
+    let shared = Value::list(vec![Value::Integer(1), Value::Integer(2)]);
+    let node = inspect(
+        &Value::tuple(vec![shared.clone(), shared]),
+        Limits::default(),
+    );
+
+    let (first, second) = (&node.children[0], &node.children[1]);
+    assert_eq!(first.shared, Some(1));
+    assert_eq!(second.shared, Some(1));
+    assert!(!first.repeated);
+    assert!(second.repeated);
+    assert_eq!(first.children.len(), 2);
+    assert!(second.children.is_empty());
+
+    // The shared list is only counted once
+    let tuple = inspect(&Value::tuple(Vec::new()), Limits::default());
+    assert_eq!(second.size, 0);
+    assert_eq!(node.size, tuple.size + first.size);
+}
+
+#[test]
+fn lazy_values_are_not_forced() {
+    let lazy = Value::lazy(Box::new(|| Ok(Value::Integer(42))));
+    let node = inspect(&lazy, Limits::default());
+    assert_eq!(node.label, "Lazy (not evaluated)");
+    assert!(node.children.is_empty());
+
+    lazy.force().unwrap();
+    let node = inspect(&lazy, Limits::default());
+    assert_eq!(node.label, "Lazy");
+    assert_eq!(node.children[0].edge.as_deref(), Some("value"));
+    assert_eq!(node.children[0].label, "Int 42");
+}
+
+#[test]
+fn children_beyond_the_limits_are_collapsed() {
+    let list = Value::list((0..25).map(Value::Integer).collect());
+    let limits = Limits {
+        depth: 4,
+        children: 10,
+    };
+    let node = inspect(&list, limits);
+
+    assert_eq!(node.children.len(), 10);
+    assert_eq!(node.collapsed, 15);
+    assert!(node.to_string().ends_with("└── … 15 more"));
+
+    // Collapsed children still count for the size
+    let shown = inspect(
+        &list,
+        Limits {
+            depth: 4,
+            children: 25,
+        },
+    );
+    assert_eq!(node.size, shown.size);
+
+    let nested = Value::list(vec![list]);
+    let node = inspect(
+        &nested,
+        Limits {
+            depth: 0,
+            children: 10,
+        },
+    );
+    assert!(node.children.is_empty());
+    assert_eq!(node.collapsed, 1);
+}
@@ -0,0 +1,200 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::try_call;
+use num_bigint::BigInt;
+use vunk_runtime::arith;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::value::Value;
+
+// The operands, with their quotient and remainder truncated and floored
+type Division = (i64, i64, (i64, i64), (i64, i64));
+
+const DIVISIONS: [Division; 4] = [
+    (7, 2, (3, 1), (3, 1)),
+    (-7, 2, (-3, -1), (-4, 1)),
+    (7, -2, (-3, 1), (-4, -1)),
+    (-7, -2, (3, -1), (3, -1)),
+];
+
+fn int(value: Result<Value, RuntimeError>) -> i64 {
+    match value {
+        Ok(Value::Integer(i)) => i,
+        other => panic!("Not an integer: {:?}", other),
+    }
+}
+
+#[test]
+fn operators_truncate_and_div_and_mod_are_floored() {
+    let builtins = Builtins::std();
+    for (a, b, truncated, floored) in DIVISIONS {
+        let (a, b) = (Value::Integer(a), Value::Integer(b));
+        let result = |name| int(try_call(&builtins, name, vec![a.clone(), b.clone()]));
+        assert_eq!((result("Std.Int.quot"), result("Std.Int.rem")), truncated);
+        assert_eq!((result("Std.Int.div"), result("Std.Int.mod")), floored);
+        assert_eq!(
+            (int(arith::div(&a, &b)), int(arith::rem(&a, &b))),
+            truncated
+        );
+    }
+}
+
+#[test]
+fn big_integers_are_divided_like_integers() {
+    for (a, b, _, floored) in DIVISIONS {
+        let (a, b) = (
+            Value::big_int(BigInt::from(a)),
+            Value::big_int(BigInt::from(b)),
+        );
+        let result = |op: fn(&Value, &Value) -> Result<Value, RuntimeError>| match op(&a, &b) {
+            Ok(Value::BigInt(i)) => i.to_string(),
+            Ok(Value::Integer(i)) => i.to_string(),
+            other => panic!("Not an integer: {:?}", other),
+        };
+        let expected = (floored.0.to_string(), floored.1.to_string());
+        assert_eq!(
+            (result(arith::div_floor), result(arith::mod_floor)),
+            expected
+        );
+    }
+}
+
+#[test]
+fn floored_division_fails_like_the_operators() {
+    let builtins = Builtins::std();
+    let by_zero = try_call(
+        &builtins,
+        "Std.Int.div",
+        vec![Value::Integer(1), Value::Integer(0)],
+    );
+    assert!(matches!(by_zero, Err(RuntimeError::DivisionByZero)));
+    let overflow = try_call(
+        &builtins,
+        "Std.Int.div",
+        vec![Value::Integer(i64::MIN), Value::Integer(-1)],
+    );
+    assert!(matches!(
+        overflow,
+        Err(RuntimeError::IntegerOverflow { op: "div" })
+    ));
+}
+
+#[test]
+fn remainders_of_the_minimum_by_minus_one_are_zero() {
+    let builtins = Builtins::std();
+    let (min, minus_one) = (Value::Integer(i64::MIN), Value::Integer(-1));
+    assert_eq!(int(arith::rem(&min, &minus_one)), 0);
+    assert_eq!(
+        int(try_call(
+            &builtins,
+            "Std.Int.rem",
+            vec![min.clone(), minus_one.clone()]
+        )),
+        0
+    );
+    assert_eq!(
+        int(try_call(
+            &builtins,
+            "Std.Int.mod",
+            vec![min.clone(), minus_one.clone()]
+        )),
+        0
+    );
+    assert!(matches!(
+        arith::div(&min, &minus_one),
+        Err(RuntimeError::IntegerOverflow { op: "/" })
+    ));
+}
+
+#[test]
+fn big_results_that_fit_are_ints() {
+    let big = |i: i64| Value::big_int(BigInt::from(i));
+    assert_eq!(int(arith::add(&Value::Integer(1), &big(1))), 2);
+    assert_eq!(int(arith::sub(&big(5), &big(4))), 1);
+    assert_eq!(
+        int(arith::neg(&Value::big_int(-BigInt::from(i64::MIN)))),
+        i64::MIN
+    );
+
+    let beyond = arith::add(&Value::Integer(i64::MAX), &big(1));
+    assert!(matches!(beyond, Ok(Value::BigInt(i)) if **i == BigInt::from(i64::MAX) + 1));
+}
+
+#[test]
+fn operators_fail_exactly_beyond_the_bounds() {
+    let (min, max) = (Value::Integer(i64::MIN), Value::Integer(i64::MAX));
+    let (one, minus_one) = (Value::Integer(1), Value::Integer(-1));
+
+    assert_eq!(
+        int(arith::add(&Value::Integer(i64::MAX - 1), &one)),
+        i64::MAX
+    );
+    assert!(matches!(
+        arith::add(&max, &one),
+        Err(RuntimeError::IntegerOverflow { op: "+" })
+    ));
+    assert_eq!(
+        int(arith::sub(&Value::Integer(i64::MIN + 1), &one)),
+        i64::MIN
+    );
+    assert!(matches!(
+        arith::sub(&min, &one),
+        Err(RuntimeError::IntegerOverflow { op: "-" })
+    ));
+    assert_eq!(int(arith::mul(&max, &minus_one)), -i64::MAX);
+    assert!(matches!(
+        arith::mul(&min, &minus_one),
+        Err(RuntimeError::IntegerOverflow { op: "*" })
+    ));
+    assert_eq!(int(arith::neg(&Value::Integer(i64::MIN + 1))), i64::MAX);
+    assert!(matches!(
+        arith::neg(&min),
+        Err(RuntimeError::IntegerOverflow { op: "-" })
+    ));
+    assert!(matches!(
+        arith::div(&one, &Value::Integer(0)),
+        Err(RuntimeError::DivisionByZero)
+    ));
+}
+
+#[test]
+fn wrapping_functions_wrap_around() {
+    let builtins = Builtins::std();
+    let wrapping = |name, a, b| {
+        int(try_call(
+            &builtins,
+            name,
+            vec![Value::Integer(a), Value::Integer(b)],
+        ))
+    };
+    assert_eq!(wrapping("Std.Int.wrappingAdd", i64::MAX, 1), i64::MIN);
+    assert_eq!(wrapping("Std.Int.wrappingAdd", i64::MAX - 1, 1), i64::MAX);
+    assert_eq!(wrapping("Std.Int.wrappingSub", i64::MIN, 1), i64::MAX);
+    assert_eq!(wrapping("Std.Int.wrappingMul", i64::MIN, -1), i64::MIN);
+    assert_eq!(wrapping("Std.Int.wrappingMul", i64::MAX, 2), -2);
+}
+
+#[test]
+fn checked_functions_give_none_beyond_the_bounds() {
+    let builtins = Builtins::std();
+    let checked = |name, a, b| {
+        try_call(&builtins, name, vec![Value::Integer(a), Value::Integer(b)])
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(
+        checked("Std.Int.checkedAdd", i64::MAX - 1, 1),
+        format!("Some {}", i64::MAX)
+    );
+    assert_eq!(checked("Std.Int.checkedAdd", i64::MAX, 1), "None");
+    assert_eq!(checked("Std.Int.checkedSub", i64::MIN, 1), "None");
+    assert_eq!(checked("Std.Int.checkedMul", i64::MAX, 2), "None");
+    assert_eq!(checked("Std.Int.checkedDiv", i64::MIN, -1), "None");
+    assert_eq!(checked("Std.Int.checkedDiv", 1, 0), "None");
+    assert_eq!(checked("Std.Int.checkedRem", 1, 0), "None");
+    assert_eq!(checked("Std.Int.checkedRem", i64::MIN, -1), "Some 0");
+}
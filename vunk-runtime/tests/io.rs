@@ -0,0 +1,135 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::function::Context;
+use vunk_runtime::io::run_main;
+use vunk_runtime::sandbox::Permission;
+use vunk_runtime::sandbox::Sandbox;
+use vunk_runtime::value::Value;
+
+struct SandboxedContext(Sandbox);
+
+impl Context for SandboxedContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        apply(self, function, args)
+    }
+
+    fn sandbox(&self) -> &Sandbox {
+        &self.0
+    }
+}
+
+fn action(builtins: &Builtins, name: &str, args: Vec<Value>) -> Value {
+    let function = builtins.value(name).unwrap();
+    apply(&mut BuiltinContext, &function, args).unwrap()
+}
+
+fn string(value: &Value) -> String {
+    match value {
+        Value::Str(s) => s.as_str().to_string(),
+        other => panic!("Not a string: {:?}", other),
+    }
+}
+
+#[test]
+fn write_append_and_read_file() {
+    let builtins = Builtins::std();
+    let path = std::env::temp_dir().join(format!("vunk-io-test-{}", std::process::id()));
+    let path = Value::string(path.to_str().unwrap());
+    let mut ctx = SandboxedContext(Sandbox::ALLOW_ALL);
+
+    let write = action(
+        &builtins,
+        "Std.IO.writeFile",
+        vec![path.clone(), Value::string("Hello")],
+    );
+    run_main(&mut ctx, &write).unwrap();
+
+    let append = action(
+        &builtins,
+        "Std.IO.appendFile",
+        vec![path.clone(), Value::string(", World")],
+    );
+    run_main(&mut ctx, &append).unwrap();
+
+    let read = action(&builtins, "Std.IO.readFile", vec![path.clone()]);
+    let contents = run_main(&mut ctx, &read).unwrap();
+    std::fs::remove_file(string(&path)).unwrap();
+
+    assert_eq!(string(&contents), "Hello, World");
+}
+
+#[test]
+fn file_access_is_denied_by_sandbox() {
+    let builtins = Builtins::std();
+    let read = action(
+        &builtins,
+        "Std.IO.readFile",
+        vec![Value::string("/does/not/matter")],
+    );
+
+    let mut ctx = SandboxedContext(Sandbox {
+        read_files: false,
+        ..Sandbox::ALLOW_ALL
+    });
+    match run_main(&mut ctx, &read) {
+        Err(RuntimeError::PermissionDenied { permission, .. }) => {
+            assert_eq!(permission, Permission::ReadFiles)
+        }
+        other => panic!("Expected permission to be denied: {:?}", other),
+    }
+
+    assert!(matches!(
+        run_main(&mut BuiltinContext, &read),
+        Err(RuntimeError::PermissionDenied { .. })
+    ));
+}
+
+struct ArgsContext(Vec<String>);
+
+impl Context for ArgsContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        apply(self, function, args)
+    }
+
+    fn program_args(&self) -> &[String] {
+        &self.0
+    }
+}
+
+#[test]
+fn program_args() {
+    let builtins = Builtins::std();
+    let args = action(&builtins, "Std.Env.args", vec![]);
+    let mut ctx = ArgsContext(vec!["-v".to_string(), "input".to_string()]);
+
+    match run_main(&mut ctx, &args).unwrap() {
+        Value::List(list) => {
+            let args = list.iter().map(string).collect::<Vec<_>>();
+            assert_eq!(args, vec!["-v", "input"]);
+        }
+        other => panic!("Not a list: {:?}", other),
+    }
+}
+
+#[test]
+fn exit_stops_the_program() {
+    let builtins = Builtins::std();
+    let exit = action(&builtins, "Std.Env.exit", vec![Value::Integer(3)]);
+    let never = action(&builtins, "Std.IO.readFile", vec![Value::string("unused")]);
+    let main = action(
+        &builtins,
+        "Std.IO.sequence",
+        vec![Value::list(vec![exit, never])],
+    );
+
+    assert!(matches!(
+        run_main(&mut BuiltinContext, &main),
+        Err(RuntimeError::Exit(3))
+    ));
+}
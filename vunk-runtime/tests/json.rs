@@ -0,0 +1,76 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::value::Value;
+
+fn string(value: &Value) -> String {
+    match value {
+        Value::Str(s) => s.as_str().to_string(),
+        other => panic!("Not a string: {:?}", other),
+    }
+}
+
+// Parse a document and return the Ok or Err value
+fn parse(builtins: &Builtins, input: &str) -> Result<Value, String> {
+    match call(builtins, "Std.Json.parse", vec![Value::string(input)]) {
+        Value::Variant(result) if result.name == "Ok" => Ok(result.members[0].clone()),
+        Value::Variant(result) if result.name == "Err" => Err(string(&result.members[0])),
+        other => panic!("Not a result: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_and_encode_round_trip() {
+    let builtins = Builtins::std();
+    let input = r#"{"a":[1,-2.5,1.0e3,true,false,null],"b":"x\"\n\u00e4\ud83d\ude00","c":{}}"#;
+    let json = parse(&builtins, input).unwrap();
+    let encoded = string(&call(&builtins, "Std.Json.encode", vec![json]));
+    assert_eq!(
+        encoded,
+        "{\"a\":[1,-2.5,1000.0,true,false,null],\"b\":\"x\\\"\\n\u{e4}\u{1f600}\",\"c\":{}}"
+    );
+}
+
+#[test]
+fn whitespace_is_allowed_between_tokens() {
+    let builtins = Builtins::std();
+    let json = parse(&builtins, " [ 1 , { \"k\" : [ ] } ]\n").unwrap();
+    let encoded = string(&call(&builtins, "Std.Json.encode", vec![json]));
+    assert_eq!(encoded, r#"[1,{"k":[]}]"#);
+}
+
+#[test]
+fn invalid_documents_are_errors() {
+    let builtins = Builtins::std();
+    for input in [
+        "",
+        "[1,]",
+        "{\"a\" 1}",
+        "tru",
+        "\"\\x\"",
+        "1 2",
+        "\"\\ud800\"",
+        "-",
+    ] {
+        assert!(parse(&builtins, input).is_err(), "{:?} was accepted", input);
+    }
+}
+
+#[test]
+fn build_json_with_constructors() {
+    let builtins = Builtins::std();
+    let one = call(&builtins, "Std.Json.Int", vec![Value::Integer(1)]);
+    let null = call(&builtins, "Std.Json.Null", vec![]);
+    let array = call(
+        &builtins,
+        "Std.Json.Array",
+        vec![Value::list(vec![one, null])],
+    );
+    let encoded = string(&call(&builtins, "Std.Json.encode", vec![array]));
+    assert_eq!(encoded, "[1,null]");
+}
@@ -0,0 +1,121 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::value::Value;
+
+fn ints(values: &[i64]) -> Value {
+    Value::list(values.iter().copied().map(Value::Integer).collect())
+}
+
+fn to_ints(value: &Value) -> Vec<i64> {
+    match value {
+        Value::List(list) => list
+            .iter()
+            .map(|element| match element {
+                Value::Integer(i) => *i,
+                other => panic!("Not an integer: {:?}", other),
+            })
+            .collect(),
+        other => panic!("Not a list: {:?}", other),
+    }
+}
+
+#[test]
+fn map_with_partially_applied_builtin() {
+    let builtins = Builtins::std();
+    let add_one = call(&builtins, "Std.Int.wrappingAdd", vec![Value::Integer(1)]);
+    let mapped = call(&builtins, "Std.List.map", vec![add_one, ints(&[1, 2, 3])]);
+    assert_eq!(to_ints(&mapped), vec![2, 3, 4]);
+}
+
+#[test]
+fn filter() {
+    let mut builtins = Builtins::std();
+    builtins.register("isEven", 1, |_, args| match args[0] {
+        Value::Integer(i) => Ok(Value::Bool(i % 2 == 0)),
+        _ => unreachable!(),
+    });
+    let is_even = builtins.value("isEven").unwrap();
+    let filtered = call(
+        &builtins,
+        "Std.List.filter",
+        vec![is_even, ints(&[1, 2, 3, 4])],
+    );
+    assert_eq!(to_ints(&filtered), vec![2, 4]);
+}
+
+#[test]
+fn fold() {
+    let builtins = Builtins::std();
+    let add = builtins.value("Std.Int.wrappingAdd").unwrap();
+    let sum = call(
+        &builtins,
+        "Std.List.fold",
+        vec![add, Value::Integer(0), ints(&[1, 2, 3, 4])],
+    );
+    assert!(matches!(sum, Value::Integer(10)));
+}
+
+#[test]
+fn length_reverse_sort() {
+    let builtins = Builtins::std();
+    let list = ints(&[3, 1, 2]);
+
+    let length = call(&builtins, "Std.List.length", vec![list.clone()]);
+    assert!(matches!(length, Value::Integer(3)));
+
+    let reversed = call(&builtins, "Std.List.reverse", vec![list.clone()]);
+    assert_eq!(to_ints(&reversed), vec![2, 1, 3]);
+
+    let sorted = call(&builtins, "Std.List.sort", vec![list.clone()]);
+    assert_eq!(to_ints(&sorted), vec![1, 2, 3]);
+
+    // copy-on-write: the original list is untouched
+    assert_eq!(to_ints(&list), vec![3, 1, 2]);
+}
+
+#[test]
+fn take_drop() {
+    let builtins = Builtins::std();
+    let list = ints(&[1, 2, 3, 4]);
+
+    let taken = call(
+        &builtins,
+        "Std.List.take",
+        vec![Value::Integer(2), list.clone()],
+    );
+    assert_eq!(to_ints(&taken), vec![1, 2]);
+
+    let dropped = call(&builtins, "Std.List.drop", vec![Value::Integer(3), list]);
+    assert_eq!(to_ints(&dropped), vec![4]);
+}
+
+#[test]
+fn zip() {
+    let builtins = Builtins::std();
+    let zipped = call(
+        &builtins,
+        "Std.List.zip",
+        vec![ints(&[1, 2, 3]), ints(&[4, 5])],
+    );
+    match zipped {
+        Value::List(pairs) => assert_eq!(pairs.len(), 2),
+        other => panic!("Not a list: {:?}", other),
+    }
+}
+
+#[test]
+fn concat_map() {
+    let mut builtins = Builtins::std();
+    builtins.register("twice", 1, |_, args| {
+        Ok(Value::list(vec![args[0].clone(), args[0].clone()]))
+    });
+    let twice = builtins.value("twice").unwrap();
+    let mapped = call(&builtins, "Std.List.concatMap", vec![twice, ints(&[1, 2])]);
+    assert_eq!(to_ints(&mapped), vec![1, 1, 2, 2]);
+}
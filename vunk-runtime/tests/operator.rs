@@ -0,0 +1,110 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+
+use vunk_runtime::builtin::int_arg;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::operator::binary;
+use vunk_runtime::operator::Instances;
+use vunk_runtime::value::Value;
+
+fn point(x: i64) -> Value {
+    Value::record(
+        Some("Point".to_string()),
+        BTreeMap::from([("x".to_string(), Value::Integer(x))]),
+    )
+}
+
+// `impl Ord on Point`, comparing the `x` of the points
+fn instances() -> Instances {
+    let mut builtins = Builtins::default();
+    builtins.register("Point.compare", 2, |_, args| {
+        let x = |point: &Value| match point {
+            Value::Record(record) => int_arg("Point.compare", record.get("x").unwrap()),
+            _ => unreachable!(),
+        };
+        let name = match x(&args[0])?.cmp(&x(&args[1])?) {
+            std::cmp::Ordering::Less => "Less",
+            std::cmp::Ordering::Equal => "Equal",
+            std::cmp::Ordering::Greater => "Greater",
+        };
+        Ok(Value::variant("Ordering", name, Vec::new()))
+    });
+
+    let mut instances = Instances::default();
+    let compare = builtins.value("Point.compare").unwrap();
+    instances.register("Ord", "Point", [("compare".to_string(), compare)]);
+    instances
+}
+
+#[test]
+fn primitives_have_built_in_instances() {
+    let instances = Instances::default();
+    let apply = |op, lhs: Value, rhs: Value| {
+        binary(&mut BuiltinContext, &instances, op, &lhs, &rhs).unwrap()
+    };
+
+    assert!(matches!(
+        apply("+", Value::Integer(1), Value::Integer(2)),
+        Value::Integer(3)
+    ));
+    assert!(matches!(
+        apply("<=", Value::Integer(2), Value::Integer(2)),
+        Value::Bool(true)
+    ));
+    match apply("++", Value::string("vu"), Value::string("nk")) {
+        Value::Str(s) => assert_eq!(s.as_str(), "vunk"),
+        other => panic!("Expected a string, got {:?}", other),
+    }
+}
+
+#[test]
+fn operators_on_user_types_use_their_instance() {
+    let instances = instances();
+    let apply = |op, lhs: i64, rhs: i64| {
+        binary(
+            &mut BuiltinContext,
+            &instances,
+            op,
+            &point(lhs),
+            &point(rhs),
+        )
+        .unwrap()
+    };
+
+    assert!(matches!(apply("<", 1, 2), Value::Bool(true)));
+    assert!(matches!(apply(">=", 1, 2), Value::Bool(false)));
+}
+
+#[test]
+fn impls_without_a_method_use_the_default() {
+    let mut builtins = Builtins::default();
+    builtins.register("Eq.eq", 2, |_, _| Ok(Value::Bool(true)));
+
+    let mut instances = Instances::default();
+    let eq = builtins.value("Eq.eq").unwrap();
+    instances.register_defaults("Eq", [("eq".to_string(), eq)]);
+    assert!(binary(&mut BuiltinContext, &instances, "==", &point(1), &point(2)).is_err());
+
+    instances.register("Eq", "Point", []);
+    assert!(matches!(
+        binary(&mut BuiltinContext, &instances, "==", &point(1), &point(2)),
+        Ok(Value::Bool(true))
+    ));
+}
+
+#[test]
+fn operators_without_an_instance_fail() {
+    let result = binary(&mut BuiltinContext, &instances(), "+", &point(1), &point(2));
+    match result {
+        Err(error @ RuntimeError::NoInstance { .. }) => assert_eq!(
+            error.to_string(),
+            "'+' needs an instance of Num for Point, like 'impl Num on Point'"
+        ),
+        other => panic!("Expected a missing instance, got {:?}", other),
+    }
+}
@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::show;
+use common::try_call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::value::Value;
+
+#[test]
+fn options_are_inspected() {
+    let builtins = Builtins::std();
+    let some = Value::some(Value::Integer(1));
+    assert_eq!(
+        show(&builtins, "Std.Option.isSome", vec![some.clone()]),
+        "true"
+    );
+    assert_eq!(show(&builtins, "Std.Option.isNone", vec![some]), "false");
+    assert_eq!(
+        show(&builtins, "Std.Option.isNone", vec![Value::none()]),
+        "true"
+    );
+    assert_eq!(show(&builtins, "Some", vec![Value::Integer(2)]), "Some 2");
+}
+
+#[test]
+fn functions_are_only_applied_to_some() {
+    let builtins = Builtins::std();
+    let increment = try_call(&builtins, "Std.Int.wrappingAdd", vec![Value::Integer(1)]).unwrap();
+    let some = Value::some(Value::Integer(1));
+    assert_eq!(
+        show(&builtins, "Std.Option.map", vec![increment.clone(), some]),
+        "Some 2"
+    );
+    assert_eq!(
+        show(&builtins, "Std.Option.map", vec![increment, Value::none()]),
+        "None"
+    );
+
+    let parse = builtins.value("Std.String.parseInt").unwrap();
+    let and_then = |option| show(&builtins, "Std.Option.andThen", vec![parse.clone(), option]);
+    assert_eq!(and_then(Value::some(Value::string("3"))), "Some 3");
+    assert_eq!(and_then(Value::some(Value::string("three"))), "None");
+    assert_eq!(and_then(Value::none()), "None");
+}
+
+#[test]
+fn options_are_unwrapped_and_turned_into_results() {
+    let builtins = Builtins::std();
+    let some = Value::some(Value::Integer(1));
+    let default = Value::Integer(0);
+    assert_eq!(
+        show(
+            &builtins,
+            "Std.Option.unwrapOr",
+            vec![default.clone(), some.clone()]
+        ),
+        "1"
+    );
+    assert_eq!(
+        show(
+            &builtins,
+            "Std.Option.unwrapOr",
+            vec![default.clone(), Value::none()]
+        ),
+        "0"
+    );
+    assert_eq!(
+        show(&builtins, "Std.Option.okOr", vec![default.clone(), some]),
+        "Ok 1"
+    );
+    assert_eq!(
+        show(&builtins, "Std.Option.okOr", vec![default, Value::none()]),
+        "Err 0"
+    );
+}
+
+#[test]
+fn other_values_are_rejected() {
+    let builtins = Builtins::std();
+    let result = Value::ok(Value::Integer(1));
+    assert!(matches!(
+        try_call(&builtins, "Std.Option.isSome", vec![result]),
+        Err(RuntimeError::InvalidArgument { .. })
+    ));
+}
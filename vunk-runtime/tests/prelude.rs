@@ -0,0 +1,15 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_runtime::builtin::Builtins;
+
+#[test]
+fn prelude_items_are_members_of_the_prelude_module() {
+    let builtins = Builtins::std();
+    for name in ["Some", "Std.Prelude.Some", "Std.Option.Some"] {
+        assert_eq!(builtins.get(name).unwrap().name, "Std.Option.Some");
+    }
+    assert!(builtins.get("Std.Prelude.isSome").is_none());
+    assert!(builtins.get("Std.PreludeSome").is_none());
+}
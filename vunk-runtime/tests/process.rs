@@ -0,0 +1,111 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![cfg(unix)]
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::function::Context;
+use vunk_runtime::io::run_main;
+use vunk_runtime::io::Io;
+use vunk_runtime::sandbox::Permission;
+use vunk_runtime::sandbox::Sandbox;
+use vunk_runtime::value::Value;
+
+struct SandboxedContext(Sandbox);
+
+impl Context for SandboxedContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        apply(self, function, args)
+    }
+
+    fn sandbox(&self) -> &Sandbox {
+        &self.0
+    }
+}
+
+fn action(builtins: &Builtins, name: &str, args: Vec<Value>) -> Value {
+    let function = builtins.value(name).unwrap();
+    apply(&mut BuiltinContext, &function, args).unwrap()
+}
+
+fn shell(script: &str) -> Vec<Value> {
+    vec![
+        Value::string("sh"),
+        Value::list(vec![Value::string("-c"), Value::string(script)]),
+    ]
+}
+
+fn run(action: &Value) -> Result<Value, RuntimeError> {
+    run_main(&mut SandboxedContext(Sandbox::ALLOW_ALL), action)
+}
+
+#[test]
+fn output_and_status_are_captured() {
+    let builtins = Builtins::std();
+    let script = shell("echo out; echo err >&2; exit 3");
+    let output = run(&action(&builtins, "Std.Process.run", script)).unwrap();
+    assert_eq!(
+        output.to_string(),
+        r#"Output { status: Some 3, stderr: "err\n", stdout: "out\n" }"#
+    );
+
+    let mut args = shell("tr a-z A-Z");
+    args.push(Value::string("shout"));
+    let output = run(&action(&builtins, "Std.Process.runWithInput", args)).unwrap();
+    assert_eq!(
+        output.to_string(),
+        r#"Output { status: Some 0, stderr: "", stdout: "SHOUT" }"#
+    );
+}
+
+#[test]
+fn missing_programs_fail() {
+    let builtins = Builtins::std();
+    let args = vec![Value::string("/does/not/exist"), Value::list(Vec::new())];
+    let result = run(&action(&builtins, "Std.Process.run", args));
+    assert!(matches!(result, Err(RuntimeError::Io { .. })));
+}
+
+#[test]
+fn lines_are_streamed_to_an_action() {
+    let mut builtins = Builtins::std();
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let collected = lines.clone();
+    builtins.register("collect", 1, move |_, args| {
+        let line = args[0].to_string();
+        let lines = collected.clone();
+        Ok(Value::io(Io::new(move |_| {
+            lines.lock().unwrap().push(line.clone());
+            Ok(Value::Unit)
+        })))
+    });
+
+    let mut args = shell("printf 'a\\nb\\r\\nc'; exit 1");
+    args.push(builtins.value("collect").unwrap());
+    let status = run(&action(&builtins, "Std.Process.stream", args)).unwrap();
+    assert_eq!(status.to_string(), "Some 1");
+    assert_eq!(*lines.lock().unwrap(), vec![r#""a""#, r#""b""#, r#""c""#]);
+}
+
+#[test]
+fn running_programs_needs_permission() {
+    let builtins = Builtins::std();
+    let run = action(&builtins, "Std.Process.run", shell("true"));
+    let mut ctx = SandboxedContext(Sandbox {
+        run_processes: false,
+        ..Sandbox::ALLOW_ALL
+    });
+    match run_main(&mut ctx, &run) {
+        Err(RuntimeError::PermissionDenied { permission, .. }) => {
+            assert_eq!(permission, Permission::RunProcesses)
+        }
+        other => panic!("Expected permission to be denied: {:?}", other),
+    }
+}
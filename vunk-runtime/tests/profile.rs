@@ -0,0 +1,67 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vunk_runtime::profile::Profile;
+use vunk_runtime::value::Value;
+
+#[test]
+fn calls_are_counted_per_function() {
+    let profile = Profile::default();
+    profile.enter("main");
+    for _ in 0..3 {
+        profile.enter("Std.List.map");
+        profile.enter("double");
+        profile.exit();
+        profile.exit();
+    }
+    profile.exit();
+
+    let functions = profile.functions();
+    assert_eq!(functions.len(), 3);
+    assert_eq!(functions["main"].calls, 1);
+    assert_eq!(functions["Std.List.map"].calls, 3);
+    assert_eq!(functions["double"].calls, 3);
+
+    let main = functions["main"];
+    assert!(main.total >= functions["Std.List.map"].total);
+    assert!(main.own <= main.total);
+}
+
+#[test]
+fn allocations_are_attributed_to_the_function_making_them() {
+    let profile = Profile::default();
+    profile.enter("outer");
+    let _outer = Value::string("outer");
+    profile.enter("inner");
+    let _inner = (0..4)
+        .map(|i| Value::string(i.to_string()))
+        .collect::<Vec<_>>();
+    profile.exit();
+    profile.exit();
+
+    // Tests running at the same time allocate too, so these are lower bounds
+    let functions = profile.functions();
+    assert!(functions["inner"].own_allocated >= 4);
+    assert!(functions["outer"].allocated >= 5);
+    assert!(functions["outer"].own_allocated >= 1);
+}
+
+#[test]
+fn recursion_is_counted_once_in_the_total() {
+    let profile = Profile::default();
+    profile.enter("loop");
+    profile.enter("loop");
+    profile.exit();
+    profile.exit();
+
+    let functions = profile.functions();
+    let collapsed = profile.collapsed();
+    let stacks = collapsed
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(functions["loop"].calls, 2);
+    assert_eq!(functions["loop"].total, functions["loop"].own);
+    assert_eq!(stacks, ["loop", "loop;loop"]);
+}
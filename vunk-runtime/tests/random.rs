@@ -0,0 +1,137 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::try_call;
+use vunk_runtime::builtin::io_arg;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::value::Value;
+
+fn seed(builtins: &Builtins, n: i64) -> Value {
+    try_call(builtins, "Std.Random.Seed.new", vec![Value::Integer(n)]).unwrap()
+}
+
+// Call a pure function, returning its result and the next seed
+fn generate(builtins: &Builtins, name: &str, mut args: Vec<Value>, seed: Value) -> (Value, Value) {
+    args.push(seed);
+    match try_call(builtins, name, args).unwrap() {
+        Value::Tuple(tuple) => (tuple.0[0].clone(), tuple.0[1].clone()),
+        other => panic!("Not a tuple: {}", other),
+    }
+}
+
+fn ints(builtins: &Builtins, low: i64, high: i64, n: usize, mut seed: Value) -> Vec<i64> {
+    (0..n)
+        .map(|_| {
+            let args = vec![Value::Integer(low), Value::Integer(high)];
+            let (value, next) = generate(builtins, "Std.Random.Seed.int", args, seed.clone());
+            seed = next;
+            match value {
+                Value::Integer(i) => i,
+                other => panic!("Not an Int: {}", other),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn the_same_seed_gives_the_same_numbers() {
+    let builtins = Builtins::std();
+    let a = ints(&builtins, 1, 6, 100, seed(&builtins, 42));
+    let b = ints(&builtins, 1, 6, 100, seed(&builtins, 42));
+    let c = ints(&builtins, 1, 6, 100, seed(&builtins, 43));
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+
+    assert!(a.iter().all(|i| (1..=6).contains(i)));
+    for face in 1..=6 {
+        assert!(a.contains(&face), "{} is never rolled", face);
+    }
+}
+
+#[test]
+fn ranges_are_inclusive_and_can_span_all_ints() {
+    let builtins = Builtins::std();
+    assert_eq!(ints(&builtins, 7, 7, 10, seed(&builtins, 1)), vec![7; 10]);
+    ints(&builtins, i64::MIN, i64::MAX, 10, seed(&builtins, 1));
+
+    let empty = try_call(
+        &builtins,
+        "Std.Random.Seed.int",
+        vec![Value::Integer(2), Value::Integer(1), seed(&builtins, 1)],
+    );
+    assert!(matches!(empty, Err(RuntimeError::InvalidArgument { .. })));
+}
+
+#[test]
+fn floats_are_below_high() {
+    let builtins = Builtins::std();
+    let mut s = seed(&builtins, 7);
+    for _ in 0..100 {
+        let args = vec![Value::Float(-1.0), Value::Float(1.0)];
+        let (value, next) = generate(&builtins, "Std.Random.Seed.float", args, s);
+        s = next;
+        match value {
+            Value::Float(x) => assert!((-1.0..1.0).contains(&x), "{}", x),
+            other => panic!("Not a Float: {}", other),
+        }
+    }
+}
+
+#[test]
+fn shuffles_keep_the_elements() {
+    let builtins = Builtins::std();
+    let list = Value::list((0..20).map(Value::Integer).collect());
+    let (shuffled, s) = generate(
+        &builtins,
+        "Std.Random.Seed.shuffle",
+        vec![list.clone()],
+        seed(&builtins, 3),
+    );
+    assert_ne!(shuffled.to_string(), list.to_string());
+    let mut elements = match shuffled {
+        Value::List(list) => list.iter().map(Value::to_string).collect::<Vec<_>>(),
+        other => panic!("Not a list: {}", other),
+    };
+    elements.sort_by_key(|e| e.parse::<i64>().unwrap());
+    assert_eq!(format!("[{}]", elements.join(" ")), list.to_string());
+
+    let (chosen, _) = generate(&builtins, "Std.Random.Seed.choice", vec![list], s.clone());
+    assert!(chosen.to_string().starts_with("Some "));
+    let (none, _) = generate(
+        &builtins,
+        "Std.Random.Seed.choice",
+        vec![Value::list(Vec::new())],
+        s,
+    );
+    assert_eq!(none.to_string(), "None");
+}
+
+#[test]
+fn actions_use_the_generator_of_the_process() {
+    let builtins = Builtins::std();
+    let roll = try_call(
+        &builtins,
+        "Std.Random.int",
+        vec![Value::Integer(1), Value::Integer(6)],
+    )
+    .unwrap();
+    let roll = io_arg("roll", &roll).unwrap();
+    for _ in 0..10 {
+        match roll.run(&mut BuiltinContext).unwrap() {
+            Value::Integer(i) => assert!((1..=6).contains(&i)),
+            other => panic!("Not an Int: {}", other),
+        }
+    }
+
+    let seed = try_call(&builtins, "Std.Random.seed", Vec::new()).unwrap();
+    let seed = io_arg("seed", &seed)
+        .unwrap()
+        .run(&mut BuiltinContext)
+        .unwrap();
+    assert_eq!(seed.type_name(), "Seed");
+}
@@ -0,0 +1,122 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::value::Value;
+
+fn compile(builtins: &Builtins, pattern: &str) -> Value {
+    let result = call(builtins, "Std.Regex.compile", vec![Value::string(pattern)]);
+    match result {
+        Value::Variant(v) if v.name == "Ok" => v.members[0].clone(),
+        other => panic!("Not compiled: {}", other),
+    }
+}
+
+fn apply_regex(builtins: &Builtins, name: &str, pattern: &str, s: &str) -> String {
+    let regex = compile(builtins, pattern);
+    call(builtins, name, vec![regex, Value::string(s)]).to_string()
+}
+
+#[test]
+fn invalid_patterns_are_errors() {
+    let builtins = Builtins::std();
+    let result = call(&builtins, "Std.Regex.compile", vec![Value::string("(a")]);
+    match result {
+        Value::Variant(v) => assert_eq!(v.name, "Err"),
+        other => panic!("Not a result: {}", other),
+    }
+}
+
+#[test]
+fn matches_are_found() {
+    let builtins = Builtins::std();
+    let regex = compile(&builtins, r"\d+");
+    assert_eq!(regex.type_name(), "Regex");
+    assert_eq!(
+        call(
+            &builtins,
+            "Std.Regex.isMatch",
+            vec![regex, Value::string("a1")]
+        )
+        .to_string(),
+        "true"
+    );
+
+    assert_eq!(
+        apply_regex(&builtins, "Std.Regex.find", r"\d+", "ab 12 345"),
+        r#"Some { end = 5, start = 3, text = "12" }"#
+    );
+    assert_eq!(
+        apply_regex(&builtins, "Std.Regex.find", r"\d+", "ab"),
+        "None"
+    );
+    assert_eq!(
+        apply_regex(&builtins, "Std.Regex.findAll", r"\d+", "ab 12 345"),
+        r#"[{ end = 5, start = 3, text = "12" } { end = 9, start = 6, text = "345" }]"#
+    );
+}
+
+#[test]
+fn offsets_count_scalar_values() {
+    let builtins = Builtins::std();
+    assert_eq!(
+        apply_regex(&builtins, "Std.Regex.findAll", "b", "äbüb"),
+        r#"[{ end = 2, start = 1, text = "b" } { end = 4, start = 3, text = "b" }]"#
+    );
+}
+
+#[test]
+fn named_groups_are_captured_as_records() {
+    let builtins = Builtins::std();
+    let date = r"(?P<year>\d{4})-(?P<month>\d{2})(-(?P<day>\d{2}))?";
+    assert_eq!(
+        apply_regex(&builtins, "Std.Regex.captures", date, "on 2024-05"),
+        r#"Some { day = None, month = Some "05", year = Some "2024" }"#
+    );
+    assert_eq!(
+        apply_regex(&builtins, "Std.Regex.capturesAll", r"(?P<n>\d)", "1 2"),
+        r#"[{ n = Some "1" } { n = Some "2" }]"#
+    );
+    assert_eq!(
+        apply_regex(&builtins, "Std.Regex.groups", r"(\d)(x)?", "a1"),
+        r#"Some [Some "1" Some "1" None]"#
+    );
+}
+
+#[test]
+fn replacements_refer_to_groups() {
+    let builtins = Builtins::std();
+    let regex = compile(&builtins, r"(?P<k>\w+)=(\w+)");
+    let replace = |name: &str| {
+        let args = vec![
+            regex.clone(),
+            Value::string("$2:${k}"),
+            Value::string("a=1 b=2"),
+        ];
+        call(&builtins, name, args).to_string()
+    };
+    assert_eq!(replace("Std.Regex.replace"), r#""1:a b=2""#);
+    assert_eq!(replace("Std.Regex.replaceAll"), r#""1:a 2:b""#);
+
+    assert_eq!(
+        apply_regex(&builtins, "Std.Regex.split", r",\s*", "a, b,c"),
+        r#"["a" "b" "c"]"#
+    );
+}
+
+#[test]
+fn compiled_patterns_are_cached() {
+    let builtins = Builtins::std();
+    let a = compile(&builtins, "cached+");
+    let b = compile(&builtins, "cached+");
+    let cmp = call(&builtins, "Std.Cmp.equal", vec![a, b.clone()]);
+    assert_eq!(cmp.to_string(), "true");
+    assert_eq!(
+        call(&builtins, "Std.Regex.pattern", vec![b]).to_string(),
+        r#""cached+""#
+    );
+}
@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::show;
+use common::try_call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::value::Value;
+
+fn ok(i: i64) -> Value {
+    Value::ok(Value::Integer(i))
+}
+
+fn err(i: i64) -> Value {
+    Value::err(Value::Integer(i))
+}
+
+#[test]
+fn results_are_inspected() {
+    let builtins = Builtins::std();
+    assert_eq!(show(&builtins, "Std.Result.isOk", vec![ok(1)]), "true");
+    assert_eq!(show(&builtins, "Std.Result.isErr", vec![ok(1)]), "false");
+    assert_eq!(show(&builtins, "Std.Result.isErr", vec![err(1)]), "true");
+    assert_eq!(show(&builtins, "Err", vec![Value::Integer(2)]), "Err 2");
+}
+
+#[test]
+fn functions_are_applied_to_their_side() {
+    let builtins = Builtins::std();
+    let increment = try_call(&builtins, "Std.Int.wrappingAdd", vec![Value::Integer(1)]).unwrap();
+    assert_eq!(
+        show(&builtins, "Std.Result.map", vec![increment.clone(), ok(1)]),
+        "Ok 2"
+    );
+    assert_eq!(
+        show(&builtins, "Std.Result.map", vec![increment.clone(), err(1)]),
+        "Err 1"
+    );
+    assert_eq!(
+        show(
+            &builtins,
+            "Std.Result.mapErr",
+            vec![increment.clone(), ok(1)]
+        ),
+        "Ok 1"
+    );
+    assert_eq!(
+        show(&builtins, "Std.Result.mapErr", vec![increment, err(1)]),
+        "Err 2"
+    );
+}
+
+#[test]
+fn errors_short_circuit_and_then() {
+    let builtins = Builtins::std();
+    let ok_or = try_call(&builtins, "Std.Option.okOr", vec![Value::Integer(0)]).unwrap();
+    let and_then = |result| show(&builtins, "Std.Result.andThen", vec![ok_or.clone(), result]);
+    assert_eq!(and_then(Value::ok(Value::some(Value::Integer(1)))), "Ok 1");
+    assert_eq!(and_then(Value::ok(Value::none())), "Err 0");
+    assert_eq!(and_then(err(1)), "Err 1");
+}
+
+#[test]
+fn results_are_unwrapped_and_turned_into_options() {
+    let builtins = Builtins::std();
+    let default = Value::Integer(0);
+    assert_eq!(
+        show(
+            &builtins,
+            "Std.Result.unwrapOr",
+            vec![default.clone(), ok(1)]
+        ),
+        "1"
+    );
+    assert_eq!(
+        show(&builtins, "Std.Result.unwrapOr", vec![default, err(1)]),
+        "0"
+    );
+    assert_eq!(show(&builtins, "Std.Result.ok", vec![ok(1)]), "Some 1");
+    assert_eq!(show(&builtins, "Std.Result.ok", vec![err(1)]), "None");
+}
+
+#[test]
+fn other_values_are_rejected() {
+    let builtins = Builtins::std();
+    assert!(matches!(
+        try_call(&builtins, "Std.Result.isOk", vec![Value::none()]),
+        Err(RuntimeError::InvalidArgument { .. })
+    ));
+}
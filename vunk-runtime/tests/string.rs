@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::try_call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::value::Value;
+
+fn strings(values: &[&str]) -> Value {
+    Value::list(values.iter().copied().map(Value::string).collect())
+}
+
+fn to_string(value: &Value) -> String {
+    match value {
+        Value::Str(s) => s.to_string(),
+        other => panic!("Not a string: {:?}", other),
+    }
+}
+
+fn option(value: Value) -> Option<Value> {
+    match value {
+        Value::Variant(variant) if variant.name == "Some" => Some(variant.members[0].clone()),
+        Value::Variant(variant) if variant.name == "None" => None,
+        other => panic!("Not an option: {:?}", other),
+    }
+}
+
+fn to_strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::List(list) => list.iter().map(to_string).collect(),
+        other => panic!("Not a list: {:?}", other),
+    }
+}
+
+#[test]
+fn lengths_and_chars_count_scalar_values() {
+    let builtins = Builtins::std();
+    let s = Value::string("añ😀");
+    match try_call(&builtins, "Std.String.length", vec![s.clone()]).unwrap() {
+        Value::Integer(length) => assert_eq!(length, 3),
+        other => panic!("Not an integer: {:?}", other),
+    }
+    let chars = try_call(&builtins, "Std.String.chars", vec![s]).unwrap();
+    assert_eq!(to_strings(&chars), ["a", "ñ", "😀"]);
+}
+
+#[test]
+fn split_and_join_are_inverse() {
+    let builtins = Builtins::std();
+    let (comma, csv) = (Value::string(","), Value::string("a,,b"));
+    let parts = try_call(&builtins, "Std.String.split", vec![comma.clone(), csv]).unwrap();
+    assert_eq!(to_strings(&parts), ["a", "", "b"]);
+    let joined = try_call(&builtins, "Std.String.join", vec![comma.clone(), parts]).unwrap();
+    assert_eq!(to_string(&joined), "a,,b");
+
+    let empty = try_call(&builtins, "Std.String.join", vec![comma, strings(&[])]).unwrap();
+    assert_eq!(to_string(&empty), "");
+    let concatenated =
+        try_call(&builtins, "Std.String.concat", vec![strings(&["a", "b"])]).unwrap();
+    assert_eq!(to_string(&concatenated), "ab");
+}
+
+#[test]
+fn strings_are_transformed() {
+    let builtins = Builtins::std();
+    let transformed =
+        |name, s| to_string(&try_call(&builtins, name, vec![Value::string(s)]).unwrap());
+    assert_eq!(transformed("Std.String.trim", " \tvunk\n"), "vunk");
+    assert_eq!(transformed("Std.String.toUpper", "straße"), "STRASSE");
+    assert_eq!(transformed("Std.String.toLower", "ÄB"), "äb");
+
+    let replaced = try_call(
+        &builtins,
+        "Std.String.replace",
+        vec![Value::string("o"), Value::string("0"), Value::string("foo")],
+    );
+    assert_eq!(to_string(&replaced.unwrap()), "f00");
+
+    let contains = |needle| {
+        let args = vec![Value::string(needle), Value::string("haystack")];
+        matches!(
+            try_call(&builtins, "Std.String.contains", args),
+            Ok(Value::Bool(true))
+        )
+    };
+    assert!(contains("st"));
+    assert!(contains(""));
+    assert!(!contains("needle"));
+}
+
+#[test]
+fn numbers_are_parsed_into_options() {
+    let builtins = Builtins::std();
+    let parsed = |name, s| option(try_call(&builtins, name, vec![Value::string(s)]).unwrap());
+    assert!(matches!(
+        parsed("Std.String.parseInt", " -42 "),
+        Some(Value::Integer(-42))
+    ));
+    assert!(parsed("Std.String.parseInt", "9223372036854775808").is_none());
+    assert!(parsed("Std.String.parseInt", "4.2").is_none());
+    assert!(matches!(
+        parsed("Std.String.parseFloat", "2.5"),
+        Some(Value::Float(f)) if f == 2.5
+    ));
+    assert!(parsed("Std.String.parseFloat", "two").is_none());
+}
+
+#[test]
+fn joining_other_values_fails() {
+    let builtins = Builtins::std();
+    let list = Value::list(vec![Value::string("a"), Value::Integer(1)]);
+    assert!(matches!(
+        try_call(&builtins, "Std.String.join", vec![Value::string(","), list]),
+        Err(RuntimeError::InvalidArgument { .. })
+    ));
+}
@@ -0,0 +1,162 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::apply;
+use vunk_runtime::function::Context;
+use vunk_runtime::io::run_main;
+use vunk_runtime::task::shutdown;
+use vunk_runtime::task::Scheduling;
+use vunk_runtime::value::Value;
+
+struct SchedulingContext(Scheduling);
+
+impl Context for SchedulingContext {
+    fn call(&mut self, function: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        apply(self, function, args)
+    }
+
+    fn scheduling(&self) -> Scheduling {
+        self.0
+    }
+}
+
+// Spawn a task that sends 1 and 2 on a channel, receive both, then join the task
+fn send_and_receive(scheduling: Scheduling) -> Vec<i64> {
+    let builtins = Builtins::std();
+    let mut ctx = SchedulingContext(scheduling);
+
+    let channel = run_main(&mut ctx, &call(&builtins, "Std.Task.channel", vec![])).unwrap();
+    let sends = [1, 2]
+        .into_iter()
+        .map(|i| {
+            call(
+                &builtins,
+                "Std.Task.send",
+                vec![channel.clone(), Value::Integer(i)],
+            )
+        })
+        .collect();
+    let producer = call(&builtins, "Std.IO.sequence", vec![Value::list(sends)]);
+    let task = run_main(&mut ctx, &call(&builtins, "Std.Task.spawn", vec![producer])).unwrap();
+
+    let receive = call(&builtins, "Std.Task.receive", vec![channel]);
+    let received = (0..2)
+        .map(|_| match run_main(&mut ctx, &receive).unwrap() {
+            Value::Integer(i) => i,
+            other => panic!("Not an integer: {:?}", other),
+        })
+        .collect();
+
+    run_main(&mut ctx, &call(&builtins, "Std.Task.join", vec![task])).unwrap();
+    received
+}
+
+#[test]
+fn channels_with_threads() {
+    assert_eq!(send_and_receive(Scheduling::Threads), vec![1, 2]);
+}
+
+#[test]
+fn channels_deterministic() {
+    assert_eq!(send_and_receive(Scheduling::Deterministic), vec![1, 2]);
+}
+
+#[test]
+fn receive_without_sender_is_a_deadlock() {
+    let builtins = Builtins::std();
+    let mut ctx = SchedulingContext(Scheduling::Deterministic);
+    let channel = run_main(&mut ctx, &call(&builtins, "Std.Task.channel", vec![])).unwrap();
+    let receive = call(&builtins, "Std.Task.receive", vec![channel]);
+
+    assert!(matches!(
+        run_main(&mut ctx, &receive),
+        Err(RuntimeError::Deadlock)
+    ));
+}
+
+#[test]
+fn cancelled_task_does_not_run() {
+    let builtins = Builtins::std();
+    let mut ctx = SchedulingContext(Scheduling::Deterministic);
+
+    let channel = run_main(&mut ctx, &call(&builtins, "Std.Task.channel", vec![])).unwrap();
+    let send = call(
+        &builtins,
+        "Std.Task.send",
+        vec![channel.clone(), Value::Unit],
+    );
+    let task = run_main(&mut ctx, &call(&builtins, "Std.Task.spawn", vec![send])).unwrap();
+    run_main(
+        &mut ctx,
+        &call(&builtins, "Std.Task.cancel", vec![task.clone()]),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        run_main(&mut ctx, &call(&builtins, "Std.Task.join", vec![task])),
+        Err(RuntimeError::Cancelled)
+    ));
+    assert!(matches!(
+        run_main(
+            &mut ctx,
+            &call(&builtins, "Std.Task.receive", vec![channel])
+        ),
+        Err(RuntimeError::Deadlock)
+    ));
+}
+
+// Every task waits for the one spawned before it, so they cannot each hold a thread of the pool
+#[test]
+fn tasks_waiting_for_each_other_outnumber_the_threads() {
+    let builtins = Builtins::std();
+    let mut ctx = SchedulingContext(Scheduling::Threads);
+    let channel = call(&builtins, "Std.Task.channel", vec![]);
+
+    let first = run_main(&mut ctx, &channel).unwrap();
+    let mut last = first.clone();
+    for _ in 0..64 {
+        let next = run_main(&mut ctx, &channel).unwrap();
+        let relay = call(
+            &builtins,
+            "Std.IO.andThen",
+            vec![
+                call(&builtins, "Std.Task.send", vec![next.clone()]),
+                call(&builtins, "Std.Task.receive", vec![last]),
+            ],
+        );
+        run_main(&mut ctx, &call(&builtins, "Std.Task.spawn", vec![relay])).unwrap();
+        last = next;
+    }
+
+    run_main(
+        &mut ctx,
+        &call(&builtins, "Std.Task.send", vec![first, Value::Integer(1)]),
+    )
+    .unwrap();
+    assert!(matches!(
+        run_main(&mut ctx, &call(&builtins, "Std.Task.receive", vec![last])),
+        Ok(Value::Integer(1))
+    ));
+}
+
+#[test]
+fn tasks_that_never_ran_are_dropped_on_shutdown() {
+    let builtins = Builtins::std();
+    let mut ctx = SchedulingContext(Scheduling::Deterministic);
+    let channel = run_main(&mut ctx, &call(&builtins, "Std.Task.channel", vec![])).unwrap();
+    let send = call(&builtins, "Std.Task.send", vec![channel, Value::Unit]);
+    let task = run_main(&mut ctx, &call(&builtins, "Std.Task.spawn", vec![send])).unwrap();
+
+    shutdown();
+
+    assert!(matches!(
+        run_main(&mut ctx, &call(&builtins, "Std.Task.join", vec![task])),
+        Err(RuntimeError::Cancelled)
+    ));
+}
@@ -0,0 +1,111 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::value::Value;
+
+fn int(value: Result<Value, RuntimeError>) -> i64 {
+    match value {
+        Ok(Value::Integer(i)) => i,
+        other => panic!("Not an integer: {:?}", other),
+    }
+}
+
+// A thunk of `value` that counts how often it is evaluated
+fn counted(evaluations: &Arc<AtomicUsize>, value: i64) -> Value {
+    let evaluations = evaluations.clone();
+    Value::lazy(Box::new(move || {
+        evaluations.fetch_add(1, Ordering::SeqCst);
+        Ok(Value::Integer(value))
+    }))
+}
+
+#[test]
+fn thunks_are_evaluated_at_most_once() {
+    let evaluations = Arc::new(AtomicUsize::new(0));
+    let thunk = counted(&evaluations, 42);
+    assert_eq!(evaluations.load(Ordering::SeqCst), 0);
+
+    assert_eq!(int(thunk.force()), 42);
+    assert_eq!(int(thunk.clone().force()), 42);
+    assert_eq!(evaluations.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn thunks_that_give_thunks_are_forced_through() {
+    let evaluations = Arc::new(AtomicUsize::new(0));
+    let inner = counted(&evaluations, 7);
+    let outer = Value::lazy(Box::new(move || Ok(inner)));
+    assert_eq!(int(outer.force()), 7);
+    assert_eq!(evaluations.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn failures_are_kept() {
+    let evaluations = Arc::new(AtomicUsize::new(0));
+    let counter = evaluations.clone();
+    let thunk = Value::lazy(Box::new(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Err(RuntimeError::DivisionByZero)
+    }));
+
+    assert!(matches!(thunk.force(), Err(RuntimeError::DivisionByZero)));
+    assert!(matches!(thunk.force(), Err(RuntimeError::DivisionByZero)));
+    assert_eq!(evaluations.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn thunks_that_depend_on_themselves_are_black_holes() {
+    // The thunk can only refer to itself through a slot that is filled after it was created
+    let slot: Arc<Mutex<Option<Value>>> = Arc::new(Mutex::new(None));
+    let itself = slot.clone();
+    let thunk = Value::lazy(Box::new(move || {
+        let itself = itself.lock().unwrap().take().unwrap();
+        itself.force()
+    }));
+    *slot.lock().unwrap() = Some(thunk.clone());
+
+    assert!(matches!(thunk.force(), Err(RuntimeError::BlackHole)));
+    assert!(matches!(thunk.force(), Err(RuntimeError::BlackHole)));
+}
+
+#[test]
+fn other_threads_wait_for_the_evaluation() {
+    let evaluations = Arc::new(AtomicUsize::new(0));
+    let counter = evaluations.clone();
+    let thunk = Value::lazy(Box::new(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(50));
+        Ok(Value::Integer(1))
+    }));
+
+    let threads = (0..4)
+        .map(|_| {
+            let thunk = thunk.clone();
+            std::thread::spawn(move || int(thunk.force()))
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), 1);
+    }
+    assert_eq!(evaluations.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn thunks_that_panic_fail() {
+    let thunk = Value::lazy(Box::new(|| panic!("evaluated")));
+    let forced = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| thunk.force()));
+    assert!(forced.is_err());
+
+    // Neither a black hole on this thread, nor waiting forever on another one
+    assert!(matches!(thunk.force(), Err(RuntimeError::Panic(_))));
+    let other = std::thread::spawn(move || thunk.force());
+    assert!(matches!(other.join().unwrap(), Err(RuntimeError::Panic(_))));
+}
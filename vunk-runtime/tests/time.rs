@@ -0,0 +1,160 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::try_call;
+use vunk_runtime::builtin::io_arg;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::function::BuiltinContext;
+use vunk_runtime::value::Value;
+
+fn run(builtins: &Builtins, name: &str) -> Value {
+    let action = try_call(builtins, name, Vec::new()).unwrap();
+    io_arg(name, &action)
+        .unwrap()
+        .run(&mut BuiltinContext)
+        .unwrap()
+}
+
+fn int(builtins: &Builtins, name: &str, value: Value) -> i64 {
+    match try_call(builtins, name, vec![value]).unwrap() {
+        Value::Integer(i) => i,
+        other => panic!("Not an Int: {}", other),
+    }
+}
+
+fn duration(builtins: &Builtins, unit: &str, n: i64) -> Value {
+    let name = format!("Std.Time.{}", unit);
+    try_call(builtins, &name, vec![Value::Integer(n)]).unwrap()
+}
+
+fn parse(builtins: &Builtins, s: &str) -> Value {
+    try_call(builtins, "Std.Time.Timestamp.parse", vec![Value::string(s)]).unwrap()
+}
+
+#[test]
+fn durations_convert_between_units() {
+    let builtins = Builtins::std();
+    let d = duration(&builtins, "milliseconds", 1500);
+    assert_eq!(d.type_name(), "Duration");
+    assert_eq!(int(&builtins, "Std.Time.Duration.toSeconds", d.clone()), 1);
+    assert_eq!(
+        int(&builtins, "Std.Time.Duration.toMicroseconds", d),
+        1_500_000
+    );
+
+    let sum = try_call(
+        &builtins,
+        "Std.Time.Duration.add",
+        vec![
+            duration(&builtins, "seconds", 2),
+            duration(&builtins, "minutes", 1),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        int(&builtins, "Std.Time.Duration.toMilliseconds", sum),
+        62_000
+    );
+
+    let scaled = try_call(
+        &builtins,
+        "Std.Time.Duration.scale",
+        vec![Value::Integer(-3), duration(&builtins, "hours", 1)],
+    )
+    .unwrap();
+    assert_eq!(int(&builtins, "Std.Time.Duration.toMinutes", scaled), -180);
+
+    let overflow = try_call(&builtins, "Std.Time.hours", vec![Value::Integer(i64::MAX)]);
+    assert!(matches!(
+        overflow,
+        Err(RuntimeError::IntegerOverflow { .. })
+    ));
+}
+
+#[test]
+fn times_of_different_types_are_not_mixed() {
+    let builtins = Builtins::std();
+    let instant = run(&builtins, "Std.Time.Instant.now");
+    let result = try_call(
+        &builtins,
+        "Std.Time.Duration.add",
+        vec![duration(&builtins, "seconds", 1), instant],
+    );
+    assert!(matches!(result, Err(RuntimeError::InvalidArgument { .. })));
+
+    let result = try_call(
+        &builtins,
+        "Std.Time.Duration.toSeconds",
+        vec![Value::Integer(1)],
+    );
+    assert!(matches!(result, Err(RuntimeError::InvalidArgument { .. })));
+}
+
+#[test]
+fn the_monotonic_clock_does_not_go_backwards() {
+    let builtins = Builtins::std();
+    let start = run(&builtins, "Std.Time.Instant.now");
+    let end = run(&builtins, "Std.Time.Instant.now");
+    let elapsed = try_call(&builtins, "Std.Time.Instant.between", vec![start, end]).unwrap();
+    assert!(int(&builtins, "Std.Time.Duration.toNanoseconds", elapsed) >= 0);
+
+    let now = run(&builtins, "Std.Time.now");
+    assert_eq!(now.type_name(), "Timestamp");
+}
+
+#[test]
+fn timestamps_are_formatted_and_parsed() {
+    let builtins = Builtins::std();
+    let format = |timestamp: Value| {
+        try_call(&builtins, "Std.Time.Timestamp.format", vec![timestamp])
+            .unwrap()
+            .to_string()
+    };
+    let since_epoch = duration(&builtins, "milliseconds", 1_714_566_600_250);
+    let timestamp = try_call(&builtins, "Std.Time.Timestamp.fromUnix", vec![since_epoch]).unwrap();
+    assert_eq!(format(timestamp.clone()), r#""2024-05-01T12:30:00.25Z""#);
+    assert_eq!(
+        parse(&builtins, "2024-05-01T14:30:00.250+02:00").to_string(),
+        format!("Some {}", timestamp)
+    );
+
+    let before_epoch = duration(&builtins, "seconds", -1);
+    let timestamp = try_call(&builtins, "Std.Time.Timestamp.fromUnix", vec![before_epoch]).unwrap();
+    assert_eq!(format(timestamp), r#""1969-12-31T23:59:59Z""#);
+
+    let leap_day = parse(&builtins, "2000-02-29 00:00:00z");
+    assert_eq!(
+        leap_day.to_string(),
+        "Some Timestamp { nanos: 951782400000000000 }"
+    );
+    for invalid in [
+        "2023-02-29T00:00:00Z",
+        "2024-05-01T24:00:00Z",
+        "2024-05-01T12:30:00",
+        "2024-05-01T12:30:00.Z",
+        "2024-5-01T12:30:00Z",
+    ] {
+        assert_eq!(parse(&builtins, invalid).to_string(), "None", "{}", invalid);
+    }
+}
+
+#[test]
+fn timestamps_are_split_into_dates() {
+    let builtins = Builtins::std();
+    let timestamp = parse(&builtins, "1999-12-31T23:59:58.5Z");
+    let timestamp = match timestamp {
+        Value::Variant(v) => v.members[0].clone(),
+        other => panic!("Not parsed: {}", other),
+    };
+    assert_eq!(
+        try_call(&builtins, "Std.Time.Timestamp.toDate", vec![timestamp])
+            .unwrap()
+            .to_string(),
+        "{ day = 31, hour = 23, minute = 59, month = 12, nanosecond = 500000000, second = 58, \
+         year = 1999 }"
+    );
+}
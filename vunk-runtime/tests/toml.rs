@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::try_call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::error::RuntimeError;
+use vunk_runtime::value::Value;
+
+fn string(value: &Value) -> String {
+    match value {
+        Value::Str(s) => s.as_str().to_string(),
+        other => panic!("Not a string: {:?}", other),
+    }
+}
+
+// Parse a document and return the Ok or Err value
+fn parse(builtins: &Builtins, name: &str, input: &str) -> Result<Value, String> {
+    match try_call(builtins, name, vec![Value::string(input)]).unwrap() {
+        Value::Variant(result) if result.name == "Ok" => Ok(result.members[0].clone()),
+        Value::Variant(result) if result.name == "Err" => Err(string(&result.members[0])),
+        other => panic!("Not a result: {:?}", other),
+    }
+}
+
+fn encode(builtins: &Builtins, name: &str, json: Value) -> String {
+    string(&try_call(builtins, name, vec![json]).unwrap())
+}
+
+const TOML: &str = r#"title = "vunk"
+released = 2023-01-02T03:04:05Z
+
+[server]
+ports = [8080, 8081]
+ratio = 0.5
+debug = false
+"#;
+
+#[test]
+fn toml_is_parsed_into_json() {
+    let builtins = Builtins::std();
+    let toml = parse(&builtins, "Std.Toml.parse", TOML).unwrap();
+    assert_eq!(
+        encode(&builtins, "Std.Json.encode", toml.clone()),
+        r#"{"released":"2023-01-02T03:04:05Z","server":{"debug":false,"ports":[8080,8081],"ratio":0.5},"title":"vunk"}"#
+    );
+
+    let encoded = encode(&builtins, "Std.Toml.encode", toml);
+    let again = parse(&builtins, "Std.Toml.parse", &encoded).unwrap();
+    assert_eq!(
+        encode(&builtins, "Std.Json.encode", again),
+        r#"{"released":"2023-01-02T03:04:05Z","server":{"debug":false,"ports":[8080,8081],"ratio":0.5},"title":"vunk"}"#
+    );
+    assert!(encoded.contains("[server]\n"), "{}", encoded);
+
+    assert!(parse(&builtins, "Std.Toml.parse", "a = ").is_err());
+    assert!(parse(&builtins, "Std.Toml.parse", "a = 1\na = 2").is_err());
+}
+
+#[test]
+fn toml_cannot_encode_everything() {
+    let builtins = Builtins::std();
+    for input in ["[1]", r#"{"a":null}"#, r#"{"a":[1,null]}"#] {
+        let json = parse(&builtins, "Std.Json.parse", input).unwrap();
+        let result = try_call(&builtins, "Std.Toml.encode", vec![json]);
+        assert!(
+            matches!(result, Err(RuntimeError::InvalidArgument { .. })),
+            "{} was encoded",
+            input
+        );
+    }
+}
@@ -0,0 +1,67 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+mod common;
+
+use common::call;
+use vunk_runtime::builtin::Builtins;
+use vunk_runtime::value::Value;
+
+fn string(value: &Value) -> String {
+    match value {
+        Value::Str(s) => s.as_str().to_string(),
+        other => panic!("Not a string: {:?}", other),
+    }
+}
+
+// Parse a document and return the Ok or Err value
+fn parse(builtins: &Builtins, name: &str, input: &str) -> Result<Value, String> {
+    match call(builtins, name, vec![Value::string(input)]) {
+        Value::Variant(result) if result.name == "Ok" => Ok(result.members[0].clone()),
+        Value::Variant(result) if result.name == "Err" => Err(string(&result.members[0])),
+        other => panic!("Not a result: {:?}", other),
+    }
+}
+
+fn encode(builtins: &Builtins, name: &str, json: Value) -> String {
+    string(&call(builtins, name, vec![json]))
+}
+
+#[test]
+fn yaml_is_parsed_into_json() {
+    let builtins = Builtins::std();
+    let input = "defaults: &defaults\n  retries: 3\n  timeout: 1.5\nservice:\n  <<: *defaults\n  name: !tagged api\n  hosts: [a, b]\n  404: missing\n  extra: ~\n";
+    let yaml = parse(&builtins, "Std.Yaml.parse", input).unwrap();
+    assert_eq!(
+        encode(&builtins, "Std.Json.encode", yaml.clone()),
+        r#"{"defaults":{"retries":3,"timeout":1.5},"service":{"404":"missing","extra":null,"hosts":["a","b"],"name":"api","retries":3,"timeout":1.5}}"#
+    );
+
+    let encoded = encode(&builtins, "Std.Yaml.encode", yaml.clone());
+    let again = parse(&builtins, "Std.Yaml.parse", &encoded).unwrap();
+    assert_eq!(
+        encode(&builtins, "Std.Json.encode", again),
+        encode(&builtins, "Std.Json.encode", yaml)
+    );
+
+    for input in ["a: [1", "[1]: x", "a: 1\na: 2"] {
+        assert!(
+            parse(&builtins, "Std.Yaml.parse", input).is_err(),
+            "{:?} was accepted",
+            input
+        );
+    }
+}
+
+#[test]
+fn yaml_strings_that_look_like_other_values_stay_strings() {
+    let builtins = Builtins::std();
+    let json = parse(&builtins, "Std.Json.parse", r#"["true","1","null",""]"#).unwrap();
+    let encoded = encode(&builtins, "Std.Yaml.encode", json);
+    let yaml = parse(&builtins, "Std.Yaml.parse", &encoded).unwrap();
+    assert_eq!(
+        encode(&builtins, "Std.Json.encode", yaml),
+        r#"["true","1","null",""]"#
+    );
+}